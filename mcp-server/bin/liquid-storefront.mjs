@@ -0,0 +1,148 @@
+#!/usr/bin/env node
+// Command-line companion to the WebAssembly module: runs queries, looks up
+// products and serves MCP over stdio with the same client code the
+// storefront uses. Build the Node package first:
+//
+//   cd storefront-api-wasm
+//   wasm-pack build --target nodejs --out-dir ../mcp-server/wasm --release
+//
+// Usage:
+//
+//   liquid-storefront query <document | @file> [--variables <json>]
+//   liquid-storefront product get <handle>
+//   liquid-storefront mcp serve
+//
+// Every command takes `--config path` and `--store name`, and reads the
+// same config file and STOREFRONT_* environment variables as
+// storefront-mcp-wasm.mjs. Results are printed to stdout as JSON; errors go
+// to stderr with a non-zero exit code.
+
+import { readFileSync } from 'node:fs';
+
+const USAGE = `Usage:
+  liquid-storefront query <document | @file> [--variables <json>]
+  liquid-storefront product get <handle>
+  liquid-storefront mcp serve
+
+Options:
+  --config <path>     JSON config file (default: $STOREFRONT_MCP_CONFIG)
+  --store <name>      Run against a store from the config's "stores"
+  --variables <json>  Variables for query, as JSON or @file
+  --help              Show this help`;
+
+function fail(message) {
+  console.error(message);
+  process.exit(1);
+}
+
+// Splits the arguments into positionals and `--name value` options.
+function parseArgs(argv) {
+  const positionals = [];
+  const options = {};
+  for (let i = 0; i < argv.length; i++) {
+    const arg = argv[i];
+    if (arg === '--help' || arg === '-h') {
+      options.help = true;
+    } else if (arg.startsWith('--')) {
+      const name = arg.slice(2);
+      if (i + 1 >= argv.length) fail(`--${name} needs a value\n\n${USAGE}`);
+      options[name] = argv[++i];
+    } else {
+      positionals.push(arg);
+    }
+  }
+  return { positionals, options };
+}
+
+// `@path` reads the file at path; anything else is taken as it is.
+function readArg(value, what) {
+  if (!value.startsWith('@')) return value;
+  try {
+    return readFileSync(value.slice(1), 'utf8');
+  } catch (error) {
+    fail(`Can't read the ${what} file ${value.slice(1)}: ${error.message}`);
+  }
+}
+
+function print(value) {
+  process.stdout.write(`${JSON.stringify(value, null, 2)}\n`);
+}
+
+// Thrown errors are Error objects with a code and category, or strings.
+function describe(error) {
+  if (error && typeof error === 'object' && error.message) {
+    return error.code ? `${error.code}: ${error.message}` : error.message;
+  }
+  return String(error);
+}
+
+const { positionals, options } = parseArgs(process.argv.slice(2));
+const [command, subcommand, ...rest] = positionals;
+if (options.help || !command) {
+  console.error(USAGE);
+  process.exit(options.help ? 0 : 1);
+}
+
+const configPath = options.config ?? process.env.STOREFRONT_MCP_CONFIG;
+let configFile;
+if (configPath) {
+  try {
+    configFile = readFileSync(configPath, 'utf8');
+  } catch (error) {
+    fail(`Can't read the config file ${configPath}: ${error.message}`);
+  }
+}
+
+if (command === 'mcp' && subcommand === 'serve') {
+  // stdout carries the protocol; send the module's console logging to stderr.
+  console.log = (...args) => console.error(...args);
+}
+
+const { McpServer, serve_stdio } = await import('../wasm/storefront_api_wasm.js');
+
+let server;
+try {
+  server = McpServer.from_config(configFile, process.env);
+} catch (error) {
+  fail(String(error));
+}
+
+async function run() {
+  switch (command) {
+    case 'query': {
+      if (!subcommand) fail(`query needs a GraphQL document\n\n${USAGE}`);
+      const document = readArg(subcommand, 'query');
+      let variables;
+      if (options.variables) {
+        try {
+          variables = JSON.parse(readArg(options.variables, 'variables'));
+        } catch (error) {
+          fail(`--variables must be JSON: ${error.message}`);
+        }
+      }
+      print(await server.client(options.store).query(document, variables));
+      return;
+    }
+    case 'product': {
+      const [handle] = rest;
+      if (subcommand !== 'get' || !handle) fail(`Usage: liquid-storefront product get <handle>`);
+      const { product } = await server.client(options.store).get_product(handle);
+      if (!product) fail(`No product with the handle "${handle}"`);
+      print(product);
+      return;
+    }
+    case 'mcp': {
+      if (subcommand !== 'serve') fail(`Usage: liquid-storefront mcp serve`);
+      serve_stdio(server);
+      return;
+    }
+    default:
+      fail(`Unknown command "${command}"\n\n${USAGE}`);
+  }
+}
+
+try {
+  await run();
+} catch (error) {
+  fail(describe(error));
+}
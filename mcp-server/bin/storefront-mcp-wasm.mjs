@@ -0,0 +1,52 @@
+#!/usr/bin/env node
+// Runs the WebAssembly MCP server (storefront-api-wasm's `mcp` module) over
+// stdio. Build the Node package first:
+//
+//   cd storefront-api-wasm
+//   wasm-pack build --target nodejs --out-dir ../mcp-server/wasm --release
+//
+// Configure it with a JSON file (`--config path` or STOREFRONT_MCP_CONFIG)
+// and/or STOREFRONT_* environment variables, which override the file. See
+// the README for the settings. Set STOREFRONT_AUDIT_FILE to also append
+// every audited tool call to that file as JSON lines.
+
+import { appendFileSync, readFileSync } from 'node:fs';
+
+// stdout carries the protocol; send the module's console logging to stderr.
+console.log = (...args) => console.error(...args);
+
+const { McpServer, serve_stdio } = await import('../wasm/storefront_api_wasm.js');
+
+const flag = process.argv.indexOf('--config');
+const configPath = flag === -1 ? process.env.STOREFRONT_MCP_CONFIG : process.argv[flag + 1];
+
+let configFile;
+if (configPath) {
+  try {
+    configFile = readFileSync(configPath, 'utf8');
+  } catch (error) {
+    console.error(`Can't read the config file ${configPath}: ${error.message}`);
+    process.exit(1);
+  }
+}
+
+let server;
+try {
+  server = McpServer.from_config(configFile, process.env);
+} catch (error) {
+  console.error(String(error));
+  process.exit(1);
+}
+
+const auditFile = process.env.STOREFRONT_AUDIT_FILE;
+if (auditFile) {
+  server.set_audit_sink((entry) => {
+    try {
+      appendFileSync(auditFile, `${JSON.stringify(entry)}\n`);
+    } catch (error) {
+      console.error(`Can't write the audit file ${auditFile}: ${error.message}`);
+    }
+  });
+}
+
+serve_stdio(server);
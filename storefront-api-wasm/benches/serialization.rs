@@ -0,0 +1,100 @@
+//! Moving a large collection response between JavaScript and Rust.
+//!
+//! Compares the two ways across the boundary for each direction: building
+//! or walking a JS object one value at a time with `serde_wasm_bindgen`,
+//! against a single JSON string parsed on the other side. The client uses
+//! the string for both (`send` and `to_js`); `get_collection` measures the
+//! whole path against a mocked `fetch`. Run in Node with
+//!
+//! ```sh
+//! cargo bench --target wasm32-unknown-unknown
+//! ```
+//!
+//! which uses the `wasm-bindgen-test-runner` set in `.cargo/config.toml`.
+#![cfg(target_arch = "wasm32")]
+
+use std::rc::Rc;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use storefront_api_wasm::testing::{connection, data, product, MockFetch};
+use storefront_api_wasm::types::{Connection, Product};
+use storefront_api_wasm::StorefrontApi;
+use wasm_bindgen_test::{wasm_bindgen_bench, Criterion};
+
+/// Products in the collection, the most one page holds.
+const PRODUCTS: usize = 250;
+
+fn collection_response() -> Value {
+    let products = (0..PRODUCTS)
+        .map(|index| product(&format!("product-{index}"), "10.0"))
+        .collect();
+    data(json!({
+        "collection": {
+            "id": "gid://shopify/Collection/1",
+            "handle": "all",
+            "title": "All",
+            "products": connection(products, false),
+        },
+    }))
+}
+
+fn products() -> Connection<Product> {
+    serde_json::from_value(collection_response()["data"]["collection"]["products"].take())
+        .expect("products in the client's shape")
+}
+
+#[wasm_bindgen_bench]
+fn response_into_rust(c: &mut Criterion) {
+    let text = collection_response().to_string();
+    c.bench_function("response: JSON.parse, then serde_wasm_bindgen", |b| {
+        b.iter(|| {
+            let object = js_sys::JSON::parse(&text).unwrap();
+            serde_wasm_bindgen::from_value::<Value>(object).unwrap()
+        })
+    });
+    c.bench_function("response: serde_json::from_str", |b| {
+        b.iter(|| serde_json::from_str::<Value>(&text).unwrap())
+    });
+}
+
+#[wasm_bindgen_bench]
+fn result_into_js(c: &mut Criterion) {
+    let products = products();
+    c.bench_function("result: serde_wasm_bindgen", |b| {
+        b.iter(|| {
+            products
+                .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+                .unwrap()
+        })
+    });
+    c.bench_function("result: serde_json::to_string, then JSON.parse", |b| {
+        b.iter(|| js_sys::JSON::parse(&serde_json::to_string(&products).unwrap()).unwrap())
+    });
+}
+
+#[wasm_bindgen_bench]
+async fn get_collection(c: &mut Criterion) {
+    let mut fetch = MockFetch::new();
+    fetch.install().unwrap();
+    let api = StorefrontApi::new(
+        "bench-shop.myshopify.com".to_string(),
+        "bench-token".to_string(),
+        "2024-01".to_string(),
+    );
+    let fetch = Rc::new(fetch);
+    let response = collection_response();
+    c.bench_async_function("get_collection: 250 products", move |b| {
+        let (api, fetch, response) = (api.clone(), Rc::clone(&fetch), response.clone());
+        Box::pin(b.iter_future(move || {
+            fetch.reset();
+            fetch.respond("getCollection", response.clone());
+            let api = api.clone();
+            async move {
+                api.get_collection("all".to_string(), Some(PRODUCTS as u32), None)
+                    .await
+            }
+        }))
+    })
+    .await;
+}
@@ -10,8 +10,9 @@ if %ERRORLEVEL% NEQ 0 (
     cargo install wasm-pack
 )
 
-REM Build the WebAssembly module
-wasm-pack build --target web --out-dir ..\Liquid-main\assets\wasm --release
+REM Build the WebAssembly module. Extra arguments go to cargo, e.g.
+REM build.bat --no-default-features --features cart for a smaller binary
+wasm-pack build --target web --out-dir ..\Liquid-main\assets\wasm --release -- %*
 
 echo Build complete! WebAssembly module is in Liquid-main\assets\wasm\
 
@@ -12,8 +12,9 @@ if ! command -v wasm-pack &> /dev/null; then
     cargo install wasm-pack
 fi
 
-# Build the WebAssembly module
-wasm-pack build --target web --out-dir ../Liquid-main/assets/wasm --release
+# Build the WebAssembly module. Extra arguments go to cargo, e.g.
+# ./build.sh --no-default-features --features cart for a smaller binary
+wasm-pack build --target web --out-dir ../Liquid-main/assets/wasm --release -- "$@"
 
 echo "Build complete! WebAssembly module is in Liquid-main/assets/wasm/"
 
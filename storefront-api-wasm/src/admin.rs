@@ -0,0 +1,233 @@
+//! A client for the Admin GraphQL API, for back-office work such as
+//! inventory adjustments and metafield writes from the same server-side
+//! code (Node, Workers) as the storefront.
+//!
+//! `AdminApi` is a `StorefrontApi` pointed at
+//! `https://{shop}/admin/api/{version}/graphql.json` and authenticated
+//! with `X-Shopify-Access-Token`, so it shares the transport, the retry
+//! policy, structured errors, logging, `on_error` and the cassettes. It
+//! never caches, prefetches, adds `@inContext` or sends visitor tracking
+//! headers, and always posts.
+//!
+//! Behind the `admin` feature, which is off by default: an Admin API token
+//! can change the shop, so it belongs in server code and environment
+//! variables, never in a bundle a browser downloads.
+//!
+//! ```js
+//! const admin = new AdminApi(shop, env.SHOPIFY_ADMIN_TOKEN, "2024-07").with_fetch(fetch);
+//! await admin.adjust_inventory([{ inventoryItemId, locationId, delta: -1 }], "correction");
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ErrorDetail, StorefrontError};
+use crate::{from_js, to_js, StorefrontApi};
+
+/// A metafield to write with `set_metafields`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetafieldsSetInput {
+    /// The GID of the resource the metafield belongs to.
+    pub owner_id: String,
+    pub namespace: String,
+    pub key: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+}
+
+/// A change of an item's `available` quantity at a location.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryChange {
+    pub inventory_item_id: String,
+    pub location_id: String,
+    pub delta: i64,
+}
+
+const METAFIELDS_SET: &str = r#"
+    mutation metafieldsSet($metafields: [MetafieldsSetInput!]!) {
+        metafieldsSet(metafields: $metafields) {
+            metafields {
+                id
+                namespace
+                key
+                type
+                value
+            }
+            userErrors {
+                field
+                message
+                code
+            }
+        }
+    }
+"#;
+
+const INVENTORY_ADJUST_QUANTITIES: &str = r#"
+    mutation inventoryAdjustQuantities($input: InventoryAdjustQuantitiesInput!) {
+        inventoryAdjustQuantities(input: $input) {
+            inventoryAdjustmentGroup {
+                createdAt
+                reason
+                changes {
+                    name
+                    delta
+                    quantityAfterChange
+                    item {
+                        id
+                    }
+                    location {
+                        id
+                    }
+                }
+            }
+            userErrors {
+                field
+                message
+                code
+            }
+        }
+    }
+"#;
+
+/// A client for the Admin GraphQL API; see `admin`.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct AdminApi {
+    client: StorefrontApi,
+}
+
+impl AdminApi {
+    /// Runs a mutation and returns its `field` payload, failing with a
+    /// `userError` `StorefrontError` if it reports any.
+    async fn mutation(&self, query: &str, variables: Value, field: &str) -> Result<Value, JsValue> {
+        let mut data = self
+            .client
+            .send_operation(query.to_string(), variables)
+            .await?;
+        let mut payload = data[field].take();
+        let user_errors: Vec<ErrorDetail> =
+            serde_json::from_value(payload["userErrors"].take()).unwrap_or_default();
+        if !user_errors.is_empty() {
+            return Err(StorefrontError::user_errors(user_errors).into());
+        }
+        Ok(payload)
+    }
+}
+
+#[wasm_bindgen]
+impl AdminApi {
+    /// A client for `shop_domain`'s Admin API, authenticated with an Admin
+    /// API access token (`shpat_...`).
+    #[wasm_bindgen(constructor)]
+    pub fn new(shop_domain: String, access_token: String, api_version: String) -> AdminApi {
+        let mut client = StorefrontApi::new(shop_domain, access_token, api_version);
+        client.admin = true;
+        AdminApi { client }
+    }
+
+    /// Sends a query or mutation and resolves to its `data`.
+    #[wasm_bindgen(unchecked_return_type = "Record<string, unknown>")]
+    pub async fn query(
+        &self,
+        query: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "Variables")] variables: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let variables: Value = match variables {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => Value::Null,
+        };
+        to_js(&self.client.send_operation(query, variables).await?)
+    }
+
+    /// Writes up to 25 metafields, `[{ ownerId, namespace, key, type,
+    /// value }]`, creating or updating each, and returns them as saved.
+    #[wasm_bindgen(unchecked_return_type = "Metafield[]")]
+    pub async fn set_metafields(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "MetafieldsSetInput[]")] metafields: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let metafields: Vec<MetafieldsSetInput> = from_js(metafields)?;
+        let mut payload = self
+            .mutation(
+                METAFIELDS_SET,
+                json!({ "metafields": metafields }),
+                "metafieldsSet",
+            )
+            .await?;
+        to_js(&payload["metafields"].take())
+    }
+
+    /// Adjusts the `available` quantity of inventory items at locations by
+    /// `delta` each, recording `reason` (`correction` by default; also
+    /// `received`, `damaged`, `restock`, ...). Returns the adjustment.
+    #[wasm_bindgen(unchecked_return_type = "InventoryAdjustmentGroup | null")]
+    pub async fn adjust_inventory(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "InventoryChange[]")] changes: JsValue,
+        reason: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        let changes: Vec<InventoryChange> = from_js(changes)?;
+        let input = json!({
+            "name": "available",
+            "reason": reason.unwrap_or_else(|| "correction".to_string()),
+            "changes": changes,
+        });
+        let mut payload = self
+            .mutation(
+                INVENTORY_ADJUST_QUANTITIES,
+                json!({ "input": input }),
+                "inventoryAdjustQuantities",
+            )
+            .await?;
+        to_js(&payload["inventoryAdjustmentGroup"].take())
+    }
+
+    /// A copy that sends its requests with `fetch`; see
+    /// `StorefrontApi.with_fetch`.
+    #[wasm_bindgen]
+    pub fn with_fetch(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Fetch")] fetch: js_sys::Function,
+    ) -> AdminApi {
+        AdminApi {
+            client: self.client.with_fetch(fetch),
+        }
+    }
+
+    /// A copy whose requests are aborted when `signal` fires.
+    #[wasm_bindgen]
+    pub fn with_signal(&self, signal: web_sys::AbortSignal) -> AdminApi {
+        AdminApi {
+            client: self.client.with_signal(signal),
+        }
+    }
+
+    /// Sets how retryable failures are retried; see
+    /// `StorefrontApi.set_retry_policy`.
+    #[wasm_bindgen]
+    pub fn set_retry_policy(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "Partial<RetryPolicy>")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        self.client.set_retry_policy(options)
+    }
+
+    /// Reports failed requests to `callback`; see `StorefrontApi.on_error`.
+    #[wasm_bindgen]
+    pub fn on_error(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "(report: ErrorReport) => void")]
+        callback: Option<js_sys::Function>,
+        #[wasm_bindgen(unchecked_optional_param_type = "ErrorReportOptions")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        self.client.on_error(callback, options)
+    }
+}
@@ -0,0 +1,333 @@
+//! Storefront analytics events, so a headless store reports the page views,
+//! product views, searches and cart updates the Online Store reports.
+//!
+//! `set_analytics({ shopId, currency, collector })` says where events go:
+//! with `shopId` (the shop's GID) to Shopify's analytics endpoint on the
+//! shop's domain, as the Monorail events Hydrogen sends; with `collector`
+//! to that URL as `{ events: [event] }`, for a store's own pipeline. Then
+//!
+//! ```js
+//! api.track("product_viewed", { productId, variantId, price, quantity: 1 });
+//! ```
+//!
+//! resolves to whether the event was sent. Events are only sent while
+//! analytics processing is allowed (see `privacy`); without consent they
+//! are dropped, not queued. Each event carries the visitor tokens from
+//! `set_tracking_tokens` and, in a browser, the page URL, referrer and
+//! title. Sending never throws: a collector that is down shouldn't break
+//! the page, so failures are only logged.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, Request, RequestInit};
+
+use crate::logging::LogLevel;
+use crate::{from_js, to_js, StorefrontApi};
+
+/// The standard events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsEventName {
+    PageViewed,
+    ProductViewed,
+    SearchSubmitted,
+    CartUpdated,
+}
+
+impl AnalyticsEventName {
+    fn parse(name: &str) -> Result<AnalyticsEventName, JsValue> {
+        serde_json::from_value(Value::String(name.to_string())).map_err(|_| {
+            JsValue::from_str(&format!(
+                "Unknown analytics event {name:?}; expected page_viewed, product_viewed, \
+                 search_submitted or cart_updated"
+            ))
+        })
+    }
+
+    /// The `event_name` Shopify knows this event by.
+    fn shopify_name(self) -> &'static str {
+        match self {
+            AnalyticsEventName::PageViewed => "page_rendered",
+            AnalyticsEventName::ProductViewed => "product_page_rendered",
+            AnalyticsEventName::SearchSubmitted => "search_submitted",
+            AnalyticsEventName::CartUpdated => "product_added_to_cart",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnalyticsOptions {
+    /// The shop's GID, `gid://shopify/Shop/1`; events go to Shopify when
+    /// it is set.
+    pub shop_id: Option<String>,
+    /// The storefront's currency code, reported with Shopify events.
+    pub currency: Option<String>,
+    /// A URL events are also posted to.
+    pub collector: Option<String>,
+}
+
+/// An event as posted to a collector.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsEvent {
+    pub name: AnalyticsEventName,
+    pub event_id: String,
+    /// Milliseconds since the epoch.
+    pub timestamp: f64,
+    pub url: Option<String>,
+    pub referrer: Option<String>,
+    pub title: Option<String>,
+    pub unique_token: Option<String>,
+    pub visit_token: Option<String>,
+    /// What `track` was given.
+    pub payload: Value,
+}
+
+impl AnalyticsEvent {
+    /// The event as a Monorail event for Shopify's endpoint: page views in
+    /// the storefront page-view schema, the rest in the customer-tracking
+    /// schema with their keys in snake_case. `None` for a cart update that
+    /// isn't an addition, which Shopify has no event for.
+    pub fn to_shopify(&self, options: &AnalyticsOptions) -> Option<Value> {
+        let shop_id = options.shop_id.as_deref()?;
+        let (schema_id, payload) = match self.name {
+            AnalyticsEventName::PageViewed => (
+                "trekkie_storefront_page_view/1.4",
+                json!({
+                    "appClientId": "6167201",
+                    "isMerchantRequest": false,
+                    "isPersistentCookie": true,
+                    "uniqToken": self.unique_token,
+                    "visitToken": self.visit_token,
+                    "url": self.url,
+                    "referrer": self.referrer,
+                    "title": self.title,
+                    "shopId": shop_id.rsplit('/').next(),
+                    "currency": options.currency,
+                }),
+            ),
+            name => {
+                if name == AnalyticsEventName::CartUpdated
+                    && self.payload["action"]
+                        .as_str()
+                        .is_some_and(|action| action != "add")
+                {
+                    return None;
+                }
+                let mut payload = Map::new();
+                if let Value::Object(fields) = &self.payload {
+                    for (key, value) in fields {
+                        payload.insert(snake_case(key), value.clone());
+                    }
+                }
+                for (key, value) in [
+                    ("source", json!("headless")),
+                    ("event_name", json!(name.shopify_name())),
+                    ("event_id", json!(self.event_id)),
+                    ("event_time", json!(self.timestamp)),
+                    ("shop_id", json!(shop_id.rsplit('/').next())),
+                    ("currency", json!(options.currency)),
+                    ("unique_token", json!(self.unique_token)),
+                    ("visit_token", json!(self.visit_token)),
+                    ("canonical_url", json!(self.url)),
+                    ("referrer", json!(self.referrer)),
+                ] {
+                    payload.insert(key.to_string(), value);
+                }
+                (
+                    "custom_storefront_customer_tracking/1.2",
+                    Value::Object(payload),
+                )
+            }
+        };
+        Some(json!({
+            "schema_id": schema_id,
+            "payload": payload,
+            "metadata": { "event_created_at_ms": self.timestamp },
+        }))
+    }
+}
+
+fn snake_case(key: &str) -> String {
+    let mut snake = String::with_capacity(key.len() + 4);
+    for character in key.chars() {
+        if character.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(character.to_ascii_lowercase());
+        } else {
+            snake.push(character);
+        }
+    }
+    snake
+}
+
+/// `globalThis[path[0]][path[1]]...` as a string, if there is one.
+fn global_string(path: &[&str]) -> Option<String> {
+    let mut value: JsValue = js_sys::global().into();
+    for key in path {
+        if !value.is_object() {
+            return None;
+        }
+        value = js_sys::Reflect::get(&value, &JsValue::from_str(key)).ok()?;
+    }
+    value.as_string().filter(|value| !value.is_empty())
+}
+
+/// A random version 4 UUID.
+fn event_id() -> String {
+    if let Some(uuid) = js_sys::Reflect::get(&js_sys::global(), &"crypto".into())
+        .ok()
+        .filter(JsValue::is_object)
+        .and_then(|crypto| {
+            let random_uuid = js_sys::Reflect::get(&crypto, &"randomUUID".into()).ok()?;
+            random_uuid
+                .dyn_ref::<js_sys::Function>()?
+                .call0(&crypto)
+                .ok()?
+                .as_string()
+        })
+    {
+        return uuid;
+    }
+    let mut hex: String = (0..32)
+        .map(|_| format!("{:x}", (js_sys::Math::random() * 16.0) as u8 & 0xf))
+        .collect();
+    hex.replace_range(12..13, "4");
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+impl StorefrontApi {
+    /// Posts `body` as JSON to `url`, kept alive past the page unloading.
+    /// Whether it was accepted.
+    async fn post_event(&self, url: &str, body: &Value) -> bool {
+        let sent = async {
+            let opts = RequestInit::new();
+            opts.set_method("POST");
+            js_sys::Reflect::set(&opts, &"keepalive".into(), &JsValue::TRUE)?;
+            let headers = Headers::new()?;
+            headers.set("Content-Type", "application/json")?;
+            opts.set_headers(&headers);
+            opts.set_body(&JsValue::from_str(&body.to_string()));
+            let request = Request::new_with_str_and_init(url, &opts)?;
+            let response: web_sys::Response =
+                JsFuture::from(self.fetch(&request)?).await?.dyn_into()?;
+            Ok::<_, JsValue>(response.ok())
+        }
+        .await;
+        let accepted = matches!(sent, Ok(true));
+        if !accepted {
+            self.log(
+                LogLevel::Warning,
+                json!({ "message": "Analytics event not accepted", "url": url }),
+            );
+        }
+        accepted
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Sets where `track` sends events: `{ shopId, currency, collector }`;
+    /// see `analytics`. `undefined` stops sending. Copies of the client
+    /// share them.
+    #[wasm_bindgen]
+    pub fn set_analytics(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "AnalyticsOptions")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        *self.analytics.borrow_mut() = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => {
+                let options: AnalyticsOptions = from_js(value)?;
+                if options.shop_id.is_none() && options.collector.is_none() {
+                    return Err(JsValue::from_str(
+                        "Analytics needs a shopId, a collector, or both",
+                    ));
+                }
+                Some(options)
+            }
+            _ => None,
+        };
+        Ok(())
+    }
+
+    /// Sends a `page_viewed`, `product_viewed`, `search_submitted` or
+    /// `cart_updated` event with `payload` (e.g. `{ searchTerm }`), and
+    /// resolves to whether it was sent: `false` without consent, without
+    /// `set_analytics`, or when no destination accepted it.
+    #[wasm_bindgen]
+    pub async fn track(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "AnalyticsEventName")] name: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "Record<string, unknown>")] payload: Option<
+            JsValue,
+        >,
+    ) -> Result<bool, JsValue> {
+        let name = AnalyticsEventName::parse(&name)?;
+        let payload: Value = match payload {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => json!({}),
+        };
+        let Some(options) = self.analytics.borrow().clone() else {
+            return Ok(false);
+        };
+        let privacy = self.privacy.borrow().clone();
+        if !privacy.analytics_allowed() {
+            self.log(
+                LogLevel::Debug,
+                json!({ "message": "Analytics event dropped without consent", "event": name }),
+            );
+            return Ok(false);
+        }
+        let event = AnalyticsEvent {
+            name,
+            event_id: event_id(),
+            timestamp: js_sys::Date::now(),
+            url: payload["url"]
+                .as_str()
+                .map(String::from)
+                .or_else(|| global_string(&["location", "href"])),
+            referrer: global_string(&["document", "referrer"]),
+            title: global_string(&["document", "title"]),
+            unique_token: privacy.unique_token,
+            visit_token: privacy.visit_token,
+            payload,
+        };
+
+        let mut sent = false;
+        if let Some(shopify) = event.to_shopify(&options) {
+            let url = format!(
+                "https://{}/.well-known/shopify/monorail/unstable/produce_batch",
+                self.config.shop_domain
+            );
+            let body = json!({
+                "events": [shopify],
+                "metadata": { "event_sent_at_ms": js_sys::Date::now() },
+            });
+            sent |= self.post_event(&url, &body).await;
+        }
+        if let Some(collector) = &options.collector {
+            sent |= self
+                .post_event(collector, &json!({ "events": [event] }))
+                .await;
+        }
+        Ok(sent)
+    }
+
+    /// The options set with `set_analytics`, or `null`.
+    #[wasm_bindgen(unchecked_return_type = "AnalyticsOptions | null")]
+    pub fn analytics(&self) -> Result<JsValue, JsValue> {
+        to_js(&*self.analytics.borrow())
+    }
+}
@@ -0,0 +1,220 @@
+//! Blogs and articles.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::pagination::{PageArgs, CONNECTION_ARGUMENTS, PAGE_INFO_FIELDS};
+use crate::search::normalize_enum_values;
+use crate::types::{Article, Blog, Connection, PageInfo};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Accepted `ArticleSortKeys` values.
+const ARTICLE_SORT_KEYS: &[&str] = &[
+    "AUTHOR",
+    "BLOG_TITLE",
+    "ID",
+    "PUBLISHED_AT",
+    "RELEVANCE",
+    "TITLE",
+    "UPDATED_AT",
+];
+
+/// Fields selected for article listings.
+const ARTICLE_SUMMARY_FIELDS: &str = r#"
+    id
+    handle
+    title
+    excerpt
+    excerptHtml
+    tags
+    publishedAt
+    onlineStoreUrl
+    image {
+        url
+        altText
+        width
+        height
+    }
+    authorV2 {
+        name
+        firstName
+        lastName
+    }
+    blog {
+        handle
+        title
+    }
+"#;
+
+/// Options for `list_articles`, passed from JavaScript as a plain object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ArticleListOptions {
+    pub first: Option<u32>,
+    pub after: Option<String>,
+    pub last: Option<u32>,
+    pub before: Option<String>,
+    /// An `ArticleSortKeys` value; defaults to `PUBLISHED_AT`, newest first.
+    pub sort_key: Option<String>,
+    pub reverse: Option<bool>,
+    /// Shopify search syntax, e.g. `tag:recipes` or `author:Jane`.
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleList {
+    pub articles: Vec<Article>,
+    pub page_info: PageInfo,
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches a blog by handle with its SEO fields and authors, or `null`.
+    #[wasm_bindgen(unchecked_return_type = "Blog | null")]
+    pub async fn get_blog(&self, handle: String) -> Result<JsValue, JsValue> {
+        let query = r#"
+            query getBlog($handle: String!) {
+                blog(handle: $handle) {
+                    id
+                    handle
+                    title
+                    onlineStoreUrl
+                    seo {
+                        title
+                        description
+                    }
+                    authors {
+                        name
+                        firstName
+                        lastName
+                        bio
+                    }
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({ "handle": handle });
+        let mut data = self.execute(query, variables).await?;
+        let blog: Option<Blog> = from_json(data["blog"].take())?;
+        to_js(&blog)
+    }
+
+    /// Lists a blog's articles, newest first by default. See
+    /// `ArticleListOptions` for paging, sorting and `query` filtering.
+    #[wasm_bindgen(unchecked_return_type = "ArticleList")]
+    pub async fn list_articles(
+        &self,
+        blog_handle: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "ArticleListOptions")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<JsValue, JsValue> {
+        let options: ArticleListOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => ArticleListOptions::default(),
+        };
+        let page_args = PageArgs {
+            first: options.first,
+            after: options.after,
+            last: options.last,
+            before: options.before,
+        }
+        .resolve()?;
+        let (sort_key, reverse) = match options.sort_key {
+            Some(key) => (
+                normalize_enum_values("article sort key", &[key], ARTICLE_SORT_KEYS)?.pop(),
+                options.reverse.unwrap_or(false),
+            ),
+            None => (
+                Some("PUBLISHED_AT".to_string()),
+                options.reverse.unwrap_or(true),
+            ),
+        };
+
+        let query = format!(
+            r#"
+            query listArticles(
+                $handle: String!
+                $first: Int
+                $after: String
+                $last: Int
+                $before: String
+                $sortKey: ArticleSortKeys
+                $reverse: Boolean
+                $query: String
+            ) {{
+                blog(handle: $handle) {{
+                    articles({CONNECTION_ARGUMENTS}, sortKey: $sortKey, reverse: $reverse, query: $query) {{
+                        edges {{
+                            node {{
+                                {ARTICLE_SUMMARY_FIELDS}
+                            }}
+                        }}
+                        {PAGE_INFO_FIELDS}
+                    }}
+                }}
+            }}
+        "#
+        );
+
+        let mut variables = serde_json::json!({
+            "handle": blog_handle,
+            "sortKey": sort_key,
+            "reverse": reverse,
+            "query": options.query
+        });
+        page_args.add_to(&mut variables);
+
+        let mut data = self.execute(&query, variables).await?;
+        let mut blog = data["blog"].take();
+        if blog.is_null() {
+            return Err(JsValue::from_str(&format!("Blog not found: {blog_handle}")));
+        }
+        let connection: Connection<Article> = from_json(blog["articles"].take())?;
+        to_js(&ArticleList {
+            articles: connection.edges.into_iter().map(|edge| edge.node).collect(),
+            page_info: connection.page_info.unwrap_or_default(),
+        })
+    }
+
+    /// Fetches one article with its HTML content, author, tags and SEO
+    /// fields, or `null` if the blog or article doesn't exist.
+    #[wasm_bindgen(unchecked_return_type = "Article | null")]
+    pub async fn get_article(
+        &self,
+        blog_handle: String,
+        article_handle: String,
+    ) -> Result<JsValue, JsValue> {
+        let query = format!(
+            r#"
+            query getArticle($blog: String!, $handle: String!) {{
+                blog(handle: $blog) {{
+                    articleByHandle(handle: $handle) {{
+                        {ARTICLE_SUMMARY_FIELDS}
+                        contentHtml
+                        authorV2 {{
+                            name
+                            firstName
+                            lastName
+                            bio
+                        }}
+                        seo {{
+                            title
+                            description
+                        }}
+                    }}
+                }}
+            }}
+        "#
+        );
+
+        let variables = serde_json::json!({
+            "blog": blog_handle,
+            "handle": article_handle
+        });
+        let mut data = self.execute(&query, variables).await?;
+        let article: Option<Article> = from_json(data["blog"]["articleByHandle"].take())?;
+        to_js(&article)
+    }
+}
@@ -0,0 +1,101 @@
+//! Bundle (component product) structure for "what's included" listings.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::product::{product_fragment, ProductOptions};
+use crate::types::{ComponentVariant, Product};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleComponent {
+    pub quantity: u32,
+    pub variant: ComponentVariant,
+}
+
+/// A bundle variant and the components it is assembled from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleVariant {
+    pub variant_id: String,
+    pub title: String,
+    pub requires_components: bool,
+    pub components: Vec<BundleComponent>,
+}
+
+/// Groups a product's variants with their components. Variants without
+/// components are omitted, so an empty list means the product isn't a bundle.
+/// The product must have been fetched with `components: true`.
+pub fn bundle_variants(product: &Product) -> Vec<BundleVariant> {
+    let Some(variants) = &product.variants else {
+        return Vec::new();
+    };
+
+    variants
+        .nodes()
+        .filter_map(|variant| {
+            let components: Vec<BundleComponent> = variant
+                .components
+                .as_ref()?
+                .nodes()
+                .map(|component| BundleComponent {
+                    quantity: component.quantity,
+                    variant: component.product_variant.clone(),
+                })
+                .collect();
+            if components.is_empty() {
+                return None;
+            }
+            Some(BundleVariant {
+                variant_id: variant.id.clone(),
+                title: variant.title.clone(),
+                requires_components: variant.requires_components.unwrap_or(false),
+                components,
+            })
+        })
+        .collect()
+}
+
+/// Returns the bundle structure of a product fetched with `components: true`.
+#[wasm_bindgen(unchecked_return_type = "BundleVariant[]")]
+pub fn bundle_components(
+    #[wasm_bindgen(unchecked_param_type = "Product")] product: JsValue,
+) -> Result<JsValue, JsValue> {
+    let product: Product = from_js(product)?;
+    to_js(&bundle_variants(&product))
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches a product with its bundle components and returns
+    /// `[{ variantId, title, requiresComponents, components: [{ quantity, variant }] }]`.
+    #[wasm_bindgen(unchecked_return_type = "BundleVariant[] | null")]
+    pub async fn get_product_bundle(&self, handle: String) -> Result<JsValue, JsValue> {
+        let options = ProductOptions {
+            components: true,
+            ..ProductOptions::default()
+        };
+        let query = format!(
+            r#"
+            query getProductBundle($handle: String!) {{
+                product(handle: $handle) {{
+                    ...ProductFields
+                }}
+            }}
+            {}
+        "#,
+            product_fragment(&options)
+        );
+
+        let variables = serde_json::json!({
+            "handle": handle
+        });
+
+        let mut data = self.execute(&query, variables).await?;
+        let mut product = data["product"].take();
+        self.complete_variants(&mut product, &options).await?;
+        let product: Option<Product> = from_json(product)?;
+        to_js(&product.map(|product| bundle_variants(&product)))
+    }
+}
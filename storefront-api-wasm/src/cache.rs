@@ -0,0 +1,377 @@
+//! Response caching with stale-while-revalidate.
+//!
+//! With `set_cache({ mode: "staleWhileRevalidate" })`, query responses
+//! are kept by operation and variables. A response younger than `maxAgeMs`
+//! answers calls outright. Older ones, up to `staleMs` past that, still
+//! answer at once, while the request is sent again in the background; when
+//! the fresh data differs, it replaces the cached response and the callback
+//! registered with `on_revalidate` receives it, so the page can re-render.
+//! Anything older is fetched as usual. Mutations are never cached, and one
+//! that succeeds drops the responses whose variables name an ID or customer
+//! token it sent, such as a `getCart` read of the cart `add_to_cart` changed.
+//!
+//! In `"revalidate"` mode every call goes to the network, but a cached
+//! response's validators make it a conditional request (see `conditional`),
+//! so unchanged data isn't downloaded again.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::conditional::Validators;
+use crate::graphql::is_mutation;
+use crate::memory::json_size;
+use crate::{from_js, to_js, StorefrontApi};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CacheMode {
+    /// Every call goes to the network.
+    #[default]
+    Off,
+    /// Serve cached data at once and refresh it in the background.
+    StaleWhileRevalidate,
+    /// Send every call, conditionally when the cached response has
+    /// validators.
+    Revalidate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CacheOptions {
+    pub mode: CacheMode,
+    /// How long a response is served without revalidating.
+    pub max_age_ms: f64,
+    /// How long after `max_age_ms` a response is still served while it's
+    /// revalidated.
+    pub stale_ms: f64,
+    /// Most responses kept; the least recently stored go first.
+    pub max_entries: usize,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        CacheOptions {
+            mode: CacheMode::Off,
+            max_age_ms: 0.0,
+            stale_ms: 5.0 * 60_000.0,
+            max_entries: 100,
+        }
+    }
+}
+
+struct Entry {
+    data: Value,
+    stored_at: f64,
+    /// Approximate size of the key and data; see `memory::json_size`.
+    bytes: usize,
+}
+
+/// What the cache has for a request.
+pub(crate) enum Cached {
+    Fresh(Value),
+    /// Serve it, and revalidate.
+    Stale(Value),
+    Missing,
+}
+
+/// Cached responses and the revalidation callback, shared by a client's
+/// copies.
+#[derive(Default)]
+pub struct ResponseCache {
+    options: CacheOptions,
+    entries: HashMap<String, Entry>,
+    /// Validators of cached responses that had them.
+    validators: HashMap<String, Validators>,
+    /// Requests being revalidated, so each is sent once at a time.
+    revalidating: HashSet<String>,
+    callback: Option<js_sys::Function>,
+}
+
+impl ResponseCache {
+    pub fn options(&self) -> &CacheOptions {
+        &self.options
+    }
+
+    /// Replaces the options; turning the cache off empties it.
+    pub fn set_options(&mut self, options: CacheOptions) {
+        if options.mode == CacheMode::Off {
+            self.clear();
+        }
+        self.options = options;
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.validators.clear();
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+        self.validators.remove(key);
+    }
+
+    /// Drops the responses whose variables name an ID or customer token
+    /// among the variables of the mutation sent with `key`.
+    pub(crate) fn invalidate(&mut self, key: &str) {
+        let mut scoped = Vec::new();
+        scoped_values(&key_variables(key), &mut scoped);
+        if scoped.is_empty() {
+            return;
+        }
+        let stale: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|key| mentions(&key_variables(key), &scoped))
+            .cloned()
+            .collect();
+        for key in stale {
+            self.remove(&key);
+        }
+    }
+
+    pub(crate) fn lookup(&mut self, key: &str, now: f64) -> Cached {
+        let Some(entry) = self.entries.get(key) else {
+            return Cached::Missing;
+        };
+        let age = now - entry.stored_at;
+        if age < self.options.max_age_ms {
+            Cached::Fresh(entry.data.clone())
+        } else if age < self.options.max_age_ms + self.options.stale_ms {
+            Cached::Stale(entry.data.clone())
+        } else {
+            self.remove(key);
+            Cached::Missing
+        }
+    }
+
+    /// The validators to revalidate a cached response with.
+    pub(crate) fn validators(&self, key: &str) -> Option<&Validators> {
+        if self.options.mode == CacheMode::Off || !self.entries.contains_key(key) {
+            return None;
+        }
+        self.validators.get(key)
+    }
+
+    /// Keeps the validators of a response about to be stored.
+    pub(crate) fn set_validators(&mut self, key: &str, validators: Validators) {
+        if self.options.mode != CacheMode::Off {
+            self.validators.insert(key.to_string(), validators);
+        }
+    }
+
+    /// The cached data a `304 Not Modified` confirmed, now fresh again.
+    pub(crate) fn not_modified(&mut self, key: &str, now: f64) -> Option<Value> {
+        let entry = self.entries.get_mut(key)?;
+        entry.stored_at = now;
+        Some(entry.data.clone())
+    }
+
+    /// Stores a response, returning whether it differs from the one it
+    /// replaces.
+    pub(crate) fn store(&mut self, key: &str, data: Value, now: f64) -> bool {
+        if self.options.max_entries == 0 {
+            return false;
+        }
+        if !self.entries.contains_key(key) && self.entries.len() >= self.options.max_entries {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by(|(_, a), (_, b)| a.stored_at.total_cmp(&b.stored_at))
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.remove(&oldest);
+            }
+        }
+        let changed = self
+            .entries
+            .get(key)
+            .is_none_or(|previous| previous.data != data);
+        let bytes = key.len() + json_size(&data);
+        self.entries.insert(
+            key.to_string(),
+            Entry {
+                data,
+                stored_at: now,
+                bytes,
+            },
+        );
+        // Validators stay only with the responses they came with.
+        let entries = &self.entries;
+        self.validators.retain(|key, _| entries.contains_key(key));
+        changed
+    }
+
+    /// How many responses are kept, and their approximate size in bytes.
+    pub fn usage(&self) -> (usize, usize) {
+        let bytes = self.entries.values().map(|entry| entry.bytes).sum();
+        (self.entries.len(), bytes)
+    }
+
+    /// Drops the least recently stored response, returning its size.
+    pub(crate) fn evict_oldest(&mut self) -> Option<usize> {
+        let (key, bytes) = self
+            .entries
+            .iter()
+            .min_by(|(_, a), (_, b)| a.stored_at.total_cmp(&b.stored_at))
+            .map(|(key, entry)| (key.clone(), entry.bytes))?;
+        self.remove(&key);
+        Some(bytes)
+    }
+}
+
+/// The variables of a request key; see `canonical::request_key`.
+fn key_variables(key: &str) -> Value {
+    key.rsplit_once('\n')
+        .and_then(|(_, variables)| serde_json::from_str(variables).ok())
+        .unwrap_or(Value::Null)
+}
+
+/// Collects the IDs and customer tokens in `variables`, e.g. a `cartId`.
+fn scoped_values(variables: &Value, values: &mut Vec<String>) {
+    match variables {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    Value::String(text)
+                        if key == "id" || key.ends_with("Id") || key.ends_with("AccessToken") =>
+                    {
+                        values.push(text.clone())
+                    }
+                    _ => scoped_values(value, values),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| scoped_values(item, values)),
+        _ => {}
+    }
+}
+
+/// Whether any string in `value` is one of `values`.
+fn mentions(value: &Value, values: &[String]) -> bool {
+    match value {
+        Value::String(text) => values.contains(text),
+        Value::Array(items) => items.iter().any(|item| mentions(item, values)),
+        Value::Object(object) => object.values().any(|item| mentions(item, values)),
+        _ => false,
+    }
+}
+
+impl StorefrontApi {
+    /// Answers a query from the cache when it's on: fresh responses as
+    /// they are, stale ones while a background request refreshes them.
+    pub(crate) fn cached_response(
+        &self,
+        key: &str,
+        query: &str,
+        variables: &Value,
+    ) -> Option<Value> {
+        let mut cache = self.cache.borrow_mut();
+        if cache.options.mode != CacheMode::StaleWhileRevalidate || is_mutation(query) {
+            return None;
+        }
+        match cache.lookup(key, js_sys::Date::now()) {
+            Cached::Fresh(data) => Some(data),
+            Cached::Stale(data) => {
+                if cache.revalidating.insert(key.to_string()) {
+                    let client = self.clone();
+                    let (key, query, variables) =
+                        (key.to_string(), query.to_string(), variables.clone());
+                    spawn_local(async move { client.revalidate(key, query, variables).await });
+                }
+                Some(data)
+            }
+            Cached::Missing => None,
+        }
+    }
+
+    /// Keeps a network response for later calls when the cache is on, or
+    /// for a mutation, drops the responses it may have made stale.
+    pub(crate) fn cache_response(&self, key: &str, query: &str, data: &Value) {
+        let mut cache = self.cache.borrow_mut();
+        if is_mutation(query) {
+            cache.invalidate(key);
+        } else if cache.options.mode != CacheMode::Off {
+            cache.store(key, data.clone(), js_sys::Date::now());
+        }
+    }
+
+    /// Sends a stale request again, telling the callback when the data
+    /// changed.
+    async fn revalidate(&self, key: String, query: String, variables: Value) {
+        let result = self.send_operation(query.clone(), variables.clone()).await;
+        let callback = {
+            let mut cache = self.cache.borrow_mut();
+            cache.revalidating.remove(&key);
+            let Ok(data) = &result else {
+                return;
+            };
+            if !cache.store(&key, data.clone(), js_sys::Date::now()) {
+                return;
+            }
+            cache.callback.clone()
+        };
+        let (Some(callback), Ok(data)) = (callback, result) else {
+            return;
+        };
+        let update = serde_json::json!({
+            "operation": crate::logging::operation_name(&query).unwrap_or("anonymous"),
+            "variables": variables,
+            "data": data,
+        });
+        if let Ok(update) = to_js(&update) {
+            let _ = callback.call1(&JsValue::NULL, &update);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Turns response caching on or off. `options` is `{ mode, maxAgeMs,
+    /// staleMs, maxEntries }`, with `mode` `"staleWhileRevalidate"`,
+    /// `"revalidate"` or `"off"` (the default). Omitted fields take their
+    /// defaults: responses revalidate on every call (`maxAgeMs` 0), are
+    /// served stale for up to five minutes, and at most 100 are kept. Copies
+    /// of the client share the cache.
+    #[wasm_bindgen]
+    pub fn set_cache(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "Partial<CacheOptions>")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        let options: CacheOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => CacheOptions::default(),
+        };
+        self.cache.borrow_mut().set_options(options);
+        Ok(())
+    }
+
+    /// The current cache options.
+    #[wasm_bindgen(unchecked_return_type = "CacheOptions")]
+    pub fn cache_options(&self) -> Result<JsValue, JsValue> {
+        to_js(self.cache.borrow().options())
+    }
+
+    /// Calls `callback({ operation, variables, data })` when a background
+    /// revalidation brings data that differs from what was served, `data`
+    /// being the operation's whole `data` payload. `null` removes it.
+    #[wasm_bindgen]
+    pub fn on_revalidate(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "(update: RevalidatedResponse) => void")]
+        callback: Option<js_sys::Function>,
+    ) {
+        self.cache.borrow_mut().callback = callback;
+    }
+
+    /// Empties the response cache.
+    #[wasm_bindgen]
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
@@ -0,0 +1,190 @@
+//! Canonical forms of requests, so the same request is recognized however
+//! it was written.
+//!
+//! The canonical query is the operation's tokens with whitespace, commas
+//! and comments dropped and single spaces only where two words would
+//! otherwise run together. Canonical variables have object keys sorted and
+//! whole-number floats (`1.0`) written as integers, and `null` variables
+//! are the same as none. Requests are keyed by both for the response
+//! cache, conditional requests and prefetch deduplication, and the
+//! persisted-query hash is the SHA-256 of the canonical query, which is
+//! therefore the text to send alongside it.
+
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::graphql::{tokenize, Token};
+use crate::{from_js, to_js};
+
+/// The query in canonical form, or the trimmed query when it can't be
+/// tokenized (the API will report what's wrong with it).
+pub fn canonical_query(query: &str) -> String {
+    let Ok(tokens) = tokenize(query) else {
+        return query.trim().to_string();
+    };
+    let mut canonical = String::with_capacity(query.len());
+    let mut after_word = false;
+    for token in tokens {
+        let word = !matches!(token, Token::Punctuator(_));
+        if word && after_word {
+            canonical.push(' ');
+        }
+        match token {
+            Token::Punctuator('.') => canonical.push_str("..."),
+            Token::Punctuator(c) => canonical.push(c),
+            Token::Name(name) | Token::Number(name) => canonical.push_str(&name),
+            Token::String(text) => canonical.push_str(&Value::String(text).to_string()),
+        }
+        after_word = word;
+    }
+    canonical
+}
+
+/// `value` with object keys sorted and whole-number floats as integers.
+pub fn canonical_value(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            let mut canonical = Map::new();
+            for key in keys {
+                canonical.insert(key.clone(), canonical_value(&object[key]));
+            }
+            Value::Object(canonical)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonical_value).collect()),
+        Value::Number(number) => match number.as_f64() {
+            Some(float)
+                if !number.is_i64()
+                    && !number.is_u64()
+                    && float.fract() == 0.0
+                    && float.abs() < 9_007_199_254_740_992.0 =>
+            {
+                Value::from(float as i64)
+            }
+            _ => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+/// The variables in canonical form; no variables and `{}` are both
+/// `null`.
+pub fn canonical_variables(variables: &Value) -> Value {
+    match variables {
+        Value::Object(object) if object.is_empty() => Value::Null,
+        _ => canonical_value(variables),
+    }
+}
+
+/// Identifies a request by its canonical query and variables.
+pub(crate) fn request_key(query: &str, variables: &Value) -> String {
+    format!(
+        "{}\n{}",
+        canonical_query(query),
+        canonical_variables(variables)
+    )
+}
+
+/// The SHA-256 of `text` as lowercase hex.
+pub fn sha256_hex(text: &str) -> String {
+    sha256(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The SHA-256 digest of `bytes`.
+pub fn sha256(bytes: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    let mut message = bytes.to_vec();
+    let length_bits = (message.len() as u64).wrapping_mul(8);
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&length_bits.to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+    let mut digest = [0u8; 32];
+    for (chunk, word) in digest.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// The persisted-query hash of `query`: the SHA-256 of its canonical form.
+pub fn persisted_query_hash(query: &str) -> String {
+    sha256_hex(&canonical_query(query))
+}
+
+/// The canonical form of a request, `{ query, variables, hash }`, where
+/// `hash` is the SHA-256 of `query` for persisted queries.
+#[wasm_bindgen(unchecked_return_type = "CanonicalRequest")]
+pub fn canonical_request(
+    query: &str,
+    #[wasm_bindgen(unchecked_optional_param_type = "Variables")] variables: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    let variables: Value = match variables {
+        Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+        _ => Value::Null,
+    };
+    let query = canonical_query(query);
+    to_js(&serde_json::json!({
+        "hash": sha256_hex(&query),
+        "query": query,
+        "variables": canonical_variables(&variables),
+    }))
+}
@@ -0,0 +1,281 @@
+//! Cart mutations and the cart selection set.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ErrorDetail, StorefrontError};
+use crate::gift_card::GiftCardRecipient;
+use crate::types::Cart;
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+pub use crate::types::Attribute;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CartItem {
+    pub variant_id: String,
+    pub quantity: u32,
+    /// Line item properties.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<Attribute>,
+    /// Recipient details for gift card lines, sent as the line attributes
+    /// Shopify uses to email the card.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gift_card_recipient: Option<GiftCardRecipient>,
+}
+
+impl CartItem {
+    /// Renders the item as a `CartLineInput`.
+    pub(crate) fn to_line_input(&self) -> serde_json::Value {
+        let mut attributes = self.attributes.clone();
+        if let Some(recipient) = &self.gift_card_recipient {
+            attributes.extend(recipient.to_attributes());
+        }
+
+        let mut line = serde_json::json!({
+            "merchandiseId": self.variant_id,
+            "quantity": self.quantity
+        });
+        if !attributes.is_empty() {
+            line["attributes"] = serde_json::json!(attributes);
+        }
+        line
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Creates a cart from `[{ variant_id, quantity, attributes?,
+    /// gift_card_recipient? }]` items and returns it.
+    #[wasm_bindgen(unchecked_return_type = "Cart")]
+    pub async fn create_cart(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "CartItem[]")] items: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let items: Vec<CartItem> = from_js(items)?;
+        to_js(&self.cart_create(&items).await?)
+    }
+}
+
+/// A `userErrors` entry from a cart mutation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartUserError {
+    #[serde(default)]
+    pub field: Option<Vec<String>>,
+    pub message: String,
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches a cart by ID, or `null` if it doesn't exist (or has been
+    /// completed).
+    #[wasm_bindgen(unchecked_return_type = "Cart | null")]
+    pub async fn get_cart(&self, cart_id: String) -> Result<JsValue, JsValue> {
+        to_js(&self.fetch_cart(&cart_id).await?)
+    }
+
+    /// Adds `[{ variant_id, quantity, attributes? }]` items to a cart and
+    /// returns the updated cart.
+    #[wasm_bindgen(unchecked_return_type = "Cart")]
+    pub async fn add_cart_lines(
+        &self,
+        cart_id: String,
+        #[wasm_bindgen(unchecked_param_type = "CartItem[]")] items: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let items: Vec<CartItem> = from_js(items)?;
+        to_js(&self.cart_lines_add(&cart_id, &items).await?)
+    }
+
+    /// Sets a cart line's quantity; `0` removes the line. Returns the
+    /// updated cart.
+    #[wasm_bindgen(unchecked_return_type = "Cart")]
+    pub async fn update_cart_line(
+        &self,
+        cart_id: String,
+        line_id: String,
+        quantity: u32,
+    ) -> Result<JsValue, JsValue> {
+        to_js(&self.cart_line_update(&cart_id, &line_id, quantity).await?)
+    }
+}
+
+impl StorefrontApi {
+    pub(crate) async fn fetch_cart(&self, cart_id: &str) -> Result<Option<Cart>, JsValue> {
+        let query = format!(
+            r#"
+            query getCart($id: ID!) {{
+                cart(id: $id) {{
+                    ...CartFields
+                }}
+            }}
+            {CART_FRAGMENT}
+        "#
+        );
+
+        let mut data = self
+            .execute(&query, serde_json::json!({ "id": cart_id }))
+            .await?;
+        from_json(data["cart"].take())
+    }
+
+    pub(crate) async fn cart_create(&self, items: &[CartItem]) -> Result<Cart, JsValue> {
+        let query = format!(
+            r#"
+            mutation createCart($lines: [CartLineInput!]!) {{
+                cartCreate(input: {{ lines: $lines }}) {{
+                    cart {{
+                        ...CartFields
+                    }}
+                    userErrors {{
+                        field
+                        message
+                        code
+                    }}
+                }}
+            }}
+            {CART_FRAGMENT}
+        "#
+        );
+
+        let variables = serde_json::json!({
+            "lines": items.iter().map(CartItem::to_line_input).collect::<Vec<_>>()
+        });
+        self.cart_mutation(&query, variables, "cartCreate").await
+    }
+
+    pub(crate) async fn cart_lines_add(
+        &self,
+        cart_id: &str,
+        items: &[CartItem],
+    ) -> Result<Cart, JsValue> {
+        let query = format!(
+            r#"
+            mutation addCartLines($cartId: ID!, $lines: [CartLineInput!]!) {{
+                cartLinesAdd(cartId: $cartId, lines: $lines) {{
+                    cart {{
+                        ...CartFields
+                    }}
+                    userErrors {{
+                        field
+                        message
+                        code
+                    }}
+                }}
+            }}
+            {CART_FRAGMENT}
+        "#
+        );
+
+        let variables = serde_json::json!({
+            "cartId": cart_id,
+            "lines": items.iter().map(CartItem::to_line_input).collect::<Vec<_>>()
+        });
+        self.cart_mutation(&query, variables, "cartLinesAdd").await
+    }
+
+    pub(crate) async fn cart_line_update(
+        &self,
+        cart_id: &str,
+        line_id: &str,
+        quantity: u32,
+    ) -> Result<Cart, JsValue> {
+        let query = format!(
+            r#"
+            mutation updateCartLine($cartId: ID!, $lines: [CartLineUpdateInput!]!) {{
+                cartLinesUpdate(cartId: $cartId, lines: $lines) {{
+                    cart {{
+                        ...CartFields
+                    }}
+                    userErrors {{
+                        field
+                        message
+                        code
+                    }}
+                }}
+            }}
+            {CART_FRAGMENT}
+        "#
+        );
+
+        let variables = serde_json::json!({
+            "cartId": cart_id,
+            "lines": [{ "id": line_id, "quantity": quantity }]
+        });
+        self.cart_mutation(&query, variables, "cartLinesUpdate")
+            .await
+    }
+
+    /// Runs a cart mutation and returns its cart, failing with a
+    /// `userError` `StorefrontError` if the mutation reports any.
+    async fn cart_mutation(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+        field: &str,
+    ) -> Result<Cart, JsValue> {
+        let mut data = self.execute(query, variables).await?;
+        let mut payload = data[field].take();
+        let user_errors: Vec<CartUserError> =
+            from_json(payload["userErrors"].take()).unwrap_or_default();
+        if !user_errors.is_empty() {
+            return Err(StorefrontError::user_errors(
+                user_errors
+                    .into_iter()
+                    .map(|error| ErrorDetail {
+                        message: error.message,
+                        code: error.code,
+                        field: error.field,
+                    })
+                    .collect(),
+            )
+            .into());
+        }
+        from_json(payload["cart"].take())
+    }
+}
+
+pub(crate) const CART_FRAGMENT: &str = r#"
+    fragment CartFields on Cart {
+        id
+        checkoutUrl
+        totalQuantity
+        cost {
+            totalAmount {
+                amount
+                currencyCode
+            }
+        }
+        discountCodes {
+            code
+            applicable
+        }
+        lines(first: 100) {
+            edges {
+                node {
+                    id
+                    quantity
+                    attributes {
+                        key
+                        value
+                    }
+                    merchandise {
+                        ... on ProductVariant {
+                            id
+                            title
+                            price {
+                                amount
+                                currencyCode
+                            }
+                            product {
+                                id
+                                title
+                                handle
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+"#;
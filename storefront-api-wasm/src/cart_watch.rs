@@ -0,0 +1,299 @@
+//! Keeping a cart view in step with changes made elsewhere.
+//!
+//! A cart can change behind a page's back: in another tab, on another
+//! device, or at Shopify (a price update, a discount that stops applying).
+//! `watch_cart(cartId, intervalMs, callback, snapshot?)` polls the cart and
+//! compares it with the page's snapshot, calling
+//! `callback(changes, cart)` with what differs:
+//!
+//! ```js
+//! const watcher = api.watch_cart(cartId, 5000, (changes, cart) => {
+//!   for (const change of changes) {
+//!     if (change.type === "discountExpired") toast(`${change.code} no longer applies`);
+//!   }
+//!   render(cart);
+//! }, cart);
+//! const updated = await api.add_cart_lines(cartId, items);
+//! watcher.update(updated); // the page's own change, not reported
+//! ```
+//!
+//! Changes are `lineAdded`, `lineRemoved`, `quantityChanged`,
+//! `priceChanged`, `discountApplied`, `discountExpired`, `totalChanged`
+//! and `cartGone`, for a cart that was completed or expired, after which
+//! the watcher stops. Without a snapshot the first poll becomes one. Polls
+//! always go to the network; a failed poll is skipped and tried again on
+//! the next tick.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::cart::CART_FRAGMENT;
+use crate::retry::sleep;
+use crate::types::{Cart, CartLine, Money};
+use crate::watch::MIN_WATCH_INTERVAL_MS;
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// A difference between two versions of a cart.
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum CartChange {
+    LineAdded {
+        line: CartLine,
+    },
+    LineRemoved {
+        line: CartLine,
+    },
+    QuantityChanged {
+        line_id: String,
+        variant_id: String,
+        previous: u32,
+        quantity: u32,
+    },
+    PriceChanged {
+        line_id: String,
+        variant_id: String,
+        previous: Money,
+        price: Money,
+    },
+    DiscountApplied {
+        code: String,
+    },
+    /// A code that applied no longer does, or was removed.
+    DiscountExpired {
+        code: String,
+    },
+    TotalChanged {
+        previous: Money,
+        total: Money,
+    },
+    /// The cart no longer exists: it was completed or expired.
+    CartGone,
+}
+
+/// What changed from `old` to `new`: lines by ID, then discount codes,
+/// then the total.
+pub fn cart_changes(old: &Cart, new: &Cart) -> Vec<CartChange> {
+    let mut changes = Vec::new();
+    for line in new.lines.nodes() {
+        let Some(previous) = old.lines.nodes().find(|previous| previous.id == line.id) else {
+            changes.push(CartChange::LineAdded { line: line.clone() });
+            continue;
+        };
+        if previous.quantity != line.quantity {
+            changes.push(CartChange::QuantityChanged {
+                line_id: line.id.clone(),
+                variant_id: line.merchandise.id.clone(),
+                previous: previous.quantity,
+                quantity: line.quantity,
+            });
+        }
+        if previous.merchandise.price != line.merchandise.price {
+            changes.push(CartChange::PriceChanged {
+                line_id: line.id.clone(),
+                variant_id: line.merchandise.id.clone(),
+                previous: previous.merchandise.price.clone(),
+                price: line.merchandise.price.clone(),
+            });
+        }
+    }
+    for line in old.lines.nodes() {
+        if !new.lines.nodes().any(|current| current.id == line.id) {
+            changes.push(CartChange::LineRemoved { line: line.clone() });
+        }
+    }
+
+    let applicable = |cart: &Cart, code: &str| {
+        cart.discount_codes
+            .iter()
+            .any(|discount| discount.applicable && discount.code.eq_ignore_ascii_case(code))
+    };
+    for discount in new
+        .discount_codes
+        .iter()
+        .filter(|discount| discount.applicable)
+    {
+        if !applicable(old, &discount.code) {
+            changes.push(CartChange::DiscountApplied {
+                code: discount.code.clone(),
+            });
+        }
+    }
+    for discount in old
+        .discount_codes
+        .iter()
+        .filter(|discount| discount.applicable)
+    {
+        if !applicable(new, &discount.code) {
+            changes.push(CartChange::DiscountExpired {
+                code: discount.code.clone(),
+            });
+        }
+    }
+
+    if old.cost.total_amount != new.cost.total_amount {
+        changes.push(CartChange::TotalChanged {
+            previous: old.cost.total_amount.clone(),
+            total: new.cost.total_amount.clone(),
+        });
+    }
+    changes
+}
+
+struct CartWatchState {
+    active: bool,
+    /// The cart as the page last saw it, `None` before the first poll
+    /// without a snapshot.
+    snapshot: Option<Cart>,
+}
+
+/// A running `watch_cart`; `stop()` ends it.
+#[wasm_bindgen]
+pub struct CartWatcher {
+    state: Rc<RefCell<CartWatchState>>,
+}
+
+#[wasm_bindgen]
+impl CartWatcher {
+    /// Stops polling. A poll already in flight reports nothing.
+    #[wasm_bindgen]
+    pub fn stop(&self) {
+        self.state.borrow_mut().active = false;
+    }
+
+    /// Whether the watcher is still polling.
+    #[wasm_bindgen]
+    pub fn is_active(&self) -> bool {
+        self.state.borrow().active
+    }
+
+    /// Replaces the snapshot with `cart`, e.g. as returned by the page's own
+    /// mutation, so its changes aren't reported back to it.
+    #[wasm_bindgen]
+    pub fn update(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Cart")] cart: JsValue,
+    ) -> Result<(), JsValue> {
+        let cart: Cart = from_js(cart)?;
+        self.state.borrow_mut().snapshot = Some(cart);
+        Ok(())
+    }
+
+    /// The snapshot changes are reported against, or `null` before the
+    /// first poll.
+    #[wasm_bindgen(unchecked_return_type = "Cart | null")]
+    pub fn current(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.state.borrow().snapshot)
+    }
+}
+
+impl StorefrontApi {
+    /// Fetches the cart once and reports how it differs from the snapshot.
+    async fn poll_cart(
+        &self,
+        query: &str,
+        cart_id: &str,
+        state: &RefCell<CartWatchState>,
+        callback: &js_sys::Function,
+    ) {
+        let variables = serde_json::json!({ "id": cart_id });
+        let Ok(mut data) = self.send_operation(query.to_string(), variables).await else {
+            return;
+        };
+        let Ok(cart) = from_json::<Option<Cart>>(data["cart"].take()) else {
+            return;
+        };
+        let changes = {
+            let mut state = state.borrow_mut();
+            if !state.active {
+                return;
+            }
+            match (&state.snapshot, cart) {
+                (_, None) => {
+                    state.active = false;
+                    state.snapshot = None;
+                    vec![CartChange::CartGone]
+                }
+                (None, Some(cart)) => {
+                    state.snapshot = Some(cart);
+                    return;
+                }
+                (Some(snapshot), Some(cart)) => {
+                    let changes = cart_changes(snapshot, &cart);
+                    state.snapshot = Some(cart);
+                    changes
+                }
+            }
+        };
+        if changes.is_empty() {
+            return;
+        }
+        let snapshot = to_js(&state.borrow().snapshot);
+        if let (Ok(changes), Ok(cart)) = (to_js(&changes), snapshot) {
+            let _ = callback.call2(&JsValue::NULL, &changes, &cart);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Polls the cart every `interval_ms` (at least 100) and calls
+    /// `callback(changes, cart)` when it differs from `snapshot`, the cart
+    /// as the page shows it (by default, the first poll's). Returns a
+    /// watcher whose `update(cart)` takes the page's own changes and whose
+    /// `stop()` ends it.
+    #[wasm_bindgen]
+    pub fn watch_cart(
+        &self,
+        cart_id: String,
+        interval_ms: u32,
+        #[wasm_bindgen(
+            unchecked_param_type = "(changes: CartChange[], cart: Cart | null) => void"
+        )]
+        callback: js_sys::Function,
+        #[wasm_bindgen(unchecked_optional_param_type = "Cart")] snapshot: Option<JsValue>,
+    ) -> Result<CartWatcher, JsValue> {
+        let snapshot: Option<Cart> = match snapshot {
+            Some(value) if !value.is_undefined() && !value.is_null() => Some(from_js(value)?),
+            _ => None,
+        };
+        let query = self.context.borrow().apply(&format!(
+            r#"
+            query watchCart($id: ID!) {{
+                cart(id: $id) {{
+                    ...CartFields
+                }}
+            }}
+            {CART_FRAGMENT}
+        "#
+        ));
+        let interval = interval_ms.max(MIN_WATCH_INTERVAL_MS);
+        let state = Rc::new(RefCell::new(CartWatchState {
+            active: true,
+            snapshot,
+        }));
+        let client = self.clone();
+        let polling = Rc::clone(&state);
+        spawn_local(async move {
+            while polling.borrow().active {
+                client
+                    .poll_cart(&query, &cart_id, &polling, &callback)
+                    .await;
+                if !polling.borrow().active {
+                    break;
+                }
+                if sleep(interval).await.is_err() {
+                    polling.borrow_mut().active = false;
+                }
+            }
+        });
+        Ok(CartWatcher { state })
+    }
+}
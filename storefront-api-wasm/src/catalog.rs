@@ -0,0 +1,52 @@
+//! Shop-wide product types and tags.
+
+use wasm_bindgen::prelude::*;
+
+use crate::collection::MAX_PAGE_SIZE;
+use crate::types::Connection;
+use crate::{from_json, StorefrontApi};
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Returns the product types in use across the shop (up to 250), for
+    /// filter sidebars. Empty types are left out.
+    #[wasm_bindgen]
+    pub async fn list_product_types(&self) -> Result<Vec<String>, JsValue> {
+        self.fetch_strings("productTypes", MAX_PAGE_SIZE).await
+    }
+
+    /// Returns up to `first` (default and maximum 250) product tags in use
+    /// across the shop, for tag clouds.
+    #[wasm_bindgen]
+    pub async fn list_product_tags(&self, first: Option<u32>) -> Result<Vec<String>, JsValue> {
+        let first = first.unwrap_or(MAX_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        self.fetch_strings("productTags", first).await
+    }
+}
+
+impl StorefrontApi {
+    /// Reads a root `StringConnection` field.
+    async fn fetch_strings(&self, field: &str, first: u32) -> Result<Vec<String>, JsValue> {
+        let query = format!(
+            r#"
+            query {field}($first: Int!) {{
+                {field}(first: $first) {{
+                    edges {{
+                        node
+                    }}
+                }}
+            }}
+        "#
+        );
+
+        let variables = serde_json::json!({ "first": first });
+        let mut data = self.execute(&query, variables).await?;
+        let connection: Connection<String> = from_json(data[field].take())?;
+        Ok(connection
+            .edges
+            .into_iter()
+            .map(|edge| edge.node)
+            .filter(|value| !value.trim().is_empty())
+            .collect())
+    }
+}
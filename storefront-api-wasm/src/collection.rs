@@ -0,0 +1,470 @@
+//! Collection queries.
+
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::pagination::{
+    ListOptions, PageArgs, CONNECTION_ARGUMENTS, CONNECTION_VARIABLES, PAGE_INFO_FIELDS,
+};
+use crate::product::{metafield_identifiers_literal, PRODUCT_CARD_FRAGMENT};
+use crate::search::FILTER_FIELDS;
+use crate::types::{
+    Collection, Connection, Filter, Image, MetafieldIdentifier, PageInfo, Product, ProductFilter,
+};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Largest `first` the Storefront API accepts on a connection.
+pub(crate) const MAX_PAGE_SIZE: u32 = 250;
+
+/// Products sampled per collection to estimate its size in `list_collections`.
+const PRODUCT_COUNT_SAMPLE: u32 = 50;
+
+/// Accepted `CollectionSortKeys` values.
+const COLLECTION_SORT_KEYS: &[&str] = &["ID", "RELEVANCE", "TITLE", "UPDATED_AT"];
+
+/// Accepted `ProductCollectionSortKeys` values.
+const PRODUCT_COLLECTION_SORT_KEYS: &[&str] = &[
+    "BEST_SELLING",
+    "COLLECTION_DEFAULT",
+    "CREATED",
+    "ID",
+    "MANUAL",
+    "PRICE",
+    "RELEVANCE",
+    "TITLE",
+];
+
+/// Optional arguments for `get_collection`, passed from JavaScript as a plain
+/// object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CollectionOptions {
+    pub filters: Vec<ProductFilter>,
+    /// A `ProductCollectionSortKeys` value such as `BEST_SELLING` or `PRICE`.
+    pub sort_key: Option<String>,
+    pub reverse: bool,
+    /// Collection metafields to include, e.g. hero copy or banner settings.
+    pub metafields: Vec<MetafieldIdentifier>,
+    /// Shorthand for an `{ available }` filter; `true` hides sold-out products.
+    pub available_for_sale: Option<bool>,
+    /// Cursor to page forward from (`pageInfo.endCursor`).
+    pub after: Option<String>,
+    /// Cursor to page backward from (`pageInfo.startCursor`), with `last`.
+    pub before: Option<String>,
+    /// Page size when paging backward; replaces `first`.
+    pub last: Option<u32>,
+}
+
+impl CollectionOptions {
+    pub(crate) fn from_js(options: Option<JsValue>) -> Result<Self, JsValue> {
+        let mut options: CollectionOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => CollectionOptions::default(),
+        };
+
+        if let Some(sort_key) = options.sort_key.as_mut() {
+            *sort_key = sort_key.to_uppercase();
+            if !PRODUCT_COLLECTION_SORT_KEYS.contains(&sort_key.as_str()) {
+                return Err(JsValue::from_str(&format!(
+                    "Unknown collection product sort key: {sort_key} (expected one of {})",
+                    PRODUCT_COLLECTION_SORT_KEYS.join(", ")
+                )));
+            }
+        }
+
+        if let Some(available) = options.available_for_sale {
+            let filter = ProductFilter {
+                available: Some(available),
+                ..ProductFilter::default()
+            };
+            if !options.filters.contains(&filter) {
+                options.filters.push(filter);
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Page size of walks over every product in a collection when none is
+/// given; a full page of 250 product cards is over the default cost limit.
+const DEFAULT_WALK_PAGE_SIZE: u32 = 100;
+
+/// Page-size and item caps for walks over every page of a connection.
+#[derive(Debug, Clone, Copy)]
+pub struct PaginationLimits {
+    pub page_size: u32,
+    pub max_items: u32,
+}
+
+impl Default for PaginationLimits {
+    fn default() -> Self {
+        PaginationLimits {
+            page_size: DEFAULT_WALK_PAGE_SIZE,
+            max_items: 5000,
+        }
+    }
+}
+
+impl PaginationLimits {
+    pub(crate) fn from_js(options: Option<JsValue>) -> Result<Self, JsValue> {
+        #[derive(Deserialize, Default)]
+        #[serde(rename_all = "camelCase", default)]
+        struct Raw {
+            page_size: Option<u32>,
+            max_items: Option<u32>,
+        }
+
+        let raw: Raw = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => Raw::default(),
+        };
+        let defaults = PaginationLimits::default();
+        Ok(PaginationLimits {
+            page_size: raw
+                .page_size
+                .unwrap_or(defaults.page_size)
+                .clamp(1, MAX_PAGE_SIZE),
+            max_items: raw.max_items.unwrap_or(defaults.max_items).max(1),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AllProductsResult {
+    pub products: Vec<Product>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionResult {
+    pub collection: Option<Collection>,
+    pub applied_filters: Vec<ProductFilter>,
+    /// Facets available for the collection's products.
+    pub filters: Vec<Filter>,
+}
+
+/// A collection page together with the facets reported for its products.
+pub(crate) struct CollectionPage {
+    pub collection: Collection,
+    pub filters: Vec<Filter>,
+}
+
+/// Summary of a collection for navigation and collection-index pages.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionSummary {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+    pub updated_at: String,
+    /// Number of products seen, capped at the sample size.
+    pub product_count: u32,
+    /// True when the collection holds more products than `product_count`.
+    pub product_count_is_lower_bound: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionList {
+    pub collections: Vec<CollectionSummary>,
+    pub page_info: PageInfo,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCollectionSummary {
+    id: String,
+    handle: String,
+    title: String,
+    description: String,
+    image: Option<Image>,
+    updated_at: String,
+    products: Connection<IgnoredAny>,
+}
+
+impl From<RawCollectionSummary> for CollectionSummary {
+    fn from(raw: RawCollectionSummary) -> Self {
+        CollectionSummary {
+            id: raw.id,
+            handle: raw.handle,
+            title: raw.title,
+            description: raw.description,
+            image: raw.image,
+            updated_at: raw.updated_at,
+            product_count: raw.products.edges.len() as u32,
+            product_count_is_lower_bound: raw
+                .products
+                .page_info
+                .map(|page_info| page_info.has_next_page)
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches a collection and a page of its products. `options.filters` is a
+    /// list of `ProductFilter` inputs (availability, price range, variant
+    /// options, product type, vendor, tags, metafields); the filters actually
+    /// sent are echoed back as `appliedFilters`. `options.sortKey` and
+    /// `options.reverse` control product order, and `options.metafields` adds
+    /// collection metafields alongside the image and SEO fields.
+    /// `options.after` pages forward; `options.before` with `options.last`
+    /// pages backward.
+    #[wasm_bindgen(unchecked_return_type = "CollectionResult")]
+    pub async fn get_collection(
+        &self,
+        handle: String,
+        first: Option<u32>,
+        #[wasm_bindgen(unchecked_optional_param_type = "CollectionOptions")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<JsValue, JsValue> {
+        let options = CollectionOptions::from_js(options)?;
+        let page_args = PageArgs {
+            first,
+            after: options.after.clone(),
+            last: options.last,
+            before: options.before.clone(),
+        }
+        .resolve()?;
+        let page = self
+            .fetch_collection_page(&handle, &page_args, &options)
+            .await?;
+        let (collection, filters) = match page {
+            Some(page) => (Some(page.collection), page.filters),
+            None => (None, Vec::new()),
+        };
+        to_js(&CollectionResult {
+            collection,
+            applied_filters: options.filters,
+            filters,
+        })
+    }
+
+    /// Walks every page of a collection's products by following
+    /// `pageInfo.endCursor`. Accepts the same options as `get_collection` plus
+    /// `pageSize` (default 100, maximum 250) and `maxItems` (default 5000);
+    /// `truncated` is true when `maxItems` stopped the walk early.
+    #[wasm_bindgen(unchecked_return_type = "AllProductsResult")]
+    pub async fn get_all_collection_products(
+        &self,
+        handle: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "CollectionOptions & PaginationLimits")]
+        options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let limits = PaginationLimits::from_js(options.clone())?;
+        let options = CollectionOptions::from_js(options)?;
+
+        let mut products = Vec::new();
+        let mut after: Option<String> = None;
+        let mut truncated = false;
+        loop {
+            let remaining = limits.max_items - products.len() as u32;
+            let first = limits.page_size.min(remaining);
+            let Some(CollectionPage { collection, .. }) = self
+                .fetch_collection_page(&handle, &PageArgs::forward(first, after.take()), &options)
+                .await?
+            else {
+                return Err(JsValue::from_str(&format!(
+                    "Collection not found: {handle}"
+                )));
+            };
+
+            let connection = collection.products.unwrap_or_default();
+            let page_info = connection.page_info.unwrap_or_default();
+            products.extend(connection.edges.into_iter().map(|edge| edge.node));
+
+            if !page_info.has_next_page {
+                break;
+            }
+            if products.len() as u32 >= limits.max_items {
+                truncated = true;
+                break;
+            }
+            match page_info.end_cursor {
+                Some(cursor) => after = Some(cursor),
+                None => break,
+            }
+        }
+
+        to_js(&AllProductsResult {
+            products,
+            truncated,
+        })
+    }
+
+    /// Lists collections a page at a time. `options` is `{ first, after,
+    /// before, last, sortKey, reverse }`, with `sortKey` one of `ID`,
+    /// `RELEVANCE`, `TITLE` (the default) or `UPDATED_AT`; pass the returned
+    /// `pageInfo.endCursor` as `after` to fetch the next page, or
+    /// `pageInfo.startCursor` as `before` (with `last`) for the previous one.
+    #[wasm_bindgen(unchecked_return_type = "CollectionList")]
+    pub async fn list_collections(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "ListOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options = ListOptions::from_js(options)?;
+        let page_args = options.page_args()?;
+        let sort_key = options
+            .sort_key
+            .map(|key| key.to_uppercase())
+            .unwrap_or_else(|| "TITLE".to_string());
+        if !COLLECTION_SORT_KEYS.contains(&sort_key.as_str()) {
+            return Err(JsValue::from_str(&format!(
+                "Unknown collection sort key: {sort_key} (expected one of {})",
+                COLLECTION_SORT_KEYS.join(", ")
+            )));
+        }
+
+        let query = format!(
+            r#"
+            query listCollections(
+                {CONNECTION_VARIABLES}
+                $sortKey: CollectionSortKeys!
+                $reverse: Boolean
+            ) {{
+                collections({CONNECTION_ARGUMENTS}, sortKey: $sortKey, reverse: $reverse) {{
+                    edges {{
+                        node {{
+                            id
+                            handle
+                            title
+                            description
+                            updatedAt
+                            image {{
+                                url
+                                altText
+                                width
+                                height
+                            }}
+                            products(first: {PRODUCT_COUNT_SAMPLE}) {{
+                                edges {{
+                                    node {{
+                                        id
+                                    }}
+                                }}
+                                pageInfo {{
+                                    hasNextPage
+                                    hasPreviousPage
+                                }}
+                            }}
+                        }}
+                    }}
+                    {PAGE_INFO_FIELDS}
+                }}
+            }}
+        "#
+        );
+
+        let mut variables = serde_json::json!({ "sortKey": sort_key, "reverse": options.reverse });
+        page_args.add_to(&mut variables);
+
+        let mut data = self.execute(&query, variables).await?;
+        let connection: Connection<RawCollectionSummary> = from_json(data["collections"].take())?;
+        let page_info = connection.page_info.unwrap_or_default();
+        let collections = connection
+            .edges
+            .into_iter()
+            .map(|edge| edge.node.into())
+            .collect();
+
+        to_js(&CollectionList {
+            collections,
+            page_info,
+        })
+    }
+}
+
+impl StorefrontApi {
+    /// Fetches the page of a collection's products selected by `page`.
+    pub(crate) async fn fetch_collection_page(
+        &self,
+        handle: &str,
+        page: &PageArgs,
+        options: &CollectionOptions,
+    ) -> Result<Option<CollectionPage>, JsValue> {
+        let metafields = if options.metafields.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "metafields(identifiers: {}) {{ id namespace key type value }}",
+                metafield_identifiers_literal(&options.metafields)
+            )
+        };
+        let query = format!(
+            r#"
+            query getCollection(
+                $handle: String!
+                $first: Int
+                $after: String
+                $last: Int
+                $before: String
+                $filters: [ProductFilter!]
+                $sortKey: ProductCollectionSortKeys
+                $reverse: Boolean
+            ) {{
+                collection(handle: $handle) {{
+                    id
+                    title
+                    description
+                    handle
+                    image {{
+                        url
+                        altText
+                        width
+                        height
+                    }}
+                    seo {{
+                        title
+                        description
+                    }}
+                    {metafields}
+                    products(
+                        {CONNECTION_ARGUMENTS}
+                        filters: $filters
+                        sortKey: $sortKey
+                        reverse: $reverse
+                    ) {{
+                        edges {{
+                            node {{
+                                ...ProductCardFields
+                            }}
+                        }}
+                        {PAGE_INFO_FIELDS}
+                        filters {{
+                            {FILTER_FIELDS}
+                        }}
+                    }}
+                }}
+            }}
+            {PRODUCT_CARD_FRAGMENT}
+        "#
+        );
+
+        let mut variables = serde_json::json!({
+            "handle": handle,
+            "filters": options.filters,
+            "sortKey": options.sort_key,
+            "reverse": options.reverse
+        });
+        page.add_to(&mut variables);
+
+        let mut data = self.execute(&query, variables).await?;
+        let mut collection = data["collection"].take();
+        if collection.is_null() {
+            return Ok(None);
+        }
+        let filters: Option<Vec<Filter>> = from_json(collection["products"]["filters"].take())?;
+        Ok(Some(CollectionPage {
+            collection: from_json(collection)?,
+            filters: filters.unwrap_or_default(),
+        }))
+    }
+}
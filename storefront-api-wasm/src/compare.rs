@@ -0,0 +1,217 @@
+//! Side-by-side product comparison tables.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::money::DEFAULT_LOCALE;
+use crate::product::ProductOptions;
+use crate::types::{MetafieldIdentifier, Product, ProductPriceRange};
+use crate::{to_js, StorefrontApi};
+
+/// Where a comparison row's values come from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RowKind {
+    Attribute,
+    Option,
+    Metafield,
+}
+
+/// One row of the table, with a value per compared product (in column order).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonRow {
+    pub kind: RowKind,
+    pub key: String,
+    pub label: String,
+    pub values: Vec<Option<String>>,
+    /// True when the products don't all share the same value, for
+    /// "highlight differences" toggles.
+    pub differs: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonTable {
+    /// The compared products, one per column.
+    pub products: Vec<Product>,
+    pub rows: Vec<ComparisonRow>,
+    /// Requested handles or IDs that matched no product.
+    pub missing: Vec<String>,
+}
+
+impl ComparisonRow {
+    fn new(kind: RowKind, key: &str, label: &str, values: Vec<Option<String>>) -> ComparisonRow {
+        let differs = values.windows(2).any(|pair| pair[0] != pair[1]);
+        ComparisonRow {
+            kind,
+            key: key.to_string(),
+            label: label.to_string(),
+            values,
+            differs,
+        }
+    }
+}
+
+fn price_label(range: &ProductPriceRange) -> String {
+    let min = range.min_variant_price.format(DEFAULT_LOCALE);
+    match &range.max_variant_price {
+        Some(max) if max.value() > range.min_variant_price.value() => {
+            format!("{min} – {}", max.format(DEFAULT_LOCALE))
+        }
+        _ => min,
+    }
+}
+
+impl ComparisonTable {
+    /// Builds rows for price, vendor, product type and availability, then one
+    /// row per option name (in first-seen order) and one per requested
+    /// metafield.
+    pub fn new(
+        products: Vec<Product>,
+        metafields: &[MetafieldIdentifier],
+        missing: Vec<String>,
+    ) -> ComparisonTable {
+        let mut rows = vec![
+            ComparisonRow::new(
+                RowKind::Attribute,
+                "price",
+                "Price",
+                products
+                    .iter()
+                    .map(|product| Some(price_label(&product.price_range)))
+                    .collect(),
+            ),
+            ComparisonRow::new(
+                RowKind::Attribute,
+                "vendor",
+                "Vendor",
+                products
+                    .iter()
+                    .map(|product| product.vendor.clone())
+                    .collect(),
+            ),
+            ComparisonRow::new(
+                RowKind::Attribute,
+                "productType",
+                "Product type",
+                products
+                    .iter()
+                    .map(|product| product.product_type.clone().filter(|t| !t.is_empty()))
+                    .collect(),
+            ),
+            ComparisonRow::new(
+                RowKind::Attribute,
+                "availableForSale",
+                "Available",
+                products
+                    .iter()
+                    .map(|product| {
+                        product
+                            .available_for_sale
+                            .map(|available| if available { "Yes" } else { "No" }.to_string())
+                    })
+                    .collect(),
+            ),
+        ];
+
+        let mut option_names: Vec<&str> = Vec::new();
+        for product in &products {
+            for option in &product.options {
+                if !option_names.contains(&option.name.as_str()) {
+                    option_names.push(&option.name);
+                }
+            }
+        }
+        for name in option_names {
+            let values = products
+                .iter()
+                .map(|product| {
+                    product
+                        .options
+                        .iter()
+                        .find(|option| option.name == name)
+                        .map(|option| {
+                            option
+                                .option_values
+                                .iter()
+                                .map(|value| value.name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        })
+                })
+                .collect();
+            rows.push(ComparisonRow::new(RowKind::Option, name, name, values));
+        }
+
+        for identifier in metafields {
+            let key = format!("{}.{}", identifier.namespace, identifier.key);
+            let values = products
+                .iter()
+                .map(|product| {
+                    product
+                        .metafields
+                        .iter()
+                        .flatten()
+                        .flatten()
+                        .find(|metafield| {
+                            metafield.namespace == identifier.namespace
+                                && metafield.key == identifier.key
+                        })
+                        .map(|metafield| metafield.value.clone())
+                })
+                .collect();
+            rows.push(ComparisonRow::new(
+                RowKind::Metafield,
+                &key,
+                &identifier.key,
+                values,
+            ));
+        }
+
+        ComparisonTable {
+            products,
+            rows,
+            missing,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches products by handle or product GID in one aliased query and
+    /// returns `{ products, rows, missing }` for a comparison table. Accepts
+    /// the same options as `get_product`; `options.metafields` become extra
+    /// rows after price, vendor, type, availability and the option axes.
+    #[wasm_bindgen(unchecked_return_type = "ComparisonTable")]
+    pub async fn compare_products(
+        &self,
+        handles_or_ids: Vec<String>,
+        #[wasm_bindgen(unchecked_optional_param_type = "ProductOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options = ProductOptions::from_js(options)?;
+
+        let mut unique: Vec<String> = Vec::new();
+        for key in handles_or_ids {
+            if !unique.contains(&key) {
+                unique.push(key);
+            }
+        }
+
+        let fetched = self.fetch_products(&unique, &options).await?;
+        let mut products = Vec::new();
+        let mut missing = Vec::new();
+        for (key, product) in unique.into_iter().zip(fetched) {
+            match product {
+                Some(product) => products.push(product),
+                None => missing.push(key),
+            }
+        }
+
+        to_js(&ComparisonTable::new(
+            products,
+            &options.metafields,
+            missing,
+        ))
+    }
+}
@@ -0,0 +1,113 @@
+//! GET transport and conditional requests.
+//!
+//! With `set_transport("get")`, queries are sent as `GET` requests with
+//! the operation in the URL (mutations, and queries whose URL would be too
+//! long, are still posted). Responses to those can carry validators: when
+//! one is cached, its `ETag` and `Last-Modified` go back as `If-None-Match`
+//! and `If-Modified-Since`, and a `304 Not Modified` is answered from the
+//! cache without downloading the body again. Validators are only kept for
+//! responses the cache keeps, so `set_cache` must be on, e.g. with
+//! `{ mode: "revalidate" }` to check every call.
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use web_sys::{Headers, Response};
+
+use crate::graphql::is_mutation;
+use crate::{GraphQLRequest, StorefrontApi};
+
+/// Longest URL a query is sent as; longer ones are posted.
+const MAX_GET_URL_LENGTH: usize = 8 * 1024;
+
+/// How operations reach the API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Transport {
+    #[default]
+    Post,
+    /// Queries as `GET`, mutations as `POST`.
+    Get,
+}
+
+/// A response's cache validators.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Reads `ETag` and `Last-Modified`; `None` when there are neither.
+    pub fn from_response(response: &Response) -> Option<Validators> {
+        let headers = response.headers();
+        let validators = Validators {
+            etag: headers.get("ETag").ok().flatten(),
+            last_modified: headers.get("Last-Modified").ok().flatten(),
+        };
+        (validators.etag.is_some() || validators.last_modified.is_some()).then_some(validators)
+    }
+
+    /// Adds the conditional headers that revalidate against these.
+    pub fn apply(&self, headers: &Headers) -> Result<(), JsValue> {
+        if let Some(etag) = &self.etag {
+            headers.set("If-None-Match", etag)?;
+        }
+        if let Some(last_modified) = &self.last_modified {
+            headers.set("If-Modified-Since", last_modified)?;
+        }
+        Ok(())
+    }
+}
+
+impl StorefrontApi {
+    /// The URL to send `request` to with `GET`, or `None` when it's to be
+    /// posted.
+    pub(crate) fn get_url(&self, endpoint: &str, request: &GraphQLRequest) -> Option<String> {
+        if *self.transport.borrow() != Transport::Get || is_mutation(&request.query) {
+            return None;
+        }
+        let mut url = format!(
+            "{endpoint}?query={}",
+            String::from(js_sys::encode_uri_component(&request.query))
+        );
+        if let Some(variables) = &request.variables {
+            url.push_str("&variables=");
+            url.push_str(&String::from(js_sys::encode_uri_component(
+                &variables.to_string(),
+            )));
+        }
+        (url.len() <= MAX_GET_URL_LENGTH).then_some(url)
+    }
+
+    /// Answers a `304 Not Modified` with the cached data it refers to.
+    pub(crate) fn not_modified(&self, key: &str) -> Result<(u16, Value), JsValue> {
+        let data = self
+            .cache
+            .borrow_mut()
+            .not_modified(key, js_sys::Date::now())
+            .ok_or_else(|| {
+                JsValue::from_str("Not Modified, but the response is no longer cached")
+            })?;
+        Ok((200, serde_json::json!({ "data": data })))
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Chooses how operations are sent: `"post"` (the default) or `"get"`,
+    /// which sends queries as `GET` so cached responses can be revalidated
+    /// with `ETag` / `Last-Modified` and CDNs can cache them. Copies of the
+    /// client share it.
+    #[wasm_bindgen]
+    pub fn set_transport(&self, transport: &str) -> Result<(), JsValue> {
+        *self.transport.borrow_mut() = match transport.to_ascii_lowercase().as_str() {
+            "post" => Transport::Post,
+            "get" => Transport::Get,
+            _ => {
+                return Err(JsValue::from_str(&format!(
+                    "Unknown transport: {transport} (expected \"post\" or \"get\")"
+                )))
+            }
+        };
+        Ok(())
+    }
+}
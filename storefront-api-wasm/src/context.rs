@@ -0,0 +1,204 @@
+//! `@inContext` directive injection for market-aware pricing and translations.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{to_js, StorefrontApi};
+
+/// Buyer country and language applied to every operation through the
+/// `@inContext` directive.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InContext {
+    /// `CountryCode`, e.g. `DE`.
+    pub country: Option<String>,
+    /// `LanguageCode`, e.g. `DE` or `PT_BR`.
+    pub language: Option<String>,
+    /// Presentment currency the country was chosen for by `with_currency`.
+    /// Not part of the directive; the API derives the currency from `country`.
+    pub currency: Option<String>,
+}
+
+/// Normalizes an enum code and rejects anything that isn't a bare GraphQL
+/// enum value, since codes are written into the operation text.
+fn enum_code(kind: &str, code: &str) -> Result<String, JsValue> {
+    let code = code.trim().replace('-', "_").to_uppercase();
+    let valid = (2..=5).contains(&code.len())
+        && code.starts_with(|c: char| c.is_ascii_uppercase())
+        && code.chars().all(|c| c.is_ascii_uppercase() || c == '_');
+    if !valid {
+        return Err(JsValue::from_str(&format!("Invalid {kind} code: {code}")));
+    }
+    Ok(code)
+}
+
+impl InContext {
+    pub fn new(country: Option<String>, language: Option<String>) -> Result<InContext, JsValue> {
+        Ok(InContext {
+            country: country
+                .filter(|code| !code.trim().is_empty())
+                .map(|code| enum_code("country", &code))
+                .transpose()?,
+            language: language
+                .filter(|code| !code.trim().is_empty())
+                .map(|code| enum_code("language", &code))
+                .transpose()?,
+            currency: None,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.country.is_none() && self.language.is_none()
+    }
+
+    /// Renders `@inContext(country: DE, language: DE)`, or an empty string
+    /// when neither is set.
+    pub fn directive(&self) -> String {
+        let mut arguments = Vec::new();
+        if let Some(country) = &self.country {
+            arguments.push(format!("country: {country}"));
+        }
+        if let Some(language) = &self.language {
+            arguments.push(format!("language: {language}"));
+        }
+        if arguments.is_empty() {
+            String::new()
+        } else {
+            format!("@inContext({})", arguments.join(", "))
+        }
+    }
+
+    /// Adds the directive to the first operation in `query`, after its name
+    /// and variable definitions. Operations that already carry `@inContext`
+    /// are left alone.
+    pub fn apply(&self, query: &str) -> String {
+        if self.is_empty() || query.contains("@inContext") {
+            return query.to_string();
+        }
+
+        let trimmed = query.trim_start();
+        let offset = query.len() - trimmed.len();
+        let Some(keyword) = ["query", "mutation"]
+            .into_iter()
+            .find(|keyword| trimmed.starts_with(keyword))
+        else {
+            // The `{ ... }` shorthand can't carry directives; spell out the
+            // anonymous query.
+            if trimmed.starts_with('{') {
+                return format!("query {} {trimmed}", self.directive());
+            }
+            return query.to_string();
+        };
+
+        // Skip the operation name, then the balanced variable definitions.
+        let mut position = offset + keyword.len();
+        let bytes = query.as_bytes();
+        while position < bytes.len() && bytes[position] != b'(' && bytes[position] != b'{' {
+            position += 1;
+        }
+        if position < bytes.len() && bytes[position] == b'(' {
+            let mut depth = 0;
+            while position < bytes.len() {
+                match bytes[position] {
+                    b'(' => depth += 1,
+                    b')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            position += 1;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                position += 1;
+            }
+        }
+
+        format!(
+            "{} {} {}",
+            &query[..position],
+            self.directive(),
+            &query[position..]
+        )
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Sets the buyer country (`CountryCode`, e.g. `"DE"`) used for prices and
+    /// availability on every later call. Pass `undefined` to clear it.
+    /// Copies of the client share it.
+    #[wasm_bindgen]
+    pub fn set_country(&self, country: Option<String>) -> Result<(), JsValue> {
+        let language = self.context.borrow().language.clone();
+        *self.context.borrow_mut() = InContext::new(country, language)?;
+        Ok(())
+    }
+
+    /// Sets the content language (`LanguageCode`, e.g. `"FR"` or `"PT_BR"`)
+    /// for translated content on every later call. Copies of the client
+    /// share it.
+    #[wasm_bindgen]
+    pub fn set_language(&self, language: Option<String>) -> Result<(), JsValue> {
+        let country = self.context.borrow().country.clone();
+        *self.context.borrow_mut() = InContext::new(country, language)?;
+        Ok(())
+    }
+
+    /// Returns a copy of the client with a different country and/or language,
+    /// for a single call: `api.with_context("CA", "FR").get_product(handle)`.
+    /// Omitted values keep the client's current setting.
+    #[wasm_bindgen]
+    pub fn with_context(
+        &self,
+        country: Option<String>,
+        language: Option<String>,
+    ) -> Result<StorefrontApi, JsValue> {
+        let current = self.context.borrow();
+        let context = InContext::new(
+            country.or_else(|| current.country.clone()),
+            language.or_else(|| current.language.clone()),
+        )?;
+        let mut client = self.clone();
+        client.context = Rc::new(RefCell::new(context));
+        Ok(client)
+    }
+
+    /// Returns a copy of the client that presents prices in `currency_code`
+    /// (e.g. `"EUR"`), by switching the `@inContext` country to one whose
+    /// market sells in that currency. The current country is kept when it
+    /// already matches. Fails when no available country uses the currency.
+    #[wasm_bindgen]
+    pub async fn with_currency(&self, currency_code: String) -> Result<StorefrontApi, JsValue> {
+        let currency_code = currency_code.trim().to_uppercase();
+        let localization = self.fetch_localization().await?;
+
+        let country = if localization.country.currency.iso_code == currency_code {
+            localization.country.iso_code
+        } else {
+            localization
+                .available_countries
+                .into_iter()
+                .find(|country| country.currency.iso_code == currency_code)
+                .map(|country| country.iso_code)
+                .ok_or_else(|| {
+                    JsValue::from_str(&format!(
+                        "No available country presents prices in {currency_code}"
+                    ))
+                })?
+        };
+
+        let client = self.with_context(Some(country), None)?;
+        client.context.borrow_mut().currency = Some(currency_code);
+        Ok(client)
+    }
+
+    /// The current `@inContext` settings as `{ country, language, currency }`.
+    #[wasm_bindgen(unchecked_return_type = "InContext")]
+    pub fn context(&self) -> Result<JsValue, JsValue> {
+        to_js(&*self.context.borrow())
+    }
+}
@@ -0,0 +1,635 @@
+//! Query cost estimation after Shopify's published cost model.
+//!
+//! Scalars and enums are free, objects cost 1, and a connection costs 2
+//! plus, for every requested node (`first` / `last`), 1 for the node and
+//! whatever is selected on it. Mutations add 10. The estimate is an upper
+//! bound in the same units Shopify's throttles use.
+//!
+//! The client prices every request before sending it and checks it
+//! against `set_cost_limits`: the most one query may cost, and a leaky
+//! bucket of points that refills over time. Over either limit, a request
+//! is sent with a warning (the default) or refused; `estimate_cost` reports
+//! the same numbers without sending anything.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::error::StorefrontError;
+use crate::graphql::{operation_starts, tokenize, Token};
+use crate::logging::{operation_name, LogLevel};
+use crate::{from_js, to_js, StorefrontApi};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(message: &str);
+}
+
+/// Base cost of a mutation.
+pub const MUTATION_COST: u64 = 10;
+
+/// Page size assumed when `first` / `last` comes from a variable that
+/// isn't set.
+const UNKNOWN_PAGE_SIZE: u64 = 10;
+
+/// How deep fragment spreads are followed, to stop cycles.
+const MAX_FRAGMENT_DEPTH: usize = 16;
+
+/// What the client does with a request whose estimate is over a limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CostMode {
+    /// Don't check.
+    Off,
+    /// Send it, logging a warning.
+    #[default]
+    Warn,
+    /// Fail without sending it.
+    Refuse,
+}
+
+/// Limits every request's estimate is checked against, after Shopify's
+/// published GraphQL limits. `0` turns a limit off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CostLimits {
+    pub mode: CostMode,
+    /// Most a single query may cost.
+    pub max_query_cost: u64,
+    /// Size of the bucket requests draw their cost from.
+    pub bucket_size: u64,
+    /// Points the bucket regains per second.
+    pub restore_rate: u64,
+}
+
+impl Default for CostLimits {
+    fn default() -> Self {
+        CostLimits {
+            mode: CostMode::Warn,
+            max_query_cost: 1000,
+            bucket_size: 1000,
+            restore_rate: 50,
+        }
+    }
+}
+
+/// A connection in a query and what it costs, nested selections included.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionCost {
+    /// Fields leading to it, e.g. `product.variants`.
+    pub path: String,
+    pub page_size: u64,
+    pub cost: u64,
+}
+
+/// A query's estimated cost and its connections, costliest first.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCost {
+    pub cost: u64,
+    pub connections: Vec<ConnectionCost>,
+}
+
+/// A query's estimate checked against the client's limits.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    pub cost: u64,
+    pub max_query_cost: u64,
+    /// Points in the bucket now, before this query; `None` without a
+    /// bucket.
+    pub bucket_available: Option<u64>,
+    pub exceeds_query_limit: bool,
+    pub exceeds_bucket: bool,
+    /// Connections in the query, costliest first.
+    pub connections: Vec<ConnectionCost>,
+}
+
+struct Estimator<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    /// Fragment name to the token index of its selection set.
+    fragments: HashMap<String, usize>,
+    variables: &'a Value,
+    fragment_depth: usize,
+    /// Fields enclosing the current selection, for `connections`.
+    path: Vec<String>,
+    connections: Vec<ConnectionCost>,
+}
+
+impl Estimator<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, punctuator: char) -> bool {
+        if self.peek() == Some(&Token::Punctuator(punctuator)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn name(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Name(name)) => {
+                let name = name.clone();
+                self.position += 1;
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// Skips a balanced `(...)`, `[...]` or `{...}` group, or one token.
+    fn skip_group(&mut self) {
+        let mut depth = 0usize;
+        while let Some(token) = self.next() {
+            match token {
+                Token::Punctuator('(' | '[' | '{') => depth += 1,
+                Token::Punctuator(')' | ']' | '}') => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+
+    fn skip_directives(&mut self) {
+        while self.eat('@') {
+            self.name();
+            if self.peek() == Some(&Token::Punctuator('(')) {
+                self.skip_group();
+            }
+        }
+    }
+
+    /// Reads a field's arguments, returning its page size when it has
+    /// `first` or `last`.
+    fn arguments(&mut self) -> Option<u64> {
+        if !self.eat('(') {
+            return None;
+        }
+        let mut page_size = None;
+        while !self.eat(')') {
+            let Some(argument) = self.name() else {
+                // Malformed; let the API report it.
+                self.position = self.tokens.len();
+                return page_size;
+            };
+            self.eat(':');
+            let sized = matches!(argument.as_str(), "first" | "last");
+            match self.peek().cloned() {
+                Some(Token::Number(number)) if sized => {
+                    self.position += 1;
+                    page_size = number.parse().ok();
+                }
+                Some(Token::Punctuator('$')) if sized => {
+                    self.position += 1;
+                    let variable = self.name().unwrap_or_default();
+                    page_size = Some(
+                        self.variables
+                            .get(&variable)
+                            .and_then(Value::as_u64)
+                            .unwrap_or(UNKNOWN_PAGE_SIZE),
+                    );
+                }
+                Some(Token::Punctuator('$')) => {
+                    self.position += 1;
+                    self.name();
+                }
+                _ => self.skip_group(),
+            }
+        }
+        page_size
+    }
+
+    /// Cost of the selection set starting at the current `{`. Inside a
+    /// connection, `edges`, `node` and `nodes` only pass their selections
+    /// through and `pageInfo` is free, since the connection already paid
+    /// for them.
+    fn selection_set(&mut self, in_connection: bool) -> u64 {
+        if !self.eat('{') {
+            return 0;
+        }
+        let mut cost = 0u64;
+        while !self.eat('}') {
+            match self.peek().cloned() {
+                None => break,
+                Some(Token::Punctuator('.')) => {
+                    self.position += 1;
+                    cost = cost.saturating_add(self.spread(in_connection));
+                }
+                Some(Token::Name(_)) => cost = cost.saturating_add(self.field(in_connection)),
+                Some(_) => self.position += 1,
+            }
+        }
+        cost
+    }
+
+    fn spread(&mut self, in_connection: bool) -> u64 {
+        match self.peek().cloned() {
+            Some(Token::Name(name)) if name == "on" => {
+                self.position += 1;
+                self.name();
+                self.skip_directives();
+                self.selection_set(in_connection)
+            }
+            Some(Token::Name(name)) => {
+                self.position += 1;
+                self.skip_directives();
+                let Some(start) = self.fragments.get(&name).copied() else {
+                    return 0;
+                };
+                if self.fragment_depth >= MAX_FRAGMENT_DEPTH {
+                    return 0;
+                }
+                let resume = self.position;
+                self.position = start;
+                self.fragment_depth += 1;
+                let cost = self.selection_set(in_connection);
+                self.fragment_depth -= 1;
+                self.position = resume;
+                cost
+            }
+            _ => {
+                self.skip_directives();
+                self.selection_set(in_connection)
+            }
+        }
+    }
+
+    fn field(&mut self, in_connection: bool) -> u64 {
+        let mut name = self.name().unwrap_or_default();
+        if self.eat(':') {
+            name = self.name().unwrap_or_default();
+        }
+        let page_size = self.arguments();
+        self.skip_directives();
+        if self.peek() != Some(&Token::Punctuator('{')) {
+            return 0;
+        }
+        if let Some(page_size) = page_size {
+            self.path.push(name);
+            // Page sizes come from the document and its variables, so a
+            // hostile `first:` must not overflow the estimate.
+            let cost = page_size
+                .saturating_mul(self.selection_set(true).saturating_add(1))
+                .saturating_add(2);
+            let path = self.path.join(".");
+            self.path.pop();
+            self.connections.push(ConnectionCost {
+                path,
+                page_size,
+                cost,
+            });
+            return cost;
+        }
+        match name.as_str() {
+            "pageInfo" if in_connection => {
+                self.selection_set(false);
+                0
+            }
+            "edges" if in_connection => self.selection_set(true),
+            "node" | "nodes" if in_connection => self.selection_set(false),
+            _ => {
+                self.path.push(name);
+                let cost = self.selection_set(false).saturating_add(1);
+                self.path.pop();
+                cost
+            }
+        }
+    }
+}
+
+/// Estimates the cost of the operation in `document`, resolving
+/// `first: $variable` page sizes from `variables`. Documents with several
+/// operations are estimated by their first one.
+pub fn estimate_query_cost(document: &str, variables: &Value) -> u64 {
+    query_cost(document, variables).cost
+}
+
+/// `estimate_query_cost`, with the connections the cost comes from.
+pub fn query_cost(document: &str, variables: &Value) -> QueryCost {
+    let Ok(tokens) = tokenize(document) else {
+        return QueryCost {
+            cost: 0,
+            connections: Vec::new(),
+        };
+    };
+
+    let mut fragments = HashMap::new();
+    for (index, window) in tokens.windows(2).enumerate() {
+        if let [Token::Name(keyword), Token::Name(name)] = window {
+            if keyword == "fragment" && name != "on" {
+                if let Some(offset) = tokens[index..]
+                    .iter()
+                    .position(|token| *token == Token::Punctuator('{'))
+                {
+                    fragments.insert(name.clone(), index + offset);
+                }
+            }
+        }
+    }
+
+    let mut estimator = Estimator {
+        tokens,
+        position: 0,
+        fragments,
+        variables,
+        fragment_depth: 0,
+        path: Vec::new(),
+        connections: Vec::new(),
+    };
+    // Without an operation keyword the document is a `{ ... }` shorthand
+    // query.
+    let mut base = 0;
+    if let Some(&start) = operation_starts(&estimator.tokens).first() {
+        if estimator.tokens[start] == Token::Name("mutation".to_string()) {
+            base = MUTATION_COST;
+        }
+        estimator.position = start + 1;
+        estimator.name();
+        if estimator.peek() == Some(&Token::Punctuator('(')) {
+            estimator.skip_group();
+        }
+        estimator.skip_directives();
+    }
+    let cost = estimator.selection_set(false).saturating_add(base);
+    let mut connections = estimator.connections;
+    connections.sort_by_key(|connection| std::cmp::Reverse(connection.cost));
+    QueryCost { cost, connections }
+}
+
+/// The client's limits and the points left in its bucket.
+#[derive(Debug, Clone, Default)]
+pub struct CostLimiter {
+    limits: CostLimits,
+    /// Points available and when that was measured; `None` is a full
+    /// bucket.
+    bucket: Option<(f64, f64)>,
+}
+
+impl CostLimiter {
+    pub fn limits(&self) -> &CostLimits {
+        &self.limits
+    }
+
+    /// How many items estimated at `per_item` points fit in one query next
+    /// to `overhead` points under the query limit: between 1 and `max`,
+    /// and `max` when the limit is off.
+    pub fn items_per_query(&self, overhead: u64, per_item: u64, max: usize) -> usize {
+        let limit = self.limits.max_query_cost;
+        if self.limits.mode == CostMode::Off || limit == 0 || per_item == 0 {
+            return max;
+        }
+        let fit = limit.saturating_sub(overhead) / per_item;
+        usize::try_from(fit).unwrap_or(max).clamp(1, max)
+    }
+
+    /// Replaces the limits, refilling the bucket.
+    pub fn set_limits(&mut self, limits: CostLimits) {
+        self.limits = limits;
+        self.bucket = None;
+    }
+
+    /// Points in the bucket at `now` (ms), or `None` without a bucket.
+    fn available(&self, now: f64) -> Option<f64> {
+        let size = self.limits.bucket_size as f64;
+        if size == 0.0 {
+            return None;
+        }
+        Some(match self.bucket {
+            None => size,
+            Some((points, at)) => {
+                let restored = (now - at).max(0.0) / 1000.0 * self.limits.restore_rate as f64;
+                (points + restored).min(size)
+            }
+        })
+    }
+
+    pub fn estimate(&self, document: &str, variables: &Value, now: f64) -> CostEstimate {
+        let QueryCost { cost, connections } = query_cost(document, variables);
+        let available = self.available(now);
+        let max_query_cost = self.limits.max_query_cost;
+        CostEstimate {
+            cost,
+            max_query_cost,
+            bucket_available: available.map(|points| points as u64),
+            exceeds_query_limit: max_query_cost > 0 && cost > max_query_cost,
+            // A query bigger than the whole bucket waits for a full one.
+            exceeds_bucket: available
+                .is_some_and(|points| (cost as f64).min(self.limits.bucket_size as f64) > points),
+            connections,
+        }
+    }
+
+    /// Draws `cost` from the bucket; it never goes below empty.
+    fn charge(&mut self, cost: u64, now: f64) {
+        if let Some(available) = self.available(now) {
+            self.bucket = Some(((available - cost as f64).max(0.0), now));
+        }
+    }
+
+    /// Seconds until the bucket holds `cost` points.
+    fn wait_seconds(&self, cost: u64, now: f64) -> f64 {
+        let (Some(available), rate) = (self.available(now), self.limits.restore_rate) else {
+            return 0.0;
+        };
+        if rate == 0 {
+            return f64::INFINITY;
+        }
+        let needed = (cost as f64).min(self.limits.bucket_size as f64);
+        ((needed - available) / rate as f64).max(0.0)
+    }
+}
+
+/// What an estimate is over, naming its costliest connection.
+fn over_limit_message(operation: &str, estimate: &CostEstimate) -> String {
+    let limit = if estimate.exceeds_query_limit {
+        format!(
+            "over the {} point limit for one query",
+            estimate.max_query_cost
+        )
+    } else {
+        format!(
+            "more than the {} points left in the cost bucket",
+            estimate.bucket_available.unwrap_or_default()
+        )
+    };
+    let mut message = format!(
+        "{operation} is estimated to cost {} points, {limit}",
+        estimate.cost
+    );
+    if let Some(connection) = estimate.connections.first() {
+        message.push_str(&format!(
+            "; {} (first: {}) costs {}",
+            connection.path, connection.page_size, connection.cost
+        ));
+    }
+    message
+}
+
+impl StorefrontApi {
+    /// Prices `query` against the cost limits before it's sent: over one,
+    /// warns or refuses as `CostLimits.mode` says. Sent requests are drawn
+    /// from the bucket.
+    pub(crate) fn check_cost(&self, query: &str, variables: &Value) -> Result<(), JsValue> {
+        let mut limiter = self.cost.borrow_mut();
+        let mode = limiter.limits.mode;
+        if mode == CostMode::Off {
+            return Ok(());
+        }
+        let now = js_sys::Date::now();
+        let estimate = limiter.estimate(query, variables, now);
+        if estimate.exceeds_query_limit || estimate.exceeds_bucket {
+            let operation = operation_name(query).unwrap_or("anonymous");
+            let message = over_limit_message(operation, &estimate);
+            if mode == CostMode::Refuse {
+                return Err(if estimate.exceeds_query_limit {
+                    StorefrontError::max_cost_exceeded(message)
+                } else {
+                    let wait = limiter.wait_seconds(estimate.cost, now).ceil();
+                    StorefrontError::throttled(format!("{message}; try again in {wait}s"))
+                }
+                .into());
+            }
+            match &self.log_sink {
+                Some(_) => self.log_event(
+                    LogLevel::Warning,
+                    "cost",
+                    serde_json::json!({
+                        "operation": operation,
+                        "cost": estimate.cost,
+                        "message": message,
+                    }),
+                ),
+                None => console_warn(&message),
+            }
+        }
+        limiter.charge(estimate.cost, now);
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Estimates what `query` would cost with `variables`, without sending
+    /// it: `{ cost, maxQueryCost, bucketAvailable, exceedsQueryLimit,
+    /// exceedsBucket, connections }`, where `connections` lists each
+    /// paginated field as `{ path, pageSize, cost }`, costliest first.
+    #[wasm_bindgen(unchecked_return_type = "CostEstimate")]
+    pub fn estimate_cost(
+        &self,
+        query: &str,
+        #[wasm_bindgen(unchecked_optional_param_type = "Variables")] variables: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let variables: Value = match variables {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => Value::Null,
+        };
+        to_js(
+            &self
+                .cost
+                .borrow()
+                .estimate(query, &variables, js_sys::Date::now()),
+        )
+    }
+
+    /// Sets the limits requests are checked against before they're sent.
+    /// `options` is `{ mode, maxQueryCost, bucketSize, restoreRate }`, with
+    /// `mode` `"warn"` (the default: send, logging a warning), `"refuse"`
+    /// (throw without sending) or `"off"`. Omitted fields take their
+    /// defaults (1000 points per query, a 1000 point bucket regaining 50 a
+    /// second) and `0` turns a limit off. Copies of the client share its
+    /// bucket.
+    #[wasm_bindgen]
+    pub fn set_cost_limits(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "Partial<CostLimits>")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        let limits: CostLimits = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => CostLimits::default(),
+        };
+        self.cost.borrow_mut().set_limits(limits);
+        Ok(())
+    }
+
+    /// The current cost limits.
+    #[wasm_bindgen(unchecked_return_type = "CostLimits")]
+    pub fn cost_limits(&self) -> Result<JsValue, JsValue> {
+        to_js(self.cost.borrow().limits())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn connections_multiply_their_selections() {
+        let document =
+            "{ products(first: 10) { edges { node { id variants(first: 5) { nodes { id } } } } } }";
+
+        let cost = query_cost(document, &json!({}));
+
+        assert_eq!(cost.cost, 2 + 10 * (1 + 2 + 5));
+        assert_eq!(cost.connections[0].path, "products");
+        assert_eq!(cost.connections[1].path, "products.variants");
+    }
+
+    #[test]
+    fn page_sizes_come_from_variables() {
+        let document = "query q($n: Int) { products(first: $n) { nodes { id } } }";
+
+        assert_eq!(estimate_query_cost(document, &json!({ "n": 3 })), 2 + 3);
+    }
+
+    #[test]
+    fn huge_page_sizes_saturate_instead_of_overflowing() {
+        let document = "query q($n: Int) { a: products(first: $n) { nodes { id } } \
+                        b: products(first: $n) { nodes { id } } }";
+        let variables = json!({ "n": u64::MAX });
+
+        assert_eq!(estimate_query_cost(document, &variables), u64::MAX);
+        let nested = "query q($n: Int) { products(first: $n) { nodes { \
+                      variants(first: $n) { nodes { id } } } } }";
+        assert_eq!(estimate_query_cost(nested, &variables), u64::MAX);
+        let mutation = "mutation m($n: Int) { products(first: $n) { nodes { id } } }";
+        assert_eq!(estimate_query_cost(mutation, &variables), u64::MAX);
+    }
+
+    #[test]
+    fn items_per_query_fill_the_query_limit() {
+        let limiter = CostLimiter::default();
+
+        assert_eq!(limiter.items_per_query(0, 300, 20), 3);
+        assert_eq!(limiter.items_per_query(400, 300, 20), 2);
+        assert_eq!(limiter.items_per_query(0, 5000, 20), 1);
+        assert_eq!(limiter.items_per_query(0, 10, 20), 20);
+
+        let mut unlimited = CostLimiter::default();
+        unlimited.set_limits(CostLimits {
+            max_query_cost: 0,
+            ..CostLimits::default()
+        });
+        assert_eq!(unlimited.items_per_query(0, 5000, 20), 20);
+    }
+}
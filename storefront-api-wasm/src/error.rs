@@ -0,0 +1,318 @@
+//! Structured errors for Storefront API failures.
+//!
+//! Every failure of a request, from a dropped connection to a mutation's
+//! `userErrors`, is thrown as a JavaScript `Error` carrying `kind` (where
+//! it came from), `category` (what went wrong: `network`, `timeout`,
+//! `cancelled`, `throttled`, `auth`, `validation`, `userError` or
+//! `server`), a machine-readable `code`, `retryable` and `errors`
+//! (`{ message, code, field }`) next to the usual `message`, so callers
+//! can react without parsing the text. The retry policy (see `retry`)
+//! only retries errors marked `retryable`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+
+use crate::GraphQLError;
+
+/// Where a failure came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    /// The response's top-level `errors`.
+    Graphql,
+    /// A mutation's `userErrors`, i.e. input the shop rejected.
+    UserError,
+    /// The API asked us to slow down (HTTP 429 or a `THROTTLED` error).
+    Throttled,
+    /// The query's estimated cost is over the single-query limit, so it was
+    /// refused before being sent; see `set_cost_limits`.
+    MaxCostExceeded,
+    /// No response arrived: `fetch` failed, timed out or was cancelled.
+    Network,
+    /// The API answered with an error status.
+    Http,
+    /// The response couldn't be used: not JSON, or over the size limit.
+    Response,
+}
+
+/// What went wrong, whatever the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorCategory {
+    /// The request didn't reach the API or no response came back.
+    Network,
+    /// The request or its response took too long.
+    Timeout,
+    /// The caller aborted the request; see `with_signal`.
+    Cancelled,
+    /// Over a rate or cost limit; worth retrying after a pause.
+    Throttled,
+    /// The access token was rejected, or the shop can't be reached with it.
+    Auth,
+    /// The request itself is wrong: a bad query, variables or version, or
+    /// one that asks for too much.
+    Validation,
+    /// The shop rejected a mutation's input.
+    UserError,
+    /// The API failed, or sent something that isn't a GraphQL response.
+    Server,
+}
+
+/// One error behind a failure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorDetail {
+    pub message: String,
+    /// `extensions.code` for GraphQL errors, `code` for user errors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Path to the offending input (user errors) or response field
+    /// (GraphQL errors).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorefrontError {
+    pub kind: ErrorKind,
+    pub category: ErrorCategory,
+    /// Machine-readable code, e.g. `THROTTLED` or `NETWORK_ERROR`.
+    pub code: String,
+    pub message: String,
+    /// Whether the same request can succeed if tried again later.
+    pub retryable: bool,
+    pub errors: Vec<ErrorDetail>,
+}
+
+/// GraphQL error codes that mean the request itself was fine.
+const RETRYABLE_CODES: &[&str] = &["THROTTLED", "INTERNAL_SERVER_ERROR"];
+
+/// GraphQL error codes for a token or shop that can't be used.
+const AUTH_CODES: &[&str] = &[
+    "ACCESS_DENIED",
+    "UNAUTHORIZED",
+    "FORBIDDEN",
+    "SHOP_INACTIVE",
+];
+
+impl StorefrontError {
+    /// A failure with a single error behind it.
+    fn single(
+        kind: ErrorKind,
+        category: ErrorCategory,
+        code: &str,
+        message: impl Into<String>,
+        retryable: bool,
+    ) -> StorefrontError {
+        let message = message.into();
+        StorefrontError {
+            kind,
+            category,
+            code: code.to_string(),
+            retryable,
+            errors: vec![ErrorDetail {
+                message: message.clone(),
+                code: Some(code.to_string()),
+                field: None,
+            }],
+            message,
+        }
+    }
+
+    pub fn graphql(errors: &[GraphQLError]) -> StorefrontError {
+        let details: Vec<ErrorDetail> = errors
+            .iter()
+            .map(|error| ErrorDetail {
+                message: error.message.clone(),
+                code: error
+                    .extensions
+                    .as_ref()
+                    .and_then(|extensions| extensions["code"].as_str())
+                    .map(str::to_string),
+                field: error.path.as_ref().map(|path| {
+                    path.iter()
+                        .map(|segment| match segment {
+                            Value::String(name) => name.clone(),
+                            other => other.to_string(),
+                        })
+                        .collect()
+                }),
+            })
+            .collect();
+        let has_code = |code: &str| details.iter().any(|e| e.code.as_deref() == Some(code));
+        let retryable = details.iter().all(|detail| {
+            detail
+                .code
+                .as_deref()
+                .is_some_and(|code| RETRYABLE_CODES.contains(&code))
+        });
+        let category = if has_code("THROTTLED") {
+            ErrorCategory::Throttled
+        } else if AUTH_CODES.iter().any(|code| has_code(code)) {
+            ErrorCategory::Auth
+        } else if has_code("INTERNAL_SERVER_ERROR") {
+            ErrorCategory::Server
+        } else {
+            ErrorCategory::Validation
+        };
+        StorefrontError {
+            kind: if category == ErrorCategory::Throttled {
+                ErrorKind::Throttled
+            } else {
+                ErrorKind::Graphql
+            },
+            category,
+            code: details
+                .iter()
+                .find_map(|detail| detail.code.clone())
+                .unwrap_or_else(|| "GRAPHQL_ERROR".to_string()),
+            message: details
+                .iter()
+                .map(|detail| detail.message.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            retryable: !details.is_empty() && retryable,
+            errors: details,
+        }
+    }
+
+    pub fn user_errors(errors: Vec<ErrorDetail>) -> StorefrontError {
+        StorefrontError {
+            kind: ErrorKind::UserError,
+            category: ErrorCategory::UserError,
+            code: errors
+                .iter()
+                .find_map(|error| error.code.clone())
+                .unwrap_or_else(|| "USER_ERROR".to_string()),
+            message: errors
+                .iter()
+                .map(|error| error.message.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            retryable: false,
+            errors,
+        }
+    }
+
+    pub fn throttled(message: impl Into<String>) -> StorefrontError {
+        StorefrontError::single(
+            ErrorKind::Throttled,
+            ErrorCategory::Throttled,
+            "THROTTLED",
+            message,
+            true,
+        )
+    }
+
+    pub fn max_cost_exceeded(message: impl Into<String>) -> StorefrontError {
+        StorefrontError::single(
+            ErrorKind::MaxCostExceeded,
+            ErrorCategory::Validation,
+            "MAX_COST_EXCEEDED",
+            message,
+            false,
+        )
+    }
+
+    /// `fetch` failed, e.g. offline or blocked by CORS.
+    pub fn network(message: impl Into<String>) -> StorefrontError {
+        StorefrontError::single(
+            ErrorKind::Network,
+            ErrorCategory::Network,
+            "NETWORK_ERROR",
+            message,
+            true,
+        )
+    }
+
+    /// The request was aborted by a timeout signal.
+    pub fn timeout(message: impl Into<String>) -> StorefrontError {
+        StorefrontError::single(
+            ErrorKind::Network,
+            ErrorCategory::Timeout,
+            "TIMEOUT",
+            message,
+            true,
+        )
+    }
+
+    pub fn cancelled() -> StorefrontError {
+        StorefrontError::single(
+            ErrorKind::Network,
+            ErrorCategory::Cancelled,
+            "CANCELLED",
+            "Request cancelled",
+            false,
+        )
+    }
+
+    /// An error status other than 429, which is `throttled`.
+    pub fn http(status: u16) -> StorefrontError {
+        let (category, code, retryable) = match status {
+            401 => (ErrorCategory::Auth, "UNAUTHORIZED", false),
+            // Shopify answers 402 for a frozen shop and 423 for a locked one.
+            402 => (ErrorCategory::Auth, "PAYMENT_REQUIRED", false),
+            403 => (ErrorCategory::Auth, "FORBIDDEN", false),
+            423 => (ErrorCategory::Auth, "LOCKED", false),
+            404 => (ErrorCategory::Validation, "NOT_FOUND", false),
+            408 => (ErrorCategory::Timeout, "REQUEST_TIMEOUT", true),
+            500..=599 => (
+                ErrorCategory::Server,
+                "SERVER_ERROR",
+                matches!(status, 500 | 502 | 503 | 504),
+            ),
+            _ => (ErrorCategory::Validation, "BAD_REQUEST", false),
+        };
+        StorefrontError::single(
+            ErrorKind::Http,
+            category,
+            code,
+            format!("The Storefront API responded with HTTP {status}"),
+            retryable,
+        )
+    }
+
+    /// The body isn't a GraphQL response, e.g. an error page from a proxy.
+    pub fn invalid_response(message: impl Into<String>) -> StorefrontError {
+        StorefrontError::single(
+            ErrorKind::Response,
+            ErrorCategory::Server,
+            "INVALID_RESPONSE",
+            message,
+            true,
+        )
+    }
+
+    /// The body is over the size limit; see `set_response_limits`.
+    pub fn response_too_large(message: impl Into<String>) -> StorefrontError {
+        StorefrontError::single(
+            ErrorKind::Response,
+            ErrorCategory::Validation,
+            "RESPONSE_TOO_LARGE",
+            message,
+            false,
+        )
+    }
+
+    /// Reads the details back from an error thrown by the client. `None`
+    /// for errors that didn't come from a request, such as invalid
+    /// arguments.
+    pub fn from_js(error: &JsValue) -> Option<StorefrontError> {
+        if !error.is_object() {
+            return None;
+        }
+        serde_wasm_bindgen::from_value(error.clone()).ok()
+    }
+}
+
+impl From<StorefrontError> for JsValue {
+    fn from(error: StorefrontError) -> JsValue {
+        let js_error = js_sys::Error::new(&error.message);
+        if let Ok(details) = crate::to_js(&error) {
+            js_sys::Object::assign(&js_error, &details.into());
+        }
+        js_error.into()
+    }
+}
@@ -0,0 +1,206 @@
+//! Running several independent operations at once.
+//!
+//! `query_all(operations, options)` sends every operation, at most
+//! `concurrency` at a time, and waits for all of them: one failing doesn't
+//! stop the others or reject the call. Each gets a result in input order,
+//! and the failures are summarized by category, so a page can render what
+//! loaded and decide about the rest in one place:
+//!
+//! ```js
+//! const { results, errors } = await api.query_all([
+//!   { name: "hero", query: HERO_QUERY },
+//!   { name: "menu", query: MENU_QUERY, variables: { handle: "main-menu" } },
+//! ]);
+//! if (errors.count > 0) console.warn(errors.byCategory);
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+
+use crate::error::StorefrontError;
+use crate::report::error_message;
+use crate::{from_js, to_js, StorefrontApi};
+
+/// One operation to run.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    /// Identifies the result; defaults to the operation's name.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub query: String,
+    #[serde(default)]
+    pub variables: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QueryAllOptions {
+    /// Most operations in flight at once.
+    pub concurrency: usize,
+}
+
+impl Default for QueryAllOptions {
+    fn default() -> Self {
+        // Browsers open about six connections per host anyway.
+        QueryAllOptions { concurrency: 6 }
+    }
+}
+
+/// How one operation ended.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationResult {
+    pub name: String,
+    pub ok: bool,
+    /// The operation's `data` when it succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    /// `{ kind, category, code, message, retryable, errors }` when it
+    /// failed (only `message` for errors that weren't classified).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+/// The failures among the results.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorSummary {
+    pub count: usize,
+    /// Failures per category, e.g. `{ network: 2 }`.
+    pub by_category: BTreeMap<String, usize>,
+    /// Names of the operations that failed.
+    pub failed: Vec<String>,
+    /// Whether every failure is worth trying again.
+    pub retryable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryAllResult {
+    /// Whether every operation succeeded.
+    pub ok: bool,
+    /// In the order of the operations.
+    pub results: Vec<OperationResult>,
+    pub errors: ErrorSummary,
+}
+
+impl QueryAllResult {
+    pub fn new(results: Vec<OperationResult>) -> QueryAllResult {
+        let mut errors = ErrorSummary {
+            retryable: true,
+            ..ErrorSummary::default()
+        };
+        for result in results.iter().filter(|result| !result.ok) {
+            let error = result.error.as_ref().unwrap_or(&Value::Null);
+            let category = error["category"].as_str().unwrap_or("unknown");
+            *errors.by_category.entry(category.to_string()).or_default() += 1;
+            errors.failed.push(result.name.clone());
+            errors.retryable &= error["retryable"].as_bool().unwrap_or(false);
+            errors.count += 1;
+        }
+        errors.retryable &= errors.count > 0;
+        QueryAllResult {
+            ok: errors.count == 0,
+            results,
+            errors,
+        }
+    }
+}
+
+/// The `error` of a failed result.
+pub(crate) fn error_value(error: &JsValue) -> Value {
+    match StorefrontError::from_js(error) {
+        Some(error) => serde_json::to_value(error).unwrap_or(Value::Null),
+        None => serde_json::json!({ "message": error_message(error) }),
+    }
+}
+
+impl StorefrontApi {
+    /// Runs `operations`, at most `concurrency` at a time.
+    pub async fn run_all(
+        &self,
+        operations: Vec<Operation>,
+        concurrency: usize,
+    ) -> Result<QueryAllResult, JsValue> {
+        let count = operations.len();
+        let mut queue: Vec<(usize, String, Operation)> = operations
+            .into_iter()
+            .enumerate()
+            .map(|(index, operation)| {
+                let name = operation.name.clone().unwrap_or_else(|| {
+                    crate::logging::operation_name(&operation.query)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| index.to_string())
+                });
+                (index, name, operation)
+            })
+            .collect();
+        queue.reverse();
+        let queue = Rc::new(RefCell::new(queue));
+        let results: Rc<RefCell<Vec<Option<OperationResult>>>> =
+            Rc::new(RefCell::new(vec![None; count]));
+
+        // Each worker takes the next operation until none are left.
+        let workers = js_sys::Array::new();
+        for _ in 0..concurrency.max(1).min(count) {
+            let (client, queue, results) = (self.clone(), queue.clone(), results.clone());
+            workers.push(&future_to_promise(async move {
+                loop {
+                    let Some((index, name, operation)) = queue.borrow_mut().pop() else {
+                        return Ok(JsValue::UNDEFINED);
+                    };
+                    let result = match client.execute(&operation.query, operation.variables).await {
+                        Ok(data) => OperationResult {
+                            name,
+                            ok: true,
+                            data: Some(data),
+                            error: None,
+                        },
+                        Err(error) => OperationResult {
+                            name,
+                            ok: false,
+                            data: None,
+                            error: Some(error_value(&error)),
+                        },
+                    };
+                    results.borrow_mut()[index] = Some(result);
+                }
+            }));
+        }
+        JsFuture::from(js_sys::Promise::all(&workers)).await?;
+
+        let results = results.take().into_iter().flatten().collect();
+        Ok(QueryAllResult::new(results))
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Runs independent operations concurrently and resolves once all of
+    /// them have finished, never rejecting for a failed one. `operations`
+    /// is an array of `{ name, query, variables }` (`name` defaults to the
+    /// operation's name) and `options` is `{ concurrency }` (default 6).
+    /// Resolves to `{ ok, results, errors }`: `results[i]` is `{ name, ok,
+    /// data }` or `{ name, ok, error }` for `operations[i]`, and `errors`
+    /// is `{ count, byCategory, failed, retryable }`.
+    #[wasm_bindgen(unchecked_return_type = "QueryAllResult")]
+    pub async fn query_all(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Operation[]")] operations: JsValue,
+        #[wasm_bindgen(unchecked_optional_param_type = "QueryAllOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let operations: Vec<Operation> = from_js(operations)?;
+        let options: QueryAllOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => QueryAllOptions::default(),
+        };
+        to_js(&self.run_all(operations, options.concurrency).await?)
+    }
+}
@@ -0,0 +1,140 @@
+//! Gift card products and recipient details on cart lines.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::cart::Attribute;
+use crate::types::{Money, Product};
+use crate::{from_js, to_js};
+
+/// Longest gift card message Shopify accepts.
+pub const MAX_GIFT_CARD_MESSAGE_LENGTH: usize = 200;
+
+/// Who receives a gift card and when. Shopify reads these from reserved line
+/// attributes, so they travel with the cart line rather than as separate
+/// mutation input.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GiftCardRecipient {
+    pub email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// ISO 8601 date the card should be sent on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_on: Option<String>,
+}
+
+impl GiftCardRecipient {
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attributes = vec![
+            attribute("__shopify_send_gift_card_to_recipient", "on"),
+            attribute("Recipient email", &self.email),
+        ];
+        if let Some(name) = &self.name {
+            attributes.push(attribute("Recipient name", name));
+        }
+        if let Some(message) = &self.message {
+            let message: String = message.chars().take(MAX_GIFT_CARD_MESSAGE_LENGTH).collect();
+            attributes.push(attribute("Message", &message));
+        }
+        if let Some(send_on) = &self.send_on {
+            attributes.push(attribute("Send on", send_on));
+        }
+        attributes
+    }
+}
+
+fn attribute(key: &str, value: &str) -> Attribute {
+    Attribute {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GiftCardDenomination {
+    pub variant_id: String,
+    pub title: String,
+    pub price: Money,
+    pub available_for_sale: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipientField {
+    /// Attribute key sent on the cart line.
+    pub key: &'static str,
+    pub required: bool,
+}
+
+/// What a storefront needs to sell a gift card product.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GiftCardInfo {
+    /// One entry per variant, i.e. per card value.
+    pub denominations: Vec<GiftCardDenomination>,
+    /// Fields of the optional "send to a recipient" form. When the buyer opts
+    /// in, the email is required.
+    pub recipient_fields: Vec<RecipientField>,
+    pub max_message_length: usize,
+}
+
+impl GiftCardInfo {
+    /// Returns `None` unless the product is a gift card.
+    pub fn for_product(product: &Product) -> Option<GiftCardInfo> {
+        if product.is_gift_card != Some(true) {
+            return None;
+        }
+
+        let denominations = product
+            .variants
+            .as_ref()
+            .map(|variants| {
+                variants
+                    .nodes()
+                    .map(|variant| GiftCardDenomination {
+                        variant_id: variant.id.clone(),
+                        title: variant.title.clone(),
+                        price: variant.price.clone(),
+                        available_for_sale: variant.available_for_sale,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(GiftCardInfo {
+            denominations,
+            recipient_fields: vec![
+                RecipientField {
+                    key: "Recipient email",
+                    required: true,
+                },
+                RecipientField {
+                    key: "Recipient name",
+                    required: false,
+                },
+                RecipientField {
+                    key: "Message",
+                    required: false,
+                },
+                RecipientField {
+                    key: "Send on",
+                    required: false,
+                },
+            ],
+            max_message_length: MAX_GIFT_CARD_MESSAGE_LENGTH,
+        })
+    }
+}
+
+/// Returns denominations and recipient form fields for a gift card product
+/// from `get_product`, or `null` for ordinary products.
+#[wasm_bindgen(unchecked_return_type = "GiftCardInfo | null")]
+pub fn gift_card_info(
+    #[wasm_bindgen(unchecked_param_type = "Product")] product: JsValue,
+) -> Result<JsValue, JsValue> {
+    let product: Product = from_js(product)?;
+    to_js(&GiftCardInfo::for_product(&product))
+}
@@ -0,0 +1,150 @@
+//! Minimal GraphQL document scanning shared by the operation and cost
+//! helpers. This is not a validating parser; the API does that.
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Punctuator(char),
+    Name(String),
+    String(String),
+    Number(String),
+}
+
+/// Splits a GraphQL document into tokens, dropping whitespace, commas and
+/// comments.
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() || c == ',' || c == '\u{feff}' => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '"' => {
+                let block = chars[i..].starts_with(&['"', '"', '"']);
+                let mut value = String::new();
+                i += if block { 3 } else { 1 };
+                loop {
+                    if i >= chars.len() {
+                        return Err("Unterminated string".to_string());
+                    }
+                    if block && chars[i..].starts_with(&['"', '"', '"']) {
+                        i += 3;
+                        break;
+                    }
+                    if !block && chars[i] == '"' {
+                        i += 1;
+                        break;
+                    }
+                    if !block && chars[i] == '\\' && i + 1 < chars.len() {
+                        i += 1;
+                        value.push(match chars[i] {
+                            'n' => '\n',
+                            't' => '\t',
+                            'r' => '\r',
+                            other => other,
+                        });
+                    } else {
+                        value.push(chars[i]);
+                    }
+                    i += 1;
+                }
+                tokens.push(Token::String(value));
+            }
+            '.' if chars[i..].starts_with(&['.', '.', '.']) => {
+                tokens.push(Token::Punctuator('.'));
+                i += 3;
+            }
+            _ if c == '-' || c.is_ascii_digit() => {
+                let start = i;
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '.' | '+' | '-'))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Number(chars[start..i].iter().collect()));
+            }
+            _ if c == '_' || c.is_ascii_alphabetic() => {
+                let start = i;
+                while i < chars.len() && (chars[i] == '_' || chars[i].is_ascii_alphanumeric()) {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+            '!' | '$' | '&' | '(' | ')' | ':' | '=' | '@' | '[' | ']' | '{' | '|' | '}' => {
+                tokens.push(Token::Punctuator(c));
+                i += 1;
+            }
+            other => return Err(format!("Unexpected character {other:?}")),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Indices of the `query` / `mutation` / `subscription` keywords that start
+/// top-level operation definitions. A keyword right after another is the
+/// operation's name (`query mutation { ... }`), not a start.
+pub(crate) fn operation_starts(tokens: &[Token]) -> Vec<usize> {
+    let mut operations: Vec<usize> = Vec::new();
+    let (mut braces, mut parens) = (0usize, 0usize);
+    for (index, token) in tokens.iter().enumerate() {
+        let named = operations.last().is_some_and(|&start| start + 1 == index);
+        if named && matches!(token, Token::Name(_)) {
+            continue;
+        }
+        match token {
+            Token::Punctuator('{') => braces += 1,
+            Token::Punctuator('}') => braces = braces.saturating_sub(1),
+            Token::Punctuator('(') => parens += 1,
+            Token::Punctuator(')') => parens = parens.saturating_sub(1),
+            Token::Name(name)
+                if braces == 0
+                    && parens == 0
+                    && matches!(name.as_str(), "query" | "mutation" | "subscription") =>
+            {
+                operations.push(index)
+            }
+            _ => {}
+        }
+    }
+    operations
+}
+
+/// Whether `query` is a mutation: never cached, and always posted. Goes by
+/// the operation keywords, so documents that open with a fragment or a
+/// comment are classified too; one that doesn't tokenize counts as a
+/// mutation.
+pub(crate) fn is_mutation(query: &str) -> bool {
+    let Ok(tokens) = tokenize(query) else {
+        return true;
+    };
+    operation_starts(&tokens)
+        .into_iter()
+        .any(|start| tokens[start] == Token::Name("mutation".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutations_are_found_after_fragments_and_comments() {
+        assert!(is_mutation("mutation m { cartCreate { cart { id } } }"));
+        assert!(is_mutation(
+            "# adds a line\nfragment F on Cart { id }\nmutation m { cartCreate { cart { ...F } } }"
+        ));
+        assert!(!is_mutation(
+            "fragment F on Shop { name }\nquery q { shop { ...F } }"
+        ));
+        assert!(!is_mutation("{ shop { mutation: name } }"));
+        assert!(!is_mutation("query mutation { shop { name } }"));
+        assert!(is_mutation(
+            "query { shop { name } } mutation { cartCreate { cart { id } } }"
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+//! hreflang alternates for international SEO.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::urls::{hreflang_language, locale_prefix};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Options for `get_hreflang_alternates`, passed from JavaScript as a plain
+/// object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HreflangOptions {
+    /// Origin of the storefront; defaults to the shop's primary domain.
+    pub base_url: Option<String>,
+    /// Limits the alternates to these `CountryCode`s.
+    pub countries: Vec<String>,
+}
+
+/// One `<link rel="alternate" hreflang href>` tag.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HreflangAlternate {
+    /// E.g. `fr-CA`, or `x-default`.
+    pub hreflang: String,
+    pub href: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IsoCode {
+    iso_code: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlternateCountry {
+    iso_code: String,
+    #[serde(default)]
+    available_languages: Vec<IsoCode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlternateLocalization {
+    available_countries: Vec<AlternateCountry>,
+    country: IsoCode,
+    language: IsoCode,
+}
+
+/// The storefront path of a resource.
+fn resource_path(kind: &str, handle: &str) -> Result<String, JsValue> {
+    match kind {
+        "product" => Ok(format!("/products/{handle}")),
+        "collection" => Ok(format!("/collections/{handle}")),
+        "page" => Ok(format!("/pages/{handle}")),
+        _ => Err(JsValue::from_str(&format!(
+            "Invalid resource kind: {kind}. Expected product, collection or page"
+        ))),
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Returns hreflang alternates (`[{ hreflang, href }]`) for a product,
+    /// collection or page in every available country and language, plus an
+    /// `x-default` entry. URLs follow Shopify's subfolder convention
+    /// (`/fr-ca/products/shirt`) on the shop's primary domain unless
+    /// `options.baseUrl` is given.
+    #[wasm_bindgen(unchecked_return_type = "HreflangAlternate[]")]
+    pub async fn get_hreflang_alternates(
+        &self,
+        kind: String,
+        handle: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "HreflangOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options: HreflangOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => HreflangOptions::default(),
+        };
+        let path = resource_path(kind.trim().to_lowercase().as_str(), &handle)?;
+        let countries: Vec<String> = options
+            .countries
+            .iter()
+            .map(|country| country.trim().to_uppercase())
+            .collect();
+
+        // Ask without the client's @inContext so `country` and `language`
+        // are the shop's defaults rather than the current buyer's.
+        let mut client = self.clone();
+        client.context = Default::default();
+        let query = r#"
+            query getHreflangAlternates {
+                localization {
+                    availableCountries {
+                        isoCode
+                        availableLanguages { isoCode }
+                    }
+                    country { isoCode }
+                    language { isoCode }
+                }
+                shop {
+                    primaryDomain { url }
+                }
+            }
+        "#;
+        let mut data = client.execute(query, serde_json::Value::Null).await?;
+        let localization: AlternateLocalization = from_json(data["localization"].take())?;
+        let base_url = match options.base_url {
+            Some(base_url) => base_url,
+            None => from_json(data["shop"]["primaryDomain"]["url"].take())?,
+        };
+        let base_url = base_url.trim_end_matches('/');
+
+        let primary_country = &localization.country.iso_code;
+        let primary_language = &localization.language.iso_code;
+        let mut alternates = Vec::new();
+        for country in &localization.available_countries {
+            if !countries.is_empty() && !countries.contains(&country.iso_code) {
+                continue;
+            }
+            for language in &country.available_languages {
+                let prefix = locale_prefix(
+                    &country.iso_code,
+                    &language.iso_code,
+                    primary_country,
+                    primary_language,
+                );
+                alternates.push(HreflangAlternate {
+                    hreflang: format!(
+                        "{}-{}",
+                        hreflang_language(&language.iso_code)
+                            .split('-')
+                            .next()
+                            .unwrap_or_default(),
+                        country.iso_code
+                    ),
+                    href: format!("{base_url}{prefix}{path}"),
+                });
+            }
+        }
+        alternates.push(HreflangAlternate {
+            hreflang: "x-default".to_string(),
+            href: format!("{base_url}{path}"),
+        });
+
+        to_js(&alternates)
+    }
+}
@@ -0,0 +1,265 @@
+//! Shopify CDN image URL transformations and responsive image attributes.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::types::Image;
+use crate::{from_js, to_js};
+
+/// Widths used for `srcset` when the caller doesn't supply any.
+pub const DEFAULT_SRCSET_WIDTHS: &[u32] = &[180, 360, 540, 720, 900, 1080, 1296, 1512, 1728, 2048];
+
+/// Accepted values for the CDN `crop` parameter.
+const CROP_VALUES: &[&str] = &["center", "top", "bottom", "left", "right"];
+
+/// Accepted values for the CDN `format` parameter.
+const FORMAT_VALUES: &[&str] = &["jpg", "pjpg", "png", "webp", "gif"];
+
+/// A Shopify CDN image URL with transformation parameters. Setters return a
+/// new value so calls chain in both Rust and JavaScript:
+/// `new ImageUrl(url).width(600).format("webp").toString()`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImageUrl {
+    base: String,
+    params: Vec<(String, String)>,
+    fragment: Option<String>,
+}
+
+#[wasm_bindgen]
+impl ImageUrl {
+    /// Parses a CDN URL, keeping any query parameters it already carries
+    /// (such as the `v` cache-buster).
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: &str) -> ImageUrl {
+        let (rest, fragment) = match url.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment.to_string())),
+            None => (url, None),
+        };
+        let (base, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let params = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (pair.to_string(), String::new()),
+            })
+            .collect();
+
+        ImageUrl {
+            base: base.to_string(),
+            params,
+            fragment,
+        }
+    }
+
+    pub fn width(&self, width: u32) -> ImageUrl {
+        self.with_param("width", width.to_string())
+    }
+
+    pub fn height(&self, height: u32) -> ImageUrl {
+        self.with_param("height", height.to_string())
+    }
+
+    /// Crops to the requested box: `center`, `top`, `bottom`, `left` or
+    /// `right`. Only takes effect with both width and height set.
+    pub fn crop(&self, crop: &str) -> Result<ImageUrl, JsValue> {
+        self.with_crop(crop)
+            .map_err(|error| JsValue::from_str(&error))
+    }
+
+    /// Converts the image: `jpg`, `pjpg`, `png`, `webp` or `gif`.
+    pub fn format(&self, format: &str) -> Result<ImageUrl, JsValue> {
+        self.with_format(format)
+            .map_err(|error| JsValue::from_str(&error))
+    }
+
+    /// Renders `url 180w, url 360w, ...` for the given widths (or
+    /// `DEFAULT_SRCSET_WIDTHS`), skipping widths above `max_width`.
+    pub fn srcset(&self, widths: Option<Vec<u32>>, max_width: Option<u32>) -> String {
+        let widths = widths.unwrap_or_else(|| DEFAULT_SRCSET_WIDTHS.to_vec());
+        widths
+            .into_iter()
+            .filter(|width| max_width.is_none_or(|max| *width <= max))
+            .map(|width| format!("{} {width}w", self.width(width).build()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn build(&self) -> String {
+        let mut url = self.base.clone();
+        if !self.params.is_empty() {
+            url.push('?');
+            let pairs: Vec<String> = self
+                .params
+                .iter()
+                .map(|(key, value)| {
+                    if value.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{key}={value}")
+                    }
+                })
+                .collect();
+            url.push_str(&pairs.join("&"));
+        }
+        if let Some(fragment) = &self.fragment {
+            url.push('#');
+            url.push_str(fragment);
+        }
+        url
+    }
+}
+
+impl ImageUrl {
+    pub fn with_crop(&self, crop: &str) -> Result<ImageUrl, String> {
+        let crop = crop.to_lowercase();
+        if !CROP_VALUES.contains(&crop.as_str()) {
+            return Err(format!(
+                "Unknown crop: {crop} (expected one of {})",
+                CROP_VALUES.join(", ")
+            ));
+        }
+        Ok(self.with_param("crop", crop))
+    }
+
+    pub fn with_format(&self, format: &str) -> Result<ImageUrl, String> {
+        let format = format.to_lowercase();
+        if !FORMAT_VALUES.contains(&format.as_str()) {
+            return Err(format!(
+                "Unknown image format: {format} (expected one of {})",
+                FORMAT_VALUES.join(", ")
+            ));
+        }
+        Ok(self.with_param("format", format))
+    }
+
+    fn with_param(&self, key: &str, value: String) -> ImageUrl {
+        let mut next = self.clone();
+        match next.params.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => next.params.push((key.to_string(), value)),
+        }
+        next
+    }
+}
+
+impl std::fmt::Display for ImageUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.build())
+    }
+}
+
+/// One `(min-width, slot width)` rule for a `sizes` attribute, e.g.
+/// `{ minWidth: 990, size: "33vw" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeRule {
+    #[serde(default)]
+    pub min_width: Option<u32>,
+    pub size: String,
+}
+
+/// Renders a `sizes` attribute; rules are emitted widest breakpoint first and a
+/// rule without `min_width` is used as the fallback.
+pub fn sizes_attribute(rules: &[SizeRule]) -> String {
+    let mut conditional: Vec<&SizeRule> = rules.iter().filter(|r| r.min_width.is_some()).collect();
+    conditional.sort_by_key(|rule| std::cmp::Reverse(rule.min_width));
+
+    let mut parts: Vec<String> = conditional
+        .iter()
+        .map(|rule| {
+            format!(
+                "(min-width: {}px) {}",
+                rule.min_width.unwrap_or(0),
+                rule.size
+            )
+        })
+        .collect();
+    parts.push(
+        rules
+            .iter()
+            .find(|rule| rule.min_width.is_none())
+            .map(|rule| rule.size.clone())
+            .unwrap_or_else(|| "100vw".to_string()),
+    );
+    parts.join(", ")
+}
+
+/// Attributes for an `<img>` built from a typed `Image`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponsiveImage {
+    pub src: String,
+    pub srcset: String,
+    pub sizes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    pub alt: String,
+}
+
+/// Options for `responsive_image`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResponsiveImageOptions {
+    pub widths: Option<Vec<u32>>,
+    pub sizes: Vec<SizeRule>,
+    /// Width of the `src` fallback; defaults to the largest srcset width.
+    pub src_width: Option<u32>,
+    pub format: Option<String>,
+}
+
+impl ResponsiveImage {
+    pub fn new(
+        image: &Image,
+        options: &ResponsiveImageOptions,
+    ) -> Result<ResponsiveImage, JsValue> {
+        let mut url = ImageUrl::new(&image.url);
+        if let Some(format) = &options.format {
+            url = url.format(format)?;
+        }
+
+        let srcset = url.srcset(options.widths.clone(), image.width);
+        let src_width = options.src_width.or_else(|| {
+            let widths = options.widths.as_deref().unwrap_or(DEFAULT_SRCSET_WIDTHS);
+            widths
+                .iter()
+                .copied()
+                .filter(|width| image.width.is_none_or(|max| *width <= max))
+                .max()
+        });
+        let src = match src_width {
+            Some(width) => url.width(width).build(),
+            None => url.build(),
+        };
+
+        Ok(ResponsiveImage {
+            src,
+            srcset,
+            sizes: sizes_attribute(&options.sizes),
+            width: image.width,
+            height: image.height,
+            alt: image.alt_text.clone().unwrap_or_default(),
+        })
+    }
+}
+
+/// Builds `{ src, srcset, sizes, width, height, alt }` for an image object
+/// returned by `get_product` or `get_collection`.
+#[wasm_bindgen(unchecked_return_type = "ResponsiveImage")]
+pub fn responsive_image(
+    #[wasm_bindgen(unchecked_param_type = "Image")] image: JsValue,
+    #[wasm_bindgen(unchecked_optional_param_type = "ResponsiveImageOptions")] options: Option<
+        JsValue,
+    >,
+) -> Result<JsValue, JsValue> {
+    let image: Image = from_js(image)?;
+    let options: ResponsiveImageOptions = match options {
+        Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+        _ => ResponsiveImageOptions::default(),
+    };
+    to_js(&ResponsiveImage::new(&image, &options)?)
+}
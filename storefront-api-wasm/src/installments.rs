@@ -0,0 +1,368 @@
+//! Shop Pay Installments eligibility and per-variant pricing.
+//!
+//! `get_installments_message(variantId, quantity)` and, for a price already
+//! on the page, `installments_message(price, shopPricing)` give what a
+//! product page's financing banner shows: whether the price qualifies, the
+//! number of payments, the amount of each and the copy.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::money::DEFAULT_LOCALE;
+use crate::types::{
+    Connection, Money, ShopPayInstallmentsPricing, ShopPayInstallmentsProductVariantPricing,
+};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Installment pricing for one variant, with display copy.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantInstallments {
+    pub variant_id: String,
+    pub title: String,
+    #[serde(flatten)]
+    pub pricing: ShopPayInstallmentsProductVariantPricing,
+    /// True when a zero-APR plan covers the variant's price.
+    pub interest_free: bool,
+    /// E.g. "4 interest-free payments of $12.50", or `null` when the variant
+    /// isn't eligible.
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallmentsPricing {
+    /// Shop-level plans; `null` when Shop Pay Installments isn't enabled.
+    pub shop: Option<ShopPayInstallmentsPricing>,
+    pub variants: Vec<VariantInstallments>,
+}
+
+/// Financing banner data for a price.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallmentsMessage {
+    /// Whether Shop Pay Installments is offered for `price`.
+    pub eligible: bool,
+    pub price: Money,
+    /// The number of payments; `null` when not eligible.
+    pub installments_count: Option<u32>,
+    /// The amount of each payment; `null` when not eligible.
+    pub price_per_term: Option<Money>,
+    pub interest_free: bool,
+    /// `ShopPayInstallmentsFinancingPlanFrequency`, e.g. `WEEKLY`.
+    pub frequency: Option<String>,
+    /// E.g. "4 interest-free payments of $12.50"; `null` when not
+    /// eligible.
+    pub message: Option<String>,
+    /// The smallest order Shop Pay Installments is offered for, for
+    /// "available on orders over" copy; `null` when it isn't enabled.
+    pub min_price: Option<Money>,
+}
+
+/// Display copy for `count` payments of `amount`.
+fn payments_message(count: u32, amount: &Money, interest_free: bool) -> String {
+    let amount = amount.format(DEFAULT_LOCALE);
+    if interest_free {
+        format!("{count} interest-free payments of {amount}")
+    } else {
+        format!("{count} payments of {amount}")
+    }
+}
+
+impl InstallmentsMessage {
+    /// The message for `price` under the shop's plans: the plan term
+    /// covering it, interest-free ones first, then the fewest payments.
+    pub fn new(price: Money, shop: Option<&ShopPayInstallmentsPricing>) -> InstallmentsMessage {
+        let value = price.value();
+        let term = shop
+            .into_iter()
+            .flat_map(|shop| &shop.financing_plans)
+            .filter(|plan| {
+                plan.min_price.currency_code == price.currency_code
+                    && plan.min_price.value() <= value
+                    && value <= plan.max_price.value()
+            })
+            .flat_map(|plan| &plan.terms)
+            .filter_map(|term| Some((term, term.installments_count.as_ref()?.count)))
+            .filter(|(_, count)| *count > 0)
+            .min_by(|(a, a_count), (b, b_count)| {
+                (a.apr > 0.0, a_count).cmp(&(b.apr > 0.0, b_count))
+            });
+
+        let mut message = InstallmentsMessage {
+            eligible: false,
+            installments_count: None,
+            price_per_term: None,
+            interest_free: false,
+            frequency: None,
+            message: None,
+            min_price: shop.map(|shop| shop.min_price.clone()),
+            price,
+        };
+        if let Some((term, count)) = term {
+            // Shop Pay puts any rounding remainder on the first payment, so
+            // the per-payment amount shown is rounded down.
+            let per_term = Money {
+                amount: format!(
+                    "{:.2}",
+                    ((value * 100.0).round() / count as f64).floor() / 100.0
+                ),
+                currency_code: message.price.currency_code.clone(),
+            };
+            message.eligible = true;
+            message.interest_free = term.apr == 0.0;
+            message.frequency = Some(term.frequency.clone());
+            message.message = Some(payments_message(count, &per_term, message.interest_free));
+            message.installments_count = Some(count);
+            message.price_per_term = Some(per_term);
+        }
+        message
+    }
+
+    /// The message from a variant's own installment pricing, which Shopify
+    /// computed for one unit.
+    fn from_variant(
+        pricing: &ShopPayInstallmentsProductVariantPricing,
+        shop: Option<&ShopPayInstallmentsPricing>,
+    ) -> InstallmentsMessage {
+        let computed = InstallmentsMessage::new(pricing.full_price.clone(), shop);
+        let count = pricing
+            .installments_count
+            .as_ref()
+            .map(|count| count.count)
+            .filter(|_| pricing.eligible && pricing.available);
+        let interest_free =
+            count.is_some_and(|count| interest_free(shop, pricing.full_price.value(), count));
+        InstallmentsMessage {
+            eligible: count.is_some(),
+            installments_count: count,
+            price_per_term: count.map(|_| pricing.price_per_term.clone()),
+            interest_free,
+            frequency: computed.frequency.filter(|_| count.is_some()),
+            message: count
+                .map(|count| payments_message(count, &pricing.price_per_term, interest_free)),
+            ..computed
+        }
+    }
+}
+
+/// Whether a zero-APR plan term covering `price` splits it into `count`
+/// payments.
+fn interest_free(shop: Option<&ShopPayInstallmentsPricing>, price: f64, count: u32) -> bool {
+    shop.into_iter()
+        .flat_map(|shop| &shop.financing_plans)
+        .filter(|plan| plan.min_price.value() <= price && price <= plan.max_price.value())
+        .flat_map(|plan| &plan.terms)
+        .any(|term| {
+            term.apr == 0.0
+                && term
+                    .installments_count
+                    .as_ref()
+                    .is_some_and(|terms| terms.count == count)
+        })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallmentVariant {
+    id: String,
+    title: String,
+    shop_pay_installments_pricing: Option<ShopPayInstallmentsProductVariantPricing>,
+}
+
+impl VariantInstallments {
+    fn new(
+        variant_id: String,
+        title: String,
+        pricing: ShopPayInstallmentsProductVariantPricing,
+        shop: Option<&ShopPayInstallmentsPricing>,
+    ) -> VariantInstallments {
+        let count = pricing.installments_count.as_ref().map(|count| count.count);
+        let price = pricing.full_price.value();
+        let interest_free = count.is_some_and(|count| interest_free(shop, price, count));
+        let message = match count {
+            Some(count) if pricing.eligible && pricing.available => Some(payments_message(
+                count,
+                &pricing.price_per_term,
+                interest_free,
+            )),
+            _ => None,
+        };
+        VariantInstallments {
+            variant_id,
+            title,
+            pricing,
+            interest_free,
+            message,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Returns Shop Pay Installments pricing for a product:
+    /// `{ shop, variants: [{ variantId, title, eligible, available,
+    /// fullPrice, installmentsCount, pricePerTerm, interestFree, message }] }`.
+    /// Variants without installment pricing are left out. Returns `null` if
+    /// the product doesn't exist.
+    #[wasm_bindgen(unchecked_return_type = "InstallmentsPricing | null")]
+    pub async fn get_installments_pricing(&self, handle: String) -> Result<JsValue, JsValue> {
+        let query = r#"
+            query getInstallmentsPricing($handle: String!) {
+                shop {
+                    shopPayInstallmentsPricing {
+                        minPrice { amount currencyCode }
+                        maxPrice { amount currencyCode }
+                        financingPlans {
+                            id
+                            minPrice { amount currencyCode }
+                            maxPrice { amount currencyCode }
+                            terms {
+                                id
+                                apr
+                                frequency
+                                installmentsCount { count precision }
+                                loanType
+                            }
+                        }
+                    }
+                }
+                product(handle: $handle) {
+                    variants(first: 100) {
+                        edges {
+                            node {
+                                id
+                                title
+                                shopPayInstallmentsPricing {
+                                    available
+                                    eligible
+                                    fullPrice { amount currencyCode }
+                                    installmentsCount { count precision }
+                                    pricePerTerm { amount currencyCode }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({ "handle": handle });
+        let mut data = self.execute(query, variables).await?;
+        let shop: Option<ShopPayInstallmentsPricing> =
+            from_json(data["shop"]["shopPayInstallmentsPricing"].take())?;
+        let mut product = data["product"].take();
+        if product.is_null() {
+            return Ok(JsValue::NULL);
+        }
+        let variants: Connection<InstallmentVariant> = from_json(product["variants"].take())?;
+
+        let variants = variants
+            .edges
+            .into_iter()
+            .filter_map(|edge| {
+                let variant = edge.node;
+                variant.shop_pay_installments_pricing.map(|pricing| {
+                    VariantInstallments::new(variant.id, variant.title, pricing, shop.as_ref())
+                })
+            })
+            .collect();
+
+        to_js(&InstallmentsPricing { shop, variants })
+    }
+
+    /// Financing banner data for `quantity` (default 1) of a variant:
+    /// `{ eligible, price, installmentsCount, pricePerTerm, interestFree,
+    /// frequency, message, minPrice }`. Returns `null` if the variant
+    /// doesn't exist.
+    #[wasm_bindgen(unchecked_return_type = "InstallmentsMessage | null")]
+    pub async fn get_installments_message(
+        &self,
+        variant_id: String,
+        quantity: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        let quantity = quantity.unwrap_or(1);
+        if quantity == 0 {
+            return Err(JsValue::from_str("The quantity must be at least 1"));
+        }
+        let query = r#"
+            query getInstallmentsMessage($id: ID!) {
+                shop {
+                    shopPayInstallmentsPricing {
+                        minPrice { amount currencyCode }
+                        maxPrice { amount currencyCode }
+                        financingPlans {
+                            id
+                            minPrice { amount currencyCode }
+                            maxPrice { amount currencyCode }
+                            terms {
+                                id
+                                apr
+                                frequency
+                                installmentsCount { count precision }
+                                loanType
+                            }
+                        }
+                    }
+                }
+                node(id: $id) {
+                    ... on ProductVariant {
+                        price { amount currencyCode }
+                        shopPayInstallmentsPricing {
+                            available
+                            eligible
+                            fullPrice { amount currencyCode }
+                            installmentsCount { count precision }
+                            pricePerTerm { amount currencyCode }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({ "id": variant_id });
+        let mut data = self.execute(query, variables).await?;
+        let shop: Option<ShopPayInstallmentsPricing> =
+            from_json(data["shop"]["shopPayInstallmentsPricing"].take())?;
+        let mut variant = data["node"].take();
+        if variant["price"].is_null() {
+            return Ok(JsValue::NULL);
+        }
+        let price: Money = from_json(variant["price"].take())?;
+        let pricing: Option<ShopPayInstallmentsProductVariantPricing> =
+            from_json(variant["shopPayInstallmentsPricing"].take())?;
+
+        let message = match pricing {
+            Some(pricing) if quantity == 1 => {
+                InstallmentsMessage::from_variant(&pricing, shop.as_ref())
+            }
+            _ => {
+                let total = Money {
+                    amount: format!("{:.2}", price.value() * quantity as f64),
+                    currency_code: price.currency_code,
+                };
+                InstallmentsMessage::new(total, shop.as_ref())
+            }
+        };
+        to_js(&message)
+    }
+}
+
+/// Financing banner data for `price` (`{ amount, currencyCode }`, e.g. the
+/// selected variant's price times the quantity) under `shop_pricing`, the
+/// `shop` of `get_installments_pricing`, without a request. Not eligible
+/// when `shop_pricing` is `null`.
+#[wasm_bindgen(unchecked_return_type = "InstallmentsMessage")]
+pub fn installments_message(
+    #[wasm_bindgen(unchecked_param_type = "Money")] price: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "ShopPayInstallmentsPricing | null")]
+    shop_pricing: JsValue,
+) -> Result<JsValue, JsValue> {
+    let price: Money = from_js(price)?;
+    let shop: Option<ShopPayInstallmentsPricing> = if shop_pricing.is_undefined() {
+        None
+    } else {
+        from_js(shop_pricing)?
+    };
+    to_js(&InstallmentsMessage::new(price, shop.as_ref()))
+}
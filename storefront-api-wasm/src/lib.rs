@@ -1,7 +1,82 @@
-use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{Request, RequestInit, RequestMode, Response, Headers};
+use web_sys::{Headers, Request, RequestInit, RequestMode, Response};
+
+#[cfg(feature = "admin")]
+pub mod admin;
+pub mod analytics;
+#[cfg(feature = "content")]
+pub mod blog;
+pub mod bundle;
+pub mod cache;
+pub mod canonical;
+#[cfg(feature = "cart")]
+pub mod cart;
+#[cfg(feature = "cart")]
+pub mod cart_watch;
+pub mod catalog;
+pub mod collection;
+pub mod compare;
+pub mod conditional;
+pub mod context;
+pub mod cost;
+pub mod error;
+pub mod fanout;
+#[cfg(feature = "cart")]
+pub mod gift_card;
+pub(crate) mod graphql;
+pub mod hreflang;
+pub mod image;
+pub mod installments;
+#[cfg(feature = "liquid")]
+pub mod liquid;
+pub mod localization;
+pub mod logging;
+pub mod markets;
+#[cfg(feature = "mcp")]
+pub mod mcp;
+pub mod memory;
+#[cfg(feature = "content")]
+pub mod menu;
+pub mod metafield;
+#[cfg(feature = "content")]
+pub mod metaobject;
+pub mod migration;
+pub mod money;
+pub mod node;
+#[cfg(feature = "content")]
+pub mod page;
+pub mod pagination;
+pub mod prefetch;
+pub mod pricing;
+pub mod privacy;
+pub mod product;
+pub mod query_state;
+pub mod redact;
+pub mod report;
+pub mod response;
+pub mod restock;
+pub mod retry;
+pub mod route;
+pub mod search;
+#[cfg(feature = "content")]
+pub mod section;
+pub mod seo;
+pub mod shop;
+pub mod sitemap;
+pub mod testing;
+pub mod translation;
+pub mod types;
+pub mod typescript;
+pub mod urls;
+pub mod variant;
+pub mod vcr;
+pub mod watch;
+pub mod webhook;
+
+#[cfg(feature = "cart")]
+pub use cart::CartItem;
 
 #[wasm_bindgen]
 extern "C" {
@@ -13,20 +88,45 @@ macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StorefrontConfig {
     pub shop_domain: String,
     pub access_token: String,
     pub api_version: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl std::fmt::Debug for StorefrontConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorefrontConfig")
+            .field("shop_domain", &self.shop_domain)
+            .field("access_token", &redact::Secret(&self.access_token))
+            .field("api_version", &self.api_version)
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct GraphQLRequest {
     pub query: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub variables: Option<serde_json::Value>,
 }
 
+impl std::fmt::Debug for GraphQLRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GraphQLRequest")
+            .field("query", &self.query)
+            .field(
+                "variables",
+                &self
+                    .variables
+                    .as_ref()
+                    .map(|variables| redact::redacted(variables, &[])),
+            )
+            .finish()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GraphQLResponse {
     pub data: Option<serde_json::Value>,
@@ -36,6 +136,8 @@ pub struct GraphQLResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GraphQLError {
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub locations: Option<Vec<ErrorLocation>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -48,9 +150,59 @@ pub struct ErrorLocation {
     pub column: u32,
 }
 
+/// Checked before every request with the final operation text and
+/// variables; an error stops the request.
+pub(crate) type RequestGuard = std::rc::Rc<dyn Fn(&str, &serde_json::Value) -> Result<(), JsValue>>;
+
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct StorefrontApi {
     config: StorefrontConfig,
+    /// Country and language injected into every operation with
+    /// `@inContext`, shared by copies; see `context`.
+    context: std::rc::Rc<std::cell::RefCell<context::InContext>>,
+    /// Consent and visitor tokens, shared by copies; see `privacy`.
+    privacy: std::rc::Rc<std::cell::RefCell<privacy::Privacy>>,
+    request_guard: Option<RequestGuard>,
+    log_sink: Option<logging::LogSink>,
+    /// Aborts in-flight requests when signalled; see `with_signal`.
+    signal: Option<web_sys::AbortSignal>,
+    /// Records responses to, or replays them from, a cassette, shared by
+    /// copies; see `vcr`.
+    vcr: std::rc::Rc<std::cell::RefCell<Option<vcr::Vcr>>>,
+    /// Cost limits and the bucket shared by copies; see `cost`.
+    cost: std::rc::Rc<std::cell::RefCell<cost::CostLimiter>>,
+    /// Response size limit and chunked parsing, shared by copies; see
+    /// `response`.
+    response_limits: std::rc::Rc<std::cell::RefCell<response::ResponseLimits>>,
+    /// Prefetched responses shared by copies; see `prefetch`.
+    prefetched: std::rc::Rc<std::cell::RefCell<prefetch::Prefetched>>,
+    /// Whether this copy's requests are prefetches.
+    prefetching: bool,
+    /// Cached responses shared by copies; see `cache`.
+    cache: std::rc::Rc<std::cell::RefCell<cache::ResponseCache>>,
+    /// Whether queries are posted or sent as `GET`, shared by copies; see
+    /// `conditional`.
+    transport: std::rc::Rc<std::cell::RefCell<conditional::Transport>>,
+    /// How retryable failures are retried, shared by copies; see `retry`.
+    retry: std::rc::Rc<std::cell::RefCell<retry::RetryPolicy>>,
+    /// Where failed requests are reported, shared by copies; see `report`.
+    error_reporter: std::rc::Rc<std::cell::RefCell<Option<report::ErrorReporter>>>,
+    /// Sends requests instead of the global `fetch`; see `with_fetch`.
+    fetch: Option<js_sys::Function>,
+    /// Schemas by API version, shared by copies; see `migration`.
+    schemas: std::rc::Rc<std::cell::RefCell<migration::Schemas>>,
+    /// Operations recorded for `migration_report`, shared by copies; see
+    /// `migration`.
+    sent_queries: std::rc::Rc<std::cell::RefCell<migration::SentQueries>>,
+    /// Whether requests go to the Admin API instead; see `admin`.
+    admin: bool,
+    /// Where `track` sends events, shared by copies; see `analytics`.
+    analytics: std::rc::Rc<std::cell::RefCell<Option<analytics::AnalyticsOptions>>>,
+    /// Where storefront URLs point, shared by copies; see `urls`.
+    urls: std::rc::Rc<std::cell::RefCell<urls::UrlSettings>>,
+    /// Variants watched for restocks, shared by copies; see `restock`.
+    availability_watches: std::rc::Rc<std::cell::RefCell<restock::AvailabilityWatches>>,
 }
 
 #[wasm_bindgen]
@@ -64,305 +216,525 @@ impl StorefrontApi {
                 access_token,
                 api_version,
             },
+            context: Default::default(),
+            privacy: Default::default(),
+            request_guard: None,
+            log_sink: None,
+            signal: None,
+            vcr: Default::default(),
+            cost: Default::default(),
+            response_limits: Default::default(),
+            prefetched: Default::default(),
+            prefetching: false,
+            cache: Default::default(),
+            transport: Default::default(),
+            retry: Default::default(),
+            error_reporter: Default::default(),
+            fetch: None,
+            schemas: Default::default(),
+            sent_queries: Default::default(),
+            admin: false,
+            analytics: Default::default(),
+            urls: Default::default(),
+            availability_watches: Default::default(),
         }
     }
 
-    #[wasm_bindgen]
-    pub async fn query(&self, query: String, variables: Option<JsValue>) -> Result<JsValue, JsValue> {
+    #[wasm_bindgen(unchecked_return_type = "Record<string, unknown>")]
+    pub async fn query(
+        &self,
+        query: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "Variables")] variables: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
         let variables_json = if let Some(vars) = variables {
             serde_wasm_bindgen::from_value(vars).unwrap_or(serde_json::Value::Null)
         } else {
             serde_json::Value::Null
         };
 
+        to_js(&self.execute(&query, variables_json).await?)
+    }
+
+    /// Returns a copy of the client whose requests are aborted when `signal`
+    /// fires: `api.with_signal(controller.signal).search_products(...)`.
+    /// Once the signal has fired, calls fail without sending a request.
+    #[wasm_bindgen]
+    pub fn with_signal(&self, signal: web_sys::AbortSignal) -> StorefrontApi {
+        let mut client = self.clone();
+        client.signal = Some(signal);
+        client
+    }
+
+    /// Returns a copy of the client that sends its requests with `fetch`
+    /// instead of the global one, for runtimes where that is missing or
+    /// needs wrapping: `new StorefrontApi(...).with_fetch(env.fetch)`.
+    /// `fetch` is called like the standard one, with a `Request`, and must
+    /// resolve to a `Response`.
+    #[wasm_bindgen]
+    pub fn with_fetch(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Fetch")] fetch: js_sys::Function,
+    ) -> StorefrontApi {
+        let mut client = self.clone();
+        client.fetch = Some(fetch);
+        client
+    }
+}
+
+impl StorefrontApi {
+    /// Sends a GraphQL operation and returns the `data` payload as JSON, so
+    /// helpers can decode typed results before handing them to JavaScript.
+    pub(crate) async fn execute(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value, JsValue> {
+        let query = self.context.borrow().apply(query);
+        let key = canonical::request_key(&query, &variables);
+        if self.prefetching {
+            return self.prefetch_request(key, query, variables).await;
+        }
+        if let Some(data) = self.take_prefetched(&key).await {
+            self.log(
+                logging::LogLevel::Debug,
+                serde_json::json!({
+                    "operation": logging::operation_name(&query).unwrap_or("anonymous"),
+                    "message": "Answered from a prefetch",
+                }),
+            );
+            return Ok(data);
+        }
+        if let Some(data) = self.cached_response(&key, &query, &variables) {
+            self.log(
+                logging::LogLevel::Debug,
+                serde_json::json!({
+                    "operation": logging::operation_name(&query).unwrap_or("anonymous"),
+                    "message": "Answered from the cache",
+                }),
+            );
+            return Ok(data);
+        }
+        self.sent_queries.borrow_mut().record(&query);
+        let data = self.send_operation(query.clone(), variables).await?;
+        self.cache_response(&key, &query, &data);
+        Ok(data)
+    }
+
+    /// `execute` without prefetched responses: checks the request, then
+    /// answers it from the cassette or the network, reporting failures to
+    /// `on_error`.
+    async fn send_operation(
+        &self,
+        query: String,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value, JsValue> {
+        let operation = logging::operation_name(&query)
+            .unwrap_or("anonymous")
+            .to_string();
+        let started = js_sys::Date::now();
+        let mut attempts = 0;
+        let result = self
+            .send_checked(query, variables.clone(), &operation, &mut attempts)
+            .await;
+        if let Err(error) = &result {
+            self.report_error(report::ErrorReport::new(
+                &operation,
+                variables,
+                js_sys::Date::now() - started,
+                attempts,
+                error,
+            ));
+        }
+        result
+    }
+
+    /// Checks a request, then sends it, retrying per the retry policy.
+    /// `attempts` counts the requests sent.
+    async fn send_checked(
+        &self,
+        query: String,
+        variables: serde_json::Value,
+        operation: &str,
+        attempts: &mut u32,
+    ) -> Result<serde_json::Value, JsValue> {
+        if self.aborted() {
+            return Err(self.abort_error().into());
+        }
+        self.check_cost(&query, &variables)?;
+        if let Some(guard) = &self.request_guard {
+            guard(&query, &variables)?;
+        }
         let graphql_request = GraphQLRequest {
             query,
-            variables: if variables_json.is_null() {
+            variables: if variables.is_null() {
                 None
             } else {
-                Some(variables_json)
+                Some(variables)
             },
         };
 
-        let url = format!(
-            "https://{}/api/{}/graphql.json",
-            self.config.shop_domain, self.config.api_version
-        );
-
-        let mut opts = RequestInit::new();
-        opts.method("POST");
-        opts.mode(RequestMode::Cors);
+        // A mutation that failed may still have been applied, so it is only
+        // sent again when the API refused it unexecuted.
+        let mutation = graphql::is_mutation(&graphql_request.query);
+        let policy = self.retry.borrow().clone();
+        let mut retries = 0;
+        loop {
+            *attempts += 1;
+            let result = self.attempt(&graphql_request, operation).await;
+            let retryable = result
+                .as_ref()
+                .err()
+                .and_then(error::StorefrontError::from_js);
+            match retryable {
+                Some(error)
+                    if error.retryable
+                        && (!mutation || error.code == "THROTTLED")
+                        && retries < policy.max_retries
+                        && !self.aborted() =>
+                {
+                    let delay = policy.delay_ms(retries);
+                    retries += 1;
+                    self.log(
+                        logging::LogLevel::Warning,
+                        serde_json::json!({
+                            "operation": operation,
+                            "message": format!("Retrying after {}", error.code),
+                            "retry": retries,
+                            "delayMs": delay,
+                        }),
+                    );
+                    retry::sleep(delay).await?;
+                }
+                _ => return result,
+            }
+        }
+    }
 
-        let headers = Headers::new().unwrap();
-        headers.set("Content-Type", "application/json").unwrap();
-        headers
-            .set("X-Shopify-Storefront-Access-Token", &self.config.access_token)
-            .unwrap();
+    /// Sends a checked request once and reads its `data`.
+    async fn attempt(
+        &self,
+        graphql_request: &GraphQLRequest,
+        operation: &str,
+    ) -> Result<serde_json::Value, JsValue> {
+        let started = js_sys::Date::now();
+        let replayed = match self.vcr.borrow_mut().as_mut() {
+            Some(vcr) => vcr.replay(graphql_request, operation, &self.config.access_token)?,
+            None => None,
+        };
+        let (status, body) = match replayed {
+            Some(response) => response,
+            None => {
+                let response = self.send(graphql_request, operation).await?;
+                if let Some(vcr) = self.vcr.borrow_mut().as_mut() {
+                    vcr.record(
+                        graphql_request,
+                        operation,
+                        &response,
+                        &self.config.access_token,
+                    );
+                }
+                response
+            }
+        };
+        if status == 429 {
+            self.log(
+                logging::LogLevel::Warning,
+                serde_json::json!({
+                    "operation": operation,
+                    "message": "Throttled by the Storefront API",
+                    "status": 429,
+                }),
+            );
+            return Err(error::StorefrontError::throttled(
+                "Throttled by the Storefront API; try again shortly",
+            )
+            .into());
+        }
+        if status >= 400 {
+            let error = error::StorefrontError::http(status);
+            self.log(
+                logging::LogLevel::Error,
+                serde_json::json!({
+                    "operation": operation,
+                    "message": error.message,
+                    "status": status,
+                }),
+            );
+            return Err(error.into());
+        }
 
-        opts.headers(&headers);
+        let response: GraphQLResponse = serde_json::from_value(body).map_err(|e| {
+            error::StorefrontError::invalid_response(format!("Not a GraphQL response: {e}"))
+        })?;
+        self.log(
+            logging::LogLevel::Debug,
+            serde_json::json!({
+                "operation": operation,
+                "status": status,
+                "durationMs": js_sys::Date::now() - started,
+            }),
+        );
 
-        let body = serde_json::to_string(&graphql_request).unwrap();
-        opts.body(Some(&JsValue::from_str(&body)));
+        if let Some(errors) = response.errors {
+            let error = error::StorefrontError::graphql(&errors);
+            let error_messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+            self.log(
+                if error.kind == error::ErrorKind::Throttled {
+                    logging::LogLevel::Warning
+                } else {
+                    logging::LogLevel::Error
+                },
+                serde_json::json!({
+                    "operation": operation,
+                    "errors": error_messages,
+                }),
+            );
+            return Err(error.into());
+        }
 
-        let request = Request::new_with_str_and_init(&url, &opts).unwrap();
+        Ok(response.data.unwrap_or(serde_json::Value::Null))
+    }
 
-        let window = web_sys::window().unwrap();
-        let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
-        let resp: Response = resp_value.dyn_into().unwrap();
+    /// Sends an operation to the Storefront API, returning the HTTP status
+    /// and the JSON body (`null` for an error status).
+    async fn send(
+        &self,
+        graphql_request: &GraphQLRequest,
+        operation: &str,
+    ) -> Result<(u16, serde_json::Value), JsValue> {
+        let endpoint = if self.admin {
+            format!(
+                "https://{}/admin/api/{}/graphql.json",
+                self.config.shop_domain, self.config.api_version
+            )
+        } else {
+            format!(
+                "https://{}/api/{}/graphql.json",
+                self.config.shop_domain, self.config.api_version
+            )
+        };
+        let get_url = self
+            .get_url(&endpoint, graphql_request)
+            .filter(|_| !self.admin);
+        // Cache key of a GET, whose response may be revalidated.
+        let key = get_url.as_ref().map(|_| {
+            canonical::request_key(
+                &graphql_request.query,
+                graphql_request
+                    .variables
+                    .as_ref()
+                    .unwrap_or(&serde_json::Value::Null),
+            )
+        });
 
-        let json = JsFuture::from(resp.json()?).await?;
-        let response: GraphQLResponse = serde_wasm_bindgen::from_value(json).unwrap();
+        let opts = RequestInit::new();
+        // Edge runtimes such as Cloudflare Workers reject `mode`.
+        if self.fetch.is_none() && !self.admin {
+            opts.set_mode(RequestMode::Cors);
+        }
 
-        if let Some(errors) = response.errors {
-            let error_messages: Vec<String> = errors.iter().map(|e| e.message.clone()).collect();
-            return Err(JsValue::from_str(&error_messages.join(", ")));
+        let headers = Headers::new()?;
+        if self.admin {
+            headers.set("X-Shopify-Access-Token", &self.config.access_token)?;
+        } else {
+            headers.set(
+                "X-Shopify-Storefront-Access-Token",
+                &self.config.access_token,
+            )?;
+            // Cloned so a consent callback can update the client.
+            let privacy = self.privacy.borrow().clone();
+            for (name, value) in privacy.tracking_headers() {
+                headers.set(name, value)?;
+            }
         }
 
-        Ok(serde_wasm_bindgen::to_value(&response.data.unwrap_or(serde_json::Value::Null)).unwrap())
-    }
+        opts.set_headers(&headers);
+        if let Some(signal) = &self.signal {
+            opts.set_signal(Some(signal));
+        }
+        if self.prefetching {
+            js_sys::Reflect::set(
+                &opts,
+                &JsValue::from_str("priority"),
+                &JsValue::from_str("low"),
+            )?;
+        }
 
-    #[wasm_bindgen]
-    pub async fn get_product(&self, handle: String) -> Result<JsValue, JsValue> {
-        let query = r#"
-            query getProduct($handle: String!) {
-                product(handle: $handle) {
-                    id
-                    title
-                    description
-                    handle
-                    vendor
-                    productType
-                    tags
-                    priceRange {
-                        minVariantPrice {
-                            amount
-                            currencyCode
-                        }
-                    }
-                    images(first: 10) {
-                        edges {
-                            node {
-                                id
-                                url
-                                altText
-                                width
-                                height
-                            }
-                        }
-                    }
-                    variants(first: 100) {
-                        edges {
-                            node {
-                                id
-                                title
-                                price {
-                                    amount
-                                    currencyCode
-                                }
-                                availableForSale
-                                selectedOptions {
-                                    name
-                                    value
-                                }
-                                image {
-                                    url
-                                    altText
-                                }
-                            }
-                        }
-                    }
+        let url = match get_url {
+            Some(url) => {
+                opts.set_method("GET");
+                if let Some(validators) = key
+                    .as_ref()
+                    .and_then(|key| self.cache.borrow().validators(key).cloned())
+                {
+                    validators.apply(&headers)?;
                 }
+                url
             }
-        "#;
-
-        let variables = serde_json::json!({
-            "handle": handle
-        });
+            None => {
+                opts.set_method("POST");
+                headers.set("Content-Type", "application/json")?;
+                let body = serde_json::to_string(graphql_request)
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?;
+                opts.set_body(&JsValue::from_str(&body));
+                endpoint
+            }
+        };
 
-        self.query(query.to_string(), Some(serde_wasm_bindgen::to_value(&variables).unwrap()))
-            .await
+        let request = Request::new_with_str_and_init(&url, &opts)?;
+
+        let resp_value = match JsFuture::from(self.fetch(&request)?).await {
+            Ok(value) => value,
+            Err(_) if self.aborted() => {
+                self.log(
+                    logging::LogLevel::Debug,
+                    serde_json::json!({
+                        "operation": operation,
+                        "message": "Request cancelled",
+                    }),
+                );
+                return Err(self.abort_error().into());
+            }
+            Err(error) => {
+                let message = error
+                    .dyn_ref::<js_sys::Error>()
+                    .map(|error| String::from(error.message()))
+                    .or_else(|| error.as_string())
+                    .unwrap_or_else(|| "Request failed".to_string());
+                self.log(
+                    logging::LogLevel::Error,
+                    serde_json::json!({
+                        "operation": operation,
+                        "message": "Request failed",
+                        "error": message,
+                    }),
+                );
+                return Err(error::StorefrontError::network(message).into());
+            }
+        };
+        let resp: Response = resp_value.dyn_into().map_err(|_| {
+            error::StorefrontError::invalid_response("fetch did not resolve to a Response")
+        })?;
+        // Error pages needn't be JSON; the status says enough.
+        if resp.status() == 429 || resp.status() >= 400 {
+            return Ok((resp.status(), serde_json::Value::Null));
+        }
+        if let Some(key) = &key {
+            if resp.status() == 304 {
+                return self.not_modified(key);
+            }
+            if let Some(validators) = conditional::Validators::from_response(&resp) {
+                self.cache.borrow_mut().set_validators(key, validators);
+            }
+        }
+        let limits = self.response_limits.borrow().clone();
+        let length = resp.headers().get("Content-Length")?;
+        if let Some(length) = length.and_then(|length| length.parse().ok()) {
+            limits
+                .check_size(length)
+                .map_err(|message| self.refuse_response(operation, message))?;
+        }
+        // Parsing the text in Rust skips building a JS object only to walk it
+        // back into JSON; see benches/serialization.rs.
+        let text = JsFuture::from(resp.text()?)
+            .await?
+            .as_string()
+            .unwrap_or_default();
+        limits
+            .check_size(text.len() as u64)
+            .map_err(|message| self.refuse_response(operation, message))?;
+        let json = limits.parse(&text).await?;
+        Ok((resp.status(), json))
     }
 
-    #[wasm_bindgen]
-    pub async fn get_collection(&self, handle: String, first: Option<u32>) -> Result<JsValue, JsValue> {
-        let limit = first.unwrap_or(20);
-        let query = format!(
-            r#"
-            query getCollection($handle: String!, $first: Int!) {{
-                collection(handle: $handle) {{
-                    id
-                    title
-                    description
-                    handle
-                    products(first: $first) {{
-                        edges {{
-                            node {{
-                                id
-                                title
-                                handle
-                                vendor
-                                priceRange {{
-                                    minVariantPrice {{
-                                        amount
-                                        currencyCode
-                                    }}
-                                }}
-                                images(first: 1) {{
-                                    edges {{
-                                        node {{
-                                            url
-                                            altText
-                                        }}
-                                    }}
-                                }}
-                            }}
-                        }}
-                        pageInfo {{
-                            hasNextPage
-                            hasPreviousPage
-                            startCursor
-                            endCursor
-                        }}
-                    }}
-                }}
-            }}
-        "#
+    /// Logs and returns the error for a response over the size limit.
+    fn refuse_response(&self, operation: &str, message: String) -> JsValue {
+        self.log(
+            logging::LogLevel::Error,
+            serde_json::json!({
+                "operation": operation,
+                "message": message,
+            }),
         );
-
-        let variables = serde_json::json!({
-            "handle": handle,
-            "first": limit
-        });
-
-        self.query(query, Some(serde_wasm_bindgen::to_value(&variables).unwrap()))
-            .await
+        error::StorefrontError::response_too_large(message).into()
     }
 
-    #[wasm_bindgen]
-    pub async fn search_products(&self, query: String, first: Option<u32>) -> Result<JsValue, JsValue> {
-        let limit = first.unwrap_or(20);
-        let graphql_query = format!(
-            r#"
-            query searchProducts($query: String!, $first: Int!) {{
-                products(first: $first, query: $query) {{
-                    edges {{
-                        node {{
-                            id
-                            title
-                            handle
-                            vendor
-                            priceRange {{
-                                minVariantPrice {{
-                                    amount
-                                    currencyCode
-                                }}
-                            }}
-                            images(first: 1) {{
-                                edges {{
-                                    node {{
-                                        url
-                                        altText
-                                    }}
-                                }}
-                            }}
-                        }}
-                    }}
-                    pageInfo {{
-                        hasNextPage
-                        hasPreviousPage
-                    }}
-                }}
-            }}
-        "#
-        );
-
-        let variables = serde_json::json!({
-            "query": query,
-            "first": limit
-        });
-
-        self.query(graphql_query, Some(serde_wasm_bindgen::to_value(&variables).unwrap()))
-            .await
+    fn aborted(&self) -> bool {
+        self.signal.as_ref().is_some_and(|signal| signal.aborted())
     }
 
-    #[wasm_bindgen]
-    pub async fn create_cart(&self, items: JsValue) -> Result<JsValue, JsValue> {
-        let cart_items: Vec<CartItem> = serde_wasm_bindgen::from_value(items).unwrap();
-        
-        let mut lines = String::new();
-        for (i, item) in cart_items.iter().enumerate() {
-            if i > 0 {
-                lines.push_str(", ");
-            }
-            lines.push_str(&format!(
-                r#"{{variantId: "{}", quantity: {}}}"#,
-                item.variant_id, item.quantity
-            ));
+    /// The error for an aborted request: a timeout when the signal came
+    /// from `AbortSignal.timeout()`, a cancellation otherwise.
+    fn abort_error(&self) -> error::StorefrontError {
+        let reason = self
+            .signal
+            .as_ref()
+            .and_then(|signal| js_sys::Reflect::get(signal, &JsValue::from_str("reason")).ok())
+            .and_then(|reason| js_sys::Reflect::get(&reason, &JsValue::from_str("name")).ok())
+            .and_then(|name| name.as_string());
+        match reason.as_deref() {
+            Some("TimeoutError") => error::StorefrontError::timeout("Request timed out"),
+            _ => error::StorefrontError::cancelled(),
         }
+    }
 
-        let query = format!(
-            r#"
-            mutation createCart($lines: [CartLineInput!]!) {{
-                cartCreate(lines: $lines) {{
-                    cart {{
-                        id
-                        checkoutUrl
-                        totalQuantity
-                        cost {{
-                            totalAmount {{
-                                amount
-                                currencyCode
-                            }}
-                        }}
-                        lines(first: 100) {{
-                            edges {{
-                                node {{
-                                    id
-                                    quantity
-                                    merchandise {{
-                                        ... on ProductVariant {{
-                                            id
-                                            title
-                                            price {{
-                                                amount
-                                                currencyCode
-                                            }}
-                                            product {{
-                                                title
-                                                handle
-                                            }}
-                                        }}
-                                    }}
-                                }}
-                            }}
-                        }}
-                    }}
-                    userErrors {{
-                        field
-                        message
-                    }}
-                }}
-            }}
-        "#
-        );
+    /// Passes a `graphql` log event to the installed sink, if any.
+    fn log(&self, level: logging::LogLevel, data: serde_json::Value) {
+        self.log_event(level, "graphql", data);
+    }
 
-        let variables = serde_json::json!({
-            "lines": cart_items.iter().map(|item| {
-                serde_json::json!({
-                    "variantId": item.variant_id,
-                    "quantity": item.quantity
-                })
-            }).collect::<Vec<_>>()
-        });
+    /// Passes a log event to the installed sink, if any, redacted so the
+    /// access token and customer details never reach it.
+    pub(crate) fn log_event(
+        &self,
+        level: logging::LogLevel,
+        logger: &str,
+        mut data: serde_json::Value,
+    ) {
+        if let Some(sink) = &self.log_sink {
+            redact::redact(&mut data, &[&self.config.access_token]);
+            sink(level, logger, data);
+        }
+    }
 
-        self.query(query, Some(serde_wasm_bindgen::to_value(&variables).unwrap()))
-            .await
+    /// Calls the `fetch` given to `with_fetch`, or else the global one,
+    /// which exists on `window`, in workers and in Node 18+, so the client
+    /// also runs outside the browser.
+    fn fetch(&self, request: &Request) -> Result<js_sys::Promise, JsValue> {
+        let fetch = match &self.fetch {
+            Some(fetch) => fetch.clone(),
+            None => js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("fetch"))?
+                .dyn_into()
+                .map_err(|_| {
+                    JsValue::from_str(
+                        "fetch is not available in this environment; pass one to with_fetch",
+                    )
+                })?,
+        };
+        let promise = fetch.call1(&JsValue::NULL, request)?;
+        // Custom fetches may return a thenable rather than a real Promise.
+        Ok(js_sys::Promise::resolve(&promise))
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CartItem {
-    pub variant_id: String,
-    pub quantity: u32,
+/// Converts a Rust value into a plain JavaScript value, emitting maps as objects
+/// rather than ES2015 `Map`s. Goes through a JSON string and `JSON.parse`, so
+/// one string crosses the boundary instead of a call per value; see
+/// benches/serialization.rs.
+pub(crate) fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsValue> {
+    let json = serde_json::to_string(value).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    js_sys::JSON::parse(&json)
 }
 
+/// Decodes a value passed in from JavaScript into a typed value.
+pub(crate) fn from_js<T: serde::de::DeserializeOwned>(value: JsValue) -> Result<T, JsValue> {
+    serde_wasm_bindgen::from_value(value).map_err(Into::into)
+}
+
+/// Decodes a JSON fragment of a response into a typed value.
+pub(crate) fn from_json<T: serde::de::DeserializeOwned>(
+    value: serde_json::Value,
+) -> Result<T, JsValue> {
+    serde_json::from_value(value)
+        .map_err(|e| JsValue::from_str(&format!("Unexpected response shape: {e}")))
+}
@@ -0,0 +1,71 @@
+//! Liquid templates, parsed and rendered in WebAssembly.
+//!
+//! `LiquidEngine.render(source, data)` renders `{{ output }}` with filters
+//! and the standard tags (`if`/`elsif`/`else`, `unless`, `case`/`when`,
+//! `for` with `else` and `limit`/`offset`/`reversed`, `tablerow`, `break`,
+//! `continue`, `assign`, `capture`, `increment`, `decrement`, `cycle`,
+//! `echo`, `liquid`, `raw` and `comment`) against plain JSON data. Values
+//! follow Liquid's rules: only `false` and `nil` are falsy, missing
+//! variables render as nothing, and `and`/`or` are evaluated right to left.
+//! Undefined variables and unknown filters are reported by
+//! `render_with_diagnostics` in lax mode, the default, and fail the render
+//! in strict mode (`set_render_mode`). `lint` checks a template without
+//! rendering it. Whitespace control (`{{-`, `-%}`) trims around tags, and
+//! `set_auto_escape` HTML-escapes output unless it ends with `| raw`.
+//!
+//! Storefront API objects passed as `product`, `collection`, `cart` and the
+//! like are turned into the drops theme templates expect (see `drops`), so
+//! `{{ product.title }}` and `{{ product.price }}` work on `getProduct`
+//! output directly, and their metafields render according to their type
+//! (see `metafields`). The `money` filters format those cent amounts with
+//! the shop's currency and money format (`set_money_format`), and `t` looks
+//! up the theme's locale files (`load_locale`), whose prefix goes in front
+//! of `routes` and drop URLs in other locales (see `routes`). `paginate`
+//! pages through a collection's products from the Storefront API once the
+//! engine has a client (`set_storefront`). `render` and `include` pull in
+//! snippets registered with `register_snippet`; `render` gives each its own
+//! variables. Sections are registered the same way, with their
+//! `{% schema %}`, and rendered from JSON template data with
+//! `render_section` or the `section` tag. Whole pages render from JSON
+//! templates in their layout with `render_json_template`, and section
+//! groups with the `sections` tag (see `theme`). The theme's settings files
+//! (`load_settings_schema`, `load_settings_data`) become `settings`. `form`
+//! renders Shopify's forms marked with the Storefront mutation each stands
+//! for (see `forms`), and `set_hydration_markers` marks interactive regions
+//! for client-side hydration (see `hydration`). Apps add their own filters
+//! and tags as JavaScript callbacks with `register_filter` and
+//! `register_tag` (see `custom`). Templates rendered repeatedly can be
+//! parsed once with `compile` and rendered with `render_compiled`, and
+//! pages streamed out a section at a time with `render_stream`.
+//! `fetch_and_render` fetches exactly the data a compiled template reads
+//! and renders it (see `query`).
+
+pub mod compiled;
+pub mod custom;
+pub mod date;
+pub mod drops;
+pub mod engine;
+pub mod error;
+pub mod expression;
+pub mod filters;
+pub mod forms;
+pub mod hydration;
+pub mod lexer;
+pub mod lint;
+pub mod locales;
+pub mod metafields;
+pub mod money;
+pub mod paginate;
+pub mod parser;
+pub mod query;
+pub mod render;
+pub mod routes;
+pub mod sections;
+pub mod settings;
+pub mod snippets;
+pub mod stream;
+pub mod theme;
+pub mod value;
+
+pub use engine::LiquidEngine;
+pub use error::LiquidError;
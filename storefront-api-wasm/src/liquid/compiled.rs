@@ -0,0 +1,130 @@
+//! Compiled templates: parsed once with `compile`, rendered many times with
+//! `render_compiled`.
+//!
+//! Compiled templates are cached by name. Compiling a name again with the
+//! same source returns the cached template without parsing it; a different
+//! source replaces it.
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use super::engine::{template_variables, LiquidEngine};
+use super::error::LiquidError;
+use super::parser::Template;
+use crate::to_js;
+
+/// A parsed template, ready to render with `render_compiled`.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct CompiledTemplate {
+    name: String,
+    template: Rc<Template>,
+}
+
+#[wasm_bindgen]
+impl CompiledTemplate {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl CompiledTemplate {
+    pub fn template(&self) -> &Template {
+        &self.template
+    }
+}
+
+/// Compiled templates by name, with the source each was parsed from.
+#[derive(Debug, Clone, Default)]
+pub struct CompiledTemplates {
+    templates: BTreeMap<String, (String, Rc<Template>)>,
+}
+
+impl CompiledTemplates {
+    pub fn clear(&mut self) {
+        self.templates.clear();
+    }
+}
+
+impl LiquidEngine {
+    /// Parses `source` as template `name`, or returns the cached template
+    /// when `name` was last compiled from the same source.
+    pub fn compile_template(
+        &self,
+        name: &str,
+        source: &str,
+    ) -> Result<CompiledTemplate, LiquidError> {
+        if let Some((cached_source, template)) = self.compiled.borrow().templates.get(name) {
+            if cached_source == source {
+                return Ok(CompiledTemplate {
+                    name: name.to_string(),
+                    template: Rc::clone(template),
+                });
+            }
+        }
+        let template = Rc::new(self.parse_template(source)?);
+        self.compiled
+            .borrow_mut()
+            .templates
+            .insert(name.to_string(), (source.to_string(), Rc::clone(&template)));
+        Ok(CompiledTemplate {
+            name: name.to_string(),
+            template,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Parses a template once for `render_compiled`, e.g. a product card
+    /// rendered for every product in a grid. Fails with the first syntax
+    /// error.
+    #[wasm_bindgen]
+    pub fn compile(&self, name: &str, source: &str) -> Result<CompiledTemplate, JsValue> {
+        Ok(self.compile_template(name, source)?)
+    }
+
+    /// Renders a compiled template with the variables in `data`, as
+    /// `render` does.
+    #[wasm_bindgen]
+    pub async fn render_compiled(
+        &self,
+        template: &CompiledTemplate,
+        #[wasm_bindgen(unchecked_optional_param_type = "TemplateData")] data: JsValue,
+    ) -> Result<String, JsValue> {
+        let variables = template_variables(data)?;
+        Ok(self.render_template(template.template(), variables).await?)
+    }
+
+    /// Like `render_compiled`, returning `{ output, diagnostics }` as
+    /// `render_with_diagnostics` does.
+    #[wasm_bindgen(unchecked_return_type = "Rendered")]
+    pub async fn render_compiled_with_diagnostics(
+        &self,
+        template: &CompiledTemplate,
+        #[wasm_bindgen(unchecked_optional_param_type = "TemplateData")] data: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let variables = template_variables(data)?;
+        to_js(
+            &self
+                .render_with_warnings(template.template(), variables)
+                .await?,
+        )
+    }
+
+    /// Drops a compiled template from the cache, returning whether it was
+    /// cached. Handles already returned by `compile` keep working.
+    #[wasm_bindgen]
+    pub fn remove_compiled(&self, name: &str) -> bool {
+        self.compiled.borrow_mut().templates.remove(name).is_some()
+    }
+
+    /// Names of the cached compiled templates.
+    #[wasm_bindgen]
+    pub fn compiled_names(&self) -> Vec<String> {
+        self.compiled.borrow().templates.keys().cloned().collect()
+    }
+}
@@ -0,0 +1,251 @@
+//! Filters and tags registered from JavaScript.
+//!
+//! A filter callback is called as `callback(input, args, options)`: the
+//! value being filtered, the positional arguments as an array and the
+//! `key: value` arguments as an object. `{{ customer | loyalty_points:
+//! 'gold', round: true }}` calls `callback(customer, ["gold"], { round:
+//! true })`. A tag callback is called as `callback(args, options, { body,
+//! variables })`, where `body` is the rendered body of a block tag and
+//! `variables` the variables in scope; what it returns is written out.
+//! Either may return a promise, which rendering awaits.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+use super::engine::LiquidEngine;
+use super::error::LiquidError;
+use super::filters::FilterArgs;
+use super::parser::{is_built_in_tag, CustomTag, TagKind};
+use super::render::Context;
+use super::value::to_text;
+use crate::{from_js, to_js};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TagOptions {
+    /// The tag wraps a body closed by `end<name>`.
+    pub block: bool,
+}
+
+/// Callbacks registered with `register_filter` and `register_tag`.
+#[derive(Debug, Clone, Default)]
+pub struct Extensions {
+    filters: BTreeMap<String, js_sys::Function>,
+    tags: BTreeMap<String, (TagKind, js_sys::Function)>,
+}
+
+impl Extensions {
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.contains_key(name)
+    }
+
+    /// The registered tags, as the parser needs them.
+    pub fn tag_kinds(&self) -> BTreeMap<String, TagKind> {
+        self.tags
+            .iter()
+            .map(|(name, (kind, _))| (name.clone(), *kind))
+            .collect()
+    }
+}
+
+/// Filter and tag names are Liquid identifiers.
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// The message of a thrown error or rejection.
+pub(crate) fn js_error(error: JsValue) -> String {
+    match error.dyn_ref::<js_sys::Error>() {
+        Some(error) => String::from(error.message()),
+        None => error
+            .as_string()
+            .unwrap_or_else(|| "The callback failed".to_string()),
+    }
+}
+
+/// Calls `callback` and awaits what it returns, so sync and async
+/// callbacks look alike.
+async fn call(
+    callback: &js_sys::Function,
+    first: &JsValue,
+    second: &JsValue,
+    third: &JsValue,
+) -> Result<JsValue, String> {
+    let returned = callback
+        .call3(&JsValue::NULL, first, second, third)
+        .map_err(js_error)?;
+    JsFuture::from(js_sys::Promise::resolve(&returned))
+        .await
+        .map_err(js_error)
+}
+
+fn js(value: &impl serde::Serialize) -> Result<JsValue, String> {
+    to_js(value).map_err(js_error)
+}
+
+impl LiquidEngine {
+    /// Applies a filter registered from JavaScript, if `name` is one.
+    pub(crate) async fn custom_filter(
+        &self,
+        name: &str,
+        input: &Value,
+        args: &FilterArgs,
+    ) -> Option<Result<Value, String>> {
+        let callback = self.extensions.borrow().filters.get(name).cloned()?;
+        let result = async {
+            let returned = call(
+                &callback,
+                &js(input)?,
+                &js(&args.positional)?,
+                &js(&args.keywords)?,
+            )
+            .await?;
+            if returned.is_undefined() {
+                return Ok(Value::Null);
+            }
+            from_js(returned).map_err(js_error)
+        };
+        Some(result.await)
+    }
+
+    pub(crate) async fn render_custom_tag(
+        &self,
+        tag: &CustomTag,
+        context: &mut Context,
+        output: &mut String,
+    ) -> Result<(), LiquidError> {
+        let fail = |message: String| LiquidError::at(message, tag.position);
+        let callback = self
+            .extensions
+            .borrow()
+            .tags
+            .get(&tag.name)
+            .map(|(_, callback)| callback.clone())
+            .ok_or_else(|| fail(format!("Tag '{}' is no longer registered", tag.name)))?;
+
+        let mut args = Vec::new();
+        for arg in &tag.args {
+            args.push(self.evaluate(arg, context).map_err(fail)?);
+        }
+        let mut keywords = Map::new();
+        for (key, value) in &tag.keywords {
+            keywords.insert(key.clone(), self.evaluate(value, context).map_err(fail)?);
+        }
+        let body = match &tag.body {
+            Some(nodes) => {
+                let mut body = String::new();
+                context.push();
+                let rendered = self.render_nodes(nodes, context, &mut body).await;
+                context.pop();
+                rendered?;
+                Some(body)
+            }
+            None => None,
+        };
+        let scope = serde_json::json!({
+            "body": body,
+            "variables": context.variables(),
+        });
+
+        let returned = call(
+            &callback,
+            &js(&args).map_err(fail)?,
+            &js(&keywords).map_err(fail)?,
+            &js(&scope).map_err(fail)?,
+        )
+        .await
+        .map_err(|error| fail(format!("{}: {error}", tag.name)))?;
+        if let Some(text) = returned.as_string() {
+            output.push_str(&text);
+        } else if !returned.is_undefined() && !returned.is_null() {
+            let value: Value = from_js(returned).map_err(|error| fail(js_error(error)))?;
+            output.push_str(&to_text(&value));
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Adds a filter implemented in JavaScript, called as
+    /// `callback(input, args, options)` and returning the filtered value or
+    /// a promise of it. A filter with a built-in's name replaces it.
+    #[wasm_bindgen]
+    pub fn register_filter(
+        &self,
+        name: &str,
+        #[wasm_bindgen(unchecked_param_type = "LiquidFilter")] callback: js_sys::Function,
+    ) -> Result<(), JsValue> {
+        if !valid_name(name) {
+            return Err(JsValue::from_str(&format!("Invalid filter name: {name}")));
+        }
+        self.extensions
+            .borrow_mut()
+            .filters
+            .insert(name.to_string(), callback);
+        Ok(())
+    }
+
+    /// Removes a registered filter, returning whether it existed.
+    #[wasm_bindgen]
+    pub fn remove_filter(&self, name: &str) -> bool {
+        self.extensions.borrow_mut().filters.remove(name).is_some()
+    }
+
+    /// Adds a tag implemented in JavaScript, called as `callback(args,
+    /// options, { body, variables })` and returning the text to write or a
+    /// promise of it. `options` is `{ block }`: block tags wrap a body
+    /// closed by `end<name>`. Templates parsed before the tag was
+    /// registered don't know it, so the compiled template cache is
+    /// cleared.
+    #[wasm_bindgen]
+    pub fn register_tag(
+        &self,
+        name: &str,
+        #[wasm_bindgen(unchecked_param_type = "LiquidTag")] callback: js_sys::Function,
+        #[wasm_bindgen(unchecked_optional_param_type = "TagOptions")] options: Option<JsValue>,
+    ) -> Result<(), JsValue> {
+        let options: TagOptions = match options {
+            Some(options) if !options.is_undefined() && !options.is_null() => from_js(options)?,
+            _ => TagOptions::default(),
+        };
+        if !valid_name(name) {
+            return Err(JsValue::from_str(&format!("Invalid tag name: {name}")));
+        }
+        if is_built_in_tag(name) {
+            return Err(JsValue::from_str(&format!(
+                "'{name}' is a built-in tag and can't be replaced"
+            )));
+        }
+        let kind = if options.block {
+            TagKind::Block
+        } else {
+            TagKind::Simple
+        };
+        self.extensions
+            .borrow_mut()
+            .tags
+            .insert(name.to_string(), (kind, callback));
+        self.compiled.borrow_mut().clear();
+        Ok(())
+    }
+
+    /// Removes a registered tag, returning whether it existed.
+    #[wasm_bindgen]
+    pub fn remove_tag(&self, name: &str) -> bool {
+        let removed = self.extensions.borrow_mut().tags.remove(name).is_some();
+        if removed {
+            self.compiled.borrow_mut().clear();
+        }
+        removed
+    }
+}
@@ -0,0 +1,357 @@
+//! Dates for the `date` filter: parsing the values templates hold and
+//! formatting them with Ruby's `strftime` directives.
+//!
+//! Dates are ISO 8601 strings as the Storefront API returns them
+//! (`2024-03-01T09:30:00Z`), the space-separated form Shopify writes
+//! (`2024-03-01 09:30:00 -0500`), Unix timestamps in seconds, or `now` and
+//! `today`. A date keeps the offset it was written with; there is no shop
+//! time zone to convert to.
+
+use serde_json::Value;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// A calendar date and time at a UTC offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i64,
+    /// 1 to 12.
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millisecond: u32,
+    /// Minutes east of UTC.
+    pub offset: i32,
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Reads an ISO 8601-like date string a piece at a time.
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.index).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matched = self.peek() == Some(byte);
+        if matched {
+            self.index += 1;
+        }
+        matched
+    }
+
+    /// Exactly `digits` decimal digits.
+    fn number(&mut self, digits: usize) -> Option<u32> {
+        let text = self.bytes.get(self.index..self.index + digits)?;
+        if !text.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        self.index += digits;
+        std::str::from_utf8(text).ok()?.parse().ok()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.index += 1;
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.index >= self.bytes.len()
+    }
+}
+
+impl DateTime {
+    /// The date and time of a Unix timestamp, in UTC.
+    pub fn from_unix(seconds: f64) -> DateTime {
+        let milliseconds = (seconds * 1000.0).floor() as i64;
+        let seconds = milliseconds.div_euclid(1000);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let time = seconds.rem_euclid(86_400) as u32;
+        DateTime {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+            millisecond: milliseconds.rem_euclid(1000) as u32,
+            offset: 0,
+        }
+    }
+
+    /// The current time, in UTC.
+    pub fn now() -> DateTime {
+        DateTime::from_unix(js_sys::Date::now() / 1000.0)
+    }
+
+    /// Parses an ISO 8601 date (`2024-03-01`), date and time
+    /// (`2024-03-01T09:30:00.000Z`) or Shopify's `2024-03-01 09:30:00
+    /// -0500`. Without an offset the time is taken as UTC.
+    pub fn parse_iso(text: &str) -> Option<DateTime> {
+        let mut scanner = Scanner {
+            bytes: text.trim().as_bytes(),
+            index: 0,
+        };
+        let year = i64::from(scanner.number(4)?);
+        scanner.eat(b'-').then_some(())?;
+        let month = scanner.number(2)?;
+        scanner.eat(b'-').then_some(())?;
+        let day = scanner.number(2)?;
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        let mut date = DateTime {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            millisecond: 0,
+            offset: 0,
+        };
+        if scanner.at_end() {
+            return Some(date);
+        }
+
+        if !scanner.eat(b'T') && !scanner.eat(b't') {
+            scanner.eat(b' ').then_some(())?;
+        }
+        date.hour = scanner.number(2)?;
+        scanner.eat(b':').then_some(())?;
+        date.minute = scanner.number(2)?;
+        if scanner.eat(b':') {
+            date.second = scanner.number(2)?;
+            if scanner.eat(b'.') {
+                let start = scanner.index;
+                while scanner.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+                    scanner.index += 1;
+                }
+                let fraction = std::str::from_utf8(&scanner.bytes[start..scanner.index]).ok()?;
+                date.millisecond = format!("{fraction:0<3}")[..3].parse().ok()?;
+            }
+        }
+        if date.hour > 23 || date.minute > 59 || date.second > 60 {
+            return None;
+        }
+
+        scanner.skip_spaces();
+        if scanner.eat(b'Z') || scanner.eat(b'z') {
+            // UTC.
+        } else if scanner.bytes[scanner.index..].eq_ignore_ascii_case(b"UTC") {
+            scanner.index += 3;
+        } else if let Some(sign @ (b'+' | b'-')) = scanner.peek() {
+            scanner.index += 1;
+            let hours = scanner.number(2)? as i32;
+            scanner.eat(b':');
+            let minutes = if scanner.at_end() {
+                0
+            } else {
+                scanner.number(2)? as i32
+            };
+            let offset = hours * 60 + minutes;
+            date.offset = if sign == b'-' { -offset } else { offset };
+        }
+        scanner.at_end().then_some(date)
+    }
+
+    /// The date a template value stands for, if any.
+    pub fn from_value(value: &Value) -> Option<DateTime> {
+        match value {
+            Value::Number(number) => number.as_f64().map(DateTime::from_unix),
+            Value::String(text) => match text.trim() {
+                "now" | "today" => Some(DateTime::now()),
+                text => match text.parse::<i64>() {
+                    Ok(seconds) => Some(DateTime::from_unix(seconds as f64)),
+                    Err(_) => DateTime::parse_iso(text),
+                },
+            },
+            _ => None,
+        }
+    }
+
+    fn days(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day)
+    }
+
+    /// Seconds since the Unix epoch.
+    pub fn unix(&self) -> i64 {
+        self.days() * 86_400 + i64::from(self.hour * 3600 + self.minute * 60 + self.second)
+            - i64::from(self.offset) * 60
+    }
+
+    /// 0 for Sunday to 6 for Saturday.
+    fn weekday(&self) -> usize {
+        (self.days() + 4).rem_euclid(7) as usize
+    }
+
+    fn day_of_year(&self) -> i64 {
+        self.days() - days_from_civil(self.year, 1, 1) + 1
+    }
+
+    fn offset_text(&self, colon: bool) -> String {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.abs();
+        let separator = if colon { ":" } else { "" };
+        format!("{sign}{:02}{separator}{:02}", offset / 60, offset % 60)
+    }
+
+    /// Formats with `strftime` directives: `%Y-%m-%d`, `%B %-d, %Y` and so
+    /// on. The `-` (no padding), `_` (space padding), `0` (zero padding)
+    /// and `^` (upper case) flags are understood; unknown directives are
+    /// written as they are.
+    pub fn format(&self, pattern: &str) -> String {
+        let mut output = String::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                output.push(c);
+                continue;
+            }
+            let flag = chars.next_if(|c| matches!(c, '-' | '_' | '0' | '^'));
+            let Some(directive) = chars.next() else {
+                output.push('%');
+                output.extend(flag);
+                break;
+            };
+            let hour12 = match self.hour % 12 {
+                0 => 12,
+                hour => hour,
+            };
+            // Numbers with their default width and padding.
+            let number = |value: i64, width: usize, pad: char| -> String {
+                match flag.unwrap_or(pad) {
+                    '-' => value.to_string(),
+                    '_' => format!("{value:>width$}"),
+                    _ => format!("{value:0>width$}"),
+                }
+            };
+            let text = match directive {
+                'Y' => self.year.to_string(),
+                'C' => number(self.year.div_euclid(100), 2, '0'),
+                'y' => number(self.year.rem_euclid(100), 2, '0'),
+                'm' => number(self.month.into(), 2, '0'),
+                'B' => MONTHS[self.month as usize - 1].to_string(),
+                'b' | 'h' => MONTHS[self.month as usize - 1][..3].to_string(),
+                'd' => number(self.day.into(), 2, '0'),
+                'e' => number(self.day.into(), 2, '_'),
+                'j' => number(self.day_of_year(), 3, '0'),
+                'H' => number(self.hour.into(), 2, '0'),
+                'k' => number(self.hour.into(), 2, '_'),
+                'I' => number(hour12.into(), 2, '0'),
+                'l' => number(hour12.into(), 2, '_'),
+                'M' => number(self.minute.into(), 2, '0'),
+                'S' => number(self.second.into(), 2, '0'),
+                'L' => number(self.millisecond.into(), 3, '0'),
+                'p' => if self.hour < 12 { "AM" } else { "PM" }.to_string(),
+                'P' => if self.hour < 12 { "am" } else { "pm" }.to_string(),
+                'A' => WEEKDAYS[self.weekday()].to_string(),
+                'a' => WEEKDAYS[self.weekday()][..3].to_string(),
+                'u' => match self.weekday() {
+                    0 => 7,
+                    weekday => weekday,
+                }
+                .to_string(),
+                'w' => self.weekday().to_string(),
+                's' => self.unix().to_string(),
+                'z' => self.offset_text(false),
+                'Z' if self.offset == 0 => "UTC".to_string(),
+                'Z' => self.offset_text(true),
+                'F' => self.format("%Y-%m-%d"),
+                'T' | 'X' => self.format("%H:%M:%S"),
+                'D' | 'x' => self.format("%m/%d/%y"),
+                'R' => self.format("%H:%M"),
+                'r' => self.format("%I:%M:%S %p"),
+                'c' => self.format("%a %b %e %H:%M:%S %Y"),
+                'n' => "\n".to_string(),
+                't' => "\t".to_string(),
+                '%' => "%".to_string(),
+                other => {
+                    output.push('%');
+                    output.extend(flag);
+                    output.push(other);
+                    continue;
+                }
+            };
+            if flag == Some('^') {
+                output.push_str(&text.to_uppercase());
+            } else {
+                output.push_str(&text);
+            }
+        }
+        output
+    }
+}
@@ -0,0 +1,341 @@
+//! Shopify theme objects built from Storefront API data.
+//!
+//! Templates written for Online Store themes expect `product.price` in
+//! cents, `product.url`, `variant.option1`, `cart.item_count` and so on.
+//! The drops here reshape the typed Storefront structs into those objects,
+//! so the output of `getProduct` or `getCart` can be passed to a template
+//! as-is.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::metafields::{metafield_drop, metafields_drop};
+use super::value::TEXT_KEY;
+use crate::types::{
+    legacy_id, Cart, CartLine, Collection, Image, Metafield, Money, Product, ProductVariant,
+};
+use crate::{from_js, to_js};
+
+/// Title Shopify gives the only variant of a product without options.
+const DEFAULT_VARIANT_TITLE: &str = "Default Title";
+
+/// A money amount in cents, as theme templates expect it.
+pub fn cents(money: &Money) -> i64 {
+    (money.value() * 100.0).round() as i64
+}
+
+/// The numeric ID of a global ID, or the ID unchanged if it has none.
+pub fn numeric_id(gid: &str) -> Value {
+    let id = legacy_id(gid);
+    id.parse::<u64>()
+        .map(Value::from)
+        .unwrap_or_else(|_| Value::from(id))
+}
+
+pub fn image_drop(image: &Image) -> Value {
+    let aspect_ratio = match (image.width, image.height) {
+        (Some(width), Some(height)) if height > 0 => Value::from(width as f64 / height as f64),
+        _ => Value::Null,
+    };
+    serde_json::json!({
+        "id": image.id.as_deref().map(numeric_id),
+        "src": image.url,
+        "alt": image.alt_text,
+        "width": image.width,
+        "height": image.height,
+        "aspect_ratio": aspect_ratio,
+    })
+}
+
+/// A variant; `product_handle` builds its URL when the variant doesn't
+/// carry its product.
+pub fn variant_drop(variant: &ProductVariant, product_handle: Option<&str>) -> Value {
+    let id = numeric_id(&variant.id);
+    let handle = product_handle.or(variant
+        .product
+        .as_ref()
+        .map(|product| product.handle.as_str()));
+    let options: Vec<&str> = variant
+        .selected_options
+        .iter()
+        .map(|option| option.value.as_str())
+        .collect();
+    let image = variant.image.as_ref().map(image_drop);
+    serde_json::json!({
+        "id": id,
+        "title": variant.title,
+        "sku": variant.sku,
+        "barcode": variant.barcode,
+        "price": cents(&variant.price),
+        "compare_at_price": variant.compare_at_price.as_ref().map(cents),
+        "unit_price": variant.unit_price.as_ref().map(cents),
+        "available": variant.available_for_sale,
+        "inventory_quantity": variant.quantity_available,
+        "options": options,
+        "option1": options.first(),
+        "option2": options.get(1),
+        "option3": options.get(2),
+        "image": image,
+        "featured_image": image,
+        "url": handle.map(|handle| format!("/products/{handle}?variant={id}")),
+    })
+}
+
+pub fn product_drop(product: &Product) -> Value {
+    let variants: Vec<&ProductVariant> = product
+        .variants
+        .iter()
+        .flat_map(|variants| variants.nodes())
+        .collect();
+    let variant_drops: Vec<Value> = variants
+        .iter()
+        .map(|variant| variant_drop(variant, Some(&product.handle)))
+        .collect();
+    let first_available = variants
+        .iter()
+        .position(|variant| variant.available_for_sale)
+        .map(|position| variant_drops[position].clone());
+    let images: Vec<Value> = product.images.nodes().map(image_drop).collect();
+
+    let price_min = cents(&product.price_range.min_variant_price);
+    let price_max = product
+        .price_range
+        .max_variant_price
+        .as_ref()
+        .map_or(price_min, cents);
+    let compare_min = product
+        .compare_at_price_range
+        .as_ref()
+        .map(|range| cents(&range.min_variant_price))
+        .filter(|&cents| cents > 0);
+    let compare_max = product
+        .compare_at_price_range
+        .as_ref()
+        .and_then(|range| range.max_variant_price.as_ref())
+        .map(cents)
+        .filter(|&cents| cents > 0)
+        .or(compare_min);
+    let options_with_values: Vec<Value> = product
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| {
+            serde_json::json!({
+                "name": option.name,
+                "position": index + 1,
+                "values": option
+                    .option_values
+                    .iter()
+                    .map(|value| value.name.as_str())
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "id": numeric_id(&product.id),
+        "handle": product.handle,
+        "title": product.title,
+        "description": product.description,
+        "content": product.description,
+        "vendor": product.vendor,
+        "type": product.product_type,
+        "tags": product.tags,
+        "available": product
+            .available_for_sale
+            .unwrap_or_else(|| variants.iter().any(|variant| variant.available_for_sale)),
+        "price": price_min,
+        "price_min": price_min,
+        "price_max": price_max,
+        "price_varies": price_min != price_max,
+        "compare_at_price": compare_min,
+        "compare_at_price_min": compare_min,
+        "compare_at_price_max": compare_max,
+        "compare_at_price_varies": compare_min != compare_max,
+        "url": format!("/products/{}", product.handle),
+        "images": images,
+        "featured_image": images.first(),
+        "variants": variant_drops,
+        "first_available_variant": first_available,
+        "selected_or_first_available_variant": first_available
+            .clone()
+            .or_else(|| variant_drops.first().cloned()),
+        "has_only_default_variant": variants.len() == 1
+            && variants[0].title == DEFAULT_VARIANT_TITLE,
+        "options": product
+            .options
+            .iter()
+            .map(|option| option.name.as_str())
+            .collect::<Vec<_>>(),
+        "options_with_values": options_with_values,
+        "gift_card?": product.is_gift_card.unwrap_or(false),
+        "published_at": product.published_at,
+        "requires_selling_plan": product.requires_selling_plan.unwrap_or(false),
+        "metafields": metafields_drop(product.metafields.as_deref().unwrap_or_default()),
+    })
+}
+
+pub fn collection_drop(collection: &Collection) -> Value {
+    let products: Vec<Value> = collection
+        .products
+        .iter()
+        .flat_map(|products| products.nodes())
+        .map(product_drop)
+        .collect();
+    let image = collection.image.as_ref().map(image_drop);
+    serde_json::json!({
+        "id": numeric_id(&collection.id),
+        "handle": collection.handle,
+        "title": collection.title,
+        "description": collection.description,
+        "image": image,
+        "featured_image": image,
+        "url": format!("/collections/{}", collection.handle),
+        "products_count": products.len(),
+        "products": products,
+        "metafields": metafields_drop(collection.metafields.as_deref().unwrap_or_default()),
+    })
+}
+
+fn line_item_drop(line: &CartLine) -> Value {
+    let merchandise = &line.merchandise;
+    let product = &merchandise.product;
+    let variant_id = numeric_id(&merchandise.id);
+    let price = cents(&merchandise.price);
+    let line_price = price * i64::from(line.quantity);
+    let title = if merchandise.title == DEFAULT_VARIANT_TITLE {
+        product.title.clone()
+    } else {
+        format!("{} - {}", product.title, merchandise.title)
+    };
+    let properties: Map<String, Value> = line
+        .attributes
+        .iter()
+        .map(|attribute| (attribute.key.clone(), Value::from(attribute.value.as_str())))
+        .collect();
+    serde_json::json!({
+        "id": variant_id,
+        "key": line.id,
+        "quantity": line.quantity,
+        "title": title,
+        "price": price,
+        "final_price": price,
+        "line_price": line_price,
+        "final_line_price": line_price,
+        "variant_id": variant_id,
+        "product_id": numeric_id(&product.id),
+        "variant": {
+            "id": variant_id,
+            "title": merchandise.title,
+            "price": price,
+        },
+        "product": {
+            "id": numeric_id(&product.id),
+            "handle": product.handle,
+            "title": product.title,
+            "url": format!("/products/{}", product.handle),
+        },
+        "url": format!("/products/{}?variant={variant_id}", product.handle),
+        "properties": properties,
+    })
+}
+
+pub fn cart_drop(cart: &Cart) -> Value {
+    let items: Vec<Value> = cart.lines.nodes().map(line_item_drop).collect();
+    let total = cents(&cart.cost.total_amount);
+    serde_json::json!({
+        "id": cart.id,
+        "item_count": cart.total_quantity,
+        "items": items,
+        "total_price": total,
+        "items_subtotal_price": total,
+        "currency": { "iso_code": cart.cost.total_amount.currency_code },
+        "checkout_url": cart.checkout_url,
+    })
+}
+
+/// The kinds of drop, by the variable names they are usually passed as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DropKind {
+    Product,
+    Variant,
+    Collection,
+    Cart,
+    Image,
+    Metafield,
+}
+
+impl DropKind {
+    fn parse(name: &str) -> Option<DropKind> {
+        match name {
+            "product" | "products" => Some(DropKind::Product),
+            "variant" | "variants" => Some(DropKind::Variant),
+            "collection" | "collections" => Some(DropKind::Collection),
+            "cart" => Some(DropKind::Cart),
+            "image" | "images" => Some(DropKind::Image),
+            "metafield" | "metafields" => Some(DropKind::Metafield),
+            _ => None,
+        }
+    }
+
+    /// The drop for `value` if it is Storefront data of this kind.
+    fn convert(self, value: &Value) -> Option<Value> {
+        fn typed<T: DeserializeOwned>(value: &Value) -> Option<T> {
+            serde_json::from_value(value.clone()).ok()
+        }
+        // Image drops still parse as images and text metafield drops as
+        // metafields; their `src` and text give them away.
+        if value.get("src").is_some() || value.get(TEXT_KEY).is_some() {
+            return None;
+        }
+        match self {
+            DropKind::Product => typed(value).map(|product| product_drop(&product)),
+            DropKind::Variant => typed(value).map(|variant| variant_drop(&variant, None)),
+            DropKind::Collection => typed(value).map(|collection| collection_drop(&collection)),
+            DropKind::Cart => typed(value).map(|cart| cart_drop(&cart)),
+            DropKind::Image => typed(value).map(|image| image_drop(&image)),
+            DropKind::Metafield => {
+                typed::<Metafield>(value).map(|metafield| metafield_drop(&metafield))
+            }
+        }
+    }
+
+    /// Converts `value`, or each element of it, leaving anything that isn't
+    /// Storefront data alone.
+    fn wrap(self, value: Value) -> Value {
+        match value {
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|item| self.wrap(item)).collect())
+            }
+            value => self.convert(&value).unwrap_or(value),
+        }
+    }
+}
+
+/// Turns Storefront API objects passed under the usual names (`product`,
+/// `products`, `variant`, `collection`, `collections`, `cart`, `image`,
+/// `images`, `metafield`, `metafields`) into drops. Anything else, including objects that are already
+/// theme-shaped, is left as it is.
+pub fn wrap_storefront_data(variables: &mut Map<String, Value>) {
+    for (name, value) in variables.iter_mut() {
+        if let Some(kind) = DropKind::parse(name) {
+            *value = kind.wrap(value.take());
+        }
+    }
+}
+
+/// Converts Storefront API data to the Liquid object themes use. `kind` is
+/// `"product"`, `"variant"`, `"collection"`, `"cart"`, `"image"` or
+/// `"metafield"`;
+/// arrays are converted element by element.
+#[wasm_bindgen(unchecked_return_type = "Record<string, unknown> | Record<string, unknown>[]")]
+pub fn liquid_drop(
+    kind: &str,
+    #[wasm_bindgen(unchecked_param_type = "LiquidDropInput | LiquidDropInput[]")] value: JsValue,
+) -> Result<JsValue, JsValue> {
+    let kind = DropKind::parse(kind)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown drop kind: {kind}")))?;
+    let value: Value = from_js(value)?;
+    to_js(&kind.wrap(value))
+}
@@ -0,0 +1,239 @@
+//! The template engine and its JavaScript API.
+
+use std::cell::{Cell, RefCell};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::compiled::CompiledTemplates;
+use super::custom::Extensions;
+use super::drops::wrap_storefront_data;
+use super::error::{Diagnostic, LiquidError};
+use super::hydration::HydrationMarker;
+use super::locales::Locales;
+use super::money::MoneyFormat;
+use super::paginate::CursorCache;
+use super::parser::{parse_with_tags, Template};
+use super::render::Context;
+use super::routes::Routes;
+use super::sections::Sections;
+use super::settings::ThemeSettings;
+use super::snippets::Snippets;
+use super::theme::Theme;
+use crate::{from_js, to_js, StorefrontApi};
+
+/// What rendering does with undefined variables and unknown filters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Render them as nothing (unknown filters pass their input through)
+    /// and report a warning, as Shopify does.
+    #[default]
+    Lax,
+    /// Fail with the line and column.
+    Strict,
+}
+
+impl RenderMode {
+    pub fn parse(mode: &str) -> Result<RenderMode, String> {
+        match mode {
+            "lax" => Ok(RenderMode::Lax),
+            "strict" => Ok(RenderMode::Strict),
+            other => Err(format!(
+                "Unknown render mode: {other}. Use \"lax\" or \"strict\""
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RenderMode::Lax => "lax",
+            RenderMode::Strict => "strict",
+        }
+    }
+}
+
+/// Rendered output and the warnings found on the way.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rendered {
+    pub output: String,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The marked regions, when hydration markers are on.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hydration: Vec<HydrationMarker>,
+}
+
+/// The variables in a render's `data` (an object; may be omitted), with
+/// Storefront objects turned into theme drops.
+pub(crate) fn template_variables(data: JsValue) -> Result<Map<String, Value>, JsValue> {
+    let mut variables: Map<String, Value> = if data.is_undefined() || data.is_null() {
+        Map::new()
+    } else {
+        from_js(data)?
+    };
+    wrap_storefront_data(&mut variables);
+    Ok(variables)
+}
+
+/// Parses and renders Liquid templates.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct LiquidEngine {
+    pub(crate) mode: Cell<RenderMode>,
+    pub(crate) money: RefCell<MoneyFormat>,
+    pub(crate) locales: RefCell<Locales>,
+    /// Client `paginate` fetches collection pages with.
+    pub(crate) storefront: RefCell<Option<StorefrontApi>>,
+    pub(crate) cursors: RefCell<CursorCache>,
+    pub(crate) snippets: RefCell<Snippets>,
+    pub(crate) sections: RefCell<Sections>,
+    pub(crate) extensions: RefCell<Extensions>,
+    pub(crate) compiled: RefCell<CompiledTemplates>,
+    pub(crate) settings: RefCell<ThemeSettings>,
+    pub(crate) routes: RefCell<Routes>,
+    pub(crate) theme: RefCell<Theme>,
+    /// Mark interactive regions for client-side hydration.
+    pub(crate) hydration: Cell<bool>,
+    /// HTML-escape `{{ output }}` unless it ends with `raw`.
+    pub(crate) auto_escape: Cell<bool>,
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> LiquidEngine {
+        LiquidEngine::default()
+    }
+
+    /// Renders `source` with the variables in `data` (an object; may be
+    /// omitted). Storefront objects under `product`, `collection`, `cart`
+    /// and similar names become theme drops. Fails with the line and column
+    /// of the first syntax or rendering error.
+    #[wasm_bindgen]
+    pub async fn render(
+        &self,
+        source: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "TemplateData")] data: JsValue,
+    ) -> Result<String, JsValue> {
+        let variables = template_variables(data)?;
+        let template = self.parse_template(&source)?;
+        Ok(self.render_template(&template, variables).await?)
+    }
+
+    /// Like `render`, returning `{ output, diagnostics }` where each
+    /// diagnostic is `{ severity, message, line, column, source }`: the
+    /// undefined variables and unknown filters lax mode rendered past. With
+    /// hydration markers on, `hydration` lists the marked regions.
+    #[wasm_bindgen(unchecked_return_type = "Rendered")]
+    pub async fn render_with_diagnostics(
+        &self,
+        source: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "TemplateData")] data: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let variables = template_variables(data)?;
+        let template = self.parse_template(&source)?;
+        to_js(&self.render_with_warnings(&template, variables).await?)
+    }
+
+    /// `"lax"` (the default) renders undefined variables as nothing and
+    /// passes values through unknown filters, reporting both as
+    /// diagnostics; `"strict"` fails on them.
+    #[wasm_bindgen]
+    pub fn set_render_mode(&self, mode: &str) -> Result<(), JsValue> {
+        let mode = RenderMode::parse(mode).map_err(|error| JsValue::from_str(&error))?;
+        self.mode.set(mode);
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn render_mode(&self) -> String {
+        self.mode.get().as_str().to_string()
+    }
+
+    /// HTML-escapes every `{{ output }}` and `echo`, so text from
+    /// shoppers (cart attributes, line item properties, search terms)
+    /// can't inject markup. `| raw` opts an output out, as do `escape`,
+    /// `json` and `t` with a key ending in `_html`. Off by default, as on
+    /// Shopify.
+    #[wasm_bindgen]
+    pub fn set_auto_escape(&self, enabled: bool) {
+        self.auto_escape.set(enabled);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn auto_escape(&self) -> bool {
+        self.auto_escape.get()
+    }
+
+    /// Marks forms, variant pickers and add to cart buttons with
+    /// `data-hydrate` and `data-hydrate-id` attributes (see `hydration`).
+    #[wasm_bindgen]
+    pub fn set_hydration_markers(&self, enabled: bool) {
+        self.hydration.set(enabled);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hydration_markers(&self) -> bool {
+        self.hydration.get()
+    }
+
+    /// Checks that `source` parses, failing with the first syntax error.
+    #[wasm_bindgen]
+    pub fn validate(&self, source: &str) -> Result<(), JsValue> {
+        self.parse_template(source)?;
+        Ok(())
+    }
+}
+
+impl LiquidEngine {
+    /// Parses `source`, accepting the tags registered with `register_tag`.
+    pub fn parse_template(&self, source: &str) -> Result<Template, LiquidError> {
+        parse_with_tags(source, self.extensions.borrow().tag_kinds())
+    }
+
+    /// The context a page renders in: its variables plus the theme's.
+    pub(crate) fn page_context(&self, mut variables: Map<String, Value>) -> Context {
+        self.add_settings(&mut variables);
+        self.add_routes(&mut variables);
+        let mut context = Context::new(variables);
+        if self.hydration.get() {
+            context.enable_hydration();
+        }
+        context
+    }
+
+    pub(crate) fn strict(&self) -> bool {
+        self.mode.get() == RenderMode::Strict
+    }
+
+    pub async fn render_template(
+        &self,
+        template: &Template,
+        variables: Map<String, Value>,
+    ) -> Result<String, LiquidError> {
+        Ok(self.render_with_warnings(template, variables).await?.output)
+    }
+
+    pub async fn render_with_warnings(
+        &self,
+        template: &Template,
+        variables: Map<String, Value>,
+    ) -> Result<Rendered, LiquidError> {
+        let mut context = self.page_context(variables);
+        let mut output = String::new();
+        self.render_nodes(&template.nodes, &mut context, &mut output)
+            .await?;
+        Ok(Rendered {
+            output,
+            diagnostics: context
+                .warnings()
+                .into_iter()
+                .map(Diagnostic::from)
+                .collect(),
+            hydration: context
+                .hydration()
+                .map(|hydration| hydration.borrow().markers())
+                .unwrap_or_default(),
+        })
+    }
+}
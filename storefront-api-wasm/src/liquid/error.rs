@@ -0,0 +1,139 @@
+//! Errors raised while parsing or rendering a template.
+
+use std::fmt;
+
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+/// A line and column in the template source, both starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub const START: Position = Position { line: 1, column: 1 };
+
+    /// The position after `text`, starting from this one.
+    pub fn advance(self, text: &str) -> Position {
+        text.chars().fold(self, |position, c| match c {
+            '\n' => Position {
+                line: position.line + 1,
+                column: 1,
+            },
+            _ => Position {
+                column: position.column + 1,
+                ..position
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidError {
+    pub message: String,
+    /// Where the offending tag or output starts, when known.
+    pub position: Option<Position>,
+    /// The snippet or section `position` is in, as `snippet 'name'`, when
+    /// not the template itself.
+    pub source: Option<String>,
+}
+
+impl LiquidError {
+    pub fn new(message: impl Into<String>) -> LiquidError {
+        LiquidError {
+            message: message.into(),
+            position: None,
+            source: None,
+        }
+    }
+
+    pub fn at(message: impl Into<String>, position: Position) -> LiquidError {
+        LiquidError {
+            message: message.into(),
+            position: Some(position),
+            source: None,
+        }
+    }
+
+    /// Marks an error raised in snippet `name`. Errors keep the innermost
+    /// snippet or section they came from.
+    pub fn in_snippet(self, name: &str) -> LiquidError {
+        self.in_source(format!("snippet '{name}'"))
+    }
+
+    pub fn in_section(self, name: &str) -> LiquidError {
+        self.in_source(format!("section '{name}'"))
+    }
+
+    pub fn in_layout(self, name: &str) -> LiquidError {
+        self.in_source(format!("layout '{name}'"))
+    }
+
+    fn in_source(mut self, source: String) -> LiquidError {
+        if self.source.is_none() {
+            self.source = Some(source);
+        }
+        self
+    }
+}
+
+impl fmt::Display for LiquidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.source, self.position) {
+            (Some(source), Some(Position { line, column })) => write!(
+                f,
+                "Liquid error ({source}, line {line}, column {column}): {}",
+                self.message
+            ),
+            (None, Some(Position { line, column })) => write!(
+                f,
+                "Liquid error (line {line}, column {column}): {}",
+                self.message
+            ),
+            (Some(source), None) => write!(f, "Liquid error ({source}): {}", self.message),
+            (None, None) => write!(f, "Liquid error: {}", self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The template fails to parse or render.
+    Error,
+    /// The template renders, perhaps not as intended.
+    Warning,
+}
+
+/// A problem reported without stopping the render, such as an undefined
+/// variable in lax mode, or found by `lint`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// The snippet or section it was found in, as `snippet 'name'`.
+    pub source: Option<String>,
+}
+
+impl From<LiquidError> for Diagnostic {
+    fn from(error: LiquidError) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            message: error.message,
+            line: error.position.map(|position| position.line),
+            column: error.position.map(|position| position.column),
+            source: error.source,
+        }
+    }
+}
+
+impl From<LiquidError> for JsValue {
+    fn from(error: LiquidError) -> JsValue {
+        JsValue::from_str(&error.to_string())
+    }
+}
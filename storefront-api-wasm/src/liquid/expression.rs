@@ -0,0 +1,567 @@
+//! The markup inside output and tags: literals, variable lookups, ranges,
+//! filter chains and conditions.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Lexeme {
+    Ident(String),
+    Str(String),
+    Number(Value),
+    Comparison(Operator),
+    Dot,
+    DotDot,
+    OpenSquare,
+    CloseSquare,
+    OpenParen,
+    CloseParen,
+    Pipe,
+    Colon,
+    Comma,
+    Equals,
+}
+
+impl Lexeme {
+    fn describe(&self) -> String {
+        match self {
+            Lexeme::Ident(name) => format!("'{name}'"),
+            Lexeme::Str(text) => format!("string \"{text}\""),
+            Lexeme::Number(number) => format!("number {number}"),
+            Lexeme::Comparison(operator) => format!("'{}'", operator.symbol()),
+            Lexeme::Dot => "'.'".to_string(),
+            Lexeme::DotDot => "'..'".to_string(),
+            Lexeme::OpenSquare => "'['".to_string(),
+            Lexeme::CloseSquare => "']'".to_string(),
+            Lexeme::OpenParen => "'('".to_string(),
+            Lexeme::CloseParen => "')'".to_string(),
+            Lexeme::Pipe => "'|'".to_string(),
+            Lexeme::Colon => "':'".to_string(),
+            Lexeme::Comma => "','".to_string(),
+            Lexeme::Equals => "'='".to_string(),
+        }
+    }
+}
+
+/// Positional values and `key: value` pairs.
+pub type Parameters = (Vec<Expr>, Vec<(String, Expr)>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+    Contains,
+}
+
+impl Operator {
+    fn symbol(self) -> &'static str {
+        match self {
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::Greater => ">",
+            Operator::LessOrEqual => "<=",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Contains => "contains",
+        }
+    }
+}
+
+fn lex(markup: &str) -> Result<Vec<Lexeme>, String> {
+    let chars: Vec<char> = markup.chars().collect();
+    let mut lexemes = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (lexeme, length) = match c {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '\'' | '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&other| other == c)
+                    .ok_or("Unterminated string")?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                (Lexeme::Str(text), end + 2)
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let mut end = i + 1;
+                while end < chars.len() && chars[end].is_ascii_digit() {
+                    end += 1;
+                }
+                // A dot is a decimal point only when a digit follows; `1..5`
+                // is a range.
+                let float = chars.get(end) == Some(&'.')
+                    && chars.get(end + 1).is_some_and(|c| c.is_ascii_digit());
+                if float {
+                    end += 1;
+                    while end < chars.len() && chars[end].is_ascii_digit() {
+                        end += 1;
+                    }
+                }
+                let text: String = chars[i..end].iter().collect();
+                let number = if float {
+                    text.parse::<f64>()
+                        .map(Value::from)
+                        .map_err(|e| e.to_string())?
+                } else {
+                    text.parse::<i64>()
+                        .map(Value::from)
+                        .map_err(|e| e.to_string())?
+                };
+                (Lexeme::Number(number), end - i)
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut end = i + 1;
+                while end < chars.len()
+                    && (chars[end].is_alphanumeric() || matches!(chars[end], '_' | '-'))
+                {
+                    end += 1;
+                }
+                if chars.get(end) == Some(&'?') {
+                    end += 1;
+                }
+                (Lexeme::Ident(chars[i..end].iter().collect()), end - i)
+            }
+            '=' if next == Some('=') => (Lexeme::Comparison(Operator::Equal), 2),
+            '!' if next == Some('=') => (Lexeme::Comparison(Operator::NotEqual), 2),
+            '<' if next == Some('>') => (Lexeme::Comparison(Operator::NotEqual), 2),
+            '<' if next == Some('=') => (Lexeme::Comparison(Operator::LessOrEqual), 2),
+            '>' if next == Some('=') => (Lexeme::Comparison(Operator::GreaterOrEqual), 2),
+            '<' => (Lexeme::Comparison(Operator::Less), 1),
+            '>' => (Lexeme::Comparison(Operator::Greater), 1),
+            '=' => (Lexeme::Equals, 1),
+            '.' if next == Some('.') => (Lexeme::DotDot, 2),
+            '.' => (Lexeme::Dot, 1),
+            '[' => (Lexeme::OpenSquare, 1),
+            ']' => (Lexeme::CloseSquare, 1),
+            '(' => (Lexeme::OpenParen, 1),
+            ')' => (Lexeme::CloseParen, 1),
+            '|' => (Lexeme::Pipe, 1),
+            ':' => (Lexeme::Colon, 1),
+            ',' => (Lexeme::Comma, 1),
+            _ => return Err(format!("Unexpected character '{c}'")),
+        };
+        lexemes.push(lexeme);
+        i += length;
+    }
+    Ok(lexemes)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    /// `empty`: equal to empty strings, arrays and objects.
+    Empty,
+    /// `blank`: like `empty`, and also `nil`, `false` and whitespace.
+    Blank,
+    /// `product.variants[0].title`; the first segment is always a key.
+    Variable(Vec<Segment>),
+    /// `(1..limit)`.
+    Range(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(String),
+    Index(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub name: String,
+    pub args: Vec<Expr>,
+    /// `name: value` arguments, in order.
+    pub keywords: Vec<(String, Expr)>,
+}
+
+/// An expression followed by filters: `product.title | upcase`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Output {
+    pub expr: Expr,
+    pub filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Test(Expr),
+    Compare(Expr, Operator, Expr),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    /// The first branch of `unless`.
+    Not(Box<Condition>),
+}
+
+/// A cursor over the lexemes of one tag's markup.
+pub struct Markup {
+    lexemes: Vec<Lexeme>,
+    index: usize,
+}
+
+impl Markup {
+    pub fn new(markup: &str) -> Result<Markup, String> {
+        Ok(Markup {
+            lexemes: lex(markup)?,
+            index: 0,
+        })
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.index >= self.lexemes.len()
+    }
+
+    fn peek(&self) -> Option<&Lexeme> {
+        self.lexemes.get(self.index)
+    }
+
+    fn eat(&mut self, lexeme: &Lexeme) -> bool {
+        let matched = self.peek() == Some(lexeme);
+        if matched {
+            self.index += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, lexeme: Lexeme) -> Result<(), String> {
+        if self.eat(&lexeme) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("Expected {}", lexeme.describe())))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(lexeme) => format!("{expected}, found {}", lexeme.describe()),
+            None => format!("{expected}, found the end of the tag"),
+        }
+    }
+
+    /// Fails unless the whole markup has been read.
+    pub fn finish(&self) -> Result<(), String> {
+        match self.peek() {
+            Some(lexeme) => Err(format!("Unexpected {}", lexeme.describe())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Lexeme::Ident(name)) => {
+                let name = name.clone();
+                self.index += 1;
+                Ok(name)
+            }
+            _ => Err(self.unexpected("Expected a name")),
+        }
+    }
+
+    /// Consumes `word` if it comes next.
+    pub fn keyword(&mut self, word: &str) -> bool {
+        self.eat(&Lexeme::Ident(word.to_string()))
+    }
+
+    /// Consumes a single `=`, as in `assign`.
+    pub fn equals(&mut self) -> Result<(), String> {
+        self.expect(Lexeme::Equals)
+    }
+
+    pub fn expression(&mut self) -> Result<Expr, String> {
+        let lexeme = match self.peek() {
+            Some(
+                lexeme @ (Lexeme::Str(_)
+                | Lexeme::Number(_)
+                | Lexeme::OpenParen
+                | Lexeme::Ident(_)
+                | Lexeme::OpenSquare),
+            ) => lexeme.clone(),
+            _ => return Err(self.unexpected("Expected a value")),
+        };
+        self.index += 1;
+        match lexeme {
+            Lexeme::Str(text) => Ok(Expr::Literal(Value::String(text))),
+            Lexeme::Number(number) => Ok(Expr::Literal(number)),
+            Lexeme::OpenParen => {
+                let start = self.expression()?;
+                self.expect(Lexeme::DotDot)?;
+                let end = self.expression()?;
+                self.expect(Lexeme::CloseParen)?;
+                Ok(Expr::Range(Box::new(start), Box::new(end)))
+            }
+            Lexeme::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "nil" | "null" => Ok(Expr::Literal(Value::Null)),
+                "empty" => Ok(Expr::Empty),
+                "blank" => Ok(Expr::Blank),
+                _ => self.path(vec![Segment::Key(name)]),
+            },
+            _ => {
+                let key = self.expression()?;
+                self.expect(Lexeme::CloseSquare)?;
+                self.path(vec![Segment::Index(key)])
+            }
+        }
+    }
+
+    fn path(&mut self, mut segments: Vec<Segment>) -> Result<Expr, String> {
+        loop {
+            if self.eat(&Lexeme::Dot) {
+                segments.push(Segment::Key(self.ident()?));
+            } else if self.eat(&Lexeme::OpenSquare) {
+                segments.push(Segment::Index(self.expression()?));
+                self.expect(Lexeme::CloseSquare)?;
+            } else {
+                return Ok(Expr::Variable(segments));
+            }
+        }
+    }
+
+    /// `| name: arg, key: value | ...`
+    pub fn filters(&mut self) -> Result<Vec<Filter>, String> {
+        let mut filters = Vec::new();
+        while self.eat(&Lexeme::Pipe) {
+            let name = self.ident()?;
+            let mut filter = Filter {
+                name,
+                args: Vec::new(),
+                keywords: Vec::new(),
+            };
+            if self.eat(&Lexeme::Colon) {
+                (filter.args, filter.keywords) = self.parameters()?;
+            }
+            filters.push(filter);
+        }
+        Ok(filters)
+    }
+
+    /// Comma-separated values and `key: value` pairs in any order, as
+    /// filters and custom tags take them: `'gold', size: 'small'`.
+    pub fn parameters(&mut self) -> Result<Parameters, String> {
+        let mut args = Vec::new();
+        let mut keywords = Vec::new();
+        loop {
+            if self.at_argument() {
+                let key = self.ident()?;
+                self.index += 1;
+                keywords.push((key, self.expression()?));
+            } else {
+                args.push(self.expression()?);
+            }
+            if !self.eat(&Lexeme::Comma) {
+                break;
+            }
+        }
+        Ok((args, keywords))
+    }
+
+    /// Consumes a comma if it comes next.
+    pub fn comma(&mut self) -> bool {
+        self.eat(&Lexeme::Comma)
+    }
+
+    /// Whether a `key: value` argument comes next.
+    pub fn at_argument(&self) -> bool {
+        matches!(
+            (self.peek(), self.lexemes.get(self.index + 1)),
+            (Some(Lexeme::Ident(_)), Some(Lexeme::Colon))
+        )
+    }
+
+    /// Consumes a colon if it comes next.
+    pub fn colon(&mut self) -> bool {
+        self.eat(&Lexeme::Colon)
+    }
+
+    /// `key: value` pairs, as tags take options (`window_size: 3`). Commas
+    /// between them are optional, as `tablerow item in items cols:3
+    /// limit:6` leaves them out.
+    pub fn arguments(&mut self) -> Result<Vec<(String, Expr)>, String> {
+        let mut arguments = Vec::new();
+        loop {
+            self.eat(&Lexeme::Comma);
+            if self.at_end() {
+                break;
+            }
+            let key = self.ident()?;
+            self.expect(Lexeme::Colon)?;
+            arguments.push((key, self.expression()?));
+        }
+        Ok(arguments)
+    }
+
+    pub fn output(&mut self) -> Result<Output, String> {
+        let expr = self.expression()?;
+        let filters = self.filters()?;
+        Ok(Output { expr, filters })
+    }
+
+    /// Conditions joined by `and`/`or`, which Liquid evaluates right to
+    /// left without precedence: `a or b and c` is `a or (b and c)`.
+    pub fn condition(&mut self) -> Result<Condition, String> {
+        let mut tests = vec![self.comparison()?];
+        let mut joins = Vec::new();
+        loop {
+            if self.keyword("and") {
+                joins.push(true);
+            } else if self.keyword("or") {
+                joins.push(false);
+            } else {
+                break;
+            }
+            tests.push(self.comparison()?);
+        }
+        let mut condition = tests.pop().expect("at least one test");
+        while let (Some(test), Some(and)) = (tests.pop(), joins.pop()) {
+            condition = if and {
+                Condition::And(Box::new(test), Box::new(condition))
+            } else {
+                Condition::Or(Box::new(test), Box::new(condition))
+            };
+        }
+        Ok(condition)
+    }
+
+    fn comparison(&mut self) -> Result<Condition, String> {
+        let left = self.expression()?;
+        let operator = match self.peek() {
+            Some(Lexeme::Comparison(operator)) => *operator,
+            Some(Lexeme::Ident(word)) if word == "contains" => Operator::Contains,
+            _ => return Ok(Condition::Test(left)),
+        };
+        self.index += 1;
+        let right = self.expression()?;
+        Ok(Condition::Compare(left, operator, right))
+    }
+}
+
+/// Parses the markup of `{{ ... }}`.
+pub fn parse_output(markup: &str) -> Result<Output, String> {
+    let mut parser = Markup::new(markup)?;
+    let output = parser.output()?;
+    parser.finish()?;
+    Ok(output)
+}
+
+/// Parses the condition of `if` and `elsif`.
+pub fn parse_condition(markup: &str) -> Result<Condition, String> {
+    let mut parser = Markup::new(markup)?;
+    let condition = parser.condition()?;
+    parser.finish()?;
+    Ok(condition)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn variable(name: &str) -> Expr {
+        Expr::Variable(vec![Segment::Key(name.to_string())])
+    }
+
+    #[test]
+    fn outputs_parse_paths_and_filters() {
+        let output = parse_output("product.variants[0] | join: ', ', last: true | size").unwrap();
+
+        assert_eq!(
+            output.expr,
+            Expr::Variable(vec![
+                Segment::Key("product".to_string()),
+                Segment::Key("variants".to_string()),
+                Segment::Index(Expr::Literal(json!(0))),
+            ])
+        );
+        assert_eq!(
+            output.filters,
+            vec![
+                Filter {
+                    name: "join".to_string(),
+                    args: vec![Expr::Literal(json!(", "))],
+                    keywords: vec![("last".to_string(), Expr::Literal(json!(true)))],
+                },
+                Filter {
+                    name: "size".to_string(),
+                    args: Vec::new(),
+                    keywords: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ranges_and_numbers() {
+        assert_eq!(
+            parse_output("(1..limit)").unwrap().expr,
+            Expr::Range(
+                Box::new(Expr::Literal(json!(1))),
+                Box::new(variable("limit"))
+            )
+        );
+        assert_eq!(
+            parse_output("-2.5").unwrap().expr,
+            Expr::Literal(json!(-2.5))
+        );
+        assert_eq!(
+            parse_output("(1.5)").unwrap_err(),
+            "Expected '..', found ')'"
+        );
+    }
+
+    #[test]
+    fn and_and_or_group_to_the_right() {
+        let condition = parse_condition("a or b and c").unwrap();
+
+        assert_eq!(
+            condition,
+            Condition::Or(
+                Box::new(Condition::Test(variable("a"))),
+                Box::new(Condition::And(
+                    Box::new(Condition::Test(variable("b"))),
+                    Box::new(Condition::Test(variable("c"))),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(
+            parse_condition("tags contains 'sale'").unwrap(),
+            Condition::Compare(
+                variable("tags"),
+                Operator::Contains,
+                Expr::Literal(json!("sale"))
+            )
+        );
+        assert_eq!(
+            parse_condition("x >= 2").unwrap(),
+            Condition::Compare(
+                variable("x"),
+                Operator::GreaterOrEqual,
+                Expr::Literal(json!(2))
+            )
+        );
+    }
+
+    #[test]
+    fn errors_name_what_was_found() {
+        assert_eq!(parse_output("'open").unwrap_err(), "Unterminated string");
+        assert_eq!(
+            parse_output("x |").unwrap_err(),
+            "Expected a name, found the end of the tag"
+        );
+        assert_eq!(parse_output("x y").unwrap_err(), "Unexpected 'y'");
+        assert_eq!(
+            parse_condition("").unwrap_err(),
+            "Expected a value, found the end of the tag"
+        );
+    }
+}
@@ -0,0 +1,1209 @@
+//! The standard filters.
+
+use serde_json::{Map, Value};
+
+use std::cmp::Ordering;
+
+use super::date::DateTime;
+use super::value::{as_integer, as_number, compare, equals, is_empty, property, to_text, truthy};
+use crate::image::ImageUrl;
+
+/// Evaluated filter arguments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterArgs {
+    pub positional: Vec<Value>,
+    pub keywords: Map<String, Value>,
+}
+
+impl FilterArgs {
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.positional.get(index)
+    }
+
+    pub fn required(&self, index: usize) -> Result<&Value, String> {
+        self.get(index)
+            .ok_or_else(|| format!("expected at least {} argument(s)", index + 1))
+    }
+
+    /// A positional argument as text, or `default` when it's missing.
+    pub fn text_or(&self, index: usize, default: &str) -> String {
+        self.get(index)
+            .map(to_text)
+            .unwrap_or_else(|| default.to_string())
+    }
+}
+
+pub type FilterFn = fn(Value, &FilterArgs) -> Result<Value, String>;
+
+/// Looks up a standard filter by name.
+pub fn standard(name: &str) -> Option<FilterFn> {
+    let filter: FilterFn = match name {
+        "append" => append,
+        "prepend" => prepend,
+        "upcase" => upcase,
+        "downcase" => downcase,
+        "capitalize" => capitalize,
+        "strip" => strip,
+        "lstrip" => lstrip,
+        "rstrip" => rstrip,
+        "strip_newlines" => strip_newlines,
+        "newline_to_br" => newline_to_br,
+        "replace" => replace,
+        "replace_first" => replace_first,
+        "remove" => remove,
+        "remove_first" => remove_first,
+        "truncate" => truncate,
+        "truncatewords" => truncatewords,
+        "split" => split,
+        "slice" => slice,
+        "strip_html" => strip_html,
+        "escape_once" => escape_once,
+        "handleize" | "handle" => handleize,
+        "url_encode" => url_encode,
+        "url_decode" => url_decode,
+        "json" => json,
+        "default" => default,
+        "date" => date,
+        "escape" => escape,
+        "raw" => raw,
+        "size" => size,
+        "join" => join,
+        "first" => first,
+        "last" => last,
+        "reverse" => reverse,
+        "where" => where_filter,
+        "map" => map,
+        "sort" => sort,
+        "sort_natural" => sort_natural,
+        "uniq" => uniq,
+        "compact" => compact,
+        "concat" => concat,
+        "sum" => sum,
+        "plus" => plus,
+        "minus" => minus,
+        "times" => times,
+        "divided_by" => divided_by,
+        "modulo" => modulo,
+        "abs" => abs,
+        "ceil" => ceil,
+        "floor" => floor,
+        "round" => round,
+        "at_least" => at_least,
+        "at_most" => at_most,
+        "image_url" => image_url,
+        _ => return None,
+    };
+    Some(filter)
+}
+
+fn text(input: &Value) -> String {
+    to_text(input)
+}
+
+fn append(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(text(&input) + &to_text(args.required(0)?)))
+}
+
+fn prepend(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(to_text(args.required(0)?) + &text(&input)))
+}
+
+fn upcase(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(text(&input).to_uppercase()))
+}
+
+fn downcase(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(text(&input).to_lowercase()))
+}
+
+fn capitalize(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    let input = text(&input);
+    let mut chars = input.chars();
+    Ok(Value::String(match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.as_str().to_lowercase().chars())
+            .collect(),
+        None => String::new(),
+    }))
+}
+
+fn strip(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(text(&input).trim().to_string()))
+}
+
+fn lstrip(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(text(&input).trim_start().to_string()))
+}
+
+fn rstrip(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(text(&input).trim_end().to_string()))
+}
+
+fn strip_newlines(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(text(&input).replace(['\r', '\n'], "")))
+}
+
+fn newline_to_br(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(
+        text(&input).replace("\r\n", "\n").replace('\n', "<br />\n"),
+    ))
+}
+
+fn replace(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let from = to_text(args.required(0)?);
+    Ok(Value::String(
+        text(&input).replace(&from, &args.text_or(1, "")),
+    ))
+}
+
+fn replace_first(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let from = to_text(args.required(0)?);
+    Ok(Value::String(text(&input).replacen(
+        &from,
+        &args.text_or(1, ""),
+        1,
+    )))
+}
+
+fn remove(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(
+        text(&input).replace(&to_text(args.required(0)?), ""),
+    ))
+}
+
+fn remove_first(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(text(&input).replacen(
+        &to_text(args.required(0)?),
+        "",
+        1,
+    )))
+}
+
+/// `truncate: length, ellipsis`: at most `length` characters (default 50),
+/// including the ellipsis (default `...`).
+fn truncate(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let input = text(&input);
+    let length = match args.get(0) {
+        Some(length) => as_integer(length).ok_or("length must be a number")?.max(0) as usize,
+        None => 50,
+    };
+    let ellipsis = args.text_or(1, "...");
+    if input.chars().count() <= length {
+        return Ok(Value::String(input));
+    }
+    let kept = length.saturating_sub(ellipsis.chars().count());
+    Ok(Value::String(
+        input.chars().take(kept).collect::<String>() + &ellipsis,
+    ))
+}
+
+/// `truncatewords: words, ellipsis`: the first `words` words (default 15)
+/// followed by the ellipsis (default `...`).
+fn truncatewords(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let input = text(&input);
+    let words = match args.get(0) {
+        Some(words) => as_integer(words).ok_or("words must be a number")?.max(1) as usize,
+        None => 15,
+    };
+    let all: Vec<&str> = input.split_whitespace().collect();
+    if all.len() <= words {
+        return Ok(Value::String(input));
+    }
+    Ok(Value::String(
+        all[..words].join(" ") + &args.text_or(1, "..."),
+    ))
+}
+
+/// Splits on a separator, dropping trailing empty parts as Ruby does. A
+/// single space splits on any whitespace; an empty separator splits into
+/// characters.
+fn split(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let input = text(&input);
+    let separator = to_text(args.required(0)?);
+    let mut parts: Vec<String> = match separator.as_str() {
+        "" => input.chars().map(String::from).collect(),
+        " " => input.split_whitespace().map(String::from).collect(),
+        separator => input.split(separator).map(String::from).collect(),
+    };
+    while parts.last().is_some_and(String::is_empty) {
+        parts.pop();
+    }
+    Ok(Value::from(parts))
+}
+
+/// `slice: start, length`: characters of a string or items of an array,
+/// `start` counting from the end when negative. `length` defaults to 1.
+fn slice(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let start = as_integer(args.required(0)?).ok_or("start must be a number")?;
+    let length = match args.get(1) {
+        Some(length) => as_integer(length).ok_or("length must be a number")?.max(0) as usize,
+        None => 1,
+    };
+    let range = |len: usize| -> (usize, usize) {
+        let start = if start < 0 {
+            (len as i64 + start).max(0) as usize
+        } else {
+            (start as usize).min(len)
+        };
+        (start, length.min(len - start))
+    };
+    Ok(match input {
+        Value::Array(items) => {
+            let (start, length) = range(items.len());
+            Value::Array(items[start..start + length].to_vec())
+        }
+        other => {
+            let chars: Vec<char> = text(&other).chars().collect();
+            let (start, length) = range(chars.len());
+            Value::String(chars[start..start + length].iter().collect())
+        }
+    })
+}
+
+/// Removes HTML tags, comments, and `script` and `style` elements.
+fn strip_html(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    let input = text(&input);
+    let lower = input.to_lowercase();
+    let mut output = String::new();
+    let mut index = 0;
+    while let Some(offset) = input[index..].find('<') {
+        let start = index + offset;
+        output.push_str(&input[index..start]);
+        let rest = &lower[start..];
+        let closing = if rest.starts_with("<!--") {
+            "-->"
+        } else if rest.starts_with("<script") {
+            "</script>"
+        } else if rest.starts_with("<style") {
+            "</style>"
+        } else {
+            ">"
+        };
+        match rest.find(closing) {
+            Some(end) => index = start + end + closing.len(),
+            None => {
+                index = input.len();
+                break;
+            }
+        }
+    }
+    output.push_str(&input[index..]);
+    Ok(Value::String(output))
+}
+
+/// Whether `text` starts with an HTML entity such as `&amp;` or `&#39;`.
+fn starts_with_entity(text: &str) -> bool {
+    let Some(body) = text.strip_prefix('&') else {
+        return false;
+    };
+    let Some(end) = body.find(';') else {
+        return false;
+    };
+    let name = &body[..end];
+    match name.strip_prefix('#') {
+        Some(number) => match number.strip_prefix(['x', 'X']) {
+            Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            None => !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+        },
+        None => !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
+/// Like `escape`, but leaves existing entities alone.
+fn escape_once(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    let input = text(&input);
+    let mut output = String::new();
+    for (index, c) in input.char_indices() {
+        if c == '&' && starts_with_entity(&input[index..]) {
+            output.push('&');
+        } else {
+            output.push_str(&escape_html(c.encode_utf8(&mut [0; 4])));
+        }
+    }
+    Ok(Value::String(output))
+}
+
+/// A lowercase handle: runs of anything but letters and digits become one
+/// `-`, as Shopify derives product handles from titles.
+pub fn handle(text: &str) -> String {
+    let mut handle = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            handle.push(c);
+        } else if !handle.is_empty() && !handle.ends_with('-') {
+            handle.push('-');
+        }
+    }
+    handle.trim_end_matches('-').to_string()
+}
+
+fn handleize(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(handle(&text(&input))))
+}
+
+/// Form-encodes text: spaces become `+` and everything but letters, digits
+/// and `-._~` is percent-encoded.
+fn url_encode(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::String(url_encoded(&text(&input))))
+}
+
+/// Percent-encodes `input` for a query string, spaces as `+`.
+pub fn url_encoded(input: &str) -> String {
+    let mut encoded = String::new();
+    for byte in input.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            byte => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn url_decode(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    let input = text(&input);
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        index += 1;
+    }
+    Ok(Value::String(
+        String::from_utf8_lossy(&decoded).into_owned(),
+    ))
+}
+
+fn json(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    serde_json::to_string(&input)
+        .map(Value::String)
+        .map_err(|error| error.to_string())
+}
+
+/// `default: fallback`: the fallback (default `""`) when the input is
+/// `nil`, `false` or empty. With `allow_false: true`, `false` is kept.
+fn default(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let allow_false = args.keywords.get("allow_false").is_some_and(truthy);
+    let missing = match &input {
+        Value::Bool(false) => !allow_false,
+        other => !truthy(other) || is_empty(other),
+    };
+    Ok(if missing {
+        args.get(0)
+            .cloned()
+            .unwrap_or_else(|| Value::String(String::new()))
+    } else {
+        input
+    })
+}
+
+/// `date: '%B %-d, %Y'`, with Ruby's `strftime` directives. Values that
+/// aren't dates, and a missing format, leave the input as it is.
+fn date(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let format = args.text_or(0, "");
+    if format.is_empty() {
+        return Ok(input);
+    }
+    Ok(match DateTime::from_value(&input) {
+        Some(date) => Value::String(date.format(&format)),
+        None => input,
+    })
+}
+
+/// Escapes HTML special characters.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Marks output as safe from auto-escaping; the value is unchanged.
+fn raw(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(input)
+}
+
+fn escape(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(match input {
+        Value::Null => Value::Null,
+        other => Value::String(escape_html(&text(&other))),
+    })
+}
+
+fn size(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(match property(&input, "size") {
+        Value::Null => Value::from(0),
+        size => size,
+    })
+}
+
+fn join(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let separator = args.text_or(0, " ");
+    Ok(Value::String(match input {
+        Value::Array(items) => items
+            .iter()
+            .map(to_text)
+            .collect::<Vec<_>>()
+            .join(&separator),
+        other => text(&other),
+    }))
+}
+
+fn first(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(match input {
+        Value::String(text) => text.chars().next().map(String::from).into(),
+        other => property(&other, "first"),
+    })
+}
+
+fn last(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(match input {
+        Value::String(text) => text.chars().last().map(String::from).into(),
+        other => property(&other, "last"),
+    })
+}
+
+fn reverse(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(match input {
+        Value::Array(mut items) => {
+            items.reverse();
+            Value::Array(items)
+        }
+        other => other,
+    })
+}
+
+/// The CDN URL of an image drop, a drop with a `featured_image` or `image`
+/// (products and variants) or a URL string.
+fn image_source(input: &Value) -> Option<String> {
+    match input {
+        Value::String(url) if !url.is_empty() => Some(url.clone()),
+        Value::Object(object) => ["src", "url"]
+            .iter()
+            .find_map(|key| object.get(*key).and_then(Value::as_str))
+            .map(String::from)
+            .or_else(|| {
+                ["featured_image", "image"]
+                    .iter()
+                    .find_map(|key| object.get(*key).and_then(image_source))
+            }),
+        _ => None,
+    }
+}
+
+/// `image_url: width: 600, height: 400, crop: 'center', format: 'webp'`,
+/// built with the same `ImageUrl` transformations as `responsive_image`.
+fn image_url(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let Some(source) = image_source(&input) else {
+        return Ok(Value::Null);
+    };
+    let dimension = |key: &str| -> Result<Option<u32>, String> {
+        match args.keywords.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => as_integer(value)
+                .and_then(|value| u32::try_from(value).ok())
+                .filter(|value| *value > 0)
+                .map(Some)
+                .ok_or_else(|| format!("{key} must be a positive number")),
+        }
+    };
+    let option = |key: &str| args.keywords.get(key).filter(|value| !value.is_null());
+
+    let mut url = ImageUrl::new(&source);
+    if let Some(width) = dimension("width")? {
+        url = url.width(width);
+    }
+    if let Some(height) = dimension("height")? {
+        url = url.height(height);
+    }
+    if let Some(crop) = option("crop") {
+        url = url.with_crop(&to_text(crop))?;
+    }
+    if let Some(format) = option("format") {
+        url = url.with_format(&to_text(format))?;
+    }
+    Ok(Value::String(url.build()))
+}
+
+/// Items of an array, or the value alone, as array filters take them.
+fn items(input: Value) -> Vec<Value> {
+    match input {
+        Value::Array(items) => items,
+        Value::Null => Vec::new(),
+        other => vec![other],
+    }
+}
+
+/// `where: 'available'` keeps items whose property is truthy;
+/// `where: 'type', 'Shirt'` those whose property equals the value.
+fn where_filter(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let key = to_text(args.required(0)?);
+    let target = args.get(1);
+    Ok(Value::Array(
+        items(input)
+            .into_iter()
+            .filter(|item| {
+                let value = property(item, &key);
+                match target {
+                    Some(target) => equals(&value, target),
+                    None => truthy(&value),
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// `map: 'title'`: the property of each item.
+fn map(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let key = to_text(args.required(0)?);
+    Ok(match input {
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .flat_map(|item| match property(item, &key) {
+                    Value::Array(nested) => nested,
+                    value => vec![value],
+                })
+                .collect(),
+        ),
+        other => property(&other, &key),
+    })
+}
+
+/// The value items are sorted or deduplicated by: a property when the
+/// filter names one, else the item itself.
+fn sort_key(item: &Value, key: Option<&str>) -> Value {
+    match key {
+        Some(key) => property(item, key),
+        None => item.clone(),
+    }
+}
+
+/// Sorts with `order`, putting `nil`s last.
+fn sort_by(
+    input: Value,
+    args: &FilterArgs,
+    order: fn(&Value, &Value) -> Ordering,
+) -> Result<Value, String> {
+    let key = args.get(0).map(to_text);
+    let mut items = items(input);
+    items.sort_by(|left, right| {
+        let (left, right) = (
+            sort_key(left, key.as_deref()),
+            sort_key(right, key.as_deref()),
+        );
+        match (left.is_null(), right.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => order(&left, &right),
+        }
+    });
+    Ok(Value::Array(items))
+}
+
+/// `sort` or `sort: 'price'`. Numbers and strings compare as such; other
+/// values keep their order.
+fn sort(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    sort_by(input, args, |left, right| {
+        compare(left, right).unwrap_or(Ordering::Equal)
+    })
+}
+
+/// Like `sort`, ignoring case.
+fn sort_natural(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    sort_by(input, args, |left, right| {
+        to_text(left)
+            .to_lowercase()
+            .cmp(&to_text(right).to_lowercase())
+    })
+}
+
+/// `uniq` or `uniq: 'id'`: the first of each run of equal items.
+fn uniq(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let key = args.get(0).map(to_text);
+    let mut seen: Vec<Value> = Vec::new();
+    let mut unique = Vec::new();
+    for item in items(input) {
+        let value = sort_key(&item, key.as_deref());
+        if !seen.iter().any(|other| equals(other, &value)) {
+            seen.push(value);
+            unique.push(item);
+        }
+    }
+    Ok(Value::Array(unique))
+}
+
+/// `compact` or `compact: 'image'`: drops `nil` items.
+fn compact(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let key = args.get(0).map(to_text);
+    Ok(Value::Array(
+        items(input)
+            .into_iter()
+            .filter(|item| !sort_key(item, key.as_deref()).is_null())
+            .collect(),
+    ))
+}
+
+fn concat(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let Value::Array(more) = args.required(0)? else {
+        return Err("expected an array".to_string());
+    };
+    let mut items = items(input);
+    items.extend(more.iter().cloned());
+    Ok(Value::Array(items))
+}
+
+/// `sum` or `sum: 'quantity'`. Items that aren't numbers count as 0.
+fn sum(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let key = args.get(0).map(to_text);
+    let total = items(input)
+        .iter()
+        .map(|item| Number::from_value(&sort_key(item, key.as_deref())))
+        .fold(Number::Integer(0), |total, number| {
+            total.combine(number, |a, b| a.checked_add(b), |a, b| a + b)
+        });
+    Ok(total.into_value())
+}
+
+/// A number as the math filters see it: integers stay integers unless a
+/// float is involved, as in Ruby.
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Numbers and numeric strings; anything else is 0.
+    fn from_value(value: &Value) -> Number {
+        match value {
+            Value::Number(number) => match number.as_i64() {
+                Some(integer) => Number::Integer(integer),
+                None => Number::Float(number.as_f64().unwrap_or(0.0)),
+            },
+            Value::String(text) => match text.trim().parse::<i64>() {
+                Ok(integer) => Number::Integer(integer),
+                Err(_) => Number::Float(as_number(value).unwrap_or(0.0)),
+            },
+            _ => Number::Integer(0),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Integer(integer) => integer as f64,
+            Number::Float(float) => float,
+        }
+    }
+
+    /// Applies `integer` to two integers, falling back to `float` when
+    /// either is a float or the integer operation overflows.
+    fn combine(
+        self,
+        other: Number,
+        integer: fn(i64, i64) -> Option<i64>,
+        float: fn(f64, f64) -> f64,
+    ) -> Number {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => match integer(a, b) {
+                Some(result) => Number::Integer(result),
+                None => Number::Float(float(a as f64, b as f64)),
+            },
+            (a, b) => Number::Float(float(a.as_f64(), b.as_f64())),
+        }
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            Number::Integer(integer) => Value::from(integer),
+            Number::Float(float) => Value::from(float),
+        }
+    }
+}
+
+fn arithmetic(
+    input: Value,
+    args: &FilterArgs,
+    integer: fn(i64, i64) -> Option<i64>,
+    float: fn(f64, f64) -> f64,
+) -> Result<Value, String> {
+    let operand = Number::from_value(args.required(0)?);
+    Ok(Number::from_value(&input)
+        .combine(operand, integer, float)
+        .into_value())
+}
+
+fn plus(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    arithmetic(input, args, i64::checked_add, |a, b| a + b)
+}
+
+fn minus(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    arithmetic(input, args, i64::checked_sub, |a, b| a - b)
+}
+
+fn times(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    arithmetic(input, args, i64::checked_mul, |a, b| a * b)
+}
+
+/// Integer division rounds down when both numbers are integers.
+fn divided_by(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    if Number::from_value(args.required(0)?).as_f64() == 0.0 {
+        return Err("divided by 0".to_string());
+    }
+    arithmetic(
+        input,
+        args,
+        |a, b| {
+            let quotient = a.checked_div(b)?;
+            Some(if a % b != 0 && (a < 0) != (b < 0) {
+                quotient - 1
+            } else {
+                quotient
+            })
+        },
+        |a, b| a / b,
+    )
+}
+
+/// The remainder takes the divisor's sign, as in Ruby.
+fn modulo(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    if Number::from_value(args.required(0)?).as_f64() == 0.0 {
+        return Err("divided by 0".to_string());
+    }
+    arithmetic(
+        input,
+        args,
+        |a, b| {
+            a.checked_rem(b).map(|rem| {
+                if rem != 0 && (rem < 0) != (b < 0) {
+                    rem + b
+                } else {
+                    rem
+                }
+            })
+        },
+        |a, b| a - b * (a / b).floor(),
+    )
+}
+
+fn abs(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(match Number::from_value(&input) {
+        Number::Integer(integer) => Value::from(integer.saturating_abs()),
+        Number::Float(float) => Value::from(float.abs()),
+    })
+}
+
+fn ceil(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::from(
+        Number::from_value(&input).as_f64().ceil() as i64
+    ))
+}
+
+fn floor(input: Value, _: &FilterArgs) -> Result<Value, String> {
+    Ok(Value::from(
+        Number::from_value(&input).as_f64().floor() as i64
+    ))
+}
+
+/// `round` to a whole number, or `round: 2` to decimal places.
+fn round(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let number = Number::from_value(&input).as_f64();
+    Ok(
+        match args
+            .get(0)
+            .and_then(as_integer)
+            .filter(|digits| *digits > 0)
+        {
+            Some(digits) => {
+                let scale = 10f64.powi(digits.min(15) as i32);
+                Value::from((number * scale).round() / scale)
+            }
+            None => Value::from(number.round() as i64),
+        },
+    )
+}
+
+fn at_least(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let (number, bound) = (
+        Number::from_value(&input),
+        Number::from_value(args.required(0)?),
+    );
+    Ok(if number.as_f64() < bound.as_f64() {
+        bound
+    } else {
+        number
+    }
+    .into_value())
+}
+
+fn at_most(input: Value, args: &FilterArgs) -> Result<Value, String> {
+    let (number, bound) = (
+        Number::from_value(&input),
+        Number::from_value(args.required(0)?),
+    );
+    Ok(if number.as_f64() > bound.as_f64() {
+        bound
+    } else {
+        number
+    }
+    .into_value())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn apply(name: &str, input: Value, positional: Vec<Value>) -> Result<Value, String> {
+        let filter = standard(name).unwrap_or_else(|| panic!("no filter '{name}'"));
+        let args = FilterArgs {
+            positional,
+            keywords: Map::new(),
+        };
+        filter(input, &args)
+    }
+
+    fn filter(name: &str, input: Value, positional: Vec<Value>) -> Value {
+        apply(name, input, positional).unwrap()
+    }
+
+    fn with_keywords(name: &str, input: Value, keywords: Value) -> Result<Value, String> {
+        let Value::Object(keywords) = keywords else {
+            panic!("keywords must be an object");
+        };
+        let args = FilterArgs {
+            positional: Vec::new(),
+            keywords,
+        };
+        standard(name).unwrap()(input, &args)
+    }
+
+    #[test]
+    fn unknown_filters_are_not_standard() {
+        assert!(standard("sparkle").is_none());
+        assert!(standard("handle").is_some());
+    }
+
+    #[test]
+    fn string_filters() {
+        assert_eq!(filter("append", json!("a"), vec![json!("b")]), "ab");
+        assert_eq!(filter("prepend", json!("a"), vec![json!(1)]), "1a");
+        assert_eq!(filter("upcase", json!("tee"), vec![]), "TEE");
+        assert_eq!(filter("downcase", json!("TEE"), vec![]), "tee");
+        assert_eq!(
+            filter("capitalize", json!("hELLO world"), vec![]),
+            "Hello world"
+        );
+        assert_eq!(filter("strip", json!("  a  "), vec![]), "a");
+        assert_eq!(filter("lstrip", json!("  a  "), vec![]), "a  ");
+        assert_eq!(filter("rstrip", json!("  a  "), vec![]), "  a");
+        assert_eq!(filter("strip_newlines", json!("a\r\nb\n"), vec![]), "ab");
+        assert_eq!(
+            filter("newline_to_br", json!("a\r\nb"), vec![]),
+            "a<br />\nb"
+        );
+        assert_eq!(
+            filter("replace", json!("a-b-c"), vec![json!("-"), json!("+")]),
+            "a+b+c"
+        );
+        assert_eq!(
+            filter(
+                "replace_first",
+                json!("a-b-c"),
+                vec![json!("-"), json!("+")]
+            ),
+            "a+b-c"
+        );
+        assert_eq!(filter("remove", json!("a-b-c"), vec![json!("-")]), "abc");
+        assert_eq!(
+            filter("remove_first", json!("a-b-c"), vec![json!("-")]),
+            "ab-c"
+        );
+        assert_eq!(
+            apply("append", json!("a"), vec![]).unwrap_err(),
+            "expected at least 1 argument(s)"
+        );
+    }
+
+    #[test]
+    fn truncation_counts_the_ellipsis() {
+        assert_eq!(
+            filter("truncate", json!("Ground control"), vec![json!(8)]),
+            "Groun..."
+        );
+        assert_eq!(
+            filter(
+                "truncate",
+                json!("Ground control"),
+                vec![json!(8), json!("")]
+            ),
+            "Ground c"
+        );
+        assert_eq!(filter("truncate", json!("short"), vec![json!(8)]), "short");
+        assert_eq!(
+            filter("truncatewords", json!("one two three"), vec![json!(2)]),
+            "one two..."
+        );
+        assert!(apply("truncate", json!("x"), vec![json!("many")]).is_err());
+    }
+
+    #[test]
+    fn split_and_slice() {
+        assert_eq!(
+            filter("split", json!("a,b,,"), vec![json!(",")]),
+            json!(["a", "b"])
+        );
+        assert_eq!(
+            filter("split", json!(" a  b "), vec![json!(" ")]),
+            json!(["a", "b"])
+        );
+        assert_eq!(
+            filter("split", json!("abc"), vec![json!("")]),
+            json!(["a", "b", "c"])
+        );
+        assert_eq!(
+            filter("slice", json!("Liquid"), vec![json!(2), json!(3)]),
+            "qui"
+        );
+        assert_eq!(
+            filter("slice", json!("Liquid"), vec![json!(-3), json!(2)]),
+            "ui"
+        );
+        assert_eq!(
+            filter("slice", json!([1, 2, 3]), vec![json!(1), json!(9)]),
+            json!([2, 3])
+        );
+        assert_eq!(filter("slice", json!("abc"), vec![json!(9)]), "");
+    }
+
+    #[test]
+    fn html_filters() {
+        assert_eq!(
+            filter("escape", json!("<a href=\"x\">'</a>"), vec![]),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&lt;/a&gt;"
+        );
+        assert_eq!(filter("escape", Value::Null, vec![]), Value::Null);
+        assert_eq!(
+            filter("escape_once", json!("1 &lt; 2 & 3"), vec![]),
+            "1 &lt; 2 &amp; 3"
+        );
+        assert_eq!(
+            filter(
+                "strip_html",
+                json!("<p>Hi<!-- x --></p><script>alert(1)</script><style>p{}</style>!"),
+                vec![]
+            ),
+            "Hi!"
+        );
+        assert_eq!(filter("raw", json!("<b>"), vec![]), "<b>");
+    }
+
+    #[test]
+    fn handles_and_url_encoding() {
+        assert_eq!(
+            filter("handleize", json!("100% Cotton T-Shirt!"), vec![]),
+            "100-cotton-t-shirt"
+        );
+        assert_eq!(
+            filter("url_encode", json!("a b&c/é"), vec![]),
+            "a+b%26c%2F%C3%A9"
+        );
+        assert_eq!(
+            filter("url_decode", json!("a+b%26c%2F%C3%A9"), vec![]),
+            "a b&c/é"
+        );
+        assert_eq!(filter("url_decode", json!("100%"), vec![]), "100%");
+    }
+
+    #[test]
+    fn default_replaces_nil_false_and_empty() {
+        assert_eq!(filter("default", Value::Null, vec![json!("x")]), "x");
+        assert_eq!(filter("default", json!(""), vec![json!("x")]), "x");
+        assert_eq!(filter("default", json!([]), vec![json!("x")]), "x");
+        assert_eq!(filter("default", json!(false), vec![json!("x")]), "x");
+        assert_eq!(filter("default", json!(0), vec![json!("x")]), json!(0));
+        assert_eq!(
+            with_keywords("default", json!(false), json!({ "allow_false": true })).unwrap(),
+            json!(false)
+        );
+    }
+
+    #[test]
+    fn json_and_date() {
+        assert_eq!(
+            filter("json", json!({ "a": [1, "b"] }), vec![]),
+            r#"{"a":[1,"b"]}"#
+        );
+        assert_eq!(
+            filter(
+                "date",
+                json!("2024-03-05T10:00:00Z"),
+                vec![json!("%B %-d, %Y")]
+            ),
+            "March 5, 2024"
+        );
+        assert_eq!(
+            filter("date", json!("someday"), vec![json!("%Y")]),
+            "someday"
+        );
+        assert_eq!(filter("date", json!("2024-03-05"), vec![]), "2024-03-05");
+    }
+
+    #[test]
+    fn array_filters() {
+        let items = json!([
+            { "title": "B", "type": "Shirt", "available": true, "price": 20 },
+            { "title": "a", "type": "Hat", "available": false, "price": 5 },
+            { "title": "C", "type": "Shirt", "available": true, "price": null },
+        ]);
+
+        assert_eq!(filter("size", items.clone(), vec![]), json!(3));
+        assert_eq!(filter("size", json!("four"), vec![]), json!(4));
+        assert_eq!(filter("size", Value::Null, vec![]), json!(0));
+        assert_eq!(filter("join", json!([1, "a"]), vec![json!(", ")]), "1, a");
+        assert_eq!(filter("first", json!([1, 2]), vec![]), json!(1));
+        assert_eq!(filter("last", json!("abc"), vec![]), "c");
+        assert_eq!(
+            filter("reverse", json!([1, 2, 3]), vec![]),
+            json!([3, 2, 1])
+        );
+        assert_eq!(
+            filter(
+                "map",
+                filter("where", items.clone(), vec![json!("available")]),
+                vec![json!("title")]
+            ),
+            json!(["B", "C"])
+        );
+        assert_eq!(
+            filter(
+                "map",
+                filter("where", items.clone(), vec![json!("type"), json!("Hat")]),
+                vec![json!("title")]
+            ),
+            json!(["a"])
+        );
+        assert_eq!(
+            filter(
+                "map",
+                filter("sort", items.clone(), vec![json!("price")]),
+                vec![json!("title")]
+            ),
+            json!(["a", "B", "C"])
+        );
+        assert_eq!(
+            filter("sort", json!(["b", "C", "a"]), vec![]),
+            json!(["C", "a", "b"])
+        );
+        assert_eq!(
+            filter("sort_natural", json!(["b", "C", "a"]), vec![]),
+            json!(["a", "b", "C"])
+        );
+        assert_eq!(
+            filter(
+                "map",
+                filter("uniq", items.clone(), vec![json!("type")]),
+                vec![json!("title")]
+            ),
+            json!(["B", "a"])
+        );
+        assert_eq!(
+            filter("uniq", json!([1, 1, "1", 2]), vec![]),
+            json!([1, "1", 2])
+        );
+        assert_eq!(
+            filter("compact", json!([1, null, 2]), vec![]),
+            json!([1, 2])
+        );
+        assert_eq!(
+            filter("concat", json!([1]), vec![json!([2, 3])]),
+            json!([1, 2, 3])
+        );
+        assert!(apply("concat", json!([1]), vec![json!(2)]).is_err());
+        assert_eq!(filter("sum", items, vec![json!("price")]), json!(25));
+    }
+
+    #[test]
+    fn math_filters_keep_integers_whole() {
+        assert_eq!(filter("plus", json!(4), vec![json!("2")]), json!(6));
+        assert_eq!(filter("plus", json!(4), vec![json!(0.5)]), json!(4.5));
+        assert_eq!(filter("minus", json!(4), vec![json!(6)]), json!(-2));
+        assert_eq!(filter("times", json!(3), vec![json!(1.5)]), json!(4.5));
+        assert_eq!(filter("divided_by", json!(7), vec![json!(2)]), json!(3));
+        assert_eq!(filter("divided_by", json!(-7), vec![json!(2)]), json!(-4));
+        assert_eq!(filter("divided_by", json!(7), vec![json!(2.0)]), json!(3.5));
+        assert_eq!(filter("modulo", json!(-7), vec![json!(3)]), json!(2));
+        assert_eq!(
+            apply("divided_by", json!(1), vec![json!(0)]).unwrap_err(),
+            "divided by 0"
+        );
+        assert_eq!(
+            apply("modulo", json!(1), vec![json!(0)]).unwrap_err(),
+            "divided by 0"
+        );
+        assert_eq!(filter("abs", json!("-3"), vec![]), json!(3));
+        assert_eq!(filter("ceil", json!(1.2), vec![]), json!(2));
+        assert_eq!(filter("floor", json!("1.8"), vec![]), json!(1));
+        assert_eq!(filter("round", json!(2.5), vec![]), json!(3));
+        assert_eq!(filter("round", json!(1.23456), vec![json!(2)]), json!(1.23));
+        assert_eq!(filter("at_least", json!(2), vec![json!(5)]), json!(5));
+        assert_eq!(filter("at_most", json!(2), vec![json!(5)]), json!(2));
+    }
+
+    #[test]
+    fn integer_overflow_falls_back_to_floats() {
+        assert_eq!(
+            filter("plus", json!(i64::MAX), vec![json!(1)]),
+            json!(i64::MAX as f64 + 1.0)
+        );
+        assert_eq!(filter("abs", json!(i64::MIN), vec![]), json!(i64::MAX));
+    }
+
+    #[test]
+    fn image_urls() {
+        assert_eq!(
+            with_keywords(
+                "image_url",
+                json!({ "featured_image": { "src": "https://cdn.shopify.com/s/files/tee.jpg" } }),
+                json!({ "width": 300 })
+            )
+            .unwrap(),
+            "https://cdn.shopify.com/s/files/tee.jpg?width=300"
+        );
+        assert_eq!(filter("image_url", Value::Null, vec![]), Value::Null);
+        assert_eq!(
+            with_keywords(
+                "image_url",
+                json!("https://cdn.shopify.com/a.jpg"),
+                json!({ "width": 0 })
+            )
+            .unwrap_err(),
+            "width must be a positive number"
+        );
+    }
+}
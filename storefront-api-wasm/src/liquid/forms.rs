@@ -0,0 +1,225 @@
+//! The `form` tag.
+//!
+//! Forms render as Shopify's would, posting to the same paths, and also
+//! carry `data-storefront-form` (the form type) and
+//! `data-storefront-mutation` (the Storefront API mutation it maps to), so
+//! a headless runtime can intercept the submit and call the API instead:
+//!
+//! ```html
+//! <form method="post" action="/cart/add" accept-charset="UTF-8"
+//!   enctype="multipart/form-data" data-storefront-form="product"
+//!   data-storefront-mutation="cartLinesAdd" data-product-id="42">
+//! ```
+//!
+//! Forms acting on an object add its ID: `data-product-id` for `product`
+//! and `data-address-id` when `customer_address` edits an address. With
+//! hydration markers on, forms and their controls are marked too (see
+//! `hydration`).
+
+use serde_json::Value;
+
+use super::engine::LiquidEngine;
+use super::error::LiquidError;
+use super::filters::escape_html;
+use super::parser::FormTag;
+use super::render::Context;
+use super::value::{to_text, truthy};
+
+/// A form type: where Shopify posts it and the mutation that replaces the
+/// post.
+struct FormType {
+    name: &'static str,
+    action: &'static str,
+    mutation: Option<&'static str>,
+}
+
+const FORM_TYPES: &[FormType] = &[
+    FormType {
+        name: "product",
+        action: "/cart/add",
+        mutation: Some("cartLinesAdd"),
+    },
+    FormType {
+        name: "cart",
+        action: "/cart",
+        mutation: Some("cartLinesUpdate"),
+    },
+    FormType {
+        name: "customer_login",
+        action: "/account/login",
+        mutation: Some("customerAccessTokenCreate"),
+    },
+    FormType {
+        name: "create_customer",
+        action: "/account",
+        mutation: Some("customerCreate"),
+    },
+    FormType {
+        name: "recover_customer_password",
+        action: "/account/recover",
+        mutation: Some("customerRecover"),
+    },
+    FormType {
+        name: "reset_customer_password",
+        action: "/account/reset",
+        mutation: Some("customerReset"),
+    },
+    FormType {
+        name: "activate_customer_password",
+        action: "/account/activate",
+        mutation: Some("customerActivate"),
+    },
+    FormType {
+        name: "customer_address",
+        action: "/account/addresses",
+        mutation: Some("customerAddressCreate"),
+    },
+    FormType {
+        name: "customer",
+        action: "/contact#contact_form",
+        mutation: Some("customerCreate"),
+    },
+    FormType {
+        name: "localization",
+        action: "/localization",
+        mutation: Some("cartBuyerIdentityUpdate"),
+    },
+    FormType {
+        name: "contact",
+        action: "/contact#contact_form",
+        mutation: None,
+    },
+    FormType {
+        name: "new_comment",
+        action: "/comments",
+        mutation: None,
+    },
+    FormType {
+        name: "guest_login",
+        action: "/account/login",
+        mutation: None,
+    },
+    FormType {
+        name: "storefront_password",
+        action: "/password",
+        mutation: None,
+    },
+];
+
+/// Arguments that become hidden inputs rather than attributes.
+const HIDDEN_ARGUMENTS: &[&str] = &["return_to"];
+
+fn attribute(html: &mut String, name: &str, value: &str) {
+    html.push_str(&format!(" {name}=\"{}\"", escape_html(value)));
+}
+
+fn hidden_input(html: &mut String, name: &str, value: &str) {
+    html.push_str(&format!(
+        "<input type=\"hidden\" name=\"{name}\" value=\"{}\" />",
+        escape_html(value)
+    ));
+}
+
+impl LiquidEngine {
+    pub(crate) async fn render_form(
+        &self,
+        form: &FormTag,
+        context: &mut Context,
+        output: &mut String,
+    ) -> Result<(), LiquidError> {
+        let fail = |message: String| LiquidError::at(message, form.position);
+        let kind = to_text(&self.evaluate(&form.kind, context).map_err(fail)?);
+        let form_type = FORM_TYPES
+            .iter()
+            .find(|form_type| form_type.name == kind)
+            .ok_or_else(|| fail(format!("Unknown form type '{kind}'")))?;
+        let object = match &form.object {
+            Some(object) => self.evaluate(object, context).map_err(fail)?,
+            None => Value::Null,
+        };
+        let object_id = object.get("id").filter(|id| !id.is_null()).map(to_text);
+
+        let mut action = form_type.action.to_string();
+        let mut mutation = form_type.mutation;
+        let mut id_attribute = None;
+        match (kind.as_str(), &object_id) {
+            ("product", Some(id)) => id_attribute = Some(("data-product-id", id.clone())),
+            ("customer_address", Some(id)) => {
+                action = format!("/account/addresses/{id}");
+                mutation = Some("customerAddressUpdate");
+                id_attribute = Some(("data-address-id", id.clone()));
+            }
+            ("new_comment", _) => {
+                if let Some(url) = object.get("url").and_then(Value::as_str) {
+                    action = format!("{url}/comments");
+                }
+            }
+            _ => {}
+        }
+
+        let mut html = String::from("<form method=\"post\"");
+        attribute(&mut html, "action", &action);
+        let mut hidden = Vec::new();
+        for (key, value) in &form.arguments {
+            let value = self.evaluate(value, context).map_err(fail)?;
+            if HIDDEN_ARGUMENTS.contains(&key.as_str()) {
+                hidden.push((key.as_str(), to_text(&value)));
+            } else if value == Value::Bool(true) {
+                html.push_str(&format!(" {key}"));
+            } else if truthy(&value) {
+                attribute(&mut html, key, &to_text(&value));
+            }
+        }
+        attribute(&mut html, "accept-charset", "UTF-8");
+        if kind == "product" {
+            attribute(&mut html, "enctype", "multipart/form-data");
+        }
+        attribute(&mut html, "data-storefront-form", &kind);
+        if let Some(mutation) = mutation {
+            attribute(&mut html, "data-storefront-mutation", mutation);
+        }
+        if let Some((name, id)) = &id_attribute {
+            attribute(&mut html, name, id);
+        }
+        let hydration_id = context.hydration().map(|hydration| {
+            let (id, attributes) = hydration.borrow_mut().mark_form(
+                &kind,
+                mutation,
+                id_attribute.as_ref().map(|(_, id)| id.as_str()),
+            );
+            html.push_str(&attributes);
+            id
+        });
+        html.push('>');
+        hidden_input(&mut html, "form_type", &kind);
+        hidden_input(&mut html, "utf8", "✓");
+        for (name, value) in hidden {
+            hidden_input(&mut html, name, &value);
+        }
+        output.push_str(&html);
+
+        // Nothing is posted while rendering, so the form has no errors yet.
+        context.push();
+        context.set_local(
+            "form",
+            serde_json::json!({
+                "id": object_id,
+                "type": kind,
+                "errors": null,
+                "posted_successfully?": false,
+            }),
+        );
+        let mut body = String::new();
+        let rendered = self.render_nodes(&form.body, context, &mut body).await;
+        context.pop();
+        rendered?;
+        match (&hydration_id, context.hydration()) {
+            (Some(id), Some(hydration)) if kind == "product" => {
+                output.push_str(&hydration.borrow_mut().mark_product_controls(&body, id));
+            }
+            _ => output.push_str(&body),
+        }
+        output.push_str("</form>");
+        Ok(())
+    }
+}
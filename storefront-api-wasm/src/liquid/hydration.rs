@@ -0,0 +1,231 @@
+//! Hydration markers: with `set_hydration_markers(true)`, interactive
+//! regions of the output carry `data-hydrate` (what the region is) and
+//! `data-hydrate-id`, and `render_with_diagnostics` lists them under
+//! `hydration`, so client-side code can find and hydrate each one:
+//!
+//! ```html
+//! <form method="post" action="/cart/add" ... data-hydrate="form"
+//!   data-hydrate-id="form-0">
+//!   <select name="id" data-hydrate="variant-picker"
+//!     data-hydrate-id="variant-picker-0">...</select>
+//!   <button type="submit" data-hydrate="cart-button"
+//!     data-hydrate-id="cart-button-0">Add to cart</button>
+//! </form>
+//! ```
+//!
+//! Every form is a region. In product forms, so are the variant picker (a
+//! `select` named `id`, or a `variant-selects`, `variant-radios` or
+//! `variant-picker` element) and the add to cart button (a submit button,
+//! or any element named `add`). IDs count each kind in document order, so
+//! the same template and data always get the same IDs.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::filters::escape_html;
+
+/// Custom elements themes wrap variant pickers in.
+const VARIANT_PICKER_ELEMENTS: &[&str] = &["variant-selects", "variant-radios", "variant-picker"];
+
+/// A marked region, as listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HydrationMarker {
+    /// The `data-hydrate-id` value.
+    pub id: String,
+    /// `form`, `variant-picker` or `cart-button`.
+    pub kind: String,
+    /// The form type, for forms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub form_type: Option<String>,
+    /// The Storefront mutation a form maps to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mutation: Option<String>,
+    /// The ID of the product or address a form acts on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<String>,
+    /// The form a control belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub form: Option<String>,
+}
+
+impl HydrationMarker {
+    fn attributes(&self) -> String {
+        format!(
+            " data-hydrate=\"{}\" data-hydrate-id=\"{}\"",
+            escape_html(&self.kind),
+            escape_html(&self.id)
+        )
+    }
+}
+
+/// The markers of one render.
+#[derive(Debug, Clone, Default)]
+pub struct Hydration {
+    markers: Vec<HydrationMarker>,
+    counts: BTreeMap<String, usize>,
+}
+
+impl Hydration {
+    fn next_id(&mut self, kind: &str) -> String {
+        let count = self.counts.entry(kind.to_string()).or_default();
+        let id = format!("{kind}-{count}");
+        *count += 1;
+        id
+    }
+
+    /// Records a form, returning its marker's attributes.
+    pub fn mark_form(
+        &mut self,
+        form_type: &str,
+        mutation: Option<&str>,
+        object_id: Option<&str>,
+    ) -> (String, String) {
+        let marker = HydrationMarker {
+            id: self.next_id("form"),
+            kind: "form".to_string(),
+            form_type: Some(form_type.to_string()),
+            mutation: mutation.map(String::from),
+            object_id: object_id.map(String::from),
+            form: None,
+        };
+        let attributes = marker.attributes();
+        let id = marker.id.clone();
+        self.markers.push(marker);
+        (id, attributes)
+    }
+
+    /// Marks the variant pickers and add to cart buttons in a product
+    /// form's rendered body.
+    pub fn mark_product_controls(&mut self, body: &str, form: &str) -> String {
+        let mut marked = String::with_capacity(body.len());
+        let mut rest = body;
+        while let Some(start) = rest.find('<') {
+            marked.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let Some(end) = tag_end(rest) else {
+                break;
+            };
+            let tag = &rest[..end];
+            match control_kind(tag) {
+                Some(kind) => {
+                    let marker = HydrationMarker {
+                        id: self.next_id(kind),
+                        kind: kind.to_string(),
+                        form_type: None,
+                        mutation: None,
+                        object_id: None,
+                        form: Some(form.to_string()),
+                    };
+                    let close = if tag.ends_with("/>") {
+                        tag.len() - 2
+                    } else {
+                        tag.len() - 1
+                    };
+                    let content = tag[..close].trim_end();
+                    marked.push_str(content);
+                    marked.push_str(&marker.attributes());
+                    marked.push_str(&tag[content.len()..]);
+                    self.markers.push(marker);
+                }
+                None => marked.push_str(tag),
+            }
+            rest = &rest[end..];
+        }
+        marked.push_str(rest);
+        marked
+    }
+
+    pub fn markers(&self) -> Vec<HydrationMarker> {
+        self.markers.clone()
+    }
+}
+
+/// The length of the tag `html` starts with, through its `>`, skipping
+/// quoted attribute values.
+fn tag_end(html: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in html.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The attributes of an opening tag, names lowercased.
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let inner = tag.trim_start_matches('<').trim_end_matches('>');
+    let inner = inner.trim_end_matches('/');
+    let mut chars = inner
+        .trim_start_matches(|c: char| !c.is_whitespace())
+        .chars()
+        .peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            name.push(c.to_ascii_lowercase());
+        }
+        if name.is_empty() {
+            return attributes;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            match chars.next_if(|c| *c == '"' || *c == '\'') {
+                Some(quote) => value.extend(chars.by_ref().take_while(|c| *c != quote)),
+                None => {
+                    while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                        value.push(c);
+                    }
+                }
+            }
+        }
+        attributes.push((name, value));
+    }
+}
+
+/// Whether an opening tag in a product form is a control to mark.
+fn control_kind(tag: &str) -> Option<&'static str> {
+    let name: String = tag[1..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    let attributes = attributes(tag);
+    let attribute = |wanted: &str| {
+        attributes
+            .iter()
+            .find(|(name, _)| name == wanted)
+            .map(|(_, value)| value.as_str())
+    };
+    if attribute("data-hydrate").is_some() {
+        return None;
+    }
+    if VARIANT_PICKER_ELEMENTS.contains(&name.as_str())
+        || (name == "select" && attribute("name") == Some("id"))
+    {
+        return Some("variant-picker");
+    }
+    let submit = match name.as_str() {
+        // A button submits unless its type says otherwise.
+        "button" => attribute("type").is_none_or(|kind| kind.eq_ignore_ascii_case("submit")),
+        "input" => attribute("type").is_some_and(|kind| kind.eq_ignore_ascii_case("submit")),
+        _ => false,
+    };
+    if submit || attribute("name") == Some("add") {
+        return Some("cart-button");
+    }
+    None
+}
@@ -0,0 +1,312 @@
+//! Splits template source into text, `{{ output }}` and `{% tag %}` tokens.
+//!
+//! `{% echo value %}` is an output, and each line of `{% liquid %}` a tag
+//! of its own, so the parser never sees either. Whitespace control is
+//! applied here too: `{{-` and `{%-` strip the whitespace before the tag,
+//! `-}}` and `-%}` the whitespace after it.
+
+use super::error::{LiquidError, Position};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Text(String),
+    Output {
+        markup: String,
+        position: Position,
+    },
+    Tag {
+        name: String,
+        markup: String,
+        position: Position,
+    },
+    /// The raw JSON of a section's `{% schema %}`.
+    Schema {
+        content: String,
+        position: Position,
+    },
+}
+
+/// Tags whose content is not parsed: `raw` is output verbatim, `schema`
+/// becomes a `Schema` token and the others are dropped. Sections'
+/// `javascript` and `stylesheet` are bundled by Shopify rather than
+/// rendered in place, so they are dropped too.
+pub(crate) const VERBATIM_TAGS: &[&str] = &["raw", "comment", "schema", "javascript", "stylesheet"];
+
+/// Finds `{% end<name> %}` in `source`, returning where it starts and ends.
+fn find_end_tag(source: &str, name: &str) -> Option<(usize, usize)> {
+    let end_name = format!("end{name}");
+    let mut offset = 0;
+    while let Some(start) = source[offset..].find("{%") {
+        let start = offset + start;
+        let close = start + source[start..].find("%}")?;
+        if source[start + 2..close].trim().trim_matches('-').trim() == end_name {
+            return Some((start, close + 2));
+        }
+        offset = start + 2;
+    }
+    None
+}
+
+/// Tokenizes the lines of a `{% liquid %}` tag, `content` being everything
+/// between `{%` and `%}` and `position` where it starts. `echo` lines are
+/// outputs; `comment` blocks and `#` comment lines are dropped.
+fn liquid_lines(
+    content: &str,
+    position: Position,
+    tokens: &mut Vec<Token>,
+) -> Result<(), LiquidError> {
+    let trimmed = content.trim_start();
+    let body = trimmed.strip_prefix("liquid").unwrap_or(trimmed);
+    let mut position = position.advance(&content[..content.len() - body.len()]);
+    let mut comment = None;
+    for line in body.split_inclusive('\n') {
+        let markup = line.trim();
+        let start = position.advance(&line[..line.len() - line.trim_start().len()]);
+        position = position.advance(line);
+        let (name, markup) = match markup.split_once(char::is_whitespace) {
+            Some((name, markup)) => (name, markup.trim()),
+            None => (markup, ""),
+        };
+        if comment.is_some() {
+            if name == "endcomment" {
+                comment = None;
+            }
+            continue;
+        }
+        match name {
+            "" => {}
+            _ if name.starts_with('#') => {}
+            "comment" => comment = Some(start),
+            "echo" => tokens.push(Token::Output {
+                markup: markup.to_string(),
+                position: start,
+            }),
+            _ => tokens.push(Token::Tag {
+                name: name.to_string(),
+                markup: markup.to_string(),
+                position: start,
+            }),
+        }
+    }
+    match comment {
+        Some(start) => Err(LiquidError::at("'comment' tag was never closed", start)),
+        None => Ok(()),
+    }
+}
+
+/// Strips trailing whitespace from the text just before a `{{-` or `{%-`.
+fn trim_last_text(tokens: &mut Vec<Token>) {
+    if let Some(Token::Text(text)) = tokens.last_mut() {
+        text.truncate(text.trim_end().len());
+        if text.is_empty() {
+            tokens.pop();
+        }
+    }
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<Token>, LiquidError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut position = Position::START;
+    // The previous tag or output ended with `-}}` or `-%}`.
+    let mut trim_next = false;
+
+    while !rest.is_empty() {
+        let open = match (rest.find("{{"), rest.find("{%")) {
+            (Some(output), Some(tag)) => output.min(tag),
+            (Some(start), None) | (None, Some(start)) => start,
+            (None, None) => rest.len(),
+        };
+        if open > 0 {
+            let text = &rest[..open];
+            let text = if trim_next { text.trim_start() } else { text };
+            if !text.is_empty() {
+                tokens.push(Token::Text(text.to_string()));
+            }
+            position = position.advance(&rest[..open]);
+            rest = &rest[open..];
+            continue;
+        }
+
+        let is_output = rest.starts_with("{{");
+        let closing = if is_output { "}}" } else { "%}" };
+        let Some(close) = rest[2..].find(closing).map(|close| close + 2) else {
+            let kind = if is_output { "Output" } else { "Tag" };
+            return Err(LiquidError::at(
+                format!("{kind} was not closed with '{closing}'"),
+                position,
+            ));
+        };
+        let content = &rest[2..close];
+        let (content, trim_before) = match content.strip_prefix('-') {
+            Some(content) => (content, true),
+            None => (content, false),
+        };
+        let (content, trim_after) = match content.strip_suffix('-') {
+            Some(content) => (content, true),
+            None => (content, false),
+        };
+        if trim_before {
+            trim_last_text(&mut tokens);
+        }
+        trim_next = trim_after;
+        let markup = content.trim();
+        let start = position;
+        let content_start = start.advance(&rest[..if trim_before { 3 } else { 2 }]);
+        position = position.advance(&rest[..close + 2]);
+        rest = &rest[close + 2..];
+
+        if is_output {
+            tokens.push(Token::Output {
+                markup: markup.to_string(),
+                position: start,
+            });
+            continue;
+        }
+
+        let (name, markup) = match markup.split_once(char::is_whitespace) {
+            Some((name, markup)) => (name, markup.trim()),
+            None => (markup, ""),
+        };
+        match name {
+            "echo" => {
+                tokens.push(Token::Output {
+                    markup: markup.to_string(),
+                    position: start,
+                });
+                continue;
+            }
+            "liquid" => {
+                liquid_lines(content, content_start, &mut tokens)?;
+                continue;
+            }
+            _ => {}
+        }
+        if VERBATIM_TAGS.contains(&name) {
+            let Some((content_end, tag_end)) = find_end_tag(rest, name) else {
+                return Err(LiquidError::at(
+                    format!("'{name}' tag was never closed"),
+                    start,
+                ));
+            };
+            let end_tag = &rest[content_end..tag_end];
+            match name {
+                "raw" => {
+                    let mut text = &rest[..content_end];
+                    if trim_after {
+                        text = text.trim_start();
+                    }
+                    if end_tag.starts_with("{%-") {
+                        text = text.trim_end();
+                    }
+                    if !text.is_empty() {
+                        tokens.push(Token::Text(text.to_string()));
+                    }
+                }
+                "schema" => tokens.push(Token::Schema {
+                    content: rest[..content_end].to_string(),
+                    position: start,
+                }),
+                _ => {}
+            }
+            trim_next = end_tag.ends_with("-%}");
+            position = position.advance(&rest[..tag_end]);
+            rest = &rest[tag_end..];
+            continue;
+        }
+        tokens.push(Token::Tag {
+            name: name.to_string(),
+            markup: markup.to_string(),
+            position: start,
+        });
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Token {
+        Token::Text(text.to_string())
+    }
+
+    #[test]
+    fn splits_text_outputs_and_tags() {
+        let tokens = tokenize("Hi {{ name }}!\n{% if x %}").unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                text("Hi "),
+                Token::Output {
+                    markup: "name".to_string(),
+                    position: Position { line: 1, column: 4 },
+                },
+                text("!\n"),
+                Token::Tag {
+                    name: "if".to_string(),
+                    markup: "x".to_string(),
+                    position: Position { line: 2, column: 1 },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn dashes_trim_the_whitespace_beside_them() {
+        let tokens = tokenize("a \n {{- b -}} \n c {%- if x %} d").unwrap();
+
+        assert_eq!(tokens[0], text("a"));
+        assert_eq!(tokens[2], text("c"));
+        assert_eq!(tokens[4], text(" d"));
+    }
+
+    #[test]
+    fn unclosed_outputs_and_tags_fail_where_they_open() {
+        let error = tokenize("one\n  two {{ three").unwrap_err();
+        assert_eq!(error.message, "Output was not closed with '}}'");
+        assert_eq!(error.position, Some(Position { line: 2, column: 7 }));
+
+        let error = tokenize("{% if x %}\n{% endif").unwrap_err();
+        assert_eq!(error.message, "Tag was not closed with '%}'");
+        assert_eq!(error.position, Some(Position { line: 2, column: 1 }));
+    }
+
+    #[test]
+    fn raw_is_verbatim_and_comments_are_dropped() {
+        let tokens =
+            tokenize("{% raw %}{{ x }}{% endraw %}{% comment %}{{ y }}{% endcomment %}").unwrap();
+        assert_eq!(tokens, vec![text("{{ x }}")]);
+
+        let error = tokenize("\n{% comment %} never closed").unwrap_err();
+        assert_eq!(error.message, "'comment' tag was never closed");
+        assert_eq!(error.position, Some(Position { line: 2, column: 1 }));
+    }
+
+    #[test]
+    fn liquid_tags_split_into_lines() {
+        let tokens = tokenize("{% liquid\n  assign x = 1\n  # a note\n  echo x\n%}").unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Tag {
+                    name: "assign".to_string(),
+                    markup: "x = 1".to_string(),
+                    position: Position { line: 2, column: 3 },
+                },
+                Token::Output {
+                    markup: "x".to_string(),
+                    position: Position { line: 4, column: 3 },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn positions_advance_over_lines() {
+        let position = Position::START.advance("ab\ncd");
+        assert_eq!(position, Position { line: 2, column: 3 });
+    }
+}
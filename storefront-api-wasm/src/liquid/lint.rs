@@ -0,0 +1,360 @@
+//! Static checks of a template, for editors and CI.
+//!
+//! `lint` reports, each with its line, column and severity:
+//!
+//! - syntax errors such as unknown tags and unclosed blocks (parsing stops
+//!   at the first one, so at most one is reported);
+//! - filters the engine doesn't know, which fail in strict mode;
+//! - deprecated tags and filters, with what replaces them;
+//! - variables that are neither Shopify objects, nor assigned or looped
+//!   over earlier in the template, nor listed in `globals`.
+
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use super::engine::LiquidEngine;
+use super::error::{Diagnostic, Position, Severity};
+use super::expression::{Condition, Expr, Output, Segment};
+use super::filters::{self, FilterArgs};
+use super::parser::{Node, SnippetKind};
+use crate::{from_js, to_js};
+
+/// Objects Shopify provides to theme templates.
+const SHOPIFY_OBJECTS: &[&str] = &[
+    "additional_checkout_buttons",
+    "all_products",
+    "article",
+    "articles",
+    "block",
+    "blog",
+    "blogs",
+    "canonical_url",
+    "cart",
+    "checkout",
+    "collection",
+    "collections",
+    "content_for_additional_checkout_buttons",
+    "content_for_header",
+    "content_for_index",
+    "content_for_layout",
+    "current_page",
+    "current_tags",
+    "customer",
+    "form",
+    "gift_card",
+    "handle",
+    "images",
+    "linklists",
+    "localization",
+    "metaobjects",
+    "order",
+    "page",
+    "page_description",
+    "page_image",
+    "page_title",
+    "pages",
+    "powered_by_link",
+    "predictive_search",
+    "product",
+    "recommendations",
+    "request",
+    "routes",
+    "scripts",
+    "search",
+    "section",
+    "settings",
+    "shop",
+    "template",
+    "theme",
+];
+
+/// Deprecated filters and what replaces them.
+const DEPRECATED_FILTERS: &[(&str, &str)] = &[
+    ("img_url", "image_url"),
+    ("img_tag", "image_tag"),
+    ("product_img_url", "image_url"),
+    ("collection_img_url", "image_url"),
+    ("article_img_url", "image_url"),
+    ("currency_selector", "a localization form"),
+    ("hex_to_rgba", "color_to_rgb with color_modify"),
+];
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LintOptions {
+    /// Variables the template's data provides besides Shopify's objects.
+    pub globals: Vec<String>,
+}
+
+struct Linter<'a> {
+    engine: &'a LiquidEngine,
+    globals: BTreeSet<String>,
+    /// Assigned names first, then loop variables innermost last.
+    scopes: Vec<BTreeSet<String>>,
+    position: Option<Position>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, severity: Severity, message: String) {
+        let diagnostic = Diagnostic {
+            severity,
+            message,
+            line: self.position.map(|position| position.line),
+            column: self.position.map(|position| position.column),
+            source: None,
+        };
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn define(&mut self, name: &str) {
+        self.scopes[0].insert(name.to_string());
+    }
+
+    fn defined(&self, name: &str) -> bool {
+        SHOPIFY_OBJECTS.contains(&name)
+            || self.globals.contains(name)
+            || self.scopes.iter().any(|scope| scope.contains(name))
+    }
+
+    /// Lints `nodes` with `names` in scope.
+    fn scoped(&mut self, names: &[&str], nodes: &[Node]) {
+        self.scopes
+            .push(names.iter().map(|name| name.to_string()).collect());
+        self.nodes(nodes);
+        self.scopes.pop();
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            if let Some(position) = node.position() {
+                self.position = Some(position);
+            }
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &Node) {
+        match node {
+            Node::Text(_) | Node::Break | Node::Continue => {}
+            Node::Section { .. } | Node::SectionGroup { .. } => {}
+            Node::Increment(_) | Node::Decrement(_) => {}
+            Node::Output { output, .. } => self.output(output),
+            Node::If {
+                branches,
+                otherwise,
+                ..
+            } => {
+                for (condition, _) in branches {
+                    self.condition(condition);
+                }
+                for (_, body) in branches {
+                    self.nodes(body);
+                }
+                if let Some(body) = otherwise {
+                    self.nodes(body);
+                }
+            }
+            Node::For(for_loop) => {
+                self.expr(&for_loop.collection);
+                for option in [&for_loop.limit, &for_loop.offset].into_iter().flatten() {
+                    self.expr(option);
+                }
+                self.scoped(&[&for_loop.variable, "forloop"], &for_loop.body);
+                if let Some(body) = &for_loop.otherwise {
+                    self.nodes(body);
+                }
+            }
+            Node::Tablerow(tablerow) => {
+                self.expr(&tablerow.collection);
+                for option in [&tablerow.cols, &tablerow.limit, &tablerow.offset]
+                    .into_iter()
+                    .flatten()
+                {
+                    self.expr(option);
+                }
+                self.scoped(&[&tablerow.variable, "tablerowloop"], &tablerow.body);
+            }
+            Node::Assign { name, value, .. } => {
+                self.output(value);
+                self.define(name);
+            }
+            Node::Capture { name, body } => {
+                self.nodes(body);
+                self.define(name);
+            }
+            Node::Cycle { group, values, .. } => {
+                self.expr(group);
+                for value in values {
+                    self.expr(value);
+                }
+            }
+            Node::Custom(tag) => {
+                for arg in tag
+                    .args
+                    .iter()
+                    .chain(tag.keywords.iter().map(|(_, arg)| arg))
+                {
+                    self.expr(arg);
+                }
+                if let Some(body) = &tag.body {
+                    self.nodes(body);
+                }
+            }
+            Node::Paginate(paginate) => {
+                self.expr(&paginate.collection);
+                self.expr(&paginate.page_size);
+                if let Some(window) = &paginate.window_size {
+                    self.expr(window);
+                }
+                self.scoped(&["paginate"], &paginate.body);
+            }
+            Node::Snippet(tag) => {
+                if tag.kind == SnippetKind::Include {
+                    self.report(
+                        Severity::Warning,
+                        "'include' is deprecated; use 'render'".to_string(),
+                    );
+                }
+                self.expr(&tag.name);
+                if let Some(value) = &tag.value {
+                    self.expr(value);
+                }
+                for (_, value) in &tag.arguments {
+                    self.expr(value);
+                }
+            }
+            Node::Form(form) => {
+                self.expr(&form.kind);
+                if let Some(object) = &form.object {
+                    self.expr(object);
+                }
+                for (_, value) in &form.arguments {
+                    self.expr(value);
+                }
+                self.scoped(&["form"], &form.body);
+            }
+        }
+    }
+
+    fn output(&mut self, output: &Output) {
+        self.expr(&output.expr);
+        for filter in &output.filters {
+            let name = filter.name.as_str();
+            if let Some((_, replacement)) = DEPRECATED_FILTERS.iter().find(|(old, _)| *old == name)
+            {
+                self.report(
+                    Severity::Warning,
+                    format!("'{name}' is deprecated; use {replacement}"),
+                );
+            } else if !self.engine.knows_filter(name) {
+                self.report(Severity::Error, format!("Unknown filter '{name}'"));
+            }
+            for arg in filter
+                .args
+                .iter()
+                .chain(filter.keywords.iter().map(|(_, arg)| arg))
+            {
+                self.expr(arg);
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &Condition) {
+        match condition {
+            Condition::Test(expr) => self.expr(expr),
+            Condition::Compare(left, _, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                self.condition(left);
+                self.condition(right);
+            }
+            Condition::Not(condition) => self.condition(condition),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) | Expr::Empty | Expr::Blank => {}
+            Expr::Range(start, end) => {
+                self.expr(start);
+                self.expr(end);
+            }
+            Expr::Variable(segments) => {
+                if let Some(Segment::Key(name)) = segments.first() {
+                    if !self.defined(name) {
+                        self.report(Severity::Warning, format!("Unknown object '{name}'"));
+                    }
+                }
+                for segment in segments {
+                    if let Segment::Index(index) = segment {
+                        self.expr(index);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl LiquidEngine {
+    /// Whether `name` is a standard, money, translation, URL or registered
+    /// filter.
+    pub(crate) fn knows_filter(&self, name: &str) -> bool {
+        filters::standard(name).is_some()
+            || self.money_filter(name, &Value::Null).is_some()
+            || matches!(name, "t" | "translate")
+            || self
+                .url_filter(name, &Value::Null, &FilterArgs::default())
+                .is_some()
+            || self.extensions.borrow().has_filter(name)
+    }
+
+    /// Checks `source` without rendering it.
+    pub fn lint_template(&self, source: &str, options: &LintOptions) -> Vec<Diagnostic> {
+        let template = match self.parse_template(source) {
+            Ok(template) => template,
+            Err(error) => {
+                return vec![Diagnostic {
+                    severity: Severity::Error,
+                    ..Diagnostic::from(error)
+                }]
+            }
+        };
+        let mut linter = Linter {
+            engine: self,
+            globals: options.globals.iter().cloned().collect(),
+            scopes: vec![BTreeSet::new()],
+            position: None,
+            diagnostics: Vec::new(),
+        };
+        linter.nodes(&template.nodes);
+        linter.diagnostics
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Checks a template without rendering it, returning `{ severity,
+    /// message, line, column }` diagnostics (empty when all is well).
+    /// `options` is `{ globals }`: names the template's data provides
+    /// besides Shopify's objects.
+    #[wasm_bindgen(unchecked_return_type = "Diagnostic[]")]
+    pub fn lint(
+        &self,
+        source: &str,
+        #[wasm_bindgen(unchecked_optional_param_type = "LintOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options: LintOptions = match options {
+            Some(options) if !options.is_undefined() && !options.is_null() => from_js(options)?,
+            _ => LintOptions::default(),
+        };
+        to_js(&self.lint_template(source, &options))
+    }
+}
@@ -0,0 +1,237 @@
+//! Theme locale files and the `t` filter.
+//!
+//! Dictionaries are the JSON of a theme's `locales/*.json` files. Keys are
+//! looked up by dotted path in the active locale, then in the default one
+//! (the file named `*.default.json`). `{{ name }}` placeholders in a
+//! translation are filled from the filter's keyword arguments, and a
+//! translation that is an object of plural forms (`zero`, `one`, `two`,
+//! `few`, `many`, `other`) is picked by the `count` argument.
+
+use std::cell::RefMut;
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use super::engine::LiquidEngine;
+use super::filters::FilterArgs;
+use super::value::{as_number, to_text};
+use crate::from_js;
+
+/// Suffix marking the theme's default locale, as in `en.default.json`.
+const DEFAULT_SUFFIX: &str = ".default";
+
+/// Loaded locale dictionaries and which one templates render in.
+#[derive(Debug, Clone, Default)]
+pub struct Locales {
+    dictionaries: BTreeMap<String, Value>,
+    default: Option<String>,
+    active: Option<String>,
+}
+
+impl Locales {
+    /// Adds or replaces a locale. A name ending in `.default`, or the first
+    /// locale loaded, becomes the default.
+    pub fn load(&mut self, name: &str, dictionary: Value) -> Result<(), String> {
+        if !dictionary.is_object() {
+            return Err(format!("Locale {name} must be a JSON object"));
+        }
+        let (name, is_default) = match name.strip_suffix(DEFAULT_SUFFIX) {
+            Some(name) => (name, true),
+            None => (name, false),
+        };
+        if is_default || self.default.is_none() {
+            self.default = Some(name.to_string());
+        }
+        self.dictionaries.insert(name.to_string(), dictionary);
+        Ok(())
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<(), String> {
+        if !self.dictionaries.contains_key(name) {
+            return Err(format!("Locale {name} hasn't been loaded"));
+        }
+        self.active = Some(name.to_string());
+        Ok(())
+    }
+
+    pub fn default_locale(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// The locale templates render in: the active one, else the default.
+    pub fn current(&self) -> Option<&str> {
+        self.active.as_deref().or(self.default.as_deref())
+    }
+
+    fn lookup(&self, locale: &str, key: &str) -> Option<&Value> {
+        key.split('.')
+            .try_fold(self.dictionaries.get(locale)?, |value, part| {
+                value.get(part)
+            })
+    }
+
+    /// Translates `key`, falling back to the default locale and then to
+    /// Shopify's `translation missing: en.key` text.
+    pub fn translate(&self, key: &str, args: &FilterArgs) -> String {
+        let Some(current) = self.current() else {
+            return format!("translation missing: {key}");
+        };
+        let found = self.lookup(current, key).or_else(|| {
+            self.default
+                .as_deref()
+                .and_then(|default| self.lookup(default, key))
+        });
+        let count = args.keywords.get("count").and_then(as_number);
+        let text = match (found, count) {
+            (Some(Value::Object(forms)), Some(count)) => {
+                plural_form(forms, current, count).map(to_text)
+            }
+            (Some(Value::Object(_) | Value::Array(_)) | None, _) => None,
+            (Some(value), _) => Some(to_text(value)),
+        };
+        match text {
+            Some(text) => interpolate(&text, args),
+            None => format!("translation missing: {current}.{key}"),
+        }
+    }
+}
+
+/// CLDR plural category of `count` in `locale`, for the languages themes
+/// commonly ship. Others use the English rule.
+fn plural_category(locale: &str, count: f64) -> &'static str {
+    let language = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(locale)
+        .to_lowercase();
+    let whole = count.fract() == 0.0;
+    let n = count.abs() as u64;
+    let (n10, n100) = (n % 10, n % 100);
+    match language.as_str() {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "tr" => "other",
+        "fr" | "pt" if n <= 1 => "one",
+        "fr" | "pt" => "other",
+        "ru" | "uk" | "be" | "hr" | "sr" | "bs" if whole => {
+            if n10 == 1 && n100 != 11 {
+                "one"
+            } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "pl" if whole => {
+            if n == 1 {
+                "one"
+            } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "cs" | "sk" if whole => match n {
+            1 => "one",
+            2..=4 => "few",
+            _ => "other",
+        },
+        _ if whole && n == 1 => "one",
+        _ => "other",
+    }
+}
+
+/// Picks a plural form. As in Shopify, an explicit `zero` form wins for a
+/// count of 0, and `other` stands in for missing categories.
+fn plural_form<'a>(
+    forms: &'a serde_json::Map<String, Value>,
+    locale: &str,
+    count: f64,
+) -> Option<&'a Value> {
+    if count == 0.0 {
+        if let Some(zero) = forms.get("zero") {
+            return Some(zero);
+        }
+    }
+    forms
+        .get(plural_category(locale, count))
+        .or_else(|| forms.get("other"))
+}
+
+/// Replaces `{{ name }}` with the keyword argument `name`. Placeholders
+/// without an argument are left as they are.
+fn interpolate(text: &str, args: &FilterArgs) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let placeholder = &rest[start..start + end + 2];
+        match args
+            .keywords
+            .get(placeholder[2..placeholder.len() - 2].trim())
+        {
+            Some(value) => output.push_str(&to_text(value)),
+            None => output.push_str(placeholder),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+impl LiquidEngine {
+    /// The loaded locales, for callers outside JavaScript.
+    pub fn locales_mut(&self) -> RefMut<'_, Locales> {
+        self.locales.borrow_mut()
+    }
+
+    /// Applies `t` (or its long form `translate`) with the loaded locales.
+    pub(crate) fn translation_filter(
+        &self,
+        name: &str,
+        input: &Value,
+        args: &FilterArgs,
+    ) -> Option<Result<Value, String>> {
+        if name != "t" && name != "translate" {
+            return None;
+        }
+        let text = self.locales.borrow().translate(&to_text(input), args);
+        Some(Ok(Value::String(text)))
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Loads a theme locale file, e.g. `load_locale("fr", frJson)`. Name the
+    /// default locale `"en.default"` as its file is named; otherwise the
+    /// first locale loaded is the default.
+    #[wasm_bindgen]
+    pub fn load_locale(
+        &self,
+        name: &str,
+        #[wasm_bindgen(unchecked_param_type = "LocaleDictionary")] dictionary: JsValue,
+    ) -> Result<(), JsValue> {
+        let dictionary: Value = from_js(dictionary)?;
+        self.locales
+            .borrow_mut()
+            .load(name, dictionary)
+            .map_err(|error| JsValue::from_str(&error))
+    }
+
+    /// Renders `t` in a loaded locale from now on.
+    #[wasm_bindgen]
+    pub fn set_locale(&self, name: &str) -> Result<(), JsValue> {
+        self.locales
+            .borrow_mut()
+            .set_active(name)
+            .map_err(|error| JsValue::from_str(&error))
+    }
+
+    /// The locale `t` renders in, if any locale has been loaded.
+    #[wasm_bindgen(getter)]
+    pub fn locale(&self) -> Option<String> {
+        self.locales.borrow().current().map(String::from)
+    }
+}
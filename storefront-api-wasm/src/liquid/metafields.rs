@@ -0,0 +1,248 @@
+//! The `metafield` drop.
+//!
+//! Products and collections fetched with metafields answer
+//! `product.metafields.custom.fit`. Written out, a metafield renders
+//! according to its type, as on the Online Store: text as it is, money as
+//! `12.50 USD`, dimensions, weights and volumes as `2.5 cm`, ratings as
+//! `4.5 out of 5.0`, links as their text, rich text as HTML, references as
+//! the title of what they point at and lists as their items joined with
+//! commas. `.value` is the typed value instead: money in cents (so `money`
+//! formats it), lists as arrays and references as drops when the metafield
+//! was fetched with them resolved.
+
+use serde_json::{Map, Value};
+
+use super::drops::{image_drop, numeric_id, product_drop, variant_drop};
+use super::filters::escape_html;
+use super::value::{to_text, TEXT_KEY};
+use crate::metafield::typed_value;
+use crate::types::{Metafield, Metaobject, ResolvedReference};
+
+/// A `money` value in cents.
+fn money_cents(money: &Value) -> Option<i64> {
+    let amount: f64 = match &money["amount"] {
+        Value::String(amount) => amount.trim().parse().ok()?,
+        amount => amount.as_f64()?,
+    };
+    Some((amount * 100.0).round() as i64)
+}
+
+/// A value of a measurement, rating or link type as text.
+fn scalar_text(kind: &str, value: &Value) -> String {
+    match kind {
+        "money" => match (&value["amount"], value["currency_code"].as_str()) {
+            (Value::Null, _) => to_text(value),
+            (amount, Some(currency)) => format!("{} {currency}", to_text(amount)),
+            (amount, None) => to_text(amount),
+        },
+        "dimension" | "volume" | "weight" => {
+            format!("{} {}", to_text(&value["value"]), to_text(&value["unit"]))
+                .trim()
+                .to_string()
+        }
+        "rating" => format!(
+            "{} out of {}",
+            to_text(&value["value"]),
+            to_text(&value["scale_max"])
+        ),
+        "link" => match &value["text"] {
+            Value::String(text) if !text.is_empty() => text.clone(),
+            _ => to_text(&value["url"]),
+        },
+        "rich_text_field" => rich_text_html(value),
+        "json" => value.to_string(),
+        _ => to_text(value),
+    }
+}
+
+/// Shopify's rich text JSON as HTML.
+fn rich_text_html(node: &Value) -> String {
+    let children: String = node["children"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(rich_text_html)
+        .collect();
+    match node["type"].as_str().unwrap_or_default() {
+        "paragraph" => format!("<p>{children}</p>"),
+        "heading" => {
+            let level = node["level"].as_u64().unwrap_or(1).clamp(1, 6);
+            format!("<h{level}>{children}</h{level}>")
+        }
+        "list" if node["listType"] == "ordered" => format!("<ol>{children}</ol>"),
+        "list" => format!("<ul>{children}</ul>"),
+        "list-item" => format!("<li>{children}</li>"),
+        "link" => {
+            let title = match node["title"].as_str() {
+                Some(title) => format!(" title=\"{}\"", escape_html(title)),
+                None => String::new(),
+            };
+            format!(
+                "<a href=\"{}\"{title}>{children}</a>",
+                escape_html(node["url"].as_str().unwrap_or_default())
+            )
+        }
+        "text" => {
+            let mut text = escape_html(node["value"].as_str().unwrap_or_default());
+            if node["italic"] == true {
+                text = format!("<em>{text}</em>");
+            }
+            if node["bold"] == true {
+                text = format!("<strong>{text}</strong>");
+            }
+            text
+        }
+        _ => children,
+    }
+}
+
+fn metaobject_drop(metaobject: &Metaobject) -> Value {
+    let mut drop = Map::new();
+    for field in &metaobject.fields {
+        let value = field
+            .value
+            .as_deref()
+            .map_or(Value::Null, |value| typed_value(&field.kind, value));
+        drop.insert(field.key.clone(), value);
+    }
+    drop.insert("id".to_string(), numeric_id(&metaobject.id));
+    drop.insert(
+        "handle".to_string(),
+        Value::from(metaobject.handle.as_str()),
+    );
+    drop.insert("type".to_string(), Value::from(metaobject.kind.as_str()));
+    Value::Object(drop)
+}
+
+/// A resolved reference's drop and how it's written out.
+fn reference_drop(reference: &ResolvedReference) -> (Value, String) {
+    match reference {
+        ResolvedReference::Product(product) => (product_drop(product), product.title.clone()),
+        ResolvedReference::ProductVariant(variant) => {
+            (variant_drop(variant, None), variant.title.clone())
+        }
+        ResolvedReference::Collection(collection) => (
+            serde_json::json!({
+                "id": numeric_id(&collection.id),
+                "handle": collection.handle,
+                "title": collection.title,
+                "url": format!("/collections/{}", collection.handle),
+            }),
+            collection.title.clone(),
+        ),
+        ResolvedReference::Page(page) => (
+            serde_json::json!({
+                "id": numeric_id(&page.id),
+                "handle": page.handle,
+                "title": page.title,
+                "url": format!("/pages/{}", page.handle),
+            }),
+            page.title.clone(),
+        ),
+        ResolvedReference::Metaobject(metaobject) => {
+            (metaobject_drop(metaobject), metaobject.handle.clone())
+        }
+        ResolvedReference::MediaImage(media) => match &media.image {
+            Some(image) => (image_drop(image), image.url.clone()),
+            None => (Value::Null, String::new()),
+        },
+        ResolvedReference::Video(video) => (
+            serde_json::json!({
+                "id": numeric_id(&video.id),
+                "alt": video.alt,
+                "preview_image": video.preview_image.as_ref().map(image_drop),
+                "sources": video.sources,
+            }),
+            video.alt.clone().unwrap_or_default(),
+        ),
+        ResolvedReference::GenericFile(file) => {
+            let url = file.url.clone().unwrap_or_default();
+            (
+                serde_json::json!({
+                    "id": numeric_id(&file.id),
+                    "url": file.url,
+                    "alt": file.alt,
+                    "mime_type": file.mime_type,
+                }),
+                url,
+            )
+        }
+        ResolvedReference::Unknown => (Value::Null, String::new()),
+    }
+}
+
+/// The typed value of a metafield and its text.
+fn metafield_value(metafield: &Metafield) -> (Value, String) {
+    let kind = metafield.kind.as_str();
+    let item_kind = kind.strip_prefix("list.");
+    let references: Option<Vec<(Value, String)>> = match (item_kind, &metafield.references) {
+        (Some(_), Some(references)) => Some(references.nodes().map(reference_drop).collect()),
+        (None, _) => metafield
+            .reference
+            .as_ref()
+            .map(|reference| vec![reference_drop(reference)]),
+        _ => None,
+    };
+    if let Some(references) = references {
+        let texts: Vec<String> = references.iter().map(|(_, text)| text.clone()).collect();
+        let mut values = references.into_iter().map(|(value, _)| value);
+        return match item_kind {
+            Some(_) => (Value::Array(values.collect()), texts.join(", ")),
+            None => (values.next().unwrap_or(Value::Null), texts.concat()),
+        };
+    }
+
+    let value = typed_value(kind, &metafield.value);
+    match (item_kind, value) {
+        (Some(item_kind), Value::Array(items)) => {
+            let texts: Vec<String> = items
+                .iter()
+                .map(|item| scalar_text(item_kind, item))
+                .collect();
+            let items = if item_kind == "money" {
+                items
+                    .iter()
+                    .map(|item| money_cents(item).map_or(Value::Null, Value::from))
+                    .collect()
+            } else {
+                items
+            };
+            (Value::Array(items), texts.join(", "))
+        }
+        (_, value) if kind == "money" => {
+            let text = scalar_text(kind, &value);
+            (money_cents(&value).map_or(value, Value::from), text)
+        }
+        (_, value) => {
+            let text = scalar_text(kind, &value);
+            (value, text)
+        }
+    }
+}
+
+pub fn metafield_drop(metafield: &Metafield) -> Value {
+    let (value, text) = metafield_value(metafield);
+    serde_json::json!({
+        "namespace": metafield.namespace,
+        "key": metafield.key,
+        "type": metafield.kind,
+        "value": value,
+        "list?": metafield.kind.starts_with("list."),
+        TEXT_KEY: text,
+    })
+}
+
+/// `metafields.namespace.key`, for the metafields an object was fetched
+/// with.
+pub fn metafields_drop(metafields: &[Option<Metafield>]) -> Value {
+    let mut namespaces = Map::new();
+    for metafield in metafields.iter().flatten() {
+        let namespace = namespaces
+            .entry(metafield.namespace.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(namespace) = namespace {
+            namespace.insert(metafield.key.clone(), metafield_drop(metafield));
+        }
+    }
+    Value::Object(namespaces)
+}
@@ -0,0 +1,150 @@
+//! The `money` filter family, formatted with the shop's currency and money
+//! format templates.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use super::engine::LiquidEngine;
+use super::value::{as_number, to_text};
+use crate::money::currency_symbol;
+use crate::types::Money;
+use crate::{from_js, to_js};
+
+/// The shop's currency and the templates its theme formats prices with, as
+/// set under Settings > General > Store currency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MoneyFormat {
+    /// ISO 4217 code of the shop's currency.
+    pub currency: String,
+    /// Used by `money`, e.g. `${{amount}}`. Defaults to the currency's
+    /// symbol before `{{amount}}`.
+    pub money_format: Option<String>,
+    /// Used by `money_with_currency`, e.g. `${{amount}} USD`. Defaults to
+    /// `money_format` followed by the currency code.
+    pub money_with_currency_format: Option<String>,
+}
+
+impl Default for MoneyFormat {
+    fn default() -> Self {
+        MoneyFormat {
+            currency: "USD".to_string(),
+            money_format: None,
+            money_with_currency_format: None,
+        }
+    }
+}
+
+impl MoneyFormat {
+    pub fn money_format(&self) -> String {
+        if let Some(format) = &self.money_format {
+            return format.clone();
+        }
+        match currency_symbol(&self.currency) {
+            Some(symbol) if symbol.chars().all(char::is_alphabetic) => {
+                format!("{symbol} {{{{amount}}}}")
+            }
+            Some(symbol) => format!("{symbol}{{{{amount}}}}"),
+            None => format!("{{{{amount}}}} {}", self.currency),
+        }
+    }
+
+    pub fn money_with_currency_format(&self) -> String {
+        if let Some(format) = &self.money_with_currency_format {
+            return format.clone();
+        }
+        let format = self.money_format();
+        if format.contains(&self.currency) {
+            format
+        } else {
+            format!("{format} {}", self.currency)
+        }
+    }
+}
+
+/// `money_without_trailing_zeros` swaps each amount placeholder for its
+/// `no_decimals` counterpart when the amount is whole.
+fn without_decimals(format: &str) -> String {
+    [
+        ("{{amount}}", "{{amount_no_decimals}}"),
+        (
+            "{{amount_with_comma_separator}}",
+            "{{amount_no_decimals_with_comma_separator}}",
+        ),
+        (
+            "{{amount_with_space_separator}}",
+            "{{amount_no_decimals_with_space_separator}}",
+        ),
+    ]
+    .iter()
+    .fold(
+        format.replace("{{ ", "{{").replace(" }}", "}}"),
+        |format, (from, to)| format.replace(from, to),
+    )
+}
+
+impl LiquidEngine {
+    pub fn set_money_settings(&self, mut settings: MoneyFormat) {
+        settings.currency = settings.currency.to_uppercase();
+        *self.money.borrow_mut() = settings;
+    }
+
+    /// Applies `name` if it is one of the money filters. Their input is an
+    /// amount in cents, as drops give prices; `nil` renders as nothing.
+    pub(crate) fn money_filter(&self, name: &str, input: &Value) -> Option<Result<Value, String>> {
+        let settings = self.money.borrow();
+        let format = match name {
+            "money" => settings.money_format(),
+            "money_with_currency" => settings.money_with_currency_format(),
+            "money_without_currency" => "{{amount}}".to_string(),
+            "money_without_trailing_zeros" => settings.money_format(),
+            _ => return None,
+        };
+        if input.is_null() {
+            return Some(Ok(Value::String(String::new())));
+        }
+        let Some(cents) = as_number(input) else {
+            return Some(Err(format!("{} is not an amount in cents", to_text(input))));
+        };
+        let whole = cents.round() as i64 % 100 == 0;
+        let format = if name == "money_without_trailing_zeros" && whole {
+            without_decimals(&format)
+        } else {
+            format
+        };
+        let money = Money {
+            amount: format!("{:.2}", cents.round() / 100.0),
+            currency_code: settings.currency.clone(),
+        };
+        Some(Ok(Value::String(money.format_with(&format))))
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Sets the shop currency and money format templates used by the
+    /// `money` filters: `{ currency: "EUR", moneyFormat:
+    /// "€{{amount_with_comma_separator}}" }`. Omitting `options` restores
+    /// USD with `${{amount}}`.
+    #[wasm_bindgen]
+    pub fn set_money_format(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "Partial<MoneyFormat>")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        let settings: MoneyFormat = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => MoneyFormat::default(),
+        };
+        self.set_money_settings(settings);
+        Ok(())
+    }
+
+    /// The current money settings.
+    #[wasm_bindgen(unchecked_return_type = "MoneyFormat")]
+    pub fn money_format(&self) -> Result<JsValue, JsValue> {
+        to_js(&*self.money.borrow())
+    }
+}
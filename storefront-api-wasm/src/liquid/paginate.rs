@@ -0,0 +1,313 @@
+//! The `paginate` tag.
+//!
+//! Inside `{% paginate collection.products by 24 %}` the paginated path
+//! holds only the current page (`current_page`, from the template's data)
+//! and `paginate` describes the pages. When the engine has a Storefront
+//! client and the path is a collection's `products`, each page is fetched
+//! with cursor pagination: page numbers are mapped onto cursors, which are
+//! cached so later pages don't walk the collection again. Otherwise the
+//! array already in the data is sliced.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use super::drops::product_drop;
+use super::engine::LiquidEngine;
+use super::error::LiquidError;
+use super::expression::{Expr, Segment};
+use super::parser::Paginate;
+use super::render::Context;
+use super::value::{as_integer, to_text};
+use crate::collection::{CollectionOptions, MAX_PAGE_SIZE};
+use crate::pagination::PageArgs;
+use crate::StorefrontApi;
+
+/// Pages either side of the current one listed in `paginate.parts`.
+const DEFAULT_WINDOW_SIZE: i64 = 3;
+
+/// Only the cursors of a collection page, used to skip ahead to a page
+/// without fetching the products before it.
+const PAGE_CURSOR_QUERY: &str = r#"
+    query paginateCollection($handle: String!, $first: Int!, $after: String) {
+        collection(handle: $handle) {
+            products(first: $first, after: $after) {
+                edges { cursor }
+                pageInfo { hasNextPage endCursor }
+            }
+        }
+    }
+"#;
+
+/// End cursors of the pages fetched so far, by collection handle and page
+/// size: `cursors[n]` is where page `n + 2` starts.
+#[derive(Debug, Clone, Default)]
+pub struct CursorCache {
+    pages: BTreeMap<(String, u32), Vec<String>>,
+}
+
+impl CursorCache {
+    fn cursors(&mut self, handle: &str, page_size: u32) -> &mut Vec<String> {
+        self.pages
+            .entry((handle.to_string(), page_size))
+            .or_default()
+    }
+
+    pub fn clear(&mut self) {
+        self.pages.clear();
+    }
+}
+
+/// One page of a paginated collection.
+struct Page {
+    items: Vec<Value>,
+    /// Total items, when known. The Storefront API doesn't count a
+    /// collection's products, so this is only known on the last page.
+    total: Option<usize>,
+    has_next: bool,
+}
+
+pub(crate) fn js_message(error: JsValue) -> String {
+    error
+        .as_string()
+        .unwrap_or_else(|| "Storefront request failed".to_string())
+}
+
+/// A link in `paginate.parts`, `paginate.previous` or `paginate.next`.
+fn part(title: impl Into<Value>, page: Option<i64>) -> Value {
+    serde_json::json!({
+        "title": title.into(),
+        "url": page.map(|page| format!("?page={page}")),
+        "is_link": page.is_some(),
+    })
+}
+
+/// The `paginate` drop. `pages` counts the pages known so far: when the
+/// total is unknown there is at least one more after the current page.
+fn paginate_drop(page: &Page, current: i64, page_size: u32, window: i64) -> Value {
+    let size = i64::from(page_size);
+    let pages = match page.total {
+        Some(total) => ((total as i64 + size - 1) / size).max(1),
+        None if page.has_next => current + 1,
+        None => current,
+    };
+    let mut parts = Vec::new();
+    let mut gap = false;
+    for number in 1..=pages {
+        if number == 1 || number == pages || (number - current).abs() <= window {
+            let link = (number != current).then_some(number);
+            parts.push(part(number, link));
+            gap = false;
+        } else if !gap {
+            parts.push(part("&hellip;", None));
+            gap = true;
+        }
+    }
+    serde_json::json!({
+        "current_page": current,
+        "current_offset": (current - 1) * size,
+        "page_size": page_size,
+        "pages": pages,
+        "items": page.total,
+        "parts": parts,
+        "previous": (current > 1).then(|| part("&laquo; Previous", Some(current - 1))),
+        "next": (current < pages).then(|| part("Next &raquo;", Some(current + 1))),
+    })
+}
+
+/// Replaces the value at `path` (property names) inside `root`.
+fn replace_at(root: &mut Value, path: &[String], value: Value) {
+    match path.split_first() {
+        None => *root = value,
+        Some((key, rest)) => {
+            if !root.is_object() {
+                *root = Value::Object(Default::default());
+            }
+            let child = root
+                .as_object_mut()
+                .expect("object")
+                .entry(key.clone())
+                .or_insert(Value::Null);
+            replace_at(child, rest, value);
+        }
+    }
+}
+
+impl LiquidEngine {
+    pub(crate) async fn render_paginate(
+        &self,
+        paginate: &Paginate,
+        context: &mut Context,
+        output: &mut String,
+    ) -> Result<(), LiquidError> {
+        let fail = |message: String| LiquidError::at(message, paginate.position);
+        let Expr::Variable(segments) = &paginate.collection else {
+            return Err(fail("paginate needs a variable".to_string()));
+        };
+        let path = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Key(key) => Ok(key.clone()),
+                Segment::Index(_) => Err(fail(
+                    "paginate needs a plain path such as collection.products".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let page_size = self.evaluate(&paginate.page_size, context).map_err(fail)?;
+        let page_size = as_integer(&page_size)
+            .and_then(|size| u32::try_from(size).ok())
+            .filter(|size| (1..=MAX_PAGE_SIZE).contains(size))
+            .ok_or_else(|| {
+                fail(format!(
+                    "Page size must be between 1 and {MAX_PAGE_SIZE}, got {}",
+                    to_text(&page_size)
+                ))
+            })?;
+        let window = match &paginate.window_size {
+            Some(window) => as_integer(&self.evaluate(window, context).map_err(fail)?)
+                .ok_or_else(|| fail("window_size must be a number".to_string()))?,
+            None => DEFAULT_WINDOW_SIZE,
+        };
+        let current = as_integer(&context.get("current_page"))
+            .filter(|page| *page >= 1)
+            .unwrap_or(1);
+
+        // `collection.products` of a collection with a handle is fetched.
+        let handle = match path.split_last() {
+            Some((last, parents)) if last == "products" && !parents.is_empty() => parents[1..]
+                .iter()
+                .fold(context.get(&parents[0]), |value, key| {
+                    value.get(key).cloned().unwrap_or(Value::Null)
+                })
+                .get("handle")
+                .and_then(Value::as_str)
+                .map(String::from),
+            _ => None,
+        };
+        let api = self.storefront.borrow().clone();
+        let page = match (api, handle) {
+            (Some(api), Some(handle)) => self
+                .collection_page(&api, &handle, page_size, current)
+                .await
+                .map_err(fail)?,
+            _ => {
+                let items = self.evaluate(&paginate.collection, context).map_err(fail)?;
+                let items = match items {
+                    Value::Array(items) => items,
+                    Value::Null => Vec::new(),
+                    other => vec![other],
+                };
+                let total = items.len();
+                let offset = ((current - 1) as usize).saturating_mul(page_size as usize);
+                Page {
+                    items: items
+                        .into_iter()
+                        .skip(offset)
+                        .take(page_size as usize)
+                        .collect(),
+                    total: Some(total),
+                    has_next: offset + (page_size as usize) < total,
+                }
+            }
+        };
+
+        let drop = paginate_drop(&page, current, page_size, window);
+        let mut root = context.get(&path[0]);
+        replace_at(&mut root, &path[1..], Value::Array(page.items));
+        context.push();
+        context.set_local(&path[0], root);
+        context.set_local("paginate", drop);
+        let rendered = self.render_nodes(&paginate.body, context, output).await;
+        context.pop();
+        rendered
+    }
+
+    /// Fetches page `number` of a collection's products, walking cursors
+    /// forward from the furthest page seen at this size.
+    async fn collection_page(
+        &self,
+        api: &StorefrontApi,
+        handle: &str,
+        page_size: u32,
+        number: i64,
+    ) -> Result<Page, String> {
+        let wanted = (number - 1) as usize;
+        let mut known = self.cursors.borrow_mut().cursors(handle, page_size).clone();
+        while known.len() < wanted {
+            let variables = serde_json::json!({
+                "handle": handle,
+                "first": page_size,
+                "after": known.last(),
+            });
+            let data = api
+                .execute(PAGE_CURSOR_QUERY, variables)
+                .await
+                .map_err(js_message)?;
+            let products = &data["collection"]["products"];
+            let page_info = &products["pageInfo"];
+            match page_info["endCursor"].as_str() {
+                Some(cursor) if page_info["hasNextPage"] == Value::Bool(true) => {
+                    known.push(cursor.to_string());
+                    self.cursors
+                        .borrow_mut()
+                        .cursors(handle, page_size)
+                        .clone_from(&known);
+                }
+                // The collection ends before the requested page.
+                _ => {
+                    let last_page = products["edges"].as_array().map_or(0, Vec::len);
+                    return Ok(Page {
+                        items: Vec::new(),
+                        total: Some(known.len() * page_size as usize + last_page),
+                        has_next: false,
+                    });
+                }
+            }
+        }
+
+        let after = wanted.checked_sub(1).map(|index| known[index].clone());
+        let collection = api
+            .fetch_collection_page(
+                handle,
+                &PageArgs::forward(page_size, after),
+                &CollectionOptions::default(),
+            )
+            .await
+            .map_err(js_message)?
+            .ok_or_else(|| format!("Collection not found: {handle}"))?
+            .collection;
+        let connection = collection.products.unwrap_or_default();
+        let page_info = connection.page_info.clone().unwrap_or_default();
+        let items: Vec<Value> = connection.nodes().map(product_drop).collect();
+        if let Some(cursor) = page_info.end_cursor.filter(|_| page_info.has_next_page) {
+            let mut cache = self.cursors.borrow_mut();
+            let cursors = cache.cursors(handle, page_size);
+            if cursors.len() == wanted {
+                cursors.push(cursor);
+            }
+        }
+        Ok(Page {
+            total: (!page_info.has_next_page).then(|| wanted * page_size as usize + items.len()),
+            items,
+            has_next: page_info.has_next_page,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Lets `paginate` fetch collection pages from the Storefront API.
+    #[wasm_bindgen]
+    pub fn set_storefront(&self, api: &StorefrontApi) {
+        *self.storefront.borrow_mut() = Some(api.clone());
+        self.cursors.borrow_mut().clear();
+    }
+
+    /// Forgets the cursors `paginate` has cached, after the catalog changes.
+    #[wasm_bindgen]
+    pub fn clear_pagination_cache(&self) {
+        self.cursors.borrow_mut().clear();
+    }
+}
@@ -0,0 +1,865 @@
+//! Builds the node tree of a template from its tokens.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use super::error::{LiquidError, Position};
+use super::expression::{parse_condition, parse_output, Condition, Expr, Markup, Operator, Output};
+use super::lexer::{tokenize, Token, VERBATIM_TAGS};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Text(String),
+    Output {
+        output: Output,
+        position: Position,
+    },
+    /// `if`/`elsif` branches in order, then `else`. `unless` and `case`
+    /// parse to this too.
+    If {
+        branches: Vec<(Condition, Vec<Node>)>,
+        otherwise: Option<Vec<Node>>,
+        position: Position,
+    },
+    For(Box<ForLoop>),
+    Assign {
+        name: String,
+        value: Output,
+        position: Position,
+    },
+    /// `capture name`: renders the body into a variable.
+    Capture {
+        name: String,
+        body: Vec<Node>,
+    },
+    /// `increment name`: outputs the counter, then adds one.
+    Increment(String),
+    /// `decrement name`: subtracts one, then outputs the counter.
+    Decrement(String),
+    /// `cycle 'a', 'b'`: outputs the next value of its group.
+    Cycle {
+        /// Named with `cycle 'group': ...`, else the tag's markup.
+        group: Expr,
+        values: Vec<Expr>,
+        position: Position,
+    },
+    Tablerow(Box<Tablerow>),
+    /// A tag registered from JavaScript.
+    Custom(Box<CustomTag>),
+    Break,
+    Continue,
+    Paginate(Box<Paginate>),
+    Snippet(Box<SnippetTag>),
+    Form(Box<FormTag>),
+    /// `section 'name'`: a registered section with its default settings.
+    Section {
+        name: String,
+        position: Position,
+    },
+    /// `sections 'name'`: a registered section group.
+    SectionGroup {
+        name: String,
+        position: Position,
+    },
+}
+
+/// `for item in collection reversed limit: 4, offset: 2`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForLoop {
+    pub variable: String,
+    pub collection: Expr,
+    pub limit: Option<Expr>,
+    pub offset: Option<Expr>,
+    /// Loops backwards, after `offset` and `limit` are applied.
+    pub reversed: bool,
+    pub body: Vec<Node>,
+    /// Rendered when there is nothing to loop over.
+    pub otherwise: Option<Vec<Node>>,
+    pub position: Position,
+}
+
+/// `tablerow item in collection cols: 3, limit: 6, offset: 0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tablerow {
+    pub variable: String,
+    pub collection: Expr,
+    /// Cells per row; all in one row when unset.
+    pub cols: Option<Expr>,
+    pub limit: Option<Expr>,
+    pub offset: Option<Expr>,
+    pub body: Vec<Node>,
+    pub position: Position,
+}
+
+impl Node {
+    /// Where the node's tag or output starts, for nodes that evaluate
+    /// markup.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Node::Output { position, .. }
+            | Node::If { position, .. }
+            | Node::Assign { position, .. }
+            | Node::Cycle { position, .. }
+            | Node::Section { position, .. }
+            | Node::SectionGroup { position, .. } => Some(*position),
+            Node::For(for_loop) => Some(for_loop.position),
+            Node::Tablerow(tablerow) => Some(tablerow.position),
+            Node::Custom(tag) => Some(tag.position),
+            Node::Paginate(paginate) => Some(paginate.position),
+            Node::Snippet(tag) => Some(tag.position),
+            Node::Form(form) => Some(form.position),
+            Node::Text(_)
+            | Node::Capture { .. }
+            | Node::Increment(_)
+            | Node::Decrement(_)
+            | Node::Break
+            | Node::Continue => None,
+        }
+    }
+}
+
+/// Whether a custom tag wraps a body closed by `end<name>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagKind {
+    Simple,
+    Block,
+}
+
+/// `name value, key: value`, for a tag registered from JavaScript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomTag {
+    pub name: String,
+    pub args: Vec<Expr>,
+    pub keywords: Vec<(String, Expr)>,
+    /// Block tags only.
+    pub body: Option<Vec<Node>>,
+    pub position: Position,
+}
+
+/// `paginate collection.products by 24, window_size: 3`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paginate {
+    /// The variable path being paginated.
+    pub collection: Expr,
+    pub page_size: Expr,
+    /// Pages shown either side of the current one in `paginate.parts`.
+    pub window_size: Option<Expr>,
+    pub body: Vec<Node>,
+    pub position: Position,
+}
+
+/// `render` gives a snippet its own variables; `include` shares the
+/// caller's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetKind {
+    Render,
+    Include,
+}
+
+/// `render 'name' with value as alias, key: value` or
+/// `render 'name' for items as item`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnippetTag {
+    pub kind: SnippetKind,
+    pub name: Expr,
+    /// The `with` or `for` value.
+    pub value: Option<Expr>,
+    /// Renders the snippet once per item of `value`.
+    pub each: bool,
+    /// Variable `value` is passed as; defaults to the snippet's name.
+    pub alias: Option<String>,
+    pub arguments: Vec<(String, Expr)>,
+    pub position: Position,
+}
+
+/// `form 'product', product, class: 'product-form'`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormTag {
+    pub kind: Expr,
+    /// The object the form acts on, such as the product or address.
+    pub object: Option<Expr>,
+    /// HTML attributes and `return_to`.
+    pub arguments: Vec<(String, Expr)>,
+    pub body: Vec<Node>,
+    pub position: Position,
+}
+
+/// A parsed template, ready to render.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub nodes: Vec<Node>,
+    /// A section's `{% schema %}`.
+    pub schema: Option<Value>,
+}
+
+/// A tag token.
+struct Tag {
+    name: String,
+    markup: String,
+    position: Position,
+}
+
+/// Tags the parser handles itself, including the ones only valid inside
+/// another tag.
+const BUILT_IN_TAGS: &[&str] = &[
+    "if",
+    "elsif",
+    "else",
+    "unless",
+    "case",
+    "when",
+    "for",
+    "tablerow",
+    "break",
+    "continue",
+    "assign",
+    "capture",
+    "increment",
+    "decrement",
+    "cycle",
+    "paginate",
+    "form",
+    "render",
+    "include",
+    "section",
+    "sections",
+    "liquid",
+    "echo",
+];
+
+/// Whether `name` is a tag of the engine's own, which custom tags can't
+/// replace.
+pub fn is_built_in_tag(name: &str) -> bool {
+    BUILT_IN_TAGS.contains(&name) || VERBATIM_TAGS.contains(&name) || name.starts_with("end")
+}
+
+struct Parser {
+    tokens: std::vec::IntoIter<Token>,
+    schema: Option<Value>,
+    custom_tags: BTreeMap<String, TagKind>,
+}
+
+/// The quoted name a `section` or `sections` tag takes.
+fn quoted_name(tag: &Tag, example: &str) -> Result<String, LiquidError> {
+    let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+    let name = match at(markup.expression(), tag.position)? {
+        Expr::Literal(Value::String(name)) => name,
+        _ => {
+            return Err(LiquidError::at(
+                format!("Expected a quoted name, as in {example}"),
+                tag.position,
+            ))
+        }
+    };
+    at(markup.finish(), tag.position)?;
+    Ok(name)
+}
+
+/// Attaches the tag's position to a markup error.
+fn at<T>(result: Result<T, String>, position: Position) -> Result<T, LiquidError> {
+    result.map_err(|message| LiquidError::at(message, position))
+}
+
+impl Parser {
+    /// Parses nodes up to one of the `ends` tags, which is returned. With no
+    /// `ends`, parses to the end of the template; otherwise running out of
+    /// tokens means `opening` was never closed.
+    fn block(
+        &mut self,
+        opening: Option<&Tag>,
+        ends: &[&str],
+    ) -> Result<(Vec<Node>, Option<Tag>), LiquidError> {
+        let mut nodes = Vec::new();
+        while let Some(token) = self.tokens.next() {
+            match token {
+                Token::Text(text) => nodes.push(Node::Text(text)),
+                Token::Output { markup, position } => nodes.push(Node::Output {
+                    output: at(parse_output(&markup), position)?,
+                    position,
+                }),
+                Token::Tag {
+                    name,
+                    markup,
+                    position,
+                } => {
+                    let tag = Tag {
+                        name,
+                        markup,
+                        position,
+                    };
+                    if ends.contains(&tag.name.as_str()) {
+                        return Ok((nodes, Some(tag)));
+                    }
+                    nodes.push(self.tag(tag)?);
+                }
+                Token::Schema { content, position } => {
+                    if opening.is_some() || self.schema.is_some() {
+                        return Err(LiquidError::at(
+                            "A template can only have one 'schema', outside any tag",
+                            position,
+                        ));
+                    }
+                    let schema = serde_json::from_str(&content).map_err(|error| {
+                        LiquidError::at(format!("Invalid schema JSON: {error}"), position)
+                    })?;
+                    self.schema = Some(schema);
+                }
+            }
+        }
+        match opening {
+            Some(tag) => Err(LiquidError::at(
+                format!("'{}' tag was never closed", tag.name),
+                tag.position,
+            )),
+            None => Ok((nodes, None)),
+        }
+    }
+
+    fn tag(&mut self, tag: Tag) -> Result<Node, LiquidError> {
+        match tag.name.as_str() {
+            "if" => self.if_tag(tag),
+            "unless" => self.unless_tag(tag),
+            "case" => self.case_tag(tag),
+            "for" => self.for_tag(tag),
+            "tablerow" => self.tablerow_tag(tag),
+            "assign" => {
+                let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+                let name = at(markup.ident(), tag.position)?;
+                at(markup.equals(), tag.position)?;
+                let value = at(markup.output(), tag.position)?;
+                at(markup.finish(), tag.position)?;
+                Ok(Node::Assign {
+                    name,
+                    value,
+                    position: tag.position,
+                })
+            }
+            "capture" => {
+                let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+                let name = at(markup.ident(), tag.position)?;
+                at(markup.finish(), tag.position)?;
+                let (body, _) = self.block(Some(&tag), &["endcapture"])?;
+                Ok(Node::Capture { name, body })
+            }
+            "increment" | "decrement" => {
+                let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+                let name = at(markup.ident(), tag.position)?;
+                at(markup.finish(), tag.position)?;
+                Ok(if tag.name == "increment" {
+                    Node::Increment(name)
+                } else {
+                    Node::Decrement(name)
+                })
+            }
+            "cycle" => cycle_tag(tag),
+            "paginate" => self.paginate_tag(tag),
+            "form" => self.form_tag(tag),
+            "render" => snippet_tag(tag, SnippetKind::Render),
+            "include" => snippet_tag(tag, SnippetKind::Include),
+            "section" => {
+                let name = quoted_name(&tag, "section 'header'")?;
+                Ok(Node::Section {
+                    name,
+                    position: tag.position,
+                })
+            }
+            "sections" => {
+                let name = quoted_name(&tag, "sections 'header-group'")?;
+                Ok(Node::SectionGroup {
+                    name,
+                    position: tag.position,
+                })
+            }
+            "break" => Ok(Node::Break),
+            "continue" => Ok(Node::Continue),
+            name if name.starts_with("end") || matches!(name, "else" | "elsif" | "when") => Err(
+                LiquidError::at(format!("Unexpected '{name}' tag"), tag.position),
+            ),
+            name => match self.custom_tags.get(name) {
+                Some(kind) => self.custom_tag(*kind, tag),
+                None => Err(LiquidError::at(
+                    format!("Unknown tag '{name}'"),
+                    tag.position,
+                )),
+            },
+        }
+    }
+
+    fn custom_tag(&mut self, kind: TagKind, tag: Tag) -> Result<Node, LiquidError> {
+        let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+        let (args, keywords) = if markup.at_end() {
+            (Vec::new(), Vec::new())
+        } else {
+            at(markup.parameters(), tag.position)?
+        };
+        at(markup.finish(), tag.position)?;
+        let body = match kind {
+            TagKind::Simple => None,
+            TagKind::Block => {
+                let end = format!("end{}", tag.name);
+                Some(self.block(Some(&tag), &[end.as_str()])?.0)
+            }
+        };
+        Ok(Node::Custom(Box::new(CustomTag {
+            name: tag.name,
+            args,
+            keywords,
+            body,
+            position: tag.position,
+        })))
+    }
+
+    fn if_tag(&mut self, tag: Tag) -> Result<Node, LiquidError> {
+        let mut branches = Vec::new();
+        let mut condition = at(parse_condition(&tag.markup), tag.position)?;
+        loop {
+            let (body, end) = self.block(Some(&tag), &["elsif", "else", "endif"])?;
+            branches.push((condition, body));
+            let end = end.expect("closed block");
+            match end.name.as_str() {
+                "elsif" => condition = at(parse_condition(&end.markup), end.position)?,
+                "else" => {
+                    let (otherwise, _) = self.block(Some(&tag), &["endif"])?;
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: Some(otherwise),
+                        position: tag.position,
+                    });
+                }
+                _ => {
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: None,
+                        position: tag.position,
+                    })
+                }
+            }
+        }
+    }
+
+    /// `unless` is `if` with its first condition negated.
+    fn unless_tag(&mut self, tag: Tag) -> Result<Node, LiquidError> {
+        let mut branches = Vec::new();
+        let mut condition =
+            Condition::Not(Box::new(at(parse_condition(&tag.markup), tag.position)?));
+        loop {
+            let (body, end) = self.block(Some(&tag), &["elsif", "else", "endunless"])?;
+            branches.push((condition, body));
+            let end = end.expect("closed block");
+            match end.name.as_str() {
+                "elsif" => condition = at(parse_condition(&end.markup), end.position)?,
+                "else" => {
+                    let (otherwise, _) = self.block(Some(&tag), &["endunless"])?;
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: Some(otherwise),
+                        position: tag.position,
+                    });
+                }
+                _ => {
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: None,
+                        position: tag.position,
+                    })
+                }
+            }
+        }
+    }
+
+    /// `case subject` with `when a, b` (or `when a or b`) branches, as an
+    /// `if` comparing the subject to each value. Anything between `case`
+    /// and the first `when` is ignored.
+    fn case_tag(&mut self, tag: Tag) -> Result<Node, LiquidError> {
+        let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+        let subject = at(markup.expression(), tag.position)?;
+        at(markup.finish(), tag.position)?;
+
+        let mut branches = Vec::new();
+        let (_, mut end) = self.block(Some(&tag), &["when", "else", "endcase"])?;
+        loop {
+            let end_tag = end.expect("closed block");
+            match end_tag.name.as_str() {
+                "when" => {
+                    let condition =
+                        at(when_condition(&subject, &end_tag.markup), end_tag.position)?;
+                    let (body, next) = self.block(Some(&tag), &["when", "else", "endcase"])?;
+                    branches.push((condition, body));
+                    end = next;
+                }
+                "else" => {
+                    let (otherwise, _) = self.block(Some(&tag), &["endcase"])?;
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: Some(otherwise),
+                        position: tag.position,
+                    });
+                }
+                _ => {
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: None,
+                        position: tag.position,
+                    })
+                }
+            }
+        }
+    }
+
+    /// `for item in collection`.
+    fn for_tag(&mut self, tag: Tag) -> Result<Node, LiquidError> {
+        let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+        let variable = at(markup.ident(), tag.position)?;
+        if !markup.keyword("in") {
+            return Err(LiquidError::at(
+                "Expected 'for item in collection'",
+                tag.position,
+            ));
+        }
+        let collection = at(markup.expression(), tag.position)?;
+        let reversed = markup.keyword("reversed");
+        let (mut limit, mut offset) = (None, None);
+        for (key, value) in at(markup.arguments(), tag.position)? {
+            match key.as_str() {
+                "limit" => limit = Some(value),
+                "offset" => offset = Some(value),
+                _ => {
+                    return Err(LiquidError::at(
+                        format!("Unknown for option '{key}'"),
+                        tag.position,
+                    ))
+                }
+            }
+        }
+        at(markup.finish(), tag.position)?;
+
+        let (body, end) = self.block(Some(&tag), &["else", "endfor"])?;
+        let otherwise = match end {
+            Some(end) if end.name == "else" => Some(self.block(Some(&tag), &["endfor"])?.0),
+            _ => None,
+        };
+        Ok(Node::For(Box::new(ForLoop {
+            variable,
+            collection,
+            limit,
+            offset,
+            reversed,
+            body,
+            otherwise,
+            position: tag.position,
+        })))
+    }
+
+    fn tablerow_tag(&mut self, tag: Tag) -> Result<Node, LiquidError> {
+        let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+        let variable = at(markup.ident(), tag.position)?;
+        if !markup.keyword("in") {
+            return Err(LiquidError::at(
+                "Expected 'tablerow item in collection'",
+                tag.position,
+            ));
+        }
+        let collection = at(markup.expression(), tag.position)?;
+        let (mut cols, mut limit, mut offset) = (None, None, None);
+        for (key, value) in at(markup.arguments(), tag.position)? {
+            match key.as_str() {
+                "cols" => cols = Some(value),
+                "limit" => limit = Some(value),
+                "offset" => offset = Some(value),
+                _ => {
+                    return Err(LiquidError::at(
+                        format!("Unknown tablerow option '{key}'"),
+                        tag.position,
+                    ))
+                }
+            }
+        }
+        at(markup.finish(), tag.position)?;
+
+        let (body, _) = self.block(Some(&tag), &["endtablerow"])?;
+        Ok(Node::Tablerow(Box::new(Tablerow {
+            variable,
+            collection,
+            cols,
+            limit,
+            offset,
+            body,
+            position: tag.position,
+        })))
+    }
+
+    fn form_tag(&mut self, tag: Tag) -> Result<Node, LiquidError> {
+        let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+        let kind = at(markup.expression(), tag.position)?;
+        let object = if markup.comma() && !markup.at_argument() {
+            Some(at(markup.expression(), tag.position)?)
+        } else {
+            None
+        };
+        let arguments = at(markup.arguments(), tag.position)?;
+        at(markup.finish(), tag.position)?;
+        let (body, _) = self.block(Some(&tag), &["endform"])?;
+        Ok(Node::Form(Box::new(FormTag {
+            kind,
+            object,
+            arguments,
+            body,
+            position: tag.position,
+        })))
+    }
+
+    fn paginate_tag(&mut self, tag: Tag) -> Result<Node, LiquidError> {
+        let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+        let collection = at(markup.expression(), tag.position)?;
+        if !matches!(collection, Expr::Variable(_)) || !markup.keyword("by") {
+            return Err(LiquidError::at(
+                "Expected 'paginate collection.products by size'",
+                tag.position,
+            ));
+        }
+        let page_size = at(markup.expression(), tag.position)?;
+        let mut window_size = None;
+        for (key, value) in at(markup.arguments(), tag.position)? {
+            match key.as_str() {
+                "window_size" => window_size = Some(value),
+                _ => {
+                    return Err(LiquidError::at(
+                        format!("Unknown paginate option '{key}'"),
+                        tag.position,
+                    ))
+                }
+            }
+        }
+        at(markup.finish(), tag.position)?;
+
+        let (body, _) = self.block(Some(&tag), &["endpaginate"])?;
+        Ok(Node::Paginate(Box::new(Paginate {
+            collection,
+            page_size,
+            window_size,
+            body,
+            position: tag.position,
+        })))
+    }
+}
+
+/// The condition a `when` matches on: any of its values equals `subject`.
+fn when_condition(subject: &Expr, markup: &str) -> Result<Condition, String> {
+    let mut markup = Markup::new(markup)?;
+    let mut condition: Option<Condition> = None;
+    loop {
+        let value = markup.expression()?;
+        let test = Condition::Compare(subject.clone(), Operator::Equal, value);
+        condition = Some(match condition {
+            Some(previous) => Condition::Or(Box::new(previous), Box::new(test)),
+            None => test,
+        });
+        if !markup.comma() && !markup.keyword("or") {
+            break;
+        }
+    }
+    markup.finish()?;
+    Ok(condition.expect("at least one value"))
+}
+
+/// `cycle 'a', 'b'` or `cycle 'group': 'a', 'b'`.
+fn cycle_tag(tag: Tag) -> Result<Node, LiquidError> {
+    let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+    let first = at(markup.expression(), tag.position)?;
+    let (group, mut values) = if markup.colon() {
+        (first, Vec::new())
+    } else {
+        markup.comma();
+        (
+            Expr::Literal(Value::String(tag.markup.trim().to_string())),
+            vec![first],
+        )
+    };
+    while !markup.at_end() {
+        values.push(at(markup.expression(), tag.position)?);
+        if !markup.comma() {
+            break;
+        }
+    }
+    at(markup.finish(), tag.position)?;
+    if values.is_empty() {
+        return Err(LiquidError::at(
+            "cycle needs at least one value",
+            tag.position,
+        ));
+    }
+    Ok(Node::Cycle {
+        group,
+        values,
+        position: tag.position,
+    })
+}
+
+fn snippet_tag(tag: Tag, kind: SnippetKind) -> Result<Node, LiquidError> {
+    let mut markup = at(Markup::new(&tag.markup), tag.position)?;
+    let name = at(markup.expression(), tag.position)?;
+    // `render` only takes a quoted name, so snippets are known statically.
+    if kind == SnippetKind::Render && !matches!(name, Expr::Literal(Value::String(_))) {
+        return Err(LiquidError::at(
+            "Expected a quoted snippet name, as in render 'product-card'",
+            tag.position,
+        ));
+    }
+    let each = markup.keyword("for");
+    let value = if each || markup.keyword("with") {
+        Some(at(markup.expression(), tag.position)?)
+    } else {
+        None
+    };
+    let alias = if value.is_some() && markup.keyword("as") {
+        Some(at(markup.ident(), tag.position)?)
+    } else {
+        None
+    };
+    let arguments = at(markup.arguments(), tag.position)?;
+    at(markup.finish(), tag.position)?;
+    Ok(Node::Snippet(Box::new(SnippetTag {
+        kind,
+        name,
+        value,
+        each,
+        alias,
+        arguments,
+        position: tag.position,
+    })))
+}
+
+pub fn parse(source: &str) -> Result<Template, LiquidError> {
+    parse_with_tags(source, BTreeMap::new())
+}
+
+/// Parses `source`, also accepting the given custom tags.
+pub fn parse_with_tags(
+    source: &str,
+    custom_tags: BTreeMap<String, TagKind>,
+) -> Result<Template, LiquidError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?.into_iter(),
+        schema: None,
+        custom_tags,
+    };
+    let (nodes, _) = parser.block(None, &[])?;
+    Ok(Template {
+        nodes,
+        schema: parser.schema,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn error(source: &str) -> (String, Position) {
+        let error = parse(source).unwrap_err();
+        (error.message, error.position.expect("a position"))
+    }
+
+    fn position(line: usize, column: usize) -> Position {
+        Position { line, column }
+    }
+
+    #[test]
+    fn for_loops_take_reversed_limit_and_offset() {
+        let template =
+            parse("{% for item in items reversed limit: 2, offset: 1 %}{% endfor %}").unwrap();
+
+        let Node::For(for_loop) = &template.nodes[0] else {
+            panic!("expected a for loop: {:?}", template.nodes);
+        };
+        assert_eq!(for_loop.variable, "item");
+        assert!(for_loop.reversed);
+        assert_eq!(for_loop.limit, Some(Expr::Literal(json!(2))));
+        assert_eq!(for_loop.offset, Some(Expr::Literal(json!(1))));
+        assert_eq!(for_loop.otherwise, None);
+    }
+
+    #[test]
+    fn for_loops_reject_unknown_options() {
+        assert_eq!(
+            error("\n  {% for x in xs step: 2 %}{% endfor %}"),
+            ("Unknown for option 'step'".to_string(), position(2, 3))
+        );
+        assert_eq!(
+            error("{% for x xs %}{% endfor %}"),
+            (
+                "Expected 'for item in collection'".to_string(),
+                position(1, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn unclosed_blocks_fail_at_their_opening_tag() {
+        assert_eq!(
+            error("a\nb {% if x %}\n{% for y in ys %}{% endfor %}"),
+            ("'if' tag was never closed".to_string(), position(2, 3))
+        );
+        assert_eq!(
+            error("{% capture x %}{% if y %}{% endcapture %}"),
+            ("Unexpected 'endcapture' tag".to_string(), position(1, 26))
+        );
+    }
+
+    #[test]
+    fn stray_and_unknown_tags_fail_where_they_are() {
+        assert_eq!(
+            error("{{ x }}\n   {% endif %}"),
+            ("Unexpected 'endif' tag".to_string(), position(2, 4))
+        );
+        assert_eq!(
+            error("{% else %}"),
+            ("Unexpected 'else' tag".to_string(), position(1, 1))
+        );
+        assert_eq!(
+            error("x {% frobnicate %}"),
+            ("Unknown tag 'frobnicate'".to_string(), position(1, 3))
+        );
+    }
+
+    #[test]
+    fn markup_errors_carry_the_tag_position() {
+        assert_eq!(
+            error("line one\nline {{ product. }}"),
+            (
+                "Expected a name, found the end of the tag".to_string(),
+                position(2, 6)
+            )
+        );
+        assert_eq!(
+            error("{% liquid\n  assign = 1\n%}"),
+            ("Expected a name, found '='".to_string(), position(2, 3))
+        );
+    }
+
+    #[test]
+    fn custom_tags_parse_once_registered() {
+        assert!(parse("{% badge %}").is_err());
+
+        let tags = BTreeMap::from([("badge".to_string(), TagKind::Simple)]);
+        let template = parse_with_tags("{% badge 'new' %}", tags).unwrap();
+        assert!(matches!(template.nodes[0], Node::Custom(_)));
+    }
+
+    #[test]
+    fn a_schema_is_kept_apart_from_the_nodes() {
+        let template = parse("x{% schema %}{\"name\": \"Hero\"}{% endschema %}").unwrap();
+        assert_eq!(template.nodes, vec![Node::Text("x".to_string())]);
+        assert_eq!(template.schema, Some(json!({ "name": "Hero" })));
+
+        assert_eq!(
+            error("{% if x %}{% schema %}{}{% endschema %}{% endif %}"),
+            (
+                "A template can only have one 'schema', outside any tag".to_string(),
+                position(1, 11)
+            )
+        );
+    }
+}
@@ -0,0 +1,781 @@
+//! Storefront queries generated from templates.
+//!
+//! `template_query` walks a compiled template, and the snippets it renders,
+//! for the fields it reads of `product` and `collection`, following
+//! `assign`, `for` and `render` aliases, and builds the smallest Storefront
+//! query that fetches them: `{{ product.title }}` with a loop over the
+//! first four variants' prices asks for the title and four variants' IDs
+//! and prices, not whole product cards. `fetch_and_render` runs that query
+//! with the engine's client and renders the template with the result, so
+//! the query never drifts from the template.
+//!
+//! Drops are still built from typed Storefront data, so the fields drops
+//! can't do without (IDs, handles, titles, prices and availability) are
+//! always fetched. A loop with a literal `limit` fetches only that many
+//! items; any other use of a list fetches a full page of it.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::compiled::CompiledTemplate;
+use super::drops::wrap_storefront_data;
+use super::engine::{template_variables, LiquidEngine};
+use super::error::LiquidError;
+use super::expression::{Condition, Expr, Output, Segment};
+use super::paginate::js_message;
+use super::parser::{Node, SnippetKind, Template};
+use super::snippets::MAX_SNIPPET_DEPTH;
+use crate::collection::MAX_PAGE_SIZE;
+use crate::product::{
+    metafield_identifiers_literal, PRODUCT_CARD_FRAGMENT, REFERENCE_PAGE_SIZE,
+    RESOLVED_REFERENCE_FIELDS,
+};
+use crate::types::MetafieldIdentifier;
+use crate::{from_js, to_js};
+
+/// Objects fetched by handle, with their query variable.
+const OBJECTS: &[(&str, &str)] = &[
+    ("product", "productHandle"),
+    ("collection", "collectionHandle"),
+];
+
+/// Filters that pass a list through as a list of the same drops.
+const LIST_FILTERS: &[&str] = &[
+    "where",
+    "sort",
+    "sort_natural",
+    "reverse",
+    "uniq",
+    "compact",
+    "concat",
+];
+
+const IMAGE_FIELDS: &str = "id url altText width height";
+
+/// Fields drops need whatever the template reads.
+const PRODUCT_BASE_FIELDS: &str = "id handle title priceRange { minVariantPrice { amount \
+     currencyCode } maxVariantPrice { amount currencyCode } }";
+const VARIANT_BASE_FIELDS: &str = "id title price { amount currencyCode } availableForSale";
+const COLLECTION_BASE_FIELDS: &str = "id handle title";
+
+/// Product drop fields read straight from one Storefront field.
+const PRODUCT_FIELDS: &[(&str, &str)] = &[
+    ("description", "description"),
+    ("content", "description"),
+    ("vendor", "vendor"),
+    ("type", "productType"),
+    ("tags", "tags"),
+    ("available", "availableForSale"),
+    ("gift_card?", "isGiftCard"),
+    ("published_at", "publishedAt"),
+    ("requires_selling_plan", "requiresSellingPlan"),
+    ("options", "options { name }"),
+    (
+        "options_with_values",
+        "options { name optionValues { name } }",
+    ),
+];
+
+/// For the `compare_at_price` product drop fields.
+const COMPARE_AT_PRICE_RANGE: &str = "compareAtPriceRange { minVariantPrice { amount \
+     currencyCode } maxVariantPrice { amount currencyCode } }";
+
+/// Product drop fields that are variants.
+const PRODUCT_VARIANT_FIELDS: &[&str] = &[
+    "variants",
+    "first_available_variant",
+    "selected_or_first_available_variant",
+    "has_only_default_variant",
+];
+
+/// Variant drop fields read straight from one Storefront field.
+const VARIANT_FIELDS: &[(&str, &str)] = &[
+    ("sku", "sku"),
+    ("barcode", "barcode"),
+    ("compare_at_price", "compareAtPrice { amount currencyCode }"),
+    ("unit_price", "unitPrice { amount currencyCode }"),
+    ("inventory_quantity", "quantityAvailable"),
+    ("options", "selectedOptions { name value }"),
+    ("option1", "selectedOptions { name value }"),
+    ("option2", "selectedOptions { name value }"),
+    ("option3", "selectedOptions { name value }"),
+];
+
+/// The drop fields a template reads, by variable: `product.variants[0].sku`
+/// is `product` > `variants` > `sku`.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Fields {
+    children: BTreeMap<String, Fields>,
+    /// The most items of this list any use reads, when every use is
+    /// bounded.
+    bound: Option<usize>,
+    /// Some use may read every item, or the value as a whole.
+    whole: bool,
+}
+
+impl Fields {
+    fn at(&mut self, path: &[String]) -> &mut Fields {
+        path.iter().fold(self, |fields, key| {
+            fields.children.entry(key.clone()).or_default()
+        })
+    }
+
+    fn get(&self, key: &str) -> Option<&Fields> {
+        self.children.get(key)
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.children.contains_key(key)
+    }
+
+    /// Items to fetch of this list.
+    fn page_size(&self) -> u32 {
+        match self.bound {
+            Some(bound) if !self.whole => (bound as u32).clamp(1, MAX_PAGE_SIZE),
+            _ => MAX_PAGE_SIZE,
+        }
+    }
+
+    /// Merges fields read through different drop fields of the same data,
+    /// such as `variants` and `first_available_variant`.
+    fn merge(&mut self, other: &Fields) {
+        for (key, child) in &other.children {
+            self.children.entry(key.clone()).or_default().merge(child);
+        }
+        self.whole |= other.whole;
+        self.bound = self.bound.max(other.bound);
+    }
+}
+
+/// Collects the fields a template reads.
+struct Analyzer<'a> {
+    engine: &'a LiquidEngine,
+    fields: Fields,
+    /// Variables standing for a path: assigned ones first, then loop
+    /// variables innermost last. `None` for variables shadowing one with
+    /// something else.
+    scopes: Vec<BTreeMap<String, Option<Vec<String>>>>,
+    /// Snippets being analyzed, so recursive ones stop.
+    snippets: Vec<String>,
+}
+
+impl Analyzer<'_> {
+    fn resolve(&self, name: &str) -> Option<Vec<String>> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(path) = scope.get(name) {
+                return path.clone();
+            }
+        }
+        Some(vec![name.to_string()])
+    }
+
+    /// Assigns `name`, which like `assign` outlives the block it's in.
+    fn define(&mut self, name: &str, path: Option<Vec<String>>) {
+        self.scopes[0].insert(name.to_string(), path);
+    }
+
+    fn bound(&mut self, path: &[String], items: usize) {
+        let fields = self.fields.at(path);
+        fields.bound = fields.bound.max(Some(items));
+    }
+
+    fn whole(&mut self, path: &[String]) {
+        self.fields.at(path).whole = true;
+    }
+
+    /// Analyzes `nodes` in a scope of their own.
+    fn scoped(&mut self, scope: BTreeMap<String, Option<Vec<String>>>, nodes: &[Node]) {
+        self.scopes.push(scope);
+        self.nodes(nodes);
+        self.scopes.pop();
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &Node) {
+        match node {
+            Node::Text(_) | Node::Break | Node::Continue => {}
+            Node::Section { .. } | Node::SectionGroup { .. } => {}
+            Node::Increment(_) | Node::Decrement(_) => {}
+            Node::Output { output, .. } => {
+                if let Some(path) = self.output(output) {
+                    self.whole(&path);
+                }
+            }
+            Node::If {
+                branches,
+                otherwise,
+                ..
+            } => {
+                for (condition, body) in branches {
+                    self.condition(condition);
+                    self.nodes(body);
+                }
+                if let Some(body) = otherwise {
+                    self.nodes(body);
+                }
+            }
+            Node::For(for_loop) => {
+                let path = self.list(
+                    &for_loop.collection,
+                    for_loop.limit.as_ref(),
+                    for_loop.offset.as_ref(),
+                );
+                let scope = BTreeMap::from([
+                    (for_loop.variable.clone(), path),
+                    ("forloop".to_string(), None),
+                ]);
+                self.scoped(scope, &for_loop.body);
+                if let Some(body) = &for_loop.otherwise {
+                    self.nodes(body);
+                }
+            }
+            Node::Tablerow(tablerow) => {
+                if let Some(cols) = &tablerow.cols {
+                    self.expr(cols);
+                }
+                let path = self.list(
+                    &tablerow.collection,
+                    tablerow.limit.as_ref(),
+                    tablerow.offset.as_ref(),
+                );
+                let scope = BTreeMap::from([
+                    (tablerow.variable.clone(), path),
+                    ("tablerowloop".to_string(), None),
+                ]);
+                self.scoped(scope, &tablerow.body);
+            }
+            Node::Assign { name, value, .. } => {
+                let path = self.output(value);
+                self.define(name, path);
+            }
+            Node::Capture { name, body } => {
+                self.nodes(body);
+                self.define(name, None);
+            }
+            Node::Cycle { group, values, .. } => {
+                self.expr(group);
+                for value in values {
+                    self.expr(value);
+                }
+            }
+            Node::Custom(tag) => {
+                for arg in tag
+                    .args
+                    .iter()
+                    .chain(tag.keywords.iter().map(|(_, arg)| arg))
+                {
+                    self.expr(arg);
+                }
+                if let Some(body) = &tag.body {
+                    self.nodes(body);
+                }
+            }
+            Node::Paginate(paginate) => {
+                self.expr(&paginate.collection);
+                self.expr(&paginate.page_size);
+                if let Some(window) = &paginate.window_size {
+                    self.expr(window);
+                }
+                let scope = BTreeMap::from([("paginate".to_string(), None)]);
+                self.scoped(scope, &paginate.body);
+            }
+            Node::Snippet(tag) => {
+                let mut scope = BTreeMap::new();
+                for (name, value) in &tag.arguments {
+                    let path = self.path(value);
+                    scope.insert(name.clone(), path);
+                }
+                let name = match &tag.name {
+                    Expr::Literal(Value::String(name)) => Some(name.clone()),
+                    name => {
+                        self.expr(name);
+                        None
+                    }
+                };
+                if let Some(value) = &tag.value {
+                    let path = if tag.each {
+                        self.list(value, None, None)
+                    } else {
+                        self.path(value)
+                    };
+                    let alias = tag.alias.clone().or_else(|| name.clone());
+                    if let Some(alias) = alias {
+                        scope.insert(alias, path);
+                    }
+                }
+                let template = name
+                    .filter(|name| {
+                        !self.snippets.contains(name) && self.snippets.len() < MAX_SNIPPET_DEPTH
+                    })
+                    .and_then(|name| Some((self.engine.snippets.borrow().get(&name)?, name)));
+                if let Some((template, name)) = template {
+                    self.snippets.push(name);
+                    match tag.kind {
+                        // `render` sees only what it's passed.
+                        SnippetKind::Render => {
+                            let outer = std::mem::take(&mut self.scopes);
+                            self.scopes.push(scope);
+                            self.nodes(&template.nodes);
+                            self.scopes = outer;
+                        }
+                        SnippetKind::Include => self.scoped(scope, &template.nodes),
+                    }
+                    self.snippets.pop();
+                }
+            }
+            Node::Form(form) => {
+                self.expr(&form.kind);
+                if let Some(object) = &form.object {
+                    self.expr(object);
+                }
+                for (_, value) in &form.arguments {
+                    self.expr(value);
+                }
+                let scope = BTreeMap::from([("form".to_string(), None)]);
+                self.scoped(scope, &form.body);
+            }
+        }
+    }
+
+    /// The path a loop over `collection` gives its items, bounding the
+    /// list when `limit` and `offset` are literals.
+    fn list(
+        &mut self,
+        collection: &Expr,
+        limit: Option<&Expr>,
+        offset: Option<&Expr>,
+    ) -> Option<Vec<String>> {
+        let path = self.path(collection)?;
+        let literal = |expr: Option<&Expr>| match expr {
+            Some(Expr::Literal(value)) => value.as_u64().map(|value| value as usize),
+            Some(_) => None,
+            None => Some(0),
+        };
+        match (limit, literal(limit), literal(offset)) {
+            (Some(_), Some(limit), Some(offset)) => self.bound(&path, limit + offset),
+            _ => self.whole(&path),
+        }
+        for expr in limit.into_iter().chain(offset) {
+            self.expr(expr);
+        }
+        Some(path)
+    }
+
+    /// Analyzes `output`, returning the path it still stands for after its
+    /// filters, if they pass drops through.
+    fn output(&mut self, output: &Output) -> Option<Vec<String>> {
+        let mut path = self.path(&output.expr);
+        for filter in &output.filters {
+            for arg in filter
+                .args
+                .iter()
+                .chain(filter.keywords.iter().map(|(_, arg)| arg))
+            {
+                self.expr(arg);
+            }
+            let Some(current) = path.take() else {
+                continue;
+            };
+            path = match filter.name.as_str() {
+                "first" => {
+                    self.bound(&current, 1);
+                    Some(current)
+                }
+                "last" => {
+                    self.whole(&current);
+                    Some(current)
+                }
+                name if LIST_FILTERS.contains(&name) => {
+                    // `where: 'available'` reads the field it filters on.
+                    if let Some(Expr::Literal(Value::String(key))) = filter.args.first() {
+                        if matches!(name, "where" | "sort" | "sort_natural") {
+                            let mut field = current.clone();
+                            field.push(key.clone());
+                            self.whole(&field);
+                        }
+                    }
+                    self.whole(&current);
+                    Some(current)
+                }
+                "map" => {
+                    self.whole(&current);
+                    match filter.args.first() {
+                        Some(Expr::Literal(Value::String(key))) => {
+                            let mut field = current;
+                            field.push(key.clone());
+                            Some(field)
+                        }
+                        _ => None,
+                    }
+                }
+                _ => {
+                    self.whole(&current);
+                    None
+                }
+            };
+        }
+        path
+    }
+
+    fn condition(&mut self, condition: &Condition) {
+        match condition {
+            Condition::Test(expr) => self.expr(expr),
+            Condition::Compare(left, _, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                self.condition(left);
+                self.condition(right);
+            }
+            Condition::Not(condition) => self.condition(condition),
+        }
+    }
+
+    /// Records a use of the whole value of `expr`.
+    fn expr(&mut self, expr: &Expr) {
+        if let Some(path) = self.path(expr) {
+            self.whole(&path);
+        }
+    }
+
+    /// The path `expr` reads, recording the lists it indexes on the way.
+    fn path(&mut self, expr: &Expr) -> Option<Vec<String>> {
+        match expr {
+            Expr::Literal(_) | Expr::Empty | Expr::Blank => None,
+            Expr::Range(start, end) => {
+                self.expr(start);
+                self.expr(end);
+                None
+            }
+            Expr::Variable(segments) => {
+                let (first, rest) = segments.split_first()?;
+                let Segment::Key(name) = first else {
+                    return None;
+                };
+                let mut path = self.resolve(name);
+                for segment in rest {
+                    let Some(current) = path.as_mut() else {
+                        if let Segment::Index(index) = segment {
+                            self.expr(index);
+                        }
+                        continue;
+                    };
+                    match segment {
+                        Segment::Key(key) if key == "first" => {
+                            let current = current.clone();
+                            self.bound(&current, 1);
+                        }
+                        Segment::Key(key) if key == "last" => {
+                            let current = current.clone();
+                            self.whole(&current);
+                        }
+                        Segment::Key(key) if key == "size" => {
+                            let current = current.clone();
+                            self.whole(&current);
+                            path = None;
+                        }
+                        Segment::Key(key) => current.push(key.clone()),
+                        Segment::Index(Expr::Literal(Value::String(key))) => {
+                            current.push(key.clone())
+                        }
+                        Segment::Index(Expr::Literal(index)) if index.as_u64().is_some() => {
+                            let items = index.as_u64().unwrap_or_default() as usize + 1;
+                            let current = current.clone();
+                            self.bound(&current, items);
+                        }
+                        Segment::Index(index) => {
+                            let current = current.clone();
+                            self.whole(&current);
+                            self.expr(index);
+                        }
+                    }
+                }
+                path
+            }
+        }
+    }
+}
+
+/// Adds `field` to `selection` unless it's there.
+fn select(selection: &mut Vec<String>, field: impl Into<String>) {
+    let field = field.into();
+    if !selection.contains(&field) {
+        selection.push(field);
+    }
+}
+
+fn connection(name: &str, first: u32, node: &str) -> String {
+    format!("{name}(first: {first}) {{ edges {{ node {{ {node} }} }} }}")
+}
+
+/// Builds selections, noting whether they need the product card fragment.
+#[derive(Default)]
+struct QueryBuilder {
+    card_fragment: bool,
+}
+
+impl QueryBuilder {
+    /// `metafields(identifiers: ...)` for the `metafields.namespace.key`
+    /// the template reads, resolving references when it reads into a
+    /// metafield's value.
+    fn metafields(&mut self, fields: Option<&Fields>) -> Option<String> {
+        let mut identifiers = Vec::new();
+        let mut resolve = false;
+        for (namespace, keys) in &fields?.children {
+            for (key, metafield) in &keys.children {
+                identifiers.push(MetafieldIdentifier {
+                    namespace: namespace.clone(),
+                    key: key.clone(),
+                });
+                resolve |= metafield
+                    .get("value")
+                    .is_some_and(|value| !value.children.is_empty());
+            }
+        }
+        if identifiers.is_empty() {
+            return None;
+        }
+        let references = if resolve {
+            self.card_fragment = true;
+            format!(
+                " reference {{ {RESOLVED_REFERENCE_FIELDS} }} {}",
+                connection("references", REFERENCE_PAGE_SIZE, RESOLVED_REFERENCE_FIELDS)
+            )
+        } else {
+            String::new()
+        };
+        Some(format!(
+            "metafields(identifiers: {}) {{ namespace key type value{references} }}",
+            metafield_identifiers_literal(&identifiers)
+        ))
+    }
+
+    fn variant(&mut self, fields: &Fields) -> String {
+        let mut selection = vec![VARIANT_BASE_FIELDS.to_string()];
+        for (key, field) in VARIANT_FIELDS {
+            if fields.has(key) {
+                select(&mut selection, *field);
+            }
+        }
+        if fields.has("image") || fields.has("featured_image") {
+            select(&mut selection, format!("image {{ {IMAGE_FIELDS} }}"));
+        }
+        selection.join(" ")
+    }
+
+    fn product(&mut self, fields: &Fields) -> String {
+        let mut selection = vec![PRODUCT_BASE_FIELDS.to_string()];
+        for (key, field) in PRODUCT_FIELDS {
+            if fields.has(key) {
+                select(&mut selection, *field);
+            }
+        }
+        if fields
+            .children
+            .keys()
+            .any(|key| key.starts_with("compare_at_price"))
+        {
+            select(&mut selection, COMPARE_AT_PRICE_RANGE);
+        }
+
+        let mut images = Fields::default();
+        if let Some(list) = fields.get("images") {
+            images.merge(list);
+        }
+        if fields.has("featured_image") {
+            images.bound = images.bound.max(Some(1));
+        }
+        if fields.has("images") || fields.has("featured_image") {
+            select(
+                &mut selection,
+                connection("images", images.page_size(), IMAGE_FIELDS),
+            );
+        }
+
+        let mut variants = Fields::default();
+        for key in PRODUCT_VARIANT_FIELDS {
+            if let Some(list) = fields.get(key) {
+                variants.merge(list);
+                // Which variant is first available depends on them all.
+                variants.whole |= *key != "variants";
+            }
+        }
+        if PRODUCT_VARIANT_FIELDS.iter().any(|key| fields.has(key)) {
+            let node = self.variant(&variants);
+            select(
+                &mut selection,
+                connection("variants", variants.page_size(), &node),
+            );
+        }
+        if let Some(metafields) = self.metafields(fields.get("metafields")) {
+            select(&mut selection, metafields);
+        }
+        selection.join(" ")
+    }
+
+    fn collection(&mut self, fields: &Fields) -> String {
+        let mut selection = vec![COLLECTION_BASE_FIELDS.to_string()];
+        if fields.has("description") {
+            select(&mut selection, "description");
+        }
+        if fields.has("image") || fields.has("featured_image") {
+            select(&mut selection, format!("image {{ {IMAGE_FIELDS} }}"));
+        }
+        if fields.has("products") || fields.has("products_count") {
+            let mut products = fields.get("products").cloned().unwrap_or_default();
+            products.whole |= fields.has("products_count");
+            let node = self.product(&products);
+            select(
+                &mut selection,
+                connection("products", products.page_size(), &node),
+            );
+        }
+        if let Some(metafields) = self.metafields(fields.get("metafields")) {
+            select(&mut selection, metafields);
+        }
+        selection.join(" ")
+    }
+}
+
+/// The query a template's data can be fetched with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateQuery {
+    pub query: String,
+    /// The objects it fetches, each by the handle in its variable:
+    /// `product` by `$productHandle`, `collection` by `$collectionHandle`.
+    pub objects: Vec<String>,
+}
+
+impl LiquidEngine {
+    /// The query fetching what `template` reads of `product` and
+    /// `collection`, or `None` when it reads neither.
+    pub fn query_for_template(&self, template: &Template) -> Option<TemplateQuery> {
+        let mut analyzer = Analyzer {
+            engine: self,
+            fields: Fields::default(),
+            scopes: vec![BTreeMap::new()],
+            snippets: Vec::new(),
+        };
+        analyzer.nodes(&template.nodes);
+
+        let mut builder = QueryBuilder::default();
+        let mut variables = Vec::new();
+        let mut selections = Vec::new();
+        let mut objects = Vec::new();
+        for (object, variable) in OBJECTS {
+            let Some(fields) = analyzer.fields.get(object) else {
+                continue;
+            };
+            let selection = match *object {
+                "product" => builder.product(fields),
+                _ => builder.collection(fields),
+            };
+            variables.push(format!("${variable}: String!"));
+            selections.push(format!(
+                "    {object}(handle: ${variable}) {{ {selection} }}"
+            ));
+            objects.push(object.to_string());
+        }
+        if objects.is_empty() {
+            return None;
+        }
+        let mut query = format!(
+            "query templateData({}) {{\n{}\n}}",
+            variables.join(", "),
+            selections.join("\n")
+        );
+        if builder.card_fragment {
+            query.push_str(PRODUCT_CARD_FRAGMENT);
+        }
+        Some(TemplateQuery { query, objects })
+    }
+
+    /// Fetches what `template` reads of the objects in `handles` (by
+    /// object name) and renders it, with `variables` as its other data.
+    pub async fn fetch_and_render_with(
+        &self,
+        template: &Template,
+        handles: &BTreeMap<String, String>,
+        mut variables: Map<String, Value>,
+    ) -> Result<String, LiquidError> {
+        if let Some(plan) = self.query_for_template(template) {
+            let api = self.storefront.borrow().clone().ok_or_else(|| {
+                LiquidError::new("fetch_and_render needs a Storefront client; call set_storefront")
+            })?;
+            let mut query_variables = Map::new();
+            for object in &plan.objects {
+                let handle = handles.get(object).ok_or_else(|| {
+                    LiquidError::new(format!("The template reads {object}; pass its handle"))
+                })?;
+                let (_, variable) = OBJECTS
+                    .iter()
+                    .find(|(name, _)| name == object)
+                    .expect("known object");
+                query_variables.insert(variable.to_string(), Value::from(handle.as_str()));
+            }
+            let mut data = api
+                .execute(&plan.query, Value::Object(query_variables))
+                .await
+                .map_err(|error| LiquidError::new(js_message(error)))?;
+            let mut fetched = Map::new();
+            for object in plan.objects {
+                match data[object.as_str()].take() {
+                    Value::Null => {
+                        return Err(LiquidError::new(format!(
+                            "No {object} with handle '{}'",
+                            handles[&object]
+                        )))
+                    }
+                    value => fetched.insert(object, value),
+                };
+            }
+            wrap_storefront_data(&mut fetched);
+            variables.extend(fetched);
+        }
+        self.render_template(template, variables).await
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// The Storefront query fetching exactly what a compiled template reads
+    /// of `product` and `collection`, as `{ query, objects }` (`objects`
+    /// lists the ones it fetches, each by a `$productHandle` or
+    /// `$collectionHandle` variable), or `null` when it reads neither.
+    #[wasm_bindgen(unchecked_return_type = "TemplateQuery | null")]
+    pub fn template_query(&self, template: &CompiledTemplate) -> Result<JsValue, JsValue> {
+        to_js(&self.query_for_template(template.template()))
+    }
+
+    /// Fetches what a compiled template reads with `template_query` and
+    /// the engine's client (`set_storefront`), then renders it:
+    /// `handles` names the objects to fetch, e.g. `{ product: "tee" }`,
+    /// and `data` holds the template's other variables, as for `render`.
+    #[wasm_bindgen]
+    pub async fn fetch_and_render(
+        &self,
+        template: &CompiledTemplate,
+        #[wasm_bindgen(unchecked_param_type = "Record<string, string> | null | undefined")] handles: JsValue,
+        #[wasm_bindgen(unchecked_optional_param_type = "TemplateData")] data: JsValue,
+    ) -> Result<String, JsValue> {
+        let handles: BTreeMap<String, String> = if handles.is_undefined() || handles.is_null() {
+            BTreeMap::new()
+        } else {
+            from_js(handles)?
+        };
+        let variables = template_variables(data)?;
+        Ok(self
+            .fetch_and_render_with(template.template(), &handles, variables)
+            .await?)
+    }
+}
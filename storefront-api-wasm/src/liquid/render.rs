@@ -0,0 +1,900 @@
+//! Renders a parsed template against its variables.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use serde_json::{Map, Value};
+
+use super::engine::LiquidEngine;
+use super::error::{LiquidError, Position};
+use super::expression::{Condition, Expr, Operator, Output, Segment};
+use super::filters::{self, FilterArgs};
+use super::hydration::Hydration;
+use super::parser::{ForLoop, Node, Tablerow};
+use super::sections::SectionData;
+use super::value::{
+    as_integer, compare, contains, equals, find_index, find_property, is_blank, is_empty, iterate,
+    to_text, truthy,
+};
+
+/// Longest range a template may build, so `(1..1000000000)` can't exhaust
+/// memory.
+const MAX_RANGE: i64 = 100_000;
+
+/// Filters whose output auto-escaping leaves alone: `raw`, and those that
+/// escape themselves or return markup or JSON.
+const SAFE_FILTERS: &[&str] = &["raw", "escape", "escape_once", "json"];
+
+/// Objects holding rendered markup, which auto-escaping leaves alone.
+const SAFE_OBJECTS: &[&str] = &["content_for_header", "content_for_layout"];
+
+/// Whether auto-escaping leaves `output` alone: it ends with a safe
+/// filter, translates a key ending in `_html` (as Shopify's locale files
+/// mark translations holding markup) or is a safe object.
+fn is_safe(output: &Output) -> bool {
+    match output.filters.last() {
+        None => match &output.expr {
+            Expr::Variable(segments) => matches!(
+                segments.as_slice(),
+                [Segment::Key(name)] if SAFE_OBJECTS.contains(&name.as_str())
+            ),
+            _ => false,
+        },
+        Some(filter) if SAFE_FILTERS.contains(&filter.name.as_str()) => true,
+        Some(filter) if filter.name == "t" || filter.name == "translate" => matches!(
+            &output.expr,
+            Expr::Literal(Value::String(key)) if key.ends_with("_html")
+        ),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interrupt {
+    Break,
+    Continue,
+}
+
+/// Variables in scope while rendering.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    /// Innermost scope last. The first scope holds the template's
+    /// variables and everything `assign`ed.
+    scopes: Vec<Map<String, Value>>,
+    interrupt: Option<Interrupt>,
+    /// `increment` and `decrement` counters, apart from assigned variables.
+    counters: BTreeMap<String, i64>,
+    /// Next value index of each `cycle` group.
+    cycles: BTreeMap<String, usize>,
+    /// How many snippets deep rendering is.
+    pub(crate) depth: usize,
+    /// The tag or output being rendered and the snippet or section it's
+    /// in, for warnings.
+    position: Option<Position>,
+    source: Option<String>,
+    /// Warnings from this render, shared with the snippets and sections it
+    /// renders.
+    warnings: Rc<RefCell<Vec<LiquidError>>>,
+    /// Hydration markers from this render, when they're on, likewise
+    /// shared.
+    hydration: Option<Rc<RefCell<Hydration>>>,
+}
+
+impl Context {
+    pub fn new(variables: Map<String, Value>) -> Context {
+        Context {
+            scopes: vec![variables],
+            interrupt: None,
+            counters: BTreeMap::new(),
+            cycles: BTreeMap::new(),
+            depth: 0,
+            position: None,
+            source: None,
+            warnings: Rc::default(),
+            hydration: None,
+        }
+    }
+
+    /// A context for a snippet or section (`source`) rendered from this
+    /// one, with only `variables` in scope.
+    pub(crate) fn isolated(&self, variables: Map<String, Value>, source: String) -> Context {
+        Context {
+            depth: self.depth + 1,
+            source: Some(source),
+            warnings: Rc::clone(&self.warnings),
+            hydration: self.hydration.clone(),
+            ..Context::new(variables)
+        }
+    }
+
+    /// Sets the snippet being rendered in this context, returning the
+    /// previous one, for `include`.
+    pub(crate) fn replace_source(&mut self, source: Option<String>) -> Option<String> {
+        std::mem::replace(&mut self.source, source)
+    }
+
+    /// Records a warning at the tag or output being rendered. Repeats, as
+    /// from inside a loop, are recorded once.
+    pub(crate) fn warn(&self, message: String) {
+        let warning = LiquidError {
+            message,
+            position: self.position,
+            source: self.source.clone(),
+        };
+        let mut warnings = self.warnings.borrow_mut();
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+
+    /// Whether a `break` or `continue` is unwinding to its loop.
+    pub(crate) fn interrupted(&self) -> bool {
+        self.interrupt.is_some()
+    }
+
+    pub(crate) fn warnings(&self) -> Vec<LiquidError> {
+        self.warnings.borrow().clone()
+    }
+
+    /// Starts recording hydration markers.
+    pub(crate) fn enable_hydration(&mut self) {
+        self.hydration = Some(Rc::default());
+    }
+
+    pub(crate) fn hydration(&self) -> Option<&RefCell<Hydration>> {
+        self.hydration.as_deref()
+    }
+
+    /// A variable, or `None` if it isn't defined.
+    fn lookup(&self, name: &str) -> Option<Value> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+    }
+
+    pub fn get(&self, name: &str) -> Value {
+        self.lookup(name).unwrap_or(Value::Null)
+    }
+
+    /// The template's variables and everything `assign`ed so far.
+    pub(crate) fn globals(&self) -> &Map<String, Value> {
+        &self.scopes[0]
+    }
+
+    /// Every variable in scope, inner scopes shadowing outer ones.
+    pub(crate) fn variables(&self) -> Map<String, Value> {
+        let mut variables = Map::new();
+        for scope in &self.scopes {
+            variables.extend(scope.clone());
+        }
+        variables
+    }
+
+    /// `assign`: visible for the rest of the template, outside any loop.
+    pub fn assign(&mut self, name: &str, value: Value) {
+        self.scopes[0].insert(name.to_string(), value);
+    }
+
+    pub(crate) fn push(&mut self) {
+        self.scopes.push(Map::new());
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Sets a variable in the innermost scope.
+    pub(crate) fn set_local(&mut self, name: &str, value: Value) {
+        self.scopes
+            .last_mut()
+            .expect("at least one scope")
+            .insert(name.to_string(), value);
+    }
+}
+
+pub type RenderFuture<'a> = Pin<Box<dyn Future<Output = Result<(), LiquidError>> + 'a>>;
+
+impl LiquidEngine {
+    // Rendering is async so tags and filters can fetch data as they go.
+    pub(crate) fn render_nodes<'a>(
+        &'a self,
+        nodes: &'a [Node],
+        context: &'a mut Context,
+        output: &'a mut String,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            for node in nodes {
+                if context.interrupt.is_some() {
+                    break;
+                }
+                self.render_node(node, context, output).await?;
+            }
+            Ok(())
+        })
+    }
+
+    async fn render_node(
+        &self,
+        node: &Node,
+        context: &mut Context,
+        output: &mut String,
+    ) -> Result<(), LiquidError> {
+        if let Some(position) = node.position() {
+            context.position = Some(position);
+        }
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Output {
+                output: expression,
+                position,
+            } => {
+                let value = self
+                    .evaluate_output(expression, context)
+                    .await
+                    .map_err(|message| LiquidError::at(message, *position))?;
+                if self.auto_escape.get() && !is_safe(expression) {
+                    output.push_str(&filters::escape_html(&to_text(&value)));
+                } else {
+                    output.push_str(&to_text(&value));
+                }
+            }
+            Node::If {
+                branches,
+                otherwise,
+                position,
+            } => {
+                for (condition, body) in branches {
+                    let matched = self
+                        .test(condition, context)
+                        .map_err(|message| LiquidError::at(message, *position))?;
+                    if matched {
+                        return self.render_nodes(body, context, output).await;
+                    }
+                }
+                if let Some(body) = otherwise {
+                    self.render_nodes(body, context, output).await?;
+                }
+            }
+            Node::For(for_loop) => self.render_for(for_loop, context, output).await?,
+            Node::Assign {
+                name,
+                value,
+                position,
+            } => {
+                let value = self
+                    .evaluate_output(value, context)
+                    .await
+                    .map_err(|message| LiquidError::at(message, *position))?;
+                context.assign(name, value);
+            }
+            Node::Capture { name, body } => {
+                let mut captured = String::new();
+                self.render_nodes(body, context, &mut captured).await?;
+                context.assign(name, Value::String(captured));
+            }
+            Node::Increment(name) => {
+                let counter = context.counters.entry(name.clone()).or_insert(0);
+                output.push_str(&counter.to_string());
+                *counter += 1;
+            }
+            Node::Decrement(name) => {
+                let counter = context.counters.entry(name.clone()).or_insert(0);
+                *counter -= 1;
+                output.push_str(&counter.to_string());
+            }
+            Node::Cycle {
+                group,
+                values,
+                position,
+            } => {
+                let group = self
+                    .evaluate(group, context)
+                    .map_err(|message| LiquidError::at(message, *position))?;
+                let next = context.cycles.entry(to_text(&group)).or_insert(0);
+                let value = &values[*next % values.len()];
+                *next += 1;
+                let value = self
+                    .evaluate(value, context)
+                    .map_err(|message| LiquidError::at(message, *position))?;
+                output.push_str(&to_text(&value));
+            }
+            Node::Tablerow(tablerow) => self.render_tablerow(tablerow, context, output).await?,
+            Node::Custom(tag) => self.render_custom_tag(tag, context, output).await?,
+            Node::Paginate(paginate) => self.render_paginate(paginate, context, output).await?,
+            Node::Snippet(tag) => self.render_snippet(tag, context, output).await?,
+            Node::Form(form) => self.render_form(form, context, output).await?,
+            Node::Section { name, position } => {
+                self.render_section_into(
+                    name,
+                    SectionData::default(),
+                    context,
+                    output,
+                    Some(*position),
+                )
+                .await?
+            }
+            Node::SectionGroup { name, position } => {
+                self.render_section_group(name, context, output, *position)
+                    .await?
+            }
+            Node::Break => context.interrupt = Some(Interrupt::Break),
+            Node::Continue => context.interrupt = Some(Interrupt::Continue),
+        }
+        Ok(())
+    }
+
+    async fn render_for(
+        &self,
+        for_loop: &ForLoop,
+        context: &mut Context,
+        output: &mut String,
+    ) -> Result<(), LiquidError> {
+        let fail = |message: String| LiquidError::at(message, for_loop.position);
+        let limit = self
+            .loop_option(&for_loop.limit, "limit", context)
+            .map_err(fail)?;
+        let offset = self
+            .loop_option(&for_loop.offset, "offset", context)
+            .map_err(fail)?
+            .unwrap_or(0);
+        let collection = self.evaluate(&for_loop.collection, context).map_err(fail)?;
+        let mut items: Vec<Value> = iterate(collection)
+            .into_iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        if for_loop.reversed {
+            items.reverse();
+        }
+        if items.is_empty() {
+            if let Some(body) = &for_loop.otherwise {
+                self.render_nodes(body, context, output).await?;
+            }
+            return Ok(());
+        }
+
+        let length = items.len();
+        // The enclosing loop's `forloop`, if this loop is nested in one.
+        let parentloop = context.get("forloop");
+        context.push();
+        for (index, item) in items.into_iter().enumerate() {
+            context.set_local(&for_loop.variable, item);
+            context.set_local(
+                "forloop",
+                serde_json::json!({
+                    "index": index + 1,
+                    "index0": index,
+                    "rindex": length - index,
+                    "rindex0": length - index - 1,
+                    "first": index == 0,
+                    "last": index + 1 == length,
+                    "length": length,
+                    "parentloop": parentloop,
+                }),
+            );
+            let rendered = self.render_nodes(&for_loop.body, context, output).await;
+            if let Err(error) = rendered {
+                context.pop();
+                return Err(error);
+            }
+            if context.interrupt.take() == Some(Interrupt::Break) {
+                break;
+            }
+        }
+        context.pop();
+        Ok(())
+    }
+
+    /// Renders cells `<td class="colN">` in rows `<tr class="rowN">`, with
+    /// `tablerowloop` describing the current cell.
+    async fn render_tablerow(
+        &self,
+        tablerow: &Tablerow,
+        context: &mut Context,
+        output: &mut String,
+    ) -> Result<(), LiquidError> {
+        let fail = |message: String| LiquidError::at(message, tablerow.position);
+        let option =
+            |expr: &Option<Expr>, name: &str| self.loop_option(expr, name, context).map_err(fail);
+        let cols = option(&tablerow.cols, "cols")?.filter(|cols| *cols > 0);
+        let limit = option(&tablerow.limit, "limit")?;
+        let offset = option(&tablerow.offset, "offset")?.unwrap_or(0);
+        let collection = self.evaluate(&tablerow.collection, context).map_err(fail)?;
+        let items: Vec<Value> = iterate(collection)
+            .into_iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+
+        let length = items.len();
+        output.push_str("<tr class=\"row1\">\n");
+        context.push();
+        for (index, item) in items.into_iter().enumerate() {
+            let (row, col) = match cols {
+                Some(cols) => (index / cols, index % cols),
+                None => (0, index),
+            };
+            let col_last = cols.is_some_and(|cols| col + 1 == cols);
+            context.set_local(&tablerow.variable, item);
+            context.set_local(
+                "tablerowloop",
+                serde_json::json!({
+                    "col": col + 1,
+                    "col0": col,
+                    "col_first": col == 0,
+                    "col_last": col_last,
+                    "row": row + 1,
+                    "index": index + 1,
+                    "index0": index,
+                    "rindex": length - index,
+                    "rindex0": length - index - 1,
+                    "first": index == 0,
+                    "last": index + 1 == length,
+                    "length": length,
+                }),
+            );
+            output.push_str(&format!("<td class=\"col{}\">", col + 1));
+            let rendered = self.render_nodes(&tablerow.body, context, output).await;
+            if let Err(error) = rendered {
+                context.pop();
+                return Err(error);
+            }
+            output.push_str("</td>");
+            if context.interrupt.take() == Some(Interrupt::Break) {
+                break;
+            }
+            if col_last && index + 1 < length {
+                output.push_str(&format!("</tr>\n<tr class=\"row{}\">", row + 2));
+            }
+        }
+        context.pop();
+        output.push_str("</tr>\n");
+        Ok(())
+    }
+
+    /// A loop's `limit`, `offset` or `cols`: a whole number, or `None` when
+    /// unset or `nil`.
+    fn loop_option(
+        &self,
+        expr: &Option<Expr>,
+        name: &str,
+        context: &Context,
+    ) -> Result<Option<usize>, String> {
+        let Some(expr) = expr else {
+            return Ok(None);
+        };
+        match self.evaluate(expr, context)? {
+            Value::Null => Ok(None),
+            value => as_integer(&value)
+                .and_then(|number| usize::try_from(number).ok())
+                .map(Some)
+                .ok_or_else(|| format!("{name} must be a whole number")),
+        }
+    }
+
+    pub(crate) fn evaluate(&self, expr: &Expr, context: &Context) -> Result<Value, String> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            // Written out, `empty` and `blank` are nothing.
+            Expr::Empty | Expr::Blank => Ok(Value::String(String::new())),
+            Expr::Variable(segments) => {
+                let mut value = Value::Null;
+                // The path so far, to name what is undefined.
+                let mut path = String::new();
+                for (position, segment) in segments.iter().enumerate() {
+                    let found = match (position, segment) {
+                        (0, Segment::Key(name)) => {
+                            path.push_str(name);
+                            context.lookup(name)
+                        }
+                        (0, Segment::Index(key)) => {
+                            let name = to_text(&self.evaluate(key, context)?);
+                            path.push_str(&format!("[{name}]"));
+                            context.lookup(&name)
+                        }
+                        (_, Segment::Key(name)) => {
+                            path.push_str(&format!(".{name}"));
+                            find_property(&value, name)
+                        }
+                        (_, Segment::Index(key)) => {
+                            let key = self.evaluate(key, context)?;
+                            path.push_str(&format!("[{}]", to_text(&key)));
+                            find_index(&value, &key)
+                        }
+                    };
+                    match found {
+                        Some(found) => value = found,
+                        None => {
+                            self.undefined(format!("Undefined variable '{path}'"), context)?;
+                            return Ok(Value::Null);
+                        }
+                    }
+                }
+                Ok(value)
+            }
+            Expr::Range(start, end) => {
+                let bound = |expr: &Expr| -> Result<i64, String> {
+                    let value = self.evaluate(expr, context)?;
+                    as_integer(&value)
+                        .ok_or_else(|| format!("Invalid range bound: {}", to_text(&value)))
+                };
+                let (start, end) = (bound(start)?, bound(end)?);
+                if end < start {
+                    return Ok(Value::Array(Vec::new()));
+                }
+                if end.checked_sub(start).is_none_or(|span| span >= MAX_RANGE) {
+                    return Err(format!("Ranges are limited to {MAX_RANGE} items"));
+                }
+                Ok(Value::Array((start..=end).map(Value::from).collect()))
+            }
+        }
+    }
+
+    pub(crate) async fn evaluate_output(
+        &self,
+        output: &Output,
+        context: &Context,
+    ) -> Result<Value, String> {
+        let mut value = self.evaluate(&output.expr, context)?;
+        for filter in &output.filters {
+            let mut args = FilterArgs::default();
+            for arg in &filter.args {
+                args.positional.push(self.evaluate(arg, context)?);
+            }
+            for (key, arg) in &filter.keywords {
+                args.keywords
+                    .insert(key.clone(), self.evaluate(arg, context)?);
+            }
+            let custom = self.custom_filter(&filter.name, &value, &args).await;
+            let filtered = match custom
+                .or_else(|| self.money_filter(&filter.name, &value))
+                .or_else(|| self.translation_filter(&filter.name, &value, &args))
+                .or_else(|| self.url_filter(&filter.name, &value, &args))
+            {
+                Some(filtered) => filtered,
+                None => match filters::standard(&filter.name) {
+                    Some(apply) => apply(value, &args),
+                    // Lax mode passes the value through, as Shopify does.
+                    None => {
+                        self.undefined(format!("Unknown filter '{}'", filter.name), context)?;
+                        continue;
+                    }
+                },
+            };
+            value = filtered.map_err(|error| format!("{}: {error}", filter.name))?;
+        }
+        Ok(value)
+    }
+
+    /// Fails in strict mode; warns and carries on in lax mode.
+    fn undefined(&self, message: String, context: &Context) -> Result<(), String> {
+        if self.strict() {
+            return Err(message);
+        }
+        context.warn(message);
+        Ok(())
+    }
+
+    pub(crate) fn test(&self, condition: &Condition, context: &Context) -> Result<bool, String> {
+        Ok(match condition {
+            Condition::Test(expr) => truthy(&self.evaluate(expr, context)?),
+            Condition::And(left, right) => {
+                self.test(left, context)? && self.test(right, context)?
+            }
+            Condition::Or(left, right) => self.test(left, context)? || self.test(right, context)?,
+            Condition::Not(condition) => !self.test(condition, context)?,
+            Condition::Compare(left, operator, right) => {
+                // `x == empty` and `x == blank` test `x` rather than compare.
+                if matches!(operator, Operator::Equal | Operator::NotEqual) {
+                    let special = match (left, right) {
+                        (other, Expr::Empty) | (Expr::Empty, other) => {
+                            Some(is_empty(&self.evaluate(other, context)?))
+                        }
+                        (other, Expr::Blank) | (Expr::Blank, other) => {
+                            Some(is_blank(&self.evaluate(other, context)?))
+                        }
+                        _ => None,
+                    };
+                    if let Some(matched) = special {
+                        return Ok(matched == (*operator == Operator::Equal));
+                    }
+                }
+                let (left, right) = (
+                    self.evaluate(left, context)?,
+                    self.evaluate(right, context)?,
+                );
+                match operator {
+                    Operator::Equal => equals(&left, &right),
+                    Operator::NotEqual => !equals(&left, &right),
+                    Operator::Contains => contains(&left, &right),
+                    Operator::Less => compare(&left, &right).is_some_and(|order| order.is_lt()),
+                    Operator::Greater => compare(&left, &right).is_some_and(|order| order.is_gt()),
+                    Operator::LessOrEqual => {
+                        compare(&left, &right).is_some_and(|order| order.is_le())
+                    }
+                    Operator::GreaterOrEqual => {
+                        compare(&left, &right).is_some_and(|order| order.is_ge())
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::task::{Context as TaskContext, Poll, Waker};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::liquid::engine::{RenderMode, Rendered};
+
+    /// Runs a render to completion. Templates that don't reach the Storefront
+    /// API or JavaScript callbacks never wait, so there is nothing to wake.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut task = TaskContext::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut task) {
+                return output;
+            }
+        }
+    }
+
+    fn render_in(
+        engine: &LiquidEngine,
+        source: &str,
+        data: Value,
+    ) -> Result<Rendered, LiquidError> {
+        let template = engine.parse_template(source)?;
+        let Value::Object(variables) = data else {
+            panic!("data must be an object");
+        };
+        block_on(engine.render_with_warnings(&template, variables))
+    }
+
+    fn render(source: &str, data: Value) -> String {
+        render_in(&LiquidEngine::new(), source, data)
+            .unwrap()
+            .output
+    }
+
+    fn render_error(source: &str, data: Value) -> LiquidError {
+        render_in(&LiquidEngine::new(), source, data).unwrap_err()
+    }
+
+    #[test]
+    fn outputs_and_filters() {
+        assert_eq!(
+            render("Hello {{ name | upcase }}!", json!({ "name": "Ada" })),
+            "Hello ADA!"
+        );
+        assert_eq!(
+            render(
+                "{{ product.variants[1].title }}",
+                json!({
+                    "product": { "variants": [{ "title": "S" }, { "title": "M" }] },
+                })
+            ),
+            "M"
+        );
+    }
+
+    #[test]
+    fn for_loops_apply_offset_then_limit_then_reversed() {
+        let data = json!({ "items": [1, 2, 3, 4, 5] });
+
+        assert_eq!(
+            render(
+                "{% for i in items limit: 2 %}{{ i }}{% endfor %}",
+                data.clone()
+            ),
+            "12"
+        );
+        assert_eq!(
+            render(
+                "{% for i in items offset: 3 %}{{ i }}{% endfor %}",
+                data.clone()
+            ),
+            "45"
+        );
+        assert_eq!(
+            render(
+                "{% for i in items reversed limit: 3, offset: 1 %}{{ i }}{% endfor %}",
+                data.clone()
+            ),
+            "432"
+        );
+        assert_eq!(
+            render(
+                "{% for i in items offset: 9 %}{{ i }}{% else %}none{% endfor %}",
+                data
+            ),
+            "none"
+        );
+    }
+
+    #[test]
+    fn for_loop_options_must_be_whole_numbers() {
+        let error = render_error(
+            "\n{% for i in items limit: -1 %}{% endfor %}",
+            json!({ "items": [1] }),
+        );
+
+        assert_eq!(error.message, "limit must be a whole number");
+        assert_eq!(error.position, Some(Position { line: 2, column: 1 }));
+    }
+
+    #[test]
+    fn forloop_describes_the_iteration() {
+        let source = "{% for i in (1..3) %}{{ forloop.index }}/{{ forloop.rindex0 }}\
+                      {% if forloop.last %}.{% else %},{% endif %}{% endfor %}";
+
+        assert_eq!(render(source, json!({})), "1/2,2/1,3/0.");
+    }
+
+    #[test]
+    fn break_and_continue() {
+        let source = "{% for i in (1..6) %}{% if i == 2 %}{% continue %}{% endif %}\
+                      {% if i == 5 %}{% break %}{% endif %}{{ i }}{% endfor %}";
+
+        assert_eq!(render(source, json!({})), "134");
+    }
+
+    #[test]
+    fn ranges_are_inclusive_and_take_variables() {
+        assert_eq!(render("{{ (1..4) | join: ',' }}", json!({})), "1,2,3,4");
+        assert_eq!(render("{{ (-1..1) | join: ',' }}", json!({})), "-1,0,1");
+        assert_eq!(
+            render(
+                "{% for i in (2..n) %}{{ i }}{% endfor %}",
+                json!({ "n": "4" })
+            ),
+            "234"
+        );
+    }
+
+    #[test]
+    fn reversed_ranges_are_empty() {
+        assert_eq!(
+            render(
+                "{% for i in (5..1) %}{{ i }}{% else %}empty{% endfor %}",
+                json!({})
+            ),
+            "empty"
+        );
+    }
+
+    #[test]
+    fn ranges_are_capped() {
+        assert_eq!(render("{{ (1..100000) | size }}", json!({})), "100000");
+
+        let error = render_error("{{ (0..100000) | size }}", json!({}));
+        assert_eq!(error.message, "Ranges are limited to 100000 items");
+    }
+
+    #[test]
+    fn ranges_spanning_more_than_an_i64_fail_rather_than_overflow() {
+        let error = render_error(
+            "\n {% for i in (-9223372036854775808..9223372036854775807) %}{% endfor %}",
+            json!({}),
+        );
+
+        assert_eq!(error.message, "Ranges are limited to 100000 items");
+        assert_eq!(error.position, Some(Position { line: 2, column: 2 }));
+    }
+
+    #[test]
+    fn range_bounds_must_be_integers() {
+        let error = render_error("{{ (1..last) }}", json!({ "last": "many" }));
+
+        assert_eq!(error.message, "Invalid range bound: many");
+    }
+
+    #[test]
+    fn whitespace_control_trims_around_tags_and_outputs() {
+        let source =
+            "<ul>\n  {%- for i in (1..2) %}\n    <li>{{- i -}}</li>\n  {%- endfor %}\n</ul>";
+
+        assert_eq!(
+            render(source, json!({})),
+            "<ul>\n    <li>1</li>\n    <li>2</li>\n</ul>"
+        );
+        assert_eq!(render("a  {{- 'b' -}}  c", json!({})), "abc");
+        assert_eq!(render("{% assign x = 1 -%}\n\n{{ x }}", json!({})), "1");
+    }
+
+    #[test]
+    fn assign_capture_and_counters() {
+        let source =
+            "{% assign name = 'tee' | upcase %}{% capture label %}{{ name }}!{% endcapture %}\
+                      {{ label }} {% increment n %}{% increment n %}{% decrement m %}";
+
+        assert_eq!(render(source, json!({})), "TEE! 01-1");
+    }
+
+    #[test]
+    fn only_false_and_nil_are_falsy() {
+        let source = "{% if zero and empty_text %}yes{% endif %}\
+                      {% unless missing %}no{% endunless %}\
+                      {% if list == empty %}empty{% endif %}";
+
+        assert_eq!(
+            render(source, json!({ "zero": 0, "empty_text": "", "list": [] })),
+            "yesnoempty"
+        );
+    }
+
+    #[test]
+    fn case_matches_any_listed_value() {
+        let source = "{% case size %}{% when 'S', 'M' %}small{% when 'L' %}large\
+                      {% else %}other{% endcase %}";
+
+        assert_eq!(render(source, json!({ "size": "M" })), "small");
+        assert_eq!(render(source, json!({ "size": "XL" })), "other");
+    }
+
+    #[test]
+    fn lax_mode_reports_undefined_variables_and_unknown_filters() {
+        let rendered = render_in(
+            &LiquidEngine::new(),
+            "[{{ missing }}]\n{{ 'x' | sparkle }}",
+            json!({}),
+        )
+        .unwrap();
+
+        assert_eq!(rendered.output, "[]\nx");
+        let warnings: Vec<_> = rendered
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.message.as_str(),
+                    diagnostic.line,
+                    diagnostic.column,
+                )
+            })
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                ("Undefined variable 'missing'", Some(1), Some(2)),
+                ("Unknown filter 'sparkle'", Some(2), Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_mode_fails_on_undefined_variables() {
+        let engine = LiquidEngine::new();
+        engine.mode.set(RenderMode::Strict);
+
+        let error = render_in(
+            &engine,
+            "ok\n  {{ product.nope }}",
+            json!({ "product": {} }),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.message, "Undefined variable 'product.nope'");
+        assert_eq!(error.position, Some(Position { line: 2, column: 3 }));
+    }
+
+    #[test]
+    fn auto_escape_spares_raw_output() {
+        let engine = LiquidEngine::new();
+        engine.auto_escape.set(true);
+        let data = json!({ "note": "<b>hi</b>" });
+
+        let rendered = render_in(&engine, "{{ note }} {{ note | raw }}", data).unwrap();
+
+        assert_eq!(rendered.output, "&lt;b&gt;hi&lt;/b&gt; <b>hi</b>");
+    }
+}
@@ -0,0 +1,167 @@
+//! The `routes` drop and locale-aware URLs.
+//!
+//! Rendering in a locale other than the default puts storefront paths
+//! under the locale's prefix, as Shopify's language subfolders do:
+//! `routes.cart_url` is `/fr/cart` and `product.url` `/fr/products/tee`.
+//! `set_root_url` sets the prefix instead, for market subfolders such as
+//! `/en-ca`. The `within`, `url_for_type` and `url_for_vendor` filters
+//! build the links drops don't carry.
+
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::engine::LiquidEngine;
+use super::filters::{url_encoded, FilterArgs};
+use super::value::{property, to_text};
+use crate::to_js;
+pub use crate::urls::localized_path;
+
+/// The `routes` drop's URLs, less the root URL.
+const ROUTES: &[(&str, &str)] = &[
+    ("account_url", "/account"),
+    ("account_login_url", "/account/login"),
+    ("account_logout_url", "/account/logout"),
+    ("account_register_url", "/account/register"),
+    ("account_recover_url", "/account/recover"),
+    ("account_addresses_url", "/account/addresses"),
+    ("all_products_collection_url", "/collections/all"),
+    ("cart_url", "/cart"),
+    ("cart_add_url", "/cart/add"),
+    ("cart_change_url", "/cart/change"),
+    ("cart_clear_url", "/cart/clear"),
+    ("cart_update_url", "/cart/update"),
+    ("collections_url", "/collections"),
+    ("predictive_search_url", "/search/suggest"),
+    ("product_recommendations_url", "/recommendations/products"),
+    ("search_url", "/search"),
+    ("storefront_login_url", "/password"),
+];
+
+/// Paths drops link to, which a locale or market prefix goes in front of.
+const STOREFRONT_PATHS: &[&str] = &["/products/", "/collections/", "/pages/", "/blogs/"];
+
+pub fn routes_drop(root: &str) -> Value {
+    let mut routes = Map::new();
+    routes.insert("root_url".to_string(), Value::from(root));
+    for (name, path) in ROUTES {
+        routes.insert(name.to_string(), Value::from(localized_path(root, path)));
+    }
+    Value::Object(routes)
+}
+
+/// Puts the `url` of every drop in `value` under `root`.
+fn localize_urls(value: &mut Value, root: &str) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(url) if key == "url" => {
+                        if STOREFRONT_PATHS.iter().any(|path| url.starts_with(path)) {
+                            *url = localized_path(root, url);
+                        }
+                    }
+                    value => localize_urls(value, root),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                localize_urls(item, root);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The root URL set with `set_root_url`.
+#[derive(Debug, Clone, Default)]
+pub struct Routes {
+    root_url: Option<String>,
+}
+
+impl LiquidEngine {
+    /// `/` in the default locale, `/<locale>` in another, unless
+    /// `set_root_url` says otherwise.
+    pub fn root_url(&self) -> String {
+        if let Some(root) = &self.routes.borrow().root_url {
+            return root.clone();
+        }
+        let locales = self.locales.borrow();
+        match (locales.current(), locales.default_locale()) {
+            (Some(current), Some(default)) if current != default => format!("/{current}"),
+            _ => "/".to_string(),
+        }
+    }
+
+    /// Adds the `routes` drop to a render's variables, unless the data
+    /// brought its own, and puts drop URLs under the root URL.
+    pub(crate) fn add_routes(&self, variables: &mut Map<String, Value>) {
+        let root = self.root_url();
+        if root != "/" {
+            for value in variables.values_mut() {
+                localize_urls(value, &root);
+            }
+        }
+        variables
+            .entry("routes")
+            .or_insert_with(|| routes_drop(&root));
+    }
+
+    /// Applies `within`, `url_for_type` or `url_for_vendor`.
+    pub(crate) fn url_filter(
+        &self,
+        name: &str,
+        input: &Value,
+        args: &FilterArgs,
+    ) -> Option<Result<Value, String>> {
+        let url = match name {
+            // `product.url | within: collection`: the product's URL under
+            // the collection's.
+            "within" => {
+                let url = to_text(input);
+                let collection = args.get(0).map_or(Value::Null, |arg| property(arg, "url"));
+                match (url.rsplit_once("/products/"), collection) {
+                    (Some((_, handle)), Value::String(collection)) => {
+                        format!("{collection}/products/{handle}")
+                    }
+                    _ => url,
+                }
+            }
+            "url_for_type" => localized_path(
+                &self.root_url(),
+                &format!("/collections/types?q={}", url_encoded(&to_text(input))),
+            ),
+            "url_for_vendor" => localized_path(
+                &self.root_url(),
+                &format!("/collections/vendors?q={}", url_encoded(&to_text(input))),
+            ),
+            _ => return None,
+        };
+        Some(Ok(Value::String(url)))
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Puts storefront URLs under `root` (e.g. `"/en-ca"` for a market
+    /// subfolder) rather than the locale's prefix. Omit it to go back to
+    /// following the locale.
+    #[wasm_bindgen]
+    pub fn set_root_url(&self, root: Option<String>) -> Result<(), JsValue> {
+        if let Some(root) = &root {
+            if !root.starts_with('/') {
+                return Err(JsValue::from_str(&format!(
+                    "The root URL must be a path starting with '/': {root}"
+                )));
+            }
+        }
+        self.routes.borrow_mut().root_url = root;
+        Ok(())
+    }
+
+    /// The `routes` drop templates render with.
+    #[wasm_bindgen(unchecked_return_type = "Record<string, string>")]
+    pub fn routes(&self) -> Result<JsValue, JsValue> {
+        to_js(&routes_drop(&self.root_url()))
+    }
+}
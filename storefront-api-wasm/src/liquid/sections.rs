@@ -0,0 +1,269 @@
+//! Theme sections: a template with a `{% schema %}`, rendered with the
+//! `section` drop built from its settings and blocks.
+//!
+//! Section data takes the shape Online Store 2.0 JSON templates store:
+//! `{ id, settings, blocks: { id: { type, settings } }, block_order }`.
+//! `blocks` may also be an array of `{ id, type, settings }`. Settings the
+//! data leaves out take their schema `default`.
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::drops::wrap_storefront_data;
+use super::engine::{template_variables, LiquidEngine};
+use super::error::{LiquidError, Position};
+use super::parser::Template;
+use super::render::Context;
+use super::snippets::MAX_SNIPPET_DEPTH;
+use crate::{from_js, to_js};
+
+/// Elements a schema's `tag` may wrap the section in.
+const SECTION_TAGS: &[&str] = &["article", "aside", "div", "footer", "header", "section"];
+
+/// Registered section templates by type.
+pub type Sections = BTreeMap<String, Rc<Template>>;
+
+/// Settings and blocks of one section instance.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SectionData {
+    /// Defaults to the section's type.
+    pub id: Option<String>,
+    pub settings: Map<String, Value>,
+    pub blocks: Value,
+    #[serde(alias = "block_order")]
+    pub block_order: Vec<String>,
+}
+
+/// `settings` with schema defaults filled in for settings it leaves out.
+fn with_defaults(definitions: Option<&Value>, mut settings: Map<String, Value>) -> Value {
+    for definition in definitions.and_then(Value::as_array).into_iter().flatten() {
+        let (Some(id), Some(default)) = (
+            definition.get("id").and_then(Value::as_str),
+            definition.get("default"),
+        ) else {
+            continue;
+        };
+        settings
+            .entry(id.to_string())
+            .or_insert_with(|| default.clone());
+    }
+    let mut settings = Value::Object(settings);
+    if let Value::Object(map) = &mut settings {
+        wrap_storefront_data(map);
+    }
+    settings
+}
+
+/// The `section` drop for `data`, checked against `schema`.
+pub fn section_drop(
+    kind: &str,
+    schema: Option<&Value>,
+    data: SectionData,
+) -> Result<Value, String> {
+    let id = data.id.unwrap_or_else(|| kind.to_string());
+    let block_schemas: Vec<&Value> = schema
+        .and_then(|schema| schema.get("blocks"))
+        .and_then(Value::as_array)
+        .map(|blocks| blocks.iter().collect())
+        .unwrap_or_default();
+
+    let mut blocks: Vec<(String, Value)> = match data.blocks {
+        Value::Null => Vec::new(),
+        Value::Array(blocks) => blocks
+            .into_iter()
+            .enumerate()
+            .map(|(index, block)| {
+                let id = block
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("{id}-block-{}", index + 1));
+                (id, block)
+            })
+            .collect(),
+        Value::Object(blocks) => blocks.into_iter().collect(),
+        _ => return Err("Section blocks must be an array or an object".to_string()),
+    };
+    if !data.block_order.is_empty() {
+        let mut ordered = Vec::new();
+        for block_id in &data.block_order {
+            let index = blocks
+                .iter()
+                .position(|(id, _)| id == block_id)
+                .ok_or_else(|| format!("block_order names a missing block: {block_id}"))?;
+            ordered.push(blocks.remove(index));
+        }
+        blocks = ordered;
+    }
+    if let Some(max) = schema
+        .and_then(|schema| schema.get("max_blocks"))
+        .and_then(Value::as_u64)
+    {
+        if blocks.len() as u64 > max {
+            return Err(format!("Section {kind} allows at most {max} blocks"));
+        }
+    }
+
+    let mut block_drops = Vec::new();
+    for (block_id, block) in blocks {
+        let block_type = block
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Block {block_id} has no type"))?;
+        let block_schema = block_schemas
+            .iter()
+            .find(|definition| definition.get("type").and_then(Value::as_str) == Some(block_type));
+        // `@app` blocks and sections without block definitions take anything.
+        if block_schema.is_none() && !block_schemas.is_empty() && !block_type.starts_with('@') {
+            return Err(format!("Section {kind} has no block type {block_type}"));
+        }
+        let settings = block
+            .get("settings")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        block_drops.push(serde_json::json!({
+            "id": block_id,
+            "type": block_type,
+            "settings": with_defaults(block_schema.and_then(|schema| schema.get("settings")), settings),
+            "shopify_attributes": "",
+        }));
+    }
+
+    Ok(serde_json::json!({
+        "id": id,
+        "settings": with_defaults(schema.and_then(|schema| schema.get("settings")), data.settings),
+        "blocks": block_drops,
+    }))
+}
+
+impl LiquidEngine {
+    /// Parses `source` and registers it as section type `kind`.
+    pub fn define_section(&self, kind: &str, source: &str) -> Result<(), LiquidError> {
+        let template = self
+            .parse_template(source)
+            .map_err(|error| error.in_section(kind))?;
+        self.sections
+            .borrow_mut()
+            .insert(kind.to_string(), Rc::new(template));
+        Ok(())
+    }
+
+    /// Renders a section instance wrapped in its `shopify-section` element,
+    /// with `variables` as the page's other objects.
+    pub async fn render_section_with(
+        &self,
+        kind: &str,
+        data: SectionData,
+        variables: Map<String, Value>,
+    ) -> Result<String, LiquidError> {
+        let mut context = self.page_context(variables);
+        let mut output = String::new();
+        self.render_section_into(kind, data, &mut context, &mut output, None)
+            .await?;
+        Ok(output)
+    }
+
+    pub(crate) async fn render_section_into(
+        &self,
+        kind: &str,
+        data: SectionData,
+        context: &mut Context,
+        output: &mut String,
+        position: Option<Position>,
+    ) -> Result<(), LiquidError> {
+        let fail = |message: String| match position {
+            Some(position) => LiquidError::at(message, position),
+            None => LiquidError::new(message),
+        };
+        let template = self
+            .sections
+            .borrow()
+            .get(kind)
+            .cloned()
+            .ok_or_else(|| fail(format!("Unknown section '{kind}'")))?;
+        if context.depth >= MAX_SNIPPET_DEPTH {
+            return Err(fail(format!(
+                "Sections and snippets can only be nested {MAX_SNIPPET_DEPTH} deep"
+            )));
+        }
+        let schema = template.schema.as_ref();
+        let section = section_drop(kind, schema, data).map_err(fail)?;
+        let tag = schema
+            .and_then(|schema| schema.get("tag"))
+            .and_then(Value::as_str)
+            .filter(|tag| SECTION_TAGS.contains(tag))
+            .unwrap_or("div");
+        let class = schema
+            .and_then(|schema| schema.get("class"))
+            .and_then(Value::as_str)
+            .map(|class| format!(" {class}"))
+            .unwrap_or_default();
+
+        // Sections see the page's objects but not the caller's loop
+        // variables, and their assigns stay inside.
+        let mut variables = context.globals().clone();
+        variables.insert("section".to_string(), section.clone());
+        let mut inner = context.isolated(variables, format!("section '{kind}'"));
+        output.push_str(&format!(
+            "<{tag} id=\"shopify-section-{}\" class=\"shopify-section{class}\">",
+            section["id"].as_str().unwrap_or(kind)
+        ));
+        self.render_nodes(&template.nodes, &mut inner, output)
+            .await
+            .map_err(|error| error.in_section(kind))?;
+        output.push_str(&format!("</{tag}>"));
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Registers a section template, `{% schema %}` included, under its
+    /// type (the file name in `sections/`).
+    #[wasm_bindgen]
+    pub fn register_section(&self, kind: &str, source: &str) -> Result<(), JsValue> {
+        Ok(self.define_section(kind, source)?)
+    }
+
+    /// Removes a section type, returning whether it was registered.
+    #[wasm_bindgen]
+    pub fn remove_section(&self, kind: &str) -> bool {
+        self.sections.borrow_mut().remove(kind).is_some()
+    }
+
+    /// The parsed `{% schema %}` of a registered section, or `null` if it
+    /// has none.
+    #[wasm_bindgen(unchecked_return_type = "SectionSchema | null")]
+    pub fn section_schema(&self, kind: &str) -> Result<JsValue, JsValue> {
+        let sections = self.sections.borrow();
+        let template = sections
+            .get(kind)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown section '{kind}'")))?;
+        to_js(&template.schema)
+    }
+
+    /// Renders a registered section with `section` data (`{ id, settings,
+    /// blocks, block_order }`, as in a JSON template) and the page's other
+    /// objects in `data`.
+    #[wasm_bindgen]
+    pub async fn render_section(
+        &self,
+        kind: String,
+        #[wasm_bindgen(unchecked_param_type = "SectionData | null | undefined")] section: JsValue,
+        #[wasm_bindgen(unchecked_optional_param_type = "TemplateData")] data: JsValue,
+    ) -> Result<String, JsValue> {
+        let section: SectionData = if section.is_undefined() || section.is_null() {
+            SectionData::default()
+        } else {
+            from_js(section)?
+        };
+        let variables = template_variables(data)?;
+        Ok(self.render_section_with(&kind, section, variables).await?)
+    }
+}
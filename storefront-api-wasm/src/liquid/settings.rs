@@ -0,0 +1,196 @@
+//! Theme settings and the `settings` drop.
+//!
+//! `settings_schema.json` declares the settings, grouped as in the theme
+//! editor, with their defaults. `settings_data.json` holds the merchant's
+//! values under `current`, which may instead name one of its `presets`.
+//! The drop is the schema defaults, overlaid with the preset and then with
+//! the current values.
+
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::engine::LiquidEngine;
+use crate::{from_js, to_js};
+
+/// The loaded `settings_schema.json` and `settings_data.json`.
+#[derive(Debug, Clone, Default)]
+pub struct ThemeSettings {
+    /// Setting definitions from every schema group.
+    definitions: Vec<Value>,
+    presets: Map<String, Value>,
+    current: Map<String, Value>,
+    /// The preset `current` names, or the one chosen with `set_preset`.
+    preset: Option<String>,
+    loaded: bool,
+}
+
+impl ThemeSettings {
+    /// Loads `settings_schema.json`: an array of groups, each with a
+    /// `settings` array of definitions.
+    pub fn load_schema(&mut self, schema: Value) -> Result<(), String> {
+        let Value::Array(groups) = schema else {
+            return Err("settings_schema.json must be an array of groups".to_string());
+        };
+        self.definitions = groups
+            .into_iter()
+            .filter_map(|mut group| match group.get_mut("settings")?.take() {
+                Value::Array(settings) => Some(settings),
+                _ => None,
+            })
+            .flatten()
+            .filter(|definition| definition.get("id").is_some_and(Value::is_string))
+            .collect();
+        self.loaded = true;
+        Ok(())
+    }
+
+    /// Loads `settings_data.json`: `{ current, presets }`, where `current`
+    /// is an object of values or the name of a preset.
+    pub fn load_data(&mut self, data: Value) -> Result<(), String> {
+        let Value::Object(mut data) = data else {
+            return Err("settings_data.json must be an object".to_string());
+        };
+        let presets = match data.remove("presets") {
+            Some(Value::Object(presets)) => presets,
+            None | Some(Value::Null) => Map::new(),
+            Some(_) => return Err("settings_data.json presets must be an object".to_string()),
+        };
+        let (current, preset) = match data.remove("current") {
+            Some(Value::String(name)) => (Map::new(), Some(name)),
+            Some(Value::Object(current)) => (current, None),
+            None | Some(Value::Null) => (Map::new(), None),
+            Some(_) => {
+                return Err(
+                    "settings_data.json current must be an object or a preset name".to_string(),
+                )
+            }
+        };
+        if let Some(name) = &preset {
+            if !presets.contains_key(name) {
+                return Err(format!("settings_data.json names a missing preset: {name}"));
+            }
+        }
+        self.presets = presets;
+        self.current = current;
+        self.preset = preset;
+        self.loaded = true;
+        Ok(())
+    }
+
+    /// Renders with a preset's values under the current ones, or with
+    /// only the current ones when `name` is `None`.
+    pub fn set_preset(&mut self, name: Option<&str>) -> Result<(), String> {
+        if let Some(name) = name {
+            if !self.presets.contains_key(name) {
+                return Err(format!("Unknown settings preset: {name}"));
+            }
+        }
+        self.preset = name.map(String::from);
+        Ok(())
+    }
+
+    pub fn preset_names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(String::as_str)
+    }
+
+    /// Whether a schema or data file has been loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    /// The `settings` drop.
+    pub fn settings_drop(&self) -> Value {
+        let mut settings = Map::new();
+        for definition in &self.definitions {
+            let id = definition["id"].as_str().unwrap_or_default();
+            let default = match (definition.get("default"), definition.get("type")) {
+                (Some(default), _) => default.clone(),
+                // Unticked checkboxes are false, not nil.
+                (None, Some(kind)) if kind == "checkbox" => Value::Bool(false),
+                (None, _) => Value::Null,
+            };
+            settings.insert(id.to_string(), default);
+        }
+        let preset = self
+            .preset
+            .as_ref()
+            .and_then(|name| self.presets.get(name))
+            .and_then(Value::as_object);
+        for values in preset.into_iter().chain([&self.current]) {
+            for (key, value) in values {
+                // Saved sections and blocks aren't settings.
+                if key != "sections" && key != "content_for_index" {
+                    settings.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Value::Object(settings)
+    }
+}
+
+impl LiquidEngine {
+    /// Adds the `settings` drop to a render's variables, unless the data
+    /// brought its own.
+    pub(crate) fn add_settings(&self, variables: &mut Map<String, Value>) {
+        let settings = self.settings.borrow();
+        if settings.is_loaded() && !variables.contains_key("settings") {
+            variables.insert("settings".to_string(), settings.settings_drop());
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Loads the theme's `config/settings_schema.json` for setting
+    /// defaults.
+    #[wasm_bindgen]
+    pub fn load_settings_schema(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "SettingsSchemaGroup[]")] schema: JsValue,
+    ) -> Result<(), JsValue> {
+        self.settings
+            .borrow_mut()
+            .load_schema(from_js(schema)?)
+            .map_err(|error| JsValue::from_str(&error))
+    }
+
+    /// Loads the theme's `config/settings_data.json`, the merchant's
+    /// values. Templates then see them as `settings` unless their data
+    /// passes its own.
+    #[wasm_bindgen]
+    pub fn load_settings_data(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "SettingsData")] data: JsValue,
+    ) -> Result<(), JsValue> {
+        self.settings
+            .borrow_mut()
+            .load_data(from_js(data)?)
+            .map_err(|error| JsValue::from_str(&error))
+    }
+
+    /// Renders with a preset from `settings_data.json` under the current
+    /// values, or with the current values alone when `name` is omitted.
+    #[wasm_bindgen]
+    pub fn set_settings_preset(&self, name: Option<String>) -> Result<(), JsValue> {
+        self.settings
+            .borrow_mut()
+            .set_preset(name.as_deref())
+            .map_err(|error| JsValue::from_str(&error))
+    }
+
+    /// Names of the presets in `settings_data.json`.
+    #[wasm_bindgen]
+    pub fn settings_presets(&self) -> Vec<String> {
+        self.settings
+            .borrow()
+            .preset_names()
+            .map(String::from)
+            .collect()
+    }
+
+    /// The `settings` drop templates render with.
+    #[wasm_bindgen(unchecked_return_type = "Record<string, unknown>")]
+    pub fn theme_settings(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.settings.borrow().settings_drop())
+    }
+}
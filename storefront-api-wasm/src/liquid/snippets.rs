@@ -0,0 +1,161 @@
+//! The snippet registry and the `render` and `include` tags.
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::engine::LiquidEngine;
+use super::error::LiquidError;
+use super::parser::{SnippetKind, SnippetTag, Template};
+use super::render::Context;
+use super::value::{iterate, to_text};
+
+/// Deepest snippets may render one another, so a snippet that renders
+/// itself fails instead of recursing forever.
+pub const MAX_SNIPPET_DEPTH: usize = 10;
+
+/// Parsed snippets by name, as in a theme's `snippets/` directory.
+#[derive(Debug, Clone, Default)]
+pub struct Snippets {
+    templates: BTreeMap<String, Rc<Template>>,
+}
+
+impl Snippets {
+    pub fn get(&self, name: &str) -> Option<Rc<Template>> {
+        self.templates.get(name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+}
+
+/// `forloop` for `render 'name' for items`.
+fn forloop(index: usize, length: usize) -> Value {
+    serde_json::json!({
+        "index": index + 1,
+        "index0": index,
+        "rindex": length - index,
+        "rindex0": length - index - 1,
+        "first": index == 0,
+        "last": index + 1 == length,
+        "length": length,
+    })
+}
+
+impl LiquidEngine {
+    /// Parses `source` and registers it as snippet `name`, replacing any
+    /// snippet of that name.
+    pub fn define_snippet(&self, name: &str, source: &str) -> Result<(), LiquidError> {
+        let template = self
+            .parse_template(source)
+            .map_err(|error| error.in_snippet(name))?;
+        self.snippets
+            .borrow_mut()
+            .templates
+            .insert(name.to_string(), Rc::new(template));
+        Ok(())
+    }
+
+    pub(crate) async fn render_snippet(
+        &self,
+        tag: &SnippetTag,
+        context: &mut Context,
+        output: &mut String,
+    ) -> Result<(), LiquidError> {
+        let fail = |message: String| LiquidError::at(message, tag.position);
+        let name = to_text(&self.evaluate(&tag.name, context).map_err(fail)?);
+        let template = self
+            .snippets
+            .borrow()
+            .get(&name)
+            .ok_or_else(|| fail(format!("Unknown snippet '{name}'")))?;
+        if context.depth >= MAX_SNIPPET_DEPTH {
+            return Err(fail(format!(
+                "Snippets can only be nested {MAX_SNIPPET_DEPTH} deep"
+            )));
+        }
+
+        let mut arguments = Map::new();
+        for (key, value) in &tag.arguments {
+            arguments.insert(key.clone(), self.evaluate(value, context).map_err(fail)?);
+        }
+        let alias = tag.alias.clone().unwrap_or_else(|| name.clone());
+        let value = match &tag.value {
+            Some(value) => Some(self.evaluate(value, context).map_err(fail)?),
+            None => None,
+        };
+        // One set of variables per rendering: per item for `for`.
+        let runs: Vec<Map<String, Value>> = match value {
+            Some(items) if tag.each => {
+                let items = iterate(items);
+                let length = items.len();
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let mut variables = arguments.clone();
+                        variables.insert(alias.clone(), item);
+                        variables.insert("forloop".to_string(), forloop(index, length));
+                        variables
+                    })
+                    .collect()
+            }
+            Some(value) => {
+                arguments.insert(alias, value);
+                vec![arguments]
+            }
+            None => vec![arguments],
+        };
+
+        for variables in runs {
+            let rendered = match tag.kind {
+                SnippetKind::Render => {
+                    let mut isolated = context.isolated(variables, format!("snippet '{name}'"));
+                    self.render_nodes(&template.nodes, &mut isolated, output)
+                        .await
+                }
+                SnippetKind::Include => {
+                    context.push();
+                    for (key, value) in variables {
+                        context.set_local(&key, value);
+                    }
+                    context.depth += 1;
+                    let source = context.replace_source(Some(format!("snippet '{name}'")));
+                    let rendered = self.render_nodes(&template.nodes, context, output).await;
+                    context.replace_source(source);
+                    context.depth -= 1;
+                    context.pop();
+                    rendered
+                }
+            };
+            rendered.map_err(|error| error.in_snippet(&name))?;
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Registers a snippet for `{% render 'name' %}` and `{% include %}`,
+    /// e.g. `register_snippet("product-card", source)`. Fails with the
+    /// first syntax error in `source`.
+    #[wasm_bindgen]
+    pub fn register_snippet(&self, name: &str, source: &str) -> Result<(), JsValue> {
+        Ok(self.define_snippet(name, source)?)
+    }
+
+    /// Removes a snippet, returning whether it was registered.
+    #[wasm_bindgen]
+    pub fn remove_snippet(&self, name: &str) -> bool {
+        self.snippets.borrow_mut().templates.remove(name).is_some()
+    }
+
+    /// Names of the registered snippets.
+    #[wasm_bindgen]
+    pub fn snippet_names(&self) -> Vec<String> {
+        self.snippets.borrow().names().map(String::from).collect()
+    }
+}
@@ -0,0 +1,126 @@
+//! Streaming rendering: output is handed over in chunks as the template
+//! renders, so a server can flush the top of a page while later sections
+//! are still fetching data.
+//!
+//! A chunk ends after each top-level block: a section, a rendered snippet
+//! or a tag with a body such as `if`, `for` or `paginate`. Text and output
+//! between blocks join the chunk that follows them.
+
+use std::future::Future;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use super::compiled::CompiledTemplate;
+use super::custom::js_error;
+use super::engine::{template_variables, LiquidEngine};
+use super::error::LiquidError;
+use super::parser::{Node, Template};
+
+/// Whether a top-level node closes a chunk.
+fn ends_chunk(node: &Node) -> bool {
+    match node {
+        Node::If { .. }
+        | Node::For(_)
+        | Node::Tablerow(_)
+        | Node::Paginate(_)
+        | Node::Snippet(_)
+        | Node::Form(_)
+        | Node::Section { .. }
+        | Node::SectionGroup { .. } => true,
+        Node::Custom(tag) => tag.body.is_some(),
+        Node::Text(_)
+        | Node::Output { .. }
+        | Node::Assign { .. }
+        | Node::Capture { .. }
+        | Node::Increment(_)
+        | Node::Decrement(_)
+        | Node::Cycle { .. }
+        | Node::Break
+        | Node::Continue => false,
+    }
+}
+
+impl LiquidEngine {
+    /// Renders `template`, passing each chunk to `on_chunk` and waiting for
+    /// it before rendering on. Empty chunks are skipped.
+    pub async fn render_streaming<F, Fut>(
+        &self,
+        template: &Template,
+        variables: serde_json::Map<String, serde_json::Value>,
+        mut on_chunk: F,
+    ) -> Result<(), LiquidError>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Result<(), LiquidError>>,
+    {
+        let mut context = self.page_context(variables);
+        let mut chunk = String::new();
+        for node in &template.nodes {
+            self.render_nodes(std::slice::from_ref(node), &mut context, &mut chunk)
+                .await?;
+            if ends_chunk(node) && !chunk.is_empty() {
+                on_chunk(std::mem::take(&mut chunk)).await?;
+            }
+            // A `break` outside any loop ends the template.
+            if context.interrupted() {
+                break;
+            }
+        }
+        if !chunk.is_empty() {
+            on_chunk(chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn stream_to(
+        &self,
+        template: &Template,
+        data: JsValue,
+        on_chunk: &js_sys::Function,
+    ) -> Result<(), JsValue> {
+        let variables = template_variables(data)?;
+        self.render_streaming(template, variables, |chunk| async move {
+            let returned = on_chunk
+                .call1(&JsValue::NULL, &JsValue::from_str(&chunk))
+                .map_err(|error| LiquidError::new(js_error(error)))?;
+            JsFuture::from(js_sys::Promise::resolve(&returned))
+                .await
+                .map_err(|error| LiquidError::new(js_error(error)))?;
+            Ok(())
+        })
+        .await?;
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Renders `source` like `render`, calling `on_chunk(html)` with each
+    /// chunk as it completes instead of returning the page. A promise
+    /// returned by `on_chunk` is awaited before rendering continues, so a
+    /// slow client holds rendering back rather than buffering the page.
+    #[wasm_bindgen]
+    pub async fn render_stream(
+        &self,
+        source: String,
+        #[wasm_bindgen(unchecked_param_type = "TemplateData | null | undefined")] data: JsValue,
+        #[wasm_bindgen(unchecked_param_type = "(html: string) => void | Promise<void>")]
+        on_chunk: js_sys::Function,
+    ) -> Result<(), JsValue> {
+        let template = self.parse_template(&source)?;
+        self.stream_to(&template, data, &on_chunk).await
+    }
+
+    /// `render_stream` for a compiled template.
+    #[wasm_bindgen]
+    pub async fn render_compiled_stream(
+        &self,
+        template: &CompiledTemplate,
+        #[wasm_bindgen(unchecked_param_type = "TemplateData | null | undefined")] data: JsValue,
+        #[wasm_bindgen(unchecked_param_type = "(html: string) => void | Promise<void>")]
+        on_chunk: js_sys::Function,
+    ) -> Result<(), JsValue> {
+        self.stream_to(template.template(), data, &on_chunk).await
+    }
+}
@@ -0,0 +1,317 @@
+//! Online Store 2.0 pages: JSON templates, section groups and layouts.
+//!
+//! A JSON template (`templates/product.json`) lists section instances
+//! under `sections` and renders them in `order`, skipping any marked
+//! `disabled`; each names its section `type` and carries the settings and
+//! blocks `render_section` takes. The result, inside the template's
+//! `wrapper` if it has one, becomes `content_for_layout` of the layout
+//! (`layout/theme.liquid` unless the template names another, or none with
+//! `"layout": false`). Section groups (`sections/header-group.json`) have
+//! the same shape and render where a layout says `{% sections
+//! 'header-group' %}`.
+//!
+//! Files may be passed as parsed objects or as their text, including the
+//! `/* ... */` comment Shopify puts at the top of generated files.
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::engine::{template_variables, LiquidEngine};
+use super::error::{LiquidError, Position};
+use super::filters::escape_html;
+use super::parser::Template;
+use super::render::Context;
+use super::sections::SectionData;
+use crate::from_js;
+
+/// Elements a JSON template's `wrapper` may be.
+const WRAPPER_TAGS: &[&str] = &["div", "main", "section"];
+
+/// One section of a JSON template or section group.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SectionEntry {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(flatten)]
+    pub data: SectionData,
+}
+
+/// A JSON template or section group.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JsonTemplate {
+    /// The layout's name, or `false` for none. Defaults to `theme`.
+    pub layout: Option<Value>,
+    /// `div#id.class[attribute=value]`, wrapping the sections.
+    pub wrapper: Option<String>,
+    pub sections: Map<String, Value>,
+    /// Section IDs in render order. Defaults to every section, by ID.
+    pub order: Vec<String>,
+}
+
+impl JsonTemplate {
+    /// Reads a JSON template or section group file, given as its parsed
+    /// JSON or its text.
+    pub fn parse(file: Value) -> Result<JsonTemplate, String> {
+        let file = match file {
+            Value::String(text) => {
+                let text = text.trim_start();
+                let text = match text.strip_prefix("/*") {
+                    Some(rest) => rest
+                        .split_once("*/")
+                        .map(|(_, json)| json)
+                        .ok_or("Unclosed comment at the top of the file")?,
+                    None => text,
+                };
+                serde_json::from_str(text).map_err(|error| error.to_string())?
+            }
+            file => file,
+        };
+        serde_json::from_value(file).map_err(|error| error.to_string())
+    }
+
+    /// The sections to render, in order.
+    fn entries(&self) -> Result<Vec<(String, SectionEntry)>, String> {
+        let order: Vec<&String> = if self.order.is_empty() {
+            self.sections.keys().collect()
+        } else {
+            self.order.iter().collect()
+        };
+        let mut entries = Vec::new();
+        for id in order {
+            let entry = self
+                .sections
+                .get(id)
+                .ok_or_else(|| format!("order names a missing section: {id}"))?;
+            let mut entry: SectionEntry = serde_json::from_value(entry.clone())
+                .map_err(|error| format!("Section {id}: {error}"))?;
+            if !entry.disabled {
+                entry.data.id.get_or_insert_with(|| id.clone());
+                entries.push((id.clone(), entry));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// The layout to render in, if any.
+    fn layout(&self) -> Result<Option<&str>, String> {
+        match &self.layout {
+            None | Some(Value::Null) => Ok(Some("theme")),
+            Some(Value::String(name)) => Ok(Some(name)),
+            Some(Value::Bool(false)) => Ok(None),
+            Some(other) => Err(format!("layout must be a name or false, not {other}")),
+        }
+    }
+}
+
+/// Opening and closing tags for a `wrapper` such as
+/// `main#product.page[data-handle=tee]`.
+fn wrapper_tags(wrapper: &str) -> Result<(String, String), String> {
+    let end = wrapper.find(['#', '.', '[']).unwrap_or(wrapper.len());
+    let tag = &wrapper[..end];
+    if !WRAPPER_TAGS.contains(&tag) {
+        return Err(format!(
+            "wrapper must be a div, main or section element, not '{tag}'"
+        ));
+    }
+    let mut id = None;
+    let mut classes = Vec::new();
+    let mut attributes = Vec::new();
+    let mut rest = &wrapper[end..];
+    while let Some(marker) = rest.chars().next() {
+        rest = &rest[1..];
+        let end = match marker {
+            '[' => rest.find(']').ok_or("Unclosed '[' in wrapper")?,
+            _ => rest.find(['#', '.', '[']).unwrap_or(rest.len()),
+        };
+        let part = &rest[..end];
+        match marker {
+            '#' => id = Some(part),
+            '.' => classes.push(part),
+            _ => attributes.push(part.split_once('=').unwrap_or((part, ""))),
+        }
+        rest = &rest[end..];
+        if marker == '[' {
+            rest = &rest[1..];
+        }
+    }
+    let mut open = format!("<{tag}");
+    if let Some(id) = id {
+        open.push_str(&format!(" id=\"{}\"", escape_html(id)));
+    }
+    if !classes.is_empty() {
+        open.push_str(&format!(" class=\"{}\"", escape_html(&classes.join(" "))));
+    }
+    for (name, value) in attributes {
+        open.push_str(&format!(
+            " {}=\"{}\"",
+            name.trim(),
+            escape_html(value.trim())
+        ));
+    }
+    open.push('>');
+    Ok((open, format!("</{tag}>")))
+}
+
+/// Registered layouts and section groups.
+#[derive(Debug, Clone, Default)]
+pub struct Theme {
+    layouts: BTreeMap<String, Rc<Template>>,
+    groups: BTreeMap<String, Rc<JsonTemplate>>,
+}
+
+impl LiquidEngine {
+    /// Parses `source` and registers it as layout `name`.
+    pub fn define_layout(&self, name: &str, source: &str) -> Result<(), LiquidError> {
+        let template = self
+            .parse_template(source)
+            .map_err(|error| error.in_layout(name))?;
+        self.theme
+            .borrow_mut()
+            .layouts
+            .insert(name.to_string(), Rc::new(template));
+        Ok(())
+    }
+
+    /// Registers section group `name`.
+    pub fn define_section_group(&self, name: &str, group: JsonTemplate) {
+        self.theme
+            .borrow_mut()
+            .groups
+            .insert(name.to_string(), Rc::new(group));
+    }
+
+    /// Renders the sections of a JSON template or section group.
+    async fn render_sections(
+        &self,
+        template: &JsonTemplate,
+        context: &mut Context,
+        output: &mut String,
+        position: Option<Position>,
+    ) -> Result<(), LiquidError> {
+        let fail = |message: String| match position {
+            Some(position) => LiquidError::at(message, position),
+            None => LiquidError::new(message),
+        };
+        for (_, entry) in template.entries().map_err(fail)? {
+            self.render_section_into(&entry.kind, entry.data, context, output, position)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// `sections 'name'`.
+    pub(crate) async fn render_section_group(
+        &self,
+        name: &str,
+        context: &mut Context,
+        output: &mut String,
+        position: Position,
+    ) -> Result<(), LiquidError> {
+        let group = self
+            .theme
+            .borrow()
+            .groups
+            .get(name)
+            .cloned()
+            .ok_or_else(|| LiquidError::at(format!("Unknown section group '{name}'"), position))?;
+        self.render_sections(&group, context, output, Some(position))
+            .await
+    }
+
+    /// Renders a page from a JSON template: its sections, in its layout.
+    pub async fn render_json_template_with(
+        &self,
+        template: &JsonTemplate,
+        variables: Map<String, Value>,
+    ) -> Result<String, LiquidError> {
+        let mut context = self.page_context(variables);
+        let mut content = String::new();
+        self.render_sections(template, &mut context, &mut content, None)
+            .await?;
+        if let Some(wrapper) = &template.wrapper {
+            let (open, close) = wrapper_tags(wrapper).map_err(LiquidError::new)?;
+            content = format!("{open}{content}{close}");
+        }
+
+        let layout = match template.layout().map_err(LiquidError::new)? {
+            Some(name) => {
+                let layout = self.theme.borrow().layouts.get(name).cloned();
+                // Without a registered `theme` layout, the page is just
+                // its content; a layout the template names must exist.
+                if layout.is_none() && template.layout.as_ref().is_some_and(Value::is_string) {
+                    return Err(LiquidError::new(format!("Unknown layout '{name}'")));
+                }
+                layout.map(|layout| (name.to_string(), layout))
+            }
+            None => None,
+        };
+        let Some((name, layout)) = layout else {
+            return Ok(content);
+        };
+        context.assign("content_for_layout", Value::String(content));
+        let mut output = String::new();
+        self.render_nodes(&layout.nodes, &mut context, &mut output)
+            .await
+            .map_err(|error| error.in_layout(&name))?;
+        Ok(output)
+    }
+}
+
+#[wasm_bindgen]
+impl LiquidEngine {
+    /// Registers a layout (`layout/theme.liquid` as `"theme"`), which
+    /// renders the page's sections where it outputs
+    /// `{{ content_for_layout }}`.
+    #[wasm_bindgen]
+    pub fn register_layout(&self, name: &str, source: &str) -> Result<(), JsValue> {
+        Ok(self.define_layout(name, source)?)
+    }
+
+    /// Removes a layout, returning whether it was registered.
+    #[wasm_bindgen]
+    pub fn remove_layout(&self, name: &str) -> bool {
+        self.theme.borrow_mut().layouts.remove(name).is_some()
+    }
+
+    /// Registers a section group file (`sections/header-group.json` as
+    /// `"header-group"`) for `{% sections 'header-group' %}`.
+    #[wasm_bindgen]
+    pub fn register_section_group(
+        &self,
+        name: &str,
+        #[wasm_bindgen(unchecked_param_type = "JsonTemplate | string")] group: JsValue,
+    ) -> Result<(), JsValue> {
+        let group = JsonTemplate::parse(from_js(group)?)
+            .map_err(|error| JsValue::from_str(&format!("Section group {name}: {error}")))?;
+        self.define_section_group(name, group);
+        Ok(())
+    }
+
+    /// Removes a section group, returning whether it was registered.
+    #[wasm_bindgen]
+    pub fn remove_section_group(&self, name: &str) -> bool {
+        self.theme.borrow_mut().groups.remove(name).is_some()
+    }
+
+    /// Renders a whole page from a JSON template (`templates/product.json`)
+    /// with the page's objects in `data`: each section in order, in the
+    /// template's layout.
+    #[wasm_bindgen]
+    pub async fn render_json_template(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "JsonTemplate | string")] template: JsValue,
+        #[wasm_bindgen(unchecked_optional_param_type = "TemplateData")] data: JsValue,
+    ) -> Result<String, JsValue> {
+        let template =
+            JsonTemplate::parse(from_js(template)?).map_err(|error| JsValue::from_str(&error))?;
+        let variables = template_variables(data)?;
+        Ok(self.render_json_template_with(&template, variables).await?)
+    }
+}
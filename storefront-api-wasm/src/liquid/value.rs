@@ -0,0 +1,161 @@
+//! Liquid semantics for JSON values: truthiness, output, lookups and
+//! comparisons.
+
+use std::cmp::Ordering;
+
+use serde_json::Value;
+
+/// Only `false` and `nil` are falsy; `0` and `""` are truthy.
+pub fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// Key under which a drop keeps how it's written out, as a metafield does.
+pub const TEXT_KEY: &str = "to_s";
+
+/// How a value is written into the output. Arrays are concatenated, as in
+/// Liquid; objects are written as JSON, unless they carry their text under
+/// `TEXT_KEY`.
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Object(object) if object.get(TEXT_KEY).is_some_and(Value::is_string) => {
+            to_text(&object[TEXT_KEY])
+        }
+        Value::Array(items) => items.iter().map(to_text).collect(),
+        other => other.to_string(),
+    }
+}
+
+/// `value.key`. Arrays and strings answer `size`, and arrays `first` and
+/// `last`; objects answer `size` unless they have a key of that name.
+pub fn property(value: &Value, key: &str) -> Value {
+    find_property(value, key).unwrap_or(Value::Null)
+}
+
+/// Like `property`, but `None` when `value` has no such property, as
+/// opposed to one that is `nil`.
+pub fn find_property(value: &Value, key: &str) -> Option<Value> {
+    match (value, key) {
+        (Value::Object(object), _) => match object.get(key) {
+            Some(found) => Some(found.clone()),
+            None if key == "size" => Some(Value::from(object.len())),
+            None => None,
+        },
+        (Value::Array(items), "size") => Some(Value::from(items.len())),
+        (Value::Array(items), "first") => Some(items.first().cloned().unwrap_or(Value::Null)),
+        (Value::Array(items), "last") => Some(items.last().cloned().unwrap_or(Value::Null)),
+        (Value::String(text), "size") => Some(Value::from(text.chars().count())),
+        _ => None,
+    }
+}
+
+/// `value[key]`: array positions (negative counts from the end) or
+/// properties.
+pub fn index(value: &Value, key: &Value) -> Value {
+    find_index(value, key).unwrap_or(Value::Null)
+}
+
+/// Like `index`, but `None` when there is nothing at `key`.
+pub fn find_index(value: &Value, key: &Value) -> Option<Value> {
+    match (value, key) {
+        (Value::Array(items), Value::Number(number)) => {
+            let position = number.as_i64()?;
+            let position = if position < 0 {
+                items.len() as i64 + position
+            } else {
+                position
+            };
+            usize::try_from(position)
+                .ok()
+                .and_then(|position| items.get(position))
+                .cloned()
+        }
+        (_, Value::String(key)) => find_property(value, key),
+        _ => None,
+    }
+}
+
+pub fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(object) => object.is_empty(),
+        _ => false,
+    }
+}
+
+pub fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(text) => text.trim().is_empty(),
+        other => is_empty(other),
+    }
+}
+
+/// `==`, comparing numbers by value so `1 == 1.0`.
+pub fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        _ => left == right,
+    }
+}
+
+/// `<`, `>` and friends. Only numbers and strings are ordered.
+pub fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+/// `contains`: a substring, an array element or an object key.
+pub fn contains(haystack: &Value, needle: &Value) -> bool {
+    match haystack {
+        Value::String(text) => text.contains(&to_text(needle)),
+        Value::Array(items) => items.iter().any(|item| equals(item, needle)),
+        Value::Object(object) => needle.as_str().is_some_and(|key| object.contains_key(key)),
+        _ => false,
+    }
+}
+
+/// The items a `for` loop walks: array elements, `[key, value]` pairs of an
+/// object, nothing for `nil` and the value itself otherwise.
+pub fn iterate(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items,
+        Value::Object(object) => object
+            .into_iter()
+            .map(|(key, value)| Value::Array(vec![Value::String(key), value]))
+            .collect(),
+        Value::Null => Vec::new(),
+        other => vec![other],
+    }
+}
+
+/// A number, or a string holding one, as Liquid coerces filter arguments.
+pub fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Like `as_number` for whole numbers, truncating decimals.
+pub fn as_integer(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(number) => number
+            .as_i64()
+            .or_else(|| number.as_f64().map(|n| n as i64)),
+        Value::String(text) => {
+            let text = text.trim();
+            text.parse()
+                .ok()
+                .or_else(|| text.parse::<f64>().ok().map(|n| n as i64))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,63 @@
+//! Localization (countries, languages and markets) queries.
+
+use wasm_bindgen::prelude::*;
+
+use crate::types::Localization;
+use crate::{from_json, to_js, StorefrontApi};
+
+/// Selection for a `Country`.
+const COUNTRY_FIELDS: &str = r#"
+    isoCode
+    name
+    unitSystem
+    currency { isoCode name symbol }
+    availableLanguages { isoCode endonymName name }
+    market { id handle }
+"#;
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Returns `{ availableCountries, availableLanguages, country, language,
+    /// market }` for country/currency and language selectors. `country` and
+    /// `language` reflect the client's `@inContext` settings.
+    #[wasm_bindgen(unchecked_return_type = "Localization")]
+    pub async fn get_localization(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.fetch_localization().await?)
+    }
+}
+
+impl StorefrontApi {
+    pub(crate) async fn fetch_localization(&self) -> Result<Localization, JsValue> {
+        let query = format!(
+            r#"
+            query getLocalization {{
+                localization {{
+                    availableCountries {{
+                        {COUNTRY_FIELDS}
+                    }}
+                    availableLanguages {{
+                        isoCode
+                        endonymName
+                        name
+                    }}
+                    country {{
+                        {COUNTRY_FIELDS}
+                    }}
+                    language {{
+                        isoCode
+                        endonymName
+                        name
+                    }}
+                    market {{
+                        id
+                        handle
+                    }}
+                }}
+            }}
+        "#
+        );
+
+        let mut data = self.execute(&query, serde_json::Value::Null).await?;
+        from_json(data["localization"].take())
+    }
+}
@@ -0,0 +1,37 @@
+//! Structured log events from the client, for hosts that want request
+//! traces somewhere other than the browser console.
+
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Syslog severities (RFC 5424), as used by MCP's `logging/setLevel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+/// Receives `(level, logger, data)` for every event the client logs.
+pub type LogSink = Rc<dyn Fn(LogLevel, &str, Value)>;
+
+/// The operation name in `query`, e.g. `getProductsByHandles`.
+pub(crate) fn operation_name(query: &str) -> Option<&str> {
+    let trimmed = query.trim_start();
+    let rest = ["query", "mutation"]
+        .into_iter()
+        .find_map(|keyword| trimmed.strip_prefix(keyword))?;
+    let name = rest
+        .trim_start()
+        .split(|c: char| !(c == '_' || c.is_ascii_alphanumeric()))
+        .next()?;
+    (!name.is_empty()).then_some(name)
+}
@@ -0,0 +1,320 @@
+//! Cross-market price lookups and market suggestions.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::types::{Localization, ProductPriceRange};
+use crate::{from_json, to_js, StorefrontApi};
+
+/// Most countries `get_prices_in_markets` accepts in one call.
+const MAX_MARKET_COUNTRIES: usize = 20;
+
+/// Common IANA time zones and the country they imply, for when the browser
+/// locale carries no region (e.g. `de` rather than `de-AT`).
+const TIME_ZONE_COUNTRIES: &[(&str, &str)] = &[
+    ("America/New_York", "US"),
+    ("America/Chicago", "US"),
+    ("America/Denver", "US"),
+    ("America/Phoenix", "US"),
+    ("America/Los_Angeles", "US"),
+    ("America/Anchorage", "US"),
+    ("Pacific/Honolulu", "US"),
+    ("America/Toronto", "CA"),
+    ("America/Vancouver", "CA"),
+    ("America/Edmonton", "CA"),
+    ("America/Winnipeg", "CA"),
+    ("America/Halifax", "CA"),
+    ("America/Mexico_City", "MX"),
+    ("America/Sao_Paulo", "BR"),
+    ("America/Argentina/Buenos_Aires", "AR"),
+    ("America/Santiago", "CL"),
+    ("America/Bogota", "CO"),
+    ("America/Lima", "PE"),
+    ("Europe/London", "GB"),
+    ("Europe/Dublin", "IE"),
+    ("Europe/Lisbon", "PT"),
+    ("Europe/Madrid", "ES"),
+    ("Europe/Paris", "FR"),
+    ("Europe/Brussels", "BE"),
+    ("Europe/Amsterdam", "NL"),
+    ("Europe/Luxembourg", "LU"),
+    ("Europe/Berlin", "DE"),
+    ("Europe/Zurich", "CH"),
+    ("Europe/Vienna", "AT"),
+    ("Europe/Rome", "IT"),
+    ("Europe/Copenhagen", "DK"),
+    ("Europe/Oslo", "NO"),
+    ("Europe/Stockholm", "SE"),
+    ("Europe/Helsinki", "FI"),
+    ("Europe/Warsaw", "PL"),
+    ("Europe/Prague", "CZ"),
+    ("Europe/Budapest", "HU"),
+    ("Europe/Athens", "GR"),
+    ("Europe/Istanbul", "TR"),
+    ("Asia/Dubai", "AE"),
+    ("Asia/Kolkata", "IN"),
+    ("Asia/Singapore", "SG"),
+    ("Asia/Hong_Kong", "HK"),
+    ("Asia/Shanghai", "CN"),
+    ("Asia/Seoul", "KR"),
+    ("Asia/Tokyo", "JP"),
+    ("Australia/Sydney", "AU"),
+    ("Australia/Melbourne", "AU"),
+    ("Australia/Brisbane", "AU"),
+    ("Australia/Perth", "AU"),
+    ("Pacific/Auckland", "NZ"),
+    ("Africa/Johannesburg", "ZA"),
+];
+
+/// Where a market suggestion's country came from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SuggestionSource {
+    Locale,
+    TimeZone,
+}
+
+/// A country and language to offer the visitor, e.g. for a "Looks like
+/// you're in Germany" banner.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSuggestion {
+    /// `CountryCode` to pass to `set_country`.
+    pub country: String,
+    pub country_name: String,
+    /// `LanguageCode` to pass to `set_language`, when the visitor's
+    /// language is available in that country.
+    pub language: Option<String>,
+    pub currency_code: String,
+    pub source: SuggestionSource,
+    /// False when the suggestion matches the current country and language,
+    /// so no banner is needed.
+    pub differs: bool,
+}
+
+/// Matches browser locales (most preferred first, e.g. `["de-AT", "en"]`)
+/// and an IANA time zone against the countries and languages the shop sells
+/// in. The first locale with an available region wins, then the time zone.
+pub fn suggest_market(
+    localization: &Localization,
+    locales: &[String],
+    time_zone: Option<&str>,
+) -> Option<MarketSuggestion> {
+    let locales: Vec<(String, Option<String>)> = locales
+        .iter()
+        .map(|locale| {
+            let mut parts = locale.split(['-', '_']);
+            let language = parts.next().unwrap_or_default().to_uppercase();
+            let region = parts
+                .find(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_alphabetic()))
+                .map(|part| part.to_uppercase());
+            (language, region)
+        })
+        .filter(|(language, _)| !language.is_empty())
+        .collect();
+
+    let find_country = |code: &str| {
+        localization
+            .available_countries
+            .iter()
+            .find(|country| country.iso_code == code)
+    };
+    let (country, source) = locales
+        .iter()
+        .filter_map(|(_, region)| region.as_deref().and_then(find_country))
+        .map(|country| (country, SuggestionSource::Locale))
+        .next()
+        .or_else(|| {
+            let time_zone = time_zone?;
+            TIME_ZONE_COUNTRIES
+                .iter()
+                .find(|(zone, _)| *zone == time_zone)
+                .and_then(|(_, code)| find_country(code))
+                .map(|country| (country, SuggestionSource::TimeZone))
+        })?;
+
+    // Prefer an exact regional language (PT_BR), then the base language.
+    let languages = if country.available_languages.is_empty() {
+        &localization.available_languages
+    } else {
+        &country.available_languages
+    };
+    let language = locales.iter().find_map(|(language, region)| {
+        let regional = region.as_ref().map(|region| format!("{language}_{region}"));
+        languages
+            .iter()
+            .find(|available| Some(&available.iso_code) == regional.as_ref())
+            .or_else(|| {
+                languages
+                    .iter()
+                    .find(|available| &available.iso_code == language)
+            })
+            .map(|available| available.iso_code.clone())
+    });
+
+    let differs = country.iso_code != localization.country.iso_code
+        || language
+            .as_ref()
+            .is_some_and(|language| *language != localization.language.iso_code);
+    Some(MarketSuggestion {
+        country: country.iso_code.clone(),
+        country_name: country.name.clone(),
+        language,
+        currency_code: country.currency.iso_code.clone(),
+        source,
+        differs,
+    })
+}
+
+/// `navigator.languages` (or `navigator.language`) from the global scope,
+/// empty outside browsers and workers.
+fn browser_locales() -> Vec<String> {
+    let Ok(navigator) = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("navigator"))
+    else {
+        return Vec::new();
+    };
+    if navigator.is_undefined() {
+        return Vec::new();
+    }
+    if let Ok(languages) = js_sys::Reflect::get(&navigator, &JsValue::from_str("languages")) {
+        if js_sys::Array::is_array(&languages) {
+            let languages: Vec<String> = js_sys::Array::from(&languages)
+                .iter()
+                .filter_map(|language| language.as_string())
+                .collect();
+            if !languages.is_empty() {
+                return languages;
+            }
+        }
+    }
+    js_sys::Reflect::get(&navigator, &JsValue::from_str("language"))
+        .ok()
+        .and_then(|language| language.as_string())
+        .into_iter()
+        .collect()
+}
+
+/// The runtime's IANA time zone, from `Intl.DateTimeFormat`.
+fn browser_time_zone() -> Option<String> {
+    let format = js_sys::Intl::DateTimeFormat::new(&js_sys::Array::new(), &js_sys::Object::new());
+    js_sys::Reflect::get(&format.resolved_options(), &JsValue::from_str("timeZone"))
+        .ok()?
+        .as_string()
+}
+
+/// A product's prices as presented in one country.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketPrice {
+    /// `CountryCode` the prices were fetched for.
+    pub country: String,
+    /// `null` when the product isn't published to that country's market.
+    pub price_range: Option<ProductPriceRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compare_at_price_range: Option<ProductPriceRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_for_sale: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PricedProduct {
+    price_range: ProductPriceRange,
+    #[serde(default)]
+    compare_at_price_range: Option<ProductPriceRange>,
+    #[serde(default)]
+    available_for_sale: Option<bool>,
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches a product's price range in each of `countries` (e.g.
+    /// `["US", "CA", "DE"]`) and returns `[{ country, priceRange,
+    /// compareAtPriceRange?, availableForSale? }]` in the same order.
+    ///
+    /// `@inContext` applies to a whole operation, so prices for different
+    /// countries can't be aliased into one query; this sends one small
+    /// query per country.
+    #[wasm_bindgen(unchecked_return_type = "MarketPrice[]")]
+    pub async fn get_prices_in_markets(
+        &self,
+        handle: String,
+        countries: Vec<String>,
+    ) -> Result<JsValue, JsValue> {
+        if countries.len() > MAX_MARKET_COUNTRIES {
+            return Err(JsValue::from_str(&format!(
+                "At most {MAX_MARKET_COUNTRIES} countries can be compared at once, got {}",
+                countries.len()
+            )));
+        }
+
+        let query = r#"
+            query getMarketPrice($handle: String!) {
+                product(handle: $handle) {
+                    availableForSale
+                    priceRange {
+                        minVariantPrice { amount currencyCode }
+                        maxVariantPrice { amount currencyCode }
+                    }
+                    compareAtPriceRange {
+                        minVariantPrice { amount currencyCode }
+                        maxVariantPrice { amount currencyCode }
+                    }
+                }
+            }
+        "#;
+
+        let mut prices = Vec::with_capacity(countries.len());
+        for country in countries {
+            let client = self.with_context(Some(country), None)?;
+            let country = client
+                .context
+                .borrow()
+                .country
+                .clone()
+                .ok_or_else(|| JsValue::from_str("Country code must not be empty"))?;
+
+            let variables = serde_json::json!({ "handle": handle });
+            let mut data = client.execute(query, variables).await?;
+            let product: Option<PricedProduct> = from_json(data["product"].take())?;
+            prices.push(match product {
+                Some(product) => MarketPrice {
+                    country,
+                    price_range: Some(product.price_range),
+                    compare_at_price_range: product.compare_at_price_range,
+                    available_for_sale: product.available_for_sale,
+                },
+                None => MarketPrice {
+                    country,
+                    price_range: None,
+                    compare_at_price_range: None,
+                    available_for_sale: None,
+                },
+            });
+        }
+
+        to_js(&prices)
+    }
+
+    /// Suggests a country and language for the visitor from their locales
+    /// and time zone: `{ country, countryName, language, currencyCode,
+    /// source, differs }`, or `null` when nothing matches an available
+    /// country. Reads `navigator.languages` and the `Intl` time zone unless
+    /// `locales` / `time_zone` are given (e.g. from `Accept-Language` when
+    /// rendering on the server).
+    #[wasm_bindgen(unchecked_return_type = "MarketSuggestion | null")]
+    pub async fn suggest_market(
+        &self,
+        locales: Option<Vec<String>>,
+        time_zone: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        let locales = locales.unwrap_or_else(browser_locales);
+        let time_zone = time_zone.or_else(browser_time_zone);
+        let localization = self.fetch_localization().await?;
+        to_js(&suggest_market(
+            &localization,
+            &locales,
+            time_zone.as_deref(),
+        ))
+    }
+}
@@ -0,0 +1,28 @@
+//! Model Context Protocol server exposing the Storefront client to AI agents.
+//!
+//! `McpServer` speaks JSON-RPC 2.0: feed it each incoming message with
+//! `handle` and write back whatever it returns. Server-initiated messages
+//! (notifications) go to the callback registered with `set_sender`.
+
+pub mod audit;
+pub mod cancellation;
+#[cfg(feature = "cart")]
+pub mod cart;
+pub mod catalog;
+pub mod completion;
+pub mod config;
+#[cfg(feature = "customer")]
+pub mod customer;
+pub mod host_tools;
+pub mod limits;
+pub mod logging;
+pub mod operations;
+pub mod prompts;
+pub mod protocol;
+pub mod resources;
+pub mod sampling;
+pub mod server;
+pub mod stdio;
+pub mod tools;
+
+pub use server::McpServer;
@@ -0,0 +1,231 @@
+//! An audit log of tool calls.
+//!
+//! Every `tools/call` is recorded with its (redacted) arguments, how long
+//! it took and how it ended. The most recent entries are kept in memory,
+//! readable from JavaScript with `audit_log()` and by the client as the
+//! `audit://tool-calls` resource; a sink registered with `set_audit_sink`
+//! receives each entry as it is recorded, for hosts that keep a permanent
+//! record.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use super::protocol::RpcError;
+use super::resources::Resource;
+use super::server::McpServer;
+use super::tools::{Content, ToolResult};
+use crate::to_js;
+
+/// URI of the audit log resource.
+pub const AUDIT_LOG_URI: &str = "audit://tool-calls";
+
+/// Entries kept when the host doesn't choose a size.
+pub const DEFAULT_AUDIT_LOG_SIZE: u32 = 200;
+
+/// How a call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditStatus {
+    Ok,
+    /// The tool ran and failed (an `isError` result).
+    Error,
+    /// The call was refused before the tool ran: an unknown tool, read-only
+    /// mode or a rate limit.
+    Rejected,
+    /// The client cancelled the call.
+    Cancelled,
+}
+
+/// One recorded tool call.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    /// When the call started, as an ISO 8601 timestamp.
+    pub started_at: String,
+    /// The name the client called, including any `store.` prefix.
+    pub tool: String,
+    pub arguments: Value,
+    pub duration_ms: f64,
+    pub status: AuditStatus,
+    /// The error class (e.g. `"graphql_error"`, `"rate_limited"`) of
+    /// failed calls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The most recent entries, oldest first.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    entries: VecDeque<AuditEntry>,
+    capacity: usize,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        AuditLog {
+            entries: VecDeque::new(),
+            capacity: DEFAULT_AUDIT_LOG_SIZE as usize,
+        }
+    }
+}
+
+impl AuditLog {
+    pub fn enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn push(&mut self, entry: AuditEntry) {
+        if !self.enabled() {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// `arguments` with the values of secret-looking keys (access tokens,
+/// passwords and the like) and email addresses replaced, at any depth.
+pub fn redact(arguments: &Value) -> Value {
+    crate::redact::redacted(arguments, &[])
+}
+
+fn result_text(result: &ToolResult) -> Option<String> {
+    result.content.first().map(|content| match content {
+        Content::Text { text } => text.clone(),
+    })
+}
+
+impl McpServer {
+    /// Whether calls are recorded, in memory or by a sink.
+    pub(crate) fn auditing(&self) -> bool {
+        self.audit.borrow().enabled() || self.audit_sink.borrow().is_some()
+    }
+
+    /// Records a finished call. `arguments` should already be redacted.
+    pub(crate) fn audit_call(
+        &self,
+        tool: &str,
+        arguments: Value,
+        started: f64,
+        cancelled: bool,
+        outcome: &Result<ToolResult, RpcError>,
+    ) {
+        let (status, error, message) = match outcome {
+            _ if cancelled => (AuditStatus::Cancelled, None, None),
+            Ok(result) if !result.is_error => (AuditStatus::Ok, None, None),
+            Ok(result) => {
+                let class = result
+                    .structured_content
+                    .as_ref()
+                    .and_then(|content| content["error"].as_str())
+                    .map(str::to_string);
+                let status = if class.as_deref() == Some("rate_limited") {
+                    AuditStatus::Rejected
+                } else {
+                    AuditStatus::Error
+                };
+                (status, class, result_text(result))
+            }
+            Err(error) => (
+                AuditStatus::Rejected,
+                Some("invalid_params".to_string()),
+                Some(error.message.clone()),
+            ),
+        };
+        let entry = AuditEntry {
+            started_at: String::from(
+                js_sys::Date::new(&JsValue::from_f64(started)).to_iso_string(),
+            ),
+            tool: tool.to_string(),
+            arguments,
+            duration_ms: js_sys::Date::now() - started,
+            status,
+            error,
+            message,
+        };
+
+        let sink = self.audit_sink.borrow().clone();
+        if let Some(sink) = sink {
+            if let Ok(value) = to_js(&entry) {
+                let _ = sink.call1(&JsValue::NULL, &value);
+            }
+        }
+        self.audit.borrow_mut().push(entry);
+    }
+
+    /// The audit log resource's text: `{ "entries": [...] }`, oldest first.
+    pub(crate) fn audit_log_text(&self) -> String {
+        let audit = self.audit.borrow();
+        let entries: Vec<&AuditEntry> = audit.entries().collect();
+        serde_json::to_string_pretty(&serde_json::json!({ "entries": entries })).unwrap_or_default()
+    }
+
+    /// The `resources/list` entry for the audit log, when it is kept.
+    pub(crate) fn audit_resource(&self) -> Option<Resource> {
+        self.audit.borrow().enabled().then(|| Resource {
+            uri: AUDIT_LOG_URI.to_string(),
+            name: "tool-calls".to_string(),
+            title: "Audit log: tool calls".to_string(),
+            mime_type: "application/json",
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl McpServer {
+    /// The recorded tool calls, oldest first: `[{ startedAt, tool,
+    /// arguments, durationMs, status, error?, message? }]`, where `status`
+    /// is `"ok"`, `"error"`, `"rejected"` or `"cancelled"`. Secret-looking
+    /// arguments (tokens, passwords) are redacted.
+    #[wasm_bindgen(unchecked_return_type = "AuditEntry[]")]
+    pub fn audit_log(&self) -> Result<JsValue, JsValue> {
+        let audit = self.audit.borrow();
+        to_js(&audit.entries().collect::<Vec<_>>())
+    }
+
+    /// Sets how many calls the audit log keeps (default 200). `0` stops
+    /// recording and hides the audit log resource.
+    #[wasm_bindgen]
+    pub fn set_audit_log_size(&self, size: u32) {
+        self.audit.borrow_mut().set_capacity(size as usize);
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_audit_log(&self) {
+        self.audit.borrow_mut().clear();
+    }
+
+    /// Registers a function called with each audit entry as it is
+    /// recorded, e.g. to append it to a file. Pass `undefined` to remove it.
+    #[wasm_bindgen]
+    pub fn set_audit_sink(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "(entry: AuditEntry) => void")] sink: Option<
+            js_sys::Function,
+        >,
+    ) {
+        *self.audit_sink.borrow_mut() = sink;
+    }
+}
@@ -0,0 +1,60 @@
+//! Request cancellation (`notifications/cancelled`).
+//!
+//! Each `tools/call` gets an `AbortController` whose signal is attached to
+//! every Storefront request the tool makes, so cancelling a call aborts its
+//! fetches instead of letting them run on and spend the session's budget.
+
+use serde::Deserialize;
+use serde_json::Value;
+use web_sys::{AbortController, AbortSignal};
+
+use super::server::McpServer;
+use crate::logging::LogLevel;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelledParams {
+    request_id: Value,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+impl McpServer {
+    /// Tracks request `id` as cancellable and returns the signal its work
+    /// should honor. `None` where `AbortController` isn't available.
+    pub(crate) fn begin_cancellable(&self, id: &Value) -> Option<AbortSignal> {
+        let controller = AbortController::new().ok()?;
+        let signal = controller.signal();
+        self.in_flight
+            .borrow_mut()
+            .insert(id.to_string(), controller);
+        Some(signal)
+    }
+
+    /// Stops tracking request `id`. Returns whether it was cancelled, in
+    /// which case no response should be sent.
+    pub(crate) fn finish_request(&self, id: &Value) -> bool {
+        match self.in_flight.borrow_mut().remove(&id.to_string()) {
+            Some(controller) => controller.signal().aborted(),
+            None => false,
+        }
+    }
+
+    /// Handles `notifications/cancelled`: aborts the request's fetches.
+    /// Unknown or finished requests are ignored, as the protocol allows.
+    pub(crate) fn cancel_request(&self, params: Value) {
+        let Ok(params) = serde_json::from_value::<CancelledParams>(params) else {
+            return;
+        };
+        let in_flight = self.in_flight.borrow();
+        let Some(controller) = in_flight.get(&params.request_id.to_string()) else {
+            return;
+        };
+        controller.abort();
+        self.log(
+            LogLevel::Debug,
+            "cancellation",
+            serde_json::json!({ "requestId": params.request_id, "reason": params.reason }),
+        );
+    }
+}
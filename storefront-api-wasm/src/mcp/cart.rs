@@ -0,0 +1,339 @@
+//! Cart tools. The cart being assembled is kept in the session, so an agent
+//! can add items across several calls and then hand off the checkout URL.
+
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::server::McpServer;
+use super::tools::{
+    markdown_link, markdown_table, money_schema, ToolContext, ToolDefinition, ToolError,
+    ToolFuture, ToolRegistry, ToolResult, Toolset,
+};
+use crate::cart::CartItem;
+use crate::money::DEFAULT_LOCALE;
+use crate::types::Cart;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LineArguments {
+    variant_id: String,
+    #[serde(default = "default_quantity")]
+    quantity: u32,
+}
+
+fn default_quantity() -> u32 {
+    1
+}
+
+impl From<LineArguments> for CartItem {
+    fn from(line: LineArguments) -> CartItem {
+        CartItem {
+            variant_id: line.variant_id,
+            quantity: line.quantity,
+            attributes: Vec::new(),
+            gift_card_recipient: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CreateCartArguments {
+    lines: Vec<LineArguments>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateLineArguments {
+    line_id: String,
+    quantity: u32,
+}
+
+/// The session's cart ID for the call's store, or an error telling the
+/// agent to create one.
+fn current_cart_id(server: &McpServer, context: &ToolContext) -> Result<String, ToolError> {
+    server
+        .session
+        .borrow()
+        .carts
+        .get(context.store_key())
+        .cloned()
+        .ok_or_else(|| ToolError::new("No cart yet. Call create_cart or add_to_cart first."))
+}
+
+/// JSON Schema for `cart_result`'s structured content.
+fn cart_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "cartId": { "type": "string" },
+            "checkoutUrl": { "type": "string" },
+            "totalQuantity": { "type": "integer" },
+            "total": { "type": "string", "description": "Formatted total" },
+            "totalAmount": money_schema(),
+            "lines": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "lineId": { "type": "string" },
+                        "variantId": { "type": "string" },
+                        "product": { "type": "string" },
+                        "variant": { "type": "string" },
+                        "quantity": { "type": "integer" },
+                        "price": { "type": "string" }
+                    },
+                    "required": ["lineId", "variantId", "product", "variant", "quantity", "price"]
+                }
+            }
+        },
+        "required": ["cartId", "checkoutUrl", "totalQuantity", "total", "totalAmount", "lines"]
+    })
+}
+
+/// Structured cart plus a markdown line table.
+fn cart_result(cart: &Cart) -> ToolResult {
+    let lines: Vec<Value> = cart
+        .lines
+        .nodes()
+        .map(|line| {
+            serde_json::json!({
+                "lineId": line.id,
+                "variantId": line.merchandise.id,
+                "product": line.merchandise.product.title,
+                "variant": line.merchandise.title,
+                "quantity": line.quantity,
+                "price": line.merchandise.price.format(DEFAULT_LOCALE),
+            })
+        })
+        .collect();
+    let total = cart.cost.total_amount.format(DEFAULT_LOCALE);
+
+    let markdown = if cart.lines.edges.is_empty() {
+        "The cart is empty.".to_string()
+    } else {
+        let rows = cart
+            .lines
+            .nodes()
+            .map(|line| {
+                vec![
+                    line.merchandise.product.title.clone(),
+                    line.merchandise.title.clone(),
+                    line.quantity.to_string(),
+                    line.merchandise.price.format(DEFAULT_LOCALE),
+                    line.id.clone(),
+                ]
+            })
+            .collect();
+        format!(
+            "{}\n**Total:** {total} ({} items)  \n{}\n",
+            markdown_table(&["Product", "Variant", "Qty", "Price", "Line ID"], rows),
+            cart.total_quantity,
+            markdown_link("Checkout", Some(&cart.checkout_url))
+        )
+    };
+
+    ToolResult::structured(
+        serde_json::json!({
+            "cartId": cart.id,
+            "checkoutUrl": cart.checkout_url,
+            "totalQuantity": cart.total_quantity,
+            "total": total,
+            "totalAmount": cart.cost.total_amount,
+            "lines": lines,
+        }),
+        markdown,
+    )
+}
+
+/// `create_cart`: starts a new cart for the session, optionally with lines.
+fn create_cart(server: &McpServer, context: ToolContext, arguments: Value) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: CreateCartArguments = serde_json::from_value(arguments)?;
+        let items: Vec<CartItem> = arguments.lines.into_iter().map(CartItem::from).collect();
+        let cart = context.api(server)?.cart_create(&items).await?;
+        server
+            .session
+            .borrow_mut()
+            .carts
+            .insert(context.store_key().to_string(), cart.id.clone());
+        Ok(cart_result(&cart))
+    })
+}
+
+/// `add_to_cart`: adds a variant, creating the session cart if needed.
+fn add_to_cart(server: &McpServer, context: ToolContext, arguments: Value) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let line: LineArguments = serde_json::from_value(arguments)?;
+        let items = [CartItem::from(line)];
+        let api = context.api(server)?;
+        let cart_id = current_cart_id(server, &context).ok();
+        let cart = match cart_id {
+            Some(cart_id) => api.cart_lines_add(&cart_id, &items).await?,
+            None => api.cart_create(&items).await?,
+        };
+        server
+            .session
+            .borrow_mut()
+            .carts
+            .insert(context.store_key().to_string(), cart.id.clone());
+        Ok(cart_result(&cart))
+    })
+}
+
+/// `update_cart_line`: changes a line's quantity; `0` removes it.
+fn update_cart_line(server: &McpServer, context: ToolContext, arguments: Value) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: UpdateLineArguments = serde_json::from_value(arguments)?;
+        let cart_id = current_cart_id(server, &context)?;
+        let cart = context
+            .api(server)?
+            .cart_line_update(&cart_id, &arguments.line_id, arguments.quantity)
+            .await?;
+        Ok(cart_result(&cart))
+    })
+}
+
+/// `view_cart`: the session cart's lines and total.
+fn view_cart(server: &McpServer, context: ToolContext, _arguments: Value) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let cart_id = current_cart_id(server, &context)?;
+        match context.api(server)?.fetch_cart(&cart_id).await? {
+            Some(cart) => Ok(cart_result(&cart)),
+            None => {
+                server
+                    .session
+                    .borrow_mut()
+                    .carts
+                    .remove(context.store_key());
+                Err(ToolError::new(
+                    "The cart no longer exists (it may have been checked out). Create a new one.",
+                ))
+            }
+        }
+    })
+}
+
+/// `get_checkout_url`: where to send the buyer to pay.
+fn get_checkout_url(server: &McpServer, context: ToolContext, _arguments: Value) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let cart_id = current_cart_id(server, &context)?;
+        let cart = context
+            .api(server)?
+            .fetch_cart(&cart_id)
+            .await?
+            .ok_or_else(|| ToolError::new("The cart no longer exists. Create a new one."))?;
+        if cart.lines.edges.is_empty() {
+            return Err(ToolError::new(
+                "The cart is empty; add items before checkout.",
+            ));
+        }
+        Ok(ToolResult::structured(
+            serde_json::json!({ "checkoutUrl": cart.checkout_url, "cartId": cart.id }),
+            format!(
+                "{} ({} items, {})",
+                markdown_link("Checkout", Some(&cart.checkout_url)),
+                cart.total_quantity,
+                cart.cost.total_amount.format(DEFAULT_LOCALE)
+            ),
+        ))
+    })
+}
+
+/// Adds the cart tools to `registry`.
+pub fn register(registry: &mut ToolRegistry) {
+    let line_schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "variantId": { "type": "string", "description": "gid://shopify/ProductVariant/... ID" },
+            "quantity": { "type": "integer", "minimum": 1, "default": 1 }
+        },
+        "required": ["variantId"]
+    });
+    let write = serde_json::json!({ "readOnlyHint": false, "destructiveHint": false });
+    let read = serde_json::json!({ "readOnlyHint": true });
+
+    registry.register(
+        Toolset::Cart,
+        ToolDefinition {
+            name: "create_cart".to_string(),
+            title: Some("Create cart".to_string()),
+            description: "Start a new cart for this session, optionally with initial lines. Replaces the session's current cart.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": { "lines": { "type": "array", "items": line_schema } }
+            }),
+            output_schema: Some(cart_schema()),
+            annotations: Some(write.clone()),
+        },
+        Rc::new(create_cart),
+    );
+    registry.register(
+        Toolset::Cart,
+        ToolDefinition {
+            name: "add_to_cart".to_string(),
+            title: Some("Add to cart".to_string()),
+            description:
+                "Add a product variant to the session's cart, creating the cart if there isn't one."
+                    .to_string(),
+            input_schema: line_schema,
+            output_schema: Some(cart_schema()),
+            annotations: Some(write.clone()),
+        },
+        Rc::new(add_to_cart),
+    );
+    registry.register(
+        Toolset::Cart,
+        ToolDefinition {
+            name: "update_cart_line".to_string(),
+            title: Some("Update cart line".to_string()),
+            description: "Change the quantity of a line in the session's cart (lineId from view_cart). Quantity 0 removes the line.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "lineId": { "type": "string" },
+                    "quantity": { "type": "integer", "minimum": 0 }
+                },
+                "required": ["lineId", "quantity"]
+            }),
+            output_schema: Some(cart_schema()),
+            annotations: Some(write),
+        },
+        Rc::new(update_cart_line),
+    );
+    registry.register(
+        Toolset::Cart,
+        ToolDefinition {
+            name: "view_cart".to_string(),
+            title: Some("View cart".to_string()),
+            description: "Show the session cart's lines, quantities, prices and total.".to_string(),
+            input_schema: serde_json::json!({ "type": "object", "properties": {} }),
+            output_schema: Some(cart_schema()),
+            annotations: Some(read.clone()),
+        },
+        Rc::new(view_cart),
+    );
+    registry.register(
+        Toolset::Cart,
+        ToolDefinition {
+            name: "get_checkout_url".to_string(),
+            title: Some("Get checkout URL".to_string()),
+            description:
+                "Get the checkout URL for the session cart, to hand the purchase off to the buyer."
+                    .to_string(),
+            input_schema: serde_json::json!({ "type": "object", "properties": {} }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "checkoutUrl": { "type": "string" },
+                    "cartId": { "type": "string" }
+                },
+                "required": ["checkoutUrl", "cartId"]
+            })),
+            annotations: Some(read),
+        },
+        Rc::new(get_checkout_url),
+    );
+}
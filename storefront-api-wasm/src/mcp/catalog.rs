@@ -0,0 +1,835 @@
+//! Catalog tools: product search, product details, collection listings
+//! and Liquid previews.
+
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::server::McpServer;
+use super::tools::{
+    markdown_link, markdown_table, money_schema, ToolContext, ToolDefinition, ToolError,
+    ToolFuture, ToolRegistry, ToolResult, Toolset,
+};
+use crate::collection::CollectionOptions;
+use crate::money::DEFAULT_LOCALE;
+use crate::pagination::PageArgs;
+use crate::product::ProductOptions;
+use crate::search::ProductQuery;
+use crate::types::{Money, Product, ProductFilter, ProductVariant};
+use crate::StorefrontApi;
+
+/// Most products `search_products` returns per call.
+const MAX_SEARCH_LIMIT: u32 = 50;
+
+/// Sort orders offered to agents, mapped to `ProductSortKeys` and `reverse`.
+const SEARCH_SORTS: &[(&str, &str, bool)] = &[
+    ("relevance", "RELEVANCE", false),
+    ("price_asc", "PRICE", false),
+    ("price_desc", "PRICE", true),
+    ("best_selling", "BEST_SELLING", false),
+    ("newest", "CREATED_AT", true),
+    ("title", "TITLE", false),
+];
+
+/// Most products `list_collection_products` returns per call.
+const MAX_COLLECTION_LIMIT: u32 = 1000;
+
+/// Products fetched per page by `list_collection_products`. Larger pages
+/// would cost more than the default per-query cost limit.
+const COLLECTION_PAGE_SIZE: u32 = 100;
+
+/// Collection sort orders, mapped to `ProductCollectionSortKeys` and
+/// `reverse`.
+const COLLECTION_SORTS: &[(&str, &str, bool)] = &[
+    ("manual", "COLLECTION_DEFAULT", false),
+    ("best_selling", "BEST_SELLING", false),
+    ("price_asc", "PRICE", false),
+    ("price_desc", "PRICE", true),
+    ("newest", "CREATED", true),
+    ("title", "TITLE", false),
+];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SearchFilters {
+    tags: Vec<String>,
+    vendor: Option<String>,
+    product_type: Option<String>,
+    min_price: Option<f64>,
+    max_price: Option<f64>,
+    available_for_sale: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchArguments {
+    #[serde(default)]
+    query: String,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    sort: Option<String>,
+    #[serde(default)]
+    filters: SearchFilters,
+    #[serde(default)]
+    after: Option<String>,
+}
+
+/// The slice of a product an agent needs to pick from search results.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductSummary {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    /// Lowest variant price, formatted.
+    pub price: String,
+    pub amount: String,
+    pub currency_code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_for_sale: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+}
+
+impl From<&Product> for ProductSummary {
+    fn from(product: &Product) -> ProductSummary {
+        let price = &product.price_range.min_variant_price;
+        ProductSummary {
+            id: product.id.clone(),
+            handle: product.handle.clone(),
+            title: product.title.clone(),
+            vendor: product.vendor.clone().filter(|vendor| !vendor.is_empty()),
+            price: price.format(DEFAULT_LOCALE),
+            amount: price.amount.clone(),
+            currency_code: price.currency_code.clone(),
+            available_for_sale: product.available_for_sale,
+            url: product.online_store_url.clone(),
+            image_url: product.images.nodes().next().map(|image| image.url.clone()),
+        }
+    }
+}
+
+impl ProductSummary {
+    /// The summary of `product`, linking to its localized storefront URL
+    /// once `api` knows the shop's base URL (see `urls`).
+    pub(crate) fn new(api: &StorefrontApi, product: &Product) -> ProductSummary {
+        ProductSummary {
+            url: api
+                .product_url(&product.handle)
+                .or_else(|| product.online_store_url.clone()),
+            ..ProductSummary::from(product)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProductArguments {
+    /// A product handle or `gid://shopify/Product/...` ID.
+    product: String,
+    #[serde(default)]
+    include_inventory: bool,
+}
+
+/// A variant as reported by `get_product`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantDetails {
+    pub id: String,
+    pub title: String,
+    /// Option name to value, e.g. `{ "Color": "Blue", "Size": "M" }`.
+    pub options: serde_json::Map<String, Value>,
+    pub price: String,
+    pub amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare_at_price: Option<String>,
+    pub available_for_sale: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity_available: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+}
+
+impl From<&ProductVariant> for VariantDetails {
+    fn from(variant: &ProductVariant) -> VariantDetails {
+        VariantDetails {
+            id: variant.id.clone(),
+            title: variant.title.clone(),
+            options: variant
+                .selected_options
+                .iter()
+                .map(|option| (option.name.clone(), Value::String(option.value.clone())))
+                .collect(),
+            price: variant.price.format(DEFAULT_LOCALE),
+            amount: variant.price.amount.clone(),
+            compare_at_price: variant
+                .compare_at_price
+                .as_ref()
+                .filter(|compare_at| compare_at.value() > variant.price.value())
+                .map(|compare_at| compare_at.format(DEFAULT_LOCALE)),
+            available_for_sale: variant.available_for_sale,
+            quantity_available: variant.quantity_available,
+            sku: variant.sku.clone().filter(|sku| !sku.is_empty()),
+        }
+    }
+}
+
+/// JSON Schema for a serialized `ProductSummary`.
+fn product_summary_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "handle": { "type": "string" },
+            "title": { "type": "string" },
+            "vendor": { "type": "string" },
+            "price": { "type": "string", "description": "Lowest variant price, formatted" },
+            "amount": { "type": "string" },
+            "currencyCode": { "type": "string" },
+            "availableForSale": { "type": "boolean" },
+            "url": { "type": "string" },
+            "imageUrl": { "type": "string" }
+        },
+        "required": ["id", "handle", "title", "price", "amount", "currencyCode"]
+    })
+}
+
+/// JSON Schema for a serialized `VariantDetails`.
+fn variant_details_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "title": { "type": "string" },
+            "options": { "type": "object", "additionalProperties": { "type": "string" } },
+            "price": { "type": "string" },
+            "amount": { "type": "string" },
+            "compareAtPrice": { "type": "string" },
+            "availableForSale": { "type": "boolean" },
+            "quantityAvailable": { "type": "integer" },
+            "sku": { "type": "string" }
+        },
+        "required": ["id", "title", "options", "price", "amount", "availableForSale"]
+    })
+}
+
+/// Product summaries as a table with linked titles and images.
+fn summaries_table(summaries: &[ProductSummary]) -> String {
+    markdown_table(
+        &["Product", "Handle", "Price", "Available", "Image"],
+        summaries
+            .iter()
+            .map(|summary| {
+                vec![
+                    markdown_link(&summary.title, summary.url.as_deref()),
+                    summary.handle.clone(),
+                    summary.price.clone(),
+                    match summary.available_for_sale {
+                        Some(true) => "Yes".to_string(),
+                        Some(false) => "No".to_string(),
+                        None => String::new(),
+                    },
+                    summary
+                        .image_url
+                        .as_deref()
+                        .map(|url| markdown_link("image", Some(url)))
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    )
+}
+
+fn price_range_label(min: &Money, max: Option<&Money>) -> String {
+    match max {
+        Some(max) if max.value() > min.value() => {
+            format!(
+                "{} – {}",
+                min.format(DEFAULT_LOCALE),
+                max.format(DEFAULT_LOCALE)
+            )
+        }
+        _ => min.format(DEFAULT_LOCALE),
+    }
+}
+
+/// `get_product`: full product data by handle or ID.
+fn get_product(server: &McpServer, context: ToolContext, arguments: Value) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: ProductArguments = serde_json::from_value(arguments)?;
+        let options = ProductOptions {
+            inventory: arguments.include_inventory,
+            ..ProductOptions::default()
+        };
+        let key = arguments.product.trim().to_string();
+        let api = context.api(server)?;
+        let Some(product) = api
+            .fetch_products(std::slice::from_ref(&key), &options)
+            .await?
+            .pop()
+            .flatten()
+        else {
+            return Err(ToolError::new(format!("No product found for {key}")));
+        };
+
+        let variants: Vec<VariantDetails> = product
+            .variants
+            .iter()
+            .flat_map(|variants| variants.nodes())
+            .map(VariantDetails::from)
+            .collect();
+        let options: Vec<Value> = product
+            .options
+            .iter()
+            .map(|option| {
+                serde_json::json!({
+                    "name": option.name,
+                    "values": option
+                        .option_values
+                        .iter()
+                        .map(|value| value.name.as_str())
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        let price = price_range_label(
+            &product.price_range.min_variant_price,
+            product.price_range.max_variant_price.as_ref(),
+        );
+
+        let url = api
+            .product_url(&product.handle)
+            .or_else(|| product.online_store_url.clone());
+
+        let mut markdown = format!("## {}\n\n", product.title);
+        if let Some(image) = product.images.nodes().next() {
+            let alt = image.alt_text.as_deref().unwrap_or(&product.title);
+            markdown.push_str(&format!("!{}\n\n", markdown_link(alt, Some(&image.url))));
+        }
+        if let Some(vendor) = product.vendor.as_ref().filter(|vendor| !vendor.is_empty()) {
+            markdown.push_str(&format!("**Vendor:** {vendor}  \n"));
+        }
+        markdown.push_str(&format!("**Price:** {price}  \n"));
+        if let Some(url) = &url {
+            markdown.push_str(&format!("**URL:** {url}  \n"));
+        }
+        let images: Vec<String> = product
+            .images
+            .nodes()
+            .enumerate()
+            .map(|(index, image)| markdown_link(&(index + 1).to_string(), Some(&image.url)))
+            .collect();
+        if images.len() > 1 {
+            markdown.push_str(&format!("**Images:** {}\n", images.join(" ")));
+        }
+        if let Some(description) = product.description.as_ref().filter(|d| !d.is_empty()) {
+            markdown.push_str(&format!("\n{description}\n"));
+        }
+        if !variants.is_empty() {
+            let mut headers = vec!["Variant", "Price", "Available"];
+            if arguments.include_inventory {
+                headers.push("Quantity");
+            }
+            let rows = variants
+                .iter()
+                .map(|variant| {
+                    let mut row = vec![
+                        variant.title.clone(),
+                        match &variant.compare_at_price {
+                            Some(compare_at) => format!("{} ~~{compare_at}~~", variant.price),
+                            None => variant.price.clone(),
+                        },
+                        if variant.available_for_sale {
+                            "Yes"
+                        } else {
+                            "No"
+                        }
+                        .to_string(),
+                    ];
+                    if arguments.include_inventory {
+                        row.push(
+                            variant
+                                .quantity_available
+                                .map(|quantity| quantity.to_string())
+                                .unwrap_or_default(),
+                        );
+                    }
+                    row
+                })
+                .collect();
+            markdown.push('\n');
+            markdown.push_str(&markdown_table(&headers, rows));
+        }
+
+        Ok(ToolResult::structured(
+            serde_json::json!({
+                "id": product.id,
+                "handle": product.handle,
+                "title": product.title,
+                "description": product.description,
+                "vendor": product.vendor,
+                "productType": product.product_type,
+                "tags": product.tags,
+                "url": url,
+                "availableForSale": product.available_for_sale,
+                "price": price,
+                "priceRange": product.price_range,
+                "options": options,
+                "variants": variants,
+                "images": product.images.nodes().map(|image| image.url.as_str()).collect::<Vec<_>>(),
+            }),
+            markdown,
+        ))
+    })
+}
+
+/// `search_products`: Shopify product search with agent-friendly filters.
+fn search_products(server: &McpServer, context: ToolContext, arguments: Value) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: SearchArguments = serde_json::from_value(arguments)?;
+        let limit = arguments.limit.unwrap_or(10).clamp(1, MAX_SEARCH_LIMIT);
+        let sort = arguments.sort.as_deref().unwrap_or("relevance");
+        let (_, sort_key, reverse) = SEARCH_SORTS
+            .iter()
+            .find(|(name, _, _)| *name == sort)
+            .ok_or_else(|| {
+                let names: Vec<&str> = SEARCH_SORTS.iter().map(|(name, _, _)| *name).collect();
+                ToolError::new(format!(
+                    "Invalid sort: {sort}. Expected one of {}",
+                    names.join(", ")
+                ))
+            })?;
+
+        let filters = arguments.filters;
+        let query = ProductQuery {
+            text: Some(arguments.query).filter(|text| !text.trim().is_empty()),
+            tags: filters.tags,
+            vendor: filters.vendor,
+            product_type: filters.product_type,
+            min_price: filters.min_price,
+            max_price: filters.max_price,
+            available_for_sale: filters.available_for_sale,
+            ..ProductQuery::default()
+        }
+        .build();
+
+        let page = PageArgs::forward(limit, arguments.after);
+        let api = context.api(server)?;
+        let products = api
+            .fetch_search_products(&query, Some(sort_key.to_string()), *reverse, &page)
+            .await?;
+
+        let summaries: Vec<ProductSummary> = products
+            .nodes()
+            .map(|product| ProductSummary::new(&api, product))
+            .collect();
+        let page_info = products.page_info.unwrap_or_default();
+        let markdown = if summaries.is_empty() {
+            format!("No products match `{query}`.")
+        } else {
+            summaries_table(&summaries)
+        };
+
+        Ok(ToolResult::structured(
+            serde_json::json!({
+                "products": summaries,
+                "hasMore": page_info.has_next_page,
+                "endCursor": page_info.end_cursor,
+            }),
+            markdown,
+        ))
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CollectionArguments {
+    collection: String,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    sort: Option<String>,
+    #[serde(default)]
+    available_only: bool,
+}
+
+/// `list_collection_products`: every product in a collection, up to
+/// `limit`, fetched a page at a time with progress notifications.
+fn list_collection_products(
+    server: &McpServer,
+    context: ToolContext,
+    arguments: Value,
+) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: CollectionArguments = serde_json::from_value(arguments)?;
+        let limit = arguments
+            .limit
+            .unwrap_or(250)
+            .clamp(1, MAX_COLLECTION_LIMIT);
+        let sort = arguments.sort.as_deref().unwrap_or("manual");
+        let (_, sort_key, reverse) = COLLECTION_SORTS
+            .iter()
+            .find(|(name, _, _)| *name == sort)
+            .ok_or_else(|| {
+                let names: Vec<&str> = COLLECTION_SORTS.iter().map(|(name, _, _)| *name).collect();
+                ToolError::new(format!(
+                    "Invalid sort: {sort}. Expected one of {}",
+                    names.join(", ")
+                ))
+            })?;
+        let options = CollectionOptions {
+            sort_key: Some(sort_key.to_string()),
+            reverse: *reverse,
+            filters: if arguments.available_only {
+                vec![ProductFilter {
+                    available: Some(true),
+                    ..ProductFilter::default()
+                }]
+            } else {
+                Vec::new()
+            },
+            ..CollectionOptions::default()
+        };
+
+        let api = context.api(server)?;
+        let handle = arguments.collection.trim();
+        let mut title = None;
+        let mut summaries: Vec<ProductSummary> = Vec::new();
+        let mut after = None;
+        let mut has_more = false;
+        while (summaries.len() as u32) < limit {
+            let first = COLLECTION_PAGE_SIZE.min(limit - summaries.len() as u32);
+            let Some(page) = api
+                .fetch_collection_page(handle, &PageArgs::forward(first, after.take()), &options)
+                .await?
+            else {
+                return Err(ToolError::new(format!("No collection found for {handle}")));
+            };
+            title.get_or_insert(page.collection.title);
+            let products = page.collection.products.unwrap_or_default();
+            summaries.extend(
+                products
+                    .nodes()
+                    .map(|product| ProductSummary::new(&api, product)),
+            );
+            let page_info = products.page_info.unwrap_or_default();
+            has_more = page_info.has_next_page;
+            context.progress(
+                server,
+                summaries.len() as u64,
+                (!has_more).then_some(summaries.len() as u64),
+                Some(&format!("Fetched {} products", summaries.len())),
+            );
+            if !has_more {
+                break;
+            }
+            after = page_info.end_cursor;
+        }
+
+        let title = title.unwrap_or_default();
+        let mut markdown = format!("## {title}\n\n{} products", summaries.len());
+        if has_more {
+            markdown.push_str(&format!(" (first {limit}; the collection has more)"));
+        }
+        markdown.push_str("\n\n");
+        markdown.push_str(&summaries_table(&summaries));
+        Ok(ToolResult::structured(
+            serde_json::json!({
+                "collection": { "handle": handle, "title": title },
+                "products": summaries,
+                "hasMore": has_more,
+            }),
+            markdown,
+        ))
+    })
+}
+
+#[cfg(feature = "liquid")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RenderArguments {
+    template: String,
+    #[serde(default)]
+    product: Option<String>,
+    #[serde(default)]
+    collection: Option<String>,
+    #[serde(default)]
+    limit: Option<u32>,
+}
+
+/// Products `render_liquid` fetches for `collection.products` by default.
+#[cfg(feature = "liquid")]
+const RENDER_COLLECTION_LIMIT: u32 = 24;
+
+/// `render_liquid`: renders a template with a product and/or collection
+/// as its `product` and `collection` drops.
+#[cfg(feature = "liquid")]
+fn render_liquid(server: &McpServer, context: ToolContext, arguments: Value) -> ToolFuture<'_> {
+    use crate::liquid::engine::template_variables;
+    use crate::liquid::LiquidEngine;
+
+    Box::pin(async move {
+        let arguments: RenderArguments = serde_json::from_value(arguments)?;
+        let invalid = |message: String| ToolError {
+            class: "invalid_arguments",
+            ..ToolError::new(message)
+        };
+        let product = arguments.product.as_deref().map(str::trim);
+        let collection = arguments.collection.as_deref().map(str::trim);
+        if product.is_none() && collection.is_none() {
+            return Err(invalid(
+                "Pass a product or collection handle to render with".to_string(),
+            ));
+        }
+        let engine = LiquidEngine::new();
+        let template = engine
+            .parse_template(&arguments.template)
+            .map_err(|error| invalid(error.to_string()))?;
+
+        let api = context.api(server)?;
+        let mut data = serde_json::Map::new();
+        if let Some(handle) = product {
+            let Some(product) = api
+                .fetch_products(&[handle.to_string()], &ProductOptions::default())
+                .await?
+                .pop()
+                .flatten()
+            else {
+                return Err(ToolError::new(format!("No product found for {handle}")));
+            };
+            data.insert("product".to_string(), serde_json::to_value(product)?);
+        }
+        if let Some(handle) = collection {
+            let limit = arguments
+                .limit
+                .unwrap_or(RENDER_COLLECTION_LIMIT)
+                .clamp(1, COLLECTION_PAGE_SIZE);
+            let Some(page) = api
+                .fetch_collection_page(
+                    handle,
+                    &PageArgs::forward(limit, None),
+                    &CollectionOptions::default(),
+                )
+                .await?
+            else {
+                return Err(ToolError::new(format!("No collection found for {handle}")));
+            };
+            data.insert(
+                "collection".to_string(),
+                serde_json::to_value(page.collection)?,
+            );
+        }
+
+        engine.set_storefront(&api);
+        let variables = template_variables(crate::to_js(&data)?)?;
+        let rendered = engine
+            .render_with_warnings(&template, variables)
+            .await
+            .map_err(|error| ToolError::new(error.to_string()))?;
+
+        let mut markdown = format!("```html\n{}\n```\n", rendered.output);
+        if !rendered.diagnostics.is_empty() {
+            markdown.push_str("\n**Warnings:**\n\n");
+            for diagnostic in &rendered.diagnostics {
+                match (diagnostic.line, diagnostic.column) {
+                    (Some(line), Some(column)) => markdown.push_str(&format!(
+                        "- line {line}, column {column}: {}\n",
+                        diagnostic.message
+                    )),
+                    _ => markdown.push_str(&format!("- {}\n", diagnostic.message)),
+                }
+            }
+        }
+        Ok(ToolResult::structured(
+            serde_json::json!({
+                "html": rendered.output,
+                "diagnostics": rendered.diagnostics,
+            }),
+            markdown,
+        ))
+    })
+}
+
+/// Adds the catalog tools to `registry`.
+pub fn register(registry: &mut ToolRegistry) {
+    let sorts: Vec<&str> = SEARCH_SORTS.iter().map(|(name, _, _)| *name).collect();
+    registry.register(
+        Toolset::Catalog,
+        ToolDefinition {
+            name: "search_products".to_string(),
+            title: Some("Search products".to_string()),
+            description: "Search the store's products by keyword and filters. Returns product summaries (handle, title, price, availability) and a cursor for more results.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Keywords; Shopify search syntax is allowed" },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_SEARCH_LIMIT, "default": 10 },
+                    "sort": { "type": "string", "enum": sorts, "default": "relevance" },
+                    "filters": {
+                        "type": "object",
+                        "properties": {
+                            "tags": { "type": "array", "items": { "type": "string" } },
+                            "vendor": { "type": "string" },
+                            "productType": { "type": "string" },
+                            "minPrice": { "type": "number" },
+                            "maxPrice": { "type": "number" },
+                            "availableForSale": { "type": "boolean" }
+                        }
+                    },
+                    "after": { "type": "string", "description": "endCursor from a previous call" }
+                }
+            }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "products": { "type": "array", "items": product_summary_schema() },
+                    "hasMore": { "type": "boolean" },
+                    "endCursor": { "type": ["string", "null"] }
+                },
+                "required": ["products", "hasMore"]
+            })),
+            annotations: Some(serde_json::json!({ "readOnlyHint": true })),
+        },
+        Rc::new(search_products),
+    );
+    registry.register(
+        Toolset::Catalog,
+        ToolDefinition {
+            name: "get_product".to_string(),
+            title: Some("Get product details".to_string()),
+            description: "Get a product's full details by handle or product ID: description, options, price range and every variant with its price and availability.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "product": { "type": "string", "description": "Product handle (e.g. \"classic-tee\") or gid://shopify/Product/... ID" },
+                    "includeInventory": { "type": "boolean", "default": false, "description": "Include per-variant quantityAvailable (needs the inventory access scope)" }
+                },
+                "required": ["product"]
+            }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "handle": { "type": "string" },
+                    "title": { "type": "string" },
+                    "description": { "type": ["string", "null"] },
+                    "vendor": { "type": ["string", "null"] },
+                    "productType": { "type": ["string", "null"] },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "url": { "type": ["string", "null"] },
+                    "availableForSale": { "type": ["boolean", "null"] },
+                    "price": { "type": "string", "description": "Formatted price or price range" },
+                    "priceRange": {
+                        "type": "object",
+                        "properties": {
+                            "minVariantPrice": money_schema(),
+                            "maxVariantPrice": money_schema()
+                        },
+                        "required": ["minVariantPrice"]
+                    },
+                    "options": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "name": { "type": "string" },
+                                "values": { "type": "array", "items": { "type": "string" } }
+                            },
+                            "required": ["name", "values"]
+                        }
+                    },
+                    "variants": { "type": "array", "items": variant_details_schema() },
+                    "images": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["id", "handle", "title", "price", "priceRange", "options", "variants", "images"]
+            })),
+            annotations: Some(serde_json::json!({ "readOnlyHint": true })),
+        },
+        Rc::new(get_product),
+    );
+    let collection_sorts: Vec<&str> = COLLECTION_SORTS.iter().map(|(name, _, _)| *name).collect();
+    registry.register(
+        Toolset::Catalog,
+        ToolDefinition {
+            name: "list_collection_products".to_string(),
+            title: Some("List collection products".to_string()),
+            description: "List the products in a collection, paging through it as needed (up to 1000 products). Sends progress notifications for large collections.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "collection": { "type": "string", "description": "Collection handle, e.g. \"summer-sale\"" },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_COLLECTION_LIMIT, "default": 250 },
+                    "sort": { "type": "string", "enum": collection_sorts, "default": "manual" },
+                    "availableOnly": { "type": "boolean", "default": false, "description": "Skip sold-out products" }
+                },
+                "required": ["collection"]
+            }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "collection": {
+                        "type": "object",
+                        "properties": {
+                            "handle": { "type": "string" },
+                            "title": { "type": "string" }
+                        },
+                        "required": ["handle", "title"]
+                    },
+                    "products": { "type": "array", "items": product_summary_schema() },
+                    "hasMore": { "type": "boolean" }
+                },
+                "required": ["collection", "products", "hasMore"]
+            })),
+            annotations: Some(serde_json::json!({ "readOnlyHint": true })),
+        },
+        Rc::new(list_collection_products),
+    );
+    #[cfg(feature = "liquid")]
+    registry.register(
+        Toolset::Catalog,
+        ToolDefinition {
+            name: "render_liquid".to_string(),
+            title: Some("Render Liquid".to_string()),
+            description: "Render a Liquid template with a product and/or collection from the store as `product` and `collection`, to preview how content looks in the theme. Returns the HTML and any warnings (undefined variables, unknown filters).".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "template": { "type": "string", "description": "Liquid source, e.g. \"<h1>{{ product.title }}</h1>\"" },
+                    "product": { "type": "string", "description": "Product handle or gid://shopify/Product/... ID, rendered as `product`" },
+                    "collection": { "type": "string", "description": "Collection handle, rendered as `collection`" },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": COLLECTION_PAGE_SIZE, "default": RENDER_COLLECTION_LIMIT, "description": "Products fetched for `collection.products`" }
+                },
+                "required": ["template"]
+            }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "html": { "type": "string" },
+                    "diagnostics": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "severity": { "type": "string" },
+                                "message": { "type": "string" },
+                                "line": { "type": ["integer", "null"] },
+                                "column": { "type": ["integer", "null"] },
+                                "source": { "type": ["string", "null"] }
+                            },
+                            "required": ["severity", "message"]
+                        }
+                    }
+                },
+                "required": ["html", "diagnostics"]
+            })),
+            annotations: Some(serde_json::json!({ "readOnlyHint": true })),
+        },
+        Rc::new(render_liquid),
+    );
+}
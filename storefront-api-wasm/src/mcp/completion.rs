@@ -0,0 +1,148 @@
+//! Argument completion (`completion/complete`) for resource templates,
+//! prompts and tools, backed by predictive search.
+//!
+//! Product and collection handles are the only values completed; any other
+//! argument gets an empty list, which tells the host to let the user type
+//! freely.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::protocol::RpcError;
+use super::resources::{COLLECTION_TEMPLATE, PRODUCT_TEMPLATE};
+use super::server::McpServer;
+use super::tools::Toolset;
+use crate::search::PredictiveSearchOptions;
+
+/// Most suggestions predictive search returns per type.
+const COMPLETION_LIMIT: u32 = 10;
+
+/// What an argument holds, as far as completion is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Completable {
+    ProductHandle,
+    CollectionHandle,
+}
+
+impl Completable {
+    fn search_type(self) -> &'static str {
+        match self {
+            Completable::ProductHandle => "PRODUCT",
+            Completable::CollectionHandle => "COLLECTION",
+        }
+    }
+}
+
+/// The thing whose argument is being completed.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum Reference {
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    /// Not part of the protocol: lets hosts that build tool calls themselves
+    /// complete `product` and `collection` arguments the same way.
+    #[serde(rename = "ref/tool")]
+    Tool { name: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionArgument {
+    name: String,
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompleteParams {
+    #[serde(rename = "ref")]
+    reference: Reference,
+    argument: CompletionArgument,
+}
+
+/// Which handle, if any, `argument` of `reference` takes.
+fn completable(reference: &Reference, argument: &str) -> Option<Completable> {
+    match (reference, argument) {
+        (Reference::Resource { uri }, "handle") if uri == PRODUCT_TEMPLATE => {
+            Some(Completable::ProductHandle)
+        }
+        (Reference::Resource { uri }, "handle") if uri == COLLECTION_TEMPLATE => {
+            Some(Completable::CollectionHandle)
+        }
+        (Reference::Prompt { name }, "handle") if name == "write_product_description" => {
+            Some(Completable::ProductHandle)
+        }
+        (Reference::Tool { .. }, "product") => Some(Completable::ProductHandle),
+        (Reference::Tool { .. }, "collection") => Some(Completable::CollectionHandle),
+        _ => None,
+    }
+}
+
+/// Orders handles that start with what the user typed ahead of the rest,
+/// keeping search order otherwise, and drops duplicates.
+fn rank_handles(mut handles: Vec<String>, typed: &str) -> Vec<String> {
+    let typed = typed.to_lowercase();
+    let mut seen = std::collections::BTreeSet::new();
+    handles.retain(|handle| seen.insert(handle.clone()));
+    handles.sort_by_key(|handle| !handle.starts_with(&typed));
+    handles
+}
+
+fn completion(values: Vec<String>) -> Value {
+    serde_json::json!({
+        "completion": {
+            "values": values,
+            "total": values.len(),
+            "hasMore": false,
+        }
+    })
+}
+
+impl McpServer {
+    /// `completion/complete`: suggests product and collection handles
+    /// matching the partial value.
+    pub(crate) async fn complete(&self, params: Value) -> Result<Value, RpcError> {
+        let params: CompleteParams = serde_json::from_value(params)
+            .map_err(|error| RpcError::invalid_params(error.to_string()))?;
+        // Handles for a store's copy of a tool come from that store.
+        let mut store = None;
+        if let Reference::Tool { name } = &params.reference {
+            let (tool_store, tool) = self.route_tool(name);
+            if self.tools.borrow().get(tool).is_none() {
+                return Err(RpcError::invalid_params(format!("Unknown tool: {name}")));
+            }
+            store = tool_store;
+        }
+        let typed = params.argument.value.trim();
+        let kind = match completable(&params.reference, &params.argument.name) {
+            Some(kind) if !typed.is_empty() && self.toolset_enabled(Toolset::Catalog) => kind,
+            _ => return Ok(completion(Vec::new())),
+        };
+
+        let options = PredictiveSearchOptions {
+            limit: Some(COMPLETION_LIMIT),
+            types: vec![kind.search_type().to_string()],
+            ..PredictiveSearchOptions::default()
+        };
+        let result = self
+            .store_api(store)
+            .ok_or_else(|| RpcError::invalid_params("Unknown store"))?
+            .fetch_predictive_search(typed, &options)
+            .await
+            .map_err(RpcError::from)?;
+        let handles = match kind {
+            Completable::ProductHandle => result
+                .products
+                .into_iter()
+                .map(|product| product.handle)
+                .collect(),
+            Completable::CollectionHandle => result
+                .collections
+                .into_iter()
+                .map(|collection| collection.handle)
+                .collect(),
+        };
+        Ok(completion(rank_handles(handles, typed)))
+    }
+}
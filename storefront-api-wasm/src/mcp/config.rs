@@ -0,0 +1,357 @@
+//! Server configuration from a JSON file and environment variables.
+//!
+//! The launcher reads the file itself (WebAssembly has no file system) and
+//! passes its text, with the process environment, to
+//! `McpServer.from_config`. Environment variables override the file. Every
+//! problem is reported at once, named by the setting it came from, so a
+//! deployment can be fixed in one pass.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use super::audit::DEFAULT_AUDIT_LOG_SIZE;
+use super::limits::LimitOptions;
+use super::logging::DEFAULT_LOG_LEVEL;
+use super::server::{valid_store_name, McpServer};
+use super::tools::Toolset;
+use crate::logging::LogLevel;
+use crate::redact::Secret;
+use crate::{from_js, StorefrontApi};
+
+/// API version used when neither the file nor the environment sets one.
+pub const DEFAULT_API_VERSION: &str = "2024-01";
+
+/// An additional shop, exposed as `name.tool_name`.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct StoreConfig {
+    pub shop_domain: String,
+    pub access_token: String,
+    /// Defaults to the server's `apiVersion`.
+    pub api_version: Option<String>,
+}
+
+/// Everything needed to start a server.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub shop_domain: String,
+    pub access_token: String,
+    pub api_version: String,
+    /// Toolset names to expose; `None` exposes all of them.
+    pub toolsets: Option<Vec<String>>,
+    /// Hide and refuse tools that change data.
+    pub read_only: bool,
+    pub limits: LimitOptions,
+    pub log_level: LogLevel,
+    pub poll_interval_ms: u32,
+    /// Tool calls kept in the audit log; `0` turns it off.
+    pub audit_log_size: u32,
+    pub stores: BTreeMap<String, StoreConfig>,
+}
+
+impl fmt::Debug for StoreConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreConfig")
+            .field("shop_domain", &self.shop_domain)
+            .field("access_token", &Secret(&self.access_token))
+            .field("api_version", &self.api_version)
+            .finish()
+    }
+}
+
+impl fmt::Debug for ServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerConfig")
+            .field("shop_domain", &self.shop_domain)
+            .field("access_token", &Secret(&self.access_token))
+            .field("api_version", &self.api_version)
+            .field("toolsets", &self.toolsets)
+            .field("read_only", &self.read_only)
+            .field("limits", &self.limits)
+            .field("log_level", &self.log_level)
+            .field("poll_interval_ms", &self.poll_interval_ms)
+            .field("audit_log_size", &self.audit_log_size)
+            .field("stores", &self.stores)
+            .finish()
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            shop_domain: String::new(),
+            access_token: String::new(),
+            api_version: DEFAULT_API_VERSION.to_string(),
+            toolsets: None,
+            read_only: false,
+            limits: LimitOptions::default(),
+            log_level: DEFAULT_LOG_LEVEL,
+            poll_interval_ms: 60_000,
+            audit_log_size: DEFAULT_AUDIT_LOG_SIZE,
+            stores: BTreeMap::new(),
+        }
+    }
+}
+
+fn parse_number(name: &str, value: &str, problems: &mut Vec<String>) -> Option<u32> {
+    match value.trim().parse() {
+        Ok(number) => Some(number),
+        Err(_) => {
+            problems.push(format!(
+                "{name} must be a whole number (0 turns it off), got \"{value}\""
+            ));
+            None
+        }
+    }
+}
+
+/// Checks a shop's domain, token and (when set) version, prefixing problems
+/// with `prefix` (e.g. `stores.eu.`).
+fn validate_shop(
+    prefix: &str,
+    shop_domain: &str,
+    access_token: &str,
+    api_version: Option<&str>,
+    problems: &mut Vec<String>,
+) {
+    if shop_domain.trim().is_empty() {
+        problems.push(format!(
+            "{prefix}shopDomain is missing; set it to your shop's domain, e.g. your-shop.myshopify.com"
+        ));
+    } else if shop_domain.contains("://") || shop_domain.contains('/') {
+        problems.push(format!(
+            "{prefix}shopDomain should be a bare host name like your-shop.myshopify.com, without https:// or a path (got \"{shop_domain}\")"
+        ));
+    } else if !shop_domain.contains('.') {
+        problems.push(format!(
+            "{prefix}shopDomain \"{shop_domain}\" is not a domain; did you mean {shop_domain}.myshopify.com?"
+        ));
+    }
+
+    if access_token.trim().is_empty() {
+        problems.push(format!(
+            "{prefix}accessToken is missing; create a Storefront API access token in the shop's Headless or custom app settings"
+        ));
+    } else if access_token.starts_with("shpat_") {
+        problems.push(format!(
+            "{prefix}accessToken is an Admin API token (shpat_...); the Storefront API needs a Storefront access token"
+        ));
+    }
+
+    let Some(api_version) = api_version else {
+        return;
+    };
+    let valid_version = api_version == "unstable"
+        || api_version.split_once('-').is_some_and(|(year, month)| {
+            year.len() == 4
+                && year.chars().all(|c| c.is_ascii_digit())
+                && matches!(month, "01" | "04" | "07" | "10")
+        });
+    if !valid_version {
+        problems.push(format!(
+            "{prefix}apiVersion must be a quarterly release such as 2024-01, 2024-04, 2024-07 or 2024-10, or \"unstable\" (got \"{api_version}\")"
+        ));
+    }
+}
+
+impl ServerConfig {
+    /// Reads `file` (the config file's JSON text, if any), applies the
+    /// `STOREFRONT_*` variables in `env` on top and validates the result.
+    pub fn load(file: Option<&str>, env: &BTreeMap<String, String>) -> Result<Self, Vec<String>> {
+        let mut config = match file.filter(|text| !text.trim().is_empty()) {
+            Some(text) => {
+                serde_json::from_str(text).map_err(|error| vec![format!("Config file: {error}")])?
+            }
+            None => ServerConfig::default(),
+        };
+        let mut problems = Vec::new();
+        config.apply_env(env, &mut problems);
+        config.validate(&mut problems);
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(problems)
+        }
+    }
+
+    fn apply_env(&mut self, env: &BTreeMap<String, String>, problems: &mut Vec<String>) {
+        let var = |name: &str| env.get(name).filter(|value| !value.trim().is_empty());
+        if let Some(value) = var("STOREFRONT_SHOP_DOMAIN") {
+            self.shop_domain = value.trim().to_string();
+        }
+        if let Some(value) = var("STOREFRONT_ACCESS_TOKEN") {
+            self.access_token = value.trim().to_string();
+        }
+        if let Some(value) = var("STOREFRONT_API_VERSION") {
+            self.api_version = value.trim().to_string();
+        }
+        if let Some(value) = var("STOREFRONT_TOOLSETS") {
+            self.toolsets = Some(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            );
+        }
+        if let Some(value) = var("STOREFRONT_READ_ONLY") {
+            match value.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" => self.read_only = true,
+                "false" | "0" | "no" => self.read_only = false,
+                _ => problems.push(format!(
+                    "STOREFRONT_READ_ONLY must be true or false (got \"{value}\")"
+                )),
+            }
+        }
+        if let Some(value) = var("STOREFRONT_LOG_LEVEL") {
+            match serde_json::from_value(serde_json::json!(value.trim().to_lowercase())) {
+                Ok(level) => self.log_level = level,
+                Err(_) => problems.push(format!(
+                    "STOREFRONT_LOG_LEVEL must be one of debug, info, notice, warning, error, critical, alert or emergency (got \"{value}\")"
+                )),
+            }
+        }
+        if let Some(value) = var("STOREFRONT_STORES") {
+            match serde_json::from_str(value) {
+                Ok(stores) => self.stores = stores,
+                Err(error) => problems.push(format!(
+                    "STOREFRONT_STORES must be a JSON object like {{\"eu\":{{\"shopDomain\":\"...\",\"accessToken\":\"...\"}}}}: {error}"
+                )),
+            }
+        }
+
+        let numbers: [(&str, &mut u32); 7] = [
+            ("STOREFRONT_POLL_INTERVAL_MS", &mut self.poll_interval_ms),
+            ("STOREFRONT_AUDIT_LOG_SIZE", &mut self.audit_log_size),
+            (
+                "STOREFRONT_TOOL_CALLS_PER_MINUTE",
+                &mut self.limits.tool_calls_per_minute,
+            ),
+            (
+                "STOREFRONT_SESSION_CALLS_PER_MINUTE",
+                &mut self.limits.session_calls_per_minute,
+            ),
+            ("STOREFRONT_MAX_QUERY_COST", &mut self.limits.max_query_cost),
+            (
+                "STOREFRONT_COST_BUCKET_SIZE",
+                &mut self.limits.cost_bucket_size,
+            ),
+            (
+                "STOREFRONT_COST_RESTORE_RATE",
+                &mut self.limits.cost_restore_rate,
+            ),
+        ];
+        for (name, field) in numbers {
+            if let Some(number) = var(name).and_then(|value| parse_number(name, value, problems)) {
+                *field = number;
+            }
+        }
+    }
+
+    fn validate(&self, problems: &mut Vec<String>) {
+        validate_shop(
+            "",
+            &self.shop_domain,
+            &self.access_token,
+            Some(&self.api_version),
+            problems,
+        );
+
+        if let Some(toolsets) = &self.toolsets {
+            if toolsets.is_empty() {
+                problems.push(
+                    "toolsets is empty, so no tools would be exposed; list at least one or leave it unset for all"
+                        .to_string(),
+                );
+            }
+            for name in toolsets {
+                if let Err(error) = Toolset::parse(name) {
+                    problems.push(format!("toolsets: {error}"));
+                }
+            }
+        }
+
+        let limits = &self.limits;
+        if limits.max_query_cost > 0
+            && limits.cost_bucket_size > 0
+            && limits.max_query_cost > limits.cost_bucket_size
+        {
+            problems.push(format!(
+                "limits.maxQueryCost ({}) is larger than limits.costBucketSize ({}), so the largest allowed queries could never run; raise the bucket or lower the cap",
+                limits.max_query_cost, limits.cost_bucket_size
+            ));
+        }
+
+        for (name, store) in &self.stores {
+            if !valid_store_name(name) {
+                problems.push(format!(
+                    "stores: \"{name}\" is not a valid store name; use letters, digits, _ and -"
+                ));
+            }
+            validate_shop(
+                &format!("stores.{name}."),
+                &store.shop_domain,
+                &store.access_token,
+                store.api_version.as_deref(),
+                problems,
+            );
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl McpServer {
+    /// Builds a server from a config file and environment variables. `file`
+    /// is the JSON text of the config file (`{ shopDomain, accessToken,
+    /// apiVersion, toolsets, readOnly, limits, logLevel, pollIntervalMs,
+    /// auditLogSize, stores }`), if there is one; `env` is an object of environment variables such as
+    /// `process.env`, whose `STOREFRONT_*` entries override the file. Fails
+    /// with a list of every problem found.
+    #[wasm_bindgen]
+    pub fn from_config(
+        file: Option<String>,
+        #[wasm_bindgen(unchecked_optional_param_type = "Record<string, string | undefined>")]
+        env: JsValue,
+    ) -> Result<McpServer, JsValue> {
+        let env: BTreeMap<String, String> = if env.is_undefined() || env.is_null() {
+            BTreeMap::new()
+        } else {
+            from_js(env)?
+        };
+        let config = ServerConfig::load(file.as_deref(), &env).map_err(|problems| {
+            JsValue::from_str(&format!(
+                "Invalid configuration:\n  - {}",
+                problems.join("\n  - ")
+            ))
+        })?;
+
+        let api = StorefrontApi::new(
+            config.shop_domain,
+            config.access_token,
+            config.api_version.clone(),
+        );
+        let server = McpServer::new(&api);
+        server.set_toolsets(config.toolsets)?;
+        server.set_read_only(config.read_only);
+        server.limiter.borrow_mut().set_options(config.limits);
+        server.log_level.set(config.log_level);
+        server.set_poll_interval(config.poll_interval_ms);
+        server.set_audit_log_size(config.audit_log_size);
+        for (name, store) in config.stores {
+            let api = StorefrontApi::new(
+                store.shop_domain,
+                store.access_token,
+                store
+                    .api_version
+                    .unwrap_or_else(|| config.api_version.clone()),
+            );
+            server.add_store(name, &api)?;
+        }
+        Ok(server)
+    }
+}
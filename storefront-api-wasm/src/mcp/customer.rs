@@ -0,0 +1,105 @@
+//! Customer tools, for agents acting for a logged-in customer. Each call
+//! takes the customer's access token; nothing about the customer is kept in
+//! the session.
+
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::server::McpServer;
+use super::tools::{
+    markdown_table, ToolContext, ToolDefinition, ToolError, ToolFuture, ToolRegistry, ToolResult,
+    Toolset,
+};
+use crate::metafield::{IdentifierInput, MetafieldOwner};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomerMetafieldArguments {
+    customer_access_token: String,
+    identifiers: Vec<IdentifierInput>,
+}
+
+/// `get_customer_metafields`: the logged-in customer's metafields, decoded
+/// by type.
+fn get_customer_metafields(
+    server: &McpServer,
+    context: ToolContext,
+    arguments: Value,
+) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: CustomerMetafieldArguments = serde_json::from_value(arguments)?;
+        let identifiers = arguments
+            .identifiers
+            .into_iter()
+            .map(IdentifierInput::into_identifier)
+            .collect::<Result<Vec<_>, _>>()?;
+        let owner = MetafieldOwner::Customer {
+            customer_access_token: arguments.customer_access_token,
+        };
+        let Some(values) = context
+            .api(server)?
+            .fetch_metafields(&owner, &identifiers)
+            .await?
+        else {
+            return Err(ToolError::new(
+                "No customer for this access token; it may have expired",
+            ));
+        };
+
+        let rows = values
+            .iter()
+            .map(|(name, metafield)| match metafield {
+                Some(metafield) => vec![
+                    name.clone(),
+                    metafield.kind.clone(),
+                    metafield.raw_value.clone(),
+                ],
+                None => vec![name.clone(), String::new(), "(not set)".to_string()],
+            })
+            .collect();
+        Ok(ToolResult::structured(
+            serde_json::json!({ "metafields": values }),
+            markdown_table(&["Metafield", "Type", "Value"], rows),
+        ))
+    })
+}
+
+/// Adds the customer tools to `registry`.
+pub fn register(registry: &mut ToolRegistry) {
+    registry.register(
+        Toolset::Customer,
+        ToolDefinition {
+            name: "get_customer_metafields".to_string(),
+            title: Some("Get customer metafields".to_string()),
+            description: "Read metafields of the logged-in customer, such as saved preferences or loyalty data, decoded according to their type. Needs the customer's access token.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "customerAccessToken": { "type": "string", "description": "From customerAccessTokenCreate" },
+                    "identifiers": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "minItems": 1,
+                        "description": "Metafields as \"namespace.key\", e.g. \"custom.shoe_size\""
+                    }
+                },
+                "required": ["customerAccessToken", "identifiers"]
+            }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "metafields": {
+                        "type": "object",
+                        "description": "\"namespace.key\" to { namespace, key, type, value, rawValue, reference? }, or null when unset",
+                        "additionalProperties": { "type": ["object", "null"] }
+                    }
+                },
+                "required": ["metafields"]
+            })),
+            annotations: Some(serde_json::json!({ "readOnlyHint": true })),
+        },
+        Rc::new(get_customer_metafields),
+    );
+}
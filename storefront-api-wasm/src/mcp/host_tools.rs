@@ -0,0 +1,138 @@
+//! Tools implemented by the host application in JavaScript.
+//!
+//! `register_tool` adds a tool whose calls are forwarded to a JS callback,
+//! so app-specific capabilities can sit next to the built-in commerce
+//! tools. Clients are told about every change with
+//! `notifications/tools/list_changed`.
+
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use super::operations::valid_tool_name;
+use super::server::McpServer;
+use super::tools::{
+    ToolContext, ToolDefinition, ToolError, ToolFuture, ToolHandler, ToolResult, Toolset,
+};
+use crate::{from_js, to_js};
+
+#[derive(Deserialize)]
+struct HostToolOptions {
+    #[serde(flatten)]
+    definition: ToolDefinition,
+    #[serde(default)]
+    toolset: Option<String>,
+}
+
+struct HostTool {
+    callback: js_sys::Function,
+}
+
+impl HostTool {
+    /// `{ store, progressToken, signal }` for the callback.
+    fn context_object(context: &ToolContext) -> Result<JsValue, JsValue> {
+        let object = to_js(&serde_json::json!({
+            "store": context.store,
+            "progressToken": context.progress_token,
+        }))?;
+        if let Some(signal) = &context.signal {
+            js_sys::Reflect::set(&object, &JsValue::from_str("signal"), signal)?;
+        }
+        Ok(object)
+    }
+
+    /// Turns what the callback returned into a result: strings become
+    /// text, objects with a `content` array are taken as a complete tool
+    /// result, and anything else is returned as structured JSON.
+    fn into_result(value: JsValue) -> Result<ToolResult, ToolError> {
+        if let Some(text) = value.as_string() {
+            return Ok(ToolResult::text(text));
+        }
+        if value.is_undefined() || value.is_null() {
+            return Ok(ToolResult::text(""));
+        }
+        let value: Value = from_js(value)?;
+        if value.get("content").is_some_and(Value::is_array) {
+            return serde_json::from_value(value).map_err(|error| {
+                ToolError::new(format!("The tool returned an invalid result: {error}"))
+            });
+        }
+        Ok(ToolResult::json(value))
+    }
+}
+
+impl ToolHandler for HostTool {
+    fn call<'a>(
+        &'a self,
+        _server: &'a McpServer,
+        context: ToolContext,
+        arguments: Value,
+    ) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let returned = self.callback.call2(
+                &JsValue::NULL,
+                &to_js(&arguments)?,
+                &HostTool::context_object(&context)?,
+            )?;
+            // Await promises from async callbacks; plain values resolve at once.
+            let value = JsFuture::from(js_sys::Promise::resolve(&returned)).await?;
+            HostTool::into_result(value)
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl McpServer {
+    /// Adds a tool implemented in JavaScript. `definition` is `{ name,
+    /// title?, description, inputSchema, outputSchema?, annotations?,
+    /// toolset? }` (toolset defaults to `"custom"`). `callback(arguments,
+    /// { store, progressToken, signal })` may be sync or async; it returns
+    /// a string (sent as text), a complete `{ content, structuredContent?,
+    /// isError? }` result, or any other JSON value (sent as structured
+    /// content). A throw or rejection becomes an `isError` result. A tool
+    /// with the same name is replaced. Read-only mode hides the tool unless
+    /// its annotations include `readOnlyHint: true`.
+    #[wasm_bindgen]
+    pub fn register_tool(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "HostToolDefinition")] definition: JsValue,
+        #[wasm_bindgen(unchecked_param_type = "HostToolCallback")] callback: js_sys::Function,
+    ) -> Result<(), JsValue> {
+        let options: HostToolOptions = serde_json::from_value(from_js(definition)?)
+            .map_err(|error| JsValue::from_str(&format!("Invalid tool definition: {error}")))?;
+        let definition = options.definition;
+        if !valid_tool_name(&definition.name) {
+            return Err(JsValue::from_str(&format!(
+                "Invalid tool name: {}. Use 1-64 letters, digits, _, - and .",
+                definition.name
+            )));
+        }
+        if definition.input_schema.get("type").and_then(Value::as_str) != Some("object") {
+            return Err(JsValue::from_str(
+                "inputSchema must be a JSON Schema with \"type\": \"object\"",
+            ));
+        }
+        let toolset = match &options.toolset {
+            Some(name) => Toolset::parse(name).map_err(|error| JsValue::from_str(&error))?,
+            None => Toolset::Custom,
+        };
+        self.tools
+            .borrow_mut()
+            .register(toolset, definition, Rc::new(HostTool { callback }));
+        self.tools_changed();
+        Ok(())
+    }
+
+    /// Removes a tool, built-in or registered. Returns whether it existed.
+    #[wasm_bindgen]
+    pub fn remove_tool(&self, name: &str) -> bool {
+        let removed = self.tools.borrow_mut().remove(name);
+        if removed {
+            self.tools_changed();
+        }
+        removed
+    }
+}
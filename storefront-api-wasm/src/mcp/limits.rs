@@ -0,0 +1,312 @@
+//! Per-tool and per-session rate limits and query cost budgets.
+//!
+//! Tool calls are counted over a sliding one-minute window, per tool and
+//! for the whole session. Every Storefront request a tool makes is priced
+//! with `estimate_query_cost` and drawn from a session cost bucket that
+//! refills at a steady rate, after Shopify's own leaky-bucket throttle, so
+//! a runaway agent runs out of budget here before it exhausts the shop's.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use super::server::McpServer;
+use super::tools::ToolResult;
+use crate::cost::estimate_query_cost;
+use crate::logging::LogLevel;
+use crate::{from_js, to_js, StorefrontApi};
+
+const WINDOW_MS: f64 = 60_000.0;
+
+/// Limits applied to one server session. `0` turns a limit off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LimitOptions {
+    /// Calls to any one tool per minute.
+    pub tool_calls_per_minute: u32,
+    /// Tool calls per minute across all tools.
+    pub session_calls_per_minute: u32,
+    /// Most a single Storefront request made by a tool may cost.
+    pub max_query_cost: u32,
+    /// Size of the session's cost bucket.
+    pub cost_bucket_size: u32,
+    /// Cost points the bucket regains per second.
+    pub cost_restore_rate: u32,
+}
+
+impl Default for LimitOptions {
+    fn default() -> Self {
+        LimitOptions {
+            tool_calls_per_minute: 30,
+            session_calls_per_minute: 120,
+            max_query_cost: 1000,
+            cost_bucket_size: 2000,
+            cost_restore_rate: 100,
+        }
+    }
+}
+
+/// Why a call or request was refused.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitExceeded {
+    /// `"tool"`, `"session"`, `"queryCost"` or `"costBudget"`.
+    pub scope: &'static str,
+    pub limit: u32,
+    /// When a retry can succeed; `None` when it never will (a query over
+    /// `maxQueryCost`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    pub message: String,
+}
+
+impl LimitExceeded {
+    /// The structured `isError` result reported to the client.
+    pub fn tool_result(&self) -> ToolResult {
+        let mut result = ToolResult::error(self.message.clone());
+        result.structured_content = Some(serde_json::json!({
+            "error": "rate_limited",
+            "scope": self.scope,
+            "limit": self.limit,
+            "retryAfterMs": self.retry_after_ms,
+        }));
+        result
+    }
+}
+
+/// Usage so far, as reported by `McpServer.usage()`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub calls_last_minute: u32,
+    pub cost_available: u32,
+    pub total_cost: u64,
+    pub total_requests: u64,
+    pub refused: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Limiter {
+    options: LimitOptions,
+    tool_calls: BTreeMap<String, VecDeque<f64>>,
+    session_calls: VecDeque<f64>,
+    /// Cost available in the bucket and when it was last topped up.
+    bucket: Option<(f64, f64)>,
+    total_cost: u64,
+    total_requests: u64,
+    refused: u64,
+}
+
+/// Drops calls older than a minute and returns how long until the oldest
+/// remaining one leaves the window.
+fn prune(calls: &mut VecDeque<f64>, now: f64) -> f64 {
+    while calls.front().is_some_and(|time| now - time >= WINDOW_MS) {
+        calls.pop_front();
+    }
+    calls
+        .front()
+        .map_or(0.0, |oldest| WINDOW_MS - (now - oldest))
+}
+
+impl Limiter {
+    pub fn new(options: LimitOptions) -> Limiter {
+        Limiter {
+            options,
+            ..Limiter::default()
+        }
+    }
+
+    pub fn options(&self) -> &LimitOptions {
+        &self.options
+    }
+
+    /// Changes the limits, keeping the call history. The bucket is
+    /// refilled to the new size.
+    pub fn set_options(&mut self, options: LimitOptions) {
+        self.options = options;
+        self.bucket = None;
+    }
+
+    /// The bucket's current level after refilling for the time since the
+    /// last update.
+    fn available(&mut self, now: f64) -> f64 {
+        let size = f64::from(self.options.cost_bucket_size);
+        let (level, updated) = self.bucket.unwrap_or((size, now));
+        let elapsed = (now - updated).max(0.0) / 1000.0;
+        let level = (level + elapsed * f64::from(self.options.cost_restore_rate)).min(size);
+        self.bucket = Some((level, now));
+        level
+    }
+
+    fn refuse(&mut self, exceeded: LimitExceeded) -> Result<(), LimitExceeded> {
+        self.refused += 1;
+        Err(exceeded)
+    }
+
+    /// Counts a call to `tool` if the call limits and cost budget allow it.
+    pub fn begin_call(&mut self, tool: &str, now: f64) -> Result<(), LimitExceeded> {
+        let per_tool = self.options.tool_calls_per_minute;
+        let calls = self.tool_calls.entry(tool.to_string()).or_default();
+        let wait = prune(calls, now);
+        if per_tool > 0 && calls.len() >= per_tool as usize {
+            return self.refuse(LimitExceeded {
+                scope: "tool",
+                limit: per_tool,
+                retry_after_ms: Some(wait.ceil() as u64),
+                message: format!(
+                    "{tool} was called {per_tool} times in the last minute; try again in {}s",
+                    (wait / 1000.0).ceil()
+                ),
+            });
+        }
+
+        let per_session = self.options.session_calls_per_minute;
+        let wait = prune(&mut self.session_calls, now);
+        if per_session > 0 && self.session_calls.len() >= per_session as usize {
+            return self.refuse(LimitExceeded {
+                scope: "session",
+                limit: per_session,
+                retry_after_ms: Some(wait.ceil() as u64),
+                message: format!(
+                    "This session made {per_session} tool calls in the last minute; try again in {}s",
+                    (wait / 1000.0).ceil()
+                ),
+            });
+        }
+
+        if self.options.cost_bucket_size > 0 && self.available(now) < 1.0 {
+            let wait = self.refill_wait(1.0, now);
+            return self.refuse(self.budget_exhausted(wait));
+        }
+
+        self.tool_calls
+            .entry(tool.to_string())
+            .or_default()
+            .push_back(now);
+        self.session_calls.push_back(now);
+        Ok(())
+    }
+
+    /// Milliseconds until the bucket holds `cost`.
+    fn refill_wait(&mut self, cost: f64, now: f64) -> f64 {
+        let missing = cost - self.available(now);
+        let rate = f64::from(self.options.cost_restore_rate);
+        if missing <= 0.0 || rate <= 0.0 {
+            0.0
+        } else {
+            missing / rate * 1000.0
+        }
+    }
+
+    fn budget_exhausted(&self, wait: f64) -> LimitExceeded {
+        LimitExceeded {
+            scope: "costBudget",
+            limit: self.options.cost_bucket_size,
+            retry_after_ms: (self.options.cost_restore_rate > 0).then_some(wait.ceil() as u64),
+            message: format!(
+                "The session's Storefront API cost budget is spent; try again in {}s",
+                (wait / 1000.0).ceil()
+            ),
+        }
+    }
+
+    /// Draws a request's estimated cost from the bucket.
+    pub fn charge(&mut self, cost: u64, now: f64) -> Result<(), LimitExceeded> {
+        let max = self.options.max_query_cost;
+        if max > 0 && cost > u64::from(max) {
+            return self.refuse(LimitExceeded {
+                scope: "queryCost",
+                limit: max,
+                retry_after_ms: None,
+                message: format!(
+                    "The query's estimated cost of {cost} is over the limit of {max}; request fewer items"
+                ),
+            });
+        }
+        if self.options.cost_bucket_size > 0 {
+            // A request bigger than the whole bucket waits for a full one.
+            let needed = (cost as f64).min(f64::from(self.options.cost_bucket_size));
+            let available = self.available(now);
+            if available < needed {
+                let wait = self.refill_wait(needed, now);
+                return self.refuse(self.budget_exhausted(wait));
+            }
+            self.bucket = Some((available - needed, now));
+        }
+        self.total_cost = self.total_cost.saturating_add(cost);
+        self.total_requests += 1;
+        Ok(())
+    }
+
+    pub fn usage(&mut self, now: f64) -> Usage {
+        prune(&mut self.session_calls, now);
+        Usage {
+            calls_last_minute: self.session_calls.len() as u32,
+            cost_available: self.available(now) as u32,
+            total_cost: self.total_cost,
+            total_requests: self.total_requests,
+            refused: self.refused,
+        }
+    }
+}
+
+impl McpServer {
+    /// A copy of `api` whose requests are priced and charged to this
+    /// session's cost budget.
+    pub(crate) fn metered(&self, mut api: StorefrontApi) -> StorefrontApi {
+        let limiter = self.limiter.clone();
+        let log = self.log_sink();
+        api.request_guard = Some(Rc::new(move |query, variables| {
+            let cost = estimate_query_cost(query, variables);
+            limiter
+                .borrow_mut()
+                .charge(cost, js_sys::Date::now())
+                .map_err(|exceeded| {
+                    log(
+                        LogLevel::Warning,
+                        "limits",
+                        serde_json::json!({ "cost": cost, "message": exceeded.message }),
+                    );
+                    JsValue::from_str(&exceeded.message)
+                })
+        }));
+        api
+    }
+}
+
+#[wasm_bindgen]
+impl McpServer {
+    /// Replaces the session's limits. `options` is `{ toolCallsPerMinute,
+    /// sessionCallsPerMinute, maxQueryCost, costBucketSize,
+    /// costRestoreRate }`; omitted fields take their defaults (30, 120,
+    /// 1000, 2000 and 100 per second) and `0` turns a limit off.
+    #[wasm_bindgen]
+    pub fn set_limits(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "Partial<LimitOptions>")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        let options: LimitOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => LimitOptions::default(),
+        };
+        self.limiter.borrow_mut().set_options(options);
+        Ok(())
+    }
+
+    /// The session's current limits.
+    #[wasm_bindgen(unchecked_return_type = "LimitOptions")]
+    pub fn limits(&self) -> Result<JsValue, JsValue> {
+        to_js(self.limiter.borrow().options())
+    }
+
+    /// `{ callsLastMinute, costAvailable, totalCost, totalRequests,
+    /// refused }` for this session.
+    #[wasm_bindgen(unchecked_return_type = "Usage")]
+    pub fn usage(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.limiter.borrow_mut().usage(js_sys::Date::now()))
+    }
+}
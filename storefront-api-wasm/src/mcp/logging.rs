@@ -0,0 +1,58 @@
+//! `logging/setLevel` and `notifications/message`: the client's log events
+//! and the server's own warnings, forwarded to the host.
+
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+
+use super::protocol::{notification, RpcError};
+use super::server::McpServer;
+use crate::logging::{LogLevel, LogSink};
+use crate::redact::redacted;
+
+/// Level used until the client sends `logging/setLevel`.
+pub const DEFAULT_LOG_LEVEL: LogLevel = LogLevel::Info;
+
+#[derive(Deserialize)]
+struct SetLevelParams {
+    level: LogLevel,
+}
+
+impl McpServer {
+    /// `logging/setLevel`.
+    pub(crate) fn set_log_level(&self, params: Value) -> Result<Value, RpcError> {
+        let params: SetLevelParams = serde_json::from_value(params)
+            .map_err(|error| RpcError::invalid_params(error.to_string()))?;
+        self.log_level.set(params.level);
+        Ok(serde_json::json!({}))
+    }
+
+    /// A sink that sends events at or above the session's level to the
+    /// client as `notifications/message`, redacted (see `redact`). It
+    /// shares the sender and level cells instead of borrowing the server,
+    /// so client copies can keep it.
+    pub(crate) fn log_sink(&self) -> LogSink {
+        let sender = self.sender.clone();
+        let minimum = self.log_level.clone();
+        Rc::new(move |level, logger, data| {
+            if level < minimum.get() {
+                return;
+            }
+            if let Some(sender) = sender.borrow().as_ref() {
+                let data = redacted(&data, &[]);
+                let message = notification(
+                    "notifications/message",
+                    serde_json::json!({ "level": level, "logger": logger, "data": data }),
+                );
+                let _ = sender.call1(&JsValue::NULL, &JsValue::from_str(&message.to_string()));
+            }
+        })
+    }
+
+    /// Logs an event from the server itself.
+    pub fn log(&self, level: LogLevel, logger: &str, data: Value) {
+        (self.log_sink())(level, logger, data);
+    }
+}
@@ -0,0 +1,442 @@
+//! Tools generated from GraphQL operations.
+//!
+//! `register_operation` takes a named query or mutation, reads its variable
+//! definitions and exposes it as a tool whose input schema is derived from
+//! the variable types, so store-specific tools need no handler code.
+
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use super::server::McpServer;
+use super::tools::{
+    ToolContext, ToolDefinition, ToolError, ToolFuture, ToolHandler, ToolResult, Toolset,
+};
+use crate::from_js;
+use crate::graphql::{operation_starts, tokenize, Token};
+
+/// A GraphQL input type reference.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeRef {
+    Named(String),
+    List(Box<TypeRef>),
+    NonNull(Box<TypeRef>),
+}
+
+impl TypeRef {
+    pub fn is_non_null(&self) -> bool {
+        matches!(self, TypeRef::NonNull(_))
+    }
+
+    /// JSON Schema for values of this type. Nullability is expressed by
+    /// `required` on the enclosing object, not here.
+    pub fn json_schema(&self) -> Value {
+        match self {
+            TypeRef::NonNull(inner) => inner.json_schema(),
+            TypeRef::List(item) => serde_json::json!({
+                "type": "array",
+                "items": item.json_schema(),
+            }),
+            TypeRef::Named(name) => scalar_schema(name),
+        }
+    }
+}
+
+impl std::fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeRef::Named(name) => write!(f, "{name}"),
+            TypeRef::List(item) => write!(f, "[{item}]"),
+            TypeRef::NonNull(inner) => write!(f, "{inner}!"),
+        }
+    }
+}
+
+/// Schema for a named type. Storefront scalars map to their JSON
+/// representation; enums and input objects are described by name only.
+fn scalar_schema(name: &str) -> Value {
+    match name {
+        "String" | "ID" | "HTML" | "Color" => serde_json::json!({ "type": "string" }),
+        // Decimal and UnsignedInt64 travel as strings to keep precision.
+        "Decimal" | "UnsignedInt64" => {
+            serde_json::json!({ "type": "string", "pattern": "^-?[0-9]+(\\.[0-9]+)?$" })
+        }
+        "URL" => serde_json::json!({ "type": "string", "format": "uri" }),
+        "DateTime" => serde_json::json!({ "type": "string", "format": "date-time" }),
+        "Int" => serde_json::json!({ "type": "integer" }),
+        "Float" => serde_json::json!({ "type": "number" }),
+        "Boolean" => serde_json::json!({ "type": "boolean" }),
+        "JSON" => serde_json::json!({}),
+        other => serde_json::json!({ "description": format!("GraphQL {other}") }),
+    }
+}
+
+/// A `$name: Type = default` variable definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDefinition {
+    pub name: String,
+    pub type_ref: TypeRef,
+    pub default: Option<Value>,
+}
+
+impl VariableDefinition {
+    /// Whether a tool call has to supply the variable.
+    pub fn is_required(&self) -> bool {
+        self.type_ref.is_non_null() && self.default.is_none()
+    }
+}
+
+/// The parts of an operation a tool is generated from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationSignature {
+    /// `query` or `mutation`.
+    pub kind: String,
+    pub name: Option<String>,
+    pub variables: Vec<VariableDefinition>,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, punctuator: char) -> bool {
+        if self.peek() == Some(&Token::Punctuator(punctuator)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punctuator: char) -> Result<(), String> {
+        if self.eat(punctuator) {
+            Ok(())
+        } else {
+            Err(format!("Expected `{punctuator}`"))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(name),
+            _ => Err("Expected a name".to_string()),
+        }
+    }
+
+    fn type_ref(&mut self) -> Result<TypeRef, String> {
+        let type_ref = if self.eat('[') {
+            let item = self.type_ref()?;
+            self.expect(']')?;
+            TypeRef::List(Box::new(item))
+        } else {
+            TypeRef::Named(self.name()?)
+        };
+        Ok(if self.eat('!') {
+            TypeRef::NonNull(Box::new(type_ref))
+        } else {
+            type_ref
+        })
+    }
+
+    /// A constant value, converted to JSON. Enum values become strings.
+    fn value(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Number(number)) => {
+                serde_json::from_str(&number).map_err(|_| format!("Invalid number {number}"))
+            }
+            Some(Token::String(string)) => Ok(Value::String(string)),
+            Some(Token::Name(name)) => Ok(match name.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                _ => Value::String(name),
+            }),
+            Some(Token::Punctuator('[')) => {
+                let mut items = Vec::new();
+                while !self.eat(']') {
+                    items.push(self.value()?);
+                }
+                Ok(Value::Array(items))
+            }
+            Some(Token::Punctuator('{')) => {
+                let mut fields = Map::new();
+                while !self.eat('}') {
+                    let name = self.name()?;
+                    self.expect(':')?;
+                    fields.insert(name, self.value()?);
+                }
+                Ok(Value::Object(fields))
+            }
+            _ => Err("Expected a constant value".to_string()),
+        }
+    }
+
+    /// Skips `@directive(arguments)`s.
+    fn skip_directives(&mut self) -> Result<(), String> {
+        while self.eat('@') {
+            self.name()?;
+            if self.eat('(') {
+                let mut depth = 1;
+                while depth > 0 {
+                    match self.next() {
+                        Some(Token::Punctuator('(')) => depth += 1,
+                        Some(Token::Punctuator(')')) => depth -= 1,
+                        Some(_) => {}
+                        None => return Err("Unbalanced directive arguments".to_string()),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn variable_definitions(&mut self) -> Result<Vec<VariableDefinition>, String> {
+        let mut variables = Vec::new();
+        if !self.eat('(') {
+            return Ok(variables);
+        }
+        while !self.eat(')') {
+            self.expect('$')?;
+            let name = self.name()?;
+            self.expect(':')?;
+            let type_ref = self.type_ref()?;
+            let default = if self.eat('=') {
+                Some(self.value()?)
+            } else {
+                None
+            };
+            self.skip_directives()?;
+            variables.push(VariableDefinition {
+                name,
+                type_ref,
+                default,
+            });
+        }
+        Ok(variables)
+    }
+}
+
+/// Reads the kind, name and variable definitions of the single operation
+/// in `document`. Fragment definitions may accompany it.
+pub fn parse_operation(document: &str) -> Result<OperationSignature, String> {
+    let tokens = tokenize(document)?;
+    let operations = operation_starts(&tokens);
+    let start = match operations.as_slice() {
+        [start] => *start,
+        [] => return Err("The document has no query or mutation".to_string()),
+        _ => return Err("The document must contain exactly one operation".to_string()),
+    };
+
+    let mut parser = Parser {
+        tokens,
+        position: start,
+    };
+    let kind = parser.name()?;
+    if kind == "subscription" {
+        return Err("Subscriptions can't be exposed as tools".to_string());
+    }
+    let name = match parser.peek() {
+        Some(Token::Name(_)) => Some(parser.name()?),
+        _ => None,
+    };
+    let variables = parser.variable_definitions()?;
+    Ok(OperationSignature {
+        kind,
+        name,
+        variables,
+    })
+}
+
+/// `getProductsByVendor` → `get_products_by_vendor`.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if index > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+pub(crate) fn valid_tool_name(name: &str) -> bool {
+    (1..=64).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// How a registered operation is presented as a tool.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OperationToolOptions {
+    /// Tool name; defaults to the operation name in snake_case.
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Per-variable descriptions for the input schema.
+    pub variable_descriptions: Map<String, Value>,
+    /// Toolset the tool belongs to; defaults to `custom`.
+    pub toolset: Option<String>,
+}
+
+/// Runs a registered operation with the call's arguments as variables.
+struct OperationHandler {
+    document: String,
+    variables: Vec<VariableDefinition>,
+}
+
+impl ToolHandler for OperationHandler {
+    fn call<'a>(
+        &'a self,
+        server: &'a McpServer,
+        context: ToolContext,
+        arguments: Value,
+    ) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let Value::Object(mut arguments) = arguments else {
+                return Err(ToolError::new("Arguments must be an object"));
+            };
+            if let Some(unknown) = arguments.keys().find(|name| {
+                !self
+                    .variables
+                    .iter()
+                    .any(|variable| &variable.name == *name)
+            }) {
+                return Err(ToolError::new(format!("Unknown argument: {unknown}")));
+            }
+            let mut variables = Map::new();
+            for variable in &self.variables {
+                match arguments.remove(&variable.name) {
+                    Some(Value::Null) | None if variable.is_required() => {
+                        return Err(ToolError::new(format!(
+                            "Missing required argument: {} ({})",
+                            variable.name, variable.type_ref
+                        )));
+                    }
+                    Some(value) => {
+                        variables.insert(variable.name.clone(), value);
+                    }
+                    None => {}
+                }
+            }
+            let data = context
+                .api(server)?
+                .execute(&self.document, Value::Object(variables))
+                .await?;
+            Ok(ToolResult::json(data))
+        })
+    }
+}
+
+/// Builds the tool definition and handler for `document`.
+pub fn operation_tool(
+    document: &str,
+    options: OperationToolOptions,
+) -> Result<(Toolset, ToolDefinition, Rc<dyn ToolHandler>), String> {
+    let signature = parse_operation(document)?;
+    let toolset = match &options.toolset {
+        Some(name) => Toolset::parse(name)?,
+        None => Toolset::Custom,
+    };
+    let name = options
+        .name
+        .or_else(|| signature.name.as_deref().map(snake_case))
+        .ok_or("Anonymous operations need a tool name")?;
+    if !valid_tool_name(&name) {
+        return Err(format!("Invalid tool name: {name}"));
+    }
+
+    let mut properties = Map::new();
+    for variable in &signature.variables {
+        let mut schema = variable.type_ref.json_schema();
+        if let Some(default) = &variable.default {
+            schema["default"] = default.clone();
+        }
+        if let Some(description) = options.variable_descriptions.get(&variable.name) {
+            schema["description"] = description.clone();
+        }
+        properties.insert(variable.name.clone(), schema);
+    }
+    let required: Vec<&str> = signature
+        .variables
+        .iter()
+        .filter(|variable| variable.is_required())
+        .map(|variable| variable.name.as_str())
+        .collect();
+    let mutation = signature.kind == "mutation";
+
+    let definition = ToolDefinition {
+        name,
+        title: options.title,
+        description: options.description.unwrap_or_else(|| {
+            format!(
+                "Runs the {} GraphQL {} against the Storefront API.",
+                signature.name.as_deref().unwrap_or("anonymous"),
+                signature.kind
+            )
+        }),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        }),
+        output_schema: None,
+        annotations: Some(serde_json::json!({ "readOnlyHint": !mutation })),
+    };
+    let handler = OperationHandler {
+        document: document.to_string(),
+        variables: signature.variables,
+    };
+    Ok((toolset, definition, Rc::new(handler)))
+}
+
+#[wasm_bindgen]
+impl McpServer {
+    /// Exposes a GraphQL query or mutation as a tool. The input schema is
+    /// derived from the operation's variable definitions, and a call's
+    /// arguments are sent as its variables. `options` is `{ name?, title?,
+    /// description?, variableDescriptions?, toolset? }`; the tool name
+    /// defaults to the operation name in snake_case and the toolset to
+    /// `"custom"`. Returns the tool name.
+    #[wasm_bindgen]
+    pub fn register_operation(
+        &self,
+        document: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "OperationToolOptions")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<String, JsValue> {
+        let options: OperationToolOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => OperationToolOptions::default(),
+        };
+        let (toolset, definition, handler) =
+            operation_tool(&document, options).map_err(|error| JsValue::from_str(&error))?;
+        let name = definition.name.clone();
+        self.tools
+            .borrow_mut()
+            .register(toolset, definition, handler);
+        self.tools_changed();
+        Ok(name)
+    }
+}
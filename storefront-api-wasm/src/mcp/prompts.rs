@@ -0,0 +1,287 @@
+//! Prompt templates for common commerce workflows, pre-filled with live
+//! Storefront data.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::catalog::ProductSummary;
+use super::protocol::RpcError;
+use super::server::McpServer;
+use super::tools::markdown_table;
+use crate::money::DEFAULT_LOCALE;
+use crate::pagination::PageArgs;
+use crate::product::ProductOptions;
+use crate::search::ProductQuery;
+
+/// Candidate products fetched for `recommend_products`.
+const RECOMMENDATION_CANDIDATES: u32 = 20;
+
+/// An argument a prompt accepts. Prompt arguments are always strings.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptArgument {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub required: bool,
+}
+
+/// An entry of `prompts/list`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptDefinition {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Deserialize)]
+struct GetPromptParams {
+    name: String,
+    #[serde(default)]
+    arguments: BTreeMap<String, String>,
+}
+
+/// The built-in prompts, in listing order.
+pub fn builtin_prompts() -> Vec<PromptDefinition> {
+    vec![
+        PromptDefinition {
+            name: "recommend_products",
+            title: "Recommend products",
+            description: "Recommend products for an occasion, within an optional budget, from the store's best sellers.",
+            arguments: vec![
+                PromptArgument {
+                    name: "occasion",
+                    description: "What the products are for, e.g. \"a housewarming gift\"",
+                    required: true,
+                },
+                PromptArgument {
+                    name: "budget",
+                    description: "Most the shopper wants to spend per item, e.g. \"50\"",
+                    required: false,
+                },
+            ],
+        },
+        PromptDefinition {
+            name: "write_product_description",
+            title: "Write a product description",
+            description: "Draft a new description for a product from its current details.",
+            arguments: vec![
+                PromptArgument {
+                    name: "handle",
+                    description: "Product handle, e.g. \"classic-tee\"",
+                    required: true,
+                },
+                PromptArgument {
+                    name: "tone",
+                    description: "Voice to write in, e.g. \"playful\" (defaults to the store's existing voice)",
+                    required: false,
+                },
+            ],
+        },
+    ]
+}
+
+/// Reads a budget such as `"50"`, `"$49.99"` or `"1,200 EUR"` as a number.
+fn parse_budget(budget: &str) -> Option<f64> {
+    let digits: String = budget
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    digits.parse().ok().filter(|value: &f64| *value > 0.0)
+}
+
+fn user_message(text: String) -> Value {
+    serde_json::json!({
+        "role": "user",
+        "content": { "type": "text", "text": text }
+    })
+}
+
+impl McpServer {
+    /// `prompts/get`: renders a built-in prompt with its arguments.
+    pub(crate) async fn get_prompt(&self, params: Value) -> Result<Value, RpcError> {
+        let params: GetPromptParams = serde_json::from_value(params)
+            .map_err(|error| RpcError::invalid_params(error.to_string()))?;
+        let definition = builtin_prompts()
+            .into_iter()
+            .find(|prompt| prompt.name == params.name)
+            .ok_or_else(|| RpcError::invalid_params(format!("Unknown prompt: {}", params.name)))?;
+        let arguments = params.arguments;
+        if let Some(missing) = definition.arguments.iter().find(|argument| {
+            argument.required
+                && arguments
+                    .get(argument.name)
+                    .is_none_or(|value| value.trim().is_empty())
+        }) {
+            return Err(RpcError::invalid_params(format!(
+                "Missing required argument: {}",
+                missing.name
+            )));
+        }
+        let argument = |name: &str| {
+            arguments
+                .get(name)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+
+        let text = match definition.name {
+            "recommend_products" => {
+                self.recommend_products_prompt(
+                    argument("occasion").unwrap_or_default(),
+                    argument("budget"),
+                )
+                .await?
+            }
+            _ => {
+                self.product_description_prompt(
+                    argument("handle").unwrap_or_default(),
+                    argument("tone"),
+                )
+                .await?
+            }
+        };
+        Ok(serde_json::json!({
+            "description": definition.description,
+            "messages": [user_message(text)],
+        }))
+    }
+
+    async fn recommend_products_prompt(
+        &self,
+        occasion: &str,
+        budget: Option<&str>,
+    ) -> Result<String, RpcError> {
+        let max_price = match budget {
+            Some(budget) => Some(
+                parse_budget(budget)
+                    .ok_or_else(|| RpcError::invalid_params(format!("Invalid budget: {budget}")))?,
+            ),
+            None => None,
+        };
+        let query = ProductQuery {
+            max_price,
+            available_for_sale: Some(true),
+            ..ProductQuery::default()
+        }
+        .build();
+        let api = self.api();
+        let products = api
+            .fetch_search_products(
+                &query,
+                Some("BEST_SELLING".to_string()),
+                false,
+                &PageArgs::forward(RECOMMENDATION_CANDIDATES, None),
+            )
+            .await?;
+        let candidates: Vec<ProductSummary> = products
+            .nodes()
+            .map(|product| ProductSummary::new(&api, product))
+            .collect();
+
+        let mut text = format!("A shopper is looking for products for {occasion}");
+        if let Some(budget) = max_price {
+            text.push_str(&format!(", spending at most {budget} per item"));
+        }
+        text.push_str(".\n\n");
+        if candidates.is_empty() {
+            text.push_str(
+                "No available products match that budget. Tell the shopper so and suggest \
+                 what they could look for instead.",
+            );
+            return Ok(text);
+        }
+        text.push_str("These in-stock products are the store's best sellers in that range:\n\n");
+        text.push_str(&markdown_table(
+            &["Product", "Handle", "Vendor", "Price"],
+            candidates
+                .iter()
+                .map(|product| {
+                    vec![
+                        product.title.clone(),
+                        product.handle.clone(),
+                        product.vendor.clone().unwrap_or_default(),
+                        product.price.clone(),
+                    ]
+                })
+                .collect(),
+        ));
+        text.push_str(
+            "\nRecommend the three to five that suit the occasion best. For each, give its \
+             title, price and one sentence on why it fits. Only recommend products from the \
+             list above.",
+        );
+        Ok(text)
+    }
+
+    async fn product_description_prompt(
+        &self,
+        handle: &str,
+        tone: Option<&str>,
+    ) -> Result<String, RpcError> {
+        let key = handle.to_string();
+        let product = self
+            .api()
+            .fetch_products(std::slice::from_ref(&key), &ProductOptions::default())
+            .await?
+            .pop()
+            .flatten()
+            .ok_or_else(|| RpcError::invalid_params(format!("No product found for {handle}")))?;
+
+        let mut text = format!(
+            "Write a product description for \"{}\".\n\n## Product details\n\n",
+            product.title
+        );
+        if let Some(vendor) = product.vendor.as_ref().filter(|vendor| !vendor.is_empty()) {
+            text.push_str(&format!("- Vendor: {vendor}\n"));
+        }
+        if let Some(kind) = product
+            .product_type
+            .as_ref()
+            .filter(|kind| !kind.is_empty())
+        {
+            text.push_str(&format!("- Type: {kind}\n"));
+        }
+        text.push_str(&format!(
+            "- Price: {}\n",
+            product.price_range.min_variant_price.format(DEFAULT_LOCALE)
+        ));
+        if !product.tags.is_empty() {
+            text.push_str(&format!("- Tags: {}\n", product.tags.join(", ")));
+        }
+        for option in &product.options {
+            let values: Vec<&str> = option
+                .option_values
+                .iter()
+                .map(|value| value.name.as_str())
+                .collect();
+            text.push_str(&format!("- {}: {}\n", option.name, values.join(", ")));
+        }
+        match product
+            .description
+            .as_ref()
+            .filter(|d| !d.trim().is_empty())
+        {
+            Some(description) => {
+                text.push_str(&format!("\n## Current description\n\n{description}\n"))
+            }
+            None => text.push_str("\nThe product has no description yet.\n"),
+        }
+
+        text.push_str("\nWrite two or three short paragraphs");
+        match tone {
+            Some(tone) => text.push_str(&format!(" in a {tone} tone")),
+            None if product.description.is_some() => {
+                text.push_str(" in the voice of the current description")
+            }
+            None => {}
+        }
+        text.push_str(
+            ". Stick to the details above and don't invent materials, measurements or claims.",
+        );
+        Ok(text)
+    }
+}
@@ -0,0 +1,249 @@
+//! JSON-RPC 2.0 message types and MCP protocol constants.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The latest MCP revision this server implements.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Every revision the server can speak, newest first.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &[PROTOCOL_VERSION, "2025-03-26", "2024-11-05"];
+
+/// The parts of the protocol that differ between the supported revisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Revision {
+    /// `title` on tools, prompts, resources and resource templates.
+    pub titles: bool,
+    /// Tool `outputSchema` and `structuredContent` results.
+    pub structured_output: bool,
+    /// Tool `annotations` (`readOnlyHint` and friends).
+    pub tool_annotations: bool,
+    /// The `completions` server capability.
+    pub completions_capability: bool,
+    /// `message` on `notifications/progress`.
+    pub progress_messages: bool,
+}
+
+impl Revision {
+    /// What `version` supports. Unknown versions are treated as the latest.
+    pub fn of(version: &str) -> Revision {
+        let since_2025_03 = version != "2024-11-05";
+        let since_2025_06 = since_2025_03 && version != "2025-03-26";
+        Revision {
+            titles: since_2025_06,
+            structured_output: since_2025_06,
+            tool_annotations: since_2025_03,
+            completions_capability: since_2025_03,
+            progress_messages: since_2025_03,
+        }
+    }
+
+    /// Rewrites the result of `method` for this revision, dropping members
+    /// it doesn't define. Structured tool output is replaced by its JSON as
+    /// an extra text block, so older clients still get the data.
+    pub fn downgrade_result(&self, method: &str, result: &mut Value) {
+        let list = match method {
+            "tools/list" => "tools",
+            "prompts/list" => "prompts",
+            "resources/list" => "resources",
+            "resources/templates/list" => "resourceTemplates",
+            "tools/call" => {
+                self.downgrade_tool_result(result);
+                return;
+            }
+            _ => return,
+        };
+        let Some(entries) = result.get_mut(list).and_then(Value::as_array_mut) else {
+            return;
+        };
+        for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+            if !self.titles {
+                entry.remove("title");
+            }
+            if !self.structured_output {
+                entry.remove("outputSchema");
+            }
+            if !self.tool_annotations {
+                entry.remove("annotations");
+            }
+        }
+    }
+
+    fn downgrade_tool_result(&self, result: &mut Value) {
+        if self.structured_output {
+            return;
+        }
+        let Some(object) = result.as_object_mut() else {
+            return;
+        };
+        let Some(structured) = object.remove("structuredContent") else {
+            return;
+        };
+        let json = serde_json::to_string_pretty(&structured).unwrap_or_default();
+        let Some(content) = object.get_mut("content").and_then(Value::as_array_mut) else {
+            return;
+        };
+        if !content.iter().any(|block| block["text"] == json.as_str()) {
+            content.push(serde_json::json!({ "type": "text", "text": json }));
+        }
+    }
+}
+
+/// Picks the revision to answer `initialize` with: the client's when we
+/// support it, otherwise our latest (and the client decides whether to
+/// disconnect). Older revisions are served with the newer features
+/// stripped; see `Revision`.
+pub fn negotiate_version(requested: Option<&str>) -> &'static str {
+    requested
+        .and_then(|requested| {
+            SUPPORTED_PROTOCOL_VERSIONS
+                .iter()
+                .find(|version| **version == requested)
+        })
+        .copied()
+        .unwrap_or(PROTOCOL_VERSION)
+}
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// MCP's code for a `resources/read` of an unknown URI.
+pub const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// A JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> RpcError {
+        RpcError::new(INVALID_PARAMS, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> RpcError {
+        RpcError::new(INTERNAL_ERROR, message)
+    }
+
+    pub fn with_data(mut self, data: Value) -> RpcError {
+        self.data = Some(data);
+        self
+    }
+}
+
+/// Storefront API failures surface as internal errors carrying the client's
+/// message.
+impl From<wasm_bindgen::JsValue> for RpcError {
+    fn from(error: wasm_bindgen::JsValue) -> RpcError {
+        RpcError::internal(error.as_string().unwrap_or_else(|| format!("{error:?}")))
+    }
+}
+
+/// An incoming message, classified by which members it carries.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Request {
+        id: Value,
+        method: String,
+        params: Value,
+    },
+    Notification {
+        method: String,
+        params: Value,
+    },
+    Response {
+        id: Value,
+        result: Result<Value, RpcError>,
+    },
+}
+
+impl Message {
+    /// Classifies one JSON-RPC message object. Fails with the error to send
+    /// back (and the request ID, when one could be read).
+    pub fn parse(value: Value) -> Result<Message, (Value, RpcError)> {
+        let Value::Object(mut object) = value else {
+            return Err((
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, "Message must be an object"),
+            ));
+        };
+        let id = object.remove("id");
+        if object.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+            return Err((
+                id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""),
+            ));
+        }
+
+        match object.remove("method") {
+            Some(Value::String(method)) => {
+                let params = object.remove("params").unwrap_or(Value::Null);
+                Ok(match id {
+                    Some(id) => Message::Request { id, method, params },
+                    None => Message::Notification { method, params },
+                })
+            }
+            Some(_) => Err((
+                id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "method must be a string"),
+            )),
+            None => {
+                let id = id.unwrap_or(Value::Null);
+                if let Some(error) = object.remove("error") {
+                    let error = serde_json::from_value(error).unwrap_or_else(|_| {
+                        RpcError::new(INTERNAL_ERROR, "Malformed error response")
+                    });
+                    Ok(Message::Response {
+                        id,
+                        result: Err(error),
+                    })
+                } else if let Some(result) = object.remove("result") {
+                    Ok(Message::Response {
+                        id,
+                        result: Ok(result),
+                    })
+                } else {
+                    Err((
+                        id,
+                        RpcError::new(INVALID_REQUEST, "Message has no method, result or error"),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Serializes a response to a request.
+pub fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    }
+}
+
+/// Serializes a server-initiated request.
+pub fn request(id: Value, method: &str, params: Value) -> Value {
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+/// Serializes a notification.
+pub fn notification(method: &str, params: Value) -> Value {
+    if params.is_null() {
+        serde_json::json!({ "jsonrpc": "2.0", "method": method })
+    } else {
+        serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+}
@@ -0,0 +1,302 @@
+//! Catalog resources: `shopify://product/{handle}` and
+//! `shopify://collection/{handle}`, listed directly and as URI templates,
+//! with change notifications for subscribed resources found by polling.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use super::audit::AUDIT_LOG_URI;
+use super::protocol::{RpcError, RESOURCE_NOT_FOUND};
+use super::server::McpServer;
+use crate::collection::CollectionOptions;
+use crate::pagination::PageArgs;
+use crate::product::ProductOptions;
+use crate::types::Connection;
+
+/// Products (and collections) listed per `resources/list` page.
+const RESOURCE_PAGE_SIZE: u32 = 100;
+
+/// Products included when a collection resource is read.
+const COLLECTION_RESOURCE_PRODUCTS: u32 = 50;
+
+const LIST_RESOURCES_QUERY: &str = r#"
+    query listResources($first: Int!, $after: String, $withCollections: Boolean!) {
+        collections(first: $first) @include(if: $withCollections) {
+            edges { node { handle title } }
+        }
+        products(first: $first, after: $after, sortKey: BEST_SELLING) {
+            edges { node { handle title } }
+            pageInfo { hasNextPage hasPreviousPage endCursor }
+        }
+    }
+"#;
+
+const PRODUCT_PREFIX: &str = "shopify://product/";
+const COLLECTION_PREFIX: &str = "shopify://collection/";
+
+pub const PRODUCT_TEMPLATE: &str = "shopify://product/{handle}";
+pub const COLLECTION_TEMPLATE: &str = "shopify://collection/{handle}";
+
+/// A resource URI this server understands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResourceUri {
+    Product(String),
+    Collection(String),
+}
+
+impl ResourceUri {
+    pub fn parse(uri: &str) -> Option<ResourceUri> {
+        let (kind, handle): (fn(String) -> ResourceUri, &str) =
+            if let Some(handle) = uri.strip_prefix(PRODUCT_PREFIX) {
+                (ResourceUri::Product, handle)
+            } else if let Some(handle) = uri.strip_prefix(COLLECTION_PREFIX) {
+                (ResourceUri::Collection, handle)
+            } else {
+                return None;
+            };
+        let handle = handle.trim_end_matches('/');
+        (!handle.is_empty() && !handle.contains('/')).then(|| kind(handle.to_string()))
+    }
+}
+
+/// An entry of `resources/list`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    pub title: String,
+    pub mime_type: &'static str,
+}
+
+/// An entry of `resources/templates/list`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    pub uri_template: &'static str,
+    pub name: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub mime_type: &'static str,
+}
+
+/// The resource URI templates, whose `handle` the client can complete with
+/// `completion/complete`.
+pub fn resource_templates() -> Vec<ResourceTemplate> {
+    vec![
+        ResourceTemplate {
+            uri_template: PRODUCT_TEMPLATE,
+            name: "product",
+            title: "Product",
+            description: "A product by handle, with its variants, options and prices.",
+            mime_type: "application/json",
+        },
+        ResourceTemplate {
+            uri_template: COLLECTION_TEMPLATE,
+            name: "collection",
+            title: "Collection",
+            description: "A collection by handle, with its first products.",
+            mime_type: "application/json",
+        },
+    ]
+}
+
+#[derive(Deserialize)]
+struct Listed {
+    handle: String,
+    title: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceUriParams {
+    uri: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ListParams {
+    cursor: Option<String>,
+}
+
+fn content_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl McpServer {
+    /// `resources/list`: collections on the first page, then products by
+    /// best-selling, a page at a time.
+    pub(crate) async fn list_resources(&self, params: Value) -> Result<Value, RpcError> {
+        let params: ListParams = serde_json::from_value(params).unwrap_or_default();
+        let first_page = params.cursor.is_none();
+        let variables = serde_json::json!({
+            "first": RESOURCE_PAGE_SIZE,
+            "after": params.cursor,
+            "withCollections": first_page,
+        });
+        let mut data = self
+            .api()
+            .execute(LIST_RESOURCES_QUERY, variables)
+            .await
+            .map_err(RpcError::from)?;
+
+        let mut resources = Vec::new();
+        if first_page {
+            resources.extend(self.audit_resource());
+            let collections: Connection<Listed> =
+                serde_json::from_value(data["collections"].take()).unwrap_or_default();
+            resources.extend(collections.edges.into_iter().map(|edge| Resource {
+                uri: format!("{COLLECTION_PREFIX}{}", edge.node.handle),
+                name: edge.node.handle,
+                title: format!("Collection: {}", edge.node.title),
+                mime_type: "application/json",
+            }));
+        }
+        let products: Connection<Listed> = serde_json::from_value(data["products"].take())
+            .map_err(|error| RpcError::internal(error.to_string()))?;
+        let page_info = products.page_info.unwrap_or_default();
+        resources.extend(products.edges.into_iter().map(|edge| Resource {
+            uri: format!("{PRODUCT_PREFIX}{}", edge.node.handle),
+            name: edge.node.handle,
+            title: format!("Product: {}", edge.node.title),
+            mime_type: "application/json",
+        }));
+
+        let mut result = serde_json::json!({ "resources": resources });
+        if page_info.has_next_page {
+            result["nextCursor"] = serde_json::json!(page_info.end_cursor);
+        }
+        Ok(result)
+    }
+
+    /// `resources/read`.
+    pub(crate) async fn read_resource(&self, params: Value) -> Result<Value, RpcError> {
+        let params: ResourceUriParams = serde_json::from_value(params)
+            .map_err(|error| RpcError::invalid_params(error.to_string()))?;
+        let text = self.resource_text(&params.uri).await?;
+        Ok(serde_json::json!({
+            "contents": [{
+                "uri": params.uri,
+                "mimeType": "application/json",
+                "text": text,
+            }]
+        }))
+    }
+
+    /// Fetches a resource and renders it as pretty JSON.
+    async fn resource_text(&self, uri: &str) -> Result<String, RpcError> {
+        let not_found = || {
+            RpcError::new(RESOURCE_NOT_FOUND, format!("Resource not found: {uri}"))
+                .with_data(serde_json::json!({ "uri": uri }))
+        };
+        if uri == AUDIT_LOG_URI {
+            let enabled = self.audit.borrow().enabled();
+            return if enabled {
+                Ok(self.audit_log_text())
+            } else {
+                Err(not_found())
+            };
+        }
+        let value = match ResourceUri::parse(uri).ok_or_else(not_found)? {
+            ResourceUri::Product(handle) => {
+                let product = self
+                    .api()
+                    .fetch_products(&[handle], &ProductOptions::default())
+                    .await
+                    .map_err(RpcError::from)?
+                    .pop()
+                    .flatten()
+                    .ok_or_else(not_found)?;
+                serde_json::to_value(product)
+            }
+            ResourceUri::Collection(handle) => {
+                let page = self
+                    .api()
+                    .fetch_collection_page(
+                        &handle,
+                        &PageArgs::forward(COLLECTION_RESOURCE_PRODUCTS, None),
+                        &CollectionOptions::default(),
+                    )
+                    .await
+                    .map_err(RpcError::from)?
+                    .ok_or_else(not_found)?;
+                serde_json::to_value(page.collection)
+            }
+        };
+        let value = value.map_err(|error| RpcError::internal(error.to_string()))?;
+        Ok(serde_json::to_string_pretty(&value).unwrap_or_default())
+    }
+
+    /// `resources/subscribe`: remembers the resource's current content so
+    /// `poll_subscriptions` can tell when it changes.
+    pub(crate) async fn subscribe_resource(&self, params: Value) -> Result<Value, RpcError> {
+        let params: ResourceUriParams = serde_json::from_value(params)
+            .map_err(|error| RpcError::invalid_params(error.to_string()))?;
+        let hash = content_hash(&self.resource_text(&params.uri).await?);
+        self.session
+            .borrow_mut()
+            .subscriptions
+            .insert(params.uri, hash);
+        Ok(serde_json::json!({}))
+    }
+
+    /// `resources/unsubscribe`.
+    pub(crate) fn unsubscribe_resource(&self, params: Value) -> Result<Value, RpcError> {
+        let params: ResourceUriParams = serde_json::from_value(params)
+            .map_err(|error| RpcError::invalid_params(error.to_string()))?;
+        self.session.borrow_mut().subscriptions.remove(&params.uri);
+        Ok(serde_json::json!({}))
+    }
+}
+
+#[wasm_bindgen]
+impl McpServer {
+    /// Re-reads every subscribed resource and sends
+    /// `notifications/resources/updated` for each one whose content changed.
+    /// Returns how many changed. `serve_stdio` calls this on a timer; other
+    /// hosts can call it themselves.
+    #[wasm_bindgen]
+    pub async fn poll_resources(&self) -> u32 {
+        let subscriptions: Vec<(String, u64)> = self
+            .session
+            .borrow()
+            .subscriptions
+            .iter()
+            .map(|(uri, hash)| (uri.clone(), *hash))
+            .collect();
+
+        let mut changed = 0;
+        for (uri, previous) in subscriptions {
+            // A resource that can't be read right now (e.g. a network error)
+            // is checked again on the next poll.
+            let Ok(text) = self.resource_text(&uri).await else {
+                continue;
+            };
+            let hash = content_hash(&text);
+            if hash == previous {
+                continue;
+            }
+            let still_subscribed = match self.session.borrow_mut().subscriptions.get_mut(&uri) {
+                Some(stored) => {
+                    *stored = hash;
+                    true
+                }
+                None => false,
+            };
+            if still_subscribed {
+                changed += 1;
+                self.notify(
+                    "notifications/resources/updated",
+                    serde_json::json!({ "uri": uri }),
+                );
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,347 @@
+//! Content generation through the host's model (`sampling/createMessage`).
+//!
+//! Tools here fetch Storefront data, then ask the client to run a
+//! completion over it. The host decides which model answers and may show
+//! the request to the user first, so these tools only work with clients
+//! that declare the `sampling` capability.
+
+use std::rc::Rc;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::server::McpServer;
+use super::tools::{
+    ToolContext, ToolDefinition, ToolError, ToolFuture, ToolRegistry, ToolResult, Toolset,
+};
+use crate::money::DEFAULT_LOCALE;
+use crate::product::ProductOptions;
+use crate::types::{MetafieldIdentifier, Product};
+use crate::StorefrontApi;
+
+/// How long the host has to answer a sampling request; hosts that ask the
+/// user for approval can take a while.
+const SAMPLING_TIMEOUT_MS: u32 = 120_000;
+
+/// Metafields review apps commonly publish ratings under.
+const RATING_METAFIELDS: [(&str, &str); 2] = [("reviews", "rating"), ("reviews", "rating_count")];
+
+/// A completion returned by the host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sampled {
+    pub text: String,
+    pub model: Option<String>,
+}
+
+impl McpServer {
+    /// Whether the client declared the `sampling` capability.
+    pub fn supports_sampling(&self) -> bool {
+        self.session
+            .borrow()
+            .client_capabilities
+            .get("sampling")
+            .is_some()
+    }
+
+    /// Asks the host's model to respond to `prompt`.
+    pub async fn create_message(
+        &self,
+        system_prompt: &str,
+        prompt: String,
+        max_tokens: u32,
+    ) -> Result<Sampled, ToolError> {
+        if !self.supports_sampling() {
+            return Err(ToolError::new(
+                "This client doesn't support sampling, so the server can't generate text",
+            ));
+        }
+        let result = self
+            .request(
+                "sampling/createMessage",
+                serde_json::json!({
+                    "messages": [{
+                        "role": "user",
+                        "content": { "type": "text", "text": prompt }
+                    }],
+                    "systemPrompt": system_prompt,
+                    "includeContext": "none",
+                    "maxTokens": max_tokens,
+                    "modelPreferences": { "intelligencePriority": 0.6, "speedPriority": 0.4 }
+                }),
+                SAMPLING_TIMEOUT_MS,
+            )
+            .await
+            .map_err(|error| ToolError::new(format!("Sampling failed: {}", error.message)))?;
+
+        let text = match &result["content"] {
+            Value::Array(blocks) => blocks
+                .iter()
+                .filter_map(|block| block["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            content => content["text"].as_str().unwrap_or_default().to_string(),
+        };
+        if text.trim().is_empty() {
+            return Err(ToolError::new("The host returned no text"));
+        }
+        Ok(Sampled {
+            text,
+            model: result["model"].as_str().map(str::to_string),
+        })
+    }
+}
+
+async fn fetch_product(
+    api: &StorefrontApi,
+    key: &str,
+    options: &ProductOptions,
+) -> Result<Product, ToolError> {
+    let key = key.trim().to_string();
+    api.fetch_products(std::slice::from_ref(&key), options)
+        .await?
+        .pop()
+        .flatten()
+        .ok_or_else(|| ToolError::new(format!("No product found for {key}")))
+}
+
+/// Title, vendor, type, price, tags, options and description as
+/// `Label: value` lines.
+fn product_facts(product: &Product) -> String {
+    let mut facts = format!("Product: {}\n", product.title);
+    if let Some(vendor) = product.vendor.as_ref().filter(|vendor| !vendor.is_empty()) {
+        facts.push_str(&format!("Vendor: {vendor}\n"));
+    }
+    if let Some(kind) = product
+        .product_type
+        .as_ref()
+        .filter(|kind| !kind.is_empty())
+    {
+        facts.push_str(&format!("Type: {kind}\n"));
+    }
+    facts.push_str(&format!(
+        "Price: {}\n",
+        product.price_range.min_variant_price.format(DEFAULT_LOCALE)
+    ));
+    if !product.tags.is_empty() {
+        facts.push_str(&format!("Tags: {}\n", product.tags.join(", ")));
+    }
+    for option in &product.options {
+        let values: Vec<&str> = option
+            .option_values
+            .iter()
+            .map(|value| value.name.as_str())
+            .collect();
+        facts.push_str(&format!("{}: {}\n", option.name, values.join(", ")));
+    }
+    if let Some(description) = product
+        .description
+        .as_ref()
+        .filter(|d| !d.trim().is_empty())
+    {
+        facts.push_str(&format!("Current description: {description}\n"));
+    }
+    facts
+}
+
+/// JSON Schema for `sampled_result`'s structured content.
+fn sampled_schema(kind: &str) -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "handle": { "type": "string" },
+            "title": { "type": "string" },
+            kind: { "type": "string" },
+            "model": { "type": ["string", "null"], "description": "Model the host used" }
+        },
+        "required": ["handle", "title", kind]
+    })
+}
+
+fn sampled_result(product: &Product, kind: &str, sampled: Sampled) -> ToolResult {
+    let markdown = format!("## {}\n\n{}", product.title, sampled.text);
+    ToolResult::structured(
+        serde_json::json!({
+            "handle": product.handle,
+            "title": product.title,
+            kind: sampled.text,
+            "model": sampled.model,
+        }),
+        markdown,
+    )
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CopyArguments {
+    product: String,
+    #[serde(default)]
+    tone: Option<String>,
+    #[serde(default)]
+    length: Option<String>,
+}
+
+/// `draft_product_copy`: marketing copy for a product from its details.
+fn draft_product_copy(
+    server: &McpServer,
+    context: ToolContext,
+    arguments: Value,
+) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: CopyArguments = serde_json::from_value(arguments)?;
+        let product = fetch_product(
+            &context.api(server)?,
+            &arguments.product,
+            &ProductOptions::default(),
+        )
+        .await?;
+
+        let (length, max_tokens) = match arguments.length.as_deref() {
+            Some("short") => ("one short paragraph", 300),
+            Some("long") => ("four or five paragraphs", 1200),
+            _ => ("two or three short paragraphs", 700),
+        };
+        let mut prompt = format!(
+            "{}\nWrite product copy for this product in {length}",
+            product_facts(&product)
+        );
+        if let Some(tone) = arguments
+            .tone
+            .as_ref()
+            .filter(|tone| !tone.trim().is_empty())
+        {
+            prompt.push_str(&format!(", in a {tone} tone"));
+        }
+        prompt.push_str(". Reply with the copy only.");
+
+        let sampled = server
+            .create_message(
+                "You write e-commerce product copy. Use only the facts given; never invent \
+                 materials, measurements, certifications or claims.",
+                prompt,
+                max_tokens,
+            )
+            .await?;
+        Ok(sampled_result(&product, "copy", sampled))
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewArguments {
+    product: String,
+    /// `namespace.key` of a metafield holding review text.
+    #[serde(default)]
+    metafield: Option<String>,
+}
+
+/// `summarize_reviews`: a summary of the product's review data, read from
+/// rating metafields and, optionally, a metafield holding review text.
+fn summarize_reviews(server: &McpServer, context: ToolContext, arguments: Value) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: ReviewArguments = serde_json::from_value(arguments)?;
+        let mut metafields: Vec<MetafieldIdentifier> = RATING_METAFIELDS
+            .iter()
+            .map(|(namespace, key)| MetafieldIdentifier {
+                namespace: namespace.to_string(),
+                key: key.to_string(),
+            })
+            .collect();
+        if let Some(identifier) = &arguments.metafield {
+            let (namespace, key) = identifier.split_once('.').ok_or_else(|| {
+                ToolError::new(format!(
+                    "Invalid metafield: {identifier}. Use namespace.key"
+                ))
+            })?;
+            metafields.push(MetafieldIdentifier {
+                namespace: namespace.to_string(),
+                key: key.to_string(),
+            });
+        }
+        let options = ProductOptions {
+            metafields,
+            ..ProductOptions::default()
+        };
+        let product = fetch_product(&context.api(server)?, &arguments.product, &options).await?;
+
+        let reviews: Vec<String> = product
+            .metafields
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|metafield| {
+                format!(
+                    "{}.{} ({}): {}",
+                    metafield.namespace, metafield.key, metafield.kind, metafield.value
+                )
+            })
+            .collect();
+        if reviews.is_empty() {
+            return Err(ToolError::new(format!(
+                "{} has no review data in its metafields",
+                product.title
+            )));
+        }
+
+        let prompt = format!(
+            "Product: {}\n\nReview data from the store:\n{}\n\nSummarize what shoppers think \
+             of this product in three to five sentences: the overall rating, and any \
+             recurring praise or complaints in the review text. If there is only rating data, \
+             say so and summarize the rating.",
+            product.title,
+            reviews.join("\n")
+        );
+        let sampled = server
+            .create_message(
+                "You summarize product reviews for shoppers, accurately and without \
+                 exaggeration.",
+                prompt,
+                500,
+            )
+            .await?;
+        Ok(sampled_result(&product, "summary", sampled))
+    })
+}
+
+/// Adds the sampling-backed tools to `registry`.
+pub fn register(registry: &mut ToolRegistry) {
+    let annotations = serde_json::json!({ "readOnlyHint": true, "openWorldHint": true });
+    registry.register(
+        Toolset::Catalog,
+        ToolDefinition {
+            name: "draft_product_copy".to_string(),
+            title: Some("Draft product copy".to_string()),
+            description: "Draft marketing copy for a product from its store details, using the host's model. Needs a client with sampling support.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "product": { "type": "string", "description": "Product handle or gid://shopify/Product/... ID" },
+                    "tone": { "type": "string", "description": "e.g. \"playful\" or \"premium\"" },
+                    "length": { "type": "string", "enum": ["short", "medium", "long"], "default": "medium" }
+                },
+                "required": ["product"]
+            }),
+            output_schema: Some(sampled_schema("copy")),
+            annotations: Some(annotations.clone()),
+        },
+        Rc::new(draft_product_copy),
+    );
+    registry.register(
+        Toolset::Catalog,
+        ToolDefinition {
+            name: "summarize_reviews".to_string(),
+            title: Some("Summarize reviews".to_string()),
+            description: "Summarize a product's reviews from its rating metafields (reviews.rating, reviews.rating_count) and, optionally, a metafield holding review text, using the host's model. Needs a client with sampling support.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "product": { "type": "string", "description": "Product handle or gid://shopify/Product/... ID" },
+                    "metafield": { "type": "string", "description": "namespace.key of a metafield with review text, e.g. \"reviews.recent\"" }
+                },
+                "required": ["product"]
+            }),
+            output_schema: Some(sampled_schema("summary")),
+            annotations: Some(annotations),
+        },
+        Rc::new(summarize_reviews),
+    );
+}
@@ -0,0 +1,668 @@
+//! Request dispatch and session state.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use super::audit::{redact, AuditLog, AUDIT_LOG_URI};
+use super::limits::{LimitOptions, Limiter};
+use super::logging::DEFAULT_LOG_LEVEL;
+use super::prompts::builtin_prompts;
+use super::protocol::{
+    negotiate_version, notification, request, response, Message, Revision, RpcError,
+    INTERNAL_ERROR, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR, PROTOCOL_VERSION,
+};
+use super::resources::resource_templates;
+use super::tools::{builtin_tools, ToolContext, ToolDefinition, ToolRegistry, ToolResult, Toolset};
+use crate::logging::LogLevel;
+use crate::StorefrontApi;
+
+/// Name reported in `serverInfo`.
+const SERVER_NAME: &str = "storefront-api-mcp";
+
+/// How often `serve_stdio` checks subscribed resources for changes.
+const DEFAULT_POLL_INTERVAL_MS: u32 = 60_000;
+
+pub(crate) fn valid_store_name(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+}
+
+/// What the client told us in `initialize`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Session {
+    /// Set once the client sends `notifications/initialized`.
+    pub initialized: bool,
+    pub protocol_version: Option<String>,
+    pub client_info: Option<Value>,
+    pub client_capabilities: Value,
+    /// Carts assembled by the cart tools, by store name (`""` for the
+    /// default store).
+    pub carts: BTreeMap<String, String>,
+    /// Subscribed resource URIs and a hash of the content last seen.
+    pub subscriptions: BTreeMap<String, u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InitializeParams {
+    #[serde(default)]
+    protocol_version: Option<String>,
+    #[serde(default)]
+    capabilities: Value,
+    #[serde(default)]
+    client_info: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct CallToolParams {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+    #[serde(default, rename = "_meta")]
+    meta: Value,
+}
+
+/// An MCP server backed by a `StorefrontApi` client.
+#[wasm_bindgen]
+pub struct McpServer {
+    api: RefCell<StorefrontApi>,
+    /// Additional shops, whose tools are exposed as `name.tool_name`.
+    stores: RefCell<BTreeMap<String, StorefrontApi>>,
+    pub(crate) session: RefCell<Session>,
+    pub(crate) tools: RefCell<ToolRegistry>,
+    /// Toolsets exposed to the client; `None` exposes every toolset.
+    toolsets: RefCell<Option<BTreeSet<Toolset>>>,
+    /// Hides and refuses tools that change data.
+    read_only: Cell<bool>,
+    pub(crate) limiter: Rc<RefCell<Limiter>>,
+    pub(crate) sender: Rc<RefCell<Option<js_sys::Function>>>,
+    /// Least severe level forwarded as `notifications/message`.
+    pub(crate) log_level: Rc<Cell<LogLevel>>,
+    poll_interval_ms: Cell<u32>,
+    /// Requests we sent the client, by ID, with the function that resolves
+    /// the promise awaiting the response.
+    pending: RefCell<BTreeMap<u64, js_sys::Function>>,
+    next_request_id: Cell<u64>,
+    /// Abort controllers of cancellable requests from the client, by
+    /// serialized request ID.
+    pub(crate) in_flight: RefCell<BTreeMap<String, web_sys::AbortController>>,
+    pub(crate) audit: RefCell<AuditLog>,
+    /// Receives each audit entry as it is recorded.
+    pub(crate) audit_sink: RefCell<Option<js_sys::Function>>,
+}
+
+#[wasm_bindgen]
+impl McpServer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(api: &StorefrontApi) -> McpServer {
+        McpServer {
+            api: RefCell::new(api.clone()),
+            stores: RefCell::new(BTreeMap::new()),
+            session: RefCell::new(Session::default()),
+            tools: RefCell::new(builtin_tools()),
+            toolsets: RefCell::new(None),
+            read_only: Cell::new(false),
+            limiter: Rc::new(RefCell::new(Limiter::new(LimitOptions::default()))),
+            sender: Rc::new(RefCell::new(None)),
+            log_level: Rc::new(Cell::new(DEFAULT_LOG_LEVEL)),
+            poll_interval_ms: Cell::new(DEFAULT_POLL_INTERVAL_MS),
+            pending: RefCell::new(BTreeMap::new()),
+            next_request_id: Cell::new(1),
+            in_flight: RefCell::new(BTreeMap::new()),
+            audit: RefCell::new(AuditLog::default()),
+            audit_sink: RefCell::new(None),
+        }
+    }
+
+    /// Registers the function server-initiated messages are passed to, as
+    /// JSON strings.
+    #[wasm_bindgen]
+    pub fn set_sender(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "(message: string) => void")] sender: Option<
+            js_sys::Function,
+        >,
+    ) {
+        *self.sender.borrow_mut() = sender;
+    }
+
+    /// Handles one incoming JSON-RPC message (or batch) and returns the
+    /// response to send back, or `undefined` when there is none (for
+    /// notifications and responses).
+    #[wasm_bindgen]
+    pub async fn handle(&self, message: String) -> Option<String> {
+        self.handle_message(&message)
+            .await
+            .map(|response| response.to_string())
+    }
+
+    /// Sets how often, in milliseconds, `serve_stdio` re-reads subscribed
+    /// resources. `0` turns polling off. Takes effect when serving starts.
+    #[wasm_bindgen]
+    pub fn set_poll_interval(&self, milliseconds: u32) {
+        self.poll_interval_ms.set(milliseconds);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn poll_interval(&self) -> u32 {
+        self.poll_interval_ms.get()
+    }
+
+    /// Limits the tools exposed to the client to the named toolsets:
+    /// `"catalog"` (product search and details, resources and prompts),
+    /// `"cart"`, `"customer"` (customer metafields and registered customer
+    /// operations), `"graphql"` (raw queries) and `"custom"` (registered
+    /// operations). Pass `undefined` to expose everything.
+    #[wasm_bindgen]
+    pub fn set_toolsets(&self, toolsets: Option<Vec<String>>) -> Result<(), JsValue> {
+        let toolsets = toolsets
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| Toolset::parse(name))
+                    .collect::<Result<BTreeSet<_>, _>>()
+            })
+            .transpose()
+            .map_err(|error| JsValue::from_str(&error))?;
+        *self.toolsets.borrow_mut() = toolsets;
+        self.tools_changed();
+        Ok(())
+    }
+
+    /// The enabled toolset names.
+    #[wasm_bindgen(getter)]
+    pub fn toolsets(&self) -> Vec<String> {
+        Toolset::ALL
+            .into_iter()
+            .filter(|toolset| self.toolset_enabled(*toolset))
+            .map(|toolset| toolset.name().to_string())
+            .collect()
+    }
+
+    /// Turns read-only mode on or off. In read-only mode tools that change
+    /// data (cart writes, mutations, raw GraphQL and any tool without a
+    /// `readOnlyHint: true` annotation) are left out of `tools/list` and
+    /// refused when called.
+    #[wasm_bindgen]
+    pub fn set_read_only(&self, read_only: bool) {
+        if self.read_only.replace(read_only) != read_only {
+            self.tools_changed();
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn read_only(&self) -> bool {
+        self.read_only.get()
+    }
+
+    /// Switches the shop and token the session works against. The
+    /// session's cart and resource subscriptions belonged to the previous
+    /// client and are dropped.
+    #[wasm_bindgen]
+    pub fn set_api(&self, api: &StorefrontApi) {
+        *self.api.borrow_mut() = api.clone();
+        let mut session = self.session.borrow_mut();
+        session.carts.remove("");
+        session.subscriptions.clear();
+    }
+
+    /// Adds another shop under `name`. Every tool is then also exposed as
+    /// `name.tool_name` (e.g. `eu.search_products`), running against that
+    /// shop; the plain tool names keep using the default client. Names may
+    /// contain letters, digits, `_` and `-`. Replacing a store drops its
+    /// cart.
+    #[wasm_bindgen]
+    pub fn add_store(&self, name: String, api: &StorefrontApi) -> Result<(), JsValue> {
+        if !valid_store_name(&name) {
+            return Err(JsValue::from_str(&format!(
+                "Invalid store name: {name}. Use letters, digits, _ and -"
+            )));
+        }
+        self.session.borrow_mut().carts.remove(&name);
+        self.stores.borrow_mut().insert(name, api.clone());
+        self.tools_changed();
+        Ok(())
+    }
+
+    /// Removes a shop added with `add_store`. Returns whether it existed.
+    #[wasm_bindgen]
+    pub fn remove_store(&self, name: &str) -> bool {
+        let removed = self.stores.borrow_mut().remove(name).is_some();
+        if removed {
+            self.session.borrow_mut().carts.remove(name);
+            self.tools_changed();
+        }
+        removed
+    }
+
+    /// Names of the shops added with `add_store`.
+    #[wasm_bindgen(getter)]
+    pub fn stores(&self) -> Vec<String> {
+        self.stores.borrow().keys().cloned().collect()
+    }
+
+    /// The Storefront client for the default shop, or for the store added
+    /// as `store`, for making calls outside a session (the command-line
+    /// launcher's `query` and `product get`). Fails for an unknown store.
+    #[wasm_bindgen]
+    pub fn client(&self, store: Option<String>) -> Result<StorefrontApi, JsValue> {
+        match store {
+            None => Ok(self.api.borrow().clone()),
+            Some(store) => self
+                .stores
+                .borrow()
+                .get(&store)
+                .cloned()
+                .ok_or_else(|| JsValue::from_str(&format!("Unknown store \"{store}\""))),
+        }
+    }
+
+    /// Whether the client has completed the `initialize` handshake.
+    #[wasm_bindgen(getter)]
+    pub fn initialized(&self) -> bool {
+        self.session.borrow().initialized
+    }
+}
+
+impl McpServer {
+    /// The Storefront client calls are made with, metered against the
+    /// session's cost budget and logging to the client.
+    pub(crate) fn api(&self) -> StorefrontApi {
+        let mut api = self.metered(self.api.borrow().clone());
+        api.log_sink = Some(self.log_sink());
+        api
+    }
+
+    /// Like `api`, for the named store (`None` for the default one).
+    /// Stores share the session's limits.
+    pub(crate) fn store_api(&self, store: Option<&str>) -> Option<StorefrontApi> {
+        let Some(store) = store else {
+            return Some(self.api());
+        };
+        let mut api = self.metered(self.stores.borrow().get(store)?.clone());
+        api.log_sink = Some(self.log_sink());
+        Some(api)
+    }
+
+    /// `tools/list`: the enabled tools, then a namespaced copy of each for
+    /// every added store.
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        let definitions = self
+            .tools
+            .borrow()
+            .definitions(self.toolsets.borrow().as_ref(), self.read_only.get());
+        let mut listed = definitions.clone();
+        for (store, api) in self.stores.borrow().iter() {
+            listed.extend(definitions.iter().map(|definition| {
+                ToolDefinition {
+                    name: format!("{store}.{}", definition.name),
+                    title: definition
+                        .title
+                        .as_ref()
+                        .map(|title| format!("{title} ({store})")),
+                    description: format!(
+                        "[{store}: {}] {}",
+                        api.config.shop_domain, definition.description
+                    ),
+                    ..definition.clone()
+                }
+            }));
+        }
+        listed
+    }
+
+    /// Splits `store.tool_name` when `store` was added with `add_store`.
+    pub(crate) fn route_tool<'a>(&self, name: &'a str) -> (Option<&'a str>, &'a str) {
+        match name.split_once('.') {
+            Some((store, tool))
+                if self.stores.borrow().contains_key(store)
+                    && self.tools.borrow().get(tool).is_some() =>
+            {
+                (Some(store), tool)
+            }
+            _ => (None, name),
+        }
+    }
+
+    /// Features of the protocol revision agreed in `initialize`.
+    pub(crate) fn revision(&self) -> Revision {
+        Revision::of(
+            self.session
+                .borrow()
+                .protocol_version
+                .as_deref()
+                .unwrap_or(PROTOCOL_VERSION),
+        )
+    }
+
+    pub(crate) fn toolset_enabled(&self, toolset: Toolset) -> bool {
+        self.toolsets
+            .borrow()
+            .as_ref()
+            .is_none_or(|enabled| enabled.contains(&toolset))
+    }
+
+    /// Handles one raw message, returning the response value if one is due.
+    pub async fn handle_message(&self, message: &str) -> Option<Value> {
+        let value: Value = match serde_json::from_str(message) {
+            Ok(value) => value,
+            Err(error) => {
+                return Some(response(
+                    Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, format!("Parse error: {error}"))),
+                ))
+            }
+        };
+
+        match value {
+            Value::Array(batch) => {
+                if batch.is_empty() {
+                    return Some(response(
+                        Value::Null,
+                        Err(RpcError::new(INVALID_REQUEST, "Empty batch")),
+                    ));
+                }
+                let mut responses = Vec::new();
+                for message in batch {
+                    if let Some(response) = self.handle_value(message).await {
+                        responses.push(response);
+                    }
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            value => self.handle_value(value).await,
+        }
+    }
+
+    async fn handle_value(&self, value: Value) -> Option<Value> {
+        match Message::parse(value) {
+            Ok(Message::Request { id, method, params }) => {
+                let mut result = self.dispatch(&id, &method, params).await;
+                if let Ok(result) = &mut result {
+                    self.revision().downgrade_result(&method, result);
+                }
+                // A cancelled request gets no response.
+                (!self.finish_request(&id)).then(|| response(id, result))
+            }
+            Ok(Message::Notification { method, params }) => {
+                self.handle_notification(&method, params);
+                None
+            }
+            Ok(Message::Response { id, result }) => {
+                let resolve = id
+                    .as_u64()
+                    .and_then(|id| self.pending.borrow_mut().remove(&id));
+                if let Some(resolve) = resolve {
+                    let _ = resolve.call1(
+                        &JsValue::NULL,
+                        &JsValue::from_str(&response(id, result).to_string()),
+                    );
+                }
+                None
+            }
+            Err((id, error)) => Some(response(id, Err(error))),
+        }
+    }
+
+    async fn dispatch(&self, id: &Value, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => self.initialize(params),
+            "ping" => Ok(serde_json::json!({})),
+            _ if self.session.borrow().protocol_version.is_none() => Err(RpcError::new(
+                INVALID_REQUEST,
+                format!("Received {method} before initialize"),
+            )),
+            "tools/list" => Ok(serde_json::json!({ "tools": self.list_tools() })),
+            "tools/call" => {
+                let params: CallToolParams = serde_json::from_value(params)
+                    .map_err(|error| RpcError::invalid_params(error.to_string()))?;
+                let context = ToolContext {
+                    progress_token: params
+                        .meta
+                        .get("progressToken")
+                        .filter(|token| token.is_string() || token.is_number())
+                        .cloned(),
+                    store: None,
+                    signal: self.begin_cancellable(id),
+                };
+                let result = self
+                    .call_tool(&params.name, context, params.arguments)
+                    .await?;
+                serde_json::to_value(result)
+                    .map_err(|error| RpcError::new(INTERNAL_ERROR, error.to_string()))
+            }
+            "logging/setLevel" => self.set_log_level(params),
+            "prompts/list" if !self.toolset_enabled(Toolset::Catalog) => {
+                Ok(serde_json::json!({ "prompts": [] }))
+            }
+            "resources/list" if !self.toolset_enabled(Toolset::Catalog) => {
+                Ok(serde_json::json!({ "resources": Vec::from_iter(self.audit_resource()) }))
+            }
+            // The audit log isn't a catalog resource.
+            "resources/read" if params["uri"] == AUDIT_LOG_URI => self.read_resource(params).await,
+            "resources/subscribe" if params["uri"] == AUDIT_LOG_URI => {
+                self.subscribe_resource(params).await
+            }
+            "resources/templates/list" if !self.toolset_enabled(Toolset::Catalog) => {
+                Ok(serde_json::json!({ "resourceTemplates": [] }))
+            }
+            "prompts/get" | "resources/read" | "resources/subscribe"
+                if !self.toolset_enabled(Toolset::Catalog) =>
+            {
+                Err(RpcError::invalid_params("The catalog toolset is disabled"))
+            }
+            "prompts/list" => Ok(serde_json::json!({ "prompts": builtin_prompts() })),
+            "prompts/get" => self.get_prompt(params).await,
+            "resources/list" => self.list_resources(params).await,
+            "resources/templates/list" => Ok(serde_json::json!({
+                "resourceTemplates": resource_templates()
+            })),
+            "resources/read" => self.read_resource(params).await,
+            "resources/subscribe" => self.subscribe_resource(params).await,
+            "resources/unsubscribe" => self.unsubscribe_resource(params),
+            "completion/complete" => self.complete(params).await,
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {method}"),
+            )),
+        }
+    }
+
+    fn handle_notification(&self, method: &str, params: Value) {
+        match method {
+            "notifications/initialized" => self.session.borrow_mut().initialized = true,
+            "notifications/cancelled" => self.cancel_request(params),
+            _ => {}
+        }
+    }
+
+    fn initialize(&self, params: Value) -> Result<Value, RpcError> {
+        let params: InitializeParams = serde_json::from_value(params)
+            .map_err(|error| RpcError::invalid_params(error.to_string()))?;
+        let version = negotiate_version(params.protocol_version.as_deref());
+
+        let mut session = self.session.borrow_mut();
+        session.protocol_version = Some(version.to_string());
+        session.client_info = params.client_info;
+        session.client_capabilities = params.capabilities;
+
+        // Prompts, resources and completions are all catalog features, apart
+        // from the audit log resource.
+        let revision = Revision::of(version);
+        let catalog = self.toolset_enabled(Toolset::Catalog);
+        let mut capabilities = serde_json::json!({
+            "tools": { "listChanged": true },
+            "logging": {},
+        });
+        if catalog || self.audit.borrow().enabled() {
+            capabilities["resources"] =
+                serde_json::json!({ "subscribe": true, "listChanged": false });
+        }
+        if catalog {
+            capabilities["prompts"] = serde_json::json!({ "listChanged": false });
+            if revision.completions_capability {
+                capabilities["completions"] = serde_json::json!({});
+            }
+        }
+
+        Ok(serde_json::json!({
+            "protocolVersion": version,
+            "capabilities": capabilities,
+            "serverInfo": {
+                "name": SERVER_NAME,
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        }))
+    }
+
+    /// Runs a tool (see `run_tool`) and records the call in the audit log.
+    pub(crate) async fn call_tool(
+        &self,
+        name: &str,
+        context: ToolContext,
+        arguments: Value,
+    ) -> Result<ToolResult, RpcError> {
+        if !self.auditing() {
+            return self.run_tool(name, context, arguments).await;
+        }
+        let started = js_sys::Date::now();
+        let recorded = redact(&arguments);
+        let signal = context.signal.clone();
+        let outcome = self.run_tool(name, context, arguments).await;
+        let cancelled = signal.is_some_and(|signal| signal.aborted());
+        self.audit_call(name, recorded, started, cancelled, &outcome);
+        outcome
+    }
+
+    /// Runs a registered tool, or a store's copy of one (`store.tool_name`).
+    /// Unknown tools are a protocol error; failures inside a tool become an
+    /// `isError` result.
+    async fn run_tool(
+        &self,
+        name: &str,
+        mut context: ToolContext,
+        arguments: Value,
+    ) -> Result<ToolResult, RpcError> {
+        let (store, tool) = self.route_tool(name);
+        context.store = store.map(str::to_string);
+        let handler = {
+            let tools = self.tools.borrow();
+            let tool = tools
+                .get(tool)
+                .filter(|tool| self.toolset_enabled(tool.toolset))
+                .ok_or_else(|| RpcError::invalid_params(format!("Unknown tool: {name}")))?;
+            if self.read_only.get() && !tool.read_only() {
+                return Err(RpcError::invalid_params(format!(
+                    "{name} changes data and the server is in read-only mode"
+                )));
+            }
+            tool.handler.clone()
+        };
+        if let Err(exceeded) = self
+            .limiter
+            .borrow_mut()
+            .begin_call(name, js_sys::Date::now())
+        {
+            self.log(
+                LogLevel::Warning,
+                "limits",
+                serde_json::json!({ "tool": name, "message": exceeded.message }),
+            );
+            return Ok(exceeded.tool_result());
+        }
+        let arguments = if arguments.is_null() {
+            serde_json::json!({})
+        } else {
+            arguments
+        };
+        Ok(match handler.call(self, context, arguments).await {
+            Ok(result) => result,
+            Err(error) => error.tool_result(),
+        })
+    }
+
+    /// Tells an initialized client that the tool list changed.
+    pub(crate) fn tools_changed(&self) {
+        if self.session.borrow().initialized {
+            self.notify("notifications/tools/list_changed", Value::Null);
+        }
+    }
+
+    /// Sends a request to the client and waits up to `timeout_ms` for its
+    /// response.
+    pub async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout_ms: u32,
+    ) -> Result<Value, RpcError> {
+        if self.sender.borrow().is_none() {
+            return Err(RpcError::internal(
+                "No sender registered for server-initiated requests",
+            ));
+        }
+        let id = self.next_request_id.get();
+        self.next_request_id.set(id + 1);
+
+        let mut resolve = None;
+        let promise = js_sys::Promise::new(&mut |resolve_fn, _reject| resolve = Some(resolve_fn));
+        let resolve = resolve.expect("Promise executors run synchronously");
+        self.pending.borrow_mut().insert(id, resolve.clone());
+
+        // Settling a promise twice is a no-op, so the timeout can simply
+        // race the response.
+        let timeout = response(
+            Value::from(id),
+            Err(RpcError::internal(format!(
+                "{method} timed out after {timeout_ms}ms"
+            ))),
+        );
+        let set_timeout: js_sys::Function =
+            js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+                .and_then(|function| function.dyn_into())
+                .map_err(RpcError::from)?;
+        set_timeout
+            .call2(
+                &JsValue::NULL,
+                &resolve.bind1(&JsValue::NULL, &JsValue::from_str(&timeout.to_string())),
+                &JsValue::from(timeout_ms),
+            )
+            .map_err(RpcError::from)?;
+
+        self.send(&request(Value::from(id), method, params));
+        let settled = wasm_bindgen_futures::JsFuture::from(promise).await;
+        self.pending.borrow_mut().remove(&id);
+
+        let message: Value = settled
+            .ok()
+            .and_then(|value| value.as_string())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or(Value::Null);
+        match Message::parse(message) {
+            Ok(Message::Response { result, .. }) => result,
+            _ => Err(RpcError::internal(format!(
+                "Malformed response to {method}"
+            ))),
+        }
+    }
+
+    /// Sends a server-initiated notification through the sender callback.
+    pub fn notify(&self, method: &str, params: Value) {
+        self.send(&notification(method, params));
+    }
+
+    pub fn send(&self, message: &Value) {
+        if let Some(sender) = self.sender.borrow().as_ref() {
+            let _ = sender.call1(&JsValue::NULL, &JsValue::from_str(&message.to_string()));
+        }
+    }
+}
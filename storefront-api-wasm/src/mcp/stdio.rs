@@ -0,0 +1,132 @@
+//! Newline-delimited JSON-RPC over stdin/stdout, for Node hosts.
+//!
+//! MCP hosts such as Claude Desktop launch a server as a command and talk
+//! to it over its standard streams, one JSON message per line. Under Node,
+//! `serve_stdio` wires an `McpServer` to `process.stdin` and
+//! `process.stdout`. Anything else written to stdout corrupts the stream,
+//! so the launcher should send `console.log` to stderr.
+
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use super::McpServer;
+
+/// Splits a byte stream into lines, holding back a trailing partial line
+/// until the rest of it arrives.
+#[derive(Debug, Default)]
+pub struct LineDecoder {
+    buffer: String,
+}
+
+impl LineDecoder {
+    /// Appends a chunk and returns the complete, non-blank lines it
+    /// finished, without their `\n` / `\r\n` terminators.
+    pub fn push(&mut self, chunk: &str) -> Vec<String> {
+        self.buffer.push_str(chunk);
+        let Some(end) = self.buffer.rfind('\n') else {
+            return Vec::new();
+        };
+        let rest = self.buffer.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.buffer, rest);
+        complete
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Looks up `process.<name>` on the global object.
+fn process_stream(name: &str) -> Result<JsValue, JsValue> {
+    let process = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("process"))?;
+    if process.is_undefined() {
+        return Err(JsValue::from_str(
+            "serve_stdio needs Node: process is not defined",
+        ));
+    }
+    js_sys::Reflect::get(&process, &JsValue::from_str(name))
+}
+
+/// Calls `target[method](...arguments)`.
+fn call_method(target: &JsValue, method: &str, arguments: &[JsValue]) -> Result<JsValue, JsValue> {
+    let function: js_sys::Function =
+        js_sys::Reflect::get(target, &JsValue::from_str(method))?.dyn_into()?;
+    function.apply(target, &arguments.iter().collect::<js_sys::Array>())
+}
+
+/// Writes one message followed by a newline to stdout.
+fn write_line(stdout: &JsValue, message: &str) {
+    let _ = call_method(
+        stdout,
+        "write",
+        &[JsValue::from_str(&format!("{message}\n"))],
+    );
+}
+
+/// Serves `server` over the process's stdin and stdout until stdin closes.
+/// Requests are handled concurrently; responses are written as they
+/// complete. Subscribed resources are polled every `poll_interval`
+/// milliseconds.
+#[wasm_bindgen]
+pub fn serve_stdio(server: McpServer) -> Result<(), JsValue> {
+    let stdin = process_stream("stdin")?;
+    let stdout = process_stream("stdout")?;
+    let server = Rc::new(server);
+    let poll_server = server.clone();
+
+    let sender_stdout = stdout.clone();
+    let sender = Closure::<dyn Fn(String)>::new(move |message: String| {
+        write_line(&sender_stdout, &message);
+    });
+    server.set_sender(Some(
+        sender.as_ref().unchecked_ref::<js_sys::Function>().clone(),
+    ));
+    sender.forget();
+
+    let mut decoder = LineDecoder::default();
+    let on_data = Closure::<dyn FnMut(JsValue)>::new(move |chunk: JsValue| {
+        // `setEncoding("utf8")` makes every chunk a string.
+        let chunk = chunk.as_string().unwrap_or_default();
+        for line in decoder.push(&chunk) {
+            let server = server.clone();
+            let stdout = stdout.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(response) = server.handle_message(&line).await {
+                    write_line(&stdout, &response.to_string());
+                }
+            });
+        }
+    });
+
+    call_method(&stdin, "setEncoding", &[JsValue::from_str("utf8")])?;
+    call_method(
+        &stdin,
+        "on",
+        &[JsValue::from_str("data"), on_data.as_ref().clone()],
+    )?;
+    on_data.forget();
+
+    let interval = poll_server.poll_interval();
+    if interval > 0 {
+        let poll = Closure::<dyn Fn()>::new(move || {
+            let server = poll_server.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if !server.session.borrow().subscriptions.is_empty() {
+                    server.poll_resources().await;
+                }
+            });
+        });
+        let timer = call_method(
+            &js_sys::global(),
+            "setInterval",
+            &[poll.as_ref().clone(), JsValue::from(interval)],
+        )?;
+        // Don't keep the process alive just for polling once stdin closes.
+        let _ = call_method(&timer, "unref", &[]);
+        poll.forget();
+    }
+    Ok(())
+}
@@ -0,0 +1,484 @@
+//! Tool definitions, results and the registry `tools/list` and `tools/call`
+//! are served from.
+
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::AbortSignal;
+
+use super::server::McpServer;
+use crate::error::{ErrorDetail, ErrorKind, StorefrontError};
+use crate::StorefrontApi;
+
+/// A group of tools that is enabled or disabled as a unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Toolset {
+    /// Read-only catalog access: product search and details, plus the
+    /// catalog resources and prompts.
+    Catalog,
+    /// Creating and changing the session's cart.
+    Cart,
+    /// Customer-scoped tools: `get_customer_metafields` (with the
+    /// `customer` feature) and registered operations that take a customer
+    /// access token.
+    Customer,
+    /// `query_storefront_api`, which runs arbitrary GraphQL.
+    Graphql,
+    /// Registered operations not assigned to another toolset.
+    Custom,
+}
+
+impl Toolset {
+    pub const ALL: [Toolset; 5] = [
+        Toolset::Catalog,
+        Toolset::Cart,
+        Toolset::Customer,
+        Toolset::Graphql,
+        Toolset::Custom,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Toolset::Catalog => "catalog",
+            Toolset::Cart => "cart",
+            Toolset::Customer => "customer",
+            Toolset::Graphql => "graphql",
+            Toolset::Custom => "custom",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Toolset, String> {
+        let name = name.trim();
+        Toolset::ALL
+            .into_iter()
+            .find(|toolset| toolset.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Toolset::ALL.iter().map(|toolset| toolset.name()).collect();
+                format!(
+                    "Unknown toolset: {name}. Expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A tool as advertised by `tools/list`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub description: String,
+    /// JSON Schema for `arguments`.
+    pub input_schema: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Value>,
+}
+
+/// One content block of a tool result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Content {
+    Text { text: String },
+}
+
+/// The result of `tools/call`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolResult {
+    pub content: Vec<Content>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_error: bool,
+}
+
+impl ToolResult {
+    pub fn text(text: impl Into<String>) -> ToolResult {
+        ToolResult {
+            content: vec![Content::Text { text: text.into() }],
+            structured_content: None,
+            is_error: false,
+        }
+    }
+
+    /// A result carrying `value` as structured content, with its JSON as the
+    /// text block for clients that only read text.
+    pub fn json(value: Value) -> ToolResult {
+        let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+        ToolResult {
+            content: vec![Content::Text { text }],
+            structured_content: Some(value),
+            is_error: false,
+        }
+    }
+
+    /// A result carrying `value` as structured content and `markdown` as
+    /// the readable text block.
+    pub fn structured(value: Value, markdown: impl Into<String>) -> ToolResult {
+        ToolResult {
+            content: vec![Content::Text {
+                text: markdown.into(),
+            }],
+            structured_content: Some(value),
+            is_error: false,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> ToolResult {
+        ToolResult {
+            is_error: true,
+            ..ToolResult::text(message)
+        }
+    }
+}
+
+/// Why a tool call failed. Reported to the client as a result with
+/// `isError: true` and structured diagnostics, so the model can see what
+/// went wrong and whether trying again could help.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolError {
+    pub message: String,
+    /// Machine-readable class: `"graphql_error"`, `"user_error"`,
+    /// `"throttled"`, `"max_cost_exceeded"`, `"network_error"`,
+    /// `"http_error"`, `"invalid_response"`, `"invalid_arguments"` or
+    /// `"tool_error"`.
+    pub class: &'static str,
+    pub retryable: bool,
+    /// The individual errors behind the failure, with codes and fields.
+    pub errors: Vec<ErrorDetail>,
+}
+
+impl ToolError {
+    pub fn new(message: impl Into<String>) -> ToolError {
+        ToolError {
+            message: message.into(),
+            class: "tool_error",
+            retryable: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// The `isError` result reported to the client.
+    pub fn tool_result(&self) -> ToolResult {
+        let mut result = ToolResult::error(self.message.clone());
+        let mut diagnostics = serde_json::json!({
+            "error": self.class,
+            "retryable": self.retryable,
+            "message": self.message,
+        });
+        if let Some(field) = self.errors.iter().find_map(|error| error.field.as_ref()) {
+            diagnostics["field"] = serde_json::json!(field);
+        }
+        if !self.errors.is_empty() {
+            diagnostics["errors"] = serde_json::json!(self.errors);
+        }
+        result.structured_content = Some(diagnostics);
+        result
+    }
+}
+
+impl From<StorefrontError> for ToolError {
+    fn from(error: StorefrontError) -> ToolError {
+        ToolError {
+            class: match error.kind {
+                ErrorKind::Graphql => "graphql_error",
+                ErrorKind::UserError => "user_error",
+                ErrorKind::Throttled => "throttled",
+                ErrorKind::MaxCostExceeded => "max_cost_exceeded",
+                ErrorKind::Network => "network_error",
+                ErrorKind::Http => "http_error",
+                ErrorKind::Response => "invalid_response",
+            },
+            retryable: error.retryable,
+            errors: error.errors,
+            message: error.message,
+        }
+    }
+}
+
+impl From<JsValue> for ToolError {
+    fn from(error: JsValue) -> ToolError {
+        if let Some(error) = StorefrontError::from_js(&error) {
+            return error.into();
+        }
+        let message = error
+            .as_string()
+            .or_else(|| {
+                error
+                    .dyn_ref::<js_sys::Error>()
+                    .map(|error| String::from(error.message()))
+            })
+            .unwrap_or_else(|| format!("{error:?}"));
+        ToolError::new(message)
+    }
+}
+
+impl From<serde_json::Error> for ToolError {
+    fn from(error: serde_json::Error) -> ToolError {
+        ToolError {
+            class: "invalid_arguments",
+            ..ToolError::new(format!("Invalid arguments: {error}"))
+        }
+    }
+}
+
+/// `[text](url)`, or just `text` when there's no URL.
+pub fn markdown_link(text: &str, url: Option<&str>) -> String {
+    let text = text.replace('[', "\\[").replace(']', "\\]");
+    match url {
+        Some(url) => format!(
+            "[{text}]({})",
+            url.replace(' ', "%20")
+                .replace('(', "%28")
+                .replace(')', "%29")
+        ),
+        None => text,
+    }
+}
+
+/// JSON Schema for a serialized `Money`.
+pub fn money_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "amount": { "type": "string", "description": "Decimal amount, e.g. \"19.99\"" },
+            "currencyCode": { "type": "string" }
+        },
+        "required": ["amount", "currencyCode"]
+    })
+}
+
+/// Renders a GitHub-flavored markdown table, escaping pipes in cells.
+pub fn markdown_table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let escape = |cell: &str| cell.replace('|', "\\|").replace('\n', " ");
+    let mut table = format!(
+        "| {} |\n|{}\n",
+        headers.join(" | "),
+        " --- |".repeat(headers.len())
+    );
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape(cell)).collect();
+        table.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    table
+}
+
+/// What a handler knows about the call beyond its arguments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolContext {
+    /// `_meta.progressToken` from the request, when the client wants
+    /// progress notifications.
+    pub progress_token: Option<Value>,
+    /// The store the tool was called for (`store.tool_name`), or `None`
+    /// for the server's default store.
+    pub store: Option<String>,
+    /// Fires when the client cancels the call.
+    pub signal: Option<AbortSignal>,
+}
+
+impl ToolContext {
+    /// The client for this call's store, metered and logging like
+    /// `McpServer::api`.
+    pub fn api(&self, server: &McpServer) -> Result<StorefrontApi, ToolError> {
+        let mut api = server.store_api(self.store.as_deref()).ok_or_else(|| {
+            ToolError::new(format!(
+                "Store {} was removed",
+                self.store.as_deref().unwrap_or_default()
+            ))
+        })?;
+        if let Some(signal) = &self.signal {
+            api = api.with_signal(signal.clone());
+        }
+        Ok(api)
+    }
+
+    /// Whether the client has cancelled the call.
+    pub fn cancelled(&self) -> bool {
+        self.signal.as_ref().is_some_and(|signal| signal.aborted())
+    }
+
+    /// Key of this call's store in per-store session state such as carts.
+    pub fn store_key(&self) -> &str {
+        self.store.as_deref().unwrap_or_default()
+    }
+
+    /// Sends `notifications/progress` for this call, if the client asked for
+    /// it. `progress` must increase with every call.
+    pub fn progress(
+        &self,
+        server: &McpServer,
+        progress: u64,
+        total: Option<u64>,
+        message: Option<&str>,
+    ) {
+        let Some(token) = &self.progress_token else {
+            return;
+        };
+        let mut params = serde_json::json!({ "progressToken": token, "progress": progress });
+        if let Some(total) = total {
+            params["total"] = Value::from(total);
+        }
+        if let Some(message) = message.filter(|_| server.revision().progress_messages) {
+            params["message"] = Value::from(message);
+        }
+        server.notify("notifications/progress", params);
+    }
+}
+
+pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = Result<ToolResult, ToolError>> + 'a>>;
+
+/// Runs a tool call. Implemented for plain `fn`s so built-in tools are just
+/// functions.
+pub trait ToolHandler {
+    fn call<'a>(
+        &'a self,
+        server: &'a McpServer,
+        context: ToolContext,
+        arguments: Value,
+    ) -> ToolFuture<'a>;
+}
+
+impl<F> ToolHandler for F
+where
+    F: for<'a> Fn(&'a McpServer, ToolContext, Value) -> ToolFuture<'a>,
+{
+    fn call<'a>(
+        &'a self,
+        server: &'a McpServer,
+        context: ToolContext,
+        arguments: Value,
+    ) -> ToolFuture<'a> {
+        self(server, context, arguments)
+    }
+}
+
+#[derive(Clone)]
+pub struct RegisteredTool {
+    pub toolset: Toolset,
+    pub definition: ToolDefinition,
+    pub handler: Rc<dyn ToolHandler>,
+}
+
+impl RegisteredTool {
+    /// Whether the tool only reads data, going by its `readOnlyHint`
+    /// annotation. Tools without the hint are assumed to write.
+    pub fn read_only(&self) -> bool {
+        self.definition
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations["readOnlyHint"].as_bool())
+            .unwrap_or(false)
+    }
+}
+
+/// The tools a server exposes, in registration order.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<RegisteredTool>,
+}
+
+impl ToolRegistry {
+    /// Adds a tool, replacing any existing tool with the same name.
+    pub fn register(
+        &mut self,
+        toolset: Toolset,
+        definition: ToolDefinition,
+        handler: Rc<dyn ToolHandler>,
+    ) {
+        self.remove(&definition.name);
+        self.tools.push(RegisteredTool {
+            toolset,
+            definition,
+            handler,
+        });
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.tools.len();
+        self.tools.retain(|tool| tool.definition.name != name);
+        self.tools.len() != before
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RegisteredTool> {
+        self.tools.iter().find(|tool| tool.definition.name == name)
+    }
+
+    /// Definitions of the tools in `enabled` toolsets (all tools when
+    /// `enabled` is `None`), leaving out tools that write when `read_only`.
+    pub fn definitions(
+        &self,
+        enabled: Option<&BTreeSet<Toolset>>,
+        read_only: bool,
+    ) -> Vec<ToolDefinition> {
+        self.tools
+            .iter()
+            .filter(|tool| enabled.is_none_or(|enabled| enabled.contains(&tool.toolset)))
+            .filter(|tool| !read_only || tool.read_only())
+            .map(|tool| tool.definition.clone())
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct QueryArguments {
+    query: String,
+    #[serde(default)]
+    variables: Value,
+}
+
+/// `query_storefront_api`: runs a raw GraphQL operation.
+fn query_storefront_api(
+    server: &McpServer,
+    context: ToolContext,
+    arguments: Value,
+) -> ToolFuture<'_> {
+    Box::pin(async move {
+        let arguments: QueryArguments = serde_json::from_value(arguments)?;
+        let data = context
+            .api(server)?
+            .execute(&arguments.query, arguments.variables)
+            .await?;
+        Ok(ToolResult::json(data))
+    })
+}
+
+/// The tools every server starts with.
+pub fn builtin_tools() -> ToolRegistry {
+    let mut registry = ToolRegistry::default();
+    registry.register(
+        Toolset::Graphql,
+        ToolDefinition {
+            name: "query_storefront_api".to_string(),
+            title: Some("Query Storefront API".to_string()),
+            description: "Execute a GraphQL query against the Shopify Storefront API and return the data payload.".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "GraphQL operation" },
+                    "variables": { "type": "object", "description": "Operation variables" }
+                },
+                "required": ["query"]
+            }),
+            output_schema: None,
+            annotations: None,
+        },
+        Rc::new(query_storefront_api),
+    );
+    super::catalog::register(&mut registry);
+    #[cfg(feature = "cart")]
+    super::cart::register(&mut registry);
+    #[cfg(feature = "customer")]
+    super::customer::register(&mut registry);
+    super::sampling::register(&mut registry);
+    registry
+}
@@ -0,0 +1,122 @@
+//! Memory use of the module and the client's caches.
+//!
+//! `memory_stats()` reports the size of the WebAssembly heap and how much
+//! of it the response cache and prefetched responses hold;
+//! `trim_caches(targetBytes)` drops cached responses, oldest first, until
+//! both together fit in `targetBytes`. Freed memory is reused by later
+//! allocations, but the heap itself never shrinks: WebAssembly memory can
+//! only grow, so trimming early keeps it from growing further.
+
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::{to_js, StorefrontApi};
+
+/// Entries in a cache and their approximate size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheUsage {
+    pub entries: usize,
+    /// The length of the entries as JSON, which their in-memory size
+    /// follows closely.
+    pub bytes: usize,
+}
+
+impl From<(usize, usize)> for CacheUsage {
+    fn from((entries, bytes): (usize, usize)) -> Self {
+        CacheUsage { entries, bytes }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryStats {
+    /// Size of the WebAssembly linear memory.
+    pub heap_bytes: f64,
+    pub response_cache: CacheUsage,
+    pub prefetched: CacheUsage,
+    /// Bytes held by both caches.
+    pub cache_bytes: usize,
+}
+
+/// Approximately how many bytes `value` takes as JSON, without writing it.
+pub fn json_size(value: &Value) -> usize {
+    match value {
+        Value::Null => 4,
+        Value::Bool(true) => 4,
+        Value::Bool(false) => 5,
+        // Most prices and counts are short.
+        Value::Number(_) => 8,
+        Value::String(text) => text.len() + 2,
+        Value::Array(items) => 2 + items.iter().map(|item| json_size(item) + 1).sum::<usize>(),
+        Value::Object(object) => {
+            2 + object
+                .iter()
+                .map(|(key, value)| key.len() + 4 + json_size(value))
+                .sum::<usize>()
+        }
+    }
+}
+
+/// Size of the module's linear memory, or 0 where it can't be read.
+fn heap_bytes() -> f64 {
+    js_sys::Reflect::get(&wasm_bindgen::memory(), &JsValue::from_str("buffer"))
+        .and_then(|buffer| js_sys::Reflect::get(&buffer, &JsValue::from_str("byteLength")))
+        .ok()
+        .and_then(|length| length.as_f64())
+        .unwrap_or(0.0)
+}
+
+impl StorefrontApi {
+    pub fn memory_usage(&self) -> MemoryStats {
+        let response_cache = CacheUsage::from(self.cache.borrow().usage());
+        let prefetched = CacheUsage::from(self.prefetched.borrow().usage());
+        MemoryStats {
+            heap_bytes: heap_bytes(),
+            response_cache,
+            prefetched,
+            cache_bytes: response_cache.bytes + prefetched.bytes,
+        }
+    }
+
+    /// Evicts cached responses until the caches hold at most
+    /// `target_bytes`: prefetched responses first, as nothing asked for
+    /// them yet, then the response cache, oldest first in each.
+    pub fn trim_to(&self, target_bytes: usize) -> MemoryStats {
+        let mut total = self.memory_usage().cache_bytes;
+        while total > target_bytes {
+            let freed = self.prefetched.borrow_mut().evict_oldest();
+            let freed = match freed {
+                Some(bytes) => bytes,
+                None => match self.cache.borrow_mut().evict_oldest() {
+                    Some(bytes) => bytes,
+                    None => break,
+                },
+            };
+            total = total.saturating_sub(freed);
+        }
+        self.memory_usage()
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// `{ heapBytes, responseCache, prefetched, cacheBytes }`: the size of
+    /// the WebAssembly heap, the entries and approximate bytes of the
+    /// response cache and of prefetched responses, and both caches' bytes
+    /// together.
+    #[wasm_bindgen(unchecked_return_type = "MemoryStats")]
+    pub fn memory_stats(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.memory_usage())
+    }
+
+    /// Drops cached and prefetched responses, oldest first, until they take
+    /// at most `target_bytes` (`0` empties both), and returns the
+    /// `memory_stats()` after trimming. The heap doesn't shrink, but the
+    /// memory freed is reused.
+    #[wasm_bindgen(unchecked_return_type = "MemoryStats")]
+    pub fn trim_caches(&self, target_bytes: f64) -> Result<JsValue, JsValue> {
+        to_js(&self.trim_to(target_bytes.max(0.0) as usize))
+    }
+}
@@ -0,0 +1,44 @@
+//! Navigation menus.
+
+use wasm_bindgen::prelude::*;
+
+use crate::types::Menu;
+use crate::{from_json, to_js, StorefrontApi};
+
+/// Selection for one level of menu items.
+const MENU_ITEM_FIELDS: &str = "id title url type resourceId tags";
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches a menu by handle (e.g. `main-menu` or `footer`) with up to
+    /// three levels of nested items, or `null` if it doesn't exist.
+    #[wasm_bindgen(unchecked_return_type = "Menu | null")]
+    pub async fn get_menu(&self, handle: String) -> Result<JsValue, JsValue> {
+        let query = format!(
+            r#"
+            query getMenu($handle: String!) {{
+                menu(handle: $handle) {{
+                    id
+                    handle
+                    title
+                    itemsCount
+                    items {{
+                        {MENU_ITEM_FIELDS}
+                        items {{
+                            {MENU_ITEM_FIELDS}
+                            items {{
+                                {MENU_ITEM_FIELDS}
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+        "#
+        );
+
+        let variables = serde_json::json!({ "handle": handle });
+        let mut data = self.execute(&query, variables).await?;
+        let menu: Option<Menu> = from_json(data["menu"].take())?;
+        to_js(&menu)
+    }
+}
@@ -0,0 +1,247 @@
+//! Metafields on any owner resource, decoded according to their type.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::product::{graphql_string, metafield_identifiers_literal};
+use crate::types::{Metafield, MetafieldIdentifier, MetafieldReference};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Global ID prefixes of the resources `get_metafields` accepts by ID.
+const METAFIELD_OWNER_TYPES: &[&str] = &[
+    "gid://shopify/Product/",
+    "gid://shopify/ProductVariant/",
+    "gid://shopify/Collection/",
+    "gid://shopify/Page/",
+    "gid://shopify/Article/",
+    "gid://shopify/Blog/",
+    "gid://shopify/Location/",
+    "gid://shopify/Market/",
+];
+
+/// The resource whose metafields `get_metafields` reads.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum MetafieldOwner {
+    /// `"shop"`, or the global ID of a product, variant, collection, page,
+    /// article, blog, location or market.
+    Resource(String),
+    /// The logged-in customer.
+    #[cfg(feature = "customer")]
+    #[serde(rename_all = "camelCase")]
+    Customer { customer_access_token: String },
+}
+
+/// A metafield identifier given either as `{ namespace, key }` or as a
+/// `"namespace.key"` string.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum IdentifierInput {
+    Identifier(MetafieldIdentifier),
+    Path(String),
+}
+
+impl IdentifierInput {
+    pub(crate) fn into_identifier(self) -> Result<MetafieldIdentifier, JsValue> {
+        match self {
+            IdentifierInput::Identifier(identifier) => Ok(identifier),
+            IdentifierInput::Path(path) => match path.rsplit_once('.') {
+                Some((namespace, key)) if !namespace.is_empty() && !key.is_empty() => {
+                    Ok(MetafieldIdentifier {
+                        namespace: namespace.to_string(),
+                        key: key.to_string(),
+                    })
+                }
+                _ => Err(JsValue::from_str(&format!(
+                    "Invalid metafield identifier: {path}. Expected namespace.key"
+                ))),
+            },
+        }
+    }
+}
+
+/// A metafield with its value decoded according to `type`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedMetafield {
+    pub namespace: String,
+    pub key: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Numbers and booleans as JSON scalars, JSON-encoded types (`json`,
+    /// `rich_text_field`, `money`, `dimension`, `rating`, `list.*`, ...)
+    /// parsed, and everything else as the original string.
+    pub value: Value,
+    /// The value exactly as stored.
+    pub raw_value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<MetafieldReference>,
+}
+
+/// Decodes a metafield's string value according to its type. Values that
+/// don't parse as their declared type are returned as strings.
+pub fn typed_value(kind: &str, value: &str) -> Value {
+    let parsed = match kind {
+        "number_integer" => value.trim().parse::<i64>().ok().map(Value::from),
+        "number_decimal" => value.trim().parse::<f64>().ok().map(Value::from),
+        "boolean" => value.trim().parse::<bool>().ok().map(Value::from),
+        "json" | "rich_text_field" | "money" | "dimension" | "volume" | "weight" | "rating"
+        | "link" => serde_json::from_str(value).ok(),
+        _ if kind.starts_with("list.") => serde_json::from_str(value).ok(),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+impl TypedMetafield {
+    pub fn new(metafield: Metafield, reference: Option<MetafieldReference>) -> TypedMetafield {
+        TypedMetafield {
+            value: typed_value(&metafield.kind, &metafield.value),
+            namespace: metafield.namespace,
+            key: metafield.key,
+            kind: metafield.kind,
+            raw_value: metafield.value,
+            reference,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OwnedMetafield {
+    #[serde(flatten)]
+    metafield: Metafield,
+    #[serde(default, rename = "metafieldReference")]
+    reference: Option<MetafieldReference>,
+}
+
+/// Decodes a metafield value from JavaScript: `parse_metafield_value("number_integer", "3")`
+/// returns `3`.
+#[wasm_bindgen(unchecked_return_type = "JsonValue")]
+pub fn parse_metafield_value(kind: String, value: String) -> Result<JsValue, JsValue> {
+    to_js(&typed_value(&kind, &value))
+}
+
+/// Metafields by `"namespace.key"`, `None` for unset ones.
+pub type MetafieldValues = BTreeMap<String, Option<TypedMetafield>>;
+
+impl StorefrontApi {
+    /// The `identifiers` metafields of `owner`, or `None` when the owner
+    /// doesn't exist.
+    pub(crate) async fn fetch_metafields(
+        &self,
+        owner: &MetafieldOwner,
+        identifiers: &[MetafieldIdentifier],
+    ) -> Result<Option<MetafieldValues>, JsValue> {
+        if identifiers.is_empty() {
+            return Ok(Some(MetafieldValues::new()));
+        }
+
+        let selection = format!(
+            r#"metafields(identifiers: {}) {{
+                namespace
+                key
+                type
+                value
+                metafieldReference: reference {{
+                    __typename
+                    ... on Product {{ id handle title }}
+                    ... on ProductVariant {{ id title }}
+                    ... on Collection {{ id handle title }}
+                    ... on Page {{ id handle title }}
+                    ... on Metaobject {{ id handle type }}
+                    ... on MediaImage {{ id alt image {{ url altText width height }} }}
+                    ... on GenericFile {{ id url mimeType alt }}
+                }}
+            }}"#,
+            metafield_identifiers_literal(identifiers)
+        );
+
+        let (query, field) = match owner {
+            MetafieldOwner::Resource(owner) if owner.eq_ignore_ascii_case("shop") => (
+                format!("query getShopMetafields {{ shop {{ {selection} }} }}"),
+                "shop",
+            ),
+            MetafieldOwner::Resource(id) => {
+                if !METAFIELD_OWNER_TYPES
+                    .iter()
+                    .any(|prefix| id.starts_with(prefix))
+                {
+                    return Err(JsValue::from_str(&format!(
+                        "Unsupported metafield owner: {id}. Expected \"shop\", a resource global ID or {{ customerAccessToken }}"
+                    )));
+                }
+                (
+                    format!(
+                        "query getMetafields {{ node(id: {}) {{ ... on HasMetafields {{ {selection} }} }} }}",
+                        graphql_string(id)
+                    ),
+                    "node",
+                )
+            }
+            #[cfg(feature = "customer")]
+            MetafieldOwner::Customer {
+                customer_access_token,
+            } => (
+                format!(
+                    "query getCustomerMetafields {{ customer(customerAccessToken: {}) {{ {selection} }} }}",
+                    graphql_string(customer_access_token)
+                ),
+                "customer",
+            ),
+        };
+
+        let mut data = self.execute(&query, Value::Null).await?;
+        if data[field].is_null() {
+            return Ok(None);
+        }
+        let metafields: Vec<Option<OwnedMetafield>> = from_json(data[field]["metafields"].take())?;
+
+        let values: MetafieldValues = identifiers
+            .iter()
+            .zip(
+                metafields
+                    .into_iter()
+                    .chain(std::iter::repeat_with(|| None)),
+            )
+            .map(|(identifier, metafield)| {
+                (
+                    format!("{}.{}", identifier.namespace, identifier.key),
+                    metafield.map(|owned| TypedMetafield::new(owned.metafield, owned.reference)),
+                )
+            })
+            .collect();
+        Ok(Some(values))
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Reads metafields from any owner: `"shop"`, a resource global ID
+    /// (product, variant, collection, page, article, blog, location or
+    /// market), or `{ customerAccessToken }` for the logged-in customer
+    /// (with the `customer` feature). `identifiers` are `{ namespace, key }` objects or `"namespace.key"`
+    /// strings. Returns a map from `"namespace.key"` to `{ namespace, key,
+    /// type, value, rawValue, reference? }`, or `null` for unset metafields;
+    /// `null` overall when the owner doesn't exist.
+    #[wasm_bindgen(unchecked_return_type = "Record<string, TypedMetafield | null> | null")]
+    pub async fn get_metafields(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "MetafieldOwner")] owner: JsValue,
+        #[wasm_bindgen(unchecked_param_type = "(MetafieldIdentifier | string)[]")]
+        identifiers: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let owner: MetafieldOwner = from_js(owner)?;
+        let identifiers: Vec<IdentifierInput> = from_js(identifiers)?;
+        let identifiers = identifiers
+            .into_iter()
+            .map(IdentifierInput::into_identifier)
+            .collect::<Result<Vec<_>, _>>()?;
+        match self.fetch_metafields(&owner, &identifiers).await? {
+            Some(values) => to_js(&values),
+            None => Ok(JsValue::NULL),
+        }
+    }
+}
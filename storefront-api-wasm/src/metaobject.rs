@@ -0,0 +1,163 @@
+//! Metaobject queries.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::pagination::{
+    ListOptions, PageArgs, CONNECTION_ARGUMENTS, CONNECTION_VARIABLES, PAGE_INFO_FIELDS,
+};
+use crate::types::{Connection, Metaobject, PageInfo};
+use crate::{from_json, to_js, StorefrontApi};
+
+/// Sort keys accepted by `metaobjects(sortKey:)`.
+const METAOBJECT_SORT_KEYS: &[&str] = &["id", "type", "updated_at", "display_name"];
+
+/// Selection for the resource behind a reference field.
+pub(crate) const REFERENCE_FIELDS: &str = r#"
+    __typename
+    ... on Product { id handle title }
+    ... on ProductVariant { id title product { id handle title } }
+    ... on Collection { id handle title }
+    ... on Page { id handle title }
+    ... on Metaobject { id handle type }
+    ... on MediaImage { id alt image { url altText width height } }
+    ... on Video { id alt sources { url mimeType format width height } }
+    ... on GenericFile { id url mimeType alt }
+"#;
+
+/// Builds the `MetaobjectFields` fragment.
+pub(crate) fn metaobject_fragment() -> String {
+    format!(
+        r#"
+    fragment MetaobjectFields on Metaobject {{
+        id
+        handle
+        type
+        updatedAt
+        fields {{
+            key
+            type
+            value
+            reference {{
+                {REFERENCE_FIELDS}
+            }}
+            references(first: 25) {{
+                edges {{
+                    node {{
+                        {REFERENCE_FIELDS}
+                    }}
+                }}
+            }}
+        }}
+    }}
+"#
+    )
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaobjectList {
+    pub metaobjects: Vec<Metaobject>,
+    pub page_info: PageInfo,
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches one metaobject by type and handle, or `null` if none exists.
+    #[wasm_bindgen(unchecked_return_type = "Metaobject | null")]
+    pub async fn get_metaobject(&self, kind: String, handle: String) -> Result<JsValue, JsValue> {
+        let query = format!(
+            r#"
+            query getMetaobject($handle: MetaobjectHandleInput!) {{
+                metaobject(handle: $handle) {{
+                    ...MetaobjectFields
+                }}
+            }}
+            {}
+        "#,
+            metaobject_fragment()
+        );
+
+        let variables = serde_json::json!({
+            "handle": { "type": kind, "handle": handle }
+        });
+
+        let mut data = self.execute(&query, variables).await?;
+        let metaobject: Option<Metaobject> = from_json(data["metaobject"].take())?;
+        to_js(&metaobject)
+    }
+
+    /// Lists metaobjects of a type a page at a time. `options` is `{ first,
+    /// after, before, last, sortKey, reverse }`, with `sortKey` one of `id`,
+    /// `type`, `updated_at` or `display_name`. Pass the returned
+    /// `pageInfo.endCursor` as `after` for the next page, or
+    /// `pageInfo.startCursor` as `before` (with `last`) for the previous one.
+    #[wasm_bindgen(unchecked_return_type = "MetaobjectList")]
+    pub async fn list_metaobjects(
+        &self,
+        kind: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "ListOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options = ListOptions::from_js(options)?;
+        let page_args = options.page_args()?;
+        let sort_key = options.sort_key.map(|key| key.to_lowercase());
+        if let Some(key) = &sort_key {
+            if !METAOBJECT_SORT_KEYS.contains(&key.as_str()) {
+                return Err(JsValue::from_str(&format!(
+                    "Unknown metaobject sort key: {key} (expected one of {})",
+                    METAOBJECT_SORT_KEYS.join(", ")
+                )));
+            }
+        }
+        let list = self
+            .fetch_metaobjects(&kind, &page_args, sort_key, options.reverse)
+            .await?;
+        to_js(&list)
+    }
+}
+
+impl StorefrontApi {
+    pub(crate) async fn fetch_metaobjects(
+        &self,
+        kind: &str,
+        page: &PageArgs,
+        sort_key: Option<String>,
+        reverse: bool,
+    ) -> Result<MetaobjectList, JsValue> {
+        let query = format!(
+            r#"
+            query listMetaobjects(
+                $type: String!
+                {CONNECTION_VARIABLES}
+                $sortKey: String
+                $reverse: Boolean
+            ) {{
+                metaobjects(type: $type, {CONNECTION_ARGUMENTS}, sortKey: $sortKey, reverse: $reverse) {{
+                    edges {{
+                        node {{
+                            ...MetaobjectFields
+                        }}
+                    }}
+                    {PAGE_INFO_FIELDS}
+                }}
+            }}
+            {}
+        "#,
+            metaobject_fragment()
+        );
+
+        let mut variables = serde_json::json!({
+            "type": kind,
+            "sortKey": sort_key,
+            "reverse": reverse
+        });
+        page.add_to(&mut variables);
+
+        let mut data = self.execute(&query, variables).await?;
+        let connection: Connection<Metaobject> = from_json(data["metaobjects"].take())?;
+        Ok(MetaobjectList {
+            metaobjects: connection.edges.into_iter().map(|edge| edge.node).collect(),
+            page_info: connection.page_info.unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,541 @@
+//! Checking queries against a newer API version before switching to it.
+//!
+//! `migration_report(from, to, queries)` walks each query's selections
+//! through the `from` schema and looks every field up in the `to` schema,
+//! reporting the ones deprecated there and the ones removed, with the
+//! replacement the deprecation reason names (`Use \`x\` instead.`):
+//!
+//! ```js
+//! const report = await api.migration_report("2024-01", "2024-07");
+//! for (const issue of report.issues) {
+//!   console.warn(`${issue.operation}: ${issue.path} is ${issue.change}`, issue.replacement);
+//! }
+//! ```
+//!
+//! Without `queries` the report covers the operations the client and its
+//! copies have sent since `record_queries(true)`, so recording while
+//! exercising a storefront checks the built-in queries the storefront uses:
+//!
+//! ```js
+//! api.record_queries(true);
+//! await exerciseStorefront(api);
+//! const report = await api.migration_report("2024-01", "2024-07");
+//! api.record_queries(false);
+//! ```
+//!
+//! Nothing is recorded otherwise, and at most 500 distinct operations
+//! while recording. Schemas are introspected once
+//! per version and cached; `load_schema` supplies one instead, e.g. from a
+//! file checked in for CI.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::graphql::{tokenize, Token};
+use crate::{from_js, to_js, StorefrontApi};
+
+/// Fetches what the report needs from a version's schema.
+const SCHEMA_QUERY: &str = r#"
+query migrationSchema {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    types {
+      name
+      fields(includeDeprecated: true) {
+        name
+        isDeprecated
+        deprecationReason
+        type { name ofType { name ofType { name ofType { name ofType { name } } } } }
+      }
+    }
+  }
+}
+"#;
+
+/// A field of an object or interface type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInfo {
+    /// The field's type without lists and non-null wrappers.
+    pub type_name: String,
+    /// The deprecation reason, if the field is deprecated.
+    pub deprecation: Option<String>,
+}
+
+/// The fields of each type in one API version.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub query_type: String,
+    pub mutation_type: String,
+    pub types: BTreeMap<String, BTreeMap<String, FieldInfo>>,
+}
+
+impl Schema {
+    /// Reads an introspection result: `{ data: { __schema } }`,
+    /// `{ __schema }` or the `__schema` object itself.
+    pub fn from_introspection(value: &Value) -> Result<Schema, String> {
+        let schema = [&value["data"]["__schema"], &value["__schema"], value]
+            .into_iter()
+            .find(|schema| schema["types"].is_array())
+            .ok_or("The introspection result has no __schema.types")?;
+        let root = |kind: &str, default: &str| {
+            schema[kind]["name"].as_str().unwrap_or(default).to_string()
+        };
+        let mut types = BTreeMap::new();
+        for object in schema["types"].as_array().into_iter().flatten() {
+            let (Some(name), Some(fields)) = (object["name"].as_str(), object["fields"].as_array())
+            else {
+                continue;
+            };
+            let fields = fields
+                .iter()
+                .filter_map(|field| {
+                    let info = FieldInfo {
+                        type_name: named_type(&field["type"])?,
+                        deprecation: match field["isDeprecated"].as_bool() {
+                            Some(true) => Some(
+                                field["deprecationReason"]
+                                    .as_str()
+                                    .unwrap_or("Deprecated")
+                                    .to_string(),
+                            ),
+                            _ => None,
+                        },
+                    };
+                    Some((field["name"].as_str()?.to_string(), info))
+                })
+                .collect();
+            types.insert(name.to_string(), fields);
+        }
+        Ok(Schema {
+            query_type: root("queryType", "QueryRoot"),
+            mutation_type: root("mutationType", "Mutation"),
+            types,
+        })
+    }
+
+    pub fn field(&self, type_name: &str, field: &str) -> Option<&FieldInfo> {
+        self.types.get(type_name)?.get(field)
+    }
+}
+
+/// The innermost name of a `type { name ofType { ... } }` chain.
+fn named_type(type_ref: &Value) -> Option<String> {
+    match type_ref["name"].as_str() {
+        Some(name) => Some(name.to_string()),
+        None if type_ref["ofType"].is_object() => named_type(&type_ref["ofType"]),
+        None => None,
+    }
+}
+
+/// Schemas by API version.
+pub(crate) type Schemas = BTreeMap<String, Rc<Schema>>;
+
+/// Most distinct operations recorded for `migration_report`.
+const MAX_RECORDED_QUERIES: usize = 500;
+
+/// The canonical text of the operations sent while recording, shared by a
+/// client's copies.
+#[derive(Debug, Default)]
+pub(crate) struct SentQueries {
+    recording: bool,
+    queries: BTreeSet<String>,
+}
+
+impl SentQueries {
+    /// Records `query` if recording and there's room.
+    pub(crate) fn record(&mut self, query: &str) {
+        if self.recording && self.queries.len() < MAX_RECORDED_QUERIES {
+            self.queries
+                .insert(crate::canonical::canonical_query(query));
+        }
+    }
+}
+
+/// A field selected by a query, with the type it was selected on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectedField {
+    /// The operation, or `fragment Name`, the field is selected in.
+    pub operation: String,
+    /// Field names from the operation's root, e.g. `product.variants.nodes.price`.
+    pub path: String,
+    pub type_name: String,
+    pub field: String,
+}
+
+/// Walks the selection sets of a document, resolving field types through
+/// a schema.
+struct Walker<'a> {
+    schema: &'a Schema,
+    tokens: Vec<Token>,
+    index: usize,
+    operation: String,
+    fields: Vec<SelectedField>,
+}
+
+impl Walker<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn peek_punctuator(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punctuator(c))
+    }
+
+    fn name(&mut self) -> Option<String> {
+        match self.tokens.get(self.index) {
+            Some(Token::Name(name)) => {
+                self.index += 1;
+                Some(name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Skips a parenthesized list, such as arguments or variable
+    /// definitions, if one follows.
+    fn skip_parens(&mut self) {
+        if !self.peek_punctuator('(') {
+            return;
+        }
+        let mut depth = 0;
+        while let Some(token) = self.tokens.get(self.index) {
+            self.index += 1;
+            match token {
+                Token::Punctuator('(') => depth += 1,
+                Token::Punctuator(')') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_directives(&mut self) {
+        while self.peek_punctuator('@') {
+            self.index += 1;
+            self.name();
+            self.skip_parens();
+        }
+    }
+
+    /// Walks the document's operations and fragments.
+    fn document(&mut self) {
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Punctuator('{') => {
+                    self.operation = "anonymous".to_string();
+                    let root = self.schema.query_type.clone();
+                    self.selection_set(Some(root), "");
+                }
+                Token::Name(keyword) => {
+                    self.index += 1;
+                    let (root, operation) = match keyword.as_str() {
+                        "fragment" => {
+                            let name = self.name().unwrap_or_default();
+                            self.name(); // on
+                            (self.name(), format!("fragment {name}"))
+                        }
+                        "mutation" => (
+                            Some(self.schema.mutation_type.clone()),
+                            self.operation_name(),
+                        ),
+                        "query" => (Some(self.schema.query_type.clone()), self.operation_name()),
+                        // Subscriptions and anything unexpected aren't checked.
+                        _ => (None, self.operation_name()),
+                    };
+                    self.operation = operation;
+                    self.skip_parens();
+                    self.skip_directives();
+                    if self.peek_punctuator('{') {
+                        self.selection_set(root, "");
+                    }
+                }
+                _ => self.index += 1,
+            }
+        }
+    }
+
+    fn operation_name(&mut self) -> String {
+        self.name().unwrap_or_else(|| "anonymous".to_string())
+    }
+
+    /// Walks a `{ ... }` selected on `type_name`, which is `None` where the
+    /// type couldn't be resolved.
+    fn selection_set(&mut self, type_name: Option<String>, path: &str) {
+        self.index += 1;
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Punctuator('}') => {
+                    self.index += 1;
+                    return;
+                }
+                Token::Punctuator('.') => {
+                    self.index += 1;
+                    let condition = match self.peek() {
+                        Some(Token::Name(name)) if name == "on" => {
+                            self.index += 1;
+                            self.name()
+                        }
+                        // A named fragment is walked on its own.
+                        Some(Token::Name(_)) => {
+                            self.index += 1;
+                            self.skip_directives();
+                            continue;
+                        }
+                        _ => type_name.clone(),
+                    };
+                    self.skip_directives();
+                    if self.peek_punctuator('{') {
+                        self.selection_set(condition, path);
+                    }
+                }
+                Token::Name(name) => {
+                    self.index += 1;
+                    let field = if self.peek_punctuator(':') {
+                        self.index += 1;
+                        self.name().unwrap_or(name)
+                    } else {
+                        name
+                    };
+                    self.skip_parens();
+                    self.skip_directives();
+                    let path = match path {
+                        "" => field.clone(),
+                        _ => format!("{path}.{field}"),
+                    };
+                    let field_type = type_name.as_ref().and_then(|type_name| {
+                        let info = self.schema.field(type_name, &field)?;
+                        self.fields.push(SelectedField {
+                            operation: self.operation.clone(),
+                            path: path.clone(),
+                            type_name: type_name.clone(),
+                            field: field.clone(),
+                        });
+                        Some(info.type_name.clone())
+                    });
+                    if self.peek_punctuator('{') {
+                        self.selection_set(field_type, &path);
+                    }
+                }
+                _ => self.index += 1,
+            }
+        }
+    }
+}
+
+/// The fields `document` selects that `schema` knows, in document order.
+pub fn selected_fields(schema: &Schema, document: &str) -> Result<Vec<SelectedField>, String> {
+    let mut walker = Walker {
+        schema,
+        tokens: tokenize(document)?,
+        index: 0,
+        operation: "anonymous".to_string(),
+        fields: Vec::new(),
+    };
+    walker.document();
+    Ok(walker.fields)
+}
+
+/// How a field changed in the newer version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FieldChange {
+    Deprecated,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationIssue {
+    pub operation: String,
+    pub path: String,
+    pub type_name: String,
+    pub field: String,
+    pub change: FieldChange,
+    /// The deprecation reason, from the newer version or, for a removed
+    /// field, the older one.
+    pub reason: Option<String>,
+    /// The field the reason suggests using instead.
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    /// Documents checked.
+    pub queries: usize,
+    pub deprecated: usize,
+    pub removed: usize,
+    pub issues: Vec<MigrationIssue>,
+    /// Documents that couldn't be read, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// The first name in backticks in a deprecation reason, as in Shopify's
+/// ``Use `priceRange` instead.``
+pub fn suggested_replacement(reason: &str) -> Option<String> {
+    let (_, rest) = reason.split_once('`')?;
+    let (name, _) = rest.split_once('`')?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Checks `documents`, read with the `from` schema, against the `to`
+/// schema.
+pub fn migration_report(
+    from_version: &str,
+    from: &Schema,
+    to_version: &str,
+    to: &Schema,
+    documents: &[String],
+) -> MigrationReport {
+    let mut report = MigrationReport {
+        from: from_version.to_string(),
+        to: to_version.to_string(),
+        queries: documents.len(),
+        deprecated: 0,
+        removed: 0,
+        issues: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let mut seen = BTreeSet::new();
+    for document in documents {
+        let fields = match selected_fields(from, document) {
+            Ok(fields) => fields,
+            Err(error) => {
+                report.skipped.push(error);
+                continue;
+            }
+        };
+        for selected in fields {
+            let (change, reason) = match to.field(&selected.type_name, &selected.field) {
+                None => (
+                    FieldChange::Removed,
+                    from.field(&selected.type_name, &selected.field)
+                        .and_then(|info| info.deprecation.clone()),
+                ),
+                Some(FieldInfo {
+                    deprecation: Some(reason),
+                    ..
+                }) => (FieldChange::Deprecated, Some(reason.clone())),
+                Some(_) => continue,
+            };
+            if !seen.insert((selected.operation.clone(), selected.path.clone())) {
+                continue;
+            }
+            match change {
+                FieldChange::Deprecated => report.deprecated += 1,
+                FieldChange::Removed => report.removed += 1,
+            }
+            report.issues.push(MigrationIssue {
+                replacement: reason.as_deref().and_then(suggested_replacement),
+                operation: selected.operation,
+                path: selected.path,
+                type_name: selected.type_name,
+                field: selected.field,
+                change,
+                reason,
+            });
+        }
+    }
+    report
+}
+
+impl StorefrontApi {
+    /// The schema of `version`, introspected on first use.
+    pub async fn schema(&self, version: &str) -> Result<Rc<Schema>, JsValue> {
+        if let Some(schema) = self.schemas.borrow().get(version) {
+            return Ok(Rc::clone(schema));
+        }
+        let mut client = self.clone();
+        client.config.api_version = version.to_string();
+        let data = client
+            .send_operation(SCHEMA_QUERY.to_string(), Value::Null)
+            .await?;
+        let schema = Rc::new(Schema::from_introspection(&data).map_err(|error| {
+            JsValue::from_str(&format!("Couldn't read the {version} schema: {error}"))
+        })?);
+        self.schemas
+            .borrow_mut()
+            .insert(version.to_string(), Rc::clone(&schema));
+        Ok(schema)
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Caches `introspection` (the result of an introspection query, with
+    /// or without its `data` wrapper) as the schema of `version`, so
+    /// `migration_report` uses it instead of fetching one.
+    #[wasm_bindgen]
+    pub fn load_schema(
+        &self,
+        version: String,
+        #[wasm_bindgen(unchecked_param_type = "Introspection")] introspection: JsValue,
+    ) -> Result<(), JsValue> {
+        let introspection: Value = from_js(introspection)?;
+        let schema = Schema::from_introspection(&introspection)
+            .map_err(|error| JsValue::from_str(&error))?;
+        self.schemas.borrow_mut().insert(version, Rc::new(schema));
+        Ok(())
+    }
+
+    /// Starts recording the operations the client and its copies send, for
+    /// `migration_report` to check (at most 500 distinct ones), or stops
+    /// and forgets them.
+    #[wasm_bindgen]
+    pub fn record_queries(&self, enabled: bool) {
+        let mut sent = self.sent_queries.borrow_mut();
+        sent.recording = enabled;
+        if !enabled {
+            sent.queries.clear();
+        }
+    }
+
+    /// Reports the fields `queries` (an array of GraphQL documents) select
+    /// that are deprecated in API version `to` or removed from it, reading
+    /// the queries with version `from`. Without `queries`, checks the
+    /// operations sent since `record_queries(true)`. Resolves to `{ from,
+    /// to, queries, deprecated, removed, issues, skipped }`, where each
+    /// issue is `{ operation, path, typeName, field, change, reason,
+    /// replacement }` and `change` is `"deprecated"` or `"removed"`.
+    #[wasm_bindgen(unchecked_return_type = "MigrationReport")]
+    pub async fn migration_report(
+        &self,
+        from: String,
+        to: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "string[]")] queries: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let documents: Vec<String> = match queries {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => {
+                let sent = self.sent_queries.borrow();
+                if !sent.recording {
+                    return Err(JsValue::from_str(
+                        "No queries were recorded: pass queries, or call record_queries(true) \
+                         before sending the operations to check",
+                    ));
+                }
+                sent.queries.iter().cloned().collect()
+            }
+        };
+        let from_schema = self.schema(&from).await?;
+        let to_schema = self.schema(&to).await?;
+        to_js(&migration_report(
+            &from,
+            &from_schema,
+            &to,
+            &to_schema,
+            &documents,
+        ))
+    }
+}
@@ -0,0 +1,269 @@
+//! Locale-aware money formatting for typed `Money` values.
+
+use wasm_bindgen::prelude::*;
+
+use crate::types::Money;
+
+/// Locale used when the caller doesn't pass one.
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Non-breaking space, used between the amount and the symbol and as the
+/// digit group separator in locales that group with spaces, so a price never
+/// wraps across lines.
+const NBSP: &str = "\u{a0}";
+
+/// How a locale lays out a currency amount.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MoneyStyle {
+    decimal: &'static str,
+    group: &'static str,
+    symbol_first: bool,
+    spaced: bool,
+}
+
+impl MoneyStyle {
+    const fn new(
+        decimal: &'static str,
+        group: &'static str,
+        symbol_first: bool,
+        spaced: bool,
+    ) -> Self {
+        MoneyStyle {
+            decimal,
+            group,
+            symbol_first,
+            spaced,
+        }
+    }
+
+    /// Resolves a BCP 47 tag (`de-DE`, `pt_BR`, `fr`) by language, with a few
+    /// region overrides. Unknown locales fall back to the `en` layout.
+    fn for_locale(locale: &str) -> MoneyStyle {
+        let locale = locale.replace('_', "-").to_lowercase();
+        let (language, region) = match locale.split_once('-') {
+            Some((language, region)) => (language, Some(region)),
+            None => (locale.as_str(), None),
+        };
+
+        match (language, region) {
+            ("de" | "fr" | "it", Some("ch")) => MoneyStyle::new(".", "’", true, true),
+            ("pt", Some("br")) | ("nl" | "id", _) => MoneyStyle::new(",", ".", true, true),
+            ("tr", _) => MoneyStyle::new(",", ".", true, false),
+            ("de" | "es" | "it" | "pt" | "da" | "el" | "ro" | "hr" | "sl", _) => {
+                MoneyStyle::new(",", ".", false, true)
+            }
+            (
+                "fr" | "sv" | "nb" | "no" | "nn" | "fi" | "pl" | "cs" | "sk" | "ru" | "uk" | "hu"
+                | "lt" | "lv" | "et" | "bg",
+                _,
+            ) => MoneyStyle::new(",", NBSP, false, true),
+            _ => MoneyStyle::new(".", ",", true, false),
+        }
+    }
+}
+
+/// Display symbol for an ISO 4217 code, when it has a well-known one.
+pub(crate) fn currency_symbol(currency_code: &str) -> Option<&'static str> {
+    Some(match currency_code {
+        "USD" | "CAD" | "AUD" | "NZD" | "MXN" | "SGD" | "HKD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" | "CNY" => "¥",
+        "INR" => "₹",
+        "KRW" => "₩",
+        "BRL" => "R$",
+        "TRY" => "₺",
+        "ILS" => "₪",
+        "CHF" => "CHF",
+        "SEK" | "NOK" | "DKK" => "kr",
+        "PLN" => "zł",
+        _ => return None,
+    })
+}
+
+/// Minor unit digits for a currency (ISO 4217 exponent).
+fn currency_decimals(currency_code: &str) -> usize {
+    match currency_code {
+        "JPY" | "KRW" | "VND" | "CLP" | "ISK" | "UGX" | "PYG" | "XAF" | "XOF" => 0,
+        "BHD" | "KWD" | "OMR" | "JOD" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// Renders `value` with `decimals` fraction digits, grouping the integer part
+/// in threes.
+fn format_number(value: f64, decimals: usize, decimal: &str, group: &str) -> String {
+    let fixed = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(group);
+        }
+        grouped.push(digit);
+    }
+    if !fraction.is_empty() {
+        grouped.push_str(decimal);
+        grouped.push_str(fraction);
+    }
+    // Avoid "-0.00" when a tiny negative amount rounds to zero.
+    if value < 0.0 && grouped.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
+
+impl Money {
+    /// Formats the amount for `locale`, e.g. `$1,234.56` for `en-US` or
+    /// `1.234,56 €` for `de-DE`. Currencies without a known symbol print their
+    /// ISO code.
+    pub fn format(&self, locale: &str) -> String {
+        let style = MoneyStyle::for_locale(locale);
+        let number = format_number(
+            self.value(),
+            currency_decimals(&self.currency_code),
+            style.decimal,
+            style.group,
+        );
+        let symbol = currency_symbol(&self.currency_code).unwrap_or(&self.currency_code);
+        // Letter symbols ("CHF", "kr") always need a gap from the digits.
+        let gap = if style.spaced || symbol.chars().all(char::is_alphabetic) {
+            NBSP
+        } else {
+            ""
+        };
+
+        if style.symbol_first {
+            format!("{symbol}{gap}{number}")
+        } else {
+            format!("{number}{gap}{symbol}")
+        }
+    }
+
+    /// Formats the amount with a shop `money_format` template such as
+    /// `${{amount}}` or `{{amount_with_comma_separator}} €`. Unknown
+    /// placeholders are left untouched.
+    pub fn format_with(&self, money_format: &str) -> String {
+        let mut output = String::new();
+        let mut rest = money_format;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            output.push_str(&rest[..start]);
+            let placeholder = &rest[start..start + end + 2];
+            match self.placeholder_value(placeholder[2..placeholder.len() - 2].trim()) {
+                Some(value) => output.push_str(&value),
+                None => output.push_str(placeholder),
+            }
+            rest = &rest[start + end + 2..];
+        }
+        output.push_str(rest);
+        output
+    }
+
+    fn placeholder_value(&self, placeholder: &str) -> Option<String> {
+        let value = self.value();
+        let (decimals, decimal, group) = match placeholder {
+            "amount" => (2, ".", ","),
+            "amount_no_decimals" => (0, ".", ","),
+            "amount_with_comma_separator" => (2, ",", "."),
+            "amount_no_decimals_with_comma_separator" => (0, ",", "."),
+            "amount_with_apostrophe_separator" => (2, ".", "'"),
+            "amount_no_decimals_with_space_separator" => (0, ",", " "),
+            "amount_with_space_separator" => (2, ",", " "),
+            "amount_with_period_and_space_separator" => (2, ".", " "),
+            _ => return None,
+        };
+        Some(format_number(value, decimals, decimal, group))
+    }
+}
+
+/// Whether `amount` is a finite decimal number; `NaN` and `inf` parse as
+/// floats but aren't amounts.
+fn is_amount(amount: &str) -> bool {
+    amount
+        .trim()
+        .parse::<f64>()
+        .is_ok_and(|value| value.is_finite())
+}
+
+fn parse_money(amount: &str, currency_code: &str) -> Result<Money, JsValue> {
+    if !is_amount(amount) {
+        return Err(JsValue::from_str(&format!("Invalid amount: {amount}")));
+    }
+    Ok(Money {
+        amount: amount.trim().to_string(),
+        currency_code: currency_code.to_uppercase(),
+    })
+}
+
+/// Formats a `Money` amount for a locale (`"en-US"` by default):
+/// `format_money("1234.56", "EUR", "de-DE")` returns `"1.234,56 €"`.
+#[wasm_bindgen]
+pub fn format_money(
+    amount: &str,
+    currency_code: &str,
+    locale: Option<String>,
+) -> Result<String, JsValue> {
+    let money = parse_money(amount, currency_code)?;
+    Ok(money.format(locale.as_deref().unwrap_or(DEFAULT_LOCALE)))
+}
+
+/// Formats a `Money` amount with the shop's `money_format` template, matching
+/// Liquid's `money` filter: `format_money_with_format("1234.5", "EUR",
+/// "€{{amount_with_comma_separator}}")` returns `"€1.234,50"`.
+#[wasm_bindgen]
+pub fn format_money_with_format(
+    amount: &str,
+    currency_code: &str,
+    money_format: &str,
+) -> Result<String, JsValue> {
+    let money = parse_money(amount, currency_code)?;
+    Ok(money.format_with(money_format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(amount: &str, currency_code: &str) -> Money {
+        Money {
+            amount: amount.to_string(),
+            currency_code: currency_code.to_string(),
+        }
+    }
+
+    #[test]
+    fn locales_lay_out_amounts() {
+        let price = money("1234.56", "EUR");
+
+        assert_eq!(money("1234.56", "USD").format("en-US"), "$1,234.56");
+        assert_eq!(price.format("de-DE"), "1.234,56\u{a0}€");
+        assert_eq!(price.format("fr"), "1\u{a0}234,56\u{a0}€");
+        assert_eq!(money("1234.5", "CHF").format("de-CH"), "CHF\u{a0}1’234.50");
+    }
+
+    #[test]
+    fn currencies_keep_their_minor_units() {
+        assert_eq!(money("1234.56", "JPY").format("en-US"), "¥1,235");
+        assert_eq!(money("1234.5", "KWD").format("en-US"), "KWD\u{a0}1,234.500");
+    }
+
+    #[test]
+    fn negatives_that_round_to_zero_lose_their_sign() {
+        assert_eq!(money("-0.004", "USD").format("en-US"), "$0.00");
+        assert_eq!(format_number(-1234.5, 2, ".", ","), "-1,234.50");
+    }
+
+    #[test]
+    fn amounts_must_be_finite_numbers() {
+        assert!(is_amount(" 12.50 "));
+        assert!(is_amount("-3"));
+        for amount in ["NaN", "inf", "-infinity", "Infinity", "", "12,50"] {
+            assert!(!is_amount(amount), "{amount} was accepted");
+        }
+    }
+}
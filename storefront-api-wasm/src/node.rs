@@ -0,0 +1,107 @@
+//! Fetching arbitrary nodes by global ID.
+
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "cart")]
+use crate::cart::CART_FRAGMENT;
+use crate::product::{product_fragment, ProductOptions};
+use crate::types::Node;
+use crate::{from_json, to_js, StorefrontApi};
+
+/// Most IDs the `nodes` root field accepts in one request.
+const MAX_NODE_IDS: usize = 250;
+
+/// The `Cart` arm of the `nodes` selection and the fragment it spreads.
+/// Without the `cart` feature, carts decode as `Unknown`.
+#[cfg(feature = "cart")]
+const CART_SELECTION: (&str, &str) = ("... on Cart { ...CartFields }", CART_FRAGMENT);
+#[cfg(not(feature = "cart"))]
+const CART_SELECTION: (&str, &str) = ("", "");
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches nodes by global ID (`gid://shopify/Product/1`, ...). Returns one
+    /// entry per ID, in order: a `Product`, `ProductVariant` (with its parent
+    /// `product` reference), `Collection` or `Cart` tagged by `__typename`,
+    /// `{ __typename: "Unknown" }` for other types, or `null` when the ID
+    /// doesn't resolve.
+    #[wasm_bindgen(unchecked_return_type = "(Node | null)[]")]
+    pub async fn get_nodes(&self, ids: Vec<String>) -> Result<JsValue, JsValue> {
+        if ids.len() > MAX_NODE_IDS {
+            return Err(JsValue::from_str(&format!(
+                "get_nodes accepts at most {MAX_NODE_IDS} IDs, got {}",
+                ids.len()
+            )));
+        }
+        if ids.is_empty() {
+            return to_js(&Vec::<Option<Node>>::new());
+        }
+
+        let (cart_selection, cart_fragment) = CART_SELECTION;
+        let query = format!(
+            r#"
+            query getNodes($ids: [ID!]!) {{
+                nodes(ids: $ids) {{
+                    __typename
+                    ... on Product {{
+                        ...ProductFields
+                    }}
+                    ... on ProductVariant {{
+                        id
+                        title
+                        sku
+                        barcode
+                        price {{
+                            amount
+                            currencyCode
+                        }}
+                        compareAtPrice {{
+                            amount
+                            currencyCode
+                        }}
+                        availableForSale
+                        selectedOptions {{
+                            name
+                            value
+                        }}
+                        image {{
+                            url
+                            altText
+                        }}
+                        product {{
+                            id
+                            handle
+                            title
+                        }}
+                    }}
+                    ... on Collection {{
+                        id
+                        handle
+                        title
+                        description
+                        image {{
+                            url
+                            altText
+                            width
+                            height
+                        }}
+                        seo {{
+                            title
+                            description
+                        }}
+                    }}
+                    {cart_selection}
+                }}
+            }}
+            {}
+            {cart_fragment}
+        "#,
+            product_fragment(&ProductOptions::default())
+        );
+
+        let variables = serde_json::json!({ "ids": ids });
+        let mut data = self.execute(&query, variables).await?;
+        let nodes: Vec<Option<Node>> = from_json(data["nodes"].take())?;
+        to_js(&nodes)
+    }
+}
@@ -0,0 +1,128 @@
+//! Online Store pages (About, FAQ, ...).
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::pagination::{
+    ListOptions, CONNECTION_ARGUMENTS, CONNECTION_VARIABLES, PAGE_INFO_FIELDS,
+};
+use crate::product::metafield_identifiers_literal;
+use crate::search::normalize_enum_values;
+use crate::types::{Connection, MetafieldIdentifier, Page, PageInfo};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// `PageSortKeys` values accepted by `list_pages`.
+const PAGE_SORT_KEYS: &[&str] = &["ID", "RELEVANCE", "TITLE", "UPDATED_AT"];
+
+/// Optional extras for `get_page`, e.g. `{ metafields: [{ namespace:
+/// "custom", key: "hero" }] }`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PageOptions {
+    pub metafields: Vec<MetafieldIdentifier>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageList {
+    pub pages: Vec<Page>,
+    pub page_info: PageInfo,
+}
+
+/// Fields selected for every page.
+const PAGE_FIELDS: &str = r#"
+    id
+    handle
+    title
+    bodySummary
+    createdAt
+    updatedAt
+    onlineStoreUrl
+    seo {
+        title
+        description
+    }
+"#;
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches a page by handle with its HTML `body`, summary and SEO fields,
+    /// or `null` if none exists. `options.metafields` adds page metafields.
+    #[wasm_bindgen(unchecked_return_type = "Page | null")]
+    pub async fn get_page(
+        &self,
+        handle: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "PageOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options: PageOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => PageOptions::default(),
+        };
+        let metafields = if options.metafields.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "metafields(identifiers: {}) {{ id namespace key type value }}",
+                metafield_identifiers_literal(&options.metafields)
+            )
+        };
+        let query = format!(
+            r#"
+            query getPage($handle: String!) {{
+                page(handle: $handle) {{
+                    {PAGE_FIELDS}
+                    body
+                    {metafields}
+                }}
+            }}
+        "#
+        );
+
+        let variables = serde_json::json!({ "handle": handle });
+        let mut data = self.execute(&query, variables).await?;
+        let page: Option<Page> = from_json(data["page"].take())?;
+        to_js(&page)
+    }
+
+    /// Lists pages a page at a time, without bodies. `options` is `{ first,
+    /// after, before, last, sortKey, reverse }`, with `sortKey` a
+    /// `PageSortKeys` value (`ID`, `RELEVANCE`, `TITLE` or `UPDATED_AT`).
+    /// Pass the returned `pageInfo.endCursor` as `after` for the next page,
+    /// or `pageInfo.startCursor` as `before` (with `last`) for the previous
+    /// one.
+    #[wasm_bindgen(unchecked_return_type = "PageList")]
+    pub async fn list_pages(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "ListOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options = ListOptions::from_js(options)?;
+        let page_args = options.page_args()?;
+        let sort_key = match options.sort_key {
+            Some(key) => normalize_enum_values("page sort key", &[key], PAGE_SORT_KEYS)?.pop(),
+            None => None,
+        };
+        let query = format!(
+            r#"
+            query listPages({CONNECTION_VARIABLES}, $sortKey: PageSortKeys, $reverse: Boolean) {{
+                pages({CONNECTION_ARGUMENTS}, sortKey: $sortKey, reverse: $reverse) {{
+                    edges {{
+                        node {{
+                            {PAGE_FIELDS}
+                        }}
+                    }}
+                    {PAGE_INFO_FIELDS}
+                }}
+            }}
+        "#
+        );
+
+        let mut variables = serde_json::json!({ "sortKey": sort_key, "reverse": options.reverse });
+        page_args.add_to(&mut variables);
+        let mut data = self.execute(&query, variables).await?;
+        let connection: Connection<Page> = from_json(data["pages"].take())?;
+        to_js(&PageList {
+            pages: connection.edges.into_iter().map(|edge| edge.node).collect(),
+            page_info: connection.page_info.unwrap_or_default(),
+        })
+    }
+}
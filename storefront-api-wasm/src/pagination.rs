@@ -0,0 +1,126 @@
+//! Cursor arguments shared by the list helpers.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::collection::MAX_PAGE_SIZE;
+
+/// Page size used when neither `first` nor `last` is given.
+pub const DEFAULT_PAGE_SIZE: u32 = 20;
+
+/// GraphQL variable declarations matching `CONNECTION_ARGUMENTS`.
+pub(crate) const CONNECTION_VARIABLES: &str =
+    "$first: Int, $after: String, $last: Int, $before: String";
+
+/// Connection arguments bound to the variables in `CONNECTION_VARIABLES`.
+pub(crate) const CONNECTION_ARGUMENTS: &str =
+    "first: $first, after: $after, last: $last, before: $before";
+
+/// Selection of every `PageInfo` field.
+pub(crate) const PAGE_INFO_FIELDS: &str =
+    "pageInfo { hasNextPage hasPreviousPage startCursor endCursor }";
+
+/// Paging and sorting options for the list helpers (`search_products`,
+/// `list_collections`, `list_pages` and `list_metaobjects`), passed from
+/// JavaScript as a plain object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ListOptions {
+    pub first: Option<u32>,
+    /// Cursor to page forward from (`pageInfo.endCursor`).
+    pub after: Option<String>,
+    /// Cursor to page backward from (`pageInfo.startCursor`), with `last`.
+    pub before: Option<String>,
+    /// Page size when paging backward; replaces `first`.
+    pub last: Option<u32>,
+    /// The connection's sort key, e.g. a `ProductSortKeys` value for
+    /// `search_products`.
+    pub sort_key: Option<String>,
+    pub reverse: bool,
+}
+
+impl ListOptions {
+    pub(crate) fn from_js(options: Option<JsValue>) -> Result<ListOptions, JsValue> {
+        match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => crate::from_js(value),
+            _ => Ok(ListOptions::default()),
+        }
+    }
+
+    /// The cursor arguments, checked by `PageArgs::resolve`.
+    pub(crate) fn page_args(&self) -> Result<PageArgs, JsValue> {
+        PageArgs {
+            first: self.first,
+            after: self.after.clone(),
+            last: self.last,
+            before: self.before.clone(),
+        }
+        .resolve()
+    }
+}
+
+/// Cursor arguments for a connection. `first`/`after` page forward from
+/// `pageInfo.endCursor`; `last`/`before` page backward from
+/// `pageInfo.startCursor`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PageArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+}
+
+impl PageArgs {
+    pub fn forward(first: u32, after: Option<String>) -> PageArgs {
+        PageArgs {
+            first: Some(first),
+            after,
+            ..PageArgs::default()
+        }
+    }
+
+    pub fn backward(last: u32, before: Option<String>) -> PageArgs {
+        PageArgs {
+            last: Some(last),
+            before,
+            ..PageArgs::default()
+        }
+    }
+
+    /// Checks the combination and fills in a default page size: `last` when
+    /// paging backward from `before`, otherwise `first`. `first` and `last`
+    /// are mutually exclusive and must be between 1 and 250.
+    pub(crate) fn resolve(mut self) -> Result<PageArgs, JsValue> {
+        if self.first.is_some() && self.last.is_some() {
+            return Err(JsValue::from_str("Pass either first or last, not both"));
+        }
+        if self.first.is_none() && self.last.is_none() {
+            if self.before.is_some() {
+                self.last = Some(DEFAULT_PAGE_SIZE);
+            } else {
+                self.first = Some(DEFAULT_PAGE_SIZE);
+            }
+        }
+        for size in [self.first, self.last].into_iter().flatten() {
+            if !(1..=MAX_PAGE_SIZE).contains(&size) {
+                return Err(JsValue::from_str(&format!(
+                    "Page size must be between 1 and {MAX_PAGE_SIZE}, got {size}"
+                )));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Adds `first`, `after`, `last` and `before` to a variables object.
+    pub(crate) fn add_to(&self, variables: &mut serde_json::Value) {
+        variables["first"] = serde_json::json!(self.first);
+        variables["after"] = serde_json::json!(self.after);
+        variables["last"] = serde_json::json!(self.last);
+        variables["before"] = serde_json::json!(self.before);
+    }
+}
@@ -0,0 +1,229 @@
+//! Speculative prefetching for hover-to-prefetch navigation.
+//!
+//! `prefetch_product` and `prefetch_collection` start the same request
+//! `get_product` and `get_collection` would make, at low fetch priority,
+//! and keep the response for `PREFETCH_TTL_MS`. A later call making that
+//! request is answered from it, or, while the prefetch is still in flight,
+//! waits for it instead of sending a second request. Failed prefetches
+//! leave nothing behind, so the real call simply goes to the network.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+
+use crate::memory::json_size;
+use crate::StorefrontApi;
+
+/// How long a prefetched response answers calls.
+pub const PREFETCH_TTL_MS: f64 = 30_000.0;
+
+/// Most responses kept; the oldest go first.
+const MAX_PREFETCHED: usize = 32;
+
+enum Entry {
+    /// In flight; `promise` settles when it finishes either way.
+    Pending {
+        promise: js_sys::Promise,
+        resolve: js_sys::Function,
+    },
+    Ready(Value),
+}
+
+/// Prefetched responses by request, shared by a client's copies.
+#[derive(Default)]
+pub struct Prefetched {
+    /// Entries and when they were started or finished (ms).
+    entries: HashMap<String, (Entry, f64)>,
+}
+
+/// What a call finds for its request.
+enum Lookup {
+    Ready(Value),
+    Pending(js_sys::Promise),
+    Missing,
+}
+
+impl Prefetched {
+    fn lookup(&mut self, key: &str, now: f64) -> Lookup {
+        self.expire(now);
+        match self.entries.get(key) {
+            Some((Entry::Ready(data), _)) => Lookup::Ready(data.clone()),
+            Some((Entry::Pending { promise, .. }, _)) => Lookup::Pending(promise.clone()),
+            None => Lookup::Missing,
+        }
+    }
+
+    /// Marks `key` in flight, unless it's already prefetched or in flight.
+    fn begin(&mut self, key: &str, now: f64) -> bool {
+        self.expire(now);
+        if self.entries.contains_key(key) {
+            return false;
+        }
+        if self.entries.len() >= MAX_PREFETCHED {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(_, (entry, _))| matches!(entry, Entry::Ready(_)))
+                .min_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b))
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => self.entries.remove(&oldest),
+                None => return false,
+            };
+        }
+        let mut resolve = None;
+        let promise = js_sys::Promise::new(&mut |resolve_fn, _reject| resolve = Some(resolve_fn));
+        let resolve = resolve.expect("Promise executors run synchronously");
+        self.entries
+            .insert(key.to_string(), (Entry::Pending { promise, resolve }, now));
+        true
+    }
+
+    /// Stores a finished prefetch (or drops a failed one) and wakes the
+    /// calls waiting on it.
+    fn finish(&mut self, key: &str, data: Option<Value>, now: f64) {
+        let Some((Entry::Pending { resolve, .. }, _)) = self.entries.remove(key) else {
+            return;
+        };
+        if let Some(data) = data {
+            self.entries
+                .insert(key.to_string(), (Entry::Ready(data), now));
+        }
+        let _ = resolve.call0(&JsValue::NULL);
+    }
+
+    /// How many responses are kept, and their approximate size in bytes.
+    /// Requests in flight don't count.
+    pub fn usage(&self) -> (usize, usize) {
+        self.entries
+            .iter()
+            .filter_map(|(key, (entry, _))| match entry {
+                Entry::Ready(data) => Some(key.len() + json_size(data)),
+                Entry::Pending { .. } => None,
+            })
+            .fold((0, 0), |(count, total), bytes| (count + 1, total + bytes))
+    }
+
+    /// Drops the oldest finished prefetch, returning its size.
+    pub(crate) fn evict_oldest(&mut self) -> Option<usize> {
+        let (key, bytes) = self
+            .entries
+            .iter()
+            .filter_map(|(key, (entry, at))| match entry {
+                Entry::Ready(data) => Some((key, key.len() + json_size(data), *at)),
+                Entry::Pending { .. } => None,
+            })
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+            .map(|(key, bytes, _)| (key.clone(), bytes))?;
+        self.entries.remove(&key);
+        Some(bytes)
+    }
+
+    fn expire(&mut self, now: f64) {
+        self.entries.retain(|_, (entry, at)| {
+            matches!(entry, Entry::Pending { .. }) || now - *at < PREFETCH_TTL_MS
+        });
+    }
+}
+
+impl StorefrontApi {
+    /// A copy whose requests are prefetches: sent at low priority, with the
+    /// response kept for later calls.
+    fn prefetcher(&self) -> StorefrontApi {
+        let mut client = self.clone();
+        client.prefetching = true;
+        client
+    }
+
+    /// Sends a prefetch, unless the same request is already prefetched
+    /// or in flight (then `null`).
+    pub(crate) async fn prefetch_request(
+        &self,
+        key: String,
+        query: String,
+        variables: Value,
+    ) -> Result<Value, JsValue> {
+        if !self
+            .prefetched
+            .borrow_mut()
+            .begin(&key, js_sys::Date::now())
+        {
+            return Ok(Value::Null);
+        }
+        let result = self.send_operation(query, variables).await;
+        self.prefetched.borrow_mut().finish(
+            &key,
+            result.as_ref().ok().cloned(),
+            js_sys::Date::now(),
+        );
+        result
+    }
+
+    /// The prefetched response to a request, waiting for one in flight.
+    pub(crate) async fn take_prefetched(&self, key: &str) -> Option<Value> {
+        let pending = match self
+            .prefetched
+            .borrow_mut()
+            .lookup(key, js_sys::Date::now())
+        {
+            Lookup::Ready(data) => return Some(data),
+            Lookup::Pending(promise) => promise,
+            Lookup::Missing => return None,
+        };
+        let _ = JsFuture::from(pending).await;
+        match self
+            .prefetched
+            .borrow_mut()
+            .lookup(key, js_sys::Date::now())
+        {
+            Lookup::Ready(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Starts fetching what `get_product(handle, options)` will ask for,
+    /// in the background, e.g. when a product link is hovered. Pass the
+    /// options the page will use so the requests match. Never throws.
+    #[wasm_bindgen]
+    pub fn prefetch_product(
+        &self,
+        handle: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "ProductOptions")] options: Option<JsValue>,
+    ) {
+        let client = self.prefetcher();
+        spawn_local(async move {
+            let _ = client.get_product(handle, options).await;
+        });
+    }
+
+    /// Starts fetching what `get_collection(handle, first, options)` will
+    /// ask for, in the background. Never throws.
+    #[wasm_bindgen]
+    pub fn prefetch_collection(
+        &self,
+        handle: String,
+        first: Option<u32>,
+        #[wasm_bindgen(unchecked_optional_param_type = "CollectionOptions")] options: Option<
+            JsValue,
+        >,
+    ) {
+        let client = self.prefetcher();
+        spawn_local(async move {
+            let _ = client.get_collection(handle, first, options).await;
+        });
+    }
+
+    /// Drops prefetched responses, e.g. when they may be out of date.
+    #[wasm_bindgen]
+    pub fn clear_prefetched(&self) {
+        self.prefetched
+            .borrow_mut()
+            .entries
+            .retain(|_, (entry, _)| matches!(entry, Entry::Pending { .. }));
+    }
+}
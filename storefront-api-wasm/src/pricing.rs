@@ -0,0 +1,195 @@
+//! Price presentation helpers computed from typed product data.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::money::DEFAULT_LOCALE;
+use crate::types::{Money, Product, ProductVariant, UnitPriceMeasurement};
+use crate::{from_js, to_js};
+
+/// Sale status for strikethrough pricing.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaleInfo {
+    pub on_sale: bool,
+    pub price: Money,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare_at_price: Option<Money>,
+    /// `compare_at_price - price`, when on sale.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_off: Option<Money>,
+    /// Discount rounded to the nearest whole percent; `0` when not on sale.
+    pub percent_off: u32,
+}
+
+impl SaleInfo {
+    pub fn new(price: &Money, compare_at_price: Option<&Money>) -> SaleInfo {
+        let compare_at = compare_at_price.filter(|compare_at| {
+            compare_at.currency_code == price.currency_code && compare_at.value() > price.value()
+        });
+
+        match compare_at {
+            Some(compare_at) => {
+                let difference = compare_at.value() - price.value();
+                SaleInfo {
+                    on_sale: true,
+                    price: price.clone(),
+                    compare_at_price: Some(compare_at.clone()),
+                    amount_off: Some(Money {
+                        amount: format!("{difference:.2}"),
+                        currency_code: price.currency_code.clone(),
+                    }),
+                    percent_off: (difference / compare_at.value() * 100.0).round() as u32,
+                }
+            }
+            None => SaleInfo {
+                on_sale: false,
+                price: price.clone(),
+                compare_at_price: None,
+                amount_off: None,
+                percent_off: 0,
+            },
+        }
+    }
+
+    pub fn for_variant(variant: &ProductVariant) -> SaleInfo {
+        SaleInfo::new(&variant.price, variant.compare_at_price.as_ref())
+    }
+
+    /// Sale status for a product: the variant with the deepest discount when
+    /// variants were fetched, otherwise the minimum prices of the two ranges.
+    pub fn for_product(product: &Product) -> SaleInfo {
+        let best_variant = product.variants.as_ref().and_then(|variants| {
+            variants
+                .nodes()
+                .map(SaleInfo::for_variant)
+                .filter(|info| info.on_sale)
+                .max_by_key(|info| info.percent_off)
+        });
+
+        best_variant.unwrap_or_else(|| {
+            SaleInfo::new(
+                &product.price_range.min_variant_price,
+                product
+                    .compare_at_price_range
+                    .as_ref()
+                    .map(|range| &range.min_variant_price),
+            )
+        })
+    }
+}
+
+/// Computes `{ onSale, price, compareAtPrice, amountOff, percentOff }` for a
+/// product returned by `get_product`.
+#[wasm_bindgen(unchecked_return_type = "SaleInfo")]
+pub fn product_sale_info(
+    #[wasm_bindgen(unchecked_param_type = "Product")] product: JsValue,
+) -> Result<JsValue, JsValue> {
+    let product: Product = from_js(product)?;
+    to_js(&SaleInfo::for_product(&product))
+}
+
+/// Computes sale status for a single variant.
+#[wasm_bindgen(unchecked_return_type = "SaleInfo")]
+pub fn variant_sale_info(
+    #[wasm_bindgen(unchecked_param_type = "ProductVariant")] variant: JsValue,
+) -> Result<JsValue, JsValue> {
+    let variant: ProductVariant = from_js(variant)?;
+    to_js(&SaleInfo::for_variant(&variant))
+}
+
+/// Formats a variant's unit price for display, e.g. `€2.50 / 100ml` or
+/// `$4.99 / kg`. Returns `None` when the variant has no unit price.
+pub fn unit_price_label(variant: &ProductVariant) -> Option<String> {
+    let unit_price = variant.unit_price.as_ref()?;
+    let measurement = variant.unit_price_measurement.as_ref()?;
+    Some(format!(
+        "{} / {}",
+        unit_price.format(DEFAULT_LOCALE),
+        reference_label(measurement)?
+    ))
+}
+
+fn reference_label(measurement: &UnitPriceMeasurement) -> Option<String> {
+    let unit = unit_abbreviation(measurement.reference_unit.as_deref()?);
+    match measurement.reference_value {
+        Some(value) if value != 1 => Some(format!("{value}{unit}")),
+        _ => Some(unit),
+    }
+}
+
+/// Lower-cases `UnitPriceMeasurementMeasuredUnit` values the way price labels
+/// print them.
+fn unit_abbreviation(unit: &str) -> String {
+    match unit {
+        "M2" => "m²".to_string(),
+        "M3" => "m³".to_string(),
+        "FTSQ" => "ft²".to_string(),
+        "FLOZ" => "fl oz".to_string(),
+        "ITEM" => "item".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+/// Formats a variant's unit price (`"€2.50 / 100ml"`), or returns `undefined`
+/// when the variant has none.
+#[wasm_bindgen]
+pub fn format_unit_price(
+    #[wasm_bindgen(unchecked_param_type = "ProductVariant")] variant: JsValue,
+) -> Result<Option<String>, JsValue> {
+    let variant: ProductVariant = from_js(variant)?;
+    Ok(unit_price_label(&variant))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(amount: &str) -> Money {
+        Money {
+            amount: amount.to_string(),
+            currency_code: "USD".to_string(),
+        }
+    }
+
+    #[test]
+    fn a_higher_compare_at_price_is_a_sale() {
+        let info = SaleInfo::new(&money("19.99"), Some(&money("29.99")));
+
+        assert!(info.on_sale);
+        assert_eq!(info.amount_off, Some(money("10.00")));
+        assert_eq!(info.percent_off, 33);
+    }
+
+    #[test]
+    fn compare_at_prices_at_or_below_the_price_are_not_sales() {
+        for compare_at in ["19.99", "9.99"] {
+            let info = SaleInfo::new(&money("19.99"), Some(&money(compare_at)));
+
+            assert!(!info.on_sale, "compare-at {compare_at}");
+            assert_eq!(info.compare_at_price, None);
+            assert_eq!(info.amount_off, None);
+            assert_eq!(info.percent_off, 0);
+        }
+    }
+
+    #[test]
+    fn a_missing_compare_at_price_is_not_a_sale() {
+        let info = SaleInfo::new(&money("19.99"), None);
+
+        assert!(!info.on_sale);
+        assert_eq!(info.percent_off, 0);
+    }
+
+    #[test]
+    fn a_zero_price_is_entirely_off() {
+        let info = SaleInfo::new(&money("0.00"), Some(&money("20.00")));
+        assert!(info.on_sale);
+        assert_eq!(info.amount_off, Some(money("20.00")));
+        assert_eq!(info.percent_off, 100);
+
+        let free = SaleInfo::new(&money("0.00"), Some(&money("0.00")));
+        assert!(!free.on_sale);
+        assert_eq!(free.percent_off, 0);
+    }
+}
@@ -0,0 +1,148 @@
+//! Consent-aware mode for the tracking parts of the client.
+//!
+//! With consent required, the Shopify visitor tokens and analytics events
+//! (see `analytics`) are held back until consent for analytics processing
+//! is granted. Consent comes from Shopify's Customer Privacy API on the JS side,
+//! either pushed with `set_consent` from a `visitorConsentCollected` listener
+//! or pulled through a callback registered with `set_consent_callback`.
+//! Copies of the client share the consent and tokens.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::redact::Secret;
+use crate::{from_js, to_js, StorefrontApi};
+
+/// Consent flags in the shape of the `visitorConsentCollected` event detail.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConsentState {
+    pub analytics_allowed: bool,
+    pub marketing_allowed: bool,
+    pub preferences_allowed: bool,
+    pub sale_of_data_allowed: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct Privacy {
+    /// When false (the default) tracking is always allowed.
+    pub consent_required: bool,
+    /// The last state passed to `set_consent`.
+    pub consent: Option<ConsentState>,
+    /// Returns a boolean or a `ConsentState`-shaped object when called.
+    pub callback: Option<js_sys::Function>,
+    /// `_shopify_y`, sent as `Shopify-Storefront-Y`.
+    pub unique_token: Option<String>,
+    /// `_shopify_s`, sent as `Shopify-Storefront-S`.
+    pub visit_token: Option<String>,
+}
+
+impl std::fmt::Debug for Privacy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn token(token: &Option<String>) -> Option<Secret<'_>> {
+            token.as_deref().map(Secret)
+        }
+        f.debug_struct("Privacy")
+            .field("consent_required", &self.consent_required)
+            .field("consent", &self.consent)
+            .field("callback", &self.callback)
+            .field("unique_token", &token(&self.unique_token))
+            .field("visit_token", &token(&self.visit_token))
+            .finish()
+    }
+}
+
+impl Privacy {
+    /// Whether analytics processing is currently allowed. The callback, when
+    /// set, is asked on every check; a callback that throws or returns
+    /// something unrecognized counts as not granted.
+    pub fn analytics_allowed(&self) -> bool {
+        if !self.consent_required {
+            return true;
+        }
+        if let Some(callback) = &self.callback {
+            return match callback.call0(&JsValue::NULL) {
+                Ok(value) => match value.as_bool() {
+                    Some(granted) => granted,
+                    None if value.is_object() => from_js::<ConsentState>(value)
+                        .map(|state| state.analytics_allowed)
+                        .unwrap_or(false),
+                    None => false,
+                },
+                Err(_) => false,
+            };
+        }
+        self.consent.is_some_and(|state| state.analytics_allowed)
+    }
+
+    /// Visitor-token headers to attach to a request, empty unless analytics
+    /// processing is allowed.
+    pub(crate) fn tracking_headers(&self) -> Vec<(&'static str, &str)> {
+        if !self.analytics_allowed() {
+            return Vec::new();
+        }
+        [
+            ("Shopify-Storefront-Y", self.unique_token.as_deref()),
+            ("Shopify-Storefront-S", self.visit_token.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Turns consent-aware mode on or off. While on, tracking headers and
+    /// analytics calls are suppressed until analytics consent is granted.
+    #[wasm_bindgen]
+    pub fn require_consent(&self, required: bool) {
+        self.privacy.borrow_mut().consent_required = required;
+    }
+
+    /// Records the visitor's consent, e.g. the `detail` of Shopify's
+    /// `visitorConsentCollected` event: `{ analyticsAllowed,
+    /// marketingAllowed, preferencesAllowed, saleOfDataAllowed }`.
+    #[wasm_bindgen]
+    pub fn set_consent(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Partial<ConsentState>")] consent: JsValue,
+    ) -> Result<(), JsValue> {
+        self.privacy.borrow_mut().consent = Some(from_js(consent)?);
+        Ok(())
+    }
+
+    /// Registers a function asked before each tracked request, e.g.
+    /// `() => window.Shopify.customerPrivacy.analyticsProcessingAllowed()`.
+    /// Takes precedence over `set_consent`. Pass `undefined` to remove it.
+    #[wasm_bindgen]
+    pub fn set_consent_callback(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "() => boolean | Partial<ConsentState>")]
+        callback: Option<js_sys::Function>,
+    ) {
+        self.privacy.borrow_mut().callback = callback;
+    }
+
+    /// Sets the Shopify visitor tokens (the `_shopify_y` and `_shopify_s`
+    /// cookies) forwarded on requests once consent allows it.
+    #[wasm_bindgen]
+    pub fn set_tracking_tokens(&self, unique_token: Option<String>, visit_token: Option<String>) {
+        let mut privacy = self.privacy.borrow_mut();
+        privacy.unique_token = unique_token.filter(|token| !token.is_empty());
+        privacy.visit_token = visit_token.filter(|token| !token.is_empty());
+    }
+
+    /// Whether analytics processing is currently allowed.
+    #[wasm_bindgen]
+    pub fn analytics_allowed(&self) -> bool {
+        let privacy = self.privacy.borrow().clone();
+        privacy.analytics_allowed()
+    }
+
+    /// The consent recorded with `set_consent`, or `null`.
+    #[wasm_bindgen(unchecked_return_type = "ConsentState | null")]
+    pub fn consent(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.privacy.borrow().consent)
+    }
+}
@@ -0,0 +1,1051 @@
+//! Product queries and the selection sets they share.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::cost::estimate_query_cost;
+use crate::search::quote_search_term;
+use crate::types::{
+    Connection, GeoCoordinate, Media, MetafieldIdentifier, Product, ProductVariant,
+    StoreAvailability,
+};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Most aliased `product` fields sent in a single batch query; fewer when
+/// the cost limit doesn't fit this many.
+const MAX_BATCH_HANDLES: usize = 20;
+
+/// Variants fetched per product.
+const VARIANT_PAGE_SIZE: u32 = 100;
+
+/// Selling plan groups fetched per product when selling plans are
+/// requested. Each costs about 64 points, so more would put the product
+/// query over the default cost limit.
+const SELLING_PLAN_GROUP_PAGE_SIZE: u32 = 3;
+
+/// Media items fetched per product when media is requested.
+const MEDIA_PAGE_SIZE: u32 = 20;
+
+/// Targets resolved per `list.*_reference` metafield.
+pub(crate) const REFERENCE_PAGE_SIZE: u32 = 25;
+
+/// Selection for resolved metafield references; products use the card
+/// fragment, so it must be appended to the operation.
+pub(crate) const RESOLVED_REFERENCE_FIELDS: &str = r#"
+    __typename
+    ... on Product { ...ProductCardFields }
+    ... on ProductVariant {
+        id
+        title
+        price { amount currencyCode }
+        compareAtPrice { amount currencyCode }
+        availableForSale
+        selectedOptions { name value }
+        image { url altText width height }
+        product { id handle title }
+    }
+    ... on Collection { id handle title }
+    ... on Page { id handle title }
+    ... on Metaobject { id handle type updatedAt fields { key type value } }
+    ... on MediaImage { id alt image { url altText width height } }
+    ... on Video { id alt sources { url mimeType format width height } }
+    ... on GenericFile { id url mimeType alt }
+"#;
+
+/// Optional extras for product queries, passed from JavaScript as a plain
+/// object, e.g. `{ metafields: [{ namespace: "custom", key: "fit" }] }`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProductOptions {
+    pub metafields: Vec<MetafieldIdentifier>,
+    /// Include `media` (images, videos, external videos and 3D models).
+    pub media: bool,
+    /// Include `sellingPlanGroups` and per-variant `sellingPlanAllocations`
+    /// for subscription pricing.
+    pub selling_plans: bool,
+    /// Include `quantityAvailable` and `currentlyNotInStock` on variants.
+    pub inventory: bool,
+    /// Include bundle data: `requiresComponents` and the `components` each
+    /// variant is assembled from.
+    pub components: bool,
+    /// Resolve reference-typed `metafields` into the objects they point at
+    /// (products, variants, collections, pages, metaobjects and files).
+    pub resolve_references: bool,
+}
+
+impl ProductOptions {
+    pub(crate) fn from_js(options: Option<JsValue>) -> Result<Self, JsValue> {
+        match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => {
+                serde_wasm_bindgen::from_value(value).map_err(Into::into)
+            }
+            _ => Ok(ProductOptions::default()),
+        }
+    }
+}
+
+/// Which products a listing shows. Both checks are off by default.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ListingVisibility {
+    /// Hide products with every variant sold out.
+    pub available_only: bool,
+    /// Hide products not published to the Online Store (no `onlineStoreUrl`).
+    pub published_only: bool,
+}
+
+impl ListingVisibility {
+    pub fn shows(&self, product: &Product) -> bool {
+        if self.published_only && product.online_store_url.is_none() {
+            return false;
+        }
+        if self.available_only {
+            // Fall back to the variants when `availableForSale` wasn't selected.
+            let available = product.available_for_sale.unwrap_or_else(|| {
+                product.variants.as_ref().is_none_or(|variants| {
+                    variants.nodes().any(|variant| variant.available_for_sale)
+                })
+            });
+            if !available {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Drops unpublished and/or sold-out products from a list returned by any
+/// listing helper. `options` is `{ availableOnly, publishedOnly }`.
+#[wasm_bindgen(unchecked_return_type = "Product[]")]
+pub fn filter_listings(
+    #[wasm_bindgen(unchecked_param_type = "Product[]")] products: JsValue,
+    #[wasm_bindgen(unchecked_optional_param_type = "ListingVisibility")] options: Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    let products: Vec<Product> = from_js(products)?;
+    let visibility: ListingVisibility = match options {
+        Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+        _ => ListingVisibility::default(),
+    };
+    let visible: Vec<&Product> = products
+        .iter()
+        .filter(|product| visibility.shows(product))
+        .collect();
+    to_js(&visible)
+}
+
+/// Builds the `ProductFields` fragment shared by every single-product query,
+/// extended with whatever `options` opts into.
+pub(crate) fn product_fragment(options: &ProductOptions) -> String {
+    let mut extra = String::new();
+    let mut fragments = String::new();
+    if !options.metafields.is_empty() {
+        let references = if options.resolve_references {
+            fragments.push_str(PRODUCT_CARD_FRAGMENT);
+            format!(
+                r#"
+            reference {{
+                {RESOLVED_REFERENCE_FIELDS}
+            }}
+            references(first: {REFERENCE_PAGE_SIZE}) {{
+                edges {{
+                    node {{
+                        {RESOLVED_REFERENCE_FIELDS}
+                    }}
+                }}
+            }}"#
+            )
+        } else {
+            String::new()
+        };
+        extra.push_str(&format!(
+            r#"
+        metafields(identifiers: {}) {{
+            id
+            namespace
+            key
+            type
+            value{references}
+        }}"#,
+            metafield_identifiers_literal(&options.metafields)
+        ));
+    }
+
+    let mut variant_extra = String::new();
+    if options.selling_plans {
+        extra.push_str(&format!(
+            r#"
+        requiresSellingPlan
+        sellingPlanGroups(first: {SELLING_PLAN_GROUP_PAGE_SIZE}) {{
+            edges {{
+                node {{
+                    name
+                    appName
+                    options {{ name values }}
+                    sellingPlans(first: 10) {{
+                        edges {{
+                            node {{
+                                id
+                                name
+                                description
+                                recurringDeliveries
+                                options {{ name value }}
+                                priceAdjustments {{
+                                    orderCount
+                                    adjustmentValue {{
+                                        __typename
+                                        ... on SellingPlanFixedAmountPriceAdjustment {{
+                                            adjustmentAmount {{ amount currencyCode }}
+                                        }}
+                                        ... on SellingPlanFixedPriceAdjustment {{
+                                            price {{ amount currencyCode }}
+                                        }}
+                                        ... on SellingPlanPercentagePriceAdjustment {{
+                                            adjustmentPercentage
+                                        }}
+                                    }}
+                                }}
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+        }}"#
+        ));
+    }
+
+    if options.inventory {
+        variant_extra.push_str(
+            r#"
+                    quantityAvailable
+                    currentlyNotInStock"#,
+        );
+    }
+
+    if options.media {
+        extra.push_str(&format!(
+            r#"
+        media(first: {MEDIA_PAGE_SIZE}) {{
+            edges {{
+                node {{
+                    ...MediaFields
+                }}
+            }}
+        }}"#
+        ));
+        fragments.push_str(MEDIA_FRAGMENT);
+    }
+
+    format!(
+        r#"
+    fragment ProductFields on Product {{
+        id
+        title
+        description
+        handle
+        vendor
+        productType
+        tags
+        isGiftCard
+        availableForSale
+        publishedAt
+        onlineStoreUrl
+        seo {{
+            title
+            description
+        }}
+        options {{
+            id
+            name
+            optionValues {{
+                id
+                name
+                swatch {{
+                    color
+                    image {{
+                        previewImage {{
+                            url
+                            altText
+                            width
+                            height
+                        }}
+                    }}
+                }}
+            }}
+        }}
+        priceRange {{
+            minVariantPrice {{
+                amount
+                currencyCode
+            }}
+            maxVariantPrice {{
+                amount
+                currencyCode
+            }}
+        }}
+        compareAtPriceRange {{
+            minVariantPrice {{
+                amount
+                currencyCode
+            }}
+            maxVariantPrice {{
+                amount
+                currencyCode
+            }}
+        }}
+        images(first: 10) {{
+            edges {{
+                node {{
+                    id
+                    url
+                    altText
+                    width
+                    height
+                }}
+            }}
+        }}
+        variants(first: {VARIANT_PAGE_SIZE}) {{
+            edges {{
+                node {{
+                    id
+                    title
+                    sku
+                    barcode
+                    price {{
+                        amount
+                        currencyCode
+                    }}
+                    compareAtPrice {{
+                        amount
+                        currencyCode
+                    }}
+                    unitPrice {{
+                        amount
+                        currencyCode
+                    }}
+                    unitPriceMeasurement {{
+                        measuredType
+                        quantityUnit
+                        quantityValue
+                        referenceUnit
+                        referenceValue
+                    }}
+                    availableForSale
+                    selectedOptions {{
+                        name
+                        value
+                    }}
+                    image {{
+                        url
+                        altText
+                    }}{variant_extra}
+                }}
+            }}
+        }}{extra}
+    }}
+{fragments}"#
+    )
+}
+
+/// Per-variant connections `options` opts into. On all of a product's
+/// variants they'd cost far more than one query may, so `complete_variants`
+/// fetches them a page of variants at a time after the product.
+fn variant_details(options: &ProductOptions) -> String {
+    let mut details = String::new();
+    if options.selling_plans {
+        details.push_str(
+            r#"
+                        sellingPlanAllocations(first: 10) {
+                            edges {
+                                node {
+                                    sellingPlan { id }
+                                    priceAdjustments {
+                                        price { amount currencyCode }
+                                        compareAtPrice { amount currencyCode }
+                                        perDeliveryPrice { amount currencyCode }
+                                    }
+                                }
+                            }
+                        }"#,
+        );
+    }
+
+    if options.components {
+        details.push_str(
+            r#"
+                        requiresComponents
+                        components(first: 25) {
+                            edges {
+                                node {
+                                    quantity
+                                    productVariant {
+                                        id
+                                        title
+                                        availableForSale
+                                        price { amount currencyCode }
+                                        image { url altText }
+                                        product { id handle title }
+                                    }
+                                }
+                            }
+                        }"#,
+        );
+    }
+    details
+}
+
+/// Fetches `details` for a page of a product's variants.
+fn variant_details_query(details: &str) -> String {
+    format!(
+        r#"
+    query getVariantDetails($id: ID!, $first: Int!, $after: String) {{
+        product(id: $id) {{
+            variants(first: $first, after: $after) {{
+                pageInfo {{
+                    hasNextPage
+                    endCursor
+                }}
+                edges {{
+                    node {{
+                        id{details}
+                    }}
+                }}
+            }}
+        }}
+    }}"#
+    )
+}
+
+/// Copies the fields of `details` (a variant from `getVariantDetails`) onto
+/// the variant of `product` with the same ID.
+fn merge_variant_details(product: &mut Value, details: Value) {
+    let Value::Object(details) = details else {
+        return;
+    };
+    let edges = product["variants"]["edges"].as_array_mut();
+    let variant = edges
+        .into_iter()
+        .flatten()
+        .find(|edge| edge["node"]["id"] == details.get("id").cloned().unwrap_or(Value::Null));
+    if let Some(Value::Object(variant)) = variant.map(|edge| &mut edge["node"]) {
+        variant.extend(details);
+    }
+}
+
+/// Candidate products for a SKU or barcode lookup, with only the variant
+/// fields the match needs.
+fn variant_lookup_query() -> String {
+    format!(
+        r#"
+    query findVariant($query: String!, $first: Int!) {{
+        products(first: $first, query: $query) {{
+            edges {{
+                node {{
+                    id
+                    variants(first: {VARIANT_PAGE_SIZE}) {{
+                        edges {{
+                            node {{
+                                id
+                                sku
+                                barcode
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+        }}
+    }}"#
+    )
+}
+
+/// A query with an aliased `product` field (`p0`, `p1`, ...) per handle or
+/// ID, and its variables.
+fn batch_query(handles_or_ids: &[String], fragment: &str) -> (String, serde_json::Value) {
+    let mut definitions = Vec::new();
+    let mut fields = String::new();
+    let mut variables = serde_json::Map::new();
+    for (i, key) in handles_or_ids.iter().enumerate() {
+        let (argument, kind) = if key.starts_with("gid://shopify/Product/") {
+            ("id", "ID!")
+        } else {
+            ("handle", "String!")
+        };
+        definitions.push(format!("$h{i}: {kind}"));
+        fields.push_str(&format!(
+            "p{i}: product({argument}: $h{i}) {{ ...ProductFields }}\n"
+        ));
+        variables.insert(format!("h{i}"), serde_json::Value::String(key.clone()));
+    }
+
+    let query = format!(
+        "query getProductsByHandles({}) {{\n{}}}\n{}",
+        definitions.join(", "),
+        fields,
+        fragment
+    );
+    (query, serde_json::Value::Object(variables))
+}
+
+/// Renders identifiers as an inline GraphQL list so the fragment stays
+/// self-contained and needs no extra operation variables.
+pub(crate) fn metafield_identifiers_literal(identifiers: &[MetafieldIdentifier]) -> String {
+    let items: Vec<String> = identifiers
+        .iter()
+        .map(|identifier| {
+            format!(
+                "{{namespace: {}, key: {}}}",
+                graphql_string(&identifier.namespace),
+                graphql_string(&identifier.key)
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Quotes a value as a GraphQL string literal (JSON escaping is compatible).
+pub(crate) fn graphql_string(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+/// Fields for every media type a product can carry.
+const MEDIA_FRAGMENT: &str = r#"
+    fragment MediaFields on Media {
+        __typename
+        ... on MediaImage {
+            id
+            alt
+            previewImage { url altText width height }
+            image { id url altText width height }
+        }
+        ... on Video {
+            id
+            alt
+            previewImage { url altText width height }
+            sources { url mimeType format width height }
+        }
+        ... on ExternalVideo {
+            id
+            alt
+            previewImage { url altText width height }
+            host
+            embedUrl
+            originUrl
+        }
+        ... on Model3d {
+            id
+            alt
+            previewImage { url altText width height }
+            sources { url mimeType format filesize }
+        }
+    }
+"#;
+
+/// Candidate products fetched for SKU/barcode lookups; the search is fuzzy, so
+/// the exact variant is matched client-side.
+const IDENTIFIER_LOOKUP_CANDIDATES: u32 = 5;
+
+/// A variant together with the product it belongs to.
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantMatch {
+    pub product: Product,
+    pub variant: ProductVariant,
+}
+
+/// Card-level product fields used by listings (collections, search).
+pub(crate) const PRODUCT_CARD_FRAGMENT: &str = r#"
+    fragment ProductCardFields on Product {
+        id
+        title
+        handle
+        vendor
+        isGiftCard
+        availableForSale
+        publishedAt
+        onlineStoreUrl
+        priceRange {
+            minVariantPrice {
+                amount
+                currencyCode
+            }
+        }
+        compareAtPriceRange {
+            minVariantPrice {
+                amount
+                currencyCode
+            }
+        }
+        images(first: 1) {
+            edges {
+                node {
+                    url
+                    altText
+                }
+            }
+        }
+    }
+"#;
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    #[wasm_bindgen(unchecked_return_type = "{ product: Product | null }")]
+    pub async fn get_product(
+        &self,
+        handle: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "ProductOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options = ProductOptions::from_js(options)?;
+        let query = format!(
+            r#"
+            query getProduct($handle: String!) {{
+                product(handle: $handle) {{
+                    ...ProductFields
+                }}
+            }}
+            {}
+        "#,
+            product_fragment(&options)
+        );
+
+        let variables = serde_json::json!({
+            "handle": handle
+        });
+
+        let mut data = self.execute(&query, variables).await?;
+        let mut product = data["product"].take();
+        self.complete_variants(&mut product, &options).await?;
+        let product: Option<Product> = from_json(product)?;
+        to_js(&serde_json::json!({ "product": product }))
+    }
+
+    /// Fetches a product by its global ID (`gid://shopify/Product/...`) using the
+    /// same selection set as `get_product`.
+    #[wasm_bindgen(unchecked_return_type = "{ product: Product | null }")]
+    pub async fn get_product_by_id(
+        &self,
+        id: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "ProductOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options = ProductOptions::from_js(options)?;
+        let query = format!(
+            r#"
+            query getProductById($id: ID!) {{
+                product(id: $id) {{
+                    ...ProductFields
+                }}
+            }}
+            {}
+        "#,
+            product_fragment(&options)
+        );
+
+        let variables = serde_json::json!({
+            "id": id
+        });
+
+        let mut data = self.execute(&query, variables).await?;
+        let mut product = data["product"].take();
+        self.complete_variants(&mut product, &options).await?;
+        let product: Option<Product> = from_json(product)?;
+        to_js(&serde_json::json!({ "product": product }))
+    }
+
+    /// Fetches several products in one aliased query and returns an object
+    /// mapping each requested handle to its product (or `null` when not found).
+    #[wasm_bindgen(unchecked_return_type = "Record<string, Product | null>")]
+    pub async fn get_products_by_handles(
+        &self,
+        handles: Vec<String>,
+        #[wasm_bindgen(unchecked_optional_param_type = "ProductOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options = ProductOptions::from_js(options)?;
+
+        let mut unique: Vec<String> = Vec::new();
+        for handle in handles {
+            if !unique.contains(&handle) {
+                unique.push(handle);
+            }
+        }
+
+        let fetched = self.fetch_products(&unique, &options).await?;
+        let products: BTreeMap<String, Option<Product>> = unique.into_iter().zip(fetched).collect();
+        to_js(&products)
+    }
+
+    /// Fetches a product's gallery media as a list of typed items (`MediaImage`,
+    /// `Video`, `ExternalVideo`, `Model3d`), each tagged with `__typename`.
+    #[wasm_bindgen(unchecked_return_type = "Media[]")]
+    pub async fn get_product_media(
+        &self,
+        handle: String,
+        first: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        let limit = first.unwrap_or(MEDIA_PAGE_SIZE);
+        let query = format!(
+            r#"
+            query getProductMedia($handle: String!, $first: Int!) {{
+                product(handle: $handle) {{
+                    media(first: $first) {{
+                        edges {{
+                            node {{
+                                ...MediaFields
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+            {MEDIA_FRAGMENT}
+        "#
+        );
+
+        let variables = serde_json::json!({
+            "handle": handle,
+            "first": limit
+        });
+
+        let mut data = self.execute(&query, variables).await?;
+        let media: Option<Connection<Media>> = from_json(data["product"]["media"].take())?;
+        let items: Vec<Media> = media
+            .map(|media| media.edges.into_iter().map(|edge| edge.node).collect())
+            .unwrap_or_default();
+        to_js(&items)
+    }
+
+    /// Fetches "you may also like" products for a product GID. `intent` is
+    /// `RELATED` (the default) or `COMPLEMENTARY`.
+    #[wasm_bindgen(unchecked_return_type = "{ productRecommendations: Product[] | null }")]
+    pub async fn get_product_recommendations(
+        &self,
+        product_id: String,
+        intent: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        let intent = intent
+            .map(|intent| intent.to_uppercase())
+            .unwrap_or_else(|| "RELATED".to_string());
+        if intent != "RELATED" && intent != "COMPLEMENTARY" {
+            return Err(JsValue::from_str(&format!(
+                "Unknown recommendation intent: {intent} (expected RELATED or COMPLEMENTARY)"
+            )));
+        }
+
+        let query = format!(
+            r#"
+            query getProductRecommendations($productId: ID!, $intent: ProductRecommendationIntent) {{
+                productRecommendations(productId: $productId, intent: $intent) {{
+                    ...ProductCardFields
+                }}
+            }}
+            {PRODUCT_CARD_FRAGMENT}
+        "#
+        );
+
+        let variables = serde_json::json!({
+            "productId": product_id,
+            "intent": intent
+        });
+
+        to_js(&self.execute(&query, variables).await?)
+    }
+
+    /// Finds the variant with exactly this SKU, returning `{ product, variant }`
+    /// or `null`.
+    #[wasm_bindgen(unchecked_return_type = "VariantMatch | null")]
+    pub async fn get_product_by_sku(&self, sku: String) -> Result<JsValue, JsValue> {
+        let found = self.find_variant("sku", &sku).await?;
+        to_js(&found)
+    }
+
+    /// Finds the variant with exactly this barcode (UPC/EAN/ISBN), returning
+    /// `{ product, variant }` or `null`.
+    #[wasm_bindgen(unchecked_return_type = "VariantMatch | null")]
+    pub async fn get_variant_by_barcode(&self, barcode: String) -> Result<JsValue, JsValue> {
+        let found = self.find_variant("barcode", &barcode).await?;
+        to_js(&found)
+    }
+
+    /// Lists pickup locations for a variant GID with availability, address and
+    /// pickup estimate. When `near` (`{ latitude, longitude }`) is given,
+    /// locations are sorted by distance from it.
+    #[wasm_bindgen(unchecked_return_type = "StoreAvailability[]")]
+    pub async fn get_store_availability(
+        &self,
+        variant_id: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "GeoCoordinate")] near: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let near: Option<GeoCoordinate> = match near {
+            Some(value) if !value.is_undefined() && !value.is_null() => Some(from_js(value)?),
+            _ => None,
+        };
+
+        let query = r#"
+            query getStoreAvailability($id: ID!, $near: GeoCoordinateInput) {
+                node(id: $id) {
+                    ... on ProductVariant {
+                        storeAvailability(first: 20, near: $near) {
+                            edges {
+                                node {
+                                    available
+                                    pickUpTime
+                                    quantityAvailable
+                                    location {
+                                        id
+                                        name
+                                        address {
+                                            address1
+                                            address2
+                                            city
+                                            province
+                                            zip
+                                            country
+                                            countryCode
+                                            phone
+                                            formatted
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let variables = serde_json::json!({
+            "id": variant_id,
+            "near": near
+        });
+
+        let mut data = self.execute(query, variables).await?;
+        let availability: Option<Connection<StoreAvailability>> =
+            from_json(data["node"]["storeAvailability"].take())?;
+        let locations: Vec<StoreAvailability> = availability
+            .map(|connection| connection.edges.into_iter().map(|edge| edge.node).collect())
+            .unwrap_or_default();
+        to_js(&locations)
+    }
+}
+
+impl StorefrontApi {
+    /// Fetches products by handle or `gid://shopify/Product/...` ID with
+    /// aliased `product` fields, as many per request as fit under the query
+    /// cost limit (at most `MAX_BATCH_HANDLES`). Results are in input order,
+    /// `None` where nothing matched.
+    pub(crate) async fn fetch_products(
+        &self,
+        handles_or_ids: &[String],
+        options: &ProductOptions,
+    ) -> Result<Vec<Option<Product>>, JsValue> {
+        let fragment = product_fragment(options);
+        let Some(first) = handles_or_ids.first() else {
+            return Ok(Vec::new());
+        };
+        let (single, variables) = batch_query(std::slice::from_ref(first), &fragment);
+        let per_product = estimate_query_cost(&single, &variables);
+        let batch_size = self
+            .cost
+            .borrow()
+            .items_per_query(0, per_product, MAX_BATCH_HANDLES);
+
+        let mut products = Vec::with_capacity(handles_or_ids.len());
+        for chunk in handles_or_ids.chunks(batch_size) {
+            let (query, variables) = batch_query(chunk, &fragment);
+            let mut data = self.execute(&query, variables).await?;
+            for i in 0..chunk.len() {
+                let mut product = data[format!("p{i}")].take();
+                self.complete_variants(&mut product, options).await?;
+                products.push(from_json(product)?);
+            }
+        }
+        Ok(products)
+    }
+
+    /// Adds the variant details `options` asks for (see `variant_details`)
+    /// to `product`, the JSON of a `ProductFields` selection or `null`, a
+    /// page of variants per request sized to the query cost limit.
+    pub(crate) async fn complete_variants(
+        &self,
+        product: &mut Value,
+        options: &ProductOptions,
+    ) -> Result<(), JsValue> {
+        let details = variant_details(options);
+        let Some(id) = product["id"].as_str().map(String::from) else {
+            return Ok(());
+        };
+        let variants = product["variants"]["edges"].as_array().map_or(0, Vec::len);
+        if details.is_empty() || variants == 0 {
+            return Ok(());
+        }
+
+        let query = variant_details_query(&details);
+        let estimate = |first: usize| {
+            estimate_query_cost(&query, &serde_json::json!({ "id": id, "first": first }))
+        };
+        let per_variant = estimate(2).saturating_sub(estimate(1));
+        let overhead = estimate(1).saturating_sub(per_variant);
+        let first = self
+            .cost
+            .borrow()
+            .items_per_query(overhead, per_variant, variants);
+
+        let mut fetched = 0;
+        let mut after: Option<String> = None;
+        while fetched < variants {
+            let variables = serde_json::json!({ "id": id, "first": first, "after": after });
+            let mut data = self.execute(&query, variables).await?;
+            let mut page = data["product"]["variants"].take();
+            let edges = page["edges"].as_array_mut().map(std::mem::take);
+            let edges = edges.unwrap_or_default();
+            if edges.is_empty() {
+                break;
+            }
+            for mut edge in edges {
+                merge_variant_details(product, edge["node"].take());
+                fetched += 1;
+            }
+            match page["pageInfo"]["endCursor"].as_str() {
+                Some(cursor) if page["pageInfo"]["hasNextPage"] == true => {
+                    after = Some(cursor.to_string());
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Searches products with `field:"value"` and returns the first variant
+    /// whose `field` is exactly `value`, with its product. Candidates are
+    /// searched with their variants' identifiers only; just the match is
+    /// fetched in full.
+    async fn find_variant(
+        &self,
+        field: &str,
+        value: &str,
+    ) -> Result<Option<VariantMatch>, JsValue> {
+        let variables = serde_json::json!({
+            "query": format!("{field}:{}", quote_search_term(value)),
+            "first": IDENTIFIER_LOOKUP_CANDIDATES
+        });
+
+        let data = self.execute(&variant_lookup_query(), variables).await?;
+        let mut candidates = data["products"]["edges"].as_array().into_iter().flatten();
+        let found = candidates.find_map(|edge| {
+            let variants = edge["node"]["variants"]["edges"].as_array()?;
+            let variant = variants
+                .iter()
+                .find(|variant| variant["node"][field] == value)?;
+            Some((
+                edge["node"]["id"].as_str()?.to_string(),
+                variant["node"]["id"].as_str()?.to_string(),
+            ))
+        });
+        let Some((product_id, variant_id)) = found else {
+            return Ok(None);
+        };
+
+        let product = self
+            .fetch_products(&[product_id], &ProductOptions::default())
+            .await?
+            .pop()
+            .flatten();
+        Ok(product.and_then(|product| {
+            let variant = product
+                .variants
+                .as_ref()?
+                .nodes()
+                .find(|variant| variant.id == variant_id)?
+                .clone();
+            Some(VariantMatch { product, variant })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::cost::{CostLimiter, CostLimits};
+
+    const LIMIT: u64 = 1000;
+
+    #[test]
+    fn product_queries_fit_under_the_default_cost_limit() {
+        assert_eq!(CostLimits::default().max_query_cost, LIMIT);
+        let options = [
+            ProductOptions::default(),
+            ProductOptions {
+                media: true,
+                ..ProductOptions::default()
+            },
+            ProductOptions {
+                selling_plans: true,
+                ..ProductOptions::default()
+            },
+            ProductOptions {
+                inventory: true,
+                ..ProductOptions::default()
+            },
+            ProductOptions {
+                components: true,
+                ..ProductOptions::default()
+            },
+        ];
+        for options in options {
+            let query = format!(
+                "query getProduct($handle: String!) {{ product(handle: $handle) {{ ...ProductFields }} }}\n{}",
+                product_fragment(&options)
+            );
+            let cost = estimate_query_cost(&query, &json!({ "handle": "tee" }));
+            assert!(cost <= LIMIT, "{options:?} costs {cost}");
+        }
+    }
+
+    #[test]
+    fn variant_detail_pages_fit_under_the_default_cost_limit() {
+        for options in [
+            ProductOptions {
+                selling_plans: true,
+                ..ProductOptions::default()
+            },
+            ProductOptions {
+                components: true,
+                ..ProductOptions::default()
+            },
+        ] {
+            let query = variant_details_query(&variant_details(&options));
+            let estimate = |first: usize| estimate_query_cost(&query, &json!({ "first": first }));
+            let per_variant = estimate(2) - estimate(1);
+            let first = CostLimiter::default().items_per_query(
+                estimate(1) - per_variant,
+                per_variant,
+                VARIANT_PAGE_SIZE as usize,
+            );
+
+            assert!(first > 1, "{options:?} fits {first} variant per page");
+            assert!(estimate(first) <= LIMIT);
+        }
+    }
+
+    #[test]
+    fn variant_lookups_fit_under_the_default_cost_limit() {
+        let variables = json!({ "query": "sku:\"A1\"", "first": IDENTIFIER_LOOKUP_CANDIDATES });
+
+        assert!(estimate_query_cost(&variant_lookup_query(), &variables) <= LIMIT);
+    }
+
+    #[test]
+    fn batches_fit_under_the_default_cost_limit() {
+        let fragment = product_fragment(&ProductOptions::default());
+        let handles: Vec<String> = (0..MAX_BATCH_HANDLES)
+            .map(|i| format!("product-{i}"))
+            .collect();
+        let (single, variables) = batch_query(&handles[..1], &fragment);
+        let per_product = estimate_query_cost(&single, &variables);
+
+        let batch_size = CostLimiter::default().items_per_query(0, per_product, MAX_BATCH_HANDLES);
+        let (query, variables) = batch_query(&handles[..batch_size], &fragment);
+
+        assert!(estimate_query_cost(&query, &variables) <= LIMIT);
+    }
+}
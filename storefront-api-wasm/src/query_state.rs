@@ -0,0 +1,320 @@
+//! Queries as observable state, for UI frameworks.
+//!
+//! `query_handle(query, variables)` returns a handle holding the query's
+//! state, `{ status, data, error, updatedAt }`, where `status` moves
+//! through:
+//!
+//! - `idle`: nothing requested yet (only with `{ lazy: true }`) or the
+//!   first request was cancelled;
+//! - `loading`: the first request is in flight;
+//! - `data`: the last request succeeded;
+//! - `error`: the last request failed; `data` keeps the last result, if
+//!   any;
+//! - `refreshing`: a `refetch()` is in flight while `data` is shown.
+//!
+//! `subscribe(listener)` calls `listener(state)` at once and on every
+//! change and returns the function that unsubscribes, which is the Svelte
+//! store contract and what React's `useSyncExternalStore` and a Vue `ref`
+//! need:
+//!
+//! ```js
+//! const handle = api.query_handle(PRODUCT_QUERY, { handle });
+//! const state = useSyncExternalStore(
+//!   (notify) => handle.subscribe(notify),
+//!   () => handle.state(),
+//! );
+//! ```
+//!
+//! `state()` returns the same object until the state changes, as
+//! `useSyncExternalStore` requires. `refetch()` always goes to the network,
+//! and `cancel()` aborts the request in flight and goes back to the state
+//! before it.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, spawn_local};
+
+use crate::fanout::error_value;
+use crate::{from_js, to_js, StorefrontApi};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueryStatus {
+    Idle,
+    Loading,
+    Data,
+    Error,
+    Refreshing,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QueryHandleOptions {
+    /// Wait for the first `refetch()` instead of loading at once.
+    pub lazy: bool,
+}
+
+/// A query's state as listeners see it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryState {
+    pub status: QueryStatus,
+    /// The last successful result.
+    pub data: Option<Value>,
+    /// The error of the last request that failed, `{ kind, category,
+    /// code, message, retryable, errors }`, until one succeeds.
+    pub error: Option<Value>,
+    /// When `data` arrived, in milliseconds since the epoch.
+    pub updated_at: Option<f64>,
+}
+
+struct HandleState {
+    state: QueryState,
+    /// `state` as a JavaScript object, rebuilt on every change.
+    snapshot: JsValue,
+    listeners: Vec<(u32, js_sys::Function)>,
+    next_listener: u32,
+    /// Counts requests, so a superseded or cancelled one is ignored.
+    request: u32,
+    /// Aborts the request in flight.
+    controller: Option<web_sys::AbortController>,
+    /// The status to go back to when that request is cancelled.
+    before: QueryStatus,
+}
+
+impl HandleState {
+    fn set_status(&mut self, status: QueryStatus) {
+        self.state.status = status;
+        self.snapshot = to_js(&self.state).unwrap_or(JsValue::NULL);
+    }
+}
+
+/// A query's state and the requests that change it; see `query_state`.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct QueryHandle {
+    client: StorefrontApi,
+    query: String,
+    variables: Value,
+    inner: Rc<RefCell<HandleState>>,
+}
+
+impl QueryHandle {
+    /// Calls every listener with the current state.
+    fn notify(&self) {
+        let (listeners, snapshot) = {
+            let inner = self.inner.borrow();
+            let listeners: Vec<js_sys::Function> = inner
+                .listeners
+                .iter()
+                .map(|(_, listener)| listener.clone())
+                .collect();
+            (listeners, inner.snapshot.clone())
+        };
+        for listener in listeners {
+            // One throwing listener mustn't keep the others from updating.
+            let _ = listener.call1(&JsValue::NULL, &snapshot);
+        }
+    }
+
+    /// Sends the query, from the network when `fresh`, and moves to
+    /// `loading` or `refreshing` at once; the future settles the result.
+    fn start(&self, fresh: bool) -> impl std::future::Future<Output = JsValue> {
+        let (request, client) = {
+            let mut inner = self.inner.borrow_mut();
+            if let Some(controller) = inner.controller.take() {
+                controller.abort();
+            } else {
+                inner.before = inner.state.status;
+            }
+            inner.request += 1;
+            let controller = web_sys::AbortController::new().ok();
+            let client = match &controller {
+                Some(controller) => self.client.with_signal(controller.signal()),
+                None => self.client.clone(),
+            };
+            inner.controller = controller;
+            let status = match inner.state.data {
+                Some(_) => QueryStatus::Refreshing,
+                None => QueryStatus::Loading,
+            };
+            inner.set_status(status);
+            (inner.request, client)
+        };
+        self.notify();
+
+        let handle = self.clone();
+        async move {
+            let result = if fresh {
+                let query = client.context.borrow().apply(&handle.query);
+                let key = crate::canonical::request_key(&query, &handle.variables);
+                let result = client
+                    .send_operation(query.clone(), handle.variables.clone())
+                    .await;
+                if let Ok(data) = &result {
+                    client.cache_response(&key, &query, data);
+                }
+                result
+            } else {
+                client
+                    .execute(&handle.query, handle.variables.clone())
+                    .await
+            };
+            handle.settle(request, result)
+        }
+    }
+
+    /// Records the result of request number `request`, unless a later one
+    /// replaced it, and returns the state.
+    fn settle(&self, request: u32, result: Result<Value, JsValue>) -> JsValue {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.request != request {
+                return inner.snapshot.clone();
+            }
+            inner.controller = None;
+            match result {
+                Ok(data) => {
+                    inner.state.data = Some(data);
+                    inner.state.error = None;
+                    inner.state.updated_at = Some(js_sys::Date::now());
+                    inner.set_status(QueryStatus::Data);
+                }
+                Err(error) => {
+                    inner.state.error = Some(error_value(&error));
+                    inner.set_status(QueryStatus::Error);
+                }
+            }
+        }
+        self.notify();
+        self.inner.borrow().snapshot.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl QueryHandle {
+    /// `{ status, data, error, updatedAt }`, the same object until the
+    /// state changes.
+    #[wasm_bindgen(unchecked_return_type = "QueryState")]
+    pub fn state(&self) -> JsValue {
+        self.inner.borrow().snapshot.clone()
+    }
+
+    /// Calls `listener(state)` now and after every change. Returns the
+    /// function that stops it.
+    #[wasm_bindgen(unchecked_return_type = "() => void")]
+    pub fn subscribe(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "(state: QueryState) => void")]
+        listener: js_sys::Function,
+    ) -> js_sys::Function {
+        let (id, snapshot) = {
+            let mut inner = self.inner.borrow_mut();
+            let id = inner.next_listener;
+            inner.next_listener += 1;
+            inner.listeners.push((id, listener.clone()));
+            (id, inner.snapshot.clone())
+        };
+        let _ = listener.call1(&JsValue::NULL, &snapshot);
+        let inner = Rc::clone(&self.inner);
+        Closure::once_into_js(move || {
+            inner
+                .borrow_mut()
+                .listeners
+                .retain(|(listener, _)| *listener != id);
+        })
+        .unchecked_into()
+    }
+
+    /// Sends the query again, bypassing the cache, and resolves to the
+    /// state once it settles. Supersedes a request already in flight. The
+    /// state is `loading` or `refreshing` as soon as this returns, so a
+    /// `cancel()` right after it takes effect.
+    #[wasm_bindgen(unchecked_return_type = "Promise<QueryState>")]
+    pub fn refetch(&self) -> js_sys::Promise {
+        let settled = self.start(true);
+        future_to_promise(async move { Ok(settled.await) })
+    }
+
+    /// Aborts the request in flight, if any, and goes back to the status
+    /// before it (`idle` if nothing had loaded).
+    #[wasm_bindgen]
+    pub fn cancel(&self) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            let Some(controller) = inner.controller.take() else {
+                return;
+            };
+            controller.abort();
+            inner.request += 1;
+            let status = inner.before;
+            inner.set_status(status);
+        }
+        self.notify();
+    }
+
+    /// Cancels the request in flight and removes every listener, for a
+    /// component being unmounted.
+    #[wasm_bindgen]
+    pub fn destroy(&self) {
+        self.cancel();
+        self.inner.borrow_mut().listeners.clear();
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// A handle on `query`'s state for UI frameworks: `state()`,
+    /// `subscribe(listener)`, `refetch()`, `cancel()` and `destroy()`.
+    /// Loads at once, from the cache when it can, unless `options` is
+    /// `{ lazy: true }`.
+    #[wasm_bindgen]
+    pub fn query_handle(
+        &self,
+        query: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "Variables")] variables: Option<JsValue>,
+        #[wasm_bindgen(unchecked_optional_param_type = "QueryHandleOptions")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<QueryHandle, JsValue> {
+        let variables: Value = match variables {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => Value::Null,
+        };
+        let options: QueryHandleOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => QueryHandleOptions::default(),
+        };
+        let state = QueryState {
+            status: QueryStatus::Idle,
+            data: None,
+            error: None,
+            updated_at: None,
+        };
+        let handle = QueryHandle {
+            client: self.clone(),
+            query,
+            variables,
+            inner: Rc::new(RefCell::new(HandleState {
+                snapshot: to_js(&state)?,
+                state,
+                listeners: Vec::new(),
+                next_listener: 0,
+                request: 0,
+                controller: None,
+                before: QueryStatus::Idle,
+            })),
+        };
+        if !options.lazy {
+            let loading = handle.start(false);
+            spawn_local(async move {
+                loading.await;
+            });
+        }
+        Ok(handle)
+    }
+}
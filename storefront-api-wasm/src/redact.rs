@@ -0,0 +1,126 @@
+//! Keeping secrets and personal data out of everything the crate writes
+//! down: log events, error reports, cassettes, the MCP audit log and
+//! `Debug` output.
+//!
+//! Three things are redacted, wherever they appear:
+//!
+//! - values under secret-looking keys, such as `accessToken`,
+//!   `customerAccessToken` or `password` (objects under them keep their
+//!   shape, with their own values checked the same way);
+//! - the known secrets passed in, typically the client's access token, and
+//!   Shopify-issued tokens recognized by their prefix (`shpat_` etc.);
+//! - email addresses.
+
+use std::fmt;
+
+use serde_json::Value;
+
+/// What redacted values are replaced with.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Key names whose values are secret, matched case-insensitively anywhere
+/// in the name, ignoring `_` and `-`.
+const SECRET_KEYS: &[&str] = &["token", "password", "secret", "authorization", "apikey"];
+
+/// Prefixes of Shopify access tokens.
+const TOKEN_PREFIXES: &[&str] = &["shpat_", "shpca_", "shppa_", "shpss_"];
+
+/// Whether values under `key` are secret.
+pub fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase().replace(['_', '-'], "");
+    SECRET_KEYS.iter().any(|secret| key.contains(secret))
+}
+
+/// `text` with `secrets`, prefixed tokens and email addresses replaced.
+pub fn redact_text(text: &str, secrets: &[&str]) -> String {
+    let mut text = text.to_string();
+    for secret in secrets.iter().filter(|secret| !secret.is_empty()) {
+        if text.contains(secret) {
+            text = text.replace(secret, REDACTED);
+        }
+    }
+    replace_words(&text)
+}
+
+/// Replaces the email addresses and prefixed tokens among the words of
+/// `text`.
+fn replace_words(text: &str) -> String {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || "._%+-@".contains(c);
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(is_word) {
+        redacted.push_str(&rest[..start]);
+        let word = &rest[start..];
+        let end = word.find(|c: char| !is_word(c)).unwrap_or(word.len());
+        let (word, after) = word.split_at(end);
+        // A word ending a sentence keeps its full stop.
+        let trimmed = word.trim_end_matches('.');
+        if is_email(trimmed) || TOKEN_PREFIXES.iter().any(|prefix| word.starts_with(prefix)) {
+            redacted.push_str(REDACTED);
+            redacted.push_str(&word[trimmed.len()..]);
+        } else {
+            redacted.push_str(word);
+        }
+        rest = after;
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+fn is_email(word: &str) -> bool {
+    let Some((local, domain)) = word.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.split('.').filter(|label| !label.is_empty()).count() >= 2
+}
+
+/// Redacts `value` in place: values under secret keys, and `secrets`,
+/// prefixed tokens and email addresses in every string.
+pub fn redact(value: &mut Value, secrets: &[&str]) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::Object(_) | Value::Array(_) | Value::Null => redact(value, secrets),
+                    _ if is_secret_key(key) => *value = Value::from(REDACTED),
+                    _ => redact(value, secrets),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact(item, secrets);
+            }
+        }
+        Value::String(text) => {
+            let redacted = redact_text(text, secrets);
+            if redacted != *text {
+                *text = redacted;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A redacted copy of `value`.
+pub fn redacted(value: &Value, secrets: &[&str]) -> Value {
+    let mut value = value.clone();
+    redact(&mut value, secrets);
+    value
+}
+
+/// Formats as `[REDACTED]` in `Debug` output, for secret fields of
+/// structs with hand-written `Debug` impls.
+pub struct Secret<'a>(pub &'a str);
+
+impl fmt::Debug for Secret<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            f.write_str("\"\"")
+        } else {
+            f.write_str(REDACTED)
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Error reports for monitoring services.
+//!
+//! `on_error(callback, options)` calls `callback(report)` once for every
+//! request that fails, after any retries, with a report ready to forward
+//! to Sentry, Datadog and the like:
+//!
+//! ```js
+//! api.on_error((report) => Sentry.captureMessage(report.message, { extra: report }));
+//! ```
+//!
+//! A report is `{ operation, variables, durationMs, attempts, kind,
+//! category, code, retryable, message, errors }`. Variables under
+//! `redactKeys` (customer details by default) are replaced, and the whole
+//! report is redacted like everything else the client writes down (see
+//! `redact`), so the access token can't leak through a message either.
+//! Cancelled requests aren't reported.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ErrorCategory, StorefrontError};
+use crate::redact::{redact, REDACTED};
+use crate::{from_js, to_js, StorefrontApi};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ErrorReportOptions {
+    /// Variable names whose values are replaced, matched
+    /// case-insensitively at any depth.
+    pub redact_keys: Vec<String>,
+    /// Whether reports carry the variables at all.
+    pub include_variables: bool,
+}
+
+impl Default for ErrorReportOptions {
+    fn default() -> Self {
+        ErrorReportOptions {
+            redact_keys: [
+                "email",
+                "phone",
+                "firstName",
+                "lastName",
+                "address1",
+                "address2",
+                "zip",
+            ]
+            .map(String::from)
+            .to_vec(),
+            include_variables: true,
+        }
+    }
+}
+
+/// The callback reports go to and how they're redacted.
+pub struct ErrorReporter {
+    callback: js_sys::Function,
+    options: ErrorReportOptions,
+}
+
+/// A failed request, as reported.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    pub operation: String,
+    pub variables: Value,
+    pub duration_ms: f64,
+    /// Requests sent, counting retries.
+    pub attempts: u32,
+    /// Classification of the error, `null` for failures that didn't come
+    /// from a request, such as a refused guard.
+    pub kind: Option<crate::error::ErrorKind>,
+    pub category: Option<ErrorCategory>,
+    pub code: Option<String>,
+    pub retryable: bool,
+    pub message: String,
+    pub errors: Vec<crate::error::ErrorDetail>,
+}
+
+impl ErrorReport {
+    pub fn new(
+        operation: &str,
+        variables: Value,
+        duration_ms: f64,
+        attempts: u32,
+        error: &JsValue,
+    ) -> ErrorReport {
+        let mut report = ErrorReport {
+            operation: operation.to_string(),
+            variables,
+            duration_ms,
+            attempts,
+            kind: None,
+            category: None,
+            code: None,
+            retryable: false,
+            message: error_message(error),
+            errors: Vec::new(),
+        };
+        if let Some(error) = StorefrontError::from_js(error) {
+            report.kind = Some(error.kind);
+            report.category = Some(error.category);
+            report.code = Some(error.code);
+            report.retryable = error.retryable;
+            report.message = error.message;
+            report.errors = error.errors;
+        }
+        report
+    }
+}
+
+/// The message of a thrown `Error` or string.
+pub(crate) fn error_message(error: &JsValue) -> String {
+    error
+        .dyn_ref::<js_sys::Error>()
+        .map(|error| String::from(error.message()))
+        .or_else(|| error.as_string())
+        .unwrap_or_else(|| "Unknown error".to_string())
+}
+
+/// Replaces the values of `keys` (lowercased) at any depth.
+fn redact_keys(value: &mut Value, keys: &[String]) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if keys.contains(&key.to_lowercase()) {
+                    *value = Value::from(REDACTED);
+                } else {
+                    redact_keys(value, keys);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_keys(item, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl ErrorReporter {
+    /// The report as the callback receives it: redacted, and without the
+    /// access token anywhere.
+    pub fn sanitize(&self, report: &ErrorReport, access_token: &str) -> Value {
+        let mut report = serde_json::to_value(report).unwrap_or(Value::Null);
+        if !self.options.include_variables {
+            report["variables"] = Value::Null;
+        }
+        let keys: Vec<String> = self
+            .options
+            .redact_keys
+            .iter()
+            .map(|key| key.to_lowercase())
+            .collect();
+        redact_keys(&mut report["variables"], &keys);
+        redact(&mut report, &[access_token]);
+        report
+    }
+}
+
+impl StorefrontApi {
+    /// Passes a failed request to the `on_error` callback, if any.
+    pub(crate) fn report_error(&self, report: ErrorReport) {
+        if report.category == Some(ErrorCategory::Cancelled) {
+            return;
+        }
+        let (callback, report) = {
+            let reporter = self.error_reporter.borrow();
+            let Some(reporter) = reporter.as_ref() else {
+                return;
+            };
+            (
+                reporter.callback.clone(),
+                reporter.sanitize(&report, &self.config.access_token),
+            )
+        };
+        // A throwing reporter mustn't hide the error being reported.
+        if let Ok(report) = to_js(&report) {
+            let _ = callback.call1(&JsValue::NULL, &report);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Calls `callback(report)` for every failed request; `null` removes
+    /// it. `options` is `{ redactKeys, includeVariables }`: variables named
+    /// in `redactKeys` (by default `email`, `phone`, `firstName`,
+    /// `lastName`, `address1`, `address2` and `zip`) are replaced with
+    /// `[REDACTED]`, and `includeVariables: false` leaves them out. The
+    /// access token never appears in a report. Copies of the client share
+    /// the callback.
+    #[wasm_bindgen]
+    pub fn on_error(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "(report: ErrorReport) => void")]
+        callback: Option<js_sys::Function>,
+        #[wasm_bindgen(unchecked_optional_param_type = "ErrorReportOptions")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        let options: ErrorReportOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => ErrorReportOptions::default(),
+        };
+        *self.error_reporter.borrow_mut() =
+            callback.map(|callback| ErrorReporter { callback, options });
+        Ok(())
+    }
+}
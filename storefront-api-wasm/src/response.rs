@@ -0,0 +1,325 @@
+//! Response bodies: a size limit, and parsing large ones without freezing
+//! the page.
+//!
+//! A body over `maxBytes` is refused: as soon as its `Content-Length` says
+//! so, or once read when the server doesn't send one. Bodies of at least
+//! `chunkedThreshold` bytes are parsed `chunkBytes` at a time, handing the
+//! event loop back between slices, so a 5 MB collection doesn't hold the
+//! main thread for the whole parse; smaller ones are parsed in one pass.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::error::StorefrontError;
+use crate::{from_js, to_js, StorefrontApi};
+
+/// How response bodies are limited and parsed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResponseLimits {
+    /// Largest body accepted, in bytes; `0` accepts any size.
+    pub max_bytes: u64,
+    /// Bodies at least this large are parsed in slices; `0` never slices.
+    pub chunked_threshold: u64,
+    /// Bytes parsed between turns of the event loop.
+    pub chunk_bytes: u64,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        ResponseLimits {
+            max_bytes: 0,
+            chunked_threshold: 1024 * 1024,
+            chunk_bytes: 256 * 1024,
+        }
+    }
+}
+
+impl ResponseLimits {
+    /// Fails when a body of `bytes` is over `max_bytes`.
+    pub fn check_size(&self, bytes: u64) -> Result<(), String> {
+        if self.max_bytes > 0 && bytes > self.max_bytes {
+            return Err(format!(
+                "Response of {bytes} bytes is over the {} byte limit",
+                self.max_bytes
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses a body, in slices when it's large.
+    pub async fn parse(&self, text: &str) -> Result<Value, JsValue> {
+        if self.chunked_threshold == 0 || (text.len() as u64) < self.chunked_threshold {
+            return serde_json::from_str(text).map_err(|e| {
+                StorefrontError::invalid_response(format!("Response is not JSON: {e}")).into()
+            });
+        }
+        let mut parser = ChunkedParser::new(text);
+        let chunk = self.chunk_bytes.max(1) as usize;
+        loop {
+            match parser.step(chunk) {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => yield_to_event_loop().await,
+                Err(error) => {
+                    return Err(StorefrontError::invalid_response(format!(
+                        "Response is not JSON: {error}"
+                    ))
+                    .into())
+                }
+            }
+        }
+    }
+}
+
+/// Waits for a `setTimeout(0)`, letting rendering and input run. Returns
+/// at once where there's no `setTimeout`.
+async fn yield_to_event_loop() {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let scheduled = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+            .and_then(|function| function.dyn_into::<js_sys::Function>())
+            .and_then(|set_timeout| set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from(0)));
+        if scheduled.is_err() {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+/// An array or object being filled in.
+enum Frame {
+    Array(Vec<Value>),
+    /// The object so far and the key of the value being parsed.
+    Object(Map<String, Value>, Option<String>),
+}
+
+/// What may come next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    /// Right after `[`.
+    ValueOrEnd,
+    /// Right after `{`.
+    KeyOrEnd,
+    /// After a `,` in an object.
+    Key,
+    Colon,
+    CommaOrEnd,
+    /// The top-level value is complete.
+    Done,
+}
+
+/// A JSON parser that can stop after any token and resume, so parsing a
+/// large document can be spread over several turns of the event loop. It
+/// keeps its place with an explicit stack, so depth costs no recursion.
+pub struct ChunkedParser<'a> {
+    text: &'a str,
+    position: usize,
+    stack: Vec<Frame>,
+    expect: Expect,
+    result: Option<Value>,
+}
+
+impl<'a> ChunkedParser<'a> {
+    pub fn new(text: &'a str) -> ChunkedParser<'a> {
+        ChunkedParser {
+            text,
+            position: 0,
+            stack: Vec::new(),
+            expect: Expect::Value,
+            result: None,
+        }
+    }
+
+    /// Parses about `budget` more bytes (a single token may run past it).
+    /// Returns the document once it's complete.
+    pub fn step(&mut self, budget: usize) -> Result<Option<Value>, String> {
+        let stop = self.position.saturating_add(budget);
+        let bytes = self.text.as_bytes();
+        loop {
+            while bytes
+                .get(self.position)
+                .is_some_and(|byte| byte.is_ascii_whitespace())
+            {
+                self.position += 1;
+            }
+            let Some(&byte) = bytes.get(self.position) else {
+                return match self.expect {
+                    Expect::Done => Ok(self.result.take()),
+                    _ => Err(self.error("unexpected end of input")),
+                };
+            };
+            if self.position >= stop {
+                return Ok(None);
+            }
+            match (self.expect, byte) {
+                (Expect::Done, _) => return Err(self.error("trailing characters")),
+                (Expect::ValueOrEnd, b']') | (Expect::KeyOrEnd, b'}') => {
+                    self.position += 1;
+                    self.close()?;
+                }
+                (Expect::CommaOrEnd, b']' | b'}') => {
+                    let closes = match self.stack.last() {
+                        Some(Frame::Array(_)) => b']',
+                        _ => b'}',
+                    };
+                    if byte != closes {
+                        return Err(self.error("mismatched bracket"));
+                    }
+                    self.position += 1;
+                    self.close()?;
+                }
+                (Expect::CommaOrEnd, b',') => {
+                    self.position += 1;
+                    self.expect = match self.stack.last() {
+                        Some(Frame::Array(_)) => Expect::Value,
+                        _ => Expect::Key,
+                    };
+                }
+                (Expect::KeyOrEnd | Expect::Key, b'"') => {
+                    let key = self.string()?;
+                    if let Some(Frame::Object(_, pending)) = self.stack.last_mut() {
+                        *pending = Some(key);
+                    }
+                    self.expect = Expect::Colon;
+                }
+                (Expect::Colon, b':') => {
+                    self.position += 1;
+                    self.expect = Expect::Value;
+                }
+                (Expect::Value | Expect::ValueOrEnd, _) => self.value(byte)?,
+                _ => return Err(self.error("unexpected character")),
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{message} at byte {}", self.position)
+    }
+
+    /// Starts or reads the value beginning with `byte`.
+    fn value(&mut self, byte: u8) -> Result<(), String> {
+        match byte {
+            b'[' => {
+                self.position += 1;
+                self.stack.push(Frame::Array(Vec::new()));
+                self.expect = Expect::ValueOrEnd;
+                Ok(())
+            }
+            b'{' => {
+                self.position += 1;
+                self.stack.push(Frame::Object(Map::new(), None));
+                self.expect = Expect::KeyOrEnd;
+                Ok(())
+            }
+            b'"' => {
+                let string = self.string()?;
+                self.complete(Value::String(string))
+            }
+            _ => {
+                let rest = &self.text.as_bytes()[self.position..];
+                let length = rest
+                    .iter()
+                    .position(|byte| !(byte.is_ascii_alphanumeric() || b"+-.".contains(byte)))
+                    .unwrap_or(rest.len());
+                let token = &self.text[self.position..self.position + length];
+                let value = match token {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ => match serde_json::from_str::<serde_json::Number>(token) {
+                        Ok(number) if length > 0 => Value::Number(number),
+                        _ => return Err(self.error("unexpected character")),
+                    },
+                };
+                self.position += length;
+                self.complete(value)
+            }
+        }
+    }
+
+    /// Reads the string starting at the current `"`.
+    fn string(&mut self) -> Result<String, String> {
+        let bytes = self.text.as_bytes();
+        let start = self.position;
+        let mut end = start + 1;
+        let mut escaped = false;
+        loop {
+            match bytes.get(end) {
+                None => return Err(self.error("unterminated string")),
+                Some(b'\\') => {
+                    escaped = true;
+                    end += 2;
+                }
+                Some(b'"') => break,
+                Some(_) => end += 1,
+            }
+        }
+        self.position = end + 1;
+        if escaped {
+            serde_json::from_str(&self.text[start..=end]).map_err(|e| self.error(&e.to_string()))
+        } else {
+            Ok(self.text[start + 1..end].to_string())
+        }
+    }
+
+    /// Ends the innermost array or object.
+    fn close(&mut self) -> Result<(), String> {
+        let value = match self.stack.pop() {
+            Some(Frame::Array(items)) => Value::Array(items),
+            Some(Frame::Object(object, _)) => Value::Object(object),
+            None => return Err(self.error("unexpected bracket")),
+        };
+        self.complete(value)
+    }
+
+    /// Adds a finished value to its parent.
+    fn complete(&mut self, value: Value) -> Result<(), String> {
+        match self.stack.last_mut() {
+            None => {
+                self.result = Some(value);
+                self.expect = Expect::Done;
+            }
+            Some(Frame::Array(items)) => {
+                items.push(value);
+                self.expect = Expect::CommaOrEnd;
+            }
+            Some(Frame::Object(object, key)) => {
+                let key = key.take().ok_or_else(|| "missing key".to_string())?;
+                object.insert(key, value);
+                self.expect = Expect::CommaOrEnd;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Sets how response bodies are limited and parsed. `options` is
+    /// `{ maxBytes, chunkedThreshold, chunkBytes }`: bodies over `maxBytes`
+    /// are refused (default `0`, no limit), and bodies of at least
+    /// `chunkedThreshold` bytes (default 1 MB) are parsed `chunkBytes`
+    /// (default 256 KB) at a time between turns of the event loop. Copies
+    /// of the client share them.
+    #[wasm_bindgen]
+    pub fn set_response_limits(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "Partial<ResponseLimits>")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        *self.response_limits.borrow_mut() = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => ResponseLimits::default(),
+        };
+        Ok(())
+    }
+
+    /// The current response limits.
+    #[wasm_bindgen(unchecked_return_type = "ResponseLimits")]
+    pub fn response_limits(&self) -> Result<JsValue, JsValue> {
+        to_js(&*self.response_limits.borrow())
+    }
+}
@@ -0,0 +1,263 @@
+//! Back-in-stock notifications for "notify me" buttons.
+//!
+//! `watch_variant_availability(variantId, intervalMs, callback)` polls a
+//! sold-out variant and calls `callback({ variantId, availableForSale,
+//! quantityAvailable })` each time it comes back in stock. All the variants
+//! a client (and its copies) watches are polled together, in one `nodes`
+//! query per tick at the shortest interval asked for, so a page with many
+//! notify-me buttons sends one request rather than one per button.
+//!
+//! A variant is reported when a poll finds it available after one found it
+//! unavailable; one already available when watching starts isn't.
+//! `quantityAvailable` needs the `unauthenticated_read_product_inventory`
+//! scope: when the token lacks it, polls go on without it and report
+//! `null`. A failed poll is skipped and tried again on the next tick.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::retry::sleep;
+use crate::watch::MIN_WATCH_INTERVAL_MS;
+use crate::{from_json, to_js, StorefrontApi};
+
+/// Most IDs the `nodes` query takes at once.
+const NODES_BATCH_SIZE: usize = 250;
+
+/// A variant's stock, as reported to callbacks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantAvailability {
+    #[serde(alias = "id")]
+    pub variant_id: String,
+    pub available_for_sale: bool,
+    #[serde(default)]
+    pub quantity_available: Option<i64>,
+}
+
+struct AvailabilityWatch {
+    id: u32,
+    variant_id: String,
+    interval_ms: u32,
+    callback: js_sys::Function,
+}
+
+/// The variants watched through a client and its copies, polled by one
+/// loop that runs while there are any.
+#[derive(Default)]
+pub(crate) struct AvailabilityWatches {
+    watches: Vec<AvailabilityWatch>,
+    /// The last poll's result by variant ID.
+    last: HashMap<String, VariantAvailability>,
+    next_id: u32,
+    polling: bool,
+    /// Set once a poll failed with `quantityAvailable` and succeeded
+    /// without it, i.e. the token can't read inventory.
+    without_quantity: bool,
+}
+
+/// A running `watch_variant_availability`; `stop()` ends it.
+#[wasm_bindgen]
+pub struct VariantWatcher {
+    id: u32,
+    watches: Rc<RefCell<AvailabilityWatches>>,
+}
+
+#[wasm_bindgen]
+impl VariantWatcher {
+    /// Stops watching. Polling ends with the last watcher stopped.
+    #[wasm_bindgen]
+    pub fn stop(&self) {
+        self.watches
+            .borrow_mut()
+            .watches
+            .retain(|watch| watch.id != self.id);
+    }
+
+    /// Whether the watcher is still watching.
+    #[wasm_bindgen]
+    pub fn is_active(&self) -> bool {
+        self.watches
+            .borrow()
+            .watches
+            .iter()
+            .any(|watch| watch.id == self.id)
+    }
+}
+
+fn availability_query(quantity: bool) -> String {
+    format!(
+        r#"
+        query variantAvailability($ids: [ID!]!) {{
+            nodes(ids: $ids) {{
+                ... on ProductVariant {{
+                    id
+                    availableForSale{}
+                }}
+            }}
+        }}
+        "#,
+        if quantity {
+            "\n                    quantityAvailable"
+        } else {
+            ""
+        }
+    )
+}
+
+impl StorefrontApi {
+    /// The stock of `ids`, without `quantityAvailable` if `quantity` is
+    /// false. Deleted variants are left out.
+    async fn fetch_availability(
+        &self,
+        ids: &[String],
+        quantity: bool,
+    ) -> Result<Vec<VariantAvailability>, JsValue> {
+        let query = self.context.borrow().apply(&availability_query(quantity));
+        let mut availability = Vec::with_capacity(ids.len());
+        for batch in ids.chunks(NODES_BATCH_SIZE) {
+            let variables = serde_json::json!({ "ids": batch });
+            let mut data = self.send_operation(query.clone(), variables).await?;
+            let nodes: Vec<Value> = from_json(data["nodes"].take())?;
+            for node in nodes.into_iter().filter(|node| !node["id"].is_null()) {
+                availability.push(from_json(node)?);
+            }
+        }
+        Ok(availability)
+    }
+
+    /// Polls the watched variants once and calls back for those back in
+    /// stock.
+    async fn poll_availability(&self, watches: &RefCell<AvailabilityWatches>) {
+        let (mut ids, without_quantity) = {
+            let watches = watches.borrow();
+            let ids: Vec<String> = watches
+                .watches
+                .iter()
+                .map(|watch| watch.variant_id.clone())
+                .collect();
+            (ids, watches.without_quantity)
+        };
+        ids.sort();
+        ids.dedup();
+        if ids.is_empty() {
+            return;
+        }
+
+        let polled = match self.fetch_availability(&ids, !without_quantity).await {
+            Ok(polled) => polled,
+            Err(_) if !without_quantity => {
+                let Ok(polled) = self.fetch_availability(&ids, false).await else {
+                    return;
+                };
+                watches.borrow_mut().without_quantity = true;
+                polled
+            }
+            Err(_) => return,
+        };
+
+        let mut restocked = Vec::new();
+        {
+            let mut watches = watches.borrow_mut();
+            for availability in polled {
+                let previous = watches
+                    .last
+                    .insert(availability.variant_id.clone(), availability.clone());
+                let was_available = previous.map(|previous| previous.available_for_sale);
+                if was_available == Some(false) && availability.available_for_sale {
+                    for watch in &watches.watches {
+                        if watch.variant_id == availability.variant_id {
+                            restocked.push((watch.callback.clone(), availability.clone()));
+                        }
+                    }
+                }
+            }
+            let watched: Vec<String> = watches
+                .watches
+                .iter()
+                .map(|watch| watch.variant_id.clone())
+                .collect();
+            watches.last.retain(|id, _| watched.contains(id));
+        }
+        // Call back after releasing the borrow, as a callback may stop its
+        // watcher or start another.
+        for (callback, availability) in restocked {
+            if let Ok(availability) = to_js(&availability) {
+                let _ = callback.call1(&JsValue::NULL, &availability);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Polls `variant_id` every `interval_ms` (at least 100), together with
+    /// the client's other watched variants, and calls
+    /// `callback({ variantId, availableForSale, quantityAvailable })` each
+    /// time it comes back in stock. Returns a watcher whose `stop()` ends
+    /// it.
+    #[wasm_bindgen]
+    pub fn watch_variant_availability(
+        &self,
+        variant_id: String,
+        interval_ms: u32,
+        #[wasm_bindgen(unchecked_param_type = "(availability: VariantAvailability) => void")]
+        callback: js_sys::Function,
+    ) -> Result<VariantWatcher, JsValue> {
+        let variant_id = variant_id.trim().to_string();
+        if !variant_id.starts_with("gid://shopify/ProductVariant/") {
+            return Err(JsValue::from_str(&format!(
+                "Invalid variant ID: {variant_id}. Expected gid://shopify/ProductVariant/..."
+            )));
+        }
+        let (id, start) = {
+            let mut watches = self.availability_watches.borrow_mut();
+            watches.next_id += 1;
+            let id = watches.next_id;
+            watches.watches.push(AvailabilityWatch {
+                id,
+                variant_id,
+                interval_ms: interval_ms.max(MIN_WATCH_INTERVAL_MS),
+                callback,
+            });
+            let start = !watches.polling;
+            watches.polling = true;
+            (id, start)
+        };
+
+        if start {
+            let client = self.clone();
+            let watches = Rc::clone(&self.availability_watches);
+            spawn_local(async move {
+                loop {
+                    let interval = watches
+                        .borrow()
+                        .watches
+                        .iter()
+                        .map(|watch| watch.interval_ms)
+                        .min();
+                    let Some(interval) = interval else {
+                        break;
+                    };
+                    client.poll_availability(&watches).await;
+                    if sleep(interval).await.is_err() {
+                        watches.borrow_mut().watches.clear();
+                        break;
+                    }
+                }
+                let mut watches = watches.borrow_mut();
+                watches.polling = false;
+                watches.last.clear();
+            });
+        }
+        Ok(VariantWatcher {
+            id,
+            watches: Rc::clone(&self.availability_watches),
+        })
+    }
+}
@@ -0,0 +1,84 @@
+//! Retrying failed requests.
+//!
+//! With `set_retry_policy({ maxRetries })`, a request that fails with an
+//! error marked `retryable` (see `error`) is sent again after an
+//! exponential backoff, up to `maxRetries` times; any other failure, and a
+//! request cancelled through its signal, is thrown at once. Retries are off
+//! by default.
+//!
+//! Mutations are only retried when throttled: a mutation that failed any
+//! other way, e.g. with a server error or a dropped connection, may still
+//! have been applied, and sending it again could add a cart line twice.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{from_js, to_js, StorefrontApi};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` never retries.
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each one after it.
+    pub base_delay_ms: u32,
+    /// Longest wait between attempts.
+    pub max_delay_ms: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            base_delay_ms: 500,
+            max_delay_ms: 10_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before retry number `retry` (from 0).
+    pub fn delay_ms(&self, retry: u32) -> u32 {
+        self.base_delay_ms
+            .saturating_mul(1 << retry.min(16))
+            .min(self.max_delay_ms)
+    }
+}
+
+/// Resolves after `ms` milliseconds.
+pub(crate) async fn sleep(ms: u32) -> Result<(), JsValue> {
+    let set_timeout: js_sys::Function =
+        js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))?.dyn_into()?;
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let _ = set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from(ms));
+    });
+    JsFuture::from(promise).await.map(|_| ())
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Sets how retryable failures are retried. `options` is
+    /// `{ maxRetries, baseDelayMs, maxDelayMs }`; omitted fields take their
+    /// defaults: no retries, waiting 500 ms before the first and doubling
+    /// up to 10 s. Copies of the client share it.
+    #[wasm_bindgen]
+    pub fn set_retry_policy(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "Partial<RetryPolicy>")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        *self.retry.borrow_mut() = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => RetryPolicy::default(),
+        };
+        Ok(())
+    }
+
+    /// The current retry policy.
+    #[wasm_bindgen(unchecked_return_type = "RetryPolicy")]
+    pub fn retry_policy(&self) -> Result<JsValue, JsValue> {
+        to_js(&*self.retry.borrow())
+    }
+}
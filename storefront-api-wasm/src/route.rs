@@ -0,0 +1,237 @@
+//! Path resolution for catch-all routes.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{from_json, to_js, StorefrontApi};
+
+/// What a storefront path should render, as decided by `resolve_route`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Route {
+    /// A URL redirect configured in the admin. `location` is a path or an
+    /// absolute URL.
+    #[serde(rename_all = "camelCase")]
+    Redirect {
+        location: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Product {
+        id: String,
+        handle: String,
+        /// Set for `/collections/{collection}/products/{product}` paths.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        collection_handle: Option<String>,
+    },
+    Collection {
+        id: String,
+        handle: String,
+    },
+    Page {
+        id: String,
+        handle: String,
+    },
+    Blog {
+        id: String,
+        handle: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Article {
+        id: String,
+        handle: String,
+        blog_handle: String,
+    },
+    NotFound,
+}
+
+/// The resource a path points at by Shopify's URL conventions.
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Product {
+        handle: String,
+        collection_handle: Option<String>,
+    },
+    Collection(String),
+    Page(String),
+    Blog(String),
+    Article {
+        blog_handle: String,
+        handle: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct UrlRedirect {
+    path: String,
+    target: String,
+}
+
+#[derive(Deserialize)]
+struct Resource {
+    id: String,
+    handle: String,
+}
+
+/// Strips the query string, fragment and trailing slash, and makes sure the
+/// path starts with `/`.
+fn normalize_path(path: &str) -> String {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let path = path.trim().trim_end_matches('/');
+    if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    }
+}
+
+/// Maps `/products/x`, `/collections/x`, `/collections/x/products/y`,
+/// `/pages/x`, `/blogs/x` and `/blogs/x/y` to their resource.
+fn parse_target(path: &str) -> Option<Target> {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let target = match segments.as_slice() {
+        ["products", handle] => Target::Product {
+            handle: handle.to_string(),
+            collection_handle: None,
+        },
+        ["collections", collection, "products", handle] => Target::Product {
+            handle: handle.to_string(),
+            collection_handle: Some(collection.to_string()),
+        },
+        ["collections", handle] => Target::Collection(handle.to_string()),
+        ["pages", handle] => Target::Page(handle.to_string()),
+        ["blogs", handle] => Target::Blog(handle.to_string()),
+        ["blogs", blog_handle, handle] => Target::Article {
+            blog_handle: blog_handle.to_string(),
+            handle: handle.to_string(),
+        },
+        _ => return None,
+    };
+    Some(target)
+}
+
+impl Target {
+    /// The root field selecting `resource { id handle }` for this target.
+    fn selection(&self) -> &'static str {
+        match self {
+            Target::Product { .. } => "resource: product(handle: $handle) { id handle }",
+            Target::Collection(_) => "resource: collection(handle: $handle) { id handle }",
+            Target::Page(_) => "resource: page(handle: $handle) { id handle }",
+            Target::Blog(_) => "resource: blog(handle: $handle) { id handle }",
+            Target::Article { .. } => {
+                "blog(handle: $blog) { resource: articleByHandle(handle: $handle) { id handle } }"
+            }
+        }
+    }
+
+    fn variables(&self) -> serde_json::Value {
+        match self {
+            Target::Product { handle, .. }
+            | Target::Collection(handle)
+            | Target::Page(handle)
+            | Target::Blog(handle) => serde_json::json!({ "handle": handle }),
+            Target::Article {
+                blog_handle,
+                handle,
+            } => serde_json::json!({ "blog": blog_handle, "handle": handle }),
+        }
+    }
+
+    fn into_route(self, resource: Resource) -> Route {
+        let Resource { id, handle } = resource;
+        match self {
+            Target::Product {
+                collection_handle, ..
+            } => Route::Product {
+                id,
+                handle,
+                collection_handle,
+            },
+            Target::Collection(_) => Route::Collection { id, handle },
+            Target::Page(_) => Route::Page { id, handle },
+            Target::Blog(_) => Route::Blog { id, handle },
+            Target::Article { blog_handle, .. } => Route::Article {
+                id,
+                handle,
+                blog_handle,
+            },
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Decides what a storefront path should render, for catch-all routes.
+    /// A matching URL redirect wins; otherwise the path is resolved by
+    /// convention to a product, collection, page, blog or article that
+    /// exists. Returns `{ kind: "redirect", location }`, `{ kind: "product",
+    /// id, handle, collectionHandle? }`, ..., or `{ kind: "notFound" }`.
+    #[wasm_bindgen(unchecked_return_type = "Route")]
+    pub async fn resolve_route(&self, path: String) -> Result<JsValue, JsValue> {
+        to_js(&self.fetch_route(&path).await?)
+    }
+}
+
+impl StorefrontApi {
+    pub(crate) async fn fetch_route(&self, path: &str) -> Result<Route, JsValue> {
+        let path = normalize_path(path);
+        let target = parse_target(&path);
+
+        let variable_definitions = match &target {
+            Some(Target::Article { .. }) => ", $blog: String!, $handle: String!",
+            Some(_) => ", $handle: String!",
+            None => "",
+        };
+        let selection = target.as_ref().map(Target::selection);
+        let query = format!(
+            r#"
+            query resolveRoute($redirect: String!{variable_definitions}) {{
+                urlRedirects(first: 5, query: $redirect) {{
+                    edges {{
+                        node {{
+                            path
+                            target
+                        }}
+                    }}
+                }}
+                {}
+            }}
+        "#,
+            selection.unwrap_or_default()
+        );
+
+        let mut variables = target
+            .as_ref()
+            .map(Target::variables)
+            .unwrap_or_else(|| serde_json::json!({}));
+        variables["redirect"] = serde_json::json!(format!("path:'{}'", path.replace('\'', "\\'")));
+
+        let mut data = self.execute(&query, variables).await?;
+
+        // The search is fuzzy, so only an exact (case-insensitive) path match
+        // counts as a redirect.
+        let redirects: Vec<UrlRedirect> = from_json(
+            data["urlRedirects"]["edges"]
+                .as_array_mut()
+                .map(|edges| edges.iter_mut().map(|edge| edge["node"].take()).collect())
+                .unwrap_or_default(),
+        )?;
+        if let Some(redirect) = redirects
+            .into_iter()
+            .find(|redirect| normalize_path(&redirect.path).eq_ignore_ascii_case(&path))
+        {
+            return Ok(Route::Redirect {
+                location: redirect.target,
+            });
+        }
+
+        let Some(target) = target else {
+            return Ok(Route::NotFound);
+        };
+        let resource = match target {
+            Target::Article { .. } => data["blog"]["resource"].take(),
+            _ => data["resource"].take(),
+        };
+        let resource: Option<Resource> = from_json(resource)?;
+        Ok(resource.map_or(Route::NotFound, |resource| target.into_route(resource)))
+    }
+}
@@ -0,0 +1,577 @@
+//! Product search and predictive (autocomplete) search.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::pagination::{
+    ListOptions, PageArgs, CONNECTION_ARGUMENTS, CONNECTION_VARIABLES, PAGE_INFO_FIELDS,
+};
+use crate::product::PRODUCT_CARD_FRAGMENT;
+use crate::types::{
+    Article, Collection, Connection, Filter, Page, PageInfo, Product, ProductFilter,
+    SearchResultItem,
+};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Accepted `PredictiveSearchType` values.
+const PREDICTIVE_SEARCH_TYPES: &[&str] = &["ARTICLE", "COLLECTION", "PAGE", "PRODUCT", "QUERY"];
+
+/// Selection for `Filter` facets on product connections and search results.
+pub(crate) const FILTER_FIELDS: &str = "id label type values { id label count input }";
+
+/// Accepted `ProductSortKeys` values.
+const PRODUCT_SORT_KEYS: &[&str] = &[
+    "BEST_SELLING",
+    "CREATED_AT",
+    "ID",
+    "PRICE",
+    "PRODUCT_TYPE",
+    "RELEVANCE",
+    "TITLE",
+    "UPDATED_AT",
+    "VENDOR",
+];
+
+/// Accepted `SearchType` values.
+const SEARCH_TYPES: &[&str] = &["ARTICLE", "PAGE", "PRODUCT"];
+
+/// Options for `search`, passed from JavaScript as a plain object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    pub first: Option<u32>,
+    pub after: Option<String>,
+    /// Page size when paging backward with `before`; replaces `first`.
+    pub last: Option<u32>,
+    pub before: Option<String>,
+    /// Filters applied to product hits.
+    pub product_filters: Vec<ProductFilter>,
+    /// `LAST` treats the final term as a prefix; `NONE` matches whole words.
+    pub prefix: Option<String>,
+    /// `HIDE`, `LAST` or `SHOW` for sold-out products.
+    pub unavailable_products: Option<String>,
+    /// Restricts hits to these `SearchType` values (`PRODUCT`, `PAGE`, `ARTICLE`).
+    pub types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub items: Vec<SearchResultItem>,
+    pub total_count: u32,
+    pub page_info: PageInfo,
+    /// Facets available for the product hits.
+    pub product_filters: Vec<Filter>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSearchConnection {
+    #[serde(flatten)]
+    connection: Connection<SearchResultItem>,
+    total_count: u32,
+    #[serde(default)]
+    product_filters: Vec<Filter>,
+}
+
+/// Quotes a term for Shopify search syntax, escaping backslashes and quotes.
+pub(crate) fn quote_search_term(term: &str) -> String {
+    format!("\"{}\"", term.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Composes Shopify product search syntax with correct quoting, e.g.
+///
+/// ```
+/// use storefront_api_wasm::search::ProductQuery;
+///
+/// let query = ProductQuery::new()
+///     .tag("summer")
+///     .vendor("Acme Co")
+///     .price_between(Some(10.0), Some(50.0))
+///     .available_for_sale(true)
+///     .build();
+/// assert_eq!(
+///     query,
+///     r#"tag:"summer" AND vendor:"Acme Co" AND variants.price:>=10 AND variants.price:<=50 AND available_for_sale:true"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProductQuery {
+    /// Free text, passed through unquoted so search operators keep working.
+    pub text: Option<String>,
+    pub tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    pub vendor: Option<String>,
+    pub product_type: Option<String>,
+    pub title: Option<String>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    /// ISO 8601 date or date-time.
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub updated_after: Option<String>,
+    pub available_for_sale: Option<bool>,
+}
+
+impl ProductQuery {
+    pub fn new() -> Self {
+        ProductQuery::default()
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn exclude_tag(mut self, tag: impl Into<String>) -> Self {
+        self.exclude_tags.push(tag.into());
+        self
+    }
+
+    pub fn vendor(mut self, vendor: impl Into<String>) -> Self {
+        self.vendor = Some(vendor.into());
+        self
+    }
+
+    pub fn product_type(mut self, product_type: impl Into<String>) -> Self {
+        self.product_type = Some(product_type.into());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn price_between(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min_price = min;
+        self.max_price = max;
+        self
+    }
+
+    pub fn created_after(mut self, date: impl Into<String>) -> Self {
+        self.created_after = Some(date.into());
+        self
+    }
+
+    pub fn created_before(mut self, date: impl Into<String>) -> Self {
+        self.created_before = Some(date.into());
+        self
+    }
+
+    pub fn updated_after(mut self, date: impl Into<String>) -> Self {
+        self.updated_after = Some(date.into());
+        self
+    }
+
+    pub fn available_for_sale(mut self, available: bool) -> Self {
+        self.available_for_sale = Some(available);
+        self
+    }
+
+    /// Renders the query string; terms are joined with `AND`.
+    pub fn build(&self) -> String {
+        let mut terms = Vec::new();
+        if let Some(text) = self
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        {
+            terms.push(text.to_string());
+        }
+        for tag in &self.tags {
+            terms.push(format!("tag:{}", quote_search_term(tag)));
+        }
+        for tag in &self.exclude_tags {
+            terms.push(format!("tag_not:{}", quote_search_term(tag)));
+        }
+        if let Some(vendor) = &self.vendor {
+            terms.push(format!("vendor:{}", quote_search_term(vendor)));
+        }
+        if let Some(product_type) = &self.product_type {
+            terms.push(format!("product_type:{}", quote_search_term(product_type)));
+        }
+        if let Some(title) = &self.title {
+            terms.push(format!("title:{}", quote_search_term(title)));
+        }
+        if let Some(min) = self.min_price {
+            terms.push(format!("variants.price:>={min}"));
+        }
+        if let Some(max) = self.max_price {
+            terms.push(format!("variants.price:<={max}"));
+        }
+        if let Some(date) = &self.created_after {
+            terms.push(format!("created_at:>{}", quote_search_term(date)));
+        }
+        if let Some(date) = &self.created_before {
+            terms.push(format!("created_at:<{}", quote_search_term(date)));
+        }
+        if let Some(date) = &self.updated_after {
+            terms.push(format!("updated_at:>{}", quote_search_term(date)));
+        }
+        if let Some(available) = self.available_for_sale {
+            terms.push(format!("available_for_sale:{available}"));
+        }
+        terms.join(" AND ")
+    }
+}
+
+impl std::fmt::Display for ProductQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.build())
+    }
+}
+
+/// Renders a `ProductQuery` object to Shopify search syntax.
+#[wasm_bindgen]
+pub fn build_product_query(
+    #[wasm_bindgen(unchecked_param_type = "ProductQuery")] query: JsValue,
+) -> Result<String, JsValue> {
+    Ok(from_js::<ProductQuery>(query)?.build())
+}
+
+/// Upper-cases enum-like option values and checks them against `allowed`.
+pub(crate) fn normalize_enum_values(
+    kind: &str,
+    values: &[String],
+    allowed: &[&str],
+) -> Result<Vec<String>, JsValue> {
+    values
+        .iter()
+        .map(|value| {
+            let value = value.to_uppercase();
+            if allowed.contains(&value.as_str()) {
+                Ok(value)
+            } else {
+                Err(JsValue::from_str(&format!(
+                    "Unknown {kind}: {value} (expected one of {})",
+                    allowed.join(", ")
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Options for `predictive_search`, passed from JavaScript as a plain object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PredictiveSearchOptions {
+    /// Results per type (the API allows 1-10).
+    pub limit: Option<u32>,
+    /// `ALL` (limit applies to the total) or `EACH` (limit per type).
+    pub limit_scope: Option<String>,
+    /// Restricts results to these `PredictiveSearchType` values.
+    pub types: Vec<String>,
+    /// `HIDE`, `LAST` or `SHOW` for sold-out products.
+    pub unavailable_products: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuerySuggestion {
+    pub text: String,
+    /// Suggestion text with the matched part wrapped in `<mark>`.
+    pub styled_text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PredictiveSearchResult {
+    pub products: Vec<Product>,
+    pub collections: Vec<Collection>,
+    pub pages: Vec<Page>,
+    pub articles: Vec<Article>,
+    pub queries: Vec<SearchQuerySuggestion>,
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Searches products with Shopify search syntax. `query` is either a raw
+    /// query string or a `ProductQuery` object (`{ text, tags, vendor,
+    /// productType, minPrice, maxPrice, createdAfter, availableForSale, ... }`).
+    /// `options` is `{ first, after, before, last, sortKey, reverse }`, with
+    /// `sortKey` a `ProductSortKeys` value such as `RELEVANCE`, `PRICE`,
+    /// `BEST_SELLING` or `CREATED_AT`. Page forward with `after` (the returned
+    /// `pageInfo.endCursor`) or backward with `before` and `last`.
+    #[wasm_bindgen(unchecked_return_type = "{ products: Connection<Product> }")]
+    pub async fn search_products(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "string | ProductQuery")] query: JsValue,
+        #[wasm_bindgen(unchecked_optional_param_type = "ListOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let query = match query.as_string() {
+            Some(raw) => raw,
+            None => from_js::<ProductQuery>(query)?.build(),
+        };
+        let options = ListOptions::from_js(options)?;
+        let sort_key = match options.sort_key.clone() {
+            Some(key) => {
+                normalize_enum_values("product sort key", &[key], PRODUCT_SORT_KEYS)?.pop()
+            }
+            None => None,
+        };
+        let page_args = options.page_args()?;
+        let products = self
+            .fetch_search_products(&query, sort_key, options.reverse, &page_args)
+            .await?;
+        to_js(&serde_json::json!({ "products": products }))
+    }
+
+    /// Autocomplete search returning typed products, collections, pages,
+    /// articles and query suggestions. See `PredictiveSearchOptions` for the
+    /// accepted options.
+    #[wasm_bindgen(unchecked_return_type = "PredictiveSearchResult")]
+    pub async fn predictive_search(
+        &self,
+        query: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "PredictiveSearchOptions")] options: Option<
+            JsValue,
+        >,
+    ) -> Result<JsValue, JsValue> {
+        let options: PredictiveSearchOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => PredictiveSearchOptions::default(),
+        };
+
+        to_js(&self.fetch_predictive_search(&query, &options).await?)
+    }
+
+    /// Full-text search over products, pages and articles using the `search`
+    /// root field, with product filters and a total hit count. Each item
+    /// carries `__typename`. See `SearchOptions` for the accepted options.
+    #[wasm_bindgen(unchecked_return_type = "SearchResult")]
+    pub async fn search(
+        &self,
+        query: String,
+        #[wasm_bindgen(unchecked_optional_param_type = "SearchOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options: SearchOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => SearchOptions::default(),
+        };
+        let types = normalize_enum_values("search type", &options.types, SEARCH_TYPES)?;
+        let page_args = PageArgs {
+            first: options.first,
+            after: options.after.clone(),
+            last: options.last,
+            before: options.before.clone(),
+        }
+        .resolve()?;
+
+        let graphql_query = format!(
+            r#"
+            query search(
+                $query: String!
+                $first: Int
+                $after: String
+                $last: Int
+                $before: String
+                $productFilters: [ProductFilter!]
+                $prefix: SearchPrefixQueryType
+                $unavailableProducts: SearchUnavailableProductsType
+                $types: [SearchType!]
+            ) {{
+                search(
+                    query: $query
+                    {CONNECTION_ARGUMENTS}
+                    productFilters: $productFilters
+                    prefix: $prefix
+                    unavailableProducts: $unavailableProducts
+                    types: $types
+                ) {{
+                    totalCount
+                    productFilters {{
+                        {FILTER_FIELDS}
+                    }}
+                    edges {{
+                        node {{
+                            __typename
+                            ... on Product {{
+                                ...ProductCardFields
+                            }}
+                            ... on Page {{
+                                id
+                                handle
+                                title
+                                bodySummary
+                                onlineStoreUrl
+                            }}
+                            ... on Article {{
+                                id
+                                handle
+                                title
+                                excerpt
+                                image {{
+                                    url
+                                    altText
+                                }}
+                                blog {{
+                                    handle
+                                }}
+                                onlineStoreUrl
+                            }}
+                        }}
+                    }}
+                    {PAGE_INFO_FIELDS}
+                }}
+            }}
+            {PRODUCT_CARD_FRAGMENT}
+        "#
+        );
+
+        let mut variables = serde_json::json!({
+            "query": query,
+            "productFilters": options.product_filters,
+            "prefix": options.prefix.map(|prefix| prefix.to_uppercase()),
+            "unavailableProducts": options.unavailable_products.map(|value| value.to_uppercase()),
+            "types": if types.is_empty() { None } else { Some(types) }
+        });
+        page_args.add_to(&mut variables);
+
+        let mut data = self.execute(&graphql_query, variables).await?;
+        let raw: RawSearchConnection = from_json(data["search"].take())?;
+        to_js(&SearchResult {
+            items: raw
+                .connection
+                .edges
+                .into_iter()
+                .map(|edge| edge.node)
+                .collect(),
+            total_count: raw.total_count,
+            page_info: raw.connection.page_info.unwrap_or_default(),
+            product_filters: raw.product_filters,
+        })
+    }
+}
+
+impl StorefrontApi {
+    /// Runs a `predictiveSearch` and decodes the typed results.
+    pub(crate) async fn fetch_predictive_search(
+        &self,
+        query: &str,
+        options: &PredictiveSearchOptions,
+    ) -> Result<PredictiveSearchResult, JsValue> {
+        let types = normalize_enum_values(
+            "predictive search type",
+            &options.types,
+            PREDICTIVE_SEARCH_TYPES,
+        )?;
+
+        let graphql_query = format!(
+            r#"
+            query predictiveSearch(
+                $query: String!
+                $limit: Int
+                $limitScope: PredictiveSearchLimitScope
+                $types: [PredictiveSearchType!]
+                $unavailableProducts: SearchUnavailableProductsType
+            ) {{
+                predictiveSearch(
+                    query: $query
+                    limit: $limit
+                    limitScope: $limitScope
+                    types: $types
+                    unavailableProducts: $unavailableProducts
+                ) {{
+                    products {{
+                        ...ProductCardFields
+                    }}
+                    collections {{
+                        id
+                        handle
+                        title
+                        image {{
+                            url
+                            altText
+                        }}
+                    }}
+                    pages {{
+                        id
+                        handle
+                        title
+                        onlineStoreUrl
+                    }}
+                    articles {{
+                        id
+                        handle
+                        title
+                        excerpt
+                        image {{
+                            url
+                            altText
+                        }}
+                        blog {{
+                            handle
+                        }}
+                        onlineStoreUrl
+                    }}
+                    queries {{
+                        text
+                        styledText
+                    }}
+                }}
+            }}
+            {PRODUCT_CARD_FRAGMENT}
+        "#
+        );
+
+        let variables = serde_json::json!({
+            "query": query,
+            "limit": options.limit,
+            "limitScope": options.limit_scope.as_ref().map(|scope| scope.to_uppercase()),
+            "types": if types.is_empty() { None } else { Some(types) },
+            "unavailableProducts": options.unavailable_products.as_ref().map(|value| value.to_uppercase())
+        });
+
+        let mut data = self.execute(&graphql_query, variables).await?;
+        let result: Option<PredictiveSearchResult> = from_json(data["predictiveSearch"].take())?;
+        Ok(result.unwrap_or_default())
+    }
+
+    /// Runs a `products(query:)` search and decodes the product cards.
+    pub(crate) async fn fetch_search_products(
+        &self,
+        query: &str,
+        sort_key: Option<String>,
+        reverse: bool,
+        page: &PageArgs,
+    ) -> Result<Connection<Product>, JsValue> {
+        let graphql_query = format!(
+            r#"
+            query searchProducts(
+                $query: String!
+                {CONNECTION_VARIABLES}
+                $sortKey: ProductSortKeys
+                $reverse: Boolean
+            ) {{
+                products({CONNECTION_ARGUMENTS}, query: $query, sortKey: $sortKey, reverse: $reverse) {{
+                    edges {{
+                        node {{
+                            ...ProductCardFields
+                        }}
+                    }}
+                    {PAGE_INFO_FIELDS}
+                }}
+            }}
+            {PRODUCT_CARD_FRAGMENT}
+        "#
+        );
+
+        let mut variables = serde_json::json!({
+            "query": query,
+            "sortKey": sort_key,
+            "reverse": reverse
+        });
+        page.add_to(&mut variables);
+
+        let mut data = self.execute(&graphql_query, variables).await?;
+        from_json(data["products"].take())
+    }
+}
@@ -0,0 +1,194 @@
+//! Section-ready content built from metaobjects.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::collection::MAX_PAGE_SIZE;
+use crate::cost::estimate_query_cost;
+use crate::metafield::typed_value;
+use crate::pagination::PAGE_INFO_FIELDS;
+use crate::product::{PRODUCT_CARD_FRAGMENT, RESOLVED_REFERENCE_FIELDS};
+use crate::types::{Connection, ResolvedReference};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Sections fetched per type when `SectionOptions.first` isn't given.
+const DEFAULT_SECTIONS_PER_TYPE: u32 = 20;
+
+/// Options for `get_sections`, passed from JavaScript as a plain object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SectionOptions {
+    /// Metaobjects fetched per type, up to 250.
+    pub first: Option<u32>,
+}
+
+/// A metaobject flattened into a section: field values decoded by type and
+/// reference fields replaced by the resources they point at.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Section {
+    pub id: String,
+    pub handle: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub settings: BTreeMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SectionField {
+    key: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    reference: Option<ResolvedReference>,
+    #[serde(default)]
+    references: Option<Connection<ResolvedReference>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SectionMetaobject {
+    id: String,
+    handle: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    fields: Vec<SectionField>,
+}
+
+impl SectionField {
+    /// Resolved references win over the raw value, which for reference
+    /// fields is only a global ID (or a JSON list of them).
+    fn setting(self) -> Value {
+        if let Some(references) = self.references {
+            let nodes: Vec<ResolvedReference> =
+                references.edges.into_iter().map(|edge| edge.node).collect();
+            return serde_json::to_value(nodes).unwrap_or(Value::Null);
+        }
+        if let Some(reference) = self.reference {
+            return serde_json::to_value(reference).unwrap_or(Value::Null);
+        }
+        match self.value {
+            Some(value) => typed_value(&self.kind, &value),
+            None => Value::Null,
+        }
+    }
+}
+
+impl From<SectionMetaobject> for Section {
+    fn from(metaobject: SectionMetaobject) -> Section {
+        Section {
+            id: metaobject.id,
+            handle: metaobject.handle,
+            kind: metaobject.kind,
+            settings: metaobject
+                .fields
+                .into_iter()
+                .map(|field| (field.key.clone(), field.setting()))
+                .collect(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches the metaobjects of each type in `types` (e.g.
+    /// `["hero_banner", "feature_grid"]`) and returns them as sections:
+    /// `[{ id, handle, type, settings }]`, in the order of `types`. Settings
+    /// hold decoded field values, with file references resolved to images,
+    /// videos and files and product references to product cards.
+    #[wasm_bindgen(unchecked_return_type = "Section[]")]
+    pub async fn get_sections(
+        &self,
+        types: Vec<String>,
+        #[wasm_bindgen(unchecked_optional_param_type = "SectionOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options: SectionOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => SectionOptions::default(),
+        };
+        let first = options
+            .first
+            .unwrap_or(DEFAULT_SECTIONS_PER_TYPE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        let query = format!(
+            r#"
+            query getSections($type: String!, $first: Int!, $after: String) {{
+                metaobjects(type: $type, first: $first, after: $after) {{
+                    edges {{
+                        node {{
+                            id
+                            handle
+                            type
+                            fields {{
+                                key
+                                type
+                                value
+                                reference {{
+                                    {RESOLVED_REFERENCE_FIELDS}
+                                }}
+                                references(first: 25) {{
+                                    edges {{
+                                        node {{
+                                            {RESOLVED_REFERENCE_FIELDS}
+                                        }}
+                                    }}
+                                }}
+                            }}
+                        }}
+                    }}
+                    {PAGE_INFO_FIELDS}
+                }}
+            }}
+            {PRODUCT_CARD_FRAGMENT}
+        "#
+        );
+
+        // Resolved references make each metaobject expensive, so a type's
+        // sections come a few per request, as many as fit the cost limit.
+        let estimate = |first: u32| {
+            estimate_query_cost(&query, &serde_json::json!({ "type": "", "first": first }))
+        };
+        let per_section = estimate(2).saturating_sub(estimate(1));
+        let overhead = estimate(1).saturating_sub(per_section);
+        let page_size = self
+            .cost
+            .borrow()
+            .items_per_query(overhead, per_section, first as usize) as u32;
+
+        let mut sections: Vec<Section> = Vec::new();
+        for kind in types {
+            let mut fetched = 0;
+            let mut after: Option<String> = None;
+            while fetched < first {
+                let variables = serde_json::json!({
+                    "type": kind,
+                    "first": page_size.min(first - fetched),
+                    "after": after
+                });
+                let mut data = self.execute(&query, variables).await?;
+                let connection: Connection<SectionMetaobject> =
+                    from_json(data["metaobjects"].take())?;
+                let page_info = connection.page_info.unwrap_or_default();
+                if connection.edges.is_empty() {
+                    break;
+                }
+                fetched += connection.edges.len() as u32;
+                sections.extend(connection.edges.into_iter().map(|edge| edge.node.into()));
+                match page_info.end_cursor {
+                    Some(cursor) if page_info.has_next_page => after = Some(cursor),
+                    _ => break,
+                }
+            }
+        }
+
+        to_js(&sections)
+    }
+}
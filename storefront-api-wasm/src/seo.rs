@@ -0,0 +1,99 @@
+//! Structured data for search engines.
+
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::from_js;
+use crate::types::{legacy_id, Product};
+
+/// Builds a schema.org `Product` object with one `Offer` per variant (or a
+/// single offer from the price range when variants weren't fetched).
+pub fn product_json_ld_value(product: &Product) -> Value {
+    let url = product.online_store_url.clone();
+
+    let offers: Vec<Value> = match &product.variants {
+        Some(variants) => variants
+            .nodes()
+            .map(|variant| {
+                let mut offer = json!({
+                    "@type": "Offer",
+                    "name": variant.title,
+                    "price": variant.price.amount,
+                    "priceCurrency": variant.price.currency_code,
+                    "availability": availability(variant.available_for_sale),
+                });
+                if let Some(url) = &url {
+                    offer["url"] = json!(format!("{url}?variant={}", legacy_id(&variant.id)));
+                }
+                offer
+            })
+            .collect(),
+        None => {
+            let price = &product.price_range.min_variant_price;
+            let mut offer = json!({
+                "@type": "Offer",
+                "price": price.amount,
+                "priceCurrency": price.currency_code,
+            });
+            if let Some(url) = &url {
+                offer["url"] = json!(url);
+            }
+            vec![offer]
+        }
+    };
+
+    let description = product
+        .seo
+        .as_ref()
+        .and_then(|seo| seo.description.clone())
+        .or_else(|| product.description.clone());
+
+    let mut ld = json!({
+        "@context": "https://schema.org/",
+        "@type": "Product",
+        "name": product.title,
+        "offers": offers,
+    });
+    if let Some(description) = description.filter(|d| !d.is_empty()) {
+        ld["description"] = json!(description);
+    }
+    if let Some(vendor) = product.vendor.as_ref().filter(|v| !v.is_empty()) {
+        ld["brand"] = json!({ "@type": "Brand", "name": vendor });
+    }
+    let images: Vec<&str> = product
+        .images
+        .nodes()
+        .map(|image| image.url.as_str())
+        .collect();
+    if !images.is_empty() {
+        ld["image"] = json!(images);
+    }
+    if let Some(url) = url {
+        ld["url"] = json!(url);
+    }
+    ld
+}
+
+fn availability(available: bool) -> &'static str {
+    if available {
+        "https://schema.org/InStock"
+    } else {
+        "https://schema.org/OutOfStock"
+    }
+}
+
+/// Serializes JSON-LD so it can be placed verbatim inside
+/// `<script type="application/ld+json">` (`</` is escaped).
+pub fn to_script_json(value: &Value) -> String {
+    value.to_string().replace("</", "<\\/")
+}
+
+/// Returns schema.org Product/Offer JSON-LD for a product returned by
+/// `get_product`, ready to inject into the page head.
+#[wasm_bindgen]
+pub fn product_json_ld(
+    #[wasm_bindgen(unchecked_param_type = "Product")] product: JsValue,
+) -> Result<String, JsValue> {
+    let product: Product = from_js(product)?;
+    Ok(to_script_json(&product_json_ld_value(&product)))
+}
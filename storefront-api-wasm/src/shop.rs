@@ -0,0 +1,128 @@
+//! Shop-level settings: name, domain, payment settings and brand.
+
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::types::{Brand, Shop};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Selection for `Shop.brand`, shared by `get_shop` and `get_brand`.
+const BRAND_FIELDS: &str = r#"
+    logo { id alt image { url altText width height } }
+    squareLogo { id alt image { url altText width height } }
+    coverImage { id alt image { url altText width height } }
+    colors {
+        primary { background foreground }
+        secondary { background foreground }
+    }
+    slogan
+    shortDescription
+"#;
+
+impl Brand {
+    /// CSS custom properties for the brand colors, e.g.
+    /// `--color-primary-background` and `--color-secondary-2-foreground`
+    /// (groups after the first are numbered from 2).
+    pub fn css_variables(&self) -> BTreeMap<String, String> {
+        let mut variables = BTreeMap::new();
+        for (role, groups) in [
+            ("primary", &self.colors.primary),
+            ("secondary", &self.colors.secondary),
+        ] {
+            for (index, group) in groups.iter().enumerate() {
+                let prefix = match index {
+                    0 => format!("--color-{role}"),
+                    _ => format!("--color-{role}-{}", index + 1),
+                };
+                if let Some(background) = &group.background {
+                    variables.insert(format!("{prefix}-background"), background.clone());
+                }
+                if let Some(foreground) = &group.foreground {
+                    variables.insert(format!("{prefix}-foreground"), foreground.clone());
+                }
+            }
+        }
+        variables
+    }
+}
+
+/// Returns the brand colors of a `get_brand` result as a map of CSS custom
+/// properties, ready to spread into a `style` attribute.
+#[wasm_bindgen(unchecked_return_type = "Record<string, string>")]
+pub fn brand_css_variables(
+    #[wasm_bindgen(unchecked_param_type = "Brand")] brand: JsValue,
+) -> Result<JsValue, JsValue> {
+    let brand: Brand = from_js(brand)?;
+    to_js(&brand.css_variables())
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Returns the shop's name, description, primary domain, payment settings
+    /// (accepted card brands, currencies, digital wallets), `shipsToCountries`
+    /// and brand assets.
+    #[wasm_bindgen(unchecked_return_type = "Shop")]
+    pub async fn get_shop(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.fetch_shop().await?)
+    }
+
+    /// Returns the brand settings (logo, square logo, cover image, colors,
+    /// slogan and short description), or `null` if none are configured.
+    #[wasm_bindgen(unchecked_return_type = "Brand | null")]
+    pub async fn get_brand(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.fetch_brand().await?)
+    }
+}
+
+impl StorefrontApi {
+    pub(crate) async fn fetch_shop(&self) -> Result<Shop, JsValue> {
+        let query = format!(
+            r#"
+            query getShop {{
+                shop {{
+                    id
+                    name
+                    description
+                    primaryDomain {{
+                        host
+                        url
+                        sslEnabled
+                    }}
+                    paymentSettings {{
+                        acceptedCardBrands
+                        countryCode
+                        currencyCode
+                        enabledPresentmentCurrencies
+                        supportedDigitalWallets
+                    }}
+                    shipsToCountries
+                    brand {{
+                        {BRAND_FIELDS}
+                    }}
+                }}
+            }}
+        "#
+        );
+
+        let mut data = self.execute(&query, serde_json::Value::Null).await?;
+        from_json(data["shop"].take())
+    }
+
+    pub(crate) async fn fetch_brand(&self) -> Result<Option<Brand>, JsValue> {
+        let query = format!(
+            r#"
+            query getBrand {{
+                shop {{
+                    brand {{
+                        {BRAND_FIELDS}
+                    }}
+                }}
+            }}
+        "#
+        );
+
+        let mut data = self.execute(&query, serde_json::Value::Null).await?;
+        from_json(data["shop"]["brand"].take())
+    }
+}
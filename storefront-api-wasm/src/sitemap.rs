@@ -0,0 +1,162 @@
+//! Sitemap entries built from every published resource.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::collection::MAX_PAGE_SIZE;
+use crate::pagination::PAGE_INFO_FIELDS;
+use crate::types::{Connection, PageInfo};
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Resource kinds included when `SitemapOptions.kinds` is empty.
+const SITEMAP_KINDS: &[&str] = &["product", "collection", "page", "article"];
+
+/// Options for `build_sitemap_entries`, passed from JavaScript as a plain
+/// object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SitemapOptions {
+    /// Origin prefixed to each path, e.g. `https://example.com`. Entries keep
+    /// a bare path when omitted.
+    pub base_url: Option<String>,
+    /// Subset of `product`, `collection`, `page` and `article`.
+    pub kinds: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SitemapEntry {
+    pub kind: String,
+    pub handle: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blog_handle: Option<String>,
+    /// Absolute when `base_url` was given, otherwise a path like
+    /// `/products/shirt`.
+    pub loc: String,
+    /// `updatedAt`, or `publishedAt` for articles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastmod: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SitemapBlog {
+    handle: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SitemapNode {
+    handle: String,
+    #[serde(default)]
+    updated_at: Option<String>,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    blog: Option<SitemapBlog>,
+}
+
+/// Root connection field and node selection for a sitemap kind.
+fn kind_selection(kind: &str) -> Option<(&'static str, &'static str)> {
+    match kind {
+        "product" => Some(("products", "handle updatedAt")),
+        "collection" => Some(("collections", "handle updatedAt")),
+        "page" => Some(("pages", "handle updatedAt")),
+        "article" => Some(("articles", "handle publishedAt blog { handle }")),
+        _ => None,
+    }
+}
+
+impl SitemapNode {
+    fn into_entry(self, kind: &str, base_url: &str) -> SitemapEntry {
+        let blog_handle = self.blog.map(|blog| blog.handle);
+        let path = match (kind, &blog_handle) {
+            ("article", Some(blog)) => format!("/blogs/{blog}/{}", self.handle),
+            _ => format!("/{kind}s/{}", self.handle),
+        };
+        SitemapEntry {
+            kind: kind.to_string(),
+            handle: self.handle,
+            blog_handle,
+            loc: format!("{base_url}{path}"),
+            lastmod: self.updated_at.or(self.published_at),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Pages through every product, collection, page and article and returns
+    /// `[{ kind, handle, blogHandle?, loc, lastmod? }]` for generating
+    /// `sitemap.xml`. Each kind is fetched 250 at a time, so large catalogs
+    /// take several requests.
+    #[wasm_bindgen(unchecked_return_type = "SitemapEntry[]")]
+    pub async fn build_sitemap_entries(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "SitemapOptions")] options: Option<JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let options: SitemapOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => SitemapOptions::default(),
+        };
+        let kinds: Vec<String> = if options.kinds.is_empty() {
+            SITEMAP_KINDS.iter().map(|kind| kind.to_string()).collect()
+        } else {
+            options
+                .kinds
+                .iter()
+                .map(|kind| kind.trim().to_lowercase())
+                .collect()
+        };
+        let base_url = options.base_url.unwrap_or_default();
+        let base_url = base_url.trim_end_matches('/');
+
+        let mut entries = Vec::new();
+        for kind in &kinds {
+            let (field, selection) = kind_selection(kind).ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Invalid sitemap kind: {kind}. Expected one of {}",
+                    SITEMAP_KINDS.join(", ")
+                ))
+            })?;
+            let query = format!(
+                r#"
+                query sitemap{field}($first: Int!, $after: String) {{
+                    {field}(first: $first, after: $after) {{
+                        edges {{
+                            node {{
+                                {selection}
+                            }}
+                        }}
+                        {PAGE_INFO_FIELDS}
+                    }}
+                }}
+            "#
+            );
+
+            let mut after: Option<String> = None;
+            loop {
+                let variables = serde_json::json!({ "first": MAX_PAGE_SIZE, "after": after });
+                let mut data = self.execute(&query, variables).await?;
+                let connection: Connection<SitemapNode> = from_json(data[field].take())?;
+                let PageInfo {
+                    has_next_page,
+                    end_cursor,
+                    ..
+                } = connection.page_info.unwrap_or_default();
+                entries.extend(
+                    connection
+                        .edges
+                        .into_iter()
+                        .map(|edge| edge.node.into_entry(kind, base_url)),
+                );
+                match end_cursor {
+                    Some(cursor) if has_next_page => after = Some(cursor),
+                    _ => break,
+                }
+            }
+        }
+
+        to_js(&entries)
+    }
+}
@@ -0,0 +1,454 @@
+//! Test helpers: a controllable `fetch` and Storefront response fixtures.
+//!
+//! `MockFetch` replaces the global `fetch` the client sends requests with,
+//! answers each request with the next response queued for its operation
+//! and keeps what was sent, so tests (this crate's `wasm-bindgen-test`
+//! suites, or a consumer's tests of their own wrappers in a headless
+//! browser or Node) can check both sides without a shop:
+//!
+//! ```js
+//! const fetch = new MockFetch();
+//! fetch.install();
+//! fetch.respond_with("getCart", { data: { cart: null } });
+//! await api.get_cart("gid://shopify/Cart/1"); // null
+//! fetch.requests()[0].variables; // { id: "gid://shopify/Cart/1" }
+//! fetch.uninstall();
+//! ```
+//!
+//! Responses queued without an operation answer any request. A request
+//! nothing is queued for fails like a network error, naming its operation.
+//! The fixture functions build response bodies in the shape the client's
+//! types decode.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::Rc;
+
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{Request, Response, ResponseInit};
+
+use crate::logging::operation_name;
+use crate::{from_js, to_js};
+
+/// What a queued response does.
+#[derive(Debug, Clone)]
+enum Outcome {
+    Json {
+        status: u16,
+        body: Value,
+        headers: BTreeMap<String, String>,
+    },
+    NetworkError(String),
+}
+
+#[derive(Debug, Clone)]
+struct Queued {
+    /// `None` answers any operation.
+    operation: Option<String>,
+    outcome: Outcome,
+}
+
+/// A request the mock received.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub operation: Option<String>,
+    pub query: String,
+    pub variables: Value,
+    /// Header names lowercased.
+    pub headers: BTreeMap<String, String>,
+    /// The JSON body, or for a `GET` the query parameters.
+    pub body: Value,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    queue: VecDeque<Queued>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockState {
+    /// Takes the first response queued for `operation`.
+    fn next(&mut self, operation: Option<&str>) -> Option<Outcome> {
+        let index = self.queue.iter().position(|queued| {
+            queued.operation.is_none() || queued.operation.as_deref() == operation
+        })?;
+        self.queue.remove(index).map(|queued| queued.outcome)
+    }
+}
+
+/// Reads the `{ query, variables }` of a `GET` request from its URL.
+fn url_body(url: &str) -> Result<Value, JsValue> {
+    let mut body = serde_json::Map::new();
+    let params = url.split_once('?').map(|(_, params)| params).unwrap_or("");
+    for param in params.split('&').filter(|param| !param.is_empty()) {
+        let (name, value) = param.split_once('=').unwrap_or((param, ""));
+        let value = String::from(js_sys::decode_uri_component(value)?);
+        let value = match name {
+            "variables" => serde_json::from_str(&value)
+                .map_err(|error| JsValue::from_str(&format!("Variables are not JSON: {error}")))?,
+            _ => Value::String(value),
+        };
+        body.insert(name.to_string(), value);
+    }
+    Ok(Value::Object(body))
+}
+
+/// Reads a request the client sent, for `requests`.
+async fn read_request(request: &Request) -> Result<RecordedRequest, JsValue> {
+    let body = if request.method() == "GET" {
+        url_body(&request.url())?
+    } else {
+        let text = JsFuture::from(request.text()?).await?;
+        serde_json::from_str(&text.as_string().unwrap_or_default())
+            .map_err(|error| JsValue::from_str(&format!("Request body is not JSON: {error}")))?
+    };
+    let query = body["query"].as_str().unwrap_or_default().to_string();
+    let mut headers = BTreeMap::new();
+    if let Some(entries) = js_sys::try_iter(&request.headers())? {
+        for entry in entries {
+            let entry: js_sys::Array = entry?.into();
+            if let (Some(name), Some(value)) = (entry.get(0).as_string(), entry.get(1).as_string())
+            {
+                headers.insert(name.to_ascii_lowercase(), value);
+            }
+        }
+    }
+    Ok(RecordedRequest {
+        method: request.method(),
+        url: request.url(),
+        operation: operation_name(&query).map(String::from),
+        query,
+        variables: body["variables"].clone(),
+        headers,
+        body,
+    })
+}
+
+fn json_response(
+    status: u16,
+    body: &Value,
+    extra_headers: &BTreeMap<String, String>,
+) -> Result<Response, JsValue> {
+    let init = ResponseInit::new();
+    init.set_status(status);
+    let headers = web_sys::Headers::new()?;
+    headers.set("Content-Type", "application/json")?;
+    for (name, value) in extra_headers {
+        headers.set(name, value)?;
+    }
+    init.set_headers(&headers);
+    // `304 Not Modified` and friends can't have a body.
+    let body = body.to_string();
+    let body = (!matches!(status, 204 | 304)).then_some(body.as_str());
+    Response::new_with_opt_str_and_init(body, &init)
+}
+
+/// A stand-in for the global `fetch`.
+#[wasm_bindgen]
+pub struct MockFetch {
+    state: Rc<RefCell<MockState>>,
+    handler: Closure<dyn FnMut(Request) -> js_sys::Promise>,
+    /// The `fetch` `install` replaced.
+    previous: Option<JsValue>,
+}
+
+impl MockFetch {
+    fn queue(&self, operation: Option<String>, outcome: Outcome) {
+        self.state
+            .borrow_mut()
+            .queue
+            .push_back(Queued { operation, outcome });
+    }
+
+    /// Queues `body` (a whole GraphQL response, e.g. `data(...)`) for the
+    /// next request for `operation`.
+    pub fn respond(&self, operation: &str, body: Value) {
+        self.respond_status(operation, 200, body);
+    }
+
+    /// Queues a response with an HTTP status, e.g. `429`.
+    pub fn respond_status(&self, operation: &str, status: u16, body: Value) {
+        self.respond_headers(operation, status, body, &[]);
+    }
+
+    /// Queues a response with an HTTP status and headers, e.g. an `ETag`.
+    pub fn respond_headers(
+        &self,
+        operation: &str,
+        status: u16,
+        body: Value,
+        headers: &[(&str, &str)],
+    ) {
+        self.queue(
+            Some(operation.to_string()),
+            Outcome::Json {
+                status,
+                body,
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            },
+        );
+    }
+
+    /// Makes the next request for `operation` fail as if the network did.
+    pub fn fail(&self, operation: &str, message: &str) {
+        self.queue(
+            Some(operation.to_string()),
+            Outcome::NetworkError(message.to_string()),
+        );
+    }
+
+    /// The requests received so far, oldest first.
+    pub fn recorded(&self) -> Vec<RecordedRequest> {
+        self.state.borrow().requests.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl MockFetch {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> MockFetch {
+        let state = Rc::new(RefCell::new(MockState::default()));
+        let handler_state = Rc::clone(&state);
+        let handler =
+            Closure::<dyn FnMut(Request) -> js_sys::Promise>::new(move |request: Request| {
+                let state = Rc::clone(&handler_state);
+                future_to_promise(async move {
+                    let recorded = read_request(&request).await?;
+                    let operation = recorded.operation.clone();
+                    state.borrow_mut().requests.push(recorded);
+                    let outcome = state.borrow_mut().next(operation.as_deref());
+                    match outcome {
+                        Some(Outcome::Json {
+                            status,
+                            body,
+                            headers,
+                        }) => Ok(json_response(status, &body, &headers)?.into()),
+                        Some(Outcome::NetworkError(message)) => {
+                            Err(js_sys::TypeError::new(&message).into())
+                        }
+                        None => Err(js_sys::TypeError::new(&format!(
+                            "MockFetch has no response queued for {}",
+                            operation.as_deref().unwrap_or("an anonymous operation")
+                        ))
+                        .into()),
+                    }
+                })
+            });
+        MockFetch {
+            state,
+            handler,
+            previous: None,
+        }
+    }
+
+    /// Replaces the global `fetch` with the mock until `uninstall`.
+    #[wasm_bindgen]
+    pub fn install(&mut self) -> Result<(), JsValue> {
+        let global = js_sys::global();
+        let fetch = JsValue::from_str("fetch");
+        if self.previous.is_none() {
+            self.previous = Some(js_sys::Reflect::get(&global, &fetch)?);
+        }
+        js_sys::Reflect::set(&global, &fetch, self.handler.as_ref())?;
+        Ok(())
+    }
+
+    /// The mock as a `fetch` function, to pass to `with_fetch` instead of
+    /// installing it globally.
+    #[wasm_bindgen(unchecked_return_type = "Fetch")]
+    pub fn as_fetch(&self) -> js_sys::Function {
+        self.handler
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone()
+    }
+
+    /// Puts back the `fetch` `install` replaced.
+    #[wasm_bindgen]
+    pub fn uninstall(&mut self) -> Result<(), JsValue> {
+        if let Some(previous) = self.previous.take() {
+            js_sys::Reflect::set(&js_sys::global(), &JsValue::from_str("fetch"), &previous)?;
+        }
+        Ok(())
+    }
+
+    /// Queues a GraphQL response body (`{ data }` or `{ errors }`) for the
+    /// next request for `operation` (e.g. `"getCart"`), or for any request
+    /// when it is omitted. `status` defaults to 200; `headers` is an
+    /// optional object of response headers, e.g. `{ ETag: "\"v1\"" }`.
+    #[wasm_bindgen]
+    pub fn respond_with(
+        &self,
+        operation: Option<String>,
+        #[wasm_bindgen(unchecked_param_type = "unknown")] body: JsValue,
+        status: Option<u16>,
+        #[wasm_bindgen(unchecked_optional_param_type = "Record<string, string>")] headers: Option<
+            JsValue,
+        >,
+    ) -> Result<(), JsValue> {
+        let body: Value = from_js(body)?;
+        let headers: BTreeMap<String, String> = match headers {
+            Some(headers) if !headers.is_undefined() && !headers.is_null() => from_js(headers)?,
+            _ => BTreeMap::new(),
+        };
+        self.queue(
+            operation,
+            Outcome::Json {
+                status: status.unwrap_or(200),
+                body,
+                headers,
+            },
+        );
+        Ok(())
+    }
+
+    /// Makes the next request for `operation` (or any, when omitted) fail
+    /// as if the network did.
+    #[wasm_bindgen]
+    pub fn fail_next(&self, operation: Option<String>, message: String) {
+        self.queue(operation, Outcome::NetworkError(message));
+    }
+
+    /// The requests received so far, as `{ method, url, operation, query,
+    /// variables, headers }`.
+    #[wasm_bindgen(unchecked_return_type = "RecordedRequest[]")]
+    pub fn requests(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.state.borrow().requests)
+    }
+
+    /// Responses queued but not yet used.
+    #[wasm_bindgen]
+    pub fn pending(&self) -> usize {
+        self.state.borrow().queue.len()
+    }
+
+    /// Forgets queued responses and received requests.
+    #[wasm_bindgen]
+    pub fn reset(&self) {
+        let mut state = self.state.borrow_mut();
+        state.queue.clear();
+        state.requests.clear();
+    }
+}
+
+impl Default for MockFetch {
+    fn default() -> Self {
+        MockFetch::new()
+    }
+}
+
+impl Drop for MockFetch {
+    fn drop(&mut self) {
+        let _ = self.uninstall();
+    }
+}
+
+/// A successful GraphQL response.
+pub fn data(data: Value) -> Value {
+    serde_json::json!({ "data": data })
+}
+
+/// A GraphQL response with top-level errors, each `(message, code)`.
+pub fn graphql_errors(errors: &[(&str, Option<&str>)]) -> Value {
+    let errors: Vec<Value> = errors
+        .iter()
+        .map(|(message, code)| match code {
+            Some(code) => serde_json::json!({
+                "message": message,
+                "extensions": { "code": code },
+            }),
+            None => serde_json::json!({ "message": message }),
+        })
+        .collect();
+    serde_json::json!({ "data": null, "errors": errors })
+}
+
+/// A connection of `nodes`, with cursors `cursor-<n>` and the page info
+/// given.
+pub fn connection(nodes: Vec<Value>, has_next_page: bool) -> Value {
+    let edges: Vec<Value> = nodes
+        .into_iter()
+        .enumerate()
+        .map(|(index, node)| serde_json::json!({ "cursor": format!("cursor-{index}"), "node": node }))
+        .collect();
+    let end_cursor = edges.last().map(|edge| edge["cursor"].clone());
+    serde_json::json!({
+        "edges": edges,
+        "pageInfo": {
+            "hasNextPage": has_next_page,
+            "hasPreviousPage": false,
+            "endCursor": end_cursor,
+        },
+    })
+}
+
+/// A product with one variant at `price` USD.
+pub fn product(handle: &str, price: &str) -> Value {
+    let money = serde_json::json!({ "amount": price, "currencyCode": "USD" });
+    serde_json::json!({
+        "id": format!("gid://shopify/Product/{handle}"),
+        "handle": handle,
+        "title": handle,
+        "availableForSale": true,
+        "priceRange": { "minVariantPrice": money, "maxVariantPrice": money },
+        "images": connection(Vec::new(), false),
+        "variants": connection(
+            vec![serde_json::json!({
+                "id": format!("gid://shopify/ProductVariant/{handle}"),
+                "title": "Default Title",
+                "price": money,
+                "availableForSale": true,
+                "selectedOptions": [],
+            })],
+            false,
+        ),
+    })
+}
+
+/// A cart holding `(line ID, variant ID, quantity)` lines at 10.00 USD
+/// each.
+pub fn cart(id: &str, lines: &[(&str, &str, u32)]) -> Value {
+    let quantity: u32 = lines.iter().map(|(_, _, quantity)| quantity).sum();
+    let lines: Vec<Value> = lines
+        .iter()
+        .map(|(line_id, variant_id, quantity)| {
+            serde_json::json!({
+                "id": line_id,
+                "quantity": quantity,
+                "attributes": [],
+                "merchandise": {
+                    "id": variant_id,
+                    "title": "Default Title",
+                    "price": { "amount": "10.0", "currencyCode": "USD" },
+                    "product": {
+                        "id": "gid://shopify/Product/1",
+                        "handle": "product",
+                        "title": "Product",
+                    },
+                },
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "id": id,
+        "checkoutUrl": format!("https://shop.example/cart/c/{}", id.rsplit('/').next().unwrap_or(id)),
+        "totalQuantity": quantity,
+        "cost": {
+            "totalAmount": {
+                "amount": format!("{:.1}", f64::from(quantity) * 10.0),
+                "currencyCode": "USD",
+            },
+        },
+        "lines": connection(lines, false),
+        "discountCodes": [],
+    })
+}
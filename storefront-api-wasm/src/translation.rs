@@ -0,0 +1,104 @@
+//! The same resource fetched in several content languages.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::types::Seo;
+use crate::{from_json, to_js, StorefrontApi};
+
+/// Most languages `get_translations` accepts in one call.
+const MAX_TRANSLATION_LANGUAGES: usize = 20;
+
+/// Translatable text of a product, collection or page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslatedContent {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+    /// Product and collection description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_html: Option<String>,
+    /// Page body.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online_store_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seo: Option<Seo>,
+}
+
+/// Root field and text selection for a translatable resource kind.
+fn resource_selection(kind: &str) -> Result<(&'static str, &'static str), JsValue> {
+    match kind {
+        "product" => Ok(("product", "descriptionHtml")),
+        "collection" => Ok(("collection", "descriptionHtml")),
+        "page" => Ok(("page", "body")),
+        _ => Err(JsValue::from_str(&format!(
+            "Invalid resource kind: {kind}. Expected product, collection or page"
+        ))),
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Fetches a product, collection or page by handle in each of
+    /// `languages` (e.g. `["EN", "FR", "PT_BR"]`) and returns a map from
+    /// language code to `{ id, handle, title, descriptionHtml | body,
+    /// onlineStoreUrl, seo }`, or `null` where the resource doesn't exist.
+    ///
+    /// `@inContext` applies to a whole operation, so this sends one query
+    /// per language.
+    #[wasm_bindgen(unchecked_return_type = "Record<string, TranslatedContent | null>")]
+    pub async fn get_translations(
+        &self,
+        kind: String,
+        handle: String,
+        languages: Vec<String>,
+    ) -> Result<JsValue, JsValue> {
+        let (field, text_field) = resource_selection(kind.trim().to_lowercase().as_str())?;
+        if languages.len() > MAX_TRANSLATION_LANGUAGES {
+            return Err(JsValue::from_str(&format!(
+                "At most {MAX_TRANSLATION_LANGUAGES} languages can be fetched at once, got {}",
+                languages.len()
+            )));
+        }
+
+        let query = format!(
+            r#"
+            query getTranslation($handle: String!) {{
+                {field}(handle: $handle) {{
+                    id
+                    handle
+                    title
+                    {text_field}
+                    onlineStoreUrl
+                    seo {{
+                        title
+                        description
+                    }}
+                }}
+            }}
+        "#
+        );
+
+        let mut translations: BTreeMap<String, Option<TranslatedContent>> = BTreeMap::new();
+        for language in languages {
+            let client = self.with_context(None, Some(language))?;
+            let language = client
+                .context
+                .borrow()
+                .language
+                .clone()
+                .ok_or_else(|| JsValue::from_str("Language code must not be empty"))?;
+
+            let variables = serde_json::json!({ "handle": handle });
+            let mut data = client.execute(&query, variables).await?;
+            translations.insert(language, from_json(data[field].take())?);
+        }
+
+        to_js(&translations)
+    }
+}
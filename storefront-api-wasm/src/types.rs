@@ -0,0 +1,1125 @@
+//! Typed views over Storefront API responses.
+//!
+//! Field names follow the GraphQL schema (camelCase) so values serialize back to
+//! JavaScript in the same shape the API returned them. Fields that only some
+//! selection sets request are optional and omitted when absent.
+
+use serde::{Deserialize, Serialize};
+
+/// Returns the trailing numeric ID of a global ID, e.g. `123` for
+/// `gid://shopify/ProductVariant/123`. Query strings are stripped.
+pub fn legacy_id(gid: &str) -> &str {
+    let id = gid.rsplit('/').next().unwrap_or(gid);
+    id.split('?').next().unwrap_or(id)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Money {
+    pub amount: String,
+    pub currency_code: String,
+}
+
+impl Money {
+    /// The decimal amount as a float, or `0.0` if the API returned something
+    /// unparsable.
+    pub fn value(&self) -> f64 {
+        self.amount.parse().unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    pub has_next_page: bool,
+    pub has_previous_page: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_cursor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Edge<T> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    pub node: T,
+}
+
+/// A GraphQL connection (`edges { node }` plus optional `pageInfo`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection<T> {
+    pub edges: Vec<Edge<T>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_info: Option<PageInfo>,
+}
+
+impl<T> Connection<T> {
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        self.edges.iter().map(|edge| &edge.node)
+    }
+}
+
+impl<T> Default for Connection<T> {
+    fn default() -> Self {
+        Connection {
+            edges: Vec::new(),
+            page_info: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductPriceRange {
+    pub min_variant_price: Money,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_variant_price: Option<Money>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedOption {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellingPlanOption {
+    pub name: Option<String>,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellingPlanGroupOption {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// How a selling plan changes the price, discriminated by `__typename`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "__typename")]
+pub enum SellingPlanPriceAdjustmentValue {
+    #[serde(rename_all = "camelCase")]
+    SellingPlanFixedAmountPriceAdjustment { adjustment_amount: Money },
+    #[serde(rename_all = "camelCase")]
+    SellingPlanFixedPriceAdjustment { price: Money },
+    #[serde(rename_all = "camelCase")]
+    SellingPlanPercentagePriceAdjustment { adjustment_percentage: f64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellingPlanPriceAdjustment {
+    pub adjustment_value: SellingPlanPriceAdjustmentValue,
+    /// Number of orders the adjustment applies to; `None` means every order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_count: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellingPlan {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub recurring_deliveries: bool,
+    #[serde(default)]
+    pub options: Vec<SellingPlanOption>,
+    #[serde(default)]
+    pub price_adjustments: Vec<SellingPlanPriceAdjustment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellingPlanGroup {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    #[serde(default)]
+    pub options: Vec<SellingPlanGroupOption>,
+    pub selling_plans: Connection<SellingPlan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellingPlanAllocationPriceAdjustment {
+    pub price: Money,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compare_at_price: Option<Money>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_delivery_price: Option<Money>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellingPlanReference {
+    pub id: String,
+}
+
+/// The resolved price of a variant under one selling plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellingPlanAllocation {
+    pub selling_plan: SellingPlanReference,
+    #[serde(default)]
+    pub price_adjustments: Vec<SellingPlanAllocationPriceAdjustment>,
+}
+
+/// Measurement a variant's unit price is expressed against, e.g. 250 ML sold
+/// and priced per 100 ML.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitPriceMeasurement {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub measured_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity_unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_value: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductVariant {
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub barcode: Option<String>,
+    pub price: Money,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compare_at_price: Option<Money>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_price: Option<Money>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_price_measurement: Option<UnitPriceMeasurement>,
+    pub available_for_sale: bool,
+    #[serde(default)]
+    pub selected_options: Vec<SelectedOption>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selling_plan_allocations: Option<Connection<SellingPlanAllocation>>,
+    /// Sellable quantity; requires the `unauthenticated_read_product_inventory` scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity_available: Option<i64>,
+    /// True when the variant is out of stock but still sellable (backorder).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currently_not_in_stock: Option<bool>,
+    /// True for bundle variants that can only be sold with their components.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires_components: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<Connection<ProductVariantComponent>>,
+    /// Parent product; selected when the variant is fetched on its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<ProductReference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductReference {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+}
+
+/// The variant a bundle component points at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentVariant {
+    pub id: String,
+    pub title: String,
+    pub available_for_sale: bool,
+    pub price: Money,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+    pub product: ProductReference,
+}
+
+/// One component of a bundle variant and how many of it the bundle holds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductVariantComponent {
+    pub quantity: u32,
+    pub product_variant: ComponentVariant,
+}
+
+/// Image backing a swatch (a `MediaImage`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwatchImage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_image: Option<Image>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductOptionValueSwatch {
+    /// Hex color such as `#1f3a93`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<SwatchImage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductOptionValue {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swatch: Option<ProductOptionValueSwatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductOption {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub option_values: Vec<ProductOptionValue>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Seo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Identifies a metafield by namespace and key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetafieldIdentifier {
+    pub namespace: String,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metafield {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub namespace: String,
+    pub key: String,
+    /// The metafield type, e.g. `single_line_text_field` or `list.product_reference`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+    /// Resolved target of a `*_reference` metafield, when requested with
+    /// `resolveReferences`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<ResolvedReference>,
+    /// Resolved targets of a `list.*_reference` metafield.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<Connection<ResolvedReference>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoSource {
+    pub url: String,
+    pub mime_type: String,
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Model3dSource {
+    pub url: String,
+    pub mime_type: String,
+    pub format: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filesize: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaImage {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_image: Option<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Video {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_image: Option<Image>,
+    #[serde(default)]
+    pub sources: Vec<VideoSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalVideo {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_image: Option<Image>,
+    /// `YOUTUBE` or `VIMEO`.
+    pub host: String,
+    pub embed_url: String,
+    pub origin_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Model3d {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_image: Option<Image>,
+    #[serde(default)]
+    pub sources: Vec<Model3dSource>,
+}
+
+/// A product media item, discriminated by its GraphQL `__typename`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "__typename")]
+pub enum Media {
+    MediaImage(MediaImage),
+    Video(Video),
+    ExternalVideo(ExternalVideo),
+    Model3d(Model3d),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Product {
+    pub id: String,
+    pub title: String,
+    pub handle: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_gift_card: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<ProductOption>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seo: Option<Seo>,
+    /// Public URL on the Online Store; `None` when the product isn't published there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online_store_url: Option<String>,
+    /// True when at least one variant is available for sale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_for_sale: Option<bool>,
+    /// ISO 8601 date-time the product was published to the storefront.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    pub price_range: ProductPriceRange,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compare_at_price_range: Option<ProductPriceRange>,
+    #[serde(default)]
+    pub images: Connection<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variants: Option<Connection<ProductVariant>>,
+    /// Requested metafields, in request order; `None` where the product has no
+    /// value for an identifier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metafields: Option<Vec<Option<Metafield>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media: Option<Connection<Media>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires_selling_plan: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selling_plan_groups: Option<Connection<SellingPlanGroup>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seo: Option<Seo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metafields: Option<Vec<Option<Metafield>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub products: Option<Connection<Product>>,
+}
+
+/// Inclusive price bounds for a `ProductFilter`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceRangeFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariantOptionFilter {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetafieldFilter {
+    pub namespace: String,
+    pub key: String,
+    pub value: String,
+}
+
+/// One entry of the `ProductFilter` input used by `products(filters:)`. Set a
+/// single field per filter; filters in a list are combined by the API.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<PriceRangeFilter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_vendor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant_option: Option<VariantOptionFilter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_metafield: Option<MetafieldFilter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant_metafield: Option<MetafieldFilter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_summary: Option<String>,
+    /// Page content as HTML.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seo: Option<Seo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metafields: Option<Vec<Option<Metafield>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online_store_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogReference {
+    pub handle: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Article {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt_html: Option<String>,
+    /// Article body as HTML.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, rename = "authorV2", skip_serializing_if = "Option::is_none")]
+    pub author: Option<ArticleAuthor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seo: Option<Seo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blog: Option<BlogReference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online_store_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleAuthor {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Blog {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seo: Option<Seo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online_store_url: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<ArticleAuthor>,
+}
+
+/// One hit from the `search` root field, discriminated by `__typename`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "__typename")]
+pub enum SearchResultItem {
+    Product(Box<Product>),
+    Page(Box<Page>),
+    Article(Box<Article>),
+}
+
+/// One selectable value of a facet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterValue {
+    pub id: String,
+    pub label: String,
+    pub count: u32,
+    /// The `ProductFilter` input that selects this value, parsed from the JSON
+    /// string the API returns so it can be passed straight back as a filter.
+    #[serde(deserialize_with = "json_string")]
+    pub input: serde_json::Value,
+}
+
+impl FilterValue {
+    /// The value's input as a typed `ProductFilter`, when it matches one.
+    pub fn product_filter(&self) -> Option<ProductFilter> {
+        serde_json::from_value(self.input.clone()).ok()
+    }
+}
+
+/// A facet available for the current product set (`Filter` in the schema).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Filter {
+    pub id: String,
+    pub label: String,
+    /// `LIST`, `PRICE_RANGE` or `BOOLEAN`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub values: Vec<FilterValue>,
+}
+
+/// Parses a field the API delivers as a JSON-encoded string.
+fn json_string<'de, D>(deserializer: D) -> Result<serde_json::Value, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw = String::deserialize(deserializer)?;
+    serde_json::from_str(&raw).map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationAddress {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address2: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub province: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    /// Address lines formatted for display.
+    #[serde(default)]
+    pub formatted: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub id: String,
+    pub name: String,
+    pub address: LocationAddress,
+}
+
+/// Pickup availability of a variant at one location.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreAvailability {
+    pub available: bool,
+    /// Estimated pickup time, e.g. "Usually ready in 24 hours".
+    pub pick_up_time: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity_available: Option<i64>,
+    pub location: Location,
+}
+
+/// Coordinates used to sort locations by distance.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoCoordinate {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenericFile {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantReference {
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<ProductReference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionReference {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageReference {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaobjectReference {
+    pub id: String,
+    pub handle: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// The resource a reference-typed metafield or metaobject field points at,
+/// discriminated by `__typename`. Types this crate doesn't model decode as
+/// `Unknown`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "__typename")]
+pub enum MetafieldReference {
+    Product(ProductReference),
+    ProductVariant(VariantReference),
+    Collection(CollectionReference),
+    Page(PageReference),
+    Metaobject(MetaobjectReference),
+    MediaImage(MediaImage),
+    Video(Video),
+    GenericFile(GenericFile),
+    #[serde(other)]
+    Unknown,
+}
+
+/// A metafield reference resolved to the full object: product cards,
+/// purchasable variants and metaobjects with their field values, for
+/// "complete the look" and related-content blocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "__typename")]
+pub enum ResolvedReference {
+    Product(Box<Product>),
+    ProductVariant(Box<ProductVariant>),
+    Collection(CollectionReference),
+    Page(PageReference),
+    Metaobject(Box<Metaobject>),
+    MediaImage(MediaImage),
+    Video(Video),
+    GenericFile(GenericFile),
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaobjectField {
+    pub key: String,
+    /// The field type, e.g. `single_line_text_field` or `file_reference`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<MetafieldReference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<Connection<MetafieldReference>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metaobject {
+    pub id: String,
+    pub handle: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<MetaobjectField>,
+}
+
+impl Metaobject {
+    pub fn field(&self, key: &str) -> Option<&MetaobjectField> {
+        self.fields.iter().find(|field| field.key == key)
+    }
+
+    /// The raw string value of a field.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.field(key)?.value.as_deref()
+    }
+}
+
+/// A cart or line item attribute (line item property).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribute {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CartMerchandise {
+    pub id: String,
+    pub title: String,
+    pub price: Money,
+    pub product: ProductReference,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CartLine {
+    pub id: String,
+    pub quantity: u32,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    pub merchandise: CartMerchandise,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CartCost {
+    pub total_amount: Money,
+}
+
+/// A discount code entered on a cart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CartDiscountCode {
+    pub code: String,
+    /// Whether the code applies to the cart's current contents.
+    pub applicable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cart {
+    pub id: String,
+    pub checkout_url: String,
+    pub total_quantity: u32,
+    pub cost: CartCost,
+    pub lines: Connection<CartLine>,
+    #[serde(default)]
+    pub discount_codes: Vec<CartDiscountCode>,
+}
+
+/// A node fetched by global ID, discriminated by `__typename`. Types without
+/// a selection in `get_nodes` decode as `Unknown`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "__typename")]
+pub enum Node {
+    Product(Box<Product>),
+    ProductVariant(Box<ProductVariant>),
+    Collection(Box<Collection>),
+    Cart(Box<Cart>),
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Currency {
+    pub iso_code: String,
+    pub name: String,
+    pub symbol: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Language {
+    pub iso_code: String,
+    /// The language's name in itself, e.g. `Deutsch`.
+    pub endonym_name: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Market {
+    pub id: String,
+    pub handle: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Country {
+    pub iso_code: String,
+    pub name: String,
+    pub currency: Currency,
+    /// `METRIC_SYSTEM` or `IMPERIAL_SYSTEM`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_system: Option<String>,
+    #[serde(default)]
+    pub available_languages: Vec<Language>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<Market>,
+}
+
+/// Countries and languages the storefront sells in, plus the ones in effect
+/// for the request's `@inContext`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Localization {
+    pub available_countries: Vec<Country>,
+    pub available_languages: Vec<Language>,
+    pub country: Country,
+    pub language: Language,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<Market>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Domain {
+    pub host: String,
+    pub url: String,
+    pub ssl_enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentSettings {
+    /// `CardBrand` values such as `VISA` or `AMERICAN_EXPRESS`.
+    #[serde(default)]
+    pub accepted_card_brands: Vec<String>,
+    pub country_code: String,
+    /// The shop's default currency.
+    pub currency_code: String,
+    #[serde(default)]
+    pub enabled_presentment_currencies: Vec<String>,
+    /// `DigitalWallet` values such as `APPLE_PAY` or `SHOPIFY_PAY`.
+    #[serde(default)]
+    pub supported_digital_wallets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrandColorGroup {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrandColors {
+    #[serde(default)]
+    pub primary: Vec<BrandColorGroup>,
+    #[serde(default)]
+    pub secondary: Vec<BrandColorGroup>,
+}
+
+/// Brand assets configured in the admin's brand settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Brand {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo: Option<MediaImage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub square_logo: Option<MediaImage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover_image: Option<MediaImage>,
+    #[serde(default)]
+    pub colors: BrandColors,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slogan: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Shop {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub primary_domain: Domain,
+    pub payment_settings: PaymentSettings,
+    /// `CountryCode` values the shop ships to.
+    #[serde(default)]
+    pub ships_to_countries: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brand: Option<Brand>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MenuItem {
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// `MenuItemType`, e.g. `COLLECTION`, `PRODUCT`, `PAGE`, `BLOG` or `HTTP`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// ID of the linked resource, for resource-backed items.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<MenuItem>,
+}
+
+/// A navigation menu from the admin's menu editor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Menu {
+    pub id: String,
+    pub handle: String,
+    pub title: String,
+    pub items_count: u32,
+    #[serde(default)]
+    pub items: Vec<MenuItem>,
+}
+
+/// A count that may be capped, e.g. `{ count: 4, precision: "EXACT" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Count {
+    pub count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShopPayInstallmentsFinancingPlanTerm {
+    pub id: String,
+    /// Annual percentage rate; `0` for interest-free plans.
+    pub apr: f64,
+    /// `ShopPayInstallmentsFinancingPlanFrequency`, e.g. `WEEKLY` or `MONTHLY`.
+    pub frequency: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installments_count: Option<Count>,
+    /// `ShopPayInstallmentsLoan`, e.g. `SPLIT_PAY` or `INTEREST`.
+    pub loan_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShopPayInstallmentsFinancingPlan {
+    pub id: String,
+    pub min_price: Money,
+    pub max_price: Money,
+    #[serde(default)]
+    pub terms: Vec<ShopPayInstallmentsFinancingPlanTerm>,
+}
+
+/// The order totals Shop Pay Installments is offered for, and its plans.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShopPayInstallmentsPricing {
+    pub min_price: Money,
+    pub max_price: Money,
+    #[serde(default)]
+    pub financing_plans: Vec<ShopPayInstallmentsFinancingPlan>,
+}
+
+/// Installment pricing for a single variant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShopPayInstallmentsProductVariantPricing {
+    pub available: bool,
+    pub eligible: bool,
+    pub full_price: Money,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installments_count: Option<Count>,
+    pub price_per_term: Money,
+}
@@ -0,0 +1,1646 @@
+//! TypeScript declarations for the values the client passes to and from
+//! JavaScript.
+//!
+//! wasm-bindgen types every `JsValue` as `any`. The interfaces below are
+//! added to the generated `.d.ts` as they are, and the methods that return
+//! or take these values name them with `unchecked_return_type`,
+//! `unchecked_param_type` and `unchecked_optional_param_type`, so
+//! TypeScript code gets `Promise<{ product: Product | null }>` from
+//! `get_product` instead of `Promise<any>`.
+//!
+//! The interfaces follow the serde shape of the Rust types: fields that
+//! are skipped when empty are optional, `Option` fields that are always
+//! written are `| null`. `tests/typescript.rs` checks that every field the
+//! Rust types write is declared here, so a field added on one side and not
+//! the other fails the build's tests.
+
+use wasm_bindgen::prelude::*;
+
+/// The declarations, as added to the `.d.ts`.
+pub const TYPESCRIPT: &str = r#"
+export interface Money {
+  amount: string;
+  currencyCode: string;
+}
+
+export interface Image {
+  id?: string;
+  url: string;
+  altText?: string;
+  width?: number;
+  height?: number;
+}
+
+export interface PageInfo {
+  hasNextPage: boolean;
+  hasPreviousPage: boolean;
+  startCursor?: string;
+  endCursor?: string;
+}
+
+export interface Edge<T> {
+  cursor?: string;
+  node: T;
+}
+
+export interface Connection<T> {
+  edges: Edge<T>[];
+  pageInfo?: PageInfo;
+}
+
+/** Cursor arguments: `first`/`after` page forward, `last`/`before` back. */
+export interface PageArgs {
+  first?: number;
+  after?: string;
+  last?: number;
+  before?: string;
+}
+
+export interface Seo {
+  title?: string;
+  description?: string;
+}
+
+export interface SelectedOption {
+  name: string;
+  value: string;
+}
+
+export interface SellingPlanOption {
+  name: string | null;
+  value: string | null;
+}
+
+export interface SellingPlanGroupOption {
+  name: string;
+  values: string[];
+}
+
+export type SellingPlanPriceAdjustmentValue =
+  | { __typename: "SellingPlanFixedAmountPriceAdjustment"; adjustmentAmount: Money }
+  | { __typename: "SellingPlanFixedPriceAdjustment"; price: Money }
+  | { __typename: "SellingPlanPercentagePriceAdjustment"; adjustmentPercentage: number };
+
+export interface SellingPlanPriceAdjustment {
+  adjustmentValue: SellingPlanPriceAdjustmentValue;
+  orderCount?: number;
+}
+
+export interface SellingPlan {
+  id: string;
+  name: string;
+  description?: string;
+  recurringDeliveries: boolean;
+  options: SellingPlanOption[];
+  priceAdjustments: SellingPlanPriceAdjustment[];
+}
+
+export interface SellingPlanGroup {
+  name: string;
+  appName?: string;
+  options: SellingPlanGroupOption[];
+  sellingPlans: Connection<SellingPlan>;
+}
+
+export interface SellingPlanAllocationPriceAdjustment {
+  price: Money;
+  compareAtPrice?: Money;
+  perDeliveryPrice?: Money;
+}
+
+export interface SellingPlanAllocation {
+  sellingPlan: { id: string };
+  priceAdjustments: SellingPlanAllocationPriceAdjustment[];
+}
+
+export interface UnitPriceMeasurement {
+  measuredType?: string;
+  quantityUnit?: string;
+  quantityValue?: number;
+  referenceUnit?: string;
+  referenceValue?: number;
+}
+
+export interface ComponentVariant {
+  id: string;
+  title: string;
+  availableForSale: boolean;
+  price: Money;
+  image?: Image;
+  product: ProductReference;
+}
+
+export interface ProductVariantComponent {
+  quantity: number;
+  productVariant: ComponentVariant;
+}
+
+export interface ProductOptionValueSwatch {
+  color?: string;
+  image?: { previewImage?: Image };
+}
+
+export interface ProductOptionValue {
+  id?: string;
+  name: string;
+  swatch?: ProductOptionValueSwatch;
+}
+
+export interface ProductOption {
+  id?: string;
+  name: string;
+  optionValues: ProductOptionValue[];
+}
+
+export interface ProductPriceRange {
+  minVariantPrice: Money;
+  maxVariantPrice?: Money;
+}
+
+export interface MetafieldIdentifier {
+  namespace: string;
+  key: string;
+}
+
+export interface Metafield {
+  id?: string;
+  namespace: string;
+  key: string;
+  type: string;
+  value: string;
+  reference?: ResolvedReference;
+  references?: Connection<ResolvedReference>;
+}
+
+export interface VideoSource {
+  url: string;
+  mimeType: string;
+  format: string;
+  width: number;
+  height: number;
+}
+
+export interface Model3dSource {
+  url: string;
+  mimeType: string;
+  format: string;
+  filesize?: number;
+}
+
+export interface MediaImage {
+  id: string;
+  alt?: string;
+  previewImage?: Image;
+  image?: Image;
+}
+
+export interface Video {
+  id: string;
+  alt?: string;
+  previewImage?: Image;
+  sources: VideoSource[];
+}
+
+export interface ExternalVideo {
+  id: string;
+  alt?: string;
+  previewImage?: Image;
+  host: string;
+  embedUrl: string;
+  originUrl: string;
+}
+
+export interface Model3d {
+  id: string;
+  alt?: string;
+  previewImage?: Image;
+  sources: Model3dSource[];
+}
+
+export type Media =
+  | ({ __typename: "MediaImage" } & MediaImage)
+  | ({ __typename: "Video" } & Video)
+  | ({ __typename: "ExternalVideo" } & ExternalVideo)
+  | ({ __typename: "Model3d" } & Model3d);
+
+export interface ProductReference {
+  id: string;
+  handle: string;
+  title: string;
+}
+
+export interface ProductVariant {
+  id: string;
+  title: string;
+  sku?: string;
+  barcode?: string;
+  price: Money;
+  compareAtPrice?: Money;
+  unitPrice?: Money;
+  unitPriceMeasurement?: UnitPriceMeasurement;
+  availableForSale: boolean;
+  selectedOptions: SelectedOption[];
+  image?: Image;
+  sellingPlanAllocations?: Connection<SellingPlanAllocation>;
+  quantityAvailable?: number;
+  currentlyNotInStock?: boolean;
+  requiresComponents?: boolean;
+  components?: Connection<ProductVariantComponent>;
+  product?: ProductReference;
+}
+
+export interface Product {
+  id: string;
+  title: string;
+  handle: string;
+  description?: string;
+  vendor?: string;
+  productType?: string;
+  tags?: string[];
+  isGiftCard?: boolean;
+  options?: ProductOption[];
+  seo?: Seo;
+  onlineStoreUrl?: string;
+  availableForSale?: boolean;
+  publishedAt?: string;
+  priceRange: ProductPriceRange;
+  compareAtPriceRange?: ProductPriceRange;
+  images: Connection<Image>;
+  variants?: Connection<ProductVariant>;
+  metafields?: (Metafield | null)[];
+  media?: Connection<Media>;
+  requiresSellingPlan?: boolean;
+  sellingPlanGroups?: Connection<SellingPlanGroup>;
+}
+
+export interface Collection {
+  id: string;
+  handle: string;
+  title: string;
+  description?: string;
+  image?: Image;
+  seo?: Seo;
+  metafields?: (Metafield | null)[];
+  products?: Connection<Product>;
+}
+
+export interface CollectionResult {
+  collection: Collection | null;
+  appliedFilters: ProductFilter[];
+  filters: Filter[];
+}
+
+export interface PriceRangeFilter {
+  min?: number;
+  max?: number;
+}
+
+export interface VariantOptionFilter {
+  name: string;
+  value: string;
+}
+
+export interface MetafieldFilter {
+  namespace: string;
+  key: string;
+  value: string;
+}
+
+/** One `ProductFilter` input; set a single field per filter. */
+export interface ProductFilter {
+  available?: boolean;
+  price?: PriceRangeFilter;
+  productType?: string;
+  productVendor?: string;
+  tag?: string;
+  variantOption?: VariantOptionFilter;
+  productMetafield?: MetafieldFilter;
+  variantMetafield?: MetafieldFilter;
+}
+
+export interface FilterValue {
+  id: string;
+  label: string;
+  count: number;
+  input: ProductFilter;
+}
+
+export interface Filter {
+  id: string;
+  label: string;
+  type: string;
+  values: FilterValue[];
+}
+
+export interface Page {
+  id: string;
+  handle: string;
+  title: string;
+  bodySummary?: string;
+  body?: string;
+  seo?: Seo;
+  metafields?: (Metafield | null)[];
+  createdAt?: string;
+  updatedAt?: string;
+  onlineStoreUrl?: string;
+}
+
+export interface BlogReference {
+  handle: string;
+  title?: string;
+}
+
+export interface ArticleAuthor {
+  name: string;
+  firstName?: string;
+  lastName?: string;
+  bio?: string;
+}
+
+export interface Article {
+  id: string;
+  handle: string;
+  title: string;
+  excerpt?: string;
+  excerptHtml?: string;
+  contentHtml?: string;
+  image?: Image;
+  tags?: string[];
+  authorV2?: ArticleAuthor;
+  publishedAt?: string;
+  seo?: Seo;
+  blog?: BlogReference;
+  onlineStoreUrl?: string;
+}
+
+export interface Blog {
+  id: string;
+  handle: string;
+  title: string;
+  seo?: Seo;
+  onlineStoreUrl?: string;
+  authors: ArticleAuthor[];
+}
+
+export type SearchResultItem =
+  | ({ __typename: "Product" } & Product)
+  | ({ __typename: "Page" } & Page)
+  | ({ __typename: "Article" } & Article);
+
+export interface LocationAddress {
+  address1?: string;
+  address2?: string;
+  city?: string;
+  province?: string;
+  zip?: string;
+  country?: string;
+  countryCode?: string;
+  phone?: string;
+  formatted: string[];
+}
+
+export interface Location {
+  id: string;
+  name: string;
+  address: LocationAddress;
+}
+
+export interface StoreAvailability {
+  available: boolean;
+  pickUpTime: string;
+  quantityAvailable?: number;
+  location: Location;
+}
+
+export interface GeoCoordinate {
+  latitude: number;
+  longitude: number;
+}
+
+export interface GenericFile {
+  id: string;
+  url?: string;
+  mimeType?: string;
+  alt?: string;
+}
+
+export interface VariantReference {
+  id: string;
+  title: string;
+  product?: ProductReference;
+}
+
+export interface CollectionReference {
+  id: string;
+  handle: string;
+  title: string;
+}
+
+export interface PageReference {
+  id: string;
+  handle: string;
+  title: string;
+}
+
+export interface MetaobjectReference {
+  id: string;
+  handle: string;
+  type: string;
+}
+
+/** What a reference-typed metaobject field points at. */
+export type MetafieldReference =
+  | ({ __typename: "Product" } & ProductReference)
+  | ({ __typename: "ProductVariant" } & VariantReference)
+  | ({ __typename: "Collection" } & CollectionReference)
+  | ({ __typename: "Page" } & PageReference)
+  | ({ __typename: "Metaobject" } & MetaobjectReference)
+  | ({ __typename: "MediaImage" } & MediaImage)
+  | ({ __typename: "Video" } & Video)
+  | ({ __typename: "GenericFile" } & GenericFile)
+  | { __typename: "Unknown" };
+
+/** A metafield reference resolved to the full object. */
+export type ResolvedReference =
+  | ({ __typename: "Product" } & Product)
+  | ({ __typename: "ProductVariant" } & ProductVariant)
+  | ({ __typename: "Collection" } & CollectionReference)
+  | ({ __typename: "Page" } & PageReference)
+  | ({ __typename: "Metaobject" } & Metaobject)
+  | ({ __typename: "MediaImage" } & MediaImage)
+  | ({ __typename: "Video" } & Video)
+  | ({ __typename: "GenericFile" } & GenericFile)
+  | { __typename: "Unknown" };
+
+export interface MetaobjectField {
+  key: string;
+  type: string;
+  value?: string;
+  reference?: MetafieldReference;
+  references?: Connection<MetafieldReference>;
+}
+
+export interface Metaobject {
+  id: string;
+  handle: string;
+  type: string;
+  updatedAt?: string;
+  fields: MetaobjectField[];
+}
+
+export type Node =
+  | ({ __typename: "Product" } & Product)
+  | ({ __typename: "ProductVariant" } & ProductVariant)
+  | ({ __typename: "Collection" } & Collection)
+  | ({ __typename: "Cart" } & Cart)
+  | { __typename: "Unknown" };
+
+export interface Currency {
+  isoCode: string;
+  name: string;
+  symbol: string;
+}
+
+export interface Language {
+  isoCode: string;
+  endonymName: string;
+  name: string;
+}
+
+export interface Market {
+  id: string;
+  handle: string;
+}
+
+export interface Country {
+  isoCode: string;
+  name: string;
+  currency: Currency;
+  unitSystem?: string;
+  availableLanguages: Language[];
+  market?: Market;
+}
+
+export interface Localization {
+  availableCountries: Country[];
+  availableLanguages: Language[];
+  country: Country;
+  language: Language;
+  market?: Market;
+}
+
+export interface Domain {
+  host: string;
+  url: string;
+  sslEnabled: boolean;
+}
+
+export interface PaymentSettings {
+  acceptedCardBrands: string[];
+  countryCode: string;
+  currencyCode: string;
+  enabledPresentmentCurrencies: string[];
+  supportedDigitalWallets: string[];
+}
+
+export interface BrandColorGroup {
+  background?: string;
+  foreground?: string;
+}
+
+export interface BrandColors {
+  primary: BrandColorGroup[];
+  secondary: BrandColorGroup[];
+}
+
+export interface Brand {
+  logo?: MediaImage;
+  squareLogo?: MediaImage;
+  coverImage?: MediaImage;
+  colors: BrandColors;
+  slogan?: string;
+  shortDescription?: string;
+}
+
+export interface Shop {
+  id: string;
+  name: string;
+  description?: string;
+  primaryDomain: Domain;
+  paymentSettings: PaymentSettings;
+  shipsToCountries: string[];
+  brand?: Brand;
+}
+
+export interface MenuItem {
+  id: string;
+  title: string;
+  url?: string;
+  type: string;
+  resourceId?: string;
+  tags?: string[];
+  items?: MenuItem[];
+}
+
+export interface Menu {
+  id: string;
+  handle: string;
+  title: string;
+  itemsCount: number;
+  items: MenuItem[];
+}
+
+export interface Count {
+  count: number;
+  precision?: string;
+}
+
+export interface ShopPayInstallmentsFinancingPlanTerm {
+  id: string;
+  apr: number;
+  frequency: string;
+  installmentsCount?: Count;
+  loanType: string;
+}
+
+export interface ShopPayInstallmentsFinancingPlan {
+  id: string;
+  minPrice: Money;
+  maxPrice: Money;
+  terms: ShopPayInstallmentsFinancingPlanTerm[];
+}
+
+export interface ShopPayInstallmentsPricing {
+  minPrice: Money;
+  maxPrice: Money;
+  financingPlans: ShopPayInstallmentsFinancingPlan[];
+}
+
+export interface ShopPayInstallmentsProductVariantPricing {
+  available: boolean;
+  eligible: boolean;
+  fullPrice: Money;
+  installmentsCount?: Count;
+  pricePerTerm: Money;
+}
+
+export interface Attribute {
+  key: string;
+  value: string;
+}
+
+export interface CartLine {
+  id: string;
+  quantity: number;
+  attributes: Attribute[];
+  merchandise: CartMerchandise;
+}
+
+export interface CartMerchandise {
+  id: string;
+  title: string;
+  price: Money;
+  product: ProductReference;
+}
+
+export interface CartDiscountCode {
+  code: string;
+  applicable: boolean;
+}
+
+export interface Cart {
+  id: string;
+  checkoutUrl: string;
+  totalQuantity: number;
+  cost: { totalAmount: Money };
+  lines: Connection<CartLine>;
+  discountCodes: CartDiscountCode[];
+}
+
+export interface CartUserError {
+  field: string[] | null;
+  message: string;
+  code: string | null;
+}
+
+export type CartChange =
+  | { type: "lineAdded"; line: CartLine }
+  | { type: "lineRemoved"; line: CartLine }
+  | { type: "quantityChanged"; lineId: string; variantId: string; previous: number; quantity: number }
+  | { type: "priceChanged"; lineId: string; variantId: string; previous: Money; price: Money }
+  | { type: "discountApplied"; code: string }
+  | { type: "discountExpired"; code: string }
+  | { type: "totalChanged"; previous: Money; total: Money }
+  | { type: "cartGone" };
+
+export interface CartItem {
+  variant_id: string;
+  quantity: number;
+  attributes?: Attribute[];
+  gift_card_recipient?: GiftCardRecipient;
+}
+
+export interface GiftCardRecipient {
+  email: string;
+  name?: string;
+  message?: string;
+  send_on?: string;
+}
+
+export type ErrorKind =
+  | "graphql"
+  | "userError"
+  | "throttled"
+  | "maxCostExceeded"
+  | "network"
+  | "http"
+  | "response";
+
+export type ErrorCategory =
+  | "network"
+  | "timeout"
+  | "cancelled"
+  | "throttled"
+  | "auth"
+  | "validation"
+  | "userError"
+  | "server";
+
+export interface ErrorDetail {
+  message: string;
+  code?: string;
+  field?: string[];
+}
+
+/** What client methods reject with (as an `Error` with these fields). */
+export interface StorefrontError {
+  kind: ErrorKind;
+  category: ErrorCategory;
+  code: string;
+  message: string;
+  retryable: boolean;
+  errors: ErrorDetail[];
+}
+
+export interface RetryPolicy {
+  maxRetries: number;
+  baseDelayMs: number;
+  maxDelayMs: number;
+}
+
+export interface ErrorReportOptions {
+  redactKeys?: string[];
+  includeVariables?: boolean;
+}
+
+export interface ErrorReport {
+  operation: string;
+  variables: unknown;
+  durationMs: number;
+  attempts: number;
+  kind: ErrorKind | null;
+  category: ErrorCategory | null;
+  code: string | null;
+  retryable: boolean;
+  message: string;
+  errors: ErrorDetail[];
+}
+
+export interface Operation {
+  name?: string;
+  query: string;
+  variables?: Record<string, unknown>;
+}
+
+export interface QueryAllOptions {
+  concurrency?: number;
+}
+
+export interface OperationResult {
+  name: string;
+  ok: boolean;
+  data?: unknown;
+  error?: Partial<StorefrontError>;
+}
+
+export interface ErrorSummary {
+  count: number;
+  byCategory: Partial<Record<ErrorCategory | "unknown", number>>;
+  failed: string[];
+  retryable: boolean;
+}
+
+export interface QueryAllResult {
+  ok: boolean;
+  results: OperationResult[];
+  errors: ErrorSummary;
+}
+
+export interface CacheUsage {
+  entries: number;
+  bytes: number;
+}
+
+export interface MemoryStats {
+  heapBytes: number;
+  responseCache: CacheUsage;
+  prefetched: CacheUsage;
+  cacheBytes: number;
+}
+
+export interface CanonicalRequest {
+  hash: string;
+  query: string;
+  variables: unknown;
+}
+
+export type QueryStatus = "idle" | "loading" | "data" | "error" | "refreshing";
+
+export interface QueryState {
+  status: QueryStatus;
+  data: unknown | null;
+  error: Partial<StorefrontError> | null;
+  updatedAt: number | null;
+}
+
+export interface MigrationIssue {
+  operation: string;
+  path: string;
+  typeName: string;
+  field: string;
+  change: "deprecated" | "removed";
+  reason: string | null;
+  replacement: string | null;
+}
+
+export interface MigrationReport {
+  from: string;
+  to: string;
+  queries: number;
+  deprecated: number;
+  removed: number;
+  issues: MigrationIssue[];
+  skipped: string[];
+}
+
+export type AnalyticsEventName =
+  | "page_viewed"
+  | "product_viewed"
+  | "search_submitted"
+  | "cart_updated";
+
+export interface AnalyticsOptions {
+  shopId?: string | null;
+  currency?: string | null;
+  collector?: string | null;
+}
+
+export interface AnalyticsEvent {
+  name: AnalyticsEventName;
+  eventId: string;
+  timestamp: number;
+  url: string | null;
+  referrer: string | null;
+  title: string | null;
+  uniqueToken: string | null;
+  visitToken: string | null;
+  payload: unknown;
+}
+
+export interface InstallmentsMessage {
+  eligible: boolean;
+  price: Money;
+  installmentsCount: number | null;
+  pricePerTerm: Money | null;
+  interestFree: boolean;
+  frequency: string | null;
+  message: string | null;
+  minPrice: Money | null;
+}
+
+export interface VariantAvailability {
+  variantId: string;
+  availableForSale: boolean;
+  quantityAvailable: number | null;
+}
+
+export interface MetafieldsSetInput {
+  ownerId: string;
+  namespace: string;
+  key: string;
+  type: string;
+  value: string;
+}
+
+export interface InventoryChange {
+  inventoryItemId: string;
+  locationId: string;
+  delta: number;
+}
+
+export interface WebhookLineItem {
+  id: string;
+  title: string;
+  quantity: number;
+  variantId: string | null;
+  productId: string | null;
+  sku: string | null;
+  price: string;
+}
+
+export interface WebhookOrder {
+  id: string;
+  adminGraphqlApiId: string | null;
+  name: string;
+  email: string | null;
+  createdAt: string | null;
+  currency: string;
+  totalPrice: string;
+  subtotalPrice: string | null;
+  financialStatus: string | null;
+  fulfillmentStatus: string | null;
+  cancelledAt: string | null;
+  lineItems: WebhookLineItem[];
+}
+
+export interface WebhookVariant {
+  id: string;
+  title: string | null;
+  sku: string | null;
+  price: string;
+  inventoryItemId: string | null;
+  inventoryQuantity: number | null;
+}
+
+export interface WebhookProduct {
+  id: string;
+  adminGraphqlApiId: string | null;
+  title: string;
+  handle: string;
+  status: string | null;
+  vendor: string | null;
+  productType: string | null;
+  tags: string | null;
+  variants: WebhookVariant[];
+}
+
+export interface WebhookInventoryLevel {
+  inventoryItemId: string;
+  locationId: string;
+  available: number | null;
+  updatedAt: string | null;
+}
+
+export interface WebhookCustomer {
+  id: string;
+  email: string | null;
+  firstName: string | null;
+  lastName: string | null;
+  state: string | null;
+}
+
+export interface WebhookShop {
+  id: string;
+  name: string;
+  domain: string | null;
+  myshopifyDomain: string | null;
+}
+
+export type WebhookPayload =
+  | ({ type: "order" } & WebhookOrder)
+  | ({ type: "product" } & WebhookProduct)
+  | { type: "deleted"; id: string }
+  | ({ type: "inventoryLevel" } & WebhookInventoryLevel)
+  | ({ type: "customer" } & WebhookCustomer)
+  | ({ type: "shop" } & WebhookShop)
+  | { type: "other"; body: unknown };
+
+export interface WebhookEvent {
+  topic: string;
+  payload: WebhookPayload;
+}
+
+export type UrlKind =
+  | "home"
+  | "product"
+  | "collection"
+  | "page"
+  | "blog"
+  | "article"
+  | "search"
+  | "cart";
+
+export interface UrlSettings {
+  baseUrl?: string | null;
+  primaryCountry?: string | null;
+  primaryLanguage?: string | null;
+  rootUrl?: string | null;
+}
+
+export interface UrlOptions {
+  variantId?: string | null;
+  collection?: string | null;
+}
+
+export interface PermalinkItem {
+  variant_id: string;
+  quantity: number;
+}
+
+export interface PermalinkOptions {
+  baseUrl?: string | null;
+  discount?: string | null;
+  note?: string | null;
+  attributes?: Record<string, string>;
+  ref?: string | null;
+  accessToken?: string | null;
+}
+
+export type Variables = Record<string, unknown>;
+
+/** A `fetch` replacement: called with a `Request`, resolves to a `Response`. */
+export type Fetch = (request: Request) => Promise<Response>;
+
+/** Paging and sorting for the list helpers, e.g. `list_collections`. */
+export interface ListOptions {
+  first?: number;
+  after?: string;
+  before?: string;
+  last?: number;
+  sortKey?: string;
+  reverse?: boolean;
+}
+
+export interface ArticleListOptions {
+  first?: number;
+  after?: string;
+  last?: number;
+  before?: string;
+  sortKey?: string;
+  reverse?: boolean;
+  query?: string;
+}
+
+export interface ArticleList {
+  articles: Article[];
+  pageInfo: PageInfo;
+}
+
+export interface BundleComponent {
+  quantity: number;
+  variant: ComponentVariant;
+}
+
+export interface BundleVariant {
+  variantId: string;
+  title: string;
+  requiresComponents: boolean;
+  components: BundleComponent[];
+}
+
+export type CacheMode = "off" | "staleWhileRevalidate" | "revalidate";
+
+export interface CacheOptions {
+  mode: CacheMode;
+  maxAgeMs: number;
+  staleMs: number;
+  maxEntries: number;
+}
+
+/** What `on_revalidate` callbacks receive. */
+export interface RevalidatedResponse {
+  operation: string;
+  variables: unknown;
+  data: Record<string, unknown>;
+}
+
+export interface CollectionOptions {
+  filters?: ProductFilter[];
+  sortKey?: string;
+  reverse?: boolean;
+  metafields?: MetafieldIdentifier[];
+  availableForSale?: boolean;
+  after?: string;
+  before?: string;
+  last?: number;
+}
+
+/** Page size and item cap for walks over every page of a connection. */
+export interface PaginationLimits {
+  pageSize?: number;
+  maxItems?: number;
+}
+
+export interface AllProductsResult {
+  products: Product[];
+  truncated: boolean;
+}
+
+export interface CollectionSummary {
+  id: string;
+  handle: string;
+  title: string;
+  description: string;
+  image?: Image;
+  updatedAt: string;
+  productCount: number;
+  productCountIsLowerBound: boolean;
+}
+
+export interface CollectionList {
+  collections: CollectionSummary[];
+  pageInfo: PageInfo;
+}
+
+export interface ProductOptions {
+  metafields?: MetafieldIdentifier[];
+  media?: boolean;
+  sellingPlans?: boolean;
+  inventory?: boolean;
+  components?: boolean;
+  resolveReferences?: boolean;
+}
+
+export interface ListingVisibility {
+  availableOnly?: boolean;
+  publishedOnly?: boolean;
+}
+
+export interface ComparisonRow {
+  kind: "attribute" | "option" | "metafield";
+  key: string;
+  label: string;
+  values: (string | null)[];
+  differs: boolean;
+}
+
+export interface ComparisonTable {
+  products: Product[];
+  rows: ComparisonRow[];
+  missing: string[];
+}
+
+export interface InContext {
+  country: string | null;
+  language: string | null;
+  currency: string | null;
+}
+
+export interface CostLimits {
+  mode: "off" | "warn" | "refuse";
+  maxQueryCost: number;
+  bucketSize: number;
+  restoreRate: number;
+}
+
+export interface ConnectionCost {
+  path: string;
+  pageSize: number;
+  cost: number;
+}
+
+export interface CostEstimate {
+  cost: number;
+  maxQueryCost: number;
+  bucketAvailable: number | null;
+  exceedsQueryLimit: boolean;
+  exceedsBucket: boolean;
+  connections: ConnectionCost[];
+}
+
+export interface GiftCardDenomination {
+  variantId: string;
+  title: string;
+  price: Money;
+  availableForSale: boolean;
+}
+
+export interface GiftCardInfo {
+  denominations: GiftCardDenomination[];
+  recipientFields: { key: string; required: boolean }[];
+  maxMessageLength: number;
+}
+
+export interface HreflangOptions {
+  baseUrl?: string;
+  countries?: string[];
+}
+
+export interface HreflangAlternate {
+  hreflang: string;
+  href: string;
+}
+
+export interface SizeRule {
+  minWidth?: number | null;
+  size: string;
+}
+
+export interface ResponsiveImageOptions {
+  widths?: number[];
+  sizes?: SizeRule[];
+  srcWidth?: number;
+  format?: string;
+}
+
+export interface ResponsiveImage {
+  src: string;
+  srcset: string;
+  sizes: string;
+  width?: number;
+  height?: number;
+  alt: string;
+}
+
+export interface VariantInstallments extends ShopPayInstallmentsProductVariantPricing {
+  variantId: string;
+  title: string;
+  interestFree: boolean;
+  message: string | null;
+}
+
+export interface InstallmentsPricing {
+  shop: ShopPayInstallmentsPricing | null;
+  variants: VariantInstallments[];
+}
+
+/** A template's variables; Storefront objects become theme drops. */
+export type TemplateData = Record<string, unknown>;
+
+export interface Diagnostic {
+  severity: "error" | "warning";
+  message: string;
+  line: number | null;
+  column: number | null;
+  source: string | null;
+}
+
+export interface HydrationMarker {
+  id: string;
+  kind: string;
+  formType?: string;
+  mutation?: string;
+  objectId?: string;
+  form?: string;
+}
+
+export interface Rendered {
+  output: string;
+  diagnostics: Diagnostic[];
+  hydration?: HydrationMarker[];
+}
+
+/** Called as `callback(input, args, options)` for `{{ input | name: args }}`. */
+export type LiquidFilter = (
+  input: unknown,
+  args: unknown[],
+  options: Record<string, unknown>,
+) => unknown;
+
+/** Called as `callback(args, options, { body, variables })`; returns the text. */
+export type LiquidTag = (
+  args: unknown[],
+  options: Record<string, unknown>,
+  scope: { body: string | null; variables: Record<string, unknown> },
+) => unknown;
+
+export interface TagOptions {
+  block?: boolean;
+}
+
+export type LiquidDropInput = Product | ProductVariant | Collection | Cart | Image | Metafield;
+
+export interface LintOptions {
+  globals?: string[];
+}
+
+export interface LocaleDictionary {
+  [key: string]: string | LocaleDictionary;
+}
+
+export interface MoneyFormat {
+  currency: string;
+  moneyFormat: string | null;
+  moneyWithCurrencyFormat: string | null;
+}
+
+export interface TemplateQuery {
+  query: string;
+  objects: string[];
+}
+
+export interface SettingDefinition {
+  type: string;
+  id?: string;
+  label?: string;
+  default?: unknown;
+  [key: string]: unknown;
+}
+
+export interface SectionSchema {
+  name?: string;
+  settings?: SettingDefinition[];
+  blocks?: { type: string; name?: string; settings?: SettingDefinition[] }[];
+  max_blocks?: number;
+  [key: string]: unknown;
+}
+
+export interface BlockData {
+  type: string;
+  settings?: Record<string, unknown>;
+}
+
+export interface SectionData {
+  id?: string;
+  settings?: Record<string, unknown>;
+  blocks?: Record<string, BlockData> | (BlockData & { id: string })[];
+  block_order?: string[];
+}
+
+export interface SettingsSchemaGroup {
+  name: string;
+  settings?: SettingDefinition[];
+  [key: string]: unknown;
+}
+
+export interface SettingsData {
+  current?: string | Record<string, unknown> | null;
+  presets?: Record<string, Record<string, unknown>> | null;
+}
+
+export interface SectionEntry extends SectionData {
+  type: string;
+  disabled?: boolean;
+}
+
+export interface JsonTemplate {
+  layout?: string | false | null;
+  wrapper?: string | null;
+  sections?: Record<string, SectionEntry>;
+  order?: string[];
+}
+
+export interface MarketPrice {
+  country: string;
+  priceRange: ProductPriceRange | null;
+  compareAtPriceRange?: ProductPriceRange;
+  availableForSale?: boolean;
+}
+
+export interface MarketSuggestion {
+  country: string;
+  countryName: string;
+  language: string | null;
+  currencyCode: string;
+  source: "locale" | "timeZone";
+  differs: boolean;
+}
+
+export interface AuditEntry {
+  startedAt: string;
+  tool: string;
+  arguments: unknown;
+  durationMs: number;
+  status: "ok" | "error" | "rejected" | "cancelled";
+  error?: string;
+  message?: string;
+}
+
+export interface ToolAnnotations {
+  title?: string;
+  readOnlyHint?: boolean;
+  destructiveHint?: boolean;
+  idempotentHint?: boolean;
+  openWorldHint?: boolean;
+}
+
+export interface ToolDefinition {
+  name: string;
+  title?: string;
+  description: string;
+  inputSchema: Record<string, unknown>;
+  outputSchema?: Record<string, unknown>;
+  annotations?: ToolAnnotations;
+}
+
+export interface HostToolDefinition extends ToolDefinition {
+  toolset?: string;
+}
+
+export interface ToolResult {
+  content: { type: "text"; text: string }[];
+  structuredContent?: unknown;
+  isError?: boolean;
+}
+
+export interface ToolCallContext {
+  store: string | null;
+  progressToken: string | number | null;
+  signal?: AbortSignal;
+}
+
+/** Returns a string, a complete `ToolResult` or any other JSON value. */
+export type HostToolCallback = (args: Record<string, unknown>, context: ToolCallContext) => unknown;
+
+export interface LimitOptions {
+  toolCallsPerMinute: number;
+  sessionCallsPerMinute: number;
+  maxQueryCost: number;
+  costBucketSize: number;
+  costRestoreRate: number;
+}
+
+export interface Usage {
+  callsLastMinute: number;
+  costAvailable: number;
+  totalCost: number;
+  totalRequests: number;
+  refused: number;
+}
+
+export interface OperationToolOptions {
+  name?: string;
+  title?: string;
+  description?: string;
+  variableDescriptions?: Record<string, string>;
+  toolset?: string;
+}
+
+export type JsonValue = string | number | boolean | null | JsonValue[] | { [key: string]: JsonValue };
+
+/** `"shop"`, a resource global ID, or the logged-in customer. */
+export type MetafieldOwner = string | { customerAccessToken: string };
+
+export interface TypedMetafield {
+  namespace: string;
+  key: string;
+  type: string;
+  value: JsonValue;
+  rawValue: string;
+  reference?: MetafieldReference;
+}
+
+export interface MetaobjectList {
+  metaobjects: Metaobject[];
+  pageInfo: PageInfo;
+}
+
+export interface IntrospectionSchema {
+  types: unknown[];
+  [key: string]: unknown;
+}
+
+/** An introspection result, with or without its `data` wrapper. */
+export type Introspection =
+  | IntrospectionSchema
+  | { __schema: IntrospectionSchema }
+  | { data: { __schema: IntrospectionSchema } };
+
+export interface PageOptions {
+  metafields?: MetafieldIdentifier[];
+}
+
+export interface PageList {
+  pages: Page[];
+  pageInfo: PageInfo;
+}
+
+export interface SaleInfo {
+  onSale: boolean;
+  price: Money;
+  compareAtPrice?: Money;
+  amountOff?: Money;
+  percentOff: number;
+}
+
+export interface ConsentState {
+  analyticsAllowed: boolean;
+  marketingAllowed: boolean;
+  preferencesAllowed: boolean;
+  saleOfDataAllowed: boolean;
+}
+
+export interface VariantMatch {
+  product: Product;
+  variant: ProductVariant;
+}
+
+export interface QueryHandleOptions {
+  lazy?: boolean;
+}
+
+export interface ResponseLimits {
+  maxBytes: number;
+  chunkedThreshold: number;
+  chunkBytes: number;
+}
+
+export type Route =
+  | { kind: "redirect"; location: string }
+  | { kind: "product"; id: string; handle: string; collectionHandle?: string }
+  | { kind: "collection"; id: string; handle: string }
+  | { kind: "page"; id: string; handle: string }
+  | { kind: "blog"; id: string; handle: string }
+  | { kind: "article"; id: string; handle: string; blogHandle: string }
+  | { kind: "notFound" };
+
+export interface InventoryAdjustmentGroup {
+  createdAt: string;
+  reason: string;
+  changes: {
+    name: string;
+    delta: number;
+    quantityAfterChange: number | null;
+    item: { id: string };
+    location: { id: string };
+  }[];
+}
+
+/** Fields of `build_product_query`; each set field adds one `AND` term. */
+export interface ProductQuery {
+  text?: string | null;
+  tags?: string[];
+  excludeTags?: string[];
+  vendor?: string | null;
+  productType?: string | null;
+  title?: string | null;
+  minPrice?: number | null;
+  maxPrice?: number | null;
+  createdAfter?: string | null;
+  createdBefore?: string | null;
+  updatedAfter?: string | null;
+  availableForSale?: boolean | null;
+}
+
+export interface SearchOptions {
+  first?: number;
+  after?: string;
+  last?: number;
+  before?: string;
+  productFilters?: ProductFilter[];
+  prefix?: "LAST" | "NONE";
+  unavailableProducts?: "HIDE" | "LAST" | "SHOW";
+  types?: ("ARTICLE" | "PAGE" | "PRODUCT")[];
+}
+
+export interface SearchResult {
+  items: SearchResultItem[];
+  totalCount: number;
+  pageInfo: PageInfo;
+  productFilters: Filter[];
+}
+
+export interface PredictiveSearchOptions {
+  limit?: number;
+  limitScope?: "ALL" | "EACH";
+  types?: ("ARTICLE" | "COLLECTION" | "PAGE" | "PRODUCT" | "QUERY")[];
+  unavailableProducts?: "HIDE" | "LAST" | "SHOW";
+}
+
+export interface SearchQuerySuggestion {
+  text: string;
+  styledText: string;
+}
+
+export interface PredictiveSearchResult {
+  products: Product[];
+  collections: Collection[];
+  pages: Page[];
+  articles: Article[];
+  queries: SearchQuerySuggestion[];
+}
+
+export interface SectionOptions {
+  first?: number;
+}
+
+export interface Section {
+  id: string;
+  handle: string;
+  type: string;
+  settings: Record<string, unknown>;
+}
+
+export interface SitemapOptions {
+  baseUrl?: string;
+  kinds?: ("product" | "collection" | "page" | "article")[];
+}
+
+export interface SitemapEntry {
+  kind: string;
+  handle: string;
+  blogHandle?: string;
+  loc: string;
+  lastmod?: string;
+}
+
+export interface TranslatedContent {
+  id: string;
+  handle: string;
+  title: string;
+  descriptionHtml?: string;
+  body?: string;
+  onlineStoreUrl?: string;
+  seo?: Seo;
+}
+
+export interface OptionValueState {
+  name: string;
+  exists: boolean;
+  available: boolean;
+  selected: boolean;
+  minPrice?: Money;
+  image?: Image;
+  swatch?: ProductOptionValueSwatch;
+}
+
+export interface OptionAxis {
+  name: string;
+  values: OptionValueState[];
+}
+
+export interface VariantCell {
+  id: string;
+  values: string[];
+  available: boolean;
+  price: Money;
+}
+
+export interface VariantMatrix {
+  options: OptionAxis[];
+  variants: VariantCell[];
+  selectedVariantId?: string;
+}
+
+export interface RecordedRequest {
+  method: string;
+  url: string;
+  operation: string | null;
+  query: string;
+  variables: unknown;
+  headers: Record<string, string>;
+  body: unknown;
+}
+
+export interface Interaction {
+  operation: string;
+  query: string;
+  variables?: unknown;
+  status: number;
+  response?: unknown;
+}
+
+export interface Cassette {
+  version: number;
+  interactions: Interaction[];
+}
+
+export interface WatchChange {
+  data: unknown;
+  previous: unknown | null;
+  changedPaths: string[];
+}
+"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_SECTION: &str = TYPESCRIPT;
@@ -0,0 +1,402 @@
+//! Canonical storefront URLs under the buyer's market and locale.
+//!
+//! Shopify serves other languages and markets from subfolders: nothing for
+//! the primary country in its primary language, `/fr` for another language
+//! in the primary country and `/fr-ca` for another country.
+//! `storefront_url(kind, handle)` builds the URL of the home page, a
+//! product, collection, page, blog, article, search or the cart under the
+//! prefix for the client's `@inContext` country and language:
+//!
+//! ```js
+//! await api.load_url_settings();
+//! api.with_context("CA", "FR").storefront_url("product", "tee", { variantId });
+//! // "https://shop.example.com/fr-ca/products/tee?variant=123"
+//! ```
+//!
+//! `load_url_settings()` reads the shop's primary domain, country and
+//! language once; `set_url_settings({ baseUrl, primaryCountry,
+//! primaryLanguage, rootUrl })` sets them without a request, and `rootUrl`
+//! overrides the derived prefix. Until either, URLs are unprefixed paths.
+//! The MCP tools link products this way once a base URL is known, and
+//! `hreflang` and the Liquid `routes` use the same prefixes.
+//!
+//! `build_cart_permalink(items, options)` builds a cart permalink,
+//! `/cart/{variant}:{quantity},...`, which opens checkout with those lines
+//! without a Cart API call: for email campaigns and "buy now" links.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::context::InContext;
+use crate::{from_js, from_json, to_js, StorefrontApi};
+
+/// Where storefront URLs point; see `urls`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UrlSettings {
+    /// Origin URLs start with, e.g. `https://shop.example.com`; paths
+    /// without it.
+    pub base_url: Option<String>,
+    /// The shop's `CountryCode` and `LanguageCode`, which need no prefix.
+    pub primary_country: Option<String>,
+    pub primary_language: Option<String>,
+    /// A fixed prefix, e.g. `/en-ca` for a market subfolder, instead of
+    /// the one derived from the context.
+    pub root_url: Option<String>,
+}
+
+/// What a URL is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UrlKind {
+    Home,
+    Product,
+    Collection,
+    Page,
+    Blog,
+    /// `handle` is `blog/article`.
+    Article,
+    /// `handle` is the search terms.
+    Search,
+    Cart,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UrlOptions {
+    /// For a product, selects this variant (a GID or its number).
+    pub variant_id: Option<String>,
+    /// For a product, the collection handle to link it within.
+    pub collection: Option<String>,
+}
+
+/// Turns a `LanguageCode` such as `PT_BR` into the `pt-BR` form hreflang
+/// expects.
+pub(crate) fn hreflang_language(language: &str) -> String {
+    match language.split_once('_') {
+        Some((language, region)) => format!("{}-{}", language.to_lowercase(), region),
+        None => language.to_lowercase(),
+    }
+}
+
+/// Shopify's subfolder prefix for a locale: none for the primary country in
+/// its primary language, `/fr` for another language in the primary country
+/// and `/fr-ca` elsewhere.
+pub(crate) fn locale_prefix(
+    country: &str,
+    language: &str,
+    primary_country: &str,
+    primary_language: &str,
+) -> String {
+    let language = hreflang_language(language).to_lowercase();
+    if country == primary_country {
+        if language == hreflang_language(primary_language).to_lowercase() {
+            String::new()
+        } else {
+            format!("/{language}")
+        }
+    } else {
+        format!("/{language}-{}", country.to_lowercase())
+    }
+}
+
+/// `path` under `root`, the root URL.
+pub fn localized_path(root: &str, path: &str) -> String {
+    match (root.trim_end_matches('/'), path) {
+        ("", _) => path.to_string(),
+        (root, "/") => root.to_string(),
+        (root, path) => format!("{root}{path}"),
+    }
+}
+
+/// The storefront path of a resource, without a locale prefix.
+pub fn resource_path(
+    kind: UrlKind,
+    handle: Option<&str>,
+    options: &UrlOptions,
+) -> Result<String, String> {
+    let handle = || {
+        handle
+            .map(str::trim)
+            .filter(|handle| !handle.is_empty())
+            .ok_or_else(|| format!("A {kind:?} URL needs a handle"))
+    };
+    Ok(match kind {
+        UrlKind::Home => "/".to_string(),
+        UrlKind::Cart => "/cart".to_string(),
+        UrlKind::Collection => format!("/collections/{}", handle()?),
+        UrlKind::Page => format!("/pages/{}", handle()?),
+        UrlKind::Blog => format!("/blogs/{}", handle()?),
+        UrlKind::Article => {
+            let handle = handle()?;
+            if !handle.contains('/') {
+                return Err(format!(
+                    "An article URL needs a blog/article handle, not {handle}"
+                ));
+            }
+            format!("/blogs/{handle}")
+        }
+        UrlKind::Search => format!(
+            "/search?q={}",
+            String::from(js_sys::encode_uri_component(handle()?))
+        ),
+        UrlKind::Product => {
+            let mut path = match &options.collection {
+                Some(collection) => format!("/collections/{collection}/products/{}", handle()?),
+                None => format!("/products/{}", handle()?),
+            };
+            if let Some(variant_id) = &options.variant_id {
+                let number = variant_id.rsplit('/').next().unwrap_or(variant_id);
+                path.push_str(&format!("?variant={number}"));
+            }
+            path
+        }
+    })
+}
+
+/// A line of a cart permalink. The same `{ variant_id, quantity }` objects
+/// the cart methods take are accepted.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PermalinkItem {
+    #[serde(alias = "variantId")]
+    pub variant_id: String,
+    pub quantity: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PermalinkOptions {
+    /// Origin the link starts with; a path without it.
+    pub base_url: Option<String>,
+    /// A discount code applied at checkout.
+    pub discount: Option<String>,
+    /// The order note.
+    pub note: Option<String>,
+    /// Cart attributes, saved on the order.
+    pub attributes: BTreeMap<String, String>,
+    /// The `ref` orders are attributed to, e.g. a campaign name.
+    #[serde(rename = "ref")]
+    pub reference: Option<String>,
+    /// A Storefront API access token, which attributes orders to its sales
+    /// channel.
+    pub access_token: Option<String>,
+}
+
+/// The cart permalink for `items`; see `urls`.
+pub fn cart_permalink(
+    items: &[PermalinkItem],
+    options: &PermalinkOptions,
+) -> Result<String, String> {
+    if items.is_empty() {
+        return Err("A cart permalink needs at least one item".to_string());
+    }
+    let mut lines = Vec::with_capacity(items.len());
+    for item in items {
+        let number = item
+            .variant_id
+            .trim()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        if number.is_empty() || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(format!("Invalid variant ID: {}", item.variant_id));
+        }
+        if item.quantity == 0 {
+            return Err(format!(
+                "The quantity of variant {number} must be at least 1"
+            ));
+        }
+        lines.push(format!("{number}:{}", item.quantity));
+    }
+
+    let encode = |text: &str| String::from(js_sys::encode_uri_component(text));
+    let mut parameters = Vec::new();
+    for (name, value) in [
+        ("discount", &options.discount),
+        ("note", &options.note),
+        ("ref", &options.reference),
+        ("access_token", &options.access_token),
+    ] {
+        if let Some(value) = value.as_deref().filter(|value| !value.is_empty()) {
+            parameters.push(format!("{name}={}", encode(value)));
+        }
+    }
+    for (name, value) in &options.attributes {
+        parameters.push(format!("attributes[{}]={}", encode(name), encode(value)));
+    }
+
+    let base_url = options.base_url.as_deref().unwrap_or_default();
+    let mut url = format!(
+        "{}/cart/{}",
+        base_url.trim_end_matches('/'),
+        lines.join(",")
+    );
+    if !parameters.is_empty() {
+        url.push('?');
+        url.push_str(&parameters.join("&"));
+    }
+    Ok(url)
+}
+
+/// Builds a cart permalink, `/cart/{variant}:{quantity},...`, for `items`
+/// (`[{ variant_id, quantity }]`), with `options` giving a `baseUrl`, a
+/// `discount` code, a `note`, cart `attributes` and the `ref` or
+/// `accessToken` orders are attributed to.
+#[wasm_bindgen]
+pub fn build_cart_permalink(
+    #[wasm_bindgen(unchecked_param_type = "PermalinkItem[]")] items: JsValue,
+    #[wasm_bindgen(unchecked_optional_param_type = "PermalinkOptions")] options: Option<JsValue>,
+) -> Result<String, JsValue> {
+    let items: Vec<PermalinkItem> = from_js(items)?;
+    let options: PermalinkOptions = match options {
+        Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+        _ => PermalinkOptions::default(),
+    };
+    cart_permalink(&items, &options).map_err(|error| JsValue::from_str(&error))
+}
+
+impl UrlSettings {
+    /// The prefix for `context`: `root_url` when set, otherwise the locale
+    /// prefix, which needs the primary country and language.
+    pub fn root(&self, context: &InContext) -> String {
+        if let Some(root) = &self.root_url {
+            return root.trim_end_matches('/').to_string();
+        }
+        let (Some(primary_country), Some(primary_language)) =
+            (&self.primary_country, &self.primary_language)
+        else {
+            return String::new();
+        };
+        locale_prefix(
+            context.country.as_deref().unwrap_or(primary_country),
+            context.language.as_deref().unwrap_or(primary_language),
+            primary_country,
+            primary_language,
+        )
+    }
+
+    /// `path` under the prefix for `context`, on the base URL if any.
+    pub fn url(&self, context: &InContext, path: &str) -> String {
+        let base_url = self.base_url.as_deref().unwrap_or_default();
+        format!(
+            "{}{}",
+            base_url.trim_end_matches('/'),
+            localized_path(&self.root(context), path)
+        )
+    }
+}
+
+impl StorefrontApi {
+    /// The absolute URL of a product for the MCP tools, when a base URL is
+    /// known.
+    #[cfg(feature = "mcp")]
+    pub(crate) fn product_url(&self, handle: &str) -> Option<String> {
+        let settings = self.urls.borrow();
+        settings.base_url.as_ref()?;
+        let path = resource_path(UrlKind::Product, Some(handle), &UrlOptions::default()).ok()?;
+        Some(settings.url(&self.context.borrow(), &path))
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Sets `{ baseUrl, primaryCountry, primaryLanguage, rootUrl }` for
+    /// `storefront_url`. Copies of the client share them.
+    #[wasm_bindgen]
+    pub fn set_url_settings(
+        &self,
+        #[wasm_bindgen(unchecked_optional_param_type = "UrlSettings")] settings: Option<JsValue>,
+    ) -> Result<(), JsValue> {
+        let mut settings: UrlSettings = match settings {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => UrlSettings::default(),
+        };
+        if let Some(root) = &settings.root_url {
+            if !root.starts_with('/') {
+                return Err(JsValue::from_str(&format!(
+                    "The root URL must be a path starting with '/': {root}"
+                )));
+            }
+        }
+        for code in [
+            &mut settings.primary_country,
+            &mut settings.primary_language,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *code = code.trim().replace('-', "_").to_uppercase();
+        }
+        *self.urls.borrow_mut() = settings;
+        Ok(())
+    }
+
+    /// Reads the shop's primary domain, country and language for
+    /// `storefront_url`, keeping a `baseUrl` or `rootUrl` already set.
+    #[wasm_bindgen(unchecked_return_type = "UrlSettings")]
+    pub async fn load_url_settings(&self) -> Result<JsValue, JsValue> {
+        // Ask without the client's @inContext so `country` and `language`
+        // are the shop's defaults rather than the current buyer's.
+        let mut client = self.clone();
+        client.context = Default::default();
+        let query = r#"
+            query getUrlSettings {
+                localization {
+                    country { isoCode }
+                    language { isoCode }
+                }
+                shop {
+                    primaryDomain { url }
+                }
+            }
+        "#;
+        let mut data = client.execute(query, Value::Null).await?;
+        let country: String = from_json(data["localization"]["country"]["isoCode"].take())?;
+        let language: String = from_json(data["localization"]["language"]["isoCode"].take())?;
+        let domain: Option<String> = from_json(data["shop"]["primaryDomain"]["url"].take())?;
+        let mut settings = self.urls.borrow_mut();
+        settings.primary_country = Some(country);
+        settings.primary_language = Some(language);
+        if settings.base_url.is_none() {
+            settings.base_url = domain;
+        }
+        to_js(&*settings)
+    }
+
+    /// The URL of a `home`, `product`, `collection`, `page`, `blog`,
+    /// `article` (`handle` is `blog/article`), `search` (`handle` is the
+    /// terms) or `cart` page for the client's country and language.
+    /// `options` may give a product's `variantId` and the `collection` to
+    /// link it within.
+    #[wasm_bindgen]
+    pub fn storefront_url(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "UrlKind")] kind: String,
+        handle: Option<String>,
+        #[wasm_bindgen(unchecked_optional_param_type = "UrlOptions")] options: Option<JsValue>,
+    ) -> Result<String, JsValue> {
+        let kind: UrlKind = serde_json::from_value(Value::String(kind.trim().to_lowercase()))
+            .map_err(|_| {
+                JsValue::from_str(&format!(
+                    "Invalid URL kind: {kind}. Expected home, product, collection, page, blog, \
+                     article, search or cart"
+                ))
+            })?;
+        let options: UrlOptions = match options {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => UrlOptions::default(),
+        };
+        let path =
+            resource_path(kind, handle.as_deref(), &options).map_err(|e| JsValue::from_str(&e))?;
+        Ok(self.urls.borrow().url(&self.context.borrow(), &path))
+    }
+
+    /// The settings `storefront_url` uses.
+    #[wasm_bindgen(unchecked_return_type = "UrlSettings")]
+    pub fn url_settings(&self) -> Result<JsValue, JsValue> {
+        to_js(&*self.urls.borrow())
+    }
+}
@@ -0,0 +1,207 @@
+//! Option/value availability matrix for variant pickers.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::types::{Image, Money, Product, ProductOptionValueSwatch, ProductVariant};
+use crate::{from_js, to_js};
+
+/// One value of an option, evaluated against the current selection of the
+/// other options.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionValueState {
+    pub name: String,
+    /// A variant exists with this value and the other selected values.
+    pub exists: bool,
+    /// One of those variants is available for sale.
+    pub available: bool,
+    pub selected: bool,
+    /// Lowest price among the matching variants.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<Money>,
+    /// Image of the first variant with this value, regardless of selection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swatch: Option<ProductOptionValueSwatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionAxis {
+    pub name: String,
+    pub values: Vec<OptionValueState>,
+}
+
+/// A variant reduced to its option values, in `OptionAxis` order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantCell {
+    pub id: String,
+    pub values: Vec<String>,
+    pub available: bool,
+    pub price: Money,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantMatrix {
+    pub options: Vec<OptionAxis>,
+    pub variants: Vec<VariantCell>,
+    /// The variant matching the selection, once every option is selected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_variant_id: Option<String>,
+}
+
+/// An option name and its values with their swatches, in display order.
+type Axis = (String, Vec<(String, Option<ProductOptionValueSwatch>)>);
+
+fn option_value<'a>(variant: &'a ProductVariant, option: &str) -> Option<&'a str> {
+    variant
+        .selected_options
+        .iter()
+        .find(|selected| selected.name == option)
+        .map(|selected| selected.value.as_str())
+}
+
+impl VariantMatrix {
+    /// Builds the matrix for a product fetched with its variants. `selection`
+    /// maps option names to the currently chosen values; options missing from
+    /// it don't constrain the other axes.
+    pub fn new(product: &Product, selection: &HashMap<String, String>) -> VariantMatrix {
+        let variants: Vec<&ProductVariant> = product
+            .variants
+            .as_ref()
+            .map(|variants| variants.nodes().collect())
+            .unwrap_or_default();
+
+        // Axis order comes from the product options, falling back to the
+        // first variant's selected options when `options` wasn't fetched.
+        let mut axes: Vec<Axis> = product
+            .options
+            .iter()
+            .map(|option| {
+                let values = option
+                    .option_values
+                    .iter()
+                    .map(|value| (value.name.clone(), value.swatch.clone()))
+                    .collect();
+                (option.name.clone(), values)
+            })
+            .collect();
+        if axes.is_empty() {
+            if let Some(first) = variants.first() {
+                axes = first
+                    .selected_options
+                    .iter()
+                    .map(|selected| (selected.name.clone(), Vec::new()))
+                    .collect();
+            }
+        }
+        for (name, values) in &mut axes {
+            for variant in &variants {
+                if let Some(value) = option_value(variant, name) {
+                    if !values.iter().any(|(known, _)| known == value) {
+                        values.push((value.to_string(), None));
+                    }
+                }
+            }
+        }
+
+        let matches_selection = |variant: &ProductVariant, skip: &str| {
+            axes.iter()
+                .filter(|(name, _)| name != skip)
+                .all(|(name, _)| match selection.get(name) {
+                    Some(chosen) => option_value(variant, name) == Some(chosen.as_str()),
+                    None => true,
+                })
+        };
+
+        let options = axes
+            .iter()
+            .map(|(name, values)| OptionAxis {
+                name: name.clone(),
+                values: values
+                    .iter()
+                    .map(|(value, swatch)| {
+                        let with_value: Vec<&ProductVariant> = variants
+                            .iter()
+                            .copied()
+                            .filter(|variant| option_value(variant, name) == Some(value.as_str()))
+                            .collect();
+                        let candidates: Vec<&ProductVariant> = with_value
+                            .iter()
+                            .copied()
+                            .filter(|variant| matches_selection(variant, name))
+                            .collect();
+
+                        OptionValueState {
+                            name: value.clone(),
+                            exists: !candidates.is_empty(),
+                            available: candidates.iter().any(|variant| variant.available_for_sale),
+                            selected: selection.get(name) == Some(value),
+                            min_price: candidates
+                                .iter()
+                                .map(|variant| &variant.price)
+                                .min_by(|a, b| a.value().total_cmp(&b.value()))
+                                .cloned(),
+                            image: with_value.iter().find_map(|variant| variant.image.clone()),
+                            swatch: swatch.clone(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let cells: Vec<VariantCell> = variants
+            .iter()
+            .map(|variant| VariantCell {
+                id: variant.id.clone(),
+                values: axes
+                    .iter()
+                    .map(|(name, _)| option_value(variant, name).unwrap_or_default().to_string())
+                    .collect(),
+                available: variant.available_for_sale,
+                price: variant.price.clone(),
+            })
+            .collect();
+
+        let fully_selected =
+            !axes.is_empty() && axes.iter().all(|(name, _)| selection.contains_key(name));
+        let selected_variant_id = fully_selected
+            .then(|| {
+                variants
+                    .iter()
+                    .find(|variant| matches_selection(variant, ""))
+                    .map(|variant| variant.id.clone())
+            })
+            .flatten();
+
+        VariantMatrix {
+            options,
+            variants: cells,
+            selected_variant_id,
+        }
+    }
+}
+
+/// Builds the option→value availability matrix for a product returned by
+/// `get_product`. `selected` is an optional `{ optionName: value }` object with
+/// the picker's current choices.
+#[wasm_bindgen(unchecked_return_type = "VariantMatrix")]
+pub fn variant_matrix(
+    #[wasm_bindgen(unchecked_param_type = "Product")] product: JsValue,
+    #[wasm_bindgen(unchecked_optional_param_type = "Record<string, string>")] selected: Option<
+        JsValue,
+    >,
+) -> Result<JsValue, JsValue> {
+    let product: Product = from_js(product)?;
+    let selection: HashMap<String, String> = match selected {
+        Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+        _ => HashMap::new(),
+    };
+    to_js(&VariantMatrix::new(&product, &selection))
+}
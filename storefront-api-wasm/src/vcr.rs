@@ -0,0 +1,206 @@
+//! Recorded responses ("cassettes") for tests that run offline.
+//!
+//! `record_responses` makes a client keep every response it gets from the
+//! Storefront API; `cassette` returns them as JSON to save as a fixture.
+//! `replay_cassette` then answers each operation from the cassette without
+//! a network request, so integration tests, and the MCP server's tests
+//! through a client passed to `McpServer`, are deterministic:
+//!
+//! ```js
+//! const api = new StorefrontApi(shop, token, "2024-01");
+//! api.replay_cassette(JSON.parse(await readFile("fixtures/product.json")));
+//! await api.get_product("tee"); // answered from the cassette
+//! ```
+//!
+//! Requests match recorded ones by operation text and variables. Repeated
+//! identical requests are answered in recorded order, then the last answer
+//! again. Secrets never reach the cassette: the access token, values of
+//! keys such as `customerAccessToken` and `password`, and email addresses
+//! are replaced with `[REDACTED]` in both variables and responses (see
+//! `redact`), and live requests are redacted the same way before matching.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::redact::redact;
+use crate::{from_js, to_js, GraphQLRequest, StorefrontApi};
+
+/// Cassette format version, bumped when old cassettes stop loading.
+pub const CASSETTE_VERSION: u32 = 1;
+
+/// One recorded request and its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Interaction {
+    pub operation: String,
+    pub query: String,
+    #[serde(default)]
+    pub variables: Value,
+    /// The HTTP status; `429` replays as throttling.
+    pub status: u16,
+    /// The GraphQL response body: `{ data, errors }`.
+    #[serde(default)]
+    pub response: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cassette {
+    pub version: u32,
+    pub interactions: Vec<Interaction>,
+}
+
+impl Default for Cassette {
+    fn default() -> Self {
+        Cassette {
+            version: CASSETTE_VERSION,
+            interactions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcrMode {
+    Record,
+    Replay,
+}
+
+/// A client's cassette and whether it records to it or replays from it.
+#[derive(Debug, Clone)]
+pub struct Vcr {
+    mode: VcrMode,
+    cassette: Cassette,
+    /// Which interactions have been replayed.
+    replayed: Vec<bool>,
+}
+
+impl Vcr {
+    pub fn recording() -> Vcr {
+        Vcr {
+            mode: VcrMode::Record,
+            cassette: Cassette::default(),
+            replayed: Vec::new(),
+        }
+    }
+
+    pub fn replaying(cassette: Cassette) -> Result<Vcr, String> {
+        if cassette.version != CASSETTE_VERSION {
+            return Err(format!(
+                "Cassette version {} is not supported; expected {CASSETTE_VERSION}",
+                cassette.version
+            ));
+        }
+        Ok(Vcr {
+            mode: VcrMode::Replay,
+            replayed: vec![false; cassette.interactions.len()],
+            cassette,
+        })
+    }
+
+    pub fn mode(&self) -> VcrMode {
+        self.mode
+    }
+
+    pub fn cassette(&self) -> &Cassette {
+        &self.cassette
+    }
+
+    /// The recorded status and body for `request` when replaying, or
+    /// `None` when recording. Fails when nothing recorded matches.
+    pub(crate) fn replay(
+        &mut self,
+        request: &GraphQLRequest,
+        operation: &str,
+        secret: &str,
+    ) -> Result<Option<(u16, Value)>, JsValue> {
+        if self.mode != VcrMode::Replay {
+            return Ok(None);
+        }
+        let mut variables = request.variables.clone().unwrap_or(Value::Null);
+        redact(&mut variables, &[secret]);
+        let matches: Vec<usize> = self
+            .cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| {
+                interaction.query == request.query && interaction.variables == variables
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let index = matches
+            .iter()
+            .copied()
+            .find(|&index| !self.replayed[index])
+            .or(matches.last().copied())
+            .ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "The cassette has no response for {operation} with these variables"
+                ))
+            })?;
+        self.replayed[index] = true;
+        let interaction = &self.cassette.interactions[index];
+        Ok(Some((interaction.status, interaction.response.clone())))
+    }
+
+    /// Adds a live response to the cassette when recording.
+    pub(crate) fn record(
+        &mut self,
+        request: &GraphQLRequest,
+        operation: &str,
+        (status, response): &(u16, Value),
+        secret: &str,
+    ) {
+        if self.mode != VcrMode::Record {
+            return;
+        }
+        let mut interaction = Interaction {
+            operation: operation.to_string(),
+            query: request.query.clone(),
+            variables: request.variables.clone().unwrap_or(Value::Null),
+            status: *status,
+            response: response.clone(),
+        };
+        redact(&mut interaction.variables, &[secret]);
+        redact(&mut interaction.response, &[secret]);
+        self.cassette.interactions.push(interaction);
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Starts recording every response this client (and its copies, such
+    /// as those from `with_signal`) receives, for `cassette`. Replaces any
+    /// cassette being recorded or replayed.
+    #[wasm_bindgen]
+    pub fn record_responses(&self) {
+        *self.vcr.borrow_mut() = Some(Vcr::recording());
+    }
+
+    /// Answers every request from a cassette saved from `cassette`, without
+    /// touching the network. Requests the cassette has no response for
+    /// fail.
+    #[wasm_bindgen]
+    pub fn replay_cassette(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Cassette")] cassette: JsValue,
+    ) -> Result<(), JsValue> {
+        let vcr = Vcr::replaying(from_js(cassette)?).map_err(|error| JsValue::from_str(&error))?;
+        *self.vcr.borrow_mut() = Some(vcr);
+        Ok(())
+    }
+
+    /// The cassette being recorded or replayed, as `{ version,
+    /// interactions }`, or `null`.
+    #[wasm_bindgen(unchecked_return_type = "Cassette | null")]
+    pub fn cassette(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.vcr.borrow().as_ref().map(Vcr::cassette))
+    }
+
+    /// Stops recording or replaying; requests go to the network again.
+    #[wasm_bindgen]
+    pub fn eject_cassette(&self) {
+        *self.vcr.borrow_mut() = None;
+    }
+}
@@ -0,0 +1,185 @@
+//! Polling a query and reporting what changed.
+//!
+//! `watch(query, variables, intervalMs, callback)` sends the query at once
+//! and then every `intervalMs`, comparing each result with the last one.
+//! `callback({ data, previous, changedPaths })` runs for the first result
+//! and afterwards only when the data differs, with the paths of the values
+//! that did (e.g. `"product.variants.nodes.0.availableForSale"`), so a page
+//! can flag a price change or a restock without polling code of its own.
+//! Polls always go to the network; a failed poll is skipped and tried
+//! again on the next tick.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::retry::sleep;
+use crate::{from_js, to_js, StorefrontApi};
+
+/// Shortest interval between polls, so a typo can't flood the API.
+pub const MIN_WATCH_INTERVAL_MS: u32 = 100;
+
+/// The dotted paths at which `new` differs from `old`, array items by
+/// index. A value that was added, removed or changed type is reported at
+/// its own path, not at each of its leaves.
+pub fn changed_paths(old: &Value, new: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    diff(old, new, &mut String::new(), &mut paths);
+    paths
+}
+
+fn diff(old: &Value, new: &Value, path: &mut String, paths: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let new_value = new.get(key).unwrap_or(&Value::Null);
+                descend(old_value, new_value, key, path, paths);
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    descend(&Value::Null, new_value, key, path, paths);
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let old_value = old.get(index).unwrap_or(&Value::Null);
+                let new_value = new.get(index).unwrap_or(&Value::Null);
+                descend(old_value, new_value, &index.to_string(), path, paths);
+            }
+        }
+        _ if old != new => paths.push(path.clone()),
+        _ => {}
+    }
+}
+
+/// Diffs the values at `path.segment`.
+fn descend(old: &Value, new: &Value, segment: &str, path: &mut String, paths: &mut Vec<String>) {
+    let length = path.len();
+    if !path.is_empty() {
+        path.push('.');
+    }
+    path.push_str(segment);
+    diff(old, new, path, paths);
+    path.truncate(length);
+}
+
+struct WatchState {
+    active: bool,
+    /// The last result reported, `None` before the first.
+    last: Option<Value>,
+}
+
+/// A running `watch`; `stop()` ends it.
+#[wasm_bindgen]
+pub struct QueryWatcher {
+    state: Rc<RefCell<WatchState>>,
+}
+
+#[wasm_bindgen]
+impl QueryWatcher {
+    /// Stops polling. A poll already in flight reports nothing.
+    #[wasm_bindgen]
+    pub fn stop(&self) {
+        self.state.borrow_mut().active = false;
+    }
+
+    /// Whether the watcher is still polling.
+    #[wasm_bindgen]
+    pub fn is_active(&self) -> bool {
+        self.state.borrow().active
+    }
+
+    /// The last result reported, or `null` before the first.
+    #[wasm_bindgen(unchecked_return_type = "unknown")]
+    pub fn current(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.state.borrow().last)
+    }
+}
+
+impl StorefrontApi {
+    /// Sends one poll and reports its result when it changed.
+    async fn poll(
+        &self,
+        query: &str,
+        variables: &Value,
+        state: &RefCell<WatchState>,
+        callback: &js_sys::Function,
+    ) {
+        let key = crate::canonical::request_key(query, variables);
+        let Ok(data) = self
+            .send_operation(query.to_string(), variables.clone())
+            .await
+        else {
+            return;
+        };
+        self.cache_response(&key, query, &data);
+        let update = {
+            let mut state = state.borrow_mut();
+            if !state.active {
+                return;
+            }
+            let previous = state.last.replace(data.clone());
+            let paths = match &previous {
+                Some(previous) => changed_paths(previous, &data),
+                None => Vec::new(),
+            };
+            if previous.is_some() && paths.is_empty() {
+                return;
+            }
+            serde_json::json!({
+                "data": data,
+                "previous": previous,
+                "changedPaths": paths,
+            })
+        };
+        if let Ok(update) = to_js(&update) {
+            let _ = callback.call1(&JsValue::NULL, &update);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StorefrontApi {
+    /// Polls `query` every `interval_ms` (at least 100) and calls
+    /// `callback({ data, previous, changedPaths })` with the first result
+    /// and then whenever the data changes. Polls don't overlap: the next
+    /// is sent `interval_ms` after the last one finished. Returns a
+    /// watcher whose `stop()` ends it.
+    #[wasm_bindgen]
+    pub fn watch(
+        &self,
+        query: String,
+        #[wasm_bindgen(unchecked_param_type = "Variables | null | undefined")] variables: Option<
+            JsValue,
+        >,
+        interval_ms: u32,
+        #[wasm_bindgen(unchecked_param_type = "(change: WatchChange) => void")]
+        callback: js_sys::Function,
+    ) -> Result<QueryWatcher, JsValue> {
+        let variables: Value = match variables {
+            Some(value) if !value.is_undefined() && !value.is_null() => from_js(value)?,
+            _ => Value::Null,
+        };
+        let query = self.context.borrow().apply(&query);
+        let interval = interval_ms.max(MIN_WATCH_INTERVAL_MS);
+        let state = Rc::new(RefCell::new(WatchState {
+            active: true,
+            last: None,
+        }));
+        let client = self.clone();
+        let polling = Rc::clone(&state);
+        spawn_local(async move {
+            while polling.borrow().active {
+                client.poll(&query, &variables, &polling, &callback).await;
+                if sleep(interval).await.is_err() {
+                    polling.borrow_mut().active = false;
+                }
+            }
+        });
+        Ok(QueryWatcher { state })
+    }
+}
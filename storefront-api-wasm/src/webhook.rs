@@ -0,0 +1,297 @@
+//! Verifying and reading Shopify webhooks, for server-side code (Node,
+//! Cloudflare Workers and other edge runtimes) that handles the webhooks
+//! complementing a storefront, such as restocks and order updates.
+//!
+//! Shopify signs each webhook with the app's secret: the
+//! `X-Shopify-Hmac-Sha256` header is the base64 HMAC-SHA256 of the raw
+//! request body. `verify_webhook(header, body, secret)` recomputes it and
+//! compares in constant time; `body` must be the bytes exactly as received,
+//! since re-serialized JSON won't match. `parse_webhook(topic, body)` then
+//! reads the payload of the common topics into typed, camelCase objects,
+//! with IDs as strings:
+//!
+//! ```js
+//! const body = new Uint8Array(await request.arrayBuffer());
+//! if (!verify_webhook(request.headers.get("X-Shopify-Hmac-Sha256"), body, env.SHOPIFY_SECRET)) {
+//!   return new Response("Unauthorized", { status: 401 });
+//! }
+//! const { payload } = parse_webhook(request.headers.get("X-Shopify-Topic"), body);
+//! if (payload.type === "inventoryLevel") restock(payload.inventoryItemId, payload.available);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::canonical::sha256;
+use crate::{from_js, to_js};
+
+/// The HMAC-SHA256 of `message` with `key` (RFC 2104).
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block_key = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block_key[..32].copy_from_slice(&sha256(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Vec::with_capacity(BLOCK + message.len());
+    inner.extend(block_key.iter().map(|byte| byte ^ 0x36));
+    inner.extend_from_slice(message);
+    let mut outer = Vec::with_capacity(BLOCK + 32);
+    outer.extend(block_key.iter().map(|byte| byte ^ 0x5c));
+    outer.extend_from_slice(&sha256(&inner));
+    sha256(&outer)
+}
+
+/// Standard, padded base64.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Compares without returning early, so the time taken doesn't tell how
+/// much of a forged signature was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Whether `hmac_header` is the signature of `body` with `secret`.
+pub fn verify_hmac(hmac_header: &str, body: &[u8], secret: &str) -> bool {
+    let expected = base64_encode(&hmac_sha256(secret.as_bytes(), body));
+    !secret.is_empty() && constant_time_eq(hmac_header.trim().as_bytes(), expected.as_bytes())
+}
+
+/// A REST resource ID. Read from the number Shopify sends and written as
+/// a string, since IDs such as `788032119674292922` are past the integers
+/// a JavaScript number holds exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct WebhookId(pub u64);
+
+impl Serialize for WebhookId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct WebhookLineItem {
+    pub id: WebhookId,
+    pub title: String,
+    pub quantity: u32,
+    pub variant_id: Option<WebhookId>,
+    pub product_id: Option<WebhookId>,
+    pub sku: Option<String>,
+    pub price: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct WebhookOrder {
+    pub id: WebhookId,
+    pub admin_graphql_api_id: Option<String>,
+    /// The order number shown to the customer, e.g. `#1001`.
+    pub name: String,
+    pub email: Option<String>,
+    pub created_at: Option<String>,
+    pub currency: String,
+    pub total_price: String,
+    pub subtotal_price: Option<String>,
+    pub financial_status: Option<String>,
+    pub fulfillment_status: Option<String>,
+    pub cancelled_at: Option<String>,
+    #[serde(default)]
+    pub line_items: Vec<WebhookLineItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct WebhookVariant {
+    pub id: WebhookId,
+    pub title: Option<String>,
+    pub sku: Option<String>,
+    pub price: String,
+    pub inventory_item_id: Option<WebhookId>,
+    pub inventory_quantity: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct WebhookProduct {
+    pub id: WebhookId,
+    pub admin_graphql_api_id: Option<String>,
+    pub title: String,
+    pub handle: String,
+    /// `active`, `draft` or `archived`.
+    pub status: Option<String>,
+    pub vendor: Option<String>,
+    pub product_type: Option<String>,
+    /// Comma-separated, as Shopify sends them.
+    pub tags: Option<String>,
+    #[serde(default)]
+    pub variants: Vec<WebhookVariant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct WebhookInventoryLevel {
+    pub inventory_item_id: WebhookId,
+    pub location_id: WebhookId,
+    /// `null` when the item isn't tracked.
+    pub available: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct WebhookCustomer {
+    pub id: WebhookId,
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    /// `enabled`, `disabled`, `invited` or `declined`.
+    pub state: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "snake_case"))]
+pub struct WebhookShop {
+    pub id: WebhookId,
+    pub name: String,
+    pub domain: Option<String>,
+    pub myshopify_domain: Option<String>,
+}
+
+/// A webhook payload, by the kind of resource its topic is about.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WebhookPayload {
+    /// `orders/create`, `orders/updated`, `orders/paid`,
+    /// `orders/cancelled`, `orders/fulfilled`.
+    Order(WebhookOrder),
+    /// `products/create`, `products/update`.
+    Product(WebhookProduct),
+    /// `products/delete`, `collections/delete` and the like, which only
+    /// carry the ID.
+    Deleted { id: WebhookId },
+    /// `inventory_levels/update`, `inventory_levels/connect`.
+    InventoryLevel(WebhookInventoryLevel),
+    /// `customers/create`, `customers/update`.
+    Customer(WebhookCustomer),
+    /// `app/uninstalled`, `shop/update`.
+    Shop(WebhookShop),
+    /// Any other topic, as sent.
+    Other { body: Value },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookEvent {
+    pub topic: String,
+    pub payload: WebhookPayload,
+}
+
+impl WebhookPayload {
+    /// Reads `body` as the payload of `topic` (the `X-Shopify-Topic`
+    /// header).
+    pub fn parse(topic: &str, body: Value) -> Result<WebhookPayload, String> {
+        fn read<T: serde::de::DeserializeOwned>(topic: &str, body: Value) -> Result<T, String> {
+            serde_json::from_value(body)
+                .map_err(|error| format!("Invalid {topic} webhook payload: {error}"))
+        }
+        let (resource, action) = topic.split_once('/').unwrap_or((topic, ""));
+        Ok(match (resource, action) {
+            (_, "delete") => WebhookPayload::Deleted {
+                id: read::<WebhookDeleted>(topic, body)?.id,
+            },
+            ("orders", _) => WebhookPayload::Order(read(topic, body)?),
+            ("products", _) => WebhookPayload::Product(read(topic, body)?),
+            ("inventory_levels", _) => WebhookPayload::InventoryLevel(read(topic, body)?),
+            ("customers", _) => WebhookPayload::Customer(read(topic, body)?),
+            ("app", "uninstalled") | ("shop", "update") => WebhookPayload::Shop(read(topic, body)?),
+            _ => WebhookPayload::Other { body },
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct WebhookDeleted {
+    id: WebhookId,
+}
+
+/// The bytes of a body passed from JavaScript: a string, a `Uint8Array`
+/// (a Node `Buffer` is one) or an `ArrayBuffer`.
+fn body_bytes(body: &JsValue) -> Result<Vec<u8>, JsValue> {
+    if let Some(text) = body.as_string() {
+        return Ok(text.into_bytes());
+    }
+    if let Some(bytes) = body.dyn_ref::<js_sys::Uint8Array>() {
+        return Ok(bytes.to_vec());
+    }
+    if let Some(buffer) = body.dyn_ref::<js_sys::ArrayBuffer>() {
+        return Ok(js_sys::Uint8Array::new(buffer).to_vec());
+    }
+    Err(JsValue::from_str(
+        "The webhook body must be the raw request body, as a string, Uint8Array or ArrayBuffer",
+    ))
+}
+
+/// Whether `hmac_header` (the `X-Shopify-Hmac-Sha256` header) is the
+/// signature of `body` with the app's `secret`. `body` is the raw request
+/// body as a string, `Uint8Array` or `ArrayBuffer`; parsed and re-encoded
+/// JSON won't verify. Returns `false` for a missing header.
+#[wasm_bindgen]
+pub fn verify_webhook(
+    hmac_header: Option<String>,
+    #[wasm_bindgen(unchecked_param_type = "string | Uint8Array | ArrayBuffer")] body: JsValue,
+    secret: &str,
+) -> Result<bool, JsValue> {
+    let Some(hmac_header) = hmac_header else {
+        return Ok(false);
+    };
+    Ok(verify_hmac(&hmac_header, &body_bytes(&body)?, secret))
+}
+
+/// Reads a webhook body (raw, or already parsed) as `{ topic, payload }`,
+/// where `payload.type` is `order`, `product`, `deleted`,
+/// `inventoryLevel`, `customer`, `shop` or, for other topics, `other` with
+/// the body as sent. Fields are camelCase. Verify the body first.
+#[wasm_bindgen(unchecked_return_type = "WebhookEvent")]
+pub fn parse_webhook(
+    topic: &str,
+    #[wasm_bindgen(unchecked_param_type = "string | Uint8Array | ArrayBuffer | object")]
+    body: JsValue,
+) -> Result<JsValue, JsValue> {
+    let body: Value = if body.is_object()
+        && !body.is_instance_of::<js_sys::Uint8Array>()
+        && !body.is_instance_of::<js_sys::ArrayBuffer>()
+    {
+        from_js(body)?
+    } else {
+        let bytes = body_bytes(&body)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|error| JsValue::from_str(&format!("The webhook body isn't JSON: {error}")))?
+    };
+    let payload = WebhookPayload::parse(topic, body).map_err(|error| JsValue::from_str(&error))?;
+    to_js(&WebhookEvent {
+        topic: topic.to_string(),
+        payload,
+    })
+}
@@ -0,0 +1,18 @@
+@echo off
+REM Test script for Storefront API WebAssembly module (Windows)
+
+echo Running native tests...
+cargo test || exit /b 1
+
+REM Check if wasm-pack is installed
+where wasm-pack >nul 2>&1
+if %ERRORLEVEL% NEQ 0 (
+    echo wasm-pack is not installed. Installing...
+    cargo install wasm-pack
+)
+
+REM Run the wasm-bindgen-test suites in headless Chrome
+echo Running wasm-bindgen-test suites...
+wasm-pack test --headless --chrome || exit /b 1
+
+echo Tests complete!
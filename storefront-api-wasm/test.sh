@@ -0,0 +1,22 @@
+#!/bin/bash
+
+# Test script for Storefront API WebAssembly module
+
+set -e
+
+echo "Running native tests..."
+cargo test
+
+# Check if wasm-pack is installed
+if ! command -v wasm-pack &> /dev/null; then
+    echo "wasm-pack is not installed. Installing..."
+    cargo install wasm-pack
+fi
+
+# Run the wasm-bindgen-test suites in a headless browser; choose it with
+# BROWSER, e.g. BROWSER=firefox ./test.sh
+BROWSER="${BROWSER:-chrome}"
+echo "Running wasm-bindgen-test suites in headless $BROWSER..."
+wasm-pack test --headless "--$BROWSER"
+
+echo "Tests complete!"
@@ -0,0 +1,107 @@
+#![cfg(all(target_arch = "wasm32", feature = "admin"))]
+
+mod common;
+
+use serde_json::json;
+use storefront_api_wasm::admin::AdminApi;
+use storefront_api_wasm::testing::{data, MockFetch};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::{error_field, json, ACCESS_TOKEN};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn admin() -> (AdminApi, MockFetch) {
+    let fetch = MockFetch::new();
+    let admin = AdminApi::new(
+        "test-shop.myshopify.com".to_string(),
+        ACCESS_TOKEN.to_string(),
+        "2024-07".to_string(),
+    )
+    .with_fetch(fetch.as_fetch());
+    (admin, fetch)
+}
+
+fn value(value: serde_json::Value) -> JsValue {
+    serde_wasm_bindgen::to_value(&value).unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn queries_go_to_the_admin_api_with_the_admin_token() {
+    let (admin, fetch) = admin();
+    fetch.respond("shopName", data(json!({ "shop": { "name": "Test shop" } })));
+
+    let result = admin
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(json(result)["shop"]["name"], "Test shop");
+    let request = &fetch.recorded()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(
+        request.url,
+        "https://test-shop.myshopify.com/admin/api/2024-07/graphql.json"
+    );
+    assert_eq!(request.headers["x-shopify-access-token"], ACCESS_TOKEN);
+    assert!(!request
+        .headers
+        .contains_key("x-shopify-storefront-access-token"));
+}
+
+#[wasm_bindgen_test]
+async fn inventory_adjustments_send_the_changes() {
+    let (admin, fetch) = admin();
+    fetch.respond(
+        "inventoryAdjustQuantities",
+        data(json!({
+            "inventoryAdjustQuantities": {
+                "inventoryAdjustmentGroup": { "reason": "correction", "changes": [] },
+                "userErrors": [],
+            }
+        })),
+    );
+    let changes = value(json!([{
+        "inventoryItemId": "gid://shopify/InventoryItem/1",
+        "locationId": "gid://shopify/Location/2",
+        "delta": -1,
+    }]));
+
+    let group = admin.adjust_inventory(changes, None).await.unwrap();
+
+    assert_eq!(json(group)["reason"], "correction");
+    let input = &fetch.recorded()[0].variables["input"];
+    assert_eq!(input["name"], "available");
+    assert_eq!(input["changes"][0]["delta"], -1);
+}
+
+#[wasm_bindgen_test]
+async fn metafield_user_errors_are_thrown() {
+    let (admin, fetch) = admin();
+    fetch.respond(
+        "metafieldsSet",
+        data(json!({
+            "metafieldsSet": {
+                "metafields": [],
+                "userErrors": [{
+                    "field": ["metafields", "0", "value"],
+                    "message": "Value must be an integer.",
+                    "code": "INVALID_VALUE",
+                }],
+            }
+        })),
+    );
+    let metafields = value(json!([{
+        "ownerId": "gid://shopify/Product/1",
+        "namespace": "custom",
+        "key": "weight",
+        "type": "number_integer",
+        "value": "heavy",
+    }]));
+
+    let error = admin.set_metafields(metafields).await.unwrap_err();
+
+    assert_eq!(error_field(&error, "kind"), "userError");
+    assert_eq!(error_field(&error, "code"), "INVALID_VALUE");
+}
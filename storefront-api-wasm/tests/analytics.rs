@@ -0,0 +1,113 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde_json::json;
+use storefront_api_wasm::testing::MockFetch;
+use storefront_api_wasm::StorefrontApi;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::client;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const COLLECTOR: &str = "https://collector.example.com/events";
+
+fn value(value: serde_json::Value) -> JsValue {
+    serde_wasm_bindgen::to_value(&value).unwrap()
+}
+
+fn accept(fetch: &MockFetch) {
+    fetch
+        .respond_with(None, value(json!({})), None, None)
+        .unwrap();
+}
+
+fn tracked_client() -> (StorefrontApi, MockFetch) {
+    let (api, fetch) = client();
+    api.set_analytics(Some(value(json!({
+        "shopId": "gid://shopify/Shop/42",
+        "currency": "CAD",
+        "collector": COLLECTOR,
+    }))))
+    .unwrap();
+    api.set_tracking_tokens(Some("unique".to_string()), Some("visit".to_string()));
+    (api, fetch)
+}
+
+#[wasm_bindgen_test]
+async fn events_go_to_shopify_and_the_collector() {
+    let (api, fetch) = tracked_client();
+    accept(&fetch);
+    accept(&fetch);
+
+    let payload = value(json!({ "productId": "gid://shopify/Product/1", "price": "10.00" }));
+    let sent = api
+        .track("product_viewed".to_string(), Some(payload))
+        .await
+        .unwrap();
+
+    assert!(sent);
+    let requests = fetch.recorded();
+    assert_eq!(
+        requests[0].url,
+        "https://test-shop.myshopify.com/.well-known/shopify/monorail/unstable/produce_batch"
+    );
+    let shopify = &requests[0].body["events"][0];
+    assert_eq!(
+        shopify["schema_id"],
+        "custom_storefront_customer_tracking/1.2"
+    );
+    assert_eq!(shopify["payload"]["event_name"], "product_page_rendered");
+    assert_eq!(shopify["payload"]["shop_id"], "42");
+    assert_eq!(shopify["payload"]["product_id"], "gid://shopify/Product/1");
+    assert_eq!(shopify["payload"]["unique_token"], "unique");
+
+    assert_eq!(requests[1].url, COLLECTOR);
+    let event = &requests[1].body["events"][0];
+    assert_eq!(event["name"], "product_viewed");
+    assert_eq!(event["visitToken"], "visit");
+    assert_eq!(event["payload"]["price"], "10.00");
+}
+
+#[wasm_bindgen_test]
+async fn nothing_is_sent_without_consent() {
+    let (api, fetch) = tracked_client();
+    api.require_consent(true);
+
+    let sent = api.track("page_viewed".to_string(), None).await.unwrap();
+
+    assert!(!sent);
+    assert!(fetch.recorded().is_empty());
+
+    api.set_consent(value(json!({ "analyticsAllowed": true })))
+        .unwrap();
+    accept(&fetch);
+    accept(&fetch);
+    assert!(api.track("page_viewed".to_string(), None).await.unwrap());
+    assert_eq!(
+        fetch.recorded()[0].body["events"][0]["schema_id"],
+        "trekkie_storefront_page_view/1.4"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn failures_are_not_thrown() {
+    let (api, fetch) = tracked_client();
+    fetch.fail_next(None, "Failed to fetch".to_string());
+    fetch
+        .respond_with(None, value(json!({})), Some(500), None)
+        .unwrap();
+
+    let sent = api
+        .track(
+            "search_submitted".to_string(),
+            Some(value(json!({ "searchTerm": "tee" }))),
+        )
+        .await
+        .unwrap();
+
+    assert!(!sent);
+    assert!(api.track("checkout".to_string(), None).await.is_err());
+}
@@ -0,0 +1,247 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::testing::data;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const QUERY: &str = "query shopName { shop { name } }";
+
+fn shop(name: &str) -> Value {
+    data(json!({ "shop": { "name": name } }))
+}
+
+fn swr(api: &storefront_api_wasm::StorefrontApi) {
+    let options = serde_wasm_bindgen::to_value(&json!({ "mode": "staleWhileRevalidate" })).unwrap();
+    api.set_cache(Some(options)).unwrap();
+}
+
+/// Waits for background work started by earlier calls.
+async fn settle() {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let set_timeout: js_sys::Function =
+            js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+                .unwrap()
+                .unchecked_into();
+        set_timeout
+            .call2(&JsValue::NULL, &resolve, &JsValue::from(50))
+            .unwrap();
+    });
+    JsFuture::from(promise).await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn stale_responses_are_served_then_revalidated() {
+    let (api, fetch) = client();
+    swr(&api);
+    let updates = Rc::new(RefCell::new(Vec::new()));
+    let seen = Rc::clone(&updates);
+    let callback =
+        Closure::<dyn Fn(JsValue)>::new(move |update| seen.borrow_mut().push(json(update)));
+    api.on_revalidate(Some(
+        callback
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone(),
+    ));
+    fetch.respond("shopName", shop("Old"));
+    fetch.respond("shopName", shop("New"));
+
+    let first = json(api.query(QUERY.to_string(), None).await.unwrap());
+    let second = json(api.query(QUERY.to_string(), None).await.unwrap());
+    settle().await;
+
+    assert_eq!(first["shop"]["name"], "Old");
+    assert_eq!(second["shop"]["name"], "Old");
+    assert_eq!(fetch.recorded().len(), 2);
+    assert_eq!(updates.borrow().len(), 1);
+    assert_eq!(updates.borrow()[0]["data"]["shop"]["name"], "New");
+    let third = json(api.query(QUERY.to_string(), None).await.unwrap());
+    assert_eq!(third["shop"]["name"], "New");
+}
+
+#[wasm_bindgen_test]
+async fn cart_mutations_drop_the_cached_cart() {
+    let (api, fetch) = client();
+    let options = serde_wasm_bindgen::to_value(
+        &json!({ "mode": "staleWhileRevalidate", "maxAgeMs": 60_000 }),
+    )
+    .unwrap();
+    api.set_cache(Some(options)).unwrap();
+    let get_cart = "query getCart($id: ID!) { cart(id: $id) { totalQuantity } }";
+    let add = "mutation addLine($cartId: ID!) { cartLinesAdd(cartId: $cartId, lines: []) { cart { id } } }";
+    let cart = "gid://shopify/Cart/1";
+    let variables = |name: &str| serde_wasm_bindgen::to_value(&json!({ name: cart })).unwrap();
+    fetch.respond("shopName", shop("Shop"));
+    fetch.respond("getCart", data(json!({ "cart": { "totalQuantity": 1 } })));
+    fetch.respond(
+        "addLine",
+        data(json!({ "cartLinesAdd": { "cart": { "id": cart } } })),
+    );
+    fetch.respond("getCart", data(json!({ "cart": { "totalQuantity": 2 } })));
+
+    api.query(QUERY.to_string(), None).await.unwrap();
+    api.query(get_cart.to_string(), Some(variables("id")))
+        .await
+        .unwrap();
+    api.query(add.to_string(), Some(variables("cartId")))
+        .await
+        .unwrap();
+    let after = json(
+        api.query(get_cart.to_string(), Some(variables("id")))
+            .await
+            .unwrap(),
+    );
+    api.query(QUERY.to_string(), None).await.unwrap();
+
+    assert_eq!(after["cart"]["totalQuantity"], 2);
+    assert_eq!(fetch.recorded().len(), 4);
+}
+
+#[wasm_bindgen_test]
+async fn mutations_are_not_cached() {
+    let (api, fetch) = client();
+    swr(&api);
+    let mutation = "mutation touch { cartCreate { cart { id } } }";
+    fetch.respond("touch", data(json!({ "cartCreate": null })));
+    fetch.respond("touch", data(json!({ "cartCreate": null })));
+
+    api.query(mutation.to_string(), None).await.unwrap();
+    api.query(mutation.to_string(), None).await.unwrap();
+
+    assert_eq!(fetch.recorded().len(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn mutations_after_fragments_and_comments_are_not_cached() {
+    let (api, fetch) = client();
+    swr(&api);
+    let mutation = "# create a cart\n\
+        fragment CartFields on Cart { id }\n\
+        mutation touch { cartCreate { cart { ...CartFields } } }";
+    let response = serde_wasm_bindgen::to_value(&data(json!({ "cartCreate": null }))).unwrap();
+    fetch
+        .respond_with(None, response.clone(), None, None)
+        .unwrap();
+    fetch.respond_with(None, response, None, None).unwrap();
+
+    api.query(mutation.to_string(), None).await.unwrap();
+    api.query(mutation.to_string(), None).await.unwrap();
+    settle().await;
+
+    assert_eq!(fetch.recorded().len(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn get_requests_are_revalidated_with_validators() {
+    let (api, fetch) = client();
+    api.set_transport("get").unwrap();
+    let options = serde_wasm_bindgen::to_value(&json!({ "mode": "revalidate" })).unwrap();
+    api.set_cache(Some(options)).unwrap();
+    fetch.respond_headers("shopName", 200, shop("Shop"), &[("ETag", "\"v1\"")]);
+    fetch.respond_headers("shopName", 304, Value::Null, &[("ETag", "\"v1\"")]);
+
+    let first = json(api.query(QUERY.to_string(), None).await.unwrap());
+    let second = json(api.query(QUERY.to_string(), None).await.unwrap());
+
+    assert_eq!(first["shop"]["name"], "Shop");
+    assert_eq!(second["shop"]["name"], "Shop");
+    let requests = fetch.recorded();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].query, QUERY);
+    assert!(!requests[0].headers.contains_key("if-none-match"));
+    assert_eq!(requests[1].headers["if-none-match"], "\"v1\"");
+}
+
+#[wasm_bindgen_test]
+async fn mutations_are_posted_with_the_get_transport() {
+    let (api, fetch) = client();
+    api.set_transport("get").unwrap();
+    let mutation = "mutation touch { cartCreate { cart { id } } }";
+    fetch.respond("touch", data(json!({ "cartCreate": null })));
+
+    api.query(mutation.to_string(), None).await.unwrap();
+
+    assert_eq!(fetch.recorded()[0].method, "POST");
+}
+
+#[wasm_bindgen_test]
+async fn mutations_after_fragments_are_posted_with_the_get_transport() {
+    let (api, fetch) = client();
+    api.set_transport("get").unwrap();
+    let mutation = "fragment CartFields on Cart { id }\n\
+        mutation touch { cartCreate { cart { ...CartFields } } }";
+    let response = serde_wasm_bindgen::to_value(&data(json!({ "cartCreate": null }))).unwrap();
+    fetch.respond_with(None, response, None, None).unwrap();
+
+    api.query(mutation.to_string(), None).await.unwrap();
+
+    assert_eq!(fetch.recorded()[0].method, "POST");
+}
+
+#[wasm_bindgen_test]
+async fn requests_written_differently_share_a_cache_entry() {
+    let (api, fetch) = client();
+    let options =
+        serde_wasm_bindgen::to_value(&json!({ "mode": "staleWhileRevalidate", "maxAgeMs": 60000 }))
+            .unwrap();
+    api.set_cache(Some(options)).unwrap();
+    let query =
+        "query product($handle: String!, $first: Int) { product(handle: $handle) { title } }";
+    let reformatted =
+        "query product(\n  $handle: String!\n  $first: Int\n) {\n  # the title only\n  product(handle: $handle) {\n    title\n  }\n}";
+    fetch.respond("product", data(json!({ "product": { "title": "Tee" } })));
+
+    let variables = |value: Value| Some(serde_wasm_bindgen::to_value(&value).unwrap());
+    api.query(
+        query.to_string(),
+        variables(json!({ "handle": "tee", "first": 1 })),
+    )
+    .await
+    .unwrap();
+    let again = json(
+        api.query(
+            reformatted.to_string(),
+            variables(json!({ "first": 1.0, "handle": "tee" })),
+        )
+        .await
+        .unwrap(),
+    );
+
+    assert_eq!(again["product"]["title"], "Tee");
+    assert_eq!(fetch.recorded().len(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn caches_are_trimmed_to_a_target_size() {
+    let (api, fetch) = client();
+    swr(&api);
+    for name in ["a", "b", "c"] {
+        fetch.respond(&format!("shop{name}"), shop(name));
+        api.query(format!("query shop{name} {{ shop {{ name }} }}"), None)
+            .await
+            .unwrap();
+    }
+
+    let stats = json(api.memory_stats().unwrap());
+    assert_eq!(stats["responseCache"]["entries"], 3);
+    assert!(stats["heapBytes"].as_f64().unwrap() > 0.0);
+    let one = stats["cacheBytes"].as_u64().unwrap() / 3;
+
+    let trimmed = json(api.trim_caches(one as f64 + 1.0).unwrap());
+
+    assert_eq!(trimmed["responseCache"]["entries"], 1);
+    assert!(trimmed["cacheBytes"].as_u64().unwrap() <= one + 1);
+    assert_eq!(json(api.trim_caches(0.0).unwrap())["cacheBytes"], 0);
+}
@@ -0,0 +1,139 @@
+#![cfg(all(target_arch = "wasm32", feature = "cart"))]
+
+mod common;
+
+use serde_json::json;
+use storefront_api_wasm::testing::{cart, data};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::{client, error_field, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const CART_ID: &str = "gid://shopify/Cart/1";
+const VARIANT_ID: &str = "gid://shopify/ProductVariant/1";
+const LINE_ID: &str = "gid://shopify/CartLine/1";
+
+fn items(quantity: u32) -> JsValue {
+    serde_wasm_bindgen::to_value(&json!([{ "variant_id": VARIANT_ID, "quantity": quantity }]))
+        .unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn create_cart_sends_its_lines() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "createCart",
+        data(json!({
+            "cartCreate": {
+                "cart": cart(CART_ID, &[(LINE_ID, VARIANT_ID, 2)]),
+                "userErrors": [],
+            },
+        })),
+    );
+
+    let result = json(api.create_cart(items(2)).await.unwrap());
+
+    assert_eq!(result["id"], json!(CART_ID));
+    assert_eq!(result["totalQuantity"], json!(2));
+    assert_eq!(
+        fetch.recorded()[0].variables,
+        json!({ "lines": [{ "merchandiseId": VARIANT_ID, "quantity": 2 }] })
+    );
+}
+
+#[wasm_bindgen_test]
+async fn create_cart_rejects_malformed_items() {
+    let (api, fetch) = client();
+    let items = serde_wasm_bindgen::to_value(&json!([{ "quantity": 1 }])).unwrap();
+
+    assert!(api.create_cart(items).await.is_err());
+    assert!(fetch.recorded().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn add_cart_lines_returns_the_cart() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "addCartLines",
+        data(json!({
+            "cartLinesAdd": {
+                "cart": cart(CART_ID, &[(LINE_ID, VARIANT_ID, 3)]),
+                "userErrors": [],
+            },
+        })),
+    );
+
+    let result = json(
+        api.add_cart_lines(CART_ID.to_string(), items(3))
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(result["id"], json!(CART_ID));
+    assert_eq!(result["totalQuantity"], json!(3));
+    assert_eq!(fetch.recorded()[0].variables["cartId"], json!(CART_ID));
+}
+
+#[wasm_bindgen_test]
+async fn user_errors_reject() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "addCartLines",
+        data(json!({
+            "cartLinesAdd": {
+                "cart": null,
+                "userErrors": [{
+                    "field": ["lines", "0", "merchandiseId"],
+                    "message": "The merchandise with id 1 does not exist.",
+                    "code": "INVALID",
+                }],
+            },
+        })),
+    );
+
+    let error = api
+        .add_cart_lines(CART_ID.to_string(), items(1))
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_field(&error, "kind"), JsValue::from_str("userError"));
+    assert_eq!(error_field(&error, "retryable"), JsValue::FALSE);
+}
+
+#[wasm_bindgen_test]
+async fn update_cart_line_sets_the_quantity() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "updateCartLine",
+        data(json!({
+            "cartLinesUpdate": {
+                "cart": cart(CART_ID, &[]),
+                "userErrors": [],
+            },
+        })),
+    );
+
+    let result = json(
+        api.update_cart_line(CART_ID.to_string(), LINE_ID.to_string(), 0)
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(result["totalQuantity"], json!(0));
+    assert_eq!(
+        fetch.recorded()[0].variables["lines"],
+        json!([{ "id": LINE_ID, "quantity": 0 }])
+    );
+}
+
+#[wasm_bindgen_test]
+async fn missing_carts_are_null() {
+    let (api, fetch) = client();
+    fetch.respond("getCart", data(json!({ "cart": null })));
+
+    let result = api.get_cart(CART_ID.to_string()).await.unwrap();
+
+    assert!(result.is_null());
+}
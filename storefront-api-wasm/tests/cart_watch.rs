@@ -0,0 +1,178 @@
+#![cfg(all(target_arch = "wasm32", feature = "cart"))]
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::cart_watch::cart_changes;
+use storefront_api_wasm::testing::{cart, data};
+use storefront_api_wasm::types::Cart;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const CART_ID: &str = "gid://shopify/Cart/1";
+const TEE: &str = "gid://shopify/ProductVariant/1";
+const HAT: &str = "gid://shopify/ProductVariant/2";
+
+fn typed(cart: Value) -> Cart {
+    serde_json::from_value(cart).unwrap()
+}
+
+async fn wait(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let set_timeout: js_sys::Function =
+            js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+                .unwrap()
+                .unchecked_into();
+        set_timeout
+            .call2(&JsValue::NULL, &resolve, &JsValue::from(ms))
+            .unwrap();
+    });
+    JsFuture::from(promise).await.unwrap();
+}
+
+/// The `(changes, cart)` a callback was called with.
+type Updates = Rc<RefCell<Vec<(Value, Value)>>>;
+
+fn recorder() -> (Updates, Closure<dyn Fn(JsValue, JsValue)>) {
+    let updates = Rc::new(RefCell::new(Vec::new()));
+    let seen = Rc::clone(&updates);
+    let callback = Closure::<dyn Fn(JsValue, JsValue)>::new(move |changes, cart| {
+        seen.borrow_mut().push((json(changes), json(cart)))
+    });
+    (updates, callback)
+}
+
+fn function(callback: &Closure<dyn Fn(JsValue, JsValue)>) -> js_sys::Function {
+    callback
+        .as_ref()
+        .unchecked_ref::<js_sys::Function>()
+        .clone()
+}
+
+#[wasm_bindgen_test]
+fn cart_changes_cover_lines_prices_discounts_and_totals() {
+    let mut old = cart(CART_ID, &[("line-1", TEE, 1), ("line-2", HAT, 1)]);
+    old["discountCodes"] = json!([{ "code": "SPRING", "applicable": true }]);
+    let mut new = cart(CART_ID, &[("line-1", TEE, 2), ("line-3", HAT, 1)]);
+    new["lines"]["edges"][0]["node"]["merchandise"]["price"]["amount"] = json!("12.0");
+    new["discountCodes"] = json!([{ "code": "SPRING", "applicable": false }]);
+
+    let changes = serde_json::to_value(cart_changes(&typed(old), &typed(new))).unwrap();
+    let types: Vec<&str> = changes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| change["type"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(
+        types,
+        [
+            "quantityChanged",
+            "priceChanged",
+            "lineAdded",
+            "lineRemoved",
+            "discountExpired",
+            "totalChanged",
+        ]
+    );
+    assert_eq!(
+        changes[0],
+        json!({
+            "type": "quantityChanged",
+            "lineId": "line-1",
+            "variantId": TEE,
+            "previous": 1,
+            "quantity": 2,
+        })
+    );
+    assert_eq!(changes[1]["price"]["amount"], "12.0");
+    assert_eq!(changes[2]["line"]["id"], "line-3");
+    assert_eq!(changes[4]["code"], "SPRING");
+}
+
+#[wasm_bindgen_test]
+async fn changes_made_elsewhere_are_reported() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "watchCart",
+        data(json!({ "cart": cart(CART_ID, &[("line-1", TEE, 1)]) })),
+    );
+    fetch.respond(
+        "watchCart",
+        data(json!({ "cart": cart(CART_ID, &[("line-1", TEE, 1), ("line-2", HAT, 1)]) })),
+    );
+    let (updates, callback) = recorder();
+
+    let watcher = api
+        .watch_cart(CART_ID.to_string(), 100, function(&callback), None)
+        .unwrap();
+    wait(150).await;
+    watcher.stop();
+
+    assert_eq!(fetch.recorded()[0].variables["id"], CART_ID);
+    let updates = updates.borrow();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].0[0]["type"], "lineAdded");
+    assert_eq!(updates[0].0[0]["line"]["merchandise"]["id"], HAT);
+    assert_eq!(updates[0].1["totalQuantity"], 2);
+}
+
+#[wasm_bindgen_test]
+async fn updated_snapshots_are_not_reported() {
+    let (api, fetch) = client();
+    let snapshot = cart(CART_ID, &[("line-1", TEE, 1)]);
+    let updated = cart(CART_ID, &[("line-1", TEE, 3)]);
+    fetch.respond("watchCart", data(json!({ "cart": updated })));
+    let (updates, callback) = recorder();
+
+    let watcher = api
+        .watch_cart(
+            CART_ID.to_string(),
+            100,
+            function(&callback),
+            Some(serde_wasm_bindgen::to_value(&snapshot).unwrap()),
+        )
+        .unwrap();
+    watcher
+        .update(serde_wasm_bindgen::to_value(&updated).unwrap())
+        .unwrap();
+    wait(50).await;
+    watcher.stop();
+
+    assert!(updates.borrow().is_empty());
+    assert_eq!(json(watcher.current().unwrap())["totalQuantity"], 3);
+}
+
+#[wasm_bindgen_test]
+async fn watchers_stop_when_the_cart_is_gone() {
+    let (api, fetch) = client();
+    fetch.respond("watchCart", data(json!({ "cart": null })));
+    let (updates, callback) = recorder();
+    let snapshot = cart(CART_ID, &[("line-1", TEE, 1)]);
+
+    let watcher = api
+        .watch_cart(
+            CART_ID.to_string(),
+            100,
+            function(&callback),
+            Some(serde_wasm_bindgen::to_value(&snapshot).unwrap()),
+        )
+        .unwrap();
+    wait(150).await;
+
+    assert_eq!(fetch.recorded().len(), 1);
+    assert_eq!(
+        *updates.borrow(),
+        [(json!([{ "type": "cartGone" }]), Value::Null)]
+    );
+    assert!(!watcher.is_active());
+}
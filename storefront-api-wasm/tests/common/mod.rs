@@ -0,0 +1,30 @@
+//! Shared setup for the `wasm-bindgen-test` suites.
+#![allow(dead_code)]
+
+use serde_json::Value;
+use storefront_api_wasm::testing::MockFetch;
+use storefront_api_wasm::StorefrontApi;
+use wasm_bindgen::JsValue;
+
+pub const ACCESS_TOKEN: &str = "test-token";
+
+/// A client for a test shop and the mock its requests go to.
+pub fn client() -> (StorefrontApi, MockFetch) {
+    let mut fetch = MockFetch::new();
+    fetch.install().expect("install the mock fetch");
+    let api = StorefrontApi::new(
+        "test-shop.myshopify.com".to_string(),
+        ACCESS_TOKEN.to_string(),
+        "2024-01".to_string(),
+    );
+    (api, fetch)
+}
+
+pub fn json(value: JsValue) -> Value {
+    serde_wasm_bindgen::from_value(value).expect("a JSON value")
+}
+
+/// A property of a thrown error, such as `kind` or `message`.
+pub fn error_field(error: &JsValue, name: &str) -> JsValue {
+    js_sys::Reflect::get(error, &JsValue::from_str(name)).expect("an error object")
+}
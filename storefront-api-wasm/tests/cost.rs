@@ -0,0 +1,111 @@
+#![cfg(all(target_arch = "wasm32", feature = "cart", feature = "content"))]
+
+mod common;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::cost::{estimate_query_cost, CostLimits};
+use storefront_api_wasm::testing::{data, product};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::client;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn value(value: Value) -> JsValue {
+    serde_wasm_bindgen::to_value(&value).unwrap()
+}
+
+/// Calls the built-in queries with little or nothing queued, so each sends
+/// its requests until one fails, then prices everything that was sent.
+#[wasm_bindgen_test]
+async fn built_in_queries_fit_under_the_default_cost_limit() {
+    let (api, fetch) = client();
+    let mut candidate = product("tee", "10.0");
+    candidate["variants"]["edges"][0]["node"]["sku"] = json!("TEE-1");
+    fetch.respond(
+        "findVariant",
+        data(json!({ "products": { "edges": [{ "node": candidate }] } })),
+    );
+    fetch.respond(
+        "getProductBundle",
+        data(json!({ "product": product("kit", "30.0") })),
+    );
+
+    let tee = || "tee".to_string();
+    let product_id = || "gid://shopify/Product/1".to_string();
+    let _ = api.get_product(tee(), None).await;
+    let _ = api
+        .get_product(tee(), Some(value(json!({ "media": true }))))
+        .await;
+    let _ = api.get_product_by_id(product_id(), None).await;
+    let _ = api
+        .get_products_by_handles(vec![tee(), "hat".to_string()], None)
+        .await;
+    let _ = api.get_product_media(tee(), None).await;
+    let _ = api.get_product_recommendations(product_id(), None).await;
+    let _ = api.get_product_by_sku("TEE-1".to_string()).await;
+    let _ = api.get_variant_by_barcode("0123456789".to_string()).await;
+    let _ = api
+        .get_store_availability("gid://shopify/ProductVariant/1".to_string(), None)
+        .await;
+    let _ = api.get_product_bundle("kit".to_string()).await;
+    let _ = api.get_nodes(vec![product_id()]).await;
+    let _ = api.get_collection(tee(), None, None).await;
+    let _ = api.get_all_collection_products(tee(), None).await;
+    let _ = api.list_collections(None).await;
+    let _ = api.search_products(JsValue::from_str("shirt"), None).await;
+    let _ = api.predictive_search("shi".to_string(), None).await;
+    let _ = api.search("shirt".to_string(), None).await;
+    let _ = api.get_cart("gid://shopify/Cart/1".to_string()).await;
+    let _ = api.get_shop().await;
+    let _ = api.get_brand().await;
+    let _ = api.get_localization().await;
+    let _ = api.get_menu("main-menu".to_string()).await;
+    let _ = api.get_blog("news".to_string()).await;
+    let _ = api.list_articles("news".to_string(), None).await;
+    let _ = api
+        .get_article("news".to_string(), "hello".to_string())
+        .await;
+    let _ = api.get_page("about".to_string(), None).await;
+    let _ = api.list_pages(None).await;
+    let _ = api
+        .get_metaobject("hero".to_string(), "home".to_string())
+        .await;
+    let _ = api.list_metaobjects("hero".to_string(), None).await;
+    let _ = api.get_sections(vec!["hero".to_string()], None).await;
+    let _ = api.get_installments_pricing(tee()).await;
+    let _ = api
+        .get_prices_in_markets(tee(), vec!["DE".to_string()])
+        .await;
+    let _ = api
+        .get_hreflang_alternates("product".to_string(), tee(), None)
+        .await;
+    let _ = api
+        .get_translations("product".to_string(), tee(), vec!["FR".to_string()])
+        .await;
+    let _ = api.resolve_route("/products/tee".to_string()).await;
+    let _ = api.build_sitemap_entries(None).await;
+    let _ = api.list_product_types().await;
+    let _ = api.list_product_tags(None).await;
+
+    let requests = fetch.recorded();
+    let operations: Vec<_> = requests
+        .iter()
+        .map(|request| request.operation.clone())
+        .collect();
+    assert!(operations.contains(&Some("getProductsByHandles".to_string())));
+    assert!(operations.contains(&Some("getVariantDetails".to_string())));
+    let limit = CostLimits::default().max_query_cost;
+    for request in requests {
+        let cost = estimate_query_cost(&request.query, &request.variables);
+        assert!(
+            cost <= limit,
+            "{} costs {cost}",
+            request
+                .operation
+                .as_deref()
+                .unwrap_or("an anonymous operation")
+        );
+    }
+}
@@ -0,0 +1,147 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::installments::installments_message;
+use storefront_api_wasm::testing::data;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn value(value: Value) -> JsValue {
+    serde_wasm_bindgen::to_value(&value).unwrap()
+}
+
+fn money(amount: &str) -> Value {
+    json!({ "amount": amount, "currencyCode": "USD" })
+}
+
+fn shop_pricing() -> Value {
+    json!({
+        "minPrice": money("50.0"),
+        "maxPrice": money("17500.0"),
+        "financingPlans": [{
+            "id": "plan",
+            "minPrice": money("50.0"),
+            "maxPrice": money("1000.0"),
+            "terms": [
+                {
+                    "id": "monthly",
+                    "apr": 15.0,
+                    "frequency": "MONTHLY",
+                    "installmentsCount": { "count": 12 },
+                    "loanType": "INTEREST",
+                },
+                {
+                    "id": "split",
+                    "apr": 0.0,
+                    "frequency": "BI_WEEKLY",
+                    "installmentsCount": { "count": 4 },
+                    "loanType": "SPLIT_PAY",
+                },
+            ],
+        }],
+    })
+}
+
+#[wasm_bindgen_test]
+fn eligible_prices_get_the_interest_free_plan() {
+    let message =
+        json(installments_message(value(money("100.00")), value(shop_pricing())).unwrap());
+
+    assert_eq!(message["eligible"], true);
+    assert_eq!(message["installmentsCount"], 4);
+    assert_eq!(message["pricePerTerm"]["amount"], "25.00");
+    assert_eq!(message["interestFree"], true);
+    assert_eq!(message["frequency"], "BI_WEEKLY");
+    assert_eq!(message["message"], "4 interest-free payments of $25.00");
+}
+
+#[wasm_bindgen_test]
+fn prices_outside_the_plans_are_not_eligible() {
+    let message = json(installments_message(value(money("20.00")), value(shop_pricing())).unwrap());
+
+    assert_eq!(message["eligible"], false);
+    assert_eq!(message["message"], Value::Null);
+    assert_eq!(message["minPrice"]["amount"], "50.0");
+
+    let message = json(installments_message(value(money("100.00")), JsValue::NULL).unwrap());
+    assert_eq!(message["eligible"], false);
+    assert_eq!(message["minPrice"], Value::Null);
+}
+
+#[wasm_bindgen_test]
+async fn variant_messages_use_the_variant_pricing() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "getInstallmentsMessage",
+        data(json!({
+            "shop": { "shopPayInstallmentsPricing": shop_pricing() },
+            "node": {
+                "price": money("80.00"),
+                "shopPayInstallmentsPricing": {
+                    "available": true,
+                    "eligible": true,
+                    "fullPrice": money("80.00"),
+                    "installmentsCount": { "count": 4 },
+                    "pricePerTerm": money("20.00"),
+                },
+            },
+        })),
+    );
+
+    let message = json(
+        api.get_installments_message("gid://shopify/ProductVariant/1".to_string(), None)
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(message["eligible"], true);
+    assert_eq!(message["pricePerTerm"]["amount"], "20.00");
+    assert_eq!(message["message"], "4 interest-free payments of $20.00");
+    assert_eq!(
+        fetch.recorded()[0].variables["id"],
+        "gid://shopify/ProductVariant/1"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn quantities_price_the_total() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "getInstallmentsMessage",
+        data(json!({
+            "shop": { "shopPayInstallmentsPricing": shop_pricing() },
+            "node": { "price": money("30.00"), "shopPayInstallmentsPricing": null },
+        })),
+    );
+
+    let message = json(
+        api.get_installments_message("gid://shopify/ProductVariant/1".to_string(), Some(3))
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(message["price"]["amount"], "90.00");
+    assert_eq!(message["pricePerTerm"]["amount"], "22.50");
+}
+
+#[wasm_bindgen_test]
+async fn missing_variants_are_null() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "getInstallmentsMessage",
+        data(json!({ "shop": { "shopPayInstallmentsPricing": null }, "node": null })),
+    );
+
+    let message = api
+        .get_installments_message("gid://shopify/ProductVariant/9".to_string(), None)
+        .await
+        .unwrap();
+
+    assert!(message.is_null());
+}
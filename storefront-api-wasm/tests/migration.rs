@@ -0,0 +1,196 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::migration::{selected_fields, suggested_replacement, Schema};
+use storefront_api_wasm::testing::data;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn field(name: &str, type_name: &str, deprecation: Option<&str>) -> Value {
+    json!({
+        "name": name,
+        "isDeprecated": deprecation.is_some(),
+        "deprecationReason": deprecation,
+        "type": { "name": null, "ofType": { "name": type_name } },
+    })
+}
+
+fn schema(product_fields: Vec<Value>) -> Value {
+    json!({ "data": { "__schema": {
+        "queryType": { "name": "QueryRoot" },
+        "mutationType": { "name": "Mutation" },
+        "types": [
+            { "name": "QueryRoot", "fields": [field("product", "Product", None)] },
+            { "name": "Product", "fields": product_fields },
+            { "name": "MoneyV2", "fields": [field("amount", "Decimal", None)] },
+            { "name": "Decimal", "fields": null },
+        ],
+    } } })
+}
+
+fn old_schema() -> Value {
+    schema(vec![
+        field("title", "String", None),
+        field("price", "MoneyV2", Some("Use `priceRange` instead.")),
+        field("onlineStoreUrl", "URL", None),
+    ])
+}
+
+fn new_schema() -> Value {
+    schema(vec![
+        field("title", "String", Some("Use `name` instead.")),
+        field("priceRange", "MoneyV2", None),
+    ])
+}
+
+const QUERY: &str = r#"
+query productPage($handle: String!) @inContext(country: CA) {
+  product(handle: $handle) {
+    heading: title
+    price { amount }
+    ...ProductLinks
+  }
+}
+fragment ProductLinks on Product { onlineStoreUrl }
+"#;
+
+#[wasm_bindgen_test]
+fn fields_are_resolved_through_aliases_and_fragments() {
+    let schema = Schema::from_introspection(&old_schema()).unwrap();
+    let fields: Vec<(String, String, String)> = selected_fields(&schema, QUERY)
+        .unwrap()
+        .into_iter()
+        .map(|field| (field.operation, field.path, field.type_name))
+        .collect();
+    let expected = [
+        ("productPage", "product", "QueryRoot"),
+        ("productPage", "product.title", "Product"),
+        ("productPage", "product.price", "Product"),
+        ("productPage", "product.price.amount", "MoneyV2"),
+        ("fragment ProductLinks", "onlineStoreUrl", "Product"),
+    ]
+    .map(|(operation, path, type_name)| {
+        (
+            operation.to_string(),
+            path.to_string(),
+            type_name.to_string(),
+        )
+    });
+    assert_eq!(fields, expected);
+}
+
+#[wasm_bindgen_test]
+fn replacements_come_from_the_deprecation_reason() {
+    assert_eq!(
+        suggested_replacement("Use `priceRange` instead."),
+        Some("priceRange".to_string())
+    );
+    assert_eq!(suggested_replacement("No longer supported."), None);
+}
+
+#[wasm_bindgen_test]
+async fn the_report_lists_deprecated_and_removed_fields() {
+    let (api, fetch) = client();
+    api.load_schema(
+        "2024-01".to_string(),
+        serde_wasm_bindgen::to_value(&old_schema()).unwrap(),
+    )
+    .unwrap();
+    api.load_schema(
+        "2024-07".to_string(),
+        serde_wasm_bindgen::to_value(&new_schema()).unwrap(),
+    )
+    .unwrap();
+    let queries = serde_wasm_bindgen::to_value(&json!([QUERY])).unwrap();
+
+    let report = json(
+        api.migration_report("2024-01".to_string(), "2024-07".to_string(), Some(queries))
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(report["queries"], 1);
+    assert_eq!(report["deprecated"], 1);
+    assert_eq!(report["removed"], 2);
+    assert_eq!(report["issues"][0]["path"], "product.title");
+    assert_eq!(report["issues"][0]["change"], "deprecated");
+    assert_eq!(report["issues"][0]["replacement"], "name");
+    assert_eq!(report["issues"][1]["path"], "product.price");
+    assert_eq!(report["issues"][1]["change"], "removed");
+    assert_eq!(report["issues"][1]["replacement"], "priceRange");
+    assert_eq!(report["issues"][2]["operation"], "fragment ProductLinks");
+    assert_eq!(report["issues"][2]["replacement"], Value::Null);
+    assert!(fetch.recorded().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn without_queries_the_report_checks_the_operations_sent() {
+    let (api, fetch) = client();
+    fetch.respond("productPage", data(json!({ "product": null })));
+    api.record_queries(true);
+    api.query(
+        QUERY.to_string(),
+        Some(serde_wasm_bindgen::to_value(&json!({ "handle": "tee" })).unwrap()),
+    )
+    .await
+    .unwrap();
+    fetch.respond("migrationSchema", old_schema());
+    api.load_schema(
+        "2024-07".to_string(),
+        serde_wasm_bindgen::to_value(&new_schema()).unwrap(),
+    )
+    .unwrap();
+
+    let report = json(
+        api.migration_report("2024-01".to_string(), "2024-07".to_string(), None)
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(report["queries"], 1);
+    assert_eq!(report["issues"].as_array().unwrap().len(), 3);
+    let schema_request = &fetch.recorded()[1];
+    assert!(schema_request.url.contains("/api/2024-01/"));
+}
+
+#[wasm_bindgen_test]
+async fn operations_are_only_recorded_on_request() {
+    let (api, fetch) = client();
+    fetch.respond("productPage", data(json!({ "product": null })));
+    let variables =
+        |handle: &str| Some(serde_wasm_bindgen::to_value(&json!({ "handle": handle })).unwrap());
+    api.query(QUERY.to_string(), variables("tee"))
+        .await
+        .unwrap();
+    api.load_schema(
+        "2024-01".to_string(),
+        serde_wasm_bindgen::to_value(&old_schema()).unwrap(),
+    )
+    .unwrap();
+    api.load_schema(
+        "2024-07".to_string(),
+        serde_wasm_bindgen::to_value(&new_schema()).unwrap(),
+    )
+    .unwrap();
+    let report = || api.migration_report("2024-01".to_string(), "2024-07".to_string(), None);
+
+    assert!(report().await.is_err());
+
+    api.record_queries(true);
+    assert_eq!(json(report().await.unwrap())["queries"], 0);
+
+    fetch.respond("productPage", data(json!({ "product": null })));
+    api.query(QUERY.to_string(), variables("hat"))
+        .await
+        .unwrap();
+    assert_eq!(json(report().await.unwrap())["queries"], 1);
+
+    api.record_queries(false);
+    api.record_queries(true);
+    assert_eq!(json(report().await.unwrap())["queries"], 0);
+}
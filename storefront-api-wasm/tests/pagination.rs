@@ -0,0 +1,115 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde_json::json;
+use storefront_api_wasm::testing::{connection, data, product};
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn collection_page(handles: &[&str], has_next_page: bool) -> serde_json::Value {
+    data(json!({
+        "collection": {
+            "id": "gid://shopify/Collection/1",
+            "handle": "shirts",
+            "title": "Shirts",
+            "products": connection(
+                handles.iter().map(|handle| product(handle, "10.0")).collect(),
+                has_next_page,
+            ),
+        },
+    }))
+}
+
+#[wasm_bindgen_test]
+async fn all_collection_products_follow_cursors() {
+    let (api, fetch) = client();
+    fetch.respond("getCollection", collection_page(&["a", "b"], true));
+    fetch.respond("getCollection", collection_page(&["c"], false));
+
+    let options = serde_wasm_bindgen::to_value(&json!({ "pageSize": 2 })).unwrap();
+    let result = json(
+        api.get_all_collection_products("shirts".to_string(), Some(options))
+            .await
+            .unwrap(),
+    );
+
+    let handles: Vec<&str> = result["products"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|product| product["handle"].as_str().unwrap())
+        .collect();
+    assert_eq!(handles, ["a", "b", "c"]);
+    assert_eq!(result["truncated"], json!(false));
+
+    let requests = fetch.recorded();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].variables["after"], json!(null));
+    assert_eq!(requests[1].variables["after"], json!("cursor-1"));
+}
+
+#[wasm_bindgen_test]
+async fn max_items_truncates_the_walk() {
+    let (api, fetch) = client();
+    fetch.respond("getCollection", collection_page(&["a", "b"], true));
+
+    let options = serde_wasm_bindgen::to_value(&json!({ "pageSize": 2, "maxItems": 2 })).unwrap();
+    let result = json(
+        api.get_all_collection_products("shirts".to_string(), Some(options))
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(result["products"].as_array().unwrap().len(), 2);
+    assert_eq!(result["truncated"], json!(true));
+    assert_eq!(fetch.pending(), 0);
+}
+
+#[wasm_bindgen_test]
+async fn missing_collections_reject() {
+    let (api, fetch) = client();
+    fetch.respond("getCollection", data(json!({ "collection": null })));
+
+    assert!(api
+        .get_all_collection_products("missing".to_string(), None)
+        .await
+        .is_err());
+}
+
+#[wasm_bindgen_test]
+async fn list_helpers_take_one_options_object() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "listCollections",
+        data(json!({ "collections": connection(vec![], false) })),
+    );
+    fetch.respond(
+        "searchProducts",
+        data(json!({ "products": connection(vec![], false) })),
+    );
+
+    let options = serde_wasm_bindgen::to_value(&json!({
+        "first": 5,
+        "after": "cursor-1",
+        "sortKey": "updated_at",
+        "reverse": true,
+    }))
+    .unwrap();
+    api.list_collections(Some(options.clone())).await.unwrap();
+    api.search_products("shirt".into(), Some(options))
+        .await
+        .unwrap();
+
+    let requests = fetch.recorded();
+    assert_eq!(requests.len(), 2);
+    for request in requests {
+        assert_eq!(request.variables["first"], json!(5));
+        assert_eq!(request.variables["after"], json!("cursor-1"));
+        assert_eq!(request.variables["sortKey"], json!("UPDATED_AT"));
+        assert_eq!(request.variables["reverse"], json!(true));
+    }
+}
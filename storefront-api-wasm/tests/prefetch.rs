@@ -0,0 +1,71 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde_json::json;
+use storefront_api_wasm::testing::{data, product};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Lets the prefetch start, as it has by the time JavaScript's next call
+/// runs.
+async fn next_tick() {
+    JsFuture::from(js_sys::Promise::resolve(&JsValue::NULL))
+        .await
+        .unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn calls_during_a_prefetch_share_its_request() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "getProduct",
+        data(json!({ "product": product("tee", "25.0") })),
+    );
+
+    api.prefetch_product("tee".to_string(), None);
+    next_tick().await;
+    let result = json(api.get_product("tee".to_string(), None).await.unwrap());
+
+    assert_eq!(result["product"]["handle"], "tee");
+    assert_eq!(fetch.recorded().len(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn prefetched_responses_answer_later_calls() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "getProduct",
+        data(json!({ "product": product("tee", "25.0") })),
+    );
+    api.prefetch_product("tee".to_string(), None);
+    next_tick().await;
+    api.get_product("tee".to_string(), None).await.unwrap();
+
+    let again = json(api.get_product("tee".to_string(), None).await.unwrap());
+
+    assert_eq!(again["product"]["handle"], "tee");
+    assert_eq!(fetch.recorded().len(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn failed_prefetches_fall_back_to_the_network() {
+    let (api, fetch) = client();
+    fetch.fail("getProduct", "Failed to fetch");
+    fetch.respond(
+        "getProduct",
+        data(json!({ "product": product("tee", "25.0") })),
+    );
+
+    api.prefetch_product("tee".to_string(), None);
+    next_tick().await;
+    let result = json(api.get_product("tee".to_string(), None).await.unwrap());
+
+    assert_eq!(result["product"]["handle"], "tee");
+    assert_eq!(fetch.recorded().len(), 2);
+}
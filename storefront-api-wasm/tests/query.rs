@@ -0,0 +1,392 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::testing::{data, graphql_errors, MockFetch};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::{client, error_field, json, ACCESS_TOKEN};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn query_returns_data() {
+    let (api, fetch) = client();
+    fetch.respond("shopName", data(json!({ "shop": { "name": "Test Shop" } })));
+
+    let result = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(json(result), json!({ "shop": { "name": "Test Shop" } }));
+    let requests = fetch.recorded();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].url,
+        "https://test-shop.myshopify.com/api/2024-01/graphql.json"
+    );
+    assert_eq!(
+        requests[0].headers["x-shopify-storefront-access-token"],
+        ACCESS_TOKEN
+    );
+}
+
+#[wasm_bindgen_test]
+async fn query_sends_variables() {
+    let (api, fetch) = client();
+    fetch.respond("getProduct", data(json!({ "product": null })));
+
+    let variables = serde_wasm_bindgen::to_value(&json!({ "handle": "tee" })).unwrap();
+    api.query(
+        "query getProduct($handle: String!) { product(handle: $handle) { id } }".to_string(),
+        Some(variables),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(fetch.recorded()[0].variables, json!({ "handle": "tee" }));
+}
+
+#[wasm_bindgen_test]
+async fn graphql_errors_reject_with_their_kind() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "shopName",
+        graphql_errors(&[("Field 'nam' doesn't exist on type 'Shop'", None)]),
+    );
+
+    let error = api
+        .query("query shopName { shop { nam } }".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_field(&error, "kind"), JsValue::from_str("graphql"));
+    assert_eq!(error_field(&error, "retryable"), JsValue::FALSE);
+}
+
+#[wasm_bindgen_test]
+async fn throttling_is_retryable() {
+    let (api, fetch) = client();
+    fetch.respond_status("shopName", 429, json!(null));
+
+    let error = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_field(&error, "kind"), JsValue::from_str("throttled"));
+    assert_eq!(error_field(&error, "retryable"), JsValue::TRUE);
+}
+
+#[wasm_bindgen_test]
+async fn throttled_graphql_errors_are_retryable() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "shopName",
+        graphql_errors(&[("Throttled", Some("THROTTLED"))]),
+    );
+
+    let error = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_field(&error, "kind"), JsValue::from_str("throttled"));
+}
+
+#[wasm_bindgen_test]
+async fn network_failures_reject() {
+    let (api, fetch) = client();
+    fetch.fail("shopName", "Failed to fetch");
+
+    let error = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        error_field(&error, "message"),
+        JsValue::from_str("Failed to fetch")
+    );
+    assert_eq!(
+        error_field(&error, "category"),
+        JsValue::from_str("network")
+    );
+    assert_eq!(
+        error_field(&error, "code"),
+        JsValue::from_str("NETWORK_ERROR")
+    );
+    assert_eq!(error_field(&error, "retryable"), JsValue::TRUE);
+}
+
+#[wasm_bindgen_test]
+async fn error_statuses_are_classified() {
+    let (api, fetch) = client();
+    fetch.respond_status("shopName", 401, Value::Null);
+    fetch.respond_status("shopName", 503, Value::Null);
+
+    let unauthorized = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap_err();
+    let unavailable = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        error_field(&unauthorized, "category"),
+        JsValue::from_str("auth")
+    );
+    assert_eq!(
+        error_field(&unauthorized, "code"),
+        JsValue::from_str("UNAUTHORIZED")
+    );
+    assert_eq!(error_field(&unauthorized, "retryable"), JsValue::FALSE);
+    assert_eq!(
+        error_field(&unavailable, "category"),
+        JsValue::from_str("server")
+    );
+    assert_eq!(error_field(&unavailable, "retryable"), JsValue::TRUE);
+}
+
+#[wasm_bindgen_test]
+async fn retryable_failures_are_retried_by_the_policy() {
+    let (api, fetch) = client();
+    let policy =
+        serde_wasm_bindgen::to_value(&json!({ "maxRetries": 2, "baseDelayMs": 1 })).unwrap();
+    api.set_retry_policy(Some(policy)).unwrap();
+    fetch.respond_status("shopName", 503, Value::Null);
+    fetch.fail("shopName", "Failed to fetch");
+    fetch.respond("shopName", data(json!({ "shop": { "name": "Shop" } })));
+
+    let result = json(
+        api.query("query shopName { shop { name } }".to_string(), None)
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(result["shop"]["name"], "Shop");
+    assert_eq!(fetch.recorded().len(), 3);
+}
+
+#[wasm_bindgen_test]
+async fn failures_that_are_not_retryable_are_thrown_at_once() {
+    let (api, fetch) = client();
+    let policy =
+        serde_wasm_bindgen::to_value(&json!({ "maxRetries": 2, "baseDelayMs": 1 })).unwrap();
+    api.set_retry_policy(Some(policy)).unwrap();
+    fetch.respond_status("shopName", 403, Value::Null);
+
+    let error = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_field(&error, "code"), JsValue::from_str("FORBIDDEN"));
+    assert_eq!(fetch.recorded().len(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn mutations_are_only_retried_when_throttled() {
+    let (api, fetch) = client();
+    let policy =
+        serde_wasm_bindgen::to_value(&json!({ "maxRetries": 2, "baseDelayMs": 1 })).unwrap();
+    api.set_retry_policy(Some(policy)).unwrap();
+    let mutation = "mutation cartCreate { cartCreate { cart { id } } }";
+    fetch.respond_status("cartCreate", 503, Value::Null);
+    fetch.respond_status("cartCreate", 429, Value::Null);
+    fetch.respond(
+        "cartCreate",
+        data(json!({ "cartCreate": { "cart": { "id": "gid://shopify/Cart/1" } } })),
+    );
+
+    let error = api.query(mutation.to_string(), None).await.unwrap_err();
+    assert_eq!(error_field(&error, "retryable"), JsValue::TRUE);
+    assert_eq!(fetch.recorded().len(), 1);
+
+    let result = json(api.query(mutation.to_string(), None).await.unwrap());
+    assert_eq!(result["cartCreate"]["cart"]["id"], "gid://shopify/Cart/1");
+    assert_eq!(fetch.recorded().len(), 3);
+}
+
+#[wasm_bindgen_test]
+async fn requests_without_a_response_fail() {
+    let (api, fetch) = client();
+
+    assert!(api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .is_err());
+    assert_eq!(fetch.recorded().len(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn queries_over_the_cost_limit_are_refused_unsent() {
+    let (api, fetch) = client();
+    let limits =
+        serde_wasm_bindgen::to_value(&json!({ "mode": "refuse", "maxQueryCost": 100 })).unwrap();
+    api.set_cost_limits(Some(limits)).unwrap();
+    let query =
+        "query variants { product(handle: \"tee\") { variants(first: 100) { nodes { id } } } }";
+
+    let estimate = json(api.estimate_cost(query, None).unwrap());
+    assert_eq!(estimate["exceedsQueryLimit"], true);
+    assert_eq!(estimate["connections"][0]["path"], "product.variants");
+
+    let error = api.query(query.to_string(), None).await.unwrap_err();
+    assert_eq!(
+        error_field(&error, "kind"),
+        JsValue::from_str("maxCostExceeded")
+    );
+    assert!(fetch.recorded().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn responses_over_the_size_limit_are_refused() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "shopName",
+        data(json!({ "shop": { "name": "x".repeat(2048) } })),
+    );
+    let limits = serde_wasm_bindgen::to_value(&json!({ "maxBytes": 1024 })).unwrap();
+    api.set_response_limits(Some(limits)).unwrap();
+
+    let error = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert!(error_field(&error, "message")
+        .as_string()
+        .unwrap()
+        .contains("1024 byte limit"));
+    assert_eq!(
+        error_field(&error, "code"),
+        JsValue::from_str("RESPONSE_TOO_LARGE")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn large_responses_parse_in_slices() {
+    let (api, fetch) = client();
+    let names: Vec<String> = (0..500).map(|index| format!("shop-{index}")).collect();
+    fetch.respond("shopName", data(json!({ "shop": { "names": names } })));
+    let limits =
+        serde_wasm_bindgen::to_value(&json!({ "chunkedThreshold": 1, "chunkBytes": 64 })).unwrap();
+    api.set_response_limits(Some(limits)).unwrap();
+
+    let result = json(
+        api.query("query shopName { shop { names } }".to_string(), None)
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(result["shop"]["names"][499], "shop-499");
+}
+
+#[wasm_bindgen_test]
+fn canonical_requests_hash_the_canonical_query() {
+    let request = json(
+        storefront_api_wasm::canonical::canonical_request(
+            "query shopName {\n  shop { name }\n}",
+            Some(serde_wasm_bindgen::to_value(&json!({ "b": 1.0, "a": true })).unwrap()),
+        )
+        .unwrap(),
+    );
+
+    assert_eq!(request["query"], "query shopName{shop{name}}");
+    assert_eq!(request["variables"].to_string(), r#"{"a":true,"b":1}"#);
+    assert_eq!(
+        request["hash"],
+        storefront_api_wasm::canonical::sha256_hex("query shopName{shop{name}}")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn query_all_reports_each_operation_and_summarizes_failures() {
+    let (api, fetch) = client();
+    fetch.respond("shopName", data(json!({ "shop": { "name": "Shop" } })));
+    fetch.fail("menu", "Failed to fetch");
+    fetch.respond("cart", data(json!({ "cart": null })));
+    let operations = serde_wasm_bindgen::to_value(&json!([
+        { "query": "query shopName { shop { name } }" },
+        { "name": "mainMenu", "query": "query menu($handle: String!) { menu(handle: $handle) { id } }", "variables": { "handle": "main" } },
+        { "query": "query cart { cart(id: \"1\") { id } }" },
+    ]))
+    .unwrap();
+    let options = serde_wasm_bindgen::to_value(&json!({ "concurrency": 2 })).unwrap();
+
+    let result = json(api.query_all(operations, Some(options)).await.unwrap());
+
+    assert_eq!(result["ok"], false);
+    assert_eq!(result["results"][0]["name"], "shopName");
+    assert_eq!(result["results"][0]["data"]["shop"]["name"], "Shop");
+    assert_eq!(result["results"][1]["name"], "mainMenu");
+    assert_eq!(result["results"][1]["error"]["category"], "network");
+    assert_eq!(result["results"][2]["ok"], true);
+    assert_eq!(result["errors"]["count"], 1);
+    assert_eq!(result["errors"]["byCategory"]["network"], 1);
+    assert_eq!(result["errors"]["failed"], json!(["mainMenu"]));
+    assert_eq!(result["errors"]["retryable"], true);
+    assert_eq!(fetch.recorded().len(), 3);
+}
+
+#[wasm_bindgen_test]
+async fn requests_go_to_the_fetch_given() {
+    let (api, global) = client();
+    let custom = MockFetch::new();
+    custom.respond("shopName", data(json!({ "shop": { "name": "Edge" } })));
+    let api = api.with_fetch(custom.as_fetch());
+
+    let result = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(json(result)["shop"]["name"], "Edge");
+    assert_eq!(custom.recorded().len(), 1);
+    assert_eq!(
+        custom.recorded()[0].headers["x-shopify-storefront-access-token"],
+        ACCESS_TOKEN
+    );
+    assert!(global.recorded().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn fetches_that_do_not_resolve_to_a_response_fail() {
+    let (api, _) = client();
+    let custom = js_sys::Function::new_no_args("return Promise.resolve({ status: 200 });");
+    let api = api.with_fetch(custom);
+
+    let error = api
+        .query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        error_field(&error, "code"),
+        JsValue::from_str("INVALID_RESPONSE")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn country_and_language_apply_to_copies_of_the_client() {
+    let (api, fetch) = client();
+    let copy = api.clone();
+    api.set_country(Some("de".to_string())).unwrap();
+    api.set_language(Some("fr".to_string())).unwrap();
+    fetch.respond("shopName", data(json!({ "shop": { "name": "Shop" } })));
+
+    copy.query("query shopName { shop { name } }".to_string(), None)
+        .await
+        .unwrap();
+
+    assert!(fetch.recorded()[0]
+        .query
+        .contains("@inContext(country: DE, language: FR)"));
+    assert_eq!(json(copy.context().unwrap())["country"], "DE");
+}
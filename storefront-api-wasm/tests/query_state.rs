@@ -0,0 +1,112 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::query_state::QueryHandle;
+use storefront_api_wasm::testing::data;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const QUERY: &str = "query shopName { shop { name } }";
+
+fn shop(name: &str) -> Value {
+    data(json!({ "shop": { "name": name } }))
+}
+
+fn lazy() -> Option<JsValue> {
+    Some(serde_wasm_bindgen::to_value(&json!({ "lazy": true })).unwrap())
+}
+
+/// Subscribes to `handle`, collecting the statuses it reports.
+fn statuses(handle: &QueryHandle) -> Rc<RefCell<Vec<String>>> {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&seen);
+    let listener = Closure::<dyn Fn(JsValue)>::new(move |state: JsValue| {
+        let status = json(state)["status"].as_str().unwrap().to_string();
+        sink.borrow_mut().push(status);
+    });
+    handle.subscribe(
+        listener
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone(),
+    );
+    listener.forget();
+    seen
+}
+
+async fn refetch(handle: &QueryHandle) -> Value {
+    json(JsFuture::from(handle.refetch()).await.unwrap())
+}
+
+#[wasm_bindgen_test]
+async fn a_handle_moves_from_loading_to_data_and_refreshes() {
+    let (api, fetch) = client();
+    fetch.respond("shopName", shop("First"));
+    fetch.respond("shopName", shop("Second"));
+    let handle = api.query_handle(QUERY.to_string(), None, lazy()).unwrap();
+    let seen = statuses(&handle);
+
+    let state = refetch(&handle).await;
+    assert_eq!(state["status"], "data");
+    assert_eq!(state["data"]["shop"]["name"], "First");
+    let state = refetch(&handle).await;
+    assert_eq!(state["data"]["shop"]["name"], "Second");
+
+    assert_eq!(
+        *seen.borrow(),
+        ["idle", "loading", "data", "refreshing", "data"]
+    );
+    assert!(state["updatedAt"].is_number());
+}
+
+#[wasm_bindgen_test]
+async fn state_is_the_same_object_until_it_changes() {
+    let (api, fetch) = client();
+    fetch.respond("shopName", shop("Shop"));
+    let handle = api.query_handle(QUERY.to_string(), None, lazy()).unwrap();
+    let before = handle.state();
+
+    assert!(js_sys::Object::is(&before, &handle.state()));
+    refetch(&handle).await;
+    assert!(!js_sys::Object::is(&before, &handle.state()));
+}
+
+#[wasm_bindgen_test]
+async fn a_failed_refetch_keeps_the_data() {
+    let (api, fetch) = client();
+    fetch.respond("shopName", shop("Shop"));
+    fetch.fail("shopName", "Failed to fetch");
+    let handle = api.query_handle(QUERY.to_string(), None, lazy()).unwrap();
+    refetch(&handle).await;
+
+    let state = refetch(&handle).await;
+
+    assert_eq!(state["status"], "error");
+    assert_eq!(state["error"]["category"], "network");
+    assert_eq!(state["data"]["shop"]["name"], "Shop");
+}
+
+#[wasm_bindgen_test]
+async fn cancelling_goes_back_to_the_state_before() {
+    let (api, fetch) = client();
+    fetch.respond("shopName", shop("Shop"));
+    let handle = api.query_handle(QUERY.to_string(), None, lazy()).unwrap();
+
+    let pending = handle.refetch();
+    assert_eq!(json(handle.state())["status"], "loading");
+    handle.cancel();
+
+    let state = json(JsFuture::from(pending).await.unwrap());
+    assert_eq!(state["status"], "idle");
+    assert_eq!(state["data"], Value::Null);
+}
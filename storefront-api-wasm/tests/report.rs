@@ -0,0 +1,132 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::*;
+
+use common::{client, json, ACCESS_TOKEN};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const QUERY: &str = "query customer($customerAccessToken: String!, $email: String) { customer(customerAccessToken: $customerAccessToken) { id } }";
+
+type Reports = Rc<RefCell<Vec<Value>>>;
+
+/// Installs an `on_error` callback and returns the reports it receives.
+fn reports(
+    api: &storefront_api_wasm::StorefrontApi,
+    options: Option<Value>,
+) -> (Reports, Closure<dyn Fn(JsValue)>) {
+    let reports = Rc::new(RefCell::new(Vec::new()));
+    let seen = Rc::clone(&reports);
+    let callback =
+        Closure::<dyn Fn(JsValue)>::new(move |report| seen.borrow_mut().push(json(report)));
+    let options = options.map(|options| serde_wasm_bindgen::to_value(&options).unwrap());
+    api.on_error(
+        Some(
+            callback
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .clone(),
+        ),
+        options,
+    )
+    .unwrap();
+    (reports, callback)
+}
+
+fn variables() -> JsValue {
+    serde_wasm_bindgen::to_value(&json!({
+        "customerAccessToken": "customer-secret",
+        "email": "ada@example.com",
+        "note": format!("token {ACCESS_TOKEN}"),
+    }))
+    .unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn failed_requests_are_reported_redacted() {
+    let (api, fetch) = client();
+    let (reports, _callback) = reports(&api, None);
+    fetch.respond_status("customer", 503, Value::Null);
+
+    api.query(QUERY.to_string(), Some(variables()))
+        .await
+        .unwrap_err();
+
+    let reports = reports.borrow();
+    assert_eq!(reports.len(), 1);
+    let report = &reports[0];
+    assert_eq!(report["operation"], "customer");
+    assert_eq!(report["category"], "server");
+    assert_eq!(report["code"], "SERVER_ERROR");
+    assert_eq!(report["attempts"], 1);
+    assert_eq!(report["variables"]["customerAccessToken"], "[REDACTED]");
+    assert_eq!(report["variables"]["email"], "[REDACTED]");
+    assert_eq!(report["variables"]["note"], "token [REDACTED]");
+    assert!(!report.to_string().contains(ACCESS_TOKEN));
+}
+
+#[wasm_bindgen_test]
+async fn variables_can_be_left_out_of_reports() {
+    let (api, fetch) = client();
+    let (reports, _callback) = reports(&api, Some(json!({ "includeVariables": false })));
+    fetch.fail("customer", "Failed to fetch");
+
+    api.query(QUERY.to_string(), Some(variables()))
+        .await
+        .unwrap_err();
+
+    assert_eq!(reports.borrow()[0]["variables"], Value::Null);
+    assert_eq!(reports.borrow()[0]["category"], "network");
+}
+
+#[wasm_bindgen_test]
+async fn successful_requests_are_not_reported() {
+    let (api, fetch) = client();
+    let (reports, _callback) = reports(&api, None);
+    fetch.respond(
+        "customer",
+        storefront_api_wasm::testing::data(json!({ "customer": null })),
+    );
+
+    api.query(QUERY.to_string(), Some(variables()))
+        .await
+        .unwrap();
+
+    assert!(reports.borrow().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn cassettes_keep_no_tokens_or_emails() {
+    let (api, fetch) = client();
+    api.record_responses();
+    fetch.respond(
+        "customer",
+        storefront_api_wasm::testing::data(json!({
+            "customer": { "email": "ada@example.com", "note": "Contact ada@example.com." },
+        })),
+    );
+
+    api.query(QUERY.to_string(), Some(variables()))
+        .await
+        .unwrap();
+
+    let cassette = json(api.cassette().unwrap());
+    let interaction = &cassette["interactions"][0];
+    assert_eq!(
+        interaction["variables"]["customerAccessToken"],
+        "[REDACTED]"
+    );
+    assert_eq!(interaction["variables"]["note"], "token [REDACTED]");
+    assert_eq!(
+        interaction["response"]["data"]["customer"]["note"],
+        "Contact [REDACTED]."
+    );
+    assert!(!cassette.to_string().contains("ada@example.com"));
+}
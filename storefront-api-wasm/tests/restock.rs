@@ -0,0 +1,153 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::testing::{data, graphql_errors};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const TEE: &str = "gid://shopify/ProductVariant/1";
+const HAT: &str = "gid://shopify/ProductVariant/2";
+
+fn stock(variants: &[(&str, bool)]) -> Value {
+    let nodes: Vec<Value> = variants
+        .iter()
+        .map(|(id, available)| {
+            json!({
+                "id": id,
+                "availableForSale": available,
+                "quantityAvailable": if *available { 3 } else { 0 },
+            })
+        })
+        .collect();
+    data(json!({ "nodes": nodes }))
+}
+
+async fn wait(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let set_timeout: js_sys::Function =
+            js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+                .unwrap()
+                .unchecked_into();
+        set_timeout
+            .call2(&JsValue::NULL, &resolve, &JsValue::from(ms))
+            .unwrap();
+    });
+    JsFuture::from(promise).await.unwrap();
+}
+
+/// What a callback was called with.
+type Updates = Rc<RefCell<Vec<Value>>>;
+
+fn recorder() -> (Updates, Closure<dyn Fn(JsValue)>) {
+    let updates = Rc::new(RefCell::new(Vec::new()));
+    let seen = Rc::clone(&updates);
+    let callback =
+        Closure::<dyn Fn(JsValue)>::new(move |update| seen.borrow_mut().push(json(update)));
+    (updates, callback)
+}
+
+fn function(callback: &Closure<dyn Fn(JsValue)>) -> js_sys::Function {
+    callback
+        .as_ref()
+        .unchecked_ref::<js_sys::Function>()
+        .clone()
+}
+
+#[wasm_bindgen_test]
+async fn watched_variants_are_polled_together_and_reported_on_restock() {
+    let (api, fetch) = client();
+    fetch.respond("variantAvailability", stock(&[(TEE, false), (HAT, false)]));
+    fetch.respond("variantAvailability", stock(&[(TEE, true), (HAT, false)]));
+    let (tee_updates, tee_callback) = recorder();
+    let (hat_updates, hat_callback) = recorder();
+
+    let tee = api
+        .watch_variant_availability(TEE.to_string(), 100, function(&tee_callback))
+        .unwrap();
+    let hat = api
+        .watch_variant_availability(HAT.to_string(), 100, function(&hat_callback))
+        .unwrap();
+    wait(150).await;
+    tee.stop();
+    hat.stop();
+
+    let requests = fetch.recorded();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].variables["ids"], json!([TEE, HAT]));
+    assert_eq!(
+        *tee_updates.borrow(),
+        [json!({ "variantId": TEE, "availableForSale": true, "quantityAvailable": 3 })]
+    );
+    assert!(hat_updates.borrow().is_empty());
+    assert!(!tee.is_active());
+}
+
+#[wasm_bindgen_test]
+async fn variants_available_at_the_start_are_not_reported() {
+    let (api, fetch) = client();
+    fetch.respond("variantAvailability", stock(&[(TEE, true)]));
+    fetch.respond("variantAvailability", stock(&[(TEE, true)]));
+    let (updates, callback) = recorder();
+
+    let watcher = api
+        .watch_variant_availability(TEE.to_string(), 100, function(&callback))
+        .unwrap();
+    wait(150).await;
+    watcher.stop();
+    wait(150).await;
+
+    assert_eq!(fetch.recorded().len(), 2);
+    assert!(updates.borrow().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn polls_go_on_without_inventory_access() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "variantAvailability",
+        graphql_errors(&[(
+            "Access denied for quantityAvailable field",
+            Some("ACCESS_DENIED"),
+        )]),
+    );
+    fetch.respond(
+        "variantAvailability",
+        data(json!({ "nodes": [{ "id": TEE, "availableForSale": false }] })),
+    );
+    fetch.respond(
+        "variantAvailability",
+        data(json!({ "nodes": [{ "id": TEE, "availableForSale": true }] })),
+    );
+    let (updates, callback) = recorder();
+
+    let watcher = api
+        .watch_variant_availability(TEE.to_string(), 100, function(&callback))
+        .unwrap();
+    wait(150).await;
+    watcher.stop();
+
+    let requests = fetch.recorded();
+    assert!(requests[0].query.contains("quantityAvailable"));
+    assert!(!requests[2].query.contains("quantityAvailable"));
+    assert_eq!(updates.borrow()[0]["quantityAvailable"], Value::Null);
+}
+
+#[wasm_bindgen_test]
+fn only_variant_ids_can_be_watched() {
+    let (api, _fetch) = client();
+    let callback = Closure::<dyn Fn(JsValue)>::new(|_| {});
+
+    assert!(api
+        .watch_variant_availability("tee".to_string(), 100, function(&callback))
+        .is_err());
+}
@@ -0,0 +1,166 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use storefront_api_wasm::analytics::{AnalyticsEvent, AnalyticsEventName, AnalyticsOptions};
+use storefront_api_wasm::cost::CostLimits;
+use storefront_api_wasm::error::StorefrontError;
+use storefront_api_wasm::fanout::QueryAllResult;
+use storefront_api_wasm::installments::InstallmentsMessage;
+use storefront_api_wasm::migration::{migration_report, Schema};
+use storefront_api_wasm::pricing::SaleInfo;
+use storefront_api_wasm::query_state::{QueryState, QueryStatus};
+use storefront_api_wasm::report::ErrorReport;
+use storefront_api_wasm::retry::RetryPolicy;
+use storefront_api_wasm::testing::{cart, product};
+use storefront_api_wasm::types::{Cart, Money, Product};
+use storefront_api_wasm::typescript::TYPESCRIPT;
+use storefront_api_wasm::urls::UrlSettings;
+use storefront_api_wasm::variant::VariantMatrix;
+use storefront_api_wasm::vcr::Cassette;
+use storefront_api_wasm::webhook::WebhookPayload;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::client;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// The property names of `export interface name` in the declarations.
+fn declared(name: &str) -> Vec<String> {
+    let start = TYPESCRIPT
+        .find(&format!("export interface {name} {{"))
+        .or_else(|| TYPESCRIPT.find(&format!("export interface {name}<")))
+        .unwrap_or_else(|| panic!("no interface {name}"));
+    TYPESCRIPT[start..]
+        .lines()
+        .skip(1)
+        .take_while(|line| *line != "}")
+        .filter_map(|line| {
+            let property = line.strip_prefix("  ")?;
+            let end = property.find([':', '?'])?;
+            Some(property[..end].to_string())
+        })
+        .collect()
+}
+
+/// Fails for a field of `value` that `interface` doesn't declare.
+fn assert_declared(value: &impl Serialize, interface: &str) {
+    let value = serde_json::to_value(value).unwrap();
+    let properties = declared(interface);
+    for key in value.as_object().expect("an object").keys() {
+        assert!(
+            properties.contains(key),
+            "{interface} doesn't declare {key}"
+        );
+    }
+}
+
+#[wasm_bindgen_test]
+fn catalog_types_are_declared() {
+    let product: Product = serde_json::from_value(product("tee", "10.0")).unwrap();
+    assert_declared(&product, "Product");
+    assert_declared(&product.price_range, "ProductPriceRange");
+    assert_declared(&product.price_range.min_variant_price, "Money");
+    let variant = &product.variants.as_ref().unwrap().edges[0];
+    assert_declared(variant, "Edge");
+    assert_declared(&variant.node, "ProductVariant");
+    assert_declared(&product.images, "Connection");
+    assert_declared(product.images.page_info.as_ref().unwrap(), "PageInfo");
+
+    let cart: Cart =
+        serde_json::from_value(cart("gid://shopify/Cart/1", &[("line", "tee", 2)])).unwrap();
+    assert_declared(&cart, "Cart");
+    assert_declared(&cart.lines.edges[0].node, "CartLine");
+}
+
+#[wasm_bindgen_test]
+fn helper_types_are_declared() {
+    let product: Product = serde_json::from_value(product("tee", "10.0")).unwrap();
+    assert_declared(&SaleInfo::for_product(&product), "SaleInfo");
+    let matrix = VariantMatrix::new(&product, &HashMap::new());
+    assert_declared(&matrix, "VariantMatrix");
+    assert_declared(&matrix.variants[0], "VariantCell");
+    assert_declared(&CostLimits::default(), "CostLimits");
+    assert_declared(&Cassette::default(), "Cassette");
+}
+
+#[wasm_bindgen_test]
+fn client_types_are_declared() {
+    assert_declared(
+        &StorefrontError::network("Failed to fetch".to_string()),
+        "StorefrontError",
+    );
+    assert_declared(&RetryPolicy::default(), "RetryPolicy");
+    let error: JsValue = StorefrontError::network("Failed to fetch".to_string()).into();
+    assert_declared(
+        &ErrorReport::new("getCart", Value::Null, 12.0, 1, &error),
+        "ErrorReport",
+    );
+
+    let result = QueryAllResult::new(Vec::new());
+    assert_declared(&result, "QueryAllResult");
+    assert_declared(&result.errors, "ErrorSummary");
+
+    let (api, _fetch) = client();
+    let stats = api.memory_usage();
+    assert_declared(&stats, "MemoryStats");
+    assert_declared(&stats.response_cache, "CacheUsage");
+
+    let state = QueryState {
+        status: QueryStatus::Data,
+        data: Some(json!({})),
+        error: None,
+        updated_at: Some(0.0),
+    };
+    assert_declared(&state, "QueryState");
+
+    let schema = Schema::from_introspection(&json!({ "types": [] })).unwrap();
+    let report = migration_report("2024-01", &schema, "2024-07", &schema, &[]);
+    assert_declared(&report, "MigrationReport");
+
+    let event = AnalyticsEvent {
+        name: AnalyticsEventName::PageViewed,
+        event_id: "id".to_string(),
+        timestamp: 0.0,
+        url: None,
+        referrer: None,
+        title: None,
+        unique_token: None,
+        visit_token: None,
+        payload: json!({}),
+    };
+    assert_declared(&event, "AnalyticsEvent");
+    assert_declared(&AnalyticsOptions::default(), "AnalyticsOptions");
+    assert_declared(&UrlSettings::default(), "UrlSettings");
+
+    let price = Money {
+        amount: "10.00".to_string(),
+        currency_code: "USD".to_string(),
+    };
+    assert_declared(
+        &InstallmentsMessage::new(price, None),
+        "InstallmentsMessage",
+    );
+}
+
+#[wasm_bindgen_test]
+fn webhook_types_are_declared() {
+    let order = json!({
+        "id": 1001,
+        "name": "#1001",
+        "currency": "USD",
+        "total_price": "20.00",
+        "line_items": [{ "id": 1, "title": "Tee", "quantity": 2, "price": "10.00" }],
+    });
+    let WebhookPayload::Order(order) = WebhookPayload::parse("orders/create", order).unwrap()
+    else {
+        panic!("not an order");
+    };
+    assert_declared(&order, "WebhookOrder");
+    assert_declared(&order.line_items[0], "WebhookLineItem");
+}
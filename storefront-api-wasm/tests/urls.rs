@@ -0,0 +1,176 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde_json::json;
+use storefront_api_wasm::testing::data;
+use storefront_api_wasm::urls::build_cart_permalink;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn value(value: serde_json::Value) -> JsValue {
+    serde_wasm_bindgen::to_value(&value).unwrap()
+}
+
+fn url(
+    api: &storefront_api_wasm::StorefrontApi,
+    kind: &str,
+    handle: Option<&str>,
+    options: Option<serde_json::Value>,
+) -> String {
+    api.storefront_url(
+        kind.to_string(),
+        handle.map(String::from),
+        options.map(value),
+    )
+    .unwrap()
+}
+
+#[wasm_bindgen_test]
+fn paths_without_settings_are_unprefixed() {
+    let (api, _fetch) = client();
+    let api = api
+        .with_context(Some("CA".to_string()), Some("FR".to_string()))
+        .unwrap();
+
+    assert_eq!(url(&api, "home", None, None), "/");
+    assert_eq!(url(&api, "cart", None, None), "/cart");
+    assert_eq!(url(&api, "product", Some("tee"), None), "/products/tee");
+    assert_eq!(
+        url(&api, "article", Some("news/launch"), None),
+        "/blogs/news/launch"
+    );
+    assert_eq!(
+        url(&api, "search", Some("red tee"), None),
+        "/search?q=red%20tee"
+    );
+    assert!(api
+        .storefront_url("collection".to_string(), None, None)
+        .is_err());
+    assert!(api
+        .storefront_url("checkout".to_string(), None, None)
+        .is_err());
+}
+
+#[wasm_bindgen_test]
+fn urls_follow_the_market_and_locale() {
+    let (api, _fetch) = client();
+    api.set_url_settings(Some(value(json!({
+        "baseUrl": "https://shop.example.com/",
+        "primaryCountry": "US",
+        "primaryLanguage": "en",
+    }))))
+    .unwrap();
+
+    assert_eq!(
+        url(&api, "collection", Some("shirts"), None),
+        "https://shop.example.com/collections/shirts"
+    );
+    let french = api.with_context(None, Some("FR".to_string())).unwrap();
+    assert_eq!(
+        url(&french, "home", None, None),
+        "https://shop.example.com/fr"
+    );
+    let canada = api
+        .with_context(Some("CA".to_string()), Some("FR".to_string()))
+        .unwrap();
+    assert_eq!(
+        url(
+            &canada,
+            "product",
+            Some("tee"),
+            Some(json!({
+                "variantId": "gid://shopify/ProductVariant/123",
+                "collection": "shirts",
+            }))
+        ),
+        "https://shop.example.com/fr-ca/collections/shirts/products/tee?variant=123"
+    );
+}
+
+#[wasm_bindgen_test]
+fn a_root_url_overrides_the_prefix() {
+    let (api, _fetch) = client();
+    api.set_url_settings(Some(value(json!({ "rootUrl": "/en-ca" }))))
+        .unwrap();
+
+    assert_eq!(url(&api, "cart", None, None), "/en-ca/cart");
+    assert!(api
+        .set_url_settings(Some(value(json!({ "rootUrl": "en-ca" }))))
+        .is_err());
+}
+
+#[wasm_bindgen_test]
+async fn settings_load_from_the_shop() {
+    let (api, fetch) = client();
+    fetch.respond(
+        "getUrlSettings",
+        data(json!({
+            "localization": {
+                "country": { "isoCode": "CA" },
+                "language": { "isoCode": "EN" },
+            },
+            "shop": { "primaryDomain": { "url": "https://shop.example.ca" } },
+        })),
+    );
+    let api = api
+        .with_context(Some("CA".to_string()), Some("FR".to_string()))
+        .unwrap();
+
+    let settings = json(api.load_url_settings().await.unwrap());
+
+    assert_eq!(settings["primaryCountry"], "CA");
+    assert_eq!(settings["baseUrl"], "https://shop.example.ca");
+    assert!(!fetch.recorded()[0].query.contains("@inContext"));
+    assert_eq!(
+        url(&api, "page", Some("about"), None),
+        "https://shop.example.ca/fr/pages/about"
+    );
+}
+
+#[wasm_bindgen_test]
+fn cart_permalinks_list_variants_and_quantities() {
+    let items = value(json!([
+        { "variant_id": "gid://shopify/ProductVariant/123", "quantity": 2 },
+        { "variantId": "456", "quantity": 1 },
+    ]));
+
+    let url = build_cart_permalink(items, None).unwrap();
+
+    assert_eq!(url, "/cart/123:2,456:1");
+}
+
+#[wasm_bindgen_test]
+fn cart_permalinks_carry_discounts_notes_and_attribution() {
+    let items = value(json!([{ "variant_id": "123", "quantity": 1 }]));
+    let options = value(json!({
+        "baseUrl": "https://shop.example.com/",
+        "discount": "SPRING 10",
+        "note": "Gift wrap",
+        "attributes": { "source": "email" },
+        "ref": "spring-campaign",
+    }));
+
+    let url = build_cart_permalink(items, Some(options)).unwrap();
+
+    assert_eq!(
+        url,
+        "https://shop.example.com/cart/123:1?discount=SPRING%2010&note=Gift%20wrap\
+         &ref=spring-campaign&attributes[source]=email"
+    );
+}
+
+#[wasm_bindgen_test]
+fn cart_permalinks_reject_bad_items() {
+    assert!(build_cart_permalink(value(json!([])), None).is_err());
+    assert!(
+        build_cart_permalink(value(json!([{ "variant_id": "tee", "quantity": 1 }])), None).is_err()
+    );
+    assert!(
+        build_cart_permalink(value(json!([{ "variant_id": "123", "quantity": 0 }])), None).is_err()
+    );
+}
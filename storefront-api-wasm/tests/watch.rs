@@ -0,0 +1,111 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use storefront_api_wasm::testing::data;
+use storefront_api_wasm::watch::changed_paths;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+use common::{client, json};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const QUERY: &str = "query stock { product(handle: \"tee\") { availableForSale } }";
+
+fn stock(available: bool) -> Value {
+    data(json!({ "product": { "availableForSale": available } }))
+}
+
+async fn wait(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let set_timeout: js_sys::Function =
+            js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+                .unwrap()
+                .unchecked_into();
+        set_timeout
+            .call2(&JsValue::NULL, &resolve, &JsValue::from(ms))
+            .unwrap();
+    });
+    JsFuture::from(promise).await.unwrap();
+}
+
+#[wasm_bindgen_test]
+fn changed_paths_name_the_values_that_differ() {
+    let old = json!({ "product": { "title": "Tee", "price": "10.0", "tags": ["a", "b"] } });
+    let new = json!({ "product": { "title": "Tee", "price": "12.0", "tags": ["a"], "new": true } });
+
+    assert_eq!(
+        changed_paths(&old, &new),
+        ["product.price", "product.tags.1", "product.new"]
+    );
+    assert!(changed_paths(&old, &old).is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn watchers_report_the_first_result_and_changes_only() {
+    let (api, fetch) = client();
+    fetch.respond("stock", stock(false));
+    fetch.respond("stock", stock(false));
+    fetch.respond("stock", stock(true));
+    let updates = Rc::new(RefCell::new(Vec::new()));
+    let seen = Rc::clone(&updates);
+    let callback =
+        Closure::<dyn Fn(JsValue)>::new(move |update| seen.borrow_mut().push(json(update)));
+
+    let watcher = api
+        .watch(
+            QUERY.to_string(),
+            None,
+            100,
+            callback
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .clone(),
+        )
+        .unwrap();
+    wait(350).await;
+    watcher.stop();
+
+    assert!(fetch.recorded().len() >= 3);
+    let updates = updates.borrow();
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0]["changedPaths"], json!([]));
+    assert_eq!(updates[0]["previous"], Value::Null);
+    assert_eq!(
+        updates[1]["changedPaths"],
+        json!(["product.availableForSale"])
+    );
+    assert_eq!(updates[1]["data"]["product"]["availableForSale"], true);
+    assert!(!watcher.is_active());
+}
+
+#[wasm_bindgen_test]
+async fn stopped_watchers_stop_polling() {
+    let (api, fetch) = client();
+    fetch.respond("stock", stock(false));
+    let callback = Closure::<dyn Fn(JsValue)>::new(|_| {});
+
+    let watcher = api
+        .watch(
+            QUERY.to_string(),
+            None,
+            100,
+            callback
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .clone(),
+        )
+        .unwrap();
+    wait(20).await;
+    watcher.stop();
+    wait(250).await;
+
+    assert_eq!(fetch.recorded().len(), 1);
+    assert_eq!(json(watcher.current().unwrap()), stock(false)["data"]);
+}
@@ -0,0 +1,65 @@
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use serde_json::json;
+use storefront_api_wasm::webhook::{base64_encode, hmac_sha256, parse_webhook, verify_webhook};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+use common::json;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const SECRET: &str = "shpss_test";
+const BODY: &str = r#"{"inventory_item_id":271878346596884015,"location_id":24826418,"available":3,"updated_at":"2026-10-15T10:00:00-04:00"}"#;
+
+fn signature(body: &str) -> String {
+    base64_encode(&hmac_sha256(SECRET.as_bytes(), body.as_bytes()))
+}
+
+#[wasm_bindgen_test]
+fn hmac_matches_the_rfc_4231_vectors() {
+    let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+    let hex: String = mac.iter().map(|byte| format!("{byte:02x}")).collect();
+    assert_eq!(
+        hex,
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
+#[wasm_bindgen_test]
+fn a_webhook_verifies_only_with_its_signature() {
+    let header = Some(signature(BODY));
+    let bytes: JsValue = js_sys::Uint8Array::from(BODY.as_bytes()).into();
+
+    assert!(verify_webhook(header.clone(), BODY.into(), SECRET).unwrap());
+    assert!(verify_webhook(header.clone(), bytes, SECRET).unwrap());
+    assert!(!verify_webhook(header.clone(), BODY.replace('3', "4").into(), SECRET).unwrap());
+    assert!(!verify_webhook(header, BODY.into(), "another secret").unwrap());
+    assert!(!verify_webhook(None, BODY.into(), SECRET).unwrap());
+    assert!(verify_webhook(Some(signature(BODY)), JsValue::from(1), SECRET).is_err());
+}
+
+#[wasm_bindgen_test]
+fn payloads_are_read_by_topic() {
+    let event = json(parse_webhook("inventory_levels/update", BODY.into()).unwrap());
+    assert_eq!(event["topic"], "inventory_levels/update");
+    assert_eq!(event["payload"]["type"], "inventoryLevel");
+    assert_eq!(event["payload"]["locationId"], "24826418");
+    assert_eq!(event["payload"]["inventoryItemId"], "271878346596884015");
+    assert_eq!(event["payload"]["available"], 3);
+
+    let deleted =
+        json(parse_webhook("products/delete", r#"{"id":788032119674292922}"#.into()).unwrap());
+    assert_eq!(
+        deleted["payload"],
+        json!({ "type": "deleted", "id": "788032119674292922" })
+    );
+
+    let other = json(parse_webhook("themes/publish", r#"{"id":1,"role":"main"}"#.into()).unwrap());
+    assert_eq!(other["payload"]["type"], "other");
+    assert_eq!(other["payload"]["body"]["role"], "main");
+
+    assert!(parse_webhook("orders/create", r#"{"id":1}"#.into()).is_err());
+}